@@ -0,0 +1,17 @@
+// Generates the gRPC service of `serve --grpc-listen` from proto/binaryx.proto when the
+// `grpc` feature is enabled; protoc comes from `PROTOC` or a vendored binary
+fn main() {
+    println!("cargo:rerun-if-changed=proto/binaryx.proto");
+    println!("cargo:rerun-if-env-changed=PROTOC");
+
+    #[cfg(feature = "grpc")]
+    {
+        if std::env::var_os("PROTOC").is_none() {
+            let protoc = protoc_bin_vendored::protoc_bin_path()
+                .expect("no vendored protoc for this host, set PROTOC");
+            std::env::set_var("PROTOC", protoc);
+        }
+        tonic_prost_build::compile_protos("proto/binaryx.proto")
+            .expect("failed to compile proto/binaryx.proto");
+    }
+}
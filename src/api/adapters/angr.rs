@@ -0,0 +1,201 @@
+use anyhow::Result;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+
+use super::{address_of, architecture, array, file_type_name, hex, text};
+use super::{InputAdapter, SourceData};
+
+/// JSON written by an angr extraction script: `main_object` (CLE loader),
+/// `functions` (knowledge-base function manager after CFGFast), `call_sites`,
+/// `imports`, `exports` and `memory_data`
+pub struct AngrAdapter;
+
+impl InputAdapter for AngrAdapter {
+    fn name(&self) -> &'static str {
+        "angr"
+    }
+
+    fn detect(&self, data: &SourceData) -> bool {
+        data.json().is_some_and(|value| {
+            text(value, "extractor").is_some_and(|e| e.eq_ignore_ascii_case("angr"))
+                || value.get("main_object").is_some_and(Value::is_object)
+        })
+    }
+
+    fn to_native(&self, data: SourceData) -> Result<Value> {
+        let SourceData::Json(value) = data else {
+            return Err(anyhow::anyhow!("angr input must be JSON"));
+        };
+        let main_object = value
+            .get("main_object")
+            .ok_or_else(|| anyhow::anyhow!("angr export missing main_object"))?;
+        let binary = text(main_object, "binary").unwrap_or("unknown");
+        let binary_name = binary.rsplit(['/', '\\']).next().unwrap_or(binary);
+
+        let libraries: HashMap<&str, &str> = array(&value, "imports")
+            .filter_map(|import| Some((text(import, "name")?, text(import, "library")?)))
+            .collect();
+
+        // PLT stubs and SimProcedure hooks stand in for imported functions;
+        // functions of other loaded objects (auto_load_libs) are skipped
+        let mut functions = Vec::new();
+        let mut imports = Vec::new();
+        let mut stubs = HashSet::new();
+        for function in array(&value, "functions") {
+            let Some(address) = function.get("addr").and_then(address_of) else {
+                continue;
+            };
+            let name = text(function, "name")
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("sub_{:x}", address));
+            let flag = |key: &str| function.get(key).and_then(Value::as_bool) == Some(true);
+
+            if flag("is_plt") || flag("is_simprocedure") {
+                if flag("is_syscall") {
+                    continue;
+                }
+                stubs.insert(name.clone());
+                imports.push(json!({
+                    "name": name,
+                    "address": hex(address),
+                    "library": libraries.get(name.as_str()).copied().unwrap_or("unknown"),
+                }));
+            } else if text(function, "binary_name").is_none_or(|owner| owner == binary_name) {
+                functions.push(json!({
+                    "name": name,
+                    "address": hex(address),
+                    "size": function.get("size").and_then(Value::as_u64),
+                }));
+            }
+        }
+
+        // Imports without a stub (e.g. PE IAT slots) are called through their relocation
+        for import in array(&value, "imports") {
+            let (Some(name), Some(address)) = (
+                text(import, "name"),
+                import.get("rebased_addr").and_then(address_of),
+            ) else {
+                continue;
+            };
+            if !stubs.contains(name) {
+                imports.push(json!({
+                    "name": name,
+                    "address": hex(address),
+                    "library": text(import, "library").unwrap_or("unknown"),
+                }));
+            }
+        }
+
+        let exports: Vec<Value> = array(&value, "exports")
+            .filter_map(|export| {
+                Some(json!({
+                    "name": text(export, "name")?,
+                    "address": hex(export.get("rebased_addr").and_then(address_of)?),
+                }))
+            })
+            .collect();
+
+        let calls: Vec<Value> = array(&value, "call_sites")
+            .filter_map(|site| {
+                let function = site.get("function").and_then(address_of)?;
+                let target = site.get("target").and_then(address_of)?;
+                let offset = site
+                    .get("ins_addr")
+                    .or_else(|| site.get("block"))
+                    .and_then(address_of);
+                Some(json!({
+                    "from_address": hex(function),
+                    "to_address": hex(target),
+                    "offset": offset.map(hex),
+                    "type": "direct",
+                }))
+            })
+            .collect();
+
+        let strings: Vec<Value> = array(&value, "memory_data")
+            .filter(|data| matches!(text(data, "sort"), Some("string" | "unicode")))
+            .filter_map(|data| {
+                let content = text(data, "content")?;
+                let kind = match text(data, "sort") {
+                    Some("unicode") => "utf16",
+                    _ => "ascii",
+                };
+                Some(json!({
+                    "value": content,
+                    "address": data.get("address").and_then(address_of).map(hex),
+                    "length": content.chars().count(),
+                    "type": kind,
+                }))
+            })
+            .collect();
+
+        let mut hashes = serde_json::Map::new();
+        for key in ["sha256", "md5", "sha1"] {
+            if let Some(hash) = text(main_object, key) {
+                hashes.insert(key.to_string(), Value::String(hash.to_lowercase()));
+            }
+        }
+        let bits = main_object.get("bits").and_then(Value::as_u64).unwrap_or(0);
+
+        Ok(json!({
+            "binary_info": {
+                "name": binary_name,
+                "file_path": binary,
+                "file_size": main_object.get("file_size").and_then(Value::as_u64).unwrap_or(0),
+                "file_type": {
+                    "type": file_type_name(text(main_object, "format").unwrap_or("")),
+                    "architecture": architecture(archinfo_processor(text(main_object, "arch").unwrap_or("unknown")), bits),
+                },
+                "hashes": hashes,
+            },
+            "functions": functions,
+            "imports": imports,
+            "exports": exports,
+            "strings": strings,
+            "calls": calls,
+        }))
+    }
+}
+
+/// Processor family of an archinfo name (`AMD64`, `X86`, `ARMEL`, `AARCH64`, ...)
+fn archinfo_processor(arch: &str) -> &str {
+    match arch.to_uppercase().as_str() {
+        "AMD64" | "X86" => "x86",
+        "ARMEL" | "ARMHF" | "ARMCORTEXM" => "arm",
+        "AARCH64" => "aarch64",
+        _ => arch,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_angr_plt_stubs_become_imports() {
+        let export = json!({
+            "extractor": "angr",
+            "main_object": {"binary": "/tmp/a.out", "arch": "AMD64", "bits": 64, "format": "ELF", "sha256": "AA"},
+            "functions": [
+                {"addr": 0x401000, "name": "main", "size": 32, "binary_name": "a.out"},
+                {"addr": 0x400500, "name": "puts", "is_plt": true, "binary_name": "a.out"},
+                {"addr": 0x700000, "name": "strlen", "binary_name": "libc.so.6"}
+            ],
+            "imports": [
+                {"name": "puts", "rebased_addr": 0x404018, "library": "libc.so.6"},
+                {"name": "exit", "rebased_addr": 0x404020, "library": "libc.so.6"}
+            ],
+            "call_sites": [{"function": 0x401000, "block": 0x401004, "ins_addr": 0x40100c, "target": 0x400500}]
+        });
+        let native = AngrAdapter.to_native(SourceData::Json(export)).unwrap();
+
+        assert_eq!(native["binary_info"]["file_type"]["architecture"], "x86_64");
+        assert_eq!(native["functions"].as_array().unwrap().len(), 1);
+        let imports = native["imports"].as_array().unwrap();
+        assert_eq!(imports.len(), 2);
+        assert_eq!(imports[0]["address"], "0x400500");
+        assert_eq!(imports[0]["library"], "libc.so.6");
+        assert_eq!(imports[1]["name"], "exit");
+        assert_eq!(native["calls"][0]["offset"], "0x40100c");
+    }
+}
@@ -0,0 +1,248 @@
+use anyhow::Result;
+use serde_json::{json, Value};
+
+use super::{hex, InputAdapter, SourceData};
+
+// Field numbers from binexport2.proto
+const META_INFORMATION: u64 = 1;
+const CALL_GRAPH: u64 = 8;
+const LIBRARY: u64 = 14;
+
+const META_EXECUTABLE_NAME: u64 = 1;
+const META_EXECUTABLE_ID: u64 = 2;
+const META_ARCHITECTURE_NAME: u64 = 3;
+
+const CALL_GRAPH_VERTEX: u64 = 1;
+const CALL_GRAPH_EDGE: u64 = 2;
+
+const VERTEX_ADDRESS: u64 = 1;
+const VERTEX_TYPE: u64 = 2;
+const VERTEX_MANGLED_NAME: u64 = 3;
+const VERTEX_DEMANGLED_NAME: u64 = 4;
+const VERTEX_LIBRARY_INDEX: u64 = 5;
+
+const EDGE_SOURCE: u64 = 1;
+const EDGE_TARGET: u64 = 2;
+
+const LIBRARY_NAME: u64 = 3;
+
+// CallGraph.Vertex.Type
+const VERTEX_LIBRARY: u64 = 1;
+const VERTEX_IMPORTED: u64 = 2;
+
+/// BinExport2 protobuf as written by the IDA, Ghidra and Binary Ninja plugins.
+/// Only the call graph is used, so calls carry no call-site offsets.
+pub struct BinExportAdapter;
+
+impl InputAdapter for BinExportAdapter {
+    fn name(&self) -> &'static str {
+        "binexport"
+    }
+
+    fn detect(&self, data: &SourceData) -> bool {
+        match data {
+            SourceData::Binary(bytes) => fields(bytes)
+                .is_ok_and(|fields| fields.iter().any(|(number, _)| *number == META_INFORMATION)),
+            SourceData::Json(_) => false,
+        }
+    }
+
+    fn to_native(&self, data: SourceData) -> Result<Value> {
+        let SourceData::Binary(bytes) = data else {
+            return Err(anyhow::anyhow!("BinExport input must be a protobuf file"));
+        };
+
+        let mut meta = Vec::new();
+        let mut call_graph = Vec::new();
+        let mut libraries = Vec::new();
+        for (number, field) in fields(&bytes)? {
+            match (number, field) {
+                (META_INFORMATION, Field::Bytes(b)) => meta = fields(b)?,
+                (CALL_GRAPH, Field::Bytes(b)) => call_graph = fields(b)?,
+                (LIBRARY, Field::Bytes(b)) => {
+                    libraries.push(string(&fields(b)?, LIBRARY_NAME).unwrap_or("unknown"))
+                }
+                _ => {}
+            }
+        }
+
+        let executable_id = string(&meta, META_EXECUTABLE_ID)
+            .ok_or_else(|| anyhow::anyhow!("BinExport meta_information missing executable_id"))?;
+        let name = string(&meta, META_EXECUTABLE_NAME).unwrap_or("unknown");
+
+        let mut vertices = Vec::new();
+        let mut edges = Vec::new();
+        for (number, field) in call_graph {
+            match (number, field) {
+                (CALL_GRAPH_VERTEX, Field::Bytes(b)) => vertices.push(fields(b)?),
+                (CALL_GRAPH_EDGE, Field::Bytes(b)) => edges.push(fields(b)?),
+                _ => {}
+            }
+        }
+
+        let mut functions = Vec::new();
+        let mut imports = Vec::new();
+        let mut addresses = Vec::with_capacity(vertices.len());
+        for vertex in &vertices {
+            let address = varint(vertex, VERTEX_ADDRESS).unwrap_or(0);
+            addresses.push(address);
+            let name = string(vertex, VERTEX_DEMANGLED_NAME)
+                .or_else(|| string(vertex, VERTEX_MANGLED_NAME))
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("sub_{:x}", address));
+            let kind = varint(vertex, VERTEX_TYPE).unwrap_or(0);
+
+            if kind == VERTEX_IMPORTED {
+                let library = varint(vertex, VERTEX_LIBRARY_INDEX)
+                    .and_then(|index| libraries.get(index as usize).copied())
+                    .unwrap_or("unknown");
+                imports.push(json!({"name": name, "address": hex(address), "library": library}));
+            } else {
+                functions.push(json!({
+                    "name": name,
+                    "address": hex(address),
+                    "is_library": kind == VERTEX_LIBRARY,
+                }));
+            }
+        }
+
+        let calls: Vec<Value> = edges
+            .iter()
+            .filter_map(|edge| {
+                let source = addresses.get(varint(edge, EDGE_SOURCE).unwrap_or(0) as usize)?;
+                let target = addresses.get(varint(edge, EDGE_TARGET).unwrap_or(0) as usize)?;
+                Some(json!({"from_address": hex(*source), "to_address": hex(*target)}))
+            })
+            .collect();
+
+        Ok(json!({
+            "binary_info": {
+                "name": name,
+                "file_path": name,
+                "file_size": 0,
+                "file_type": {
+                    "type": "unknown",
+                    "architecture": string(&meta, META_ARCHITECTURE_NAME).unwrap_or("unknown"),
+                },
+                "hashes": {"sha256": executable_id.to_lowercase()},
+            },
+            "functions": functions,
+            "imports": imports,
+            "calls": calls,
+        }))
+    }
+}
+
+/// Protobuf field value; fixed-width values are not used by any field read here
+enum Field<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+    Fixed,
+}
+
+/// Decode the top-level fields of one protobuf message
+fn fields(mut bytes: &[u8]) -> Result<Vec<(u64, Field<'_>)>> {
+    let mut fields = Vec::new();
+    while !bytes.is_empty() {
+        let key = read_varint(&mut bytes)?;
+        let field = match key & 7 {
+            0 => Field::Varint(read_varint(&mut bytes)?),
+            1 => {
+                skip(&mut bytes, 8)?;
+                Field::Fixed
+            }
+            2 => {
+                let length = read_varint(&mut bytes)? as usize;
+                Field::Bytes(skip(&mut bytes, length)?)
+            }
+            5 => {
+                skip(&mut bytes, 4)?;
+                Field::Fixed
+            }
+            wire_type => {
+                return Err(anyhow::anyhow!(
+                    "Unsupported protobuf wire type {}",
+                    wire_type
+                ))
+            }
+        };
+        fields.push((key >> 3, field));
+    }
+    Ok(fields)
+}
+
+fn read_varint(bytes: &mut &[u8]) -> Result<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = bytes
+            .split_first()
+            .ok_or_else(|| anyhow::anyhow!("Truncated protobuf varint"))?;
+        *bytes = rest;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(anyhow::anyhow!("Protobuf varint too long"))
+}
+
+fn skip<'a>(bytes: &mut &'a [u8], length: usize) -> Result<&'a [u8]> {
+    if bytes.len() < length {
+        return Err(anyhow::anyhow!("Truncated protobuf field"));
+    }
+    let (value, rest) = bytes.split_at(length);
+    *bytes = rest;
+    Ok(value)
+}
+
+fn varint(fields: &[(u64, Field)], number: u64) -> Option<u64> {
+    fields.iter().find_map(|(n, field)| match field {
+        Field::Varint(value) if *n == number => Some(*value),
+        _ => None,
+    })
+}
+
+fn string<'a>(fields: &[(u64, Field<'a>)], number: u64) -> Option<&'a str> {
+    fields.iter().find_map(|(n, field)| match field {
+        Field::Bytes(bytes) if *n == number => std::str::from_utf8(bytes).ok(),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(fields: &[(u64, &[u8])]) -> Vec<u8> {
+        let mut out = Vec::new();
+        for (number, bytes) in fields {
+            out.push(((number << 3) | 2) as u8);
+            out.push(bytes.len() as u8);
+            out.extend_from_slice(bytes);
+        }
+        out
+    }
+
+    #[test]
+    fn test_binexport_call_graph() {
+        let meta = message(&[(1, b"a.exe"), (2, b"ABCD")]);
+        let mut main = vec![0x08, 0x80, 0x20]; // address 0x1000
+        main.extend(message(&[(3, b"main")]));
+        let import = [0x08, 0x80, 0x40, 0x10, 0x02, 0x28, 0x00]; // 0x2000, IMPORTED, library 0
+        let edge = [0x08, 0x00, 0x10, 0x01];
+        let call_graph = message(&[(1, &main), (1, &import), (2, &edge)]);
+        let library = message(&[(3, b"KERNEL32.dll")]);
+        let bytes = message(&[(1, &meta), (8, &call_graph), (14, &library)]);
+
+        let data = SourceData::Binary(bytes);
+        assert!(BinExportAdapter.detect(&data));
+        let native = BinExportAdapter.to_native(data).unwrap();
+
+        assert_eq!(native["binary_info"]["hashes"]["sha256"], "abcd");
+        assert_eq!(native["functions"][0]["name"], "main");
+        assert_eq!(native["imports"][0]["address"], "0x2000");
+        assert_eq!(native["imports"][0]["library"], "KERNEL32.dll");
+        assert_eq!(native["calls"][0]["from_address"], "0x1000");
+        assert_eq!(native["calls"][0]["to_address"], "0x2000");
+    }
+}
@@ -0,0 +1,157 @@
+use anyhow::Result;
+use serde_json::{json, Map, Value};
+
+use super::{address_of, architecture, array, containing_function, file_type_name, hex, text};
+use super::{InputAdapter, SourceData};
+
+/// JSON written by a Ghidra export script: a `program` object with the
+/// `Program` API fields, `functions`, `exports`, `strings` and `references`
+pub struct GhidraAdapter;
+
+impl InputAdapter for GhidraAdapter {
+    fn name(&self) -> &'static str {
+        "ghidra"
+    }
+
+    fn detect(&self, data: &SourceData) -> bool {
+        data.json()
+            .is_some_and(|value| value.get("program").is_some_and(Value::is_object))
+    }
+
+    fn to_native(&self, data: SourceData) -> Result<Value> {
+        let SourceData::Json(value) = data else {
+            return Err(anyhow::anyhow!("Ghidra input must be JSON"));
+        };
+        let program = value
+            .get("program")
+            .ok_or_else(|| anyhow::anyhow!("Ghidra export missing program"))?;
+
+        let mut functions = Vec::new();
+        let mut imports = Vec::new();
+        let mut ranges = Vec::new();
+        for function in array(&value, "functions") {
+            // External functions are called through their thunk or IAT slot
+            let external = function.get("is_external").and_then(Value::as_bool) == Some(true);
+            let entry = if external {
+                function
+                    .get("address")
+                    .or_else(|| function.get("entry_point"))
+            } else {
+                function.get("entry_point")
+            };
+            let Some(entry) = entry.and_then(address_of) else {
+                continue;
+            };
+            let name = text(function, "name").unwrap_or("unknown");
+            if external {
+                imports.push(json!({
+                    "name": name,
+                    "address": hex(entry),
+                    "library": text(function, "library").unwrap_or("unknown"),
+                }));
+                continue;
+            }
+            let size = function.get("body_size").and_then(Value::as_u64);
+            ranges.push((entry, size.unwrap_or(0)));
+            functions.push(json!({"name": name, "address": hex(entry), "size": size}));
+        }
+        ranges.sort_unstable();
+
+        let exports: Vec<Value> = array(&value, "exports")
+            .filter_map(|export| {
+                Some(json!({
+                    "name": text(export, "name")?,
+                    "address": hex(export.get("address").and_then(address_of)?),
+                }))
+            })
+            .collect();
+
+        let strings: Vec<Value> = array(&value, "strings")
+            .filter_map(|string| {
+                let value = text(string, "value")?;
+                Some(json!({
+                    "value": value,
+                    "address": string.get("address").and_then(address_of).map(hex),
+                    "length": value.len(),
+                    "type": text(string, "data_type").unwrap_or("string"),
+                }))
+            })
+            .collect();
+
+        let mut calls = Vec::new();
+        let mut data_refs = Vec::new();
+        for reference in array(&value, "references") {
+            let (Some(from), Some(to)) = (
+                reference.get("from_address").and_then(address_of),
+                reference.get("to_address").and_then(address_of),
+            ) else {
+                continue;
+            };
+            let Some(function) = containing_function(&ranges, from) else {
+                continue;
+            };
+            let ref_type = text(reference, "ref_type").unwrap_or("DATA").to_uppercase();
+            if ref_type.contains("CALL") {
+                let call_type = if ref_type.contains("COMPUTED") {
+                    "indirect"
+                } else {
+                    "direct"
+                };
+                calls.push(json!({
+                    "from_address": hex(function),
+                    "to_address": hex(to),
+                    "offset": hex(from),
+                    "type": call_type,
+                }));
+            } else if !ref_type.contains("JUMP") && !ref_type.contains("FLOW") {
+                let access = if ref_type.contains("WRITE") {
+                    "write"
+                } else {
+                    "read"
+                };
+                data_refs.push(json!({
+                    "from_address": hex(function),
+                    "to_address": hex(to),
+                    "offset": hex(from),
+                    "type": access,
+                }));
+            }
+        }
+
+        let (processor, bits) = parse_language_id(text(program, "language_id").unwrap_or(""));
+        let name = text(program, "name").unwrap_or("unknown");
+        let mut hashes = Map::new();
+        for (key, field) in [("sha256", "executable_sha256"), ("md5", "executable_md5")] {
+            if let Some(hash) = text(program, field) {
+                hashes.insert(key.to_string(), Value::String(hash.to_lowercase()));
+            }
+        }
+
+        Ok(json!({
+            "binary_info": {
+                "name": name,
+                "file_path": text(program, "executable_path").unwrap_or(name),
+                "file_size": program.get("file_size").and_then(Value::as_u64).unwrap_or(0),
+                "file_type": {
+                    "type": file_type_name(text(program, "executable_format").unwrap_or("")),
+                    "architecture": architecture(processor, bits),
+                },
+                "hashes": hashes,
+            },
+            "functions": functions,
+            "imports": imports,
+            "exports": exports,
+            "strings": strings,
+            "calls": calls,
+            "data_refs": data_refs,
+        }))
+    }
+}
+
+/// Processor and pointer size of a language ID such as `x86:LE:64:default`
+fn parse_language_id(language_id: &str) -> (&str, u64) {
+    let mut parts = language_id.split(':');
+    let processor = parts.next().unwrap_or("unknown");
+    let bits = parts.nth(1).and_then(|b| b.parse().ok()).unwrap_or(0);
+    (processor, bits)
+}
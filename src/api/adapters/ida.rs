@@ -0,0 +1,249 @@
+use anyhow::Result;
+use serde_json::{json, Value};
+use std::collections::HashSet;
+
+use super::{address_of, architecture, array, containing_function, file_type_name, hex, text};
+use super::{InputAdapter, SourceData};
+
+/// Prefixes IDA uses for names it generates from an address (`sub_401000`, `loc_401020`, ...)
+const AUTO_NAME_PREFIXES: [&str; 13] = [
+    "sub_", "loc_", "locret_", "nullsub_", "j_sub_", "unk_", "off_", "byte_", "word_", "dword_",
+    "qword_", "asc_", "stru_",
+];
+
+/// JSON written by an IDAPython exporter: `metadata` (with `exporter: "ida"`),
+/// `segments`, `functions`, `imports`, `exports`, `strings` and `xrefs`
+pub struct IdaAdapter;
+
+impl InputAdapter for IdaAdapter {
+    fn name(&self) -> &'static str {
+        "ida"
+    }
+
+    fn detect(&self, data: &SourceData) -> bool {
+        data.json().is_some_and(|value| {
+            value
+                .get("metadata")
+                .and_then(|metadata| text(metadata, "exporter"))
+                .is_some_and(|exporter| exporter.eq_ignore_ascii_case("ida"))
+        })
+    }
+
+    fn to_native(&self, data: SourceData) -> Result<Value> {
+        let SourceData::Json(value) = data else {
+            return Err(anyhow::anyhow!("IDA input must be JSON"));
+        };
+        let metadata = value
+            .get("metadata")
+            .ok_or_else(|| anyhow::anyhow!("IDA export missing metadata"))?;
+        let addresses = IdaAddresses::new(&value);
+
+        let mut functions = Vec::new();
+        let mut ranges = Vec::new();
+        for function in array(&value, "functions") {
+            let Some(start) = function.get("start").and_then(|a| addresses.resolve(a)) else {
+                continue;
+            };
+            let end = function.get("end").and_then(|a| addresses.resolve(a));
+            let size = end.map(|end| end.saturating_sub(start));
+            ranges.push((start, size.unwrap_or(0)));
+
+            // A function created on a code label keeps the label's `loc_` name
+            let name = match text(function, "name") {
+                Some(name) => match name.strip_prefix("loc_") {
+                    Some(rest) => format!("sub_{}", rest),
+                    None => name.to_string(),
+                },
+                None => format!("sub_{:X}", start),
+            };
+            let is_library = array(function, "flags").any(|flag| flag.as_str() == Some("lib"));
+            functions.push(json!({
+                "name": name,
+                "address": hex(start),
+                "size": size,
+                "is_library": is_library,
+            }));
+        }
+        ranges.sort_unstable();
+        let entries: HashSet<u64> = ranges.iter().map(|(entry, _)| *entry).collect();
+
+        let mut import_addresses = HashSet::new();
+        let imports: Vec<Value> = array(&value, "imports")
+            .filter_map(|import| {
+                let address = import.get("ea").and_then(|a| addresses.resolve(a))?;
+                import_addresses.insert(address);
+                let name = text(import, "name").map(str::to_string).or_else(|| {
+                    let ordinal = import.get("ordinal")?.as_u64()?;
+                    Some(format!("ord_{}", ordinal))
+                })?;
+                Some(json!({
+                    "name": name,
+                    "address": hex(address),
+                    "library": text(import, "module").unwrap_or("unknown"),
+                }))
+            })
+            .collect();
+
+        let exports: Vec<Value> = array(&value, "exports")
+            .filter_map(|export| {
+                Some(json!({
+                    "name": text(export, "name")?,
+                    "address": hex(export.get("ea").and_then(|a| addresses.resolve(a))?),
+                    "ordinal": export.get("ordinal"),
+                }))
+            })
+            .collect();
+
+        let strings: Vec<Value> = array(&value, "strings")
+            .filter_map(|string| {
+                let value = text(string, "value")?;
+                Some(json!({
+                    "value": value,
+                    "address": string.get("ea").and_then(|a| addresses.resolve(a)).map(hex),
+                    "length": value.chars().count(),
+                    "type": text(string, "type").unwrap_or("C"),
+                }))
+            })
+            .collect();
+
+        let mut calls = Vec::new();
+        let mut data_refs = Vec::new();
+        for xref in array(&value, "xrefs") {
+            let (Some(from), Some(to)) = (
+                xref.get("from").and_then(|a| addresses.resolve(a)),
+                xref.get("to").and_then(|a| addresses.resolve(a)),
+            ) else {
+                continue;
+            };
+            let Some(caller) = containing_function(&ranges, from) else {
+                continue;
+            };
+            let xref_type = text(xref, "type").unwrap_or("dr_R");
+            match xref_type {
+                "fl_CN" | "fl_CF" => {
+                    // Calls to a `loc_` label land inside the function that owns it
+                    let target = if entries.contains(&to) || import_addresses.contains(&to) {
+                        to
+                    } else {
+                        containing_function(&ranges, to).unwrap_or(to)
+                    };
+                    calls.push(json!({
+                        "from_address": hex(caller),
+                        "to_address": hex(target),
+                        "offset": hex(from),
+                        "type": "direct",
+                    }));
+                }
+                "dr_R" | "dr_W" | "dr_O" => {
+                    let access = match xref_type {
+                        "dr_W" => "write",
+                        "dr_O" => "offset",
+                        _ => "read",
+                    };
+                    data_refs.push(json!({
+                        "from_address": hex(caller),
+                        "to_address": hex(to),
+                        "offset": hex(from),
+                        "type": access,
+                    }));
+                }
+                _ => {}
+            }
+        }
+
+        let input_file = text(metadata, "input_file").unwrap_or("unknown");
+        let bits = metadata.get("bitness").and_then(Value::as_u64).unwrap_or(0);
+        let mut hashes = serde_json::Map::new();
+        for key in ["sha256", "md5"] {
+            if let Some(hash) = text(metadata, key) {
+                hashes.insert(key.to_string(), Value::String(hash.to_lowercase()));
+            }
+        }
+
+        Ok(json!({
+            "binary_info": {
+                "name": input_file.rsplit(['/', '\\']).next().unwrap_or(input_file),
+                "file_path": input_file,
+                "file_size": metadata.get("file_size").and_then(Value::as_u64).unwrap_or(0),
+                "file_type": {
+                    "type": file_type_name(text(metadata, "file_type").unwrap_or("")),
+                    "architecture": architecture(text(metadata, "processor").unwrap_or("unknown"), bits),
+                },
+                "compiler": text(metadata, "compiler"),
+                "hashes": hashes,
+            },
+            "functions": functions,
+            "imports": imports,
+            "exports": exports,
+            "strings": strings,
+            "calls": calls,
+            "data_refs": data_refs,
+        }))
+    }
+}
+
+/// Resolves the address forms found in IDA exports
+struct IdaAddresses {
+    /// Segment name, start and end address
+    segments: Vec<(String, u64, u64)>,
+}
+
+impl IdaAddresses {
+    fn new(value: &Value) -> Self {
+        let segments = array(value, "segments")
+            .filter_map(|segment| {
+                let name = text(segment, "name")?.to_string();
+                let start = segment.get("start").and_then(address_of)?;
+                let end = segment.get("end").and_then(address_of).unwrap_or(start);
+                Some((name, start, end))
+            })
+            .collect();
+        Self { segments }
+    }
+
+    /// Numbers, hex strings, `segment:address` pairs and auto names such as `sub_401000`.
+    /// The part after the segment name is taken as an offset into the segment
+    /// unless it already lies inside it, as in IDA's `.text:00401000` listing form.
+    fn resolve(&self, value: &Value) -> Option<u64> {
+        if let Some(address) = address_of(value) {
+            return Some(address);
+        }
+        let text = value.as_str()?.trim();
+
+        if let Some((segment, offset)) = text.rsplit_once(':') {
+            let (_, start, end) = self.segments.iter().find(|(name, ..)| name == segment)?;
+            let offset = u64::from_str_radix(offset, 16).ok()?;
+            return Some(if (*start..*end).contains(&offset) {
+                offset
+            } else {
+                start + offset
+            });
+        }
+
+        AUTO_NAME_PREFIXES
+            .iter()
+            .find_map(|prefix| text.strip_prefix(prefix))
+            .and_then(|rest| u64::from_str_radix(rest, 16).ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ida_address_forms() {
+        let addresses = IdaAddresses::new(&json!({
+            "segments": [{"name": ".text", "start": "0x401000", "end": "0x402000"}]
+        }));
+
+        assert_eq!(addresses.resolve(&json!("0x401000")), Some(0x401000));
+        assert_eq!(addresses.resolve(&json!(4198400)), Some(0x401000));
+        assert_eq!(addresses.resolve(&json!(".text:00000020")), Some(0x401020));
+        assert_eq!(addresses.resolve(&json!(".text:00401020")), Some(0x401020));
+        assert_eq!(addresses.resolve(&json!("sub_401000")), Some(0x401000));
+        assert_eq!(addresses.resolve(&json!("loc_401020")), Some(0x401020));
+        assert_eq!(addresses.resolve(&json!("seg001:0010")), None);
+        assert_eq!(addresses.resolve(&json!("main")), None);
+    }
+}
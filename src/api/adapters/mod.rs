@@ -0,0 +1,222 @@
+//! Input format adapters
+//!
+//! Every extractor output is rewritten into the native BinaryX document
+//! (`binary_info`, `functions`, `imports`, `exports`, `strings`, `calls`,
+//! `data_refs`) before it reaches `ImportSession`, so supporting a new format
+//! only takes another `InputAdapter` listed in `ADAPTERS`.
+
+mod angr;
+mod binexport;
+mod ghidra;
+mod ida;
+mod native;
+mod radare2;
+
+use anyhow::Result;
+use serde_json::Value;
+use std::path::Path;
+
+use crate::models::Architecture;
+use crate::utils::uid::format_address;
+
+pub use angr::AngrAdapter;
+pub use binexport::BinExportAdapter;
+pub use ghidra::GhidraAdapter;
+pub use ida::IdaAdapter;
+pub use native::NativeAdapter;
+pub use radare2::Radare2Adapter;
+
+/// Extractor output format selected with `--source`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum SourceFormat {
+    /// Detect the format from the file contents
+    #[default]
+    Auto,
+    /// BinaryX-IDA JSON
+    Native,
+    /// IDAPython exporter JSON (`metadata.exporter` = `ida`)
+    Ida,
+    /// Ghidra program export JSON (`program`, `functions`, `references`, ...)
+    Ghidra,
+    /// angr extraction script JSON (CLE `main_object`, CFGFast functions)
+    Angr,
+    /// radare2 `ij`/`itj`/`aflj`/`iij`/`iEj`/`izj` output combined in one object
+    R2,
+    /// BinExport2 protobuf (`.BinExport`)
+    Binexport,
+}
+
+/// Raw contents of an input file
+pub enum SourceData {
+    Json(Value),
+    Binary(Vec<u8>),
+}
+
+impl SourceData {
+    /// Parse files starting with `{` or `[` as JSON, keep anything else as bytes
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let bytes = std::fs::read(path)?;
+        match bytes.iter().find(|b| !b.is_ascii_whitespace()) {
+            Some(b'{') | Some(b'[') => Ok(SourceData::Json(serde_json::from_slice(&bytes)?)),
+            _ => Ok(SourceData::Binary(bytes)),
+        }
+    }
+
+    fn json(&self) -> Option<&Value> {
+        match self {
+            SourceData::Json(value) => Some(value),
+            SourceData::Binary(_) => None,
+        }
+    }
+}
+
+/// Converts one extractor format into the native BinaryX document
+pub trait InputAdapter: Sync {
+    /// Format name, as accepted by `--source`
+    fn name(&self) -> &'static str;
+
+    /// Whether `data` looks like this format
+    fn detect(&self, data: &SourceData) -> bool;
+
+    fn to_native(&self, data: SourceData) -> Result<Value>;
+}
+
+/// Adapters tried in order by auto-detection
+static ADAPTERS: [&dyn InputAdapter; 6] = [
+    &NativeAdapter,
+    &IdaAdapter,
+    &GhidraAdapter,
+    &AngrAdapter,
+    &Radare2Adapter,
+    &BinExportAdapter,
+];
+
+/// Adapter for an explicit `--source`, or the first one recognising `data`
+pub fn select_adapter(
+    source: SourceFormat,
+    data: &SourceData,
+) -> Result<&'static dyn InputAdapter> {
+    let adapter: &'static dyn InputAdapter = match source {
+        SourceFormat::Native => &NativeAdapter,
+        SourceFormat::Ida => &IdaAdapter,
+        SourceFormat::Ghidra => &GhidraAdapter,
+        SourceFormat::Angr => &AngrAdapter,
+        SourceFormat::R2 => &Radare2Adapter,
+        SourceFormat::Binexport => &BinExportAdapter,
+        SourceFormat::Auto => {
+            let detected = ADAPTERS
+                .iter()
+                .copied()
+                .find(|adapter| adapter.detect(data));
+            return detected.ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Unrecognised input format, pass --source native|ida|ghidra|angr|r2|binexport"
+                )
+            });
+        }
+    };
+    Ok(adapter)
+}
+
+/// Read `path` and convert it to the native document, returning the adapter used
+pub fn load_native<P: AsRef<Path>>(path: P, source: SourceFormat) -> Result<(Value, &'static str)> {
+    let data = SourceData::read(path)?;
+    let adapter = select_adapter(source, &data)?;
+    Ok((adapter.to_native(data)?, adapter.name()))
+}
+
+/// Native `file_type.type` for a format description ("pe", "ELF", "Mac OS X Mach-O", ...)
+fn file_type_name(format: &str) -> &'static str {
+    let format = format.to_lowercase();
+    if format.contains("mach") {
+        "Mach-O"
+    } else if format.contains("elf") {
+        "ELF"
+    } else if format.contains("pe") || format.contains("portable executable") {
+        "PE"
+    } else {
+        "unknown"
+    }
+}
+
+/// Architecture label from a processor name and word size, e.g. `x86` + 64 -> `x86_64`;
+/// unrecognized processors are passed through for the importer to report
+fn architecture(processor: &str, bits: u64) -> String {
+    match Architecture::parse(processor, Some(bits as u32)) {
+        Architecture::Unknown => processor.to_string(),
+        architecture => architecture.as_str().to_string(),
+    }
+}
+
+/// Address given as a JSON number or a hex string
+fn address_of(value: &Value) -> Option<u64> {
+    match value {
+        Value::Number(n) => n.as_u64(),
+        Value::String(s) => crate::utils::uid::parse_address(s),
+        _ => None,
+    }
+}
+
+/// Function entry containing `address`, given `(entry, size)` pairs sorted by entry
+fn containing_function(ranges: &[(u64, u64)], address: u64) -> Option<u64> {
+    let index = ranges.partition_point(|(entry, _)| *entry <= address);
+    let (entry, size) = *ranges.get(index.checked_sub(1)?)?;
+    (address < entry.saturating_add(size.max(1))).then_some(entry)
+}
+
+fn hex(address: u64) -> Value {
+    Value::String(format_address(address))
+}
+
+fn array<'a>(value: &'a Value, key: &str) -> impl Iterator<Item = &'a Value> {
+    value
+        .get(key)
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+}
+
+fn text<'a>(value: &'a Value, key: &str) -> Option<&'a str> {
+    value.get(key).and_then(Value::as_str)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_detects_json_formats() {
+        let native = SourceData::Json(json!({"binary_info": {}, "functions": []}));
+        let ghidra = SourceData::Json(json!({"program": {"name": "a.exe"}, "functions": []}));
+        let r2 = SourceData::Json(json!({"info": {"core": {}, "bin": {}}, "functions": []}));
+        let ida = SourceData::Json(json!({"metadata": {"exporter": "ida"}, "functions": []}));
+        let angr = SourceData::Json(json!({"extractor": "angr", "main_object": {}}));
+
+        let name = |data: &SourceData| select_adapter(SourceFormat::Auto, data).unwrap().name();
+        assert_eq!(name(&native), "native");
+        assert_eq!(name(&ghidra), "ghidra");
+        assert_eq!(name(&r2), "r2");
+        assert_eq!(name(&ida), "ida");
+        assert_eq!(name(&angr), "angr");
+        assert!(select_adapter(SourceFormat::Auto, &SourceData::Json(json!([]))).is_err());
+    }
+
+    #[test]
+    fn test_containing_function() {
+        let ranges = [(0x1000, 0x20), (0x2000, 0x10)];
+        assert_eq!(containing_function(&ranges, 0x1010), Some(0x1000));
+        assert_eq!(containing_function(&ranges, 0x1020), None);
+        assert_eq!(containing_function(&ranges, 0x2000), Some(0x2000));
+        assert_eq!(containing_function(&ranges, 0x0fff), None);
+    }
+
+    #[test]
+    fn test_architecture() {
+        assert_eq!(architecture("x86", 64), "x86_64");
+        assert_eq!(architecture("x86", 32), "x86");
+        assert_eq!(architecture("AARCH64", 64), "arm64");
+        assert_eq!(architecture("mips", 32), "mips");
+        assert_eq!(architecture("ppc", 64), "ppc64");
+    }
+}
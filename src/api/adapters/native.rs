@@ -0,0 +1,25 @@
+use anyhow::Result;
+use serde_json::Value;
+
+use super::{InputAdapter, SourceData};
+
+/// BinaryX-IDA JSON, imported as is
+pub struct NativeAdapter;
+
+impl InputAdapter for NativeAdapter {
+    fn name(&self) -> &'static str {
+        "native"
+    }
+
+    fn detect(&self, data: &SourceData) -> bool {
+        data.json()
+            .is_some_and(|value| value.get("binary_info").is_some())
+    }
+
+    fn to_native(&self, data: SourceData) -> Result<Value> {
+        match data {
+            SourceData::Json(value) => Ok(value),
+            SourceData::Binary(_) => Err(anyhow::anyhow!("Native input must be JSON")),
+        }
+    }
+}
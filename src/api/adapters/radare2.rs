@@ -0,0 +1,140 @@
+use anyhow::Result;
+use serde_json::{json, Value};
+
+use super::{address_of, architecture, array, file_type_name, hex, text};
+use super::{InputAdapter, SourceData};
+
+/// radare2 JSON output combined in one object: `info` (`ij`), `hashes` (`itj`),
+/// `functions` (`aflj`), `imports` (`iij`), `exports` (`iEj`) and `strings` (`izj`)
+pub struct Radare2Adapter;
+
+impl InputAdapter for Radare2Adapter {
+    fn name(&self) -> &'static str {
+        "r2"
+    }
+
+    fn detect(&self, data: &SourceData) -> bool {
+        data.json().is_some_and(|value| {
+            value
+                .get("info")
+                .is_some_and(|info| info.get("core").is_some() || info.get("bin").is_some())
+        })
+    }
+
+    fn to_native(&self, data: SourceData) -> Result<Value> {
+        let SourceData::Json(value) = data else {
+            return Err(anyhow::anyhow!("radare2 input must be JSON"));
+        };
+        let info = value
+            .get("info")
+            .ok_or_else(|| anyhow::anyhow!("radare2 input missing info (ij)"))?;
+        let core = info.get("core").unwrap_or(&Value::Null);
+        let bin = info.get("bin").unwrap_or(&Value::Null);
+
+        let mut functions = Vec::new();
+        let mut calls = Vec::new();
+        let mut data_refs = Vec::new();
+        for function in array(&value, "functions") {
+            let Some(entry) = function.get("offset").and_then(address_of) else {
+                continue;
+            };
+            functions.push(json!({
+                "name": text(function, "name").unwrap_or("unknown"),
+                "address": hex(entry),
+                "size": function.get("size").and_then(Value::as_u64),
+            }));
+
+            for callref in array(function, "callrefs") {
+                let kind = text(callref, "type").unwrap_or("CALL");
+                if !kind.eq_ignore_ascii_case("CALL") && !kind.eq_ignore_ascii_case("C") {
+                    continue;
+                }
+                let Some(target) = callref.get("addr").and_then(address_of) else {
+                    continue;
+                };
+                calls.push(json!({
+                    "from_address": hex(entry),
+                    "to_address": hex(target),
+                    "offset": callref.get("at").and_then(address_of).map(hex),
+                    "type": "direct",
+                }));
+            }
+
+            // Older releases list plain addresses, newer ones `{addr, at}` objects
+            for dataref in array(function, "datarefs") {
+                let target = dataref.get("addr").unwrap_or(dataref);
+                let Some(target) = address_of(target) else {
+                    continue;
+                };
+                data_refs.push(json!({
+                    "from_address": hex(entry),
+                    "to_address": hex(target),
+                    "offset": dataref.get("at").and_then(address_of).map(hex),
+                }));
+            }
+        }
+
+        let imports: Vec<Value> = array(&value, "imports")
+            .filter_map(|import| {
+                let address = ["plt", "vaddr"]
+                    .iter()
+                    .find_map(|key| import.get(*key).and_then(address_of))
+                    .filter(|address| *address != 0)?;
+                Some(json!({
+                    "name": text(import, "name")?,
+                    "address": hex(address),
+                    "library": text(import, "libname").unwrap_or("unknown"),
+                }))
+            })
+            .collect();
+
+        let exports: Vec<Value> = array(&value, "exports")
+            .filter_map(|export| {
+                Some(json!({
+                    "name": text(export, "realname").or_else(|| text(export, "name"))?,
+                    "address": hex(export.get("vaddr").and_then(address_of)?),
+                    "ordinal": export.get("ordinal"),
+                }))
+            })
+            .collect();
+
+        let strings: Vec<Value> = array(&value, "strings")
+            .filter_map(|string| {
+                let value = text(string, "string")?;
+                Some(json!({
+                    "value": value,
+                    "address": string.get("vaddr").and_then(address_of).map(hex),
+                    "length": string.get("length").and_then(Value::as_u64),
+                    "type": text(string, "type").unwrap_or("ascii"),
+                }))
+            })
+            .collect();
+
+        let file_path = text(core, "file").unwrap_or("unknown");
+        let name = file_path.rsplit(['/', '\\']).next().unwrap_or(file_path);
+        let format = text(bin, "bintype")
+            .or_else(|| text(core, "format"))
+            .unwrap_or("");
+        let bits = bin.get("bits").and_then(Value::as_u64).unwrap_or(0);
+
+        Ok(json!({
+            "binary_info": {
+                "name": name,
+                "file_path": file_path,
+                "file_size": core.get("size").and_then(Value::as_u64).unwrap_or(0),
+                "file_type": {
+                    "type": file_type_name(format),
+                    "architecture": architecture(text(bin, "arch").unwrap_or("unknown"), bits),
+                },
+                "compiler": text(bin, "compiler"),
+                "hashes": value.get("hashes").cloned().unwrap_or_else(|| json!({})),
+            },
+            "functions": functions,
+            "imports": imports,
+            "exports": exports,
+            "strings": strings,
+            "calls": calls,
+            "data_refs": data_refs,
+        }))
+    }
+}
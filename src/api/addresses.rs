@@ -0,0 +1,204 @@
+//! Address fields of extractor output
+//!
+//! Every address an import matches functions, calls and strings by is checked before
+//! the import, so unparseable ones are reported per file instead of silently dropping
+//! the items they belong to. When `binary_info.image_base` is given, the addresses are
+//! also rewritten relative to it, so a file listing virtual addresses and one listing
+//! RVAs of the same binary produce the same UIDs. Addresses below the image base are
+//! taken as RVAs already.
+
+use serde_json::Value;
+
+use crate::utils::uid::{format_address, parse_address, relative_address, try_parse_address};
+
+/// Keys holding an address in the entries of each section
+const ADDRESS_KEYS: &[(&str, &[&str])] = &[
+    ("functions", &["address", "chunks"]),
+    ("imports", &["address"]),
+    ("exports", &["address"]),
+    ("strings", &["address", "decrypted_by"]),
+    ("calls", &["from_address", "to_address", "offset"]),
+    (
+        "data_refs",
+        &["from_address", "function", "to_address", "target", "offset"],
+    ),
+    (
+        "indirect_targets",
+        &["from_address", "function", "offset", "call_site", "targets"],
+    ),
+    ("vtables", &["address", "slots", "bases", "base"]),
+];
+
+/// `image_base` of a `binary_info`, as a number or an address string; 0 counts as none
+pub fn image_base(binary_info: &Value) -> Option<u64> {
+    let value = binary_info.get("image_base")?;
+    value
+        .as_u64()
+        .or_else(|| value.as_str().and_then(parse_address))
+        .filter(|base| *base != 0)
+}
+
+/// JSON pointers to the address strings and numbers of `data`
+fn address_pointers(data: &Value) -> Vec<String> {
+    let mut pointers = Vec::new();
+    for (section, keys) in ADDRESS_KEYS {
+        let Some(entries) = data.get(*section).and_then(Value::as_array) else {
+            continue;
+        };
+        for (index, entry) in entries.iter().enumerate() {
+            for key in *keys {
+                if let Some(value) = entry.get(*key) {
+                    let pointer = format!("/{}/{}/{}", section, index, key);
+                    collect_addresses(value, pointer, &mut pointers);
+                }
+            }
+        }
+    }
+
+    // A list of addresses or `{address, kind}` objects, or an object of kind to addresses
+    match data.get("entry_points") {
+        Some(Value::Array(entries)) => {
+            for (index, value) in entries.iter().enumerate() {
+                collect_addresses(value, format!("/entry_points/{}", index), &mut pointers);
+            }
+        }
+        Some(Value::Object(kinds)) => {
+            for (kind, value) in kinds {
+                // JSON pointer escapes of `~` and `/`
+                let kind = kind.replace('~', "~0").replace('/', "~1");
+                collect_addresses(value, format!("/entry_points/{}", kind), &mut pointers);
+            }
+        }
+        _ => {}
+    }
+
+    if let Some(entry_point) = data
+        .pointer("/binary_info/pe_info/entry_point")
+        .filter(|value| value.is_string())
+    {
+        let pointer = "/binary_info/pe_info/entry_point".to_string();
+        collect_addresses(entry_point, pointer, &mut pointers);
+    }
+    pointers
+}
+
+/// An address string or number, a list of them or an object's `address` (or the
+/// `start` and `end` of a function chunk)
+fn collect_addresses(value: &Value, pointer: String, pointers: &mut Vec<String>) {
+    match value {
+        Value::String(_) | Value::Number(_) => pointers.push(pointer),
+        Value::Array(values) => {
+            for (index, value) in values.iter().enumerate() {
+                collect_addresses(value, format!("{}/{}", pointer, index), pointers);
+            }
+        }
+        Value::Object(object) => {
+            for key in ["address", "start", "end"] {
+                if let Some(address) = object.get(key) {
+                    collect_addresses(address, format!("{}/{}", pointer, key), pointers);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Address fields of `data` that do not parse, as `<JSON pointer>: '<value>' (<reason>)`
+pub fn unparseable(data: &Value) -> Vec<String> {
+    address_pointers(data)
+        .into_iter()
+        .filter_map(|pointer| {
+            let value = data.pointer(&pointer)?;
+            let error = match value {
+                Value::String(text) => try_parse_address(text).err()?,
+                _ if value.is_u64() => return None,
+                _ => crate::utils::uid::AddressError::Malformed,
+            };
+            let text = value
+                .as_str()
+                .map_or_else(|| value.to_string(), str::to_string);
+            Some(format!("{}: '{}' ({})", pointer, text, error))
+        })
+        .collect()
+}
+
+/// Rewrite the addresses of `data` relative to `image_base`, returning how many changed
+pub fn make_relative(data: &mut Value, image_base: u64) -> usize {
+    let mut rewritten = 0;
+    for pointer in address_pointers(data) {
+        let Some(value) = data.pointer_mut(&pointer) else {
+            continue;
+        };
+        let address = match value {
+            Value::String(text) => parse_address(text),
+            _ => value.as_u64(),
+        };
+        let Some(address) = address.filter(|address| *address >= image_base) else {
+            continue;
+        };
+        let relative = relative_address(address, image_base);
+        *value = if value.is_string() {
+            Value::from(format_address(relative))
+        } else {
+            Value::from(relative)
+        };
+        rewritten += 1;
+    }
+    rewritten
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_make_relative() {
+        let mut data = json!({
+            "binary_info": {"image_base": "0x400000", "pe_info": {"entry_point": "0x401000"}},
+            "functions": [
+                {"address": "0x401000", "chunks": [{"start": "0x405000", "end": "0x405040"}]},
+                {"address": "0x2000"}
+            ],
+            "calls": [{"from_address": "0x401000", "to_address": "2000", "offset": "0x401005"}],
+            "indirect_targets": [
+                {"from_address": "0x401000", "targets": ["0x402000", {"address": "0x403000"}]}
+            ],
+            "entry_points": {"main": "0x401000", "tls_callbacks": ["0x404000"]},
+        });
+        let image_base = image_base(&data["binary_info"]).unwrap();
+        assert_eq!(make_relative(&mut data, image_base), 11);
+
+        assert_eq!(data["functions"][0]["address"], "0x1000");
+        assert_eq!(data["functions"][1]["address"], "0x2000");
+        assert_eq!(
+            data["functions"][0]["chunks"],
+            json!([{"start": "0x5000", "end": "0x5040"}])
+        );
+        assert_eq!(data["calls"][0]["to_address"], "2000");
+        assert_eq!(data["calls"][0]["offset"], "0x1005");
+        assert_eq!(
+            data["indirect_targets"][0]["targets"],
+            json!(["0x2000", {"address": "0x3000"}])
+        );
+        assert_eq!(data["entry_points"]["tls_callbacks"], json!(["0x4000"]));
+        assert_eq!(data["binary_info"]["pe_info"]["entry_point"], "0x1000");
+    }
+
+    #[test]
+    fn test_unparseable() {
+        let data = json!({
+            "functions": [{"address": "0x401000"}, {"address": "sub_401000"}],
+            "calls": [{"from_address": "0x401000", "to_address": "0x1ffffffffffffffff"}],
+            "vtables": [{"address": "0x405000", "slots": [null, {"address": -1}]}],
+        });
+        assert_eq!(
+            unparseable(&data),
+            vec![
+                "/functions/1/address: 'sub_401000' (not a number)",
+                "/calls/0/to_address: '0x1ffffffffffffffff' (exceeds 64 bits)",
+                "/vtables/0/slots/1/address: '-1' (not a number)",
+            ]
+        );
+    }
+}
@@ -0,0 +1,148 @@
+use anyhow::Result;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::utils::uid::parse_address;
+
+/// API resolved from a hash constant
+#[derive(Debug, Clone, PartialEq)]
+pub struct HashedApi {
+    /// Lowercased DLL name, with `.dll` added when the mapping gives none
+    pub library: Option<String>,
+    pub name: String,
+}
+
+/// Hash constants of API-hashing code mapped to the APIs they resolve, as produced by
+/// an analyst script (e.g. a HashDB lookup)
+///
+/// Accepts an object of hash to name (`{"0x7c0dfcaa": "kernel32.dll!GetProcAddress"}`)
+/// or an array of `{hash, api, library}` entries. Names may carry their library as
+/// `library!name`.
+#[derive(Debug, Clone, Default)]
+pub struct ApiHashes {
+    apis: HashMap<u64, HashedApi>,
+}
+
+impl ApiHashes {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            anyhow::anyhow!("Failed to read API hashes '{}': {}", path.display(), e)
+        })?;
+        let value: Value = serde_json::from_str(&text).map_err(|e| {
+            anyhow::anyhow!("Failed to parse API hashes '{}': {}", path.display(), e)
+        })?;
+        Self::parse(&value)
+    }
+
+    pub fn parse(value: &Value) -> Result<Self> {
+        let mut apis = HashMap::new();
+        match value {
+            Value::Object(map) => {
+                for (hash, api) in map {
+                    let api = api
+                        .as_str()
+                        .ok_or_else(|| anyhow::anyhow!("API hash {} must map to a name", hash))?;
+                    apis.insert(
+                        parse_hash(&Value::from(hash.as_str()))?,
+                        hashed_api(None, api),
+                    );
+                }
+            }
+            Value::Array(entries) => {
+                for entry in entries {
+                    let hash = parse_hash(entry.get("hash").unwrap_or(&Value::Null))?;
+                    let api = entry
+                        .get("api")
+                        .or_else(|| entry.get("name"))
+                        .and_then(|v| v.as_str())
+                        .ok_or_else(|| anyhow::anyhow!("API hash entry missing api"))?;
+                    let library = entry.get("library").and_then(|v| v.as_str());
+                    apis.insert(hash, hashed_api(library, api));
+                }
+            }
+            _ => {
+                return Err(anyhow::anyhow!(
+                "API hashes must be an object of hash to name or an array of {{hash, api}} entries"
+            ))
+            }
+        }
+        Ok(Self { apis })
+    }
+
+    pub fn len(&self) -> usize {
+        self.apis.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.apis.is_empty()
+    }
+
+    pub fn get(&self, hash: u64) -> Option<&HashedApi> {
+        self.apis.get(&hash)
+    }
+}
+
+fn parse_hash(value: &Value) -> Result<u64> {
+    value
+        .as_u64()
+        .or_else(|| value.as_str().and_then(parse_address))
+        .ok_or_else(|| anyhow::anyhow!("Invalid API hash: {}", value))
+}
+
+fn hashed_api(library: Option<&str>, api: &str) -> HashedApi {
+    let (library, name) = match api.split_once('!') {
+        Some((library, name)) => (Some(library), name),
+        None => (library, api),
+    };
+    HashedApi {
+        library: library
+            .map(|library| library.trim().to_lowercase())
+            .filter(|library| !library.is_empty())
+            .map(|library| {
+                if library.contains('.') {
+                    library
+                } else {
+                    format!("{}.dll", library)
+                }
+            }),
+        name: name.trim().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_api_hashes() {
+        let hashes = ApiHashes::parse(&json!({
+            "0x7c0dfcaa": "KERNEL32!GetProcAddress",
+            "0xec0e4e8e": "LoadLibraryA"
+        }))
+        .unwrap();
+        assert_eq!(
+            hashes.get(0x7c0d_fcaa),
+            Some(&HashedApi {
+                library: Some("kernel32.dll".to_string()),
+                name: "GetProcAddress".to_string()
+            })
+        );
+        assert_eq!(hashes.get(0xec0e_4e8e).unwrap().library, None);
+
+        let entries = ApiHashes::parse(&json!([
+            { "hash": 3960360590u64, "api": "LoadLibraryA", "library": "kernel32.dll" }
+        ]))
+        .unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(
+            entries.get(0xec0e_4e8e).unwrap().library.as_deref(),
+            Some("kernel32.dll")
+        );
+
+        assert!(ApiHashes::parse(&json!([{ "api": "Sleep" }])).is_err());
+        assert!(ApiHashes::parse(&json!("0x1")).is_err());
+    }
+}
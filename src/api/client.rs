@@ -1,45 +1,151 @@
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::fs::File;
-use std::io::BufReader;
+use std::io::{BufReader, BufWriter, Write};
 use std::path::Path;
+use std::time::Instant;
 
 use crate::config::Config;
-use crate::neo4j::{GraphImporter, Neo4jConnection};
+use crate::models::Annotations;
+use crate::neo4j::autotune::BatchTuner;
+use crate::neo4j::connection::{export_record, Cypher, QueryTiming, QueryTracing};
+use crate::neo4j::{metrics, GraphImporter, Neo4jConnection};
 
-use super::ImportSession;
+use super::api_hashes::ApiHashes;
+use super::signatures::LibrarySignatures;
+use super::{ErrorPolicy, ImportSession, StringStorage};
 
 #[derive(Clone)]
 pub struct DataImporter {
     connection: Neo4jConnection,
     importer: GraphImporter,
+    library_signatures: Option<LibrarySignatures>,
+    api_hashes: Option<ApiHashes>,
+    prune: bool,
+    strings: StringStorage,
+    error_policy: Option<ErrorPolicy>,
+    relative_addresses: bool,
+    strict_addresses: bool,
 }
 
 impl DataImporter {
     pub async fn new(config: &Config) -> Result<Self> {
         let connection = Neo4jConnection::new(config).await?;
-        let importer = GraphImporter::new(connection.clone());
+        let importer = GraphImporter::new(connection.clone()).with_batch_size(config.batch_size);
 
         Ok(Self {
             connection,
             importer,
+            library_signatures: None,
+            api_hashes: None,
+            prune: false,
+            strings: StringStorage::from_config(config),
+            error_policy: None,
+            relative_addresses: config.relative_addresses,
+            strict_addresses: false,
         })
     }
 
-    pub async fn import_from_file<P: AsRef<Path>>(&self, file_path: P) -> Result<ImportResult> {
-        let file = File::open(file_path)?;
-        let reader = BufReader::new(file);
-        let data: Value = serde_json::from_reader(reader)?;
+    /// Importer for read-heavy commands: read-only queries are spread over the
+    /// configured read replicas, writes still go to the primary
+    pub async fn for_analysis(config: &Config) -> Result<Self> {
+        let connection = Neo4jConnection::new(config)
+            .await?
+            .with_read_replicas(config)
+            .await?;
+        let importer = GraphImporter::new(connection.clone()).with_batch_size(config.batch_size);
+
+        Ok(Self {
+            connection,
+            importer,
+            library_signatures: None,
+            api_hashes: None,
+            prune: false,
+            strings: StringStorage::from_config(config),
+            error_policy: None,
+            relative_addresses: config.relative_addresses,
+            strict_addresses: false,
+        })
+    }
+
+    /// Flag imported functions matching a local signature list as library code
+    pub fn with_library_signatures(mut self, signatures: Option<LibrarySignatures>) -> Self {
+        self.library_signatures = signatures;
+        self
+    }
+
+    /// Resolve API hash constants in functions through an analyst-provided mapping
+    pub fn with_api_hashes(mut self, api_hashes: Option<ApiHashes>) -> Self {
+        self.api_hashes = api_hashes;
+        self
+    }
+
+    /// On re-import, delete functions, strings and calls missing from the new data
+    pub fn with_pruning(mut self, prune: bool) -> Self {
+        self.prune = prune;
+        self
+    }
+
+    /// Abort imports once more errors than the policy allows occurred, counted across
+    /// all imports of this importer and its clones
+    pub fn with_error_policy(mut self, policy: Option<ErrorPolicy>) -> Self {
+        self.error_policy = policy;
+        self
+    }
 
-        self.import_from_json(data).await
+    /// Refuse files with unparseable addresses instead of skipping the items holding them
+    pub fn with_strict_addresses(mut self, strict: bool) -> Self {
+        self.strict_addresses = strict;
+        self
+    }
+
+    pub fn error_policy(&self) -> Option<&ErrorPolicy> {
+        self.error_policy.as_ref()
+    }
+
+    /// Rows written per statement, overriding `batch_size` from the config
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.importer = self.importer.with_batch_size(batch_size);
+        self
+    }
+
+    /// Size batched writes from measured latency, see [`BatchTuner`]
+    pub fn with_tuner(mut self, tuner: Option<BatchTuner>) -> Self {
+        self.importer = self.importer.with_tuner(tuner);
+        self
     }
 
     pub async fn import_from_json(&self, data: Value) -> Result<ImportResult> {
-        let session = ImportSession::new(self.importer.clone());
-        session.import_data(data).await
+        let session = ImportSession::new(self.importer.clone())
+            .with_library_signatures(self.library_signatures.clone())
+            .with_api_hashes(self.api_hashes.clone())
+            .with_pruning(self.prune)
+            .with_string_storage(self.strings.clone())
+            .with_error_policy(self.error_policy.clone())
+            .with_relative_addresses(self.relative_addresses)
+            .with_strict_addresses(self.strict_addresses);
+        let started = Instant::now();
+        match session.import_data(data).await {
+            Ok(result) => {
+                metrics::global().record_import(
+                    started.elapsed(),
+                    result.statistics.total_nodes.max(0) as u64,
+                    result.statistics.functions.max(0) as u64,
+                );
+                Ok(result)
+            }
+            Err(e) => {
+                metrics::global().record_failed_import(started.elapsed());
+                if e.chain().any(|cause| cause.is::<neo4rs::Error>()) {
+                    metrics::global().record_neo4j_error();
+                }
+                Err(e)
+            }
+        }
     }
 
-    pub async fn validate_data(&self, data: &Value) -> Result<ValidationResult> {
+    pub fn validate_data(data: &Value) -> Result<ValidationResult> {
         let mut errors = Vec::new();
         let warnings = Vec::new();
 
@@ -64,7 +170,15 @@ impl DataImporter {
             }
         }
 
-        let array_fields = ["strings", "imports", "exports"];
+        let array_fields = [
+            "strings",
+            "imports",
+            "exports",
+            "resources",
+            "data_refs",
+            "indirect_targets",
+            "vtables",
+        ];
         for field in &array_fields {
             if let Some(value) = data.get(field) {
                 if !value.is_array() {
@@ -80,20 +194,6 @@ impl DataImporter {
         })
     }
 
-    pub async fn get_import_statistics(&self) -> Result<ImportStatistics> {
-        let stats = self.importer.get_statistics_async().await?;
-
-        Ok(ImportStatistics {
-            binaries: stats.binaries as i64,
-            functions: stats.functions as i64,
-            strings: stats.strings as i64,
-            libraries: stats.libraries as i64,
-            calls_relationships: stats.calls_relationships as i64,
-            total_nodes: (stats.binaries + stats.functions + stats.strings + stats.libraries)
-                as i64,
-        })
-    }
-
     pub async fn export_to_json<P: AsRef<Path>>(&self, file_path: P) -> Result<()> {
         let query = "MATCH (n) OPTIONAL MATCH (n)-[r]->(m) RETURN n, type(r) as rel_type, m";
         let results = self.connection.execute_query(query, None).await?;
@@ -104,6 +204,225 @@ impl DataImporter {
         Ok(())
     }
 
+    /// Same records as [`DataImporter::export_to_json`], one JSON object per line,
+    /// each written as its row arrives
+    pub async fn export_to_jsonl<P: AsRef<Path>>(&self, file_path: P) -> Result<()> {
+        let query = "MATCH (n) OPTIONAL MATCH (n)-[r]->(m) RETURN n, type(r) as rel_type, m";
+
+        let mut writer = BufWriter::new(File::create(file_path)?);
+        self.connection
+            .for_each_row(Cypher::new(query), |row| write_jsonl_row(&mut writer, &row))
+            .await?;
+        writer.flush()?;
+
+        Ok(())
+    }
+
+    /// Write one Parquet file per node label and relationship type to `dir`: nodes
+    /// with `_id` and `_labels` columns, relationships with `_id`, `_start` and `_end`,
+    /// followed by one column per property. Returns each file with its row count.
+    #[cfg(feature = "parquet")]
+    pub async fn export_to_parquet<P: AsRef<Path>>(
+        &self,
+        dir: P,
+    ) -> Result<Vec<(std::path::PathBuf, usize)>> {
+        use crate::output::parquet::{bolt_value, ColumnValue, ColumnarTable};
+        use crate::output::AtomicFile;
+        use neo4rs::{BoltMap, Row};
+        use std::collections::BTreeMap;
+
+        fn properties(row: &Row) -> Result<BTreeMap<String, ColumnValue>> {
+            let props: BoltMap = row.get("props")?;
+            Ok(props
+                .value
+                .iter()
+                .map(|(key, value)| (key.value.clone(), bolt_value(value)))
+                .collect())
+        }
+
+        fn file_name(prefix: &str, name: &str) -> String {
+            let name: String = name
+                .chars()
+                .map(|c| {
+                    if c.is_ascii_alphanumeric() || c == '_' {
+                        c
+                    } else {
+                        '_'
+                    }
+                })
+                .collect();
+            format!("{}_{}.parquet", prefix, name)
+        }
+
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+
+        let labels: Vec<String> = self
+            .connection
+            .read(Cypher::new(
+                "CALL db.labels() YIELD label RETURN label ORDER BY label",
+            ))
+            .await?
+            .iter()
+            .map(|row| row.get("label"))
+            .collect::<std::result::Result<_, _>>()?;
+        let types: Vec<String> = self
+            .connection
+            .read(Cypher::new(
+                "CALL db.relationshipTypes() YIELD relationshipType \
+                 RETURN relationshipType ORDER BY relationshipType",
+            ))
+            .await?
+            .iter()
+            .map(|row| row.get("relationshipType"))
+            .collect::<std::result::Result<_, _>>()?;
+
+        let mut tables = Vec::new();
+        for label in &labels {
+            let rows = self
+                .connection
+                .read(Cypher::new(format!(
+                    "MATCH (n:`{}`) RETURN elementId(n) AS id, labels(n) AS labels, \
+                     properties(n) AS props",
+                    label.replace('`', "``")
+                )))
+                .await?;
+            let records = rows
+                .iter()
+                .map(|row| {
+                    let mut record = properties(row)?;
+                    let labels: Vec<String> = row.get("labels")?;
+                    record.insert("_id".into(), ColumnValue::Text(row.get("id")?));
+                    record.insert("_labels".into(), ColumnValue::Text(labels.join(":")));
+                    Ok(record)
+                })
+                .collect::<Result<Vec<_>>>()?;
+            tables.push((
+                file_name("nodes", label),
+                ColumnarTable::from_records(&["_id", "_labels"], records),
+            ));
+        }
+        for rel_type in &types {
+            let rows = self
+                .connection
+                .read(Cypher::new(format!(
+                    "MATCH (a)-[r:`{}`]->(b) RETURN elementId(r) AS id, \
+                     elementId(a) AS start, elementId(b) AS end, properties(r) AS props",
+                    rel_type.replace('`', "``")
+                )))
+                .await?;
+            let records = rows
+                .iter()
+                .map(|row| {
+                    let mut record = properties(row)?;
+                    record.insert("_id".into(), ColumnValue::Text(row.get("id")?));
+                    record.insert("_start".into(), ColumnValue::Text(row.get("start")?));
+                    record.insert("_end".into(), ColumnValue::Text(row.get("end")?));
+                    Ok(record)
+                })
+                .collect::<Result<Vec<_>>>()?;
+            tables.push((
+                file_name("relationships", rel_type),
+                ColumnarTable::from_records(&["_id", "_start", "_end"], records),
+            ));
+        }
+
+        let mut written = Vec::new();
+        for (name, table) in tables {
+            if table.is_empty() {
+                continue;
+            }
+            let path = dir.join(name);
+            let mut file = AtomicFile::create(&path.to_string_lossy(), true)?;
+            table.write(&mut file)?;
+            file.commit()?;
+            written.push((path, table.len()));
+        }
+
+        Ok(written)
+    }
+
+    /// Write the analyst annotations of the graph to a JSON file
+    pub async fn export_annotations<P: AsRef<Path>>(&self, file_path: P) -> Result<Annotations> {
+        let annotations = self.session().query_annotations().await?;
+
+        let mut writer = BufWriter::new(File::create(file_path)?);
+        serde_json::to_writer_pretty(&mut writer, &annotations)?;
+        writer.flush()?;
+
+        Ok(annotations)
+    }
+
+    pub async fn import_annotations_from_file<P: AsRef<Path>>(
+        &self,
+        file_path: P,
+    ) -> Result<AnnotationImportResult> {
+        let file = File::open(file_path)?;
+        let reader = BufReader::new(file);
+        let annotations: Annotations = serde_json::from_reader(reader)?;
+
+        self.session().import_annotations(&annotations).await
+    }
+
+    pub async fn import_relations_from_file<P: AsRef<Path>>(
+        &self,
+        file_path: P,
+    ) -> Result<RelationImportResult> {
+        let file = File::open(file_path)?;
+        let reader = BufReader::new(file);
+        let data: Value = serde_json::from_reader(reader)?;
+
+        self.session().import_relations(data).await
+    }
+
+    pub async fn import_trace_from_file<P: AsRef<Path>>(
+        &self,
+        file_path: P,
+        binary_hash: Option<&str>,
+    ) -> Result<TraceImportResult> {
+        let file = File::open(file_path)?;
+        let reader = BufReader::new(file);
+        let data: Value = serde_json::from_reader(reader)?;
+
+        self.session().import_trace(data, binary_hash).await
+    }
+
+    pub async fn import_network_from_file<P: AsRef<Path>>(
+        &self,
+        file_path: P,
+        binary_hash: Option<&str>,
+    ) -> Result<NetworkImportResult> {
+        let file = File::open(file_path)?;
+        let reader = BufReader::new(file);
+        let data: Value = serde_json::from_reader(reader)?;
+
+        self.session().import_network(data, binary_hash).await
+    }
+
+    pub async fn import_objects_from_file<P: AsRef<Path>>(
+        &self,
+        file_path: P,
+        binary_hash: Option<&str>,
+    ) -> Result<ObjectImportResult> {
+        let file = File::open(file_path)?;
+        let reader = BufReader::new(file);
+        let data: Value = serde_json::from_reader(reader)?;
+
+        self.session().import_objects(data, binary_hash).await
+    }
+
+    pub async fn import_symbols_from_file<P: AsRef<Path>>(
+        &self,
+        file_path: P,
+        binary: &str,
+        image_base: u64,
+    ) -> Result<SymbolImportResult> {
+        let text = std::fs::read_to_string(file_path)?;
+        self.session()
+            .import_symbols(&text, binary, image_base)
+            .await
+    }
+
     pub fn session(&self) -> ImportSession {
         ImportSession::new(self.importer.clone())
     }
@@ -111,23 +430,140 @@ impl DataImporter {
     pub async fn get_database_stats(&self) -> Result<crate::neo4j::DatabaseStats> {
         self.connection.get_database_stats().await
     }
+
+    /// Report timing or plans of the read queries run from now on
+    pub fn trace_queries(&self, tracing: QueryTracing) {
+        self.connection.trace_queries(tracing);
+    }
+
+    pub fn query_timings(&self) -> Vec<QueryTiming> {
+        self.connection.query_timings()
+    }
+}
+
+/// Write the export record of `row`, if any, as one line of JSON Lines
+fn write_jsonl_row<W: Write>(writer: &mut W, row: &neo4rs::Row) -> Result<()> {
+    if let Some(record) = export_record(row) {
+        serde_json::to_writer(&mut *writer, &record)?;
+        writeln!(writer)?;
+    }
+    Ok(())
 }
 
 #[derive(Debug, Clone)]
 pub struct ImportResult {
     pub success: bool,
+    /// SHA-256 of the imported binary, unless its `binary_info` was missing or invalid
+    pub binary_hash: Option<String>,
     pub statistics: ImportStatistics,
     pub errors: Vec<String>,
+    /// Address fields that could not be parsed, as `<JSON pointer>: '<value>' (<reason>)`
+    pub unparseable_addresses: Vec<String>,
+    /// Changes against an earlier import of the same binary
+    pub merge: Option<MergeSummary>,
+}
+
+/// Difference between a re-imported binary and what the graph held before
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MergeSummary {
+    pub functions_added: usize,
+    pub functions_removed: usize,
+    pub strings_added: usize,
+    pub strings_removed: usize,
+    pub calls_added: usize,
+    pub calls_removed: usize,
+    /// Whether the removed elements were deleted or left in place
+    pub pruned: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct RelationImportResult {
+    pub success: bool,
+    pub relations: i64,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TraceImportResult {
+    pub binary_hash: String,
+    /// Distinct APIs in the trace
+    pub apis: i64,
+    pub relationships: i64,
+    /// APIs without a matching import function
+    pub unresolved: Vec<String>,
 }
 
 #[derive(Debug, Clone)]
+pub struct NetworkImportResult {
+    pub success: bool,
+    pub binary_hash: String,
+    /// Contacted domains, IP addresses and URLs
+    pub indicators: i64,
+    /// Domain to IP address resolutions
+    pub resolutions: i64,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ObjectImportResult {
+    pub success: bool,
+    pub binary_hash: String,
+    /// Distinct mutexes, pipes, events and semaphores in the report
+    pub objects: i64,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AnnotationImportResult {
+    /// Function entries in the file
+    pub functions: usize,
+    /// Functions found in the graph and updated
+    pub functions_applied: usize,
+    pub binaries: usize,
+    pub binaries_applied: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct SymbolImportResult {
+    pub binary_hash: String,
+    /// Entries read from the symbol map
+    pub symbols: i64,
+    /// Functions whose name was updated
+    pub renamed: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ImportStatistics {
     pub binaries: i64,
     pub functions: i64,
     pub strings: i64,
     pub libraries: i64,
+    pub resources: i64,
     pub calls_relationships: i64,
+    pub data_references: i64,
+    pub vtables: i64,
     pub total_nodes: i64,
+    /// Calls dropped because an end matched no imported function
+    pub skipped_calls: i64,
+    /// String occurrences that repeated an already imported value
+    pub duplicate_strings: i64,
+}
+
+impl ImportStatistics {
+    /// Add the counts of another import, e.g. for a directory total
+    pub fn add(&mut self, other: &ImportStatistics) {
+        self.binaries += other.binaries;
+        self.functions += other.functions;
+        self.strings += other.strings;
+        self.libraries += other.libraries;
+        self.resources += other.resources;
+        self.calls_relationships += other.calls_relationships;
+        self.data_references += other.data_references;
+        self.vtables += other.vtables;
+        self.total_nodes += other.total_nodes;
+        self.skipped_calls += other.skipped_calls;
+        self.duplicate_strings += other.duplicate_strings;
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -136,3 +572,56 @@ pub struct ValidationResult {
     pub errors: Vec<String>,
     pub warnings: Vec<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neo4rs::{BoltList, BoltMap, BoltNode, BoltType, Row};
+
+    fn node(label: &str, uid: &str) -> BoltType {
+        let mut properties = BoltMap::default();
+        properties.put("uid".into(), uid.into());
+        BoltType::Node(BoltNode::new(
+            0.into(),
+            vec![BoltType::from(label)].into(),
+            properties,
+        ))
+    }
+
+    fn row(values: Vec<BoltType>) -> Row {
+        let fields: BoltList = vec!["n".into(), "rel_type".into(), "m".into()].into();
+        Row::new(fields, values.into())
+    }
+
+    #[test]
+    fn test_write_jsonl_rows() {
+        let rows = [
+            row(vec![
+                node("Binary", "bin:h"),
+                "CONTAINS".into(),
+                node("Function", "func:h:0x1000"),
+            ]),
+            row(vec![
+                node("Function", "func:h:0x1000"),
+                BoltType::Null(Default::default()),
+                BoltType::Null(Default::default()),
+            ]),
+        ];
+
+        let mut out = Vec::new();
+        for row in &rows {
+            write_jsonl_row(&mut out, row).unwrap();
+        }
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<Value> = text
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["node"]["uid"], "bin:h");
+        assert_eq!(lines[0]["relationship_type"], "CONTAINS");
+        assert_eq!(lines[0]["target"]["labels"][0], "Function");
+        assert_eq!(lines[1]["node"]["labels"][0], "Function");
+        assert!(lines[1].get("target").is_none());
+    }
+}
@@ -0,0 +1,563 @@
+//! Direct PE/ELF/Mach-O parsing for `import binary`
+//!
+//! Produces a native BinaryX document from the executable itself: headers,
+//! sections, imports, exports, the entry point, symbol-table functions and
+//! printable strings. There is no disassembly, so the document carries no
+//! calls or data references.
+
+use anyhow::Result;
+use goblin::elf::program_header::PT_LOAD;
+use goblin::elf::section_header::SHT_NOBITS;
+use goblin::elf::sym::{STB_GLOBAL, STB_WEAK, STT_FUNC, STT_OBJECT};
+use goblin::mach::load_command::{cmd_to_str, CommandVariant};
+use goblin::mach::{cputype, Mach, MachO};
+use goblin::Object;
+use md5::Md5;
+use serde_json::{json, Value};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use crate::utils::uid::format_address;
+
+/// Mapping of a file range to its virtual address: `(file_offset, size, address)`
+type Mapping = (u64, u64, u64);
+
+/// Parse the executable at `path` into a native BinaryX document
+pub fn parse_executable<P: AsRef<Path>>(path: P, min_string_length: usize) -> Result<Value> {
+    let path = path.as_ref();
+    let bytes = std::fs::read(path)?;
+
+    // Strings and file offsets refer to the first slice of a universal binary
+    let mut image: &[u8] = &bytes;
+    let mut parsed = match Object::parse(&bytes)? {
+        Object::PE(pe) => parse_pe(&pe, &bytes),
+        Object::Elf(elf) => parse_elf(&elf, &bytes),
+        Object::Mach(Mach::Binary(macho)) => parse_macho(&macho, &bytes),
+        Object::Mach(Mach::Fat(fat)) => {
+            let arch = fat
+                .iter_arches()
+                .next()
+                .ok_or_else(|| anyhow::anyhow!("Universal binary has no architectures"))??;
+            image = arch.slice(&bytes);
+            if fat.narches > 1 {
+                eprintln!(
+                    "[WARN] Universal binary with {} architectures, importing the first one",
+                    fat.narches
+                );
+            }
+            parse_macho(&MachO::parse(image, 0)?, image)
+        }
+        _ => return Err(anyhow::anyhow!("Not a PE, ELF or Mach-O executable")),
+    };
+
+    let strings: Vec<Value> = extract_strings(image, min_string_length)
+        .into_iter()
+        .map(|(offset, value, kind)| {
+            let address = parsed
+                .mappings
+                .iter()
+                .find(|(start, size, _)| (*start..start + size).contains(&offset))
+                .map(|(start, _, address)| format_address(address + offset - start));
+            json!({
+                "value": value,
+                "address": address,
+                "length": value.chars().count(),
+                "type": kind,
+            })
+        })
+        .collect();
+
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    parsed.binary_info.insert("name".into(), json!(name));
+    parsed
+        .binary_info
+        .insert("file_path".into(), json!(path.display().to_string()));
+    parsed
+        .binary_info
+        .insert("file_size".into(), json!(bytes.len()));
+    parsed.binary_info.insert(
+        "hashes".into(),
+        json!({
+            "sha256": hex_digest(Sha256::digest(&bytes).as_slice()),
+            "md5": hex_digest(Md5::digest(&bytes).as_slice()),
+            "sha1": hex_digest(Sha1::digest(&bytes).as_slice()),
+        }),
+    );
+
+    let functions: Vec<Value> = parsed
+        .functions
+        .into_iter()
+        .map(|(address, (name, size))| {
+            json!({"name": name, "address": format_address(address), "size": size})
+        })
+        .collect();
+
+    Ok(json!({
+        "binary_info": parsed.binary_info,
+        "functions": functions,
+        "imports": parsed.imports,
+        "exports": parsed.exports,
+        "strings": strings,
+    }))
+}
+
+/// Format-specific part of the document
+#[derive(Default)]
+struct Parsed {
+    binary_info: serde_json::Map<String, Value>,
+    /// Address to name and size
+    functions: BTreeMap<u64, (String, Option<u64>)>,
+    imports: Vec<Value>,
+    exports: Vec<Value>,
+    mappings: Vec<Mapping>,
+}
+
+impl Parsed {
+    fn file_type(&mut self, format: &str, architecture: &str) {
+        self.binary_info.insert(
+            "file_type".into(),
+            json!({"type": format, "architecture": architecture}),
+        );
+    }
+
+    fn entry_point(&mut self, address: u64) {
+        if address != 0 {
+            self.functions
+                .entry(address)
+                .or_insert_with(|| ("start".to_string(), None));
+        }
+    }
+
+    fn import(&mut self, name: &str, address: u64, library: &str) {
+        self.imports.push(json!({
+            "name": name,
+            "address": format_address(address),
+            "library": library,
+        }));
+    }
+
+    fn export(&mut self, name: &str, address: u64) {
+        self.exports
+            .push(json!({"name": name, "address": format_address(address)}));
+    }
+}
+
+fn parse_pe(pe: &goblin::pe::PE, bytes: &[u8]) -> Parsed {
+    use goblin::pe::header::{
+        COFF_MACHINE_ARM, COFF_MACHINE_ARM64, COFF_MACHINE_ARMNT, COFF_MACHINE_X86,
+        COFF_MACHINE_X86_64,
+    };
+
+    let mut parsed = Parsed::default();
+    let architecture = match pe.header.coff_header.machine {
+        COFF_MACHINE_X86_64 => "x86_64",
+        COFF_MACHINE_X86 => "x86",
+        COFF_MACHINE_ARM64 => "arm64",
+        COFF_MACHINE_ARM | COFF_MACHINE_ARMNT => "arm",
+        _ => "unknown",
+    };
+    parsed.file_type(if pe.is_64 { "PE64" } else { "PE32" }, architecture);
+    parsed
+        .binary_info
+        .insert("image_base".into(), json!(format_address(pe.image_base)));
+
+    let entry = (pe.entry != 0).then(|| pe.image_base + u64::from(pe.entry));
+    if let Some(entry) = entry {
+        parsed.entry_point(entry);
+        parsed.binary_info.insert(
+            "pe_info".into(),
+            json!({"entry_point": format_address(entry)}),
+        );
+    }
+
+    let mut sections = Vec::new();
+    for section in &pe.sections {
+        let offset = u64::from(section.pointer_to_raw_data);
+        let size = u64::from(section.size_of_raw_data);
+        parsed.mappings.push((
+            offset,
+            size,
+            pe.image_base + u64::from(section.virtual_address),
+        ));
+        sections.push(section_entry(
+            section.name().unwrap_or(""),
+            file_range(bytes, offset, size),
+        ));
+    }
+    parsed
+        .binary_info
+        .insert("sections".into(), json!(sections));
+
+    for import in &pe.imports {
+        let name = if import.name.starts_with("ORDINAL ") {
+            format!("ord_{}", import.ordinal)
+        } else {
+            import.name.to_string()
+        };
+        parsed.import(&name, pe.image_base + import.offset as u64, import.dll);
+    }
+
+    // Forwarded exports live in another DLL
+    for export in pe.exports.iter().filter(|e| e.reexport.is_none()) {
+        if let Some(name) = export.name {
+            parsed.export(name, pe.image_base + export.rva as u64);
+        }
+    }
+
+    parsed
+}
+
+fn parse_elf(elf: &goblin::elf::Elf, bytes: &[u8]) -> Parsed {
+    use goblin::elf::header::{
+        EM_386, EM_AARCH64, EM_ARM, EM_MIPS, EM_PPC, EM_PPC64, EM_RISCV, EM_X86_64,
+    };
+
+    let mut parsed = Parsed::default();
+    let architecture = match elf.header.e_machine {
+        EM_X86_64 => "x86_64",
+        EM_386 => "x86",
+        EM_AARCH64 => "arm64",
+        EM_ARM => "arm",
+        EM_MIPS if elf.is_64 => "mips64",
+        EM_MIPS => "mips",
+        EM_PPC => "ppc",
+        EM_PPC64 => "ppc64",
+        EM_RISCV if elf.is_64 => "riscv64",
+        EM_RISCV => "riscv32",
+        _ => "unknown",
+    };
+    parsed.file_type(if elf.is_64 { "ELF64" } else { "ELF32" }, architecture);
+
+    parsed.mappings = elf
+        .program_headers
+        .iter()
+        .filter(|ph| ph.p_type == PT_LOAD)
+        .map(|ph| (ph.p_offset, ph.p_filesz, ph.p_vaddr))
+        .collect();
+
+    let sections: Vec<Value> = elf
+        .section_headers
+        .iter()
+        .filter(|sh| sh.sh_type != SHT_NOBITS && sh.sh_size > 0)
+        .filter_map(|sh| {
+            let name = elf.shdr_strtab.get_at(sh.sh_name)?;
+            Some(section_entry(
+                name,
+                file_range(bytes, sh.sh_offset, sh.sh_size),
+            ))
+        })
+        .collect();
+    parsed
+        .binary_info
+        .insert("sections".into(), json!(sections));
+
+    for (symbols, strtab) in [(&elf.syms, &elf.strtab), (&elf.dynsyms, &elf.dynstrtab)] {
+        for sym in symbols.iter() {
+            if sym.st_type() != STT_FUNC || sym.is_import() || sym.st_value == 0 {
+                continue;
+            }
+            if let Some(name) = strtab.get_at(sym.st_name).filter(|n| !n.is_empty()) {
+                parsed
+                    .functions
+                    .entry(sym.st_value)
+                    .or_insert_with(|| (name.to_string(), Some(sym.st_size)));
+            }
+        }
+    }
+    parsed.entry_point(elf.entry);
+
+    // Undefined symbols are reached through their GOT slot. The defining library
+    // comes from the symbol's version requirement, or is the only one needed.
+    let default_library = match elf.libraries.as_slice() {
+        [library] => *library,
+        _ => "unknown",
+    };
+    let mut version_files = BTreeMap::new();
+    let mut symbol_versions = BTreeSet::new();
+    if let Some(verneed) = &elf.verneed {
+        for need in verneed.iter() {
+            if let Some(file) = elf.dynstrtab.get_at(need.vn_file) {
+                for aux in need.iter() {
+                    version_files.insert(aux.vna_other, file);
+                    if let Some(version) = elf.dynstrtab.get_at(aux.vna_name) {
+                        symbol_versions.insert(format!("{}@{}", file, version));
+                    }
+                }
+            }
+        }
+    }
+    parsed.binary_info.insert(
+        "elf_info".into(),
+        json!({
+            "interpreter": elf.interpreter,
+            "soname": elf.soname,
+            "needed": elf.libraries,
+            "symbol_versions": symbol_versions,
+        }),
+    );
+    let library_of = |index: usize| {
+        elf.versym
+            .as_ref()
+            .and_then(|versym| versym.get_at(index))
+            .and_then(|versym| version_files.get(&versym.version()).copied())
+            .unwrap_or(default_library)
+    };
+    let mut slots = BTreeMap::new();
+    for reloc in elf
+        .pltrelocs
+        .iter()
+        .chain(elf.dynrelas.iter())
+        .chain(elf.dynrels.iter())
+    {
+        slots.entry(reloc.r_sym).or_insert(reloc.r_offset);
+    }
+    for (index, sym) in elf.dynsyms.iter().enumerate() {
+        let Some(name) = elf.dynstrtab.get_at(sym.st_name).filter(|n| !n.is_empty()) else {
+            continue;
+        };
+        if sym.is_import() {
+            if let Some(slot) = slots.get(&index) {
+                parsed.import(name, *slot, library_of(index));
+            }
+        } else if sym.st_value != 0
+            && matches!(sym.st_bind(), STB_GLOBAL | STB_WEAK)
+            && matches!(sym.st_type(), STT_FUNC | STT_OBJECT)
+        {
+            parsed.export(name, sym.st_value);
+        }
+    }
+
+    parsed
+}
+
+fn parse_macho(macho: &MachO, bytes: &[u8]) -> Parsed {
+    let mut parsed = Parsed::default();
+    let architecture = match macho.header.cputype {
+        cputype::CPU_TYPE_X86_64 => "x86_64",
+        cputype::CPU_TYPE_X86 => "x86",
+        cputype::CPU_TYPE_ARM64 => "arm64",
+        cputype::CPU_TYPE_ARM => "arm",
+        _ => "unknown",
+    };
+    parsed.file_type("Mach-O", architecture);
+
+    let mut sections = Vec::new();
+    let mut text_base = 0;
+    for segment in macho.segments.iter() {
+        if segment.name().ok() == Some("__TEXT") {
+            text_base = segment.vmaddr;
+        }
+        parsed
+            .mappings
+            .push((segment.fileoff, segment.filesize, segment.vmaddr));
+        for (section, data) in segment.sections().unwrap_or_default() {
+            sections.push(section_entry(section.name().unwrap_or(""), data));
+        }
+    }
+    parsed
+        .binary_info
+        .insert("sections".into(), json!(sections));
+    parsed.entry_point(macho.entry);
+
+    let mut team_id = None;
+    for command in &macho.load_commands {
+        if let CommandVariant::CodeSignature(signature) = &command.command {
+            let blob = file_range(
+                bytes,
+                u64::from(signature.dataoff),
+                u64::from(signature.datasize),
+            );
+            team_id = code_signature_team_id(blob);
+        }
+    }
+    let load_commands: Vec<&str> = macho
+        .load_commands
+        .iter()
+        .map(|command| cmd_to_str(command.command.cmd()))
+        .collect();
+    parsed.binary_info.insert(
+        "macho_info".into(),
+        json!({
+            "load_commands": load_commands,
+            "team_id": team_id,
+            "rpaths": macho.rpaths,
+        }),
+    );
+
+    if let Ok(imports) = macho.imports() {
+        for import in imports {
+            parsed.import(import.name, import.address, import.dylib);
+        }
+    }
+    // Export offsets are relative to the image base (the __TEXT segment)
+    if let Ok(exports) = macho.exports() {
+        for export in exports {
+            parsed.export(&export.name, text_base + export.offset);
+        }
+    }
+
+    parsed
+}
+
+/// Team identifier of the CodeDirectory in an embedded signature SuperBlob.
+/// All blob fields are big-endian.
+fn code_signature_team_id(blob: &[u8]) -> Option<String> {
+    const SUPERBLOB_MAGIC: u32 = 0xfade_0cc0;
+    const CODE_DIRECTORY_MAGIC: u32 = 0xfade_0c02;
+    /// First CodeDirectory version with a team offset
+    const SUPPORTS_TEAM_ID: u32 = 0x20200;
+
+    let read_u32 = |data: &[u8], offset: usize| -> Option<u32> {
+        let bytes = data.get(offset..offset.checked_add(4)?)?;
+        Some(u32::from_be_bytes(bytes.try_into().ok()?))
+    };
+
+    if read_u32(blob, 0)? != SUPERBLOB_MAGIC {
+        return None;
+    }
+    let count = read_u32(blob, 8)? as usize;
+    (0..count).find_map(|index| {
+        let offset = read_u32(blob, 12 + index * 8 + 4)? as usize;
+        let directory = blob.get(offset..)?;
+        if read_u32(directory, 0)? != CODE_DIRECTORY_MAGIC
+            || read_u32(directory, 8)? < SUPPORTS_TEAM_ID
+        {
+            return None;
+        }
+        let team_offset = read_u32(directory, 0x30)? as usize;
+        if team_offset == 0 {
+            return None;
+        }
+        let team = directory.get(team_offset..)?;
+        let end = team.iter().position(|b| *b == 0)?;
+        String::from_utf8(team[..end].to_vec()).ok()
+    })
+}
+
+fn file_range(bytes: &[u8], offset: u64, size: u64) -> &[u8] {
+    let start = (offset as usize).min(bytes.len());
+    let end = start.saturating_add(size as usize).min(bytes.len());
+    &bytes[start..end]
+}
+
+fn section_entry(name: &str, data: &[u8]) -> Value {
+    json!({"name": name, "size": data.len(), "entropy": entropy(data)})
+}
+
+/// Shannon entropy in bits per byte
+fn entropy(data: &[u8]) -> f64 {
+    if data.is_empty() {
+        return 0.0;
+    }
+    let mut counts = [0usize; 256];
+    for byte in data {
+        counts[*byte as usize] += 1;
+    }
+    let len = data.len() as f64;
+    counts
+        .iter()
+        .filter(|count| **count > 0)
+        .map(|count| {
+            let p = *count as f64 / len;
+            -p * p.log2()
+        })
+        .sum::<f64>()
+        .abs()
+}
+
+fn is_printable(byte: u8) -> bool {
+    byte == b'\t' || (0x20..0x7f).contains(&byte)
+}
+
+/// Printable ASCII and UTF-16LE runs of at least `min_length` characters,
+/// as `(file_offset, value, type)`
+fn extract_strings(bytes: &[u8], min_length: usize) -> Vec<(u64, String, &'static str)> {
+    let min_length = min_length.max(1);
+    let mut strings = Vec::new();
+
+    let mut start = 0;
+    for (index, byte) in bytes.iter().chain(std::iter::once(&0)).enumerate() {
+        if !is_printable(*byte) {
+            if index - start >= min_length {
+                let value = String::from_utf8_lossy(&bytes[start..index]).into_owned();
+                strings.push((start as u64, value, "ascii"));
+            }
+            start = index + 1;
+        }
+    }
+
+    for alignment in 0..2 {
+        let mut run = String::new();
+        let mut run_start = alignment;
+        let units = bytes[alignment..].chunks(2);
+        for (index, unit) in units.chain(std::iter::once(&[0u8, 1][..])).enumerate() {
+            let offset = alignment + index * 2;
+            if unit.len() == 2 && unit[1] == 0 && is_printable(unit[0]) {
+                if run.is_empty() {
+                    run_start = offset;
+                }
+                run.push(unit[0] as char);
+            } else {
+                if run.len() >= min_length {
+                    strings.push((run_start as u64, std::mem::take(&mut run), "utf16"));
+                }
+                run.clear();
+            }
+        }
+    }
+
+    strings.sort_by_key(|(offset, ..)| *offset);
+    strings
+}
+
+fn hex_digest(digest: &[u8]) -> String {
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_strings() {
+        let bytes = b"\x00\x01cmd.exe\x00\x01\x02h\x00t\x00t\x00p\x00:\x00\x00\x00";
+        let strings = extract_strings(bytes, 4);
+
+        assert_eq!(strings[0], (2, "cmd.exe".to_string(), "ascii"));
+        assert_eq!(strings[1], (12, "http:".to_string(), "utf16"));
+        assert_eq!(strings.len(), 2);
+    }
+
+    #[test]
+    fn test_code_signature_team_id() {
+        let mut directory = vec![0u8; 0x58];
+        directory[0..4].copy_from_slice(&0xfade_0c02u32.to_be_bytes());
+        directory[8..12].copy_from_slice(&0x20400u32.to_be_bytes());
+        directory[0x30..0x34].copy_from_slice(&0x58u32.to_be_bytes());
+        directory.extend_from_slice(b"ABCDE12345\0");
+
+        let mut blob = Vec::new();
+        blob.extend_from_slice(&0xfade_0cc0u32.to_be_bytes());
+        blob.extend_from_slice(&0u32.to_be_bytes());
+        blob.extend_from_slice(&1u32.to_be_bytes());
+        blob.extend_from_slice(&0u32.to_be_bytes());
+        blob.extend_from_slice(&20u32.to_be_bytes());
+        blob.extend_from_slice(&directory);
+
+        assert_eq!(code_signature_team_id(&blob).as_deref(), Some("ABCDE12345"));
+        assert_eq!(code_signature_team_id(&blob[..12]), None);
+    }
+
+    #[test]
+    fn test_entropy() {
+        assert_eq!(entropy(&[]), 0.0);
+        assert_eq!(entropy(&[7; 64]), 0.0);
+        let uniform: Vec<u8> = (0..=255).collect();
+        assert!((entropy(&uniform) - 8.0).abs() < 1e-9);
+    }
+}
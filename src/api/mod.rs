@@ -1,5 +1,15 @@
+pub mod adapters;
+pub mod addresses;
+pub mod api_hashes;
 pub mod client;
+pub mod executable;
+pub mod network;
+pub mod objects;
 pub mod session;
+pub mod signatures;
+pub mod snapshot;
+pub mod symbols;
+pub mod trace;
 
 pub use client::*;
 pub use session::*;
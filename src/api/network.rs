@@ -0,0 +1,188 @@
+use anyhow::Result;
+use serde_json::Value;
+use std::collections::HashSet;
+
+use crate::models::{IndicatorKind, NetworkIndicator};
+use crate::utils::ioc::{classify_host, extract_indicators};
+
+/// Network activity of one sample extracted from a sandbox report
+#[derive(Debug, Clone, Default)]
+pub struct NetworkActivity {
+    /// SHA-256 of the analyzed sample, when the report names it
+    pub sha256: Option<String>,
+    /// Contacted domains, IP addresses and URLs
+    pub indicators: Vec<NetworkIndicator>,
+    /// (domain, IP address) pairs from DNS answers
+    pub resolutions: Vec<(String, String)>,
+}
+
+impl NetworkActivity {
+    fn add(&mut self, seen: &mut HashSet<NetworkIndicator>, indicator: Option<NetworkIndicator>) {
+        if let Some(indicator) = indicator {
+            if seen.insert(indicator.clone()) {
+                self.indicators.push(indicator);
+            }
+        }
+    }
+
+    fn resolve(&mut self, domain: &str, ip: &str) {
+        let (Some(domain), Some(ip)) = (classify_host(domain), classify_host(ip)) else {
+            return;
+        };
+        if domain.kind == IndicatorKind::Domain && ip.kind == IndicatorKind::IpAddress {
+            let pair = (domain.value, ip.value);
+            if !self.resolutions.contains(&pair) {
+                self.resolutions.push(pair);
+            }
+        }
+    }
+}
+
+/// Parse the `network` section of a Cuckoo/CAPE report or a simple
+/// `{sha256, domains: [], ips: [], urls: []}` document
+///
+/// Private and loopback addresses (e.g. the sandbox gateway) are dropped.
+pub fn parse_network(report: &Value) -> Result<NetworkActivity> {
+    let mut activity = NetworkActivity {
+        sha256: report
+            .pointer("/target/file/sha256")
+            .or_else(|| report.get("sha256"))
+            .and_then(|v| v.as_str())
+            .map(|v| v.trim().to_lowercase()),
+        ..Default::default()
+    };
+    let mut seen = HashSet::new();
+
+    let Some(network) = report.get("network").or_else(|| {
+        ["domains", "ips", "urls"]
+            .iter()
+            .any(|key| report.get(key).is_some())
+            .then_some(report)
+    }) else {
+        return Err(anyhow::anyhow!(
+            "Report has no network section or domains/ips/urls arrays"
+        ));
+    };
+
+    for entry in array(network, "domains") {
+        let domain = entry
+            .get("domain")
+            .and_then(|v| v.as_str())
+            .or_else(|| entry.as_str());
+        if let Some(domain) = domain {
+            activity.add(&mut seen, classify_host(domain));
+            if let Some(ip) = entry.get("ip").and_then(|v| v.as_str()) {
+                activity.add(&mut seen, classify_host(ip));
+                activity.resolve(domain, ip);
+            }
+        }
+    }
+
+    for entry in array(network, "dns") {
+        let Some(request) = entry.get("request").and_then(|v| v.as_str()) else {
+            continue;
+        };
+        activity.add(&mut seen, classify_host(request));
+        for answer in array(entry, "answers") {
+            let is_address = answer
+                .get("type")
+                .and_then(|v| v.as_str())
+                .is_none_or(|t| t == "A" || t == "AAAA");
+            if let Some(data) = answer.get("data").and_then(|v| v.as_str()) {
+                if is_address {
+                    activity.add(&mut seen, classify_host(data));
+                    activity.resolve(request, data);
+                }
+            }
+        }
+    }
+
+    for key in ["hosts", "ips"] {
+        for entry in array(network, key) {
+            let ip = entry
+                .get("ip")
+                .and_then(|v| v.as_str())
+                .or_else(|| entry.as_str());
+            activity.add(&mut seen, ip.and_then(classify_host));
+        }
+    }
+
+    for key in ["tcp", "udp"] {
+        for entry in array(network, key) {
+            let dst = entry.get("dst").and_then(|v| v.as_str());
+            activity.add(&mut seen, dst.and_then(classify_host));
+        }
+    }
+
+    for key in ["http", "http_ex", "https_ex", "urls"] {
+        for entry in array(network, key) {
+            let url = match (entry.as_str(), entry.get("uri").and_then(|v| v.as_str())) {
+                (Some(url), _) => url.to_string(),
+                // http_ex entries carry the path in `uri` and the host separately
+                (None, Some(uri)) if uri.starts_with('/') => {
+                    let host = entry.get("host").and_then(|v| v.as_str()).unwrap_or("");
+                    let scheme = entry
+                        .get("protocol")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or(if key == "https_ex" { "https" } else { "http" });
+                    format!("{}://{}{}", scheme, host, uri)
+                }
+                (None, Some(uri)) => uri.to_string(),
+                (None, None) => continue,
+            };
+            for indicator in extract_indicators(&url) {
+                activity.add(&mut seen, Some(indicator));
+            }
+        }
+    }
+
+    Ok(activity)
+}
+
+fn array<'a>(value: &'a Value, key: &str) -> &'a [Value] {
+    value
+        .get(key)
+        .and_then(|v| v.as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_cuckoo_network_section() {
+        let report = json!({
+            "target": { "file": { "sha256": "ABC" } },
+            "network": {
+                "hosts": ["192.168.56.1", "45.33.12.7"],
+                "dns": [{ "request": "c2.example.com", "type": "A",
+                          "answers": [{ "type": "A", "data": "45.33.12.7" }] }],
+                "http_ex": [{ "host": "c2.example.com", "uri": "/gate.php", "protocol": "http" }]
+            }
+        });
+
+        let activity = parse_network(&report).unwrap();
+        let values: Vec<&str> = activity
+            .indicators
+            .iter()
+            .map(|i| i.value.as_str())
+            .collect();
+
+        assert_eq!(activity.sha256.as_deref(), Some("abc"));
+        assert_eq!(
+            values,
+            vec![
+                "c2.example.com",
+                "45.33.12.7",
+                "http://c2.example.com/gate.php"
+            ]
+        );
+        assert_eq!(
+            activity.resolutions,
+            vec![("c2.example.com".to_string(), "45.33.12.7".to_string())]
+        );
+    }
+}
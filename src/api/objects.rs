@@ -0,0 +1,121 @@
+use anyhow::Result;
+use serde_json::Value;
+use std::collections::HashSet;
+
+use crate::models::{NamedObject, ObjectKind};
+use crate::utils::ioc::{classify_named_object, named_object};
+
+/// Report keys listing object names, by kind
+const OBJECT_KEYS: [(&str, ObjectKind); 5] = [
+    ("mutexes", ObjectKind::Mutex),
+    ("mutants", ObjectKind::Mutex),
+    ("pipes", ObjectKind::Pipe),
+    ("events", ObjectKind::Event),
+    ("semaphores", ObjectKind::Semaphore),
+];
+
+/// Report keys listing file paths, which include named pipes
+const FILE_KEYS: [&str; 4] = ["files", "file_opened", "file_created", "file_written"];
+
+/// Named objects one sample used, extracted from a sandbox report
+#[derive(Debug, Clone, Default)]
+pub struct SandboxObjects {
+    /// SHA-256 of the analyzed sample, when the report names it
+    pub sha256: Option<String>,
+    pub objects: Vec<NamedObject>,
+}
+
+/// Parse the `behavior.summary` section of a Cuckoo/CAPE report or a simple
+/// `{sha256, mutexes: [], pipes: [], events: [], semaphores: []}` document
+///
+/// Pipes are also picked from the opened and created file paths.
+pub fn parse_objects(report: &Value) -> Result<SandboxObjects> {
+    let summary = report
+        .pointer("/behavior/summary")
+        .or_else(|| {
+            OBJECT_KEYS
+                .iter()
+                .any(|(key, _)| report.get(key).is_some())
+                .then_some(report)
+        })
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Report has no behavior summary or mutexes/pipes/events/semaphores arrays"
+            )
+        })?;
+
+    let mut activity = SandboxObjects {
+        sha256: report
+            .pointer("/target/file/sha256")
+            .or_else(|| report.get("sha256"))
+            .and_then(|v| v.as_str())
+            .map(|v| v.trim().to_lowercase()),
+        ..Default::default()
+    };
+    let mut seen = HashSet::new();
+    let mut add = |object: Option<NamedObject>| {
+        if let Some(object) = object {
+            if seen.insert(object.clone()) {
+                activity.objects.push(object);
+            }
+        }
+    };
+
+    for (key, kind) in OBJECT_KEYS {
+        for name in names(summary, key) {
+            add(named_object(kind, name));
+        }
+    }
+    for key in FILE_KEYS {
+        for path in names(summary, key) {
+            add(classify_named_object(path).filter(|object| object.kind == ObjectKind::Pipe));
+        }
+    }
+
+    Ok(activity)
+}
+
+/// Entries of `key` given as plain strings or as objects with a `name`
+fn names<'a>(value: &'a Value, key: &str) -> impl Iterator<Item = &'a str> {
+    value
+        .get(key)
+        .and_then(|v| v.as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|entry| {
+            entry
+                .as_str()
+                .or_else(|| entry.get("name").and_then(|v| v.as_str()))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_cuckoo_behavior_summary() {
+        let report = json!({
+            "target": { "file": { "sha256": "ABC" } },
+            "behavior": { "summary": {
+                "mutexes": ["Global\\QWERTY", "\\BaseNamedObjects\\QWERTY", "Global\\QWERTY"],
+                "files": ["C:\\Users\\x\\a.txt", "\\\\.\\PIPE\\msagent_12"],
+                "events": [{ "name": "Local\\ready" }]
+            }}
+        });
+
+        let activity = parse_objects(&report).unwrap();
+        assert_eq!(activity.sha256.as_deref(), Some("abc"));
+        assert_eq!(
+            activity.objects,
+            vec![
+                NamedObject::new(ObjectKind::Mutex, "Global\\QWERTY"),
+                NamedObject::new(ObjectKind::Mutex, "QWERTY"),
+                NamedObject::new(ObjectKind::Event, "Local\\ready"),
+                NamedObject::new(ObjectKind::Pipe, "msagent_12"),
+            ]
+        );
+    }
+}
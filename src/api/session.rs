@@ -1,260 +1,283 @@
 use anyhow::Result;
 use serde_json::Value;
-use std::collections::HashMap;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 
+use crate::api::api_hashes::{ApiHashes, HashedApi};
+use crate::api::signatures::LibrarySignatures;
+use crate::models::FunctionInfo;
 use crate::models::*;
-use crate::neo4j::{CallGraph, GraphImporter, Xref};
-use crate::utils::uid::{normalize_address, parse_address};
-
-pub struct ImportSession {
-    importer: GraphImporter,
+use crate::neo4j::{
+    BinaryFilter, BinarySubgraph, CallEdge, CallGraph, DataXref, GraphImporter, HotCallee, Xref,
+};
+use crate::store::{EmbeddedStore, GraphStore};
+use crate::utils::crypto::detect_crypto;
+use crate::utils::encoding::{decode_wide, normalize_encoding};
+use crate::utils::fuzzy::{ssdeep_compare, tlsh_distance};
+use crate::utils::ioc::{classify_named_object, extract_indicators};
+use crate::utils::time::compile_time;
+use crate::utils::uid::{
+    format_address, function_uid, normalize_address, parse_address, vtable_uid,
+};
+
+/// Call-site offsets kept per unresolved address
+const UNRESOLVED_CALL_SITES: usize = 10;
+
+/// Share of functions (same address and name) above which a binary stored under another
+/// hash is reported as a likely duplicate
+const DUPLICATE_OVERLAP: f64 = 0.9;
+
+/// Binaries with fewer functions are too small to be compared by function overlap
+const DUPLICATE_MIN_FUNCTIONS: usize = 10;
+
+/// How long strings and decompiled code are stored, from `max_string_length`,
+/// `max_pseudocode_length` and `string_blob_dir`
+#[derive(Debug, Clone, Default)]
+pub struct StringStorage {
+    pub max_length: Option<usize>,
+    pub max_pseudocode_length: Option<usize>,
+    pub blob_dir: Option<PathBuf>,
 }
 
-impl ImportSession {
-    pub fn new(importer: GraphImporter) -> Self {
-        Self { importer }
+impl StringStorage {
+    pub fn from_config(config: &crate::config::Config) -> Self {
+        Self {
+            max_length: config.max_string_length,
+            max_pseudocode_length: Some(config.max_pseudocode_length),
+            blob_dir: config.string_blob_dir.as_ref().map(PathBuf::from),
+        }
     }
 
-    pub async fn import_data(&self, data: Value) -> Result<crate::api::ImportResult> {
-        let mut errors = Vec::new();
-        let mut stats = crate::api::ImportStatistics {
-            binaries: 0,
-            functions: 0,
-            strings: 0,
-            libraries: 0,
-            calls_relationships: 0,
-            total_nodes: 0,
-        };
-
-        let mut address_to_uid: HashMap<String, String> = HashMap::new();
-
-        let binary_info = match data.get("binary_info") {
-            Some(info) => info,
-            None => {
-                errors.push("Missing binary_info in data".to_string());
-                return Ok(crate::api::ImportResult {
-                    success: false,
-                    statistics: stats,
-                    errors,
-                });
-            }
+    /// Truncate `string` to the maximum length, writing its full content to the blob
+    /// directory (named by its SHA-256) when one is configured
+    fn apply(&self, string: &mut StringNode) -> Result<()> {
+        let Some(full) = self.max_length.and_then(|max| string.truncate(max)) else {
+            return Ok(());
         };
+        string.blob = self.write_blob(string.content_hash(), &full)?;
+        Ok(())
+    }
 
-        let binary = match self.parse_binary_info(binary_info) {
-            Ok(b) => b,
-            Err(e) => {
-                errors.push(format!("Failed to parse binary info: {}", e));
-                return Ok(crate::api::ImportResult {
-                    success: false,
-                    statistics: stats,
-                    errors,
-                });
-            }
+    /// Store `code` as the function's pseudocode, cut to the maximum length with the
+    /// full text written to the blob directory (named by its SHA-256)
+    fn apply_pseudocode(&self, function: &mut Function, code: &str) -> Result<()> {
+        let end = self
+            .max_pseudocode_length
+            .and_then(|max| code.char_indices().nth(max))
+            .map(|(end, _)| end);
+        let Some(end) = end else {
+            function.pseudocode = Some(code.to_string());
+            return Ok(());
         };
 
-        self.importer.import_binary(&binary).await?;
-        stats.binaries = 1;
-        let binary_hash = binary.hash.clone();
+        function.pseudocode = Some(code[..end].to_string());
+        let hash = format!("{:x}", Sha256::digest(code.as_bytes()));
+        function.pseudocode_blob = self.write_blob(&hash, code)?;
+        Ok(())
+    }
 
-        if let Some(functions_data) = data.get("functions") {
-            match self.parse_functions(functions_data, &binary_hash) {
-                Ok(functions) => {
-                    stats.functions += functions.len() as i64;
+    /// Write `content` to the blob directory as `name` unless it is already there;
+    /// returns its path, or `None` without a blob directory
+    fn write_blob(&self, name: &str, content: &str) -> Result<Option<String>> {
+        let Some(dir) = &self.blob_dir else {
+            return Ok(None);
+        };
 
-                    for function in &functions {
-                        if let Some(address) = &function.address {
-                            if let Some(normalized) = normalize_address(address) {
-                                address_to_uid.insert(normalized, function.uid.clone());
-                            }
-                            address_to_uid.insert(address.clone(), function.uid.clone());
-                        }
-                    }
+        let path = dir.join(name);
+        if !path.exists() {
+            std::fs::create_dir_all(dir)?;
+            std::fs::write(&path, content)
+                .map_err(|e| anyhow::anyhow!("Failed to write blob '{}': {}", path.display(), e))?;
+        }
+        Ok(Some(path.to_string_lossy().into_owned()))
+    }
+}
 
-                    for chunk in functions.chunks(1000) {
-                        self.importer.import_functions_batch(chunk).await?;
+/// How many failed items imports tolerate before they are aborted
+///
+/// Clones share their count, so a directory import stops once its files together
+/// exceed the limit. A limit of 0 aborts at the first error.
+#[derive(Debug, Clone)]
+pub struct ErrorPolicy {
+    max_errors: usize,
+    seen: Arc<AtomicUsize>,
+}
 
-                        for function in chunk {
-                            if let Err(e) = self
-                                .importer
-                                .create_contains_relationship(&binary_hash, &function.uid)
-                                .await
-                            {
-                                errors
-                                    .push(format!("Failed to create CONTAINS relationship: {}", e));
-                            }
-                        }
-                    }
-                }
-                Err(e) => {
-                    errors.push(format!("Failed to parse functions: {}", e));
-                }
-            }
+impl ErrorPolicy {
+    pub fn new(max_errors: usize) -> Self {
+        Self {
+            max_errors,
+            seen: Arc::new(AtomicUsize::new(0)),
         }
+    }
 
-        if let Some(strings_data) = data.get("strings") {
-            match self.parse_strings(strings_data) {
-                Ok(parsed_strings) => {
-                    let mut unique_strings: HashMap<String, StringNode> = HashMap::new();
-                    let mut occurrences: Vec<StringOccurrence> =
-                        Vec::with_capacity(parsed_strings.len());
+    pub fn max_errors(&self) -> usize {
+        self.max_errors
+    }
 
-                    for (string_node, occurrence) in parsed_strings {
-                        unique_strings
-                            .entry(string_node.uid.clone())
-                            .or_insert(string_node);
-                        occurrences.push(occurrence);
-                    }
+    /// Count `count` more errors; false once the total exceeds the limit
+    pub fn record(&self, count: usize) -> bool {
+        self.seen.fetch_add(count, Ordering::SeqCst) + count <= self.max_errors
+    }
 
-                    stats.strings += unique_strings.len() as i64;
+    pub fn exceeded(&self) -> bool {
+        self.seen.load(Ordering::SeqCst) > self.max_errors
+    }
+}
 
-                    for string_node in unique_strings.values() {
-                        if let Err(e) = self.importer.import_string_node(string_node).await {
-                            errors.push(format!("Failed to import string: {}", e));
-                        }
-                    }
+/// Functions and addresses written by `import_graph`, for the steps that follow it
+struct GraphImport {
+    functions: Vec<Function>,
+    /// Function uid of each function, import and export address, raw and normalized
+    address_to_uid: HashMap<String, String>,
+    /// Call endpoints that matched no function, `None` without a calls section
+    unresolved: Option<Vec<UnresolvedCall>>,
+}
 
-                    for occurrence in occurrences {
-                        if let Err(e) = self
-                            .importer
-                            .create_contains_string_relationship(
-                                &binary_hash,
-                                &occurrence.string_uid,
-                                occurrence.address.as_deref(),
-                            )
-                            .await
-                        {
-                            errors.push(format!(
-                                "Failed to create CONTAINS_STRING relationship: {}",
-                                e
-                            ));
-                        }
-                    }
-                }
-                Err(e) => {
-                    errors.push(format!("Failed to parse strings: {}", e));
-                }
-            }
+/// Errors of one `import_data` run, checked against the error policy as they occur
+struct ImportErrors<'a> {
+    errors: Vec<String>,
+    policy: Option<&'a ErrorPolicy>,
+}
+
+impl ImportErrors<'_> {
+    fn push(&mut self, message: String) -> Result<()> {
+        self.errors.push(message);
+        match self.policy {
+            Some(policy) if !policy.record(1) => Err(crate::error::partial_import(
+                if policy.max_errors() == 0 {
+                    "Import aborted at the first error".to_string()
+                } else {
+                    format!(
+                        "Import aborted after more than {} errors",
+                        policy.max_errors()
+                    )
+                },
+                self.errors.clone(),
+            )),
+            _ => Ok(()),
         }
+    }
+}
 
-        if let Some(imports_data) = data.get("imports") {
-            match self.parse_imports(imports_data) {
-                Ok((libraries, imports)) => {
-                    stats.libraries += libraries.len() as i64;
+/// Import and query operations over a graph store, Neo4j unless another store is given
+pub struct ImportSession<S = GraphImporter> {
+    importer: S,
+    library_signatures: Option<LibrarySignatures>,
+    api_hashes: Option<ApiHashes>,
+    prune: bool,
+    strings: StringStorage,
+    error_policy: Option<ErrorPolicy>,
+    relative_addresses: bool,
+    strict_addresses: bool,
+}
 
-                    for library in &libraries {
-                        if let Err(e) = self.importer.import_library(library).await {
-                            errors.push(format!("Failed to import library: {}", e));
-                        }
-                        // Create Binary-IMPORTS_LIBRARY->Library relationship
-                        if let Err(e) = self
-                            .importer
-                            .create_imports_relationship(&binary_hash, &library.name)
-                            .await
-                        {
-                            errors.push(format!("Failed to create IMPORTS relationship: {}", e));
-                        }
-                    }
+impl<S> ImportSession<S> {
+    pub fn new(importer: S) -> Self {
+        Self {
+            importer,
+            library_signatures: None,
+            api_hashes: None,
+            prune: false,
+            strings: StringStorage::default(),
+            error_policy: None,
+            relative_addresses: true,
+            strict_addresses: false,
+        }
+    }
 
-                    for import in &imports {
-                        let lib_name_lower = import.library.to_lowercase();
-                        let function = Function::create_import(&lib_name_lower, &import.name);
+    /// Flag imported functions whose name matches `signatures` as library code
+    pub fn with_library_signatures(mut self, signatures: Option<LibrarySignatures>) -> Self {
+        self.library_signatures = signatures;
+        self
+    }
 
-                        let import_address_normalized = normalize_address(&import.address)
-                            .unwrap_or_else(|| import.address.clone());
+    /// Resolve hash constants in functions to APIs, in addition to each file's
+    /// `api_hashes` section
+    pub fn with_api_hashes(mut self, api_hashes: Option<ApiHashes>) -> Self {
+        self.api_hashes = api_hashes;
+        self
+    }
 
-                        if let Some(normalized) = normalize_address(&import.address) {
-                            address_to_uid.insert(normalized, function.uid.clone());
-                        }
-                        address_to_uid.insert(import.address.clone(), function.uid.clone());
+    /// Delete functions, strings and calls of an earlier import that the new data lacks
+    pub fn with_pruning(mut self, prune: bool) -> Self {
+        self.prune = prune;
+        self
+    }
 
-                        if let Err(e) = self.importer.import_function(&function).await {
-                            errors.push(format!("Failed to import function: {}", e));
-                        }
-                        if let Err(e) = self
-                            .importer
-                            .create_belongs_to_relationship(&function.uid, &lib_name_lower)
-                            .await
-                        {
-                            errors.push(format!("Failed to create BELONGS_TO relationship: {}", e));
-                        }
-                        if let Err(e) = self
-                            .importer
-                            .create_imports_function_relationship_with_address(
-                                &binary_hash,
-                                &function.uid,
-                                &import_address_normalized,
-                            )
-                            .await
-                        {
-                            errors.push(format!(
-                                "Failed to create IMPORTS relationship for import: {}",
-                                e
-                            ));
-                        }
-                    }
-                }
-                Err(e) => {
-                    errors.push(format!("Failed to parse imports: {}", e));
-                }
-            }
-        }
+    /// Truncate long strings and store their full content as configured
+    pub fn with_string_storage(mut self, strings: StringStorage) -> Self {
+        self.strings = strings;
+        self
+    }
 
-        if let Some(exports_data) = data.get("exports") {
-            match self.parse_exports(exports_data) {
-                Ok(exports) => {
-                    for export in exports {
-                        let address = match parse_address(&export.address) {
-                            Some(addr) => addr,
-                            None => {
-                                errors.push(format!("Invalid export address: {}", export.address));
-                                continue;
-                            }
-                        };
-                        let function =
-                            Function::create_internal(&binary_hash, address, &export.name, true);
+    /// Abort the import with a partial-import error once the policy's limit is exceeded
+    pub fn with_error_policy(mut self, policy: Option<ErrorPolicy>) -> Self {
+        self.error_policy = policy;
+        self
+    }
 
-                        if let Some(func_addr) = &function.address {
-                            if !address_to_uid.contains_key(func_addr) {
-                                if let Some(normalized) = normalize_address(func_addr) {
-                                    address_to_uid.insert(normalized, function.uid.clone());
-                                }
-                                address_to_uid.insert(func_addr.clone(), function.uid.clone());
-                            }
-                        }
+    /// Store addresses relative to `binary_info.image_base` when a file gives one;
+    /// when off, the image base is ignored and addresses are kept as given
+    pub fn with_relative_addresses(mut self, relative: bool) -> Self {
+        self.relative_addresses = relative;
+        self
+    }
 
-                        if let Err(e) = self.importer.import_function(&function).await {
-                            errors.push(format!("Failed to import export function: {}", e));
-                        }
-                    }
-                }
-                Err(e) => {
-                    errors.push(format!("Failed to parse exports: {}", e));
-                }
+    /// Refuse files with unparseable addresses instead of skipping the items holding them
+    pub fn with_strict_addresses(mut self, strict: bool) -> Self {
+        self.strict_addresses = strict;
+        self
+    }
+
+    /// `data` with its addresses made relative to its image base, if enabled and given,
+    /// and the addresses that could not be parsed
+    fn prepare(&self, mut data: Value) -> Result<(Value, Vec<String>)> {
+        let unparseable = crate::api::addresses::unparseable(&data);
+        if !unparseable.is_empty() {
+            if self.strict_addresses {
+                return Err(crate::error::validation(
+                    format!("{} addresses could not be parsed", unparseable.len()),
+                    unparseable,
+                ));
             }
+            eprintln!(
+                "[WARN] {} addresses could not be parsed (listed in the import summary)",
+                unparseable.len()
+            );
         }
 
-        if let Some(calls_data) = data.get("calls") {
-            match self
-                .import_calls_with_mapping(calls_data, &address_to_uid)
-                .await
+        if self.relative_addresses {
+            if let Some(image_base) = data
+                .get("binary_info")
+                .and_then(crate::api::addresses::image_base)
             {
-                Ok(call_count) => {
-                    stats.calls_relationships += call_count;
-                }
-                Err(e) => {
-                    errors.push(format!("Failed to import calls: {}", e));
-                }
+                crate::api::addresses::make_relative(&mut data, image_base);
             }
         }
+        Ok((data, unparseable))
+    }
 
-        stats.total_nodes = stats.binaries + stats.functions + stats.strings + stats.libraries;
-
-        Ok(crate::api::ImportResult {
-            success: errors.is_empty(),
-            statistics: stats,
-            errors,
-        })
+    /// Binary of `binary_info`, or the message of the import error if it is missing or
+    /// invalid
+    fn binary_from_data(&self, data: &Value) -> std::result::Result<Binary, String> {
+        let binary_info = data
+            .get("binary_info")
+            .ok_or_else(|| "Missing binary_info in data".to_string())?;
+        let mut binary = self
+            .parse_binary_info(binary_info)
+            .map_err(|e| format!("Failed to parse binary info: {}", e))?;
+
+        let import_count = data
+            .get("imports")
+            .and_then(|v| v.as_array())
+            .map_or(0, |imports| imports.len());
+        binary.likely_packed = binary.is_likely_packed(import_count);
+        Ok(binary)
     }
 
     fn parse_binary_info(&self, binary_info: &Value) -> Result<Binary> {
@@ -304,21 +327,83 @@ impl ImportSession {
             BinaryFormat::PE // Default fallback
         };
 
-        let arch = file_type
+        let arch_name = file_type
             .get("architecture")
             .and_then(|v| v.as_str())
             .unwrap_or("unknown");
+        let bits = ["bits", "bitness"]
+            .iter()
+            .find_map(|key| file_type.get(*key).and_then(|v| v.as_u64()))
+            .map(|bits| bits as u32);
+        let arch = Architecture::parse(arch_name, bits);
+        if arch == Architecture::Unknown && !arch_name.eq_ignore_ascii_case("unknown") {
+            eprintln!(
+                "[WARN] Unrecognized architecture '{}', stored as unknown",
+                arch_name
+            );
+        }
+
+        let hash_field = |key: &str| {
+            optional_text(hashes, key).or_else(|| optional_text(hashes, &key.to_uppercase()))
+        };
 
         Ok(Binary {
             hash: sha256.to_string(),
+            md5: hash_field("md5").map(|h| h.to_lowercase()),
+            sha1: hash_field("sha1").map(|h| h.to_lowercase()),
+            ssdeep: hash_field("ssdeep"),
+            tlsh: hash_field("tlsh").map(|h| h.to_uppercase()),
             filename: filename.to_string(),
             file_path: file_path.to_string(),
             file_size,
             format,
-            arch: arch.to_string(),
+            arch,
+            bitness: arch.bits().or(bits),
+            image_base: crate::api::addresses::image_base(binary_info)
+                .filter(|_| self.relative_addresses)
+                .map(format_address),
+            compiler: optional_text(binary_info, "compiler"),
+            packer: optional_text(binary_info, "packer"),
+            family: optional_text(binary_info, "family"),
+            linker_version: optional_text(binary_info, "linker_version"),
+            pdb_path: optional_text(binary_info, "pdb_path"),
+            timestamp: optional_text(binary_info, "timestamp"),
+            compiled_at: optional_text(binary_info, "timestamp")
+                .as_deref()
+                .and_then(compile_time),
+            first_imported: None,
+            last_updated: None,
+            pe_info: self.parse_pe_info(binary_info, hashes),
+            elf_info: parse_elf_info(binary_info),
+            macho_info: parse_macho_info(binary_info),
+            risk_score: None,
+            sections: parse_sections(binary_info),
+            overlay_size: binary_info
+                .get("overlay_size")
+                .or_else(|| binary_info.get("overlay").and_then(|o| o.get("size")))
+                .and_then(|v| v.as_u64()),
+            likely_packed: false,
         })
     }
 
+    /// PE header details from `binary_info.pe_info`; the imphash may also be given in `hashes`
+    fn parse_pe_info(&self, binary_info: &Value, hashes: &Value) -> Option<PeInfo> {
+        let pe = binary_info.get("pe_info").unwrap_or(&Value::Null);
+
+        let pe_info = PeInfo {
+            imphash: optional_text(pe, "imphash")
+                .or_else(|| optional_text(hashes, "imphash"))
+                .map(|h| h.to_lowercase()),
+            rich_header_hash: optional_text(pe, "rich_header_hash").map(|h| h.to_lowercase()),
+            entry_point: optional_text(pe, "entry_point")
+                .map(|ep| normalize_address(&ep).unwrap_or(ep)),
+            subsystem: optional_text(pe, "subsystem"),
+            signer: optional_text(pe, "signer"),
+        };
+
+        (!pe_info.is_empty()).then_some(pe_info)
+    }
+
     fn parse_functions(&self, functions_data: &Value, binary_hash: &str) -> Result<Vec<Function>> {
         let functions_array = functions_data
             .as_array()
@@ -337,12 +422,46 @@ impl ImportSession {
                 .and_then(|v| v.as_str())
                 .unwrap_or("0x0");
 
-            let address = parse_address(address_str).unwrap_or(0);
+            // Listed among the file's unparseable addresses rather than merged at 0x0
+            let Some(address) = parse_address(address_str) else {
+                continue;
+            };
 
             let size = func_data.get("size").and_then(|v| v.as_u64());
 
             let mut function = Function::create_internal(binary_hash, address, name, false);
             function.size = size;
+            function.mnemonic_histogram = parse_mnemonic_histogram(func_data);
+            function.prototype = parse_prototype(func_data);
+            function.crypto = detect_crypto(&parse_constants(func_data));
+            function.content_hash = ["hash", "content_hash", "bytes_hash"]
+                .iter()
+                .find_map(|key| optional_text(func_data, key))
+                .map(|hash| hash.to_lowercase());
+            function.chunks = parse_chunks(func_data, address);
+            if let Some(code) = func_data
+                .get("decompiled")
+                .or_else(|| func_data.get("pseudocode"))
+                .and_then(|v| v.as_str())
+                .filter(|code| !code.trim().is_empty())
+            {
+                self.strings.apply_pseudocode(&mut function, code)?;
+            }
+            function.signature_match = optional_text(func_data, "signature_match");
+            function.is_library = func_data
+                .get("is_library")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(function.signature_match.is_some());
+            if !function.is_library {
+                if let Some(pattern) = self
+                    .library_signatures
+                    .as_ref()
+                    .and_then(|signatures| signatures.matching(name))
+                {
+                    function.is_library = true;
+                    function.signature_match = Some(pattern.to_string());
+                }
+            }
             functions.push(function);
         }
 
@@ -370,8 +489,25 @@ impl ImportSession {
                 .and_then(|v| v.as_str())
                 .map(|s| normalize_address(s).unwrap_or_else(|| s.to_string()));
 
-            let string_node = StringNode::new(value.to_string());
-            let occurrence = StringOccurrence::new(string_node.uid.clone(), address);
+            // Extractors mark wide strings with `encoding`, or with `type` in the native format
+            let encoding = optional_text(string_data, "encoding")
+                .or_else(|| optional_text(string_data, "type"))
+                .map(|name| normalize_encoding(&name));
+            let value = match &encoding {
+                Some(encoding) => decode_wide(value, encoding),
+                None => value.to_string(),
+            };
+
+            let mut string_node = StringNode::new(value);
+            self.strings.apply(&mut string_node)?;
+            let mut occurrence = StringOccurrence::new(string_node.uid.clone(), address, encoding);
+            occurrence.source = optional_text(string_data, "source").and_then(|name| {
+                StringSource::from_str(&name)
+                    .map_err(|e| eprintln!("[WARN] Ignoring string source: {}", e))
+                    .ok()
+            });
+            occurrence.decrypted_by = optional_text(string_data, "decrypted_by")
+                .map(|a| normalize_address(&a).unwrap_or(a));
             parsed.push((string_node, occurrence));
         }
 
@@ -418,6 +554,37 @@ impl ImportSession {
         Ok((libraries_vec, imports))
     }
 
+    fn parse_resources(&self, resources_data: &Value) -> Result<Vec<Resource>> {
+        let resources_array = resources_data
+            .as_array()
+            .ok_or_else(|| anyhow::anyhow!("resources must be an array"))?;
+
+        let mut resources = Vec::with_capacity(resources_array.len());
+
+        for resource_data in resources_array {
+            let sha256 = resource_data
+                .get("sha256")
+                .and_then(|v| v.as_str())
+                .filter(|v| !v.trim().is_empty())
+                .ok_or_else(|| anyhow::anyhow!("Resource missing sha256"))?;
+
+            let r#type =
+                optional_text(resource_data, "type").unwrap_or_else(|| "UNKNOWN".to_string());
+            let size = resource_data
+                .get("size")
+                .and_then(|v| v.as_u64())
+                .unwrap_or(0);
+
+            let mut resource = Resource::new(sha256, &r#type, size);
+            resource.name = optional_text(resource_data, "name");
+            resource.language = optional_text(resource_data, "language");
+            resource.file_type = optional_text(resource_data, "file_type");
+            resources.push(resource);
+        }
+
+        Ok(resources)
+    }
+
     fn parse_exports(&self, exports_data: &Value) -> Result<Vec<Export>> {
         let exports_array = exports_data
             .as_array()
@@ -444,32 +611,220 @@ impl ImportSession {
 
         Ok(exports)
     }
+}
 
-    async fn import_calls_with_mapping(
+impl<S: GraphStore> ImportSession<S> {
+    /// Write the functions, imports, exports and calls of `data` for the binary, after
+    /// the binary itself
+    async fn import_graph(
         &self,
-        calls_data: &Value,
-        address_to_uid: &HashMap<String, String>,
-    ) -> Result<i64> {
-        let calls_array = calls_data
-            .as_array()
-            .ok_or_else(|| anyhow::anyhow!("calls must be an array"))?;
+        binary_hash: &str,
+        data: &Value,
+        errors: &mut ImportErrors<'_>,
+        stats: &mut crate::api::ImportStatistics,
+        imported: &mut BinarySubgraph,
+    ) -> Result<GraphImport> {
+        let mut address_to_uid: HashMap<String, String> = HashMap::new();
+        let mut parsed = Vec::new();
+        let mut unresolved = None;
 
-        let mut call_count = 0i64;
-        let mut skipped_count = 0i64;
+        if let Some(functions_data) = data.get("functions") {
+            match self.parse_functions(functions_data, binary_hash) {
+                Ok(functions) => {
+                    stats.functions += functions.len() as i64;
 
-        for call_data in calls_array {
-            let from_addr = call_data
-                .get("from_address")
-                .and_then(|v| v.as_str())
-                .ok_or_else(|| anyhow::anyhow!("Call missing from_address"))?;
+                    for function in &functions {
+                        imported.functions.insert(function.uid.clone());
+                        if let Some(address) = &function.address {
+                            if let Some(normalized) = normalize_address(address) {
+                                address_to_uid.insert(normalized, function.uid.clone());
+                            }
+                            address_to_uid.insert(address.clone(), function.uid.clone());
+                        }
+                    }
 
-            let to_addr = call_data
-                .get("to_address")
-                .and_then(|v| v.as_str())
-                .ok_or_else(|| anyhow::anyhow!("Call missing to_address"))?;
+                    if let Err(e) = self
+                        .importer
+                        .import_functions_batch(binary_hash, &functions)
+                        .await
+                    {
+                        errors.push(format!("Failed to import functions: {}", e))?;
+                    }
+                    parsed = functions;
+                }
+                Err(e) => {
+                    errors.push(format!("Failed to parse functions: {}", e))?;
+                }
+            }
+        }
 
-            let offset = call_data
-                .get("offset")
+        if let Some(imports_data) = data.get("imports") {
+            match self.parse_imports(imports_data) {
+                Ok((libraries, imports)) => {
+                    stats.libraries += libraries.len() as i64;
+
+                    for library in &libraries {
+                        if let Err(e) = self.importer.import_library(library).await {
+                            errors.push(format!("Failed to import library: {}", e))?;
+                        }
+                        // Create Binary-IMPORTS_LIBRARY->Library relationship
+                        if let Err(e) = self
+                            .importer
+                            .create_imports_relationship(binary_hash, &library.name)
+                            .await
+                        {
+                            errors.push(format!("Failed to create IMPORTS relationship: {}", e))?;
+                        }
+                    }
+
+                    for import in &imports {
+                        let lib_name_lower = import.library.to_lowercase();
+                        let function = Function::create_import(&lib_name_lower, &import.name);
+
+                        let import_address_normalized = normalize_address(&import.address)
+                            .unwrap_or_else(|| import.address.clone());
+
+                        if let Some(normalized) = normalize_address(&import.address) {
+                            address_to_uid.insert(normalized, function.uid.clone());
+                        }
+                        address_to_uid.insert(import.address.clone(), function.uid.clone());
+
+                        if let Err(e) = self.importer.import_function(&function).await {
+                            errors.push(format!("Failed to import function: {}", e))?;
+                        }
+                        if let Err(e) = self
+                            .importer
+                            .create_belongs_to_relationship(&function.uid, &lib_name_lower)
+                            .await
+                        {
+                            errors
+                                .push(format!("Failed to create BELONGS_TO relationship: {}", e))?;
+                        }
+                        if let Err(e) = self
+                            .importer
+                            .create_imports_function_relationship_with_address(
+                                binary_hash,
+                                &function.uid,
+                                &import_address_normalized,
+                            )
+                            .await
+                        {
+                            errors.push(format!(
+                                "Failed to create IMPORTS relationship for import: {}",
+                                e
+                            ))?;
+                        }
+                    }
+                }
+                Err(e) => {
+                    errors.push(format!("Failed to parse imports: {}", e))?;
+                }
+            }
+        }
+
+        if let Some(exports_data) = data.get("exports") {
+            match self.parse_exports(exports_data) {
+                Ok(exports) => {
+                    for export in exports {
+                        let address = match parse_address(&export.address) {
+                            Some(addr) => addr,
+                            None => {
+                                errors
+                                    .push(format!("Invalid export address: {}", export.address))?;
+                                continue;
+                            }
+                        };
+                        let function =
+                            Function::create_internal(binary_hash, address, &export.name, true);
+
+                        if let Some(func_addr) = &function.address {
+                            if !address_to_uid.contains_key(func_addr) {
+                                if let Some(normalized) = normalize_address(func_addr) {
+                                    address_to_uid.insert(normalized, function.uid.clone());
+                                }
+                                address_to_uid.insert(func_addr.clone(), function.uid.clone());
+                            }
+                        }
+
+                        if let Err(e) = self.importer.import_function(&function).await {
+                            errors.push(format!("Failed to import export function: {}", e))?;
+                        }
+                    }
+                }
+                Err(e) => {
+                    errors.push(format!("Failed to parse exports: {}", e))?;
+                }
+            }
+        }
+
+        // Resolved indirect-call targets are imported as additional calls
+        let indirect = match data.get("indirect_targets").map(indirect_calls) {
+            Some(Ok(calls)) => calls,
+            Some(Err(e)) => {
+                errors.push(format!("Failed to parse indirect call targets: {}", e))?;
+                Vec::new()
+            }
+            None => Vec::new(),
+        };
+        let calls_data = match data.get("calls") {
+            Some(Value::Array(calls)) => Some(calls.as_slice()),
+            Some(_) => {
+                errors.push("Failed to import calls: calls must be an array".to_string())?;
+                None
+            }
+            None => (!indirect.is_empty()).then_some(&[][..]),
+        };
+        if let Some(calls_data) = calls_data {
+            match self
+                .import_calls_with_mapping(
+                    calls_data.iter().chain(&indirect),
+                    &address_to_uid,
+                    &mut imported.calls,
+                )
+                .await
+            {
+                Ok((call_count, skipped_count, calls_unresolved)) => {
+                    stats.calls_relationships += call_count;
+                    stats.skipped_calls += skipped_count;
+                    unresolved = Some(calls_unresolved);
+                }
+                Err(e) => {
+                    errors.push(format!("Failed to import calls: {}", e))?;
+                }
+            }
+        }
+
+        Ok(GraphImport {
+            functions: parsed,
+            address_to_uid,
+            unresolved,
+        })
+    }
+
+    async fn import_calls_with_mapping<'a>(
+        &self,
+        calls_array: impl IntoIterator<Item = &'a Value>,
+        address_to_uid: &HashMap<String, String>,
+        imported_calls: &mut HashSet<(String, String)>,
+    ) -> Result<(i64, i64, Vec<UnresolvedCall>)> {
+        let mut call_count = 0i64;
+        let mut skipped_count = 0i64;
+        let mut resolved: Vec<(&str, &str, Calls)> = Vec::new();
+        let mut unresolved: BTreeMap<(&str, String), UnresolvedCall> = BTreeMap::new();
+
+        for call_data in calls_array {
+            let from_addr = call_data
+                .get("from_address")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| anyhow::anyhow!("Call missing from_address"))?;
+
+            let to_addr = call_data
+                .get("to_address")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| anyhow::anyhow!("Call missing to_address"))?;
+
+            let offset = call_data
+                .get("offset")
                 .and_then(|v| v.as_str())
                 .unwrap_or("0x0");
 
@@ -492,65 +847,2118 @@ impl ImportSession {
                 .or_else(|| address_to_uid.get(to_addr));
 
             if let (Some(from_uid), Some(to_uid)) = (from_uid, to_uid) {
-                let calls = Calls::new(offset.to_string(), call_type);
-                self.importer
-                    .create_calls_relationship(&calls, from_uid, to_uid)
-                    .await?;
+                let (source, confidence) = edge_provenance(call_data);
+                let mut call =
+                    Calls::new(offset.to_string(), call_type).with_provenance(source, confidence);
+                call.resolved_by = call_data
+                    .get("resolved_by")
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string);
+                resolved.push((from_uid.as_str(), to_uid.as_str(), call));
+                imported_calls.insert((from_uid.clone(), to_uid.clone()));
                 call_count += 1;
             } else {
                 skipped_count += 1;
+
+                let endpoints = [
+                    ("caller", from_uid.is_some(), from_normalized),
+                    ("target", to_uid.is_some(), to_normalized),
+                ];
+                for (role, resolved, address) in endpoints {
+                    if resolved {
+                        continue;
+                    }
+                    let entry = unresolved
+                        .entry((role, address.clone()))
+                        .or_insert_with(|| UnresolvedCall {
+                            address,
+                            role: role.to_string(),
+                            count: 0,
+                            call_sites: Vec::new(),
+                            binary: String::new(),
+                        });
+                    entry.count += 1;
+                    if entry.call_sites.len() < UNRESOLVED_CALL_SITES {
+                        entry.call_sites.push(offset.to_string());
+                    }
+                }
             }
         }
 
+        self.importer.create_calls_batch(&resolved).await?;
+
+        let unresolved: Vec<UnresolvedCall> = unresolved.into_values().collect();
+
         if skipped_count > 0 {
             eprintln!(
-                "[WARN] Skipped {} call relationships due to unresolved addresses",
-                skipped_count
+                "[WARN] Skipped {} call relationships due to {} unresolved addresses (see `query unresolved`)",
+                skipped_count,
+                unresolved.len()
             );
         }
 
-        Ok(call_count)
+        Ok((call_count, skipped_count, unresolved))
     }
 
-    pub async fn query_functions(
-        &self,
-        pattern: &str,
-        binary: Option<&str>,
-    ) -> Result<Vec<Function>> {
-        self.importer.query_functions(pattern, binary).await
+    pub async fn query_binary_functions(&self, binary_hash: &str) -> Result<Vec<Function>> {
+        self.importer.query_binary_functions(binary_hash).await
     }
 
     pub async fn query_binary_info(&self, binary_name: &str) -> Result<Option<Binary>> {
         self.importer.query_binary_info(binary_name).await
     }
 
-    pub async fn query_callgraph_with_depth(
+    pub async fn query_binary_imports(&self, binary_hash: &str) -> Result<Vec<ImportGroup>> {
+        self.importer.query_binary_imports(binary_hash).await
+    }
+
+    pub async fn query_binary_call_graph(
         &self,
-        function_name: &str,
-        binary: Option<&str>,
-        max_depth: usize,
-    ) -> Result<CallGraph> {
-        self.importer
-            .query_callgraph_with_depth(function_name, binary, max_depth)
-            .await
+        binary_hash: &str,
+    ) -> Result<(Vec<Function>, Vec<CallGraphLink>)> {
+        self.importer.query_binary_call_graph(binary_hash).await
     }
 
-    pub async fn query_xrefs(&self, address: &str, binary: Option<&str>) -> Result<Vec<Xref>> {
-        self.importer.query_xrefs(address, binary).await
+    pub async fn query_binary_by_hash(&self, hash: &str) -> Result<Option<Binary>> {
+        self.importer.query_binary_by_hash(hash).await
     }
 
-    pub async fn query_strings_fulltext(
+    pub async fn query_binaries(
         &self,
-        lucene_query: &str,
-        binary: Option<&str>,
+        filter: &BinaryFilter<'_>,
         limit: usize,
-    ) -> Result<Vec<crate::models::StringSearchHit>> {
-        self.importer
-            .query_strings_fulltext(lucene_query, binary, limit)
-            .await
+    ) -> Result<Vec<Binary>> {
+        self.importer.query_binaries(filter, limit).await
     }
 
-    pub fn importer(&self) -> &crate::neo4j::GraphImporter {
-        &self.importer
+    pub async fn query_binary_resources(&self, binary_hash: &str) -> Result<Vec<Resource>> {
+        self.importer.query_binary_resources(binary_hash).await
+    }
+}
+
+impl ImportSession {
+    pub async fn import_data(&self, data: Value) -> Result<crate::api::ImportResult> {
+        let (data, unparseable_addresses) = self.prepare(data)?;
+        let mut errors = ImportErrors {
+            errors: Vec::new(),
+            policy: self.error_policy.as_ref(),
+        };
+        let mut stats = crate::api::ImportStatistics::default();
+
+        let mut address_to_string: HashMap<String, StringNode> = HashMap::new();
+
+        let binary = match self.binary_from_data(&data) {
+            Ok(binary) => binary,
+            Err(message) => {
+                errors.push(message)?;
+                return Ok(crate::api::ImportResult {
+                    success: false,
+                    binary_hash: None,
+                    statistics: stats,
+                    errors: errors.errors,
+                    unparseable_addresses,
+                    merge: None,
+                });
+            }
+        };
+
+        let existing = self.importer.query_binary_subgraph(&binary.hash).await?;
+        let mut imported = BinarySubgraph::default();
+
+        self.importer.import_binary(&binary).await?;
+        stats.binaries = 1;
+        let binary_hash = binary.hash.clone();
+
+        let GraphImport {
+            functions,
+            address_to_uid,
+            unresolved,
+        } = self
+            .import_graph(&binary_hash, &data, &mut errors, &mut stats, &mut imported)
+            .await?;
+
+        if existing.is_none() {
+            if let Err(e) = self.warn_duplicates(&binary, &functions).await {
+                eprintln!("[WARN] Failed to check for duplicate samples: {}", e);
+            }
+        }
+
+        // Replaces the records of an earlier import of the same binary
+        if let Some(unresolved) = unresolved {
+            if let Err(e) = self
+                .importer
+                .record_unresolved_calls(&binary_hash, &unresolved)
+                .await
+            {
+                errors.push(format!("Failed to record unresolved calls: {}", e))?;
+            }
+        }
+
+        if let Some(strings_data) = data.get("strings") {
+            match self.parse_strings(strings_data) {
+                Ok(parsed_strings) => {
+                    let mut unique_strings: HashMap<String, StringNode> = HashMap::new();
+                    let mut occurrences: Vec<StringOccurrence> =
+                        Vec::with_capacity(parsed_strings.len());
+
+                    for (string_node, occurrence) in parsed_strings {
+                        if let Some(address) = &occurrence.address {
+                            address_to_string.insert(address.clone(), string_node.clone());
+                        }
+                        unique_strings
+                            .entry(string_node.uid.clone())
+                            .or_insert(string_node);
+                        occurrences.push(occurrence);
+                    }
+
+                    stats.strings += unique_strings.len() as i64;
+                    stats.duplicate_strings += (occurrences.len() - unique_strings.len()) as i64;
+                    imported.strings.extend(unique_strings.keys().cloned());
+
+                    let string_nodes: Vec<&StringNode> = unique_strings.values().collect();
+                    if let Err(e) = self.importer.import_strings_batch(&string_nodes).await {
+                        errors.push(format!("Failed to import strings: {}", e))?;
+                    }
+                    if let Err(e) = self
+                        .importer
+                        .create_contains_string_relationships(&binary_hash, &occurrences)
+                        .await
+                    {
+                        errors.push(format!(
+                            "Failed to create CONTAINS_STRING relationships: {}",
+                            e
+                        ))?;
+                    }
+
+                    let mut unknown_decoders = HashSet::new();
+                    for occurrence in &occurrences {
+                        let Some(decoder) = &occurrence.decrypted_by else {
+                            continue;
+                        };
+                        let Some(function_uid) = address_to_uid.get(decoder) else {
+                            unknown_decoders.insert(decoder.clone());
+                            continue;
+                        };
+                        if let Err(e) = self
+                            .importer
+                            .create_decrypted_by_relationship(&occurrence.string_uid, function_uid)
+                            .await
+                        {
+                            errors.push(format!(
+                                "Failed to create DECRYPTED_BY relationship: {}",
+                                e
+                            ))?;
+                        }
+                    }
+                    if !unknown_decoders.is_empty() {
+                        eprintln!(
+                            "[WARN] Skipped DECRYPTED_BY for {} decrypting function addresses not found among the functions",
+                            unknown_decoders.len()
+                        );
+                    }
+
+                    // Domains, IPs and URLs embedded in strings become REFERENCES_IOC edges
+                    let mut indicators = HashSet::new();
+                    for string_node in unique_strings.values() {
+                        indicators.extend(extract_indicators(&string_node.value));
+                    }
+                    for indicator in &indicators {
+                        if let Err(e) = self
+                            .importer
+                            .create_indicator_relationship(
+                                &binary_hash,
+                                indicator,
+                                IndicatorRelation::ReferencesIoc,
+                            )
+                            .await
+                        {
+                            errors.push(format!(
+                                "Failed to create REFERENCES_IOC relationship: {}",
+                                e
+                            ))?;
+                        }
+                    }
+
+                    // Pipe paths and Global\/Local\ names become CREATES_OBJECT edges
+                    let objects: HashSet<NamedObject> = unique_strings
+                        .values()
+                        .filter_map(|string_node| classify_named_object(&string_node.value))
+                        .collect();
+                    for object in &objects {
+                        if let Err(e) = self
+                            .importer
+                            .create_object_relationship(&binary_hash, object, "strings")
+                            .await
+                        {
+                            errors.push(format!(
+                                "Failed to create CREATES_OBJECT relationship: {}",
+                                e
+                            ))?;
+                        }
+                    }
+                }
+                Err(e) => {
+                    errors.push(format!("Failed to parse strings: {}", e))?;
+                }
+            }
+        }
+
+        // Entry kinds are marked on every import so stale marks are cleared
+        match parse_entry_points(&data, binary.pe_info.as_ref()) {
+            Ok(entry_points) => {
+                let mut resolved = Vec::new();
+                let mut skipped = 0;
+                for (address, kind) in entry_points {
+                    match normalize_address(&address).and_then(|a| address_to_uid.get(&a)) {
+                        Some(uid) => resolved.push((uid.clone(), kind)),
+                        None => skipped += 1,
+                    }
+                }
+                if skipped > 0 {
+                    eprintln!(
+                        "[WARN] Skipped {} entry points that matched no function",
+                        skipped
+                    );
+                }
+                if let Err(e) = self
+                    .importer
+                    .mark_entry_points(&binary_hash, &resolved)
+                    .await
+                {
+                    errors.push(format!("Failed to mark entry points: {}", e))?;
+                }
+            }
+            Err(e) => {
+                errors.push(format!("Failed to parse entry points: {}", e))?;
+            }
+        }
+
+        // After the imports, so hashed APIs resolve to the binary's own import nodes
+        let section = match data.get("api_hashes").map(ApiHashes::parse) {
+            Some(Ok(section)) => Some(section),
+            Some(Err(e)) => {
+                errors.push(format!("Failed to parse API hashes: {}", e))?;
+                None
+            }
+            None => None,
+        };
+        if let (Some(functions_data), true) = (
+            data.get("functions"),
+            section.is_some() || self.api_hashes.is_some(),
+        ) {
+            let calls = hashed_api_calls(functions_data, &address_to_uid, |hash| {
+                section
+                    .as_ref()
+                    .and_then(|section| section.get(hash))
+                    .or_else(|| self.api_hashes.as_ref()?.get(hash))
+            });
+            match self
+                .importer
+                .create_api_hash_calls(&binary_hash, &calls)
+                .await
+            {
+                Ok(linked) => {
+                    let skipped = calls.len() - linked.len();
+                    if skipped > 0 {
+                        eprintln!(
+                            "[WARN] Skipped {} hashed API calls matching no import node (map them as library!name to create one)",
+                            skipped
+                        );
+                    }
+                    stats.calls_relationships += linked.len() as i64;
+                    imported.calls.extend(
+                        linked
+                            .into_iter()
+                            .map(|(caller, callee, _)| (caller, callee)),
+                    );
+                }
+                Err(e) => {
+                    errors.push(format!("Failed to create API hash calls: {}", e))?;
+                }
+            }
+        }
+
+        if let Some(data_refs) = data.get("data_refs") {
+            match self
+                .import_data_refs(data_refs, &binary_hash, &address_to_uid, &address_to_string)
+                .await
+            {
+                Ok(ref_count) => {
+                    stats.data_references += ref_count;
+                }
+                Err(e) => {
+                    errors.push(format!("Failed to import data references: {}", e))?;
+                }
+            }
+        }
+
+        // After the calls, so virtual calls can be expanded through the vtable slots
+        if let Some(vtables_data) = data.get("vtables") {
+            match parse_vtables(vtables_data, &binary_hash, &address_to_uid) {
+                Ok((vtables, skipped)) => {
+                    if skipped > 0 {
+                        eprintln!(
+                            "[WARN] Skipped {} vtable slots that matched no function",
+                            skipped
+                        );
+                    }
+                    match self.importer.import_vtables(&binary_hash, &vtables).await {
+                        Ok(()) => stats.vtables += vtables.len() as i64,
+                        Err(e) => errors.push(format!("Failed to import vtables: {}", e))?,
+                    }
+                }
+                Err(e) => {
+                    errors.push(format!("Failed to parse vtables: {}", e))?;
+                }
+            }
+        }
+
+        if let Some(resources_data) = data.get("resources") {
+            match self.parse_resources(resources_data) {
+                Ok(resources) => {
+                    stats.resources += resources.len() as i64;
+
+                    for resource in &resources {
+                        if let Err(e) = self.importer.import_resource(resource).await {
+                            errors.push(format!("Failed to import resource: {}", e))?;
+                            continue;
+                        }
+                        if let Err(e) = self
+                            .importer
+                            .create_has_resource_relationship(&binary_hash, resource)
+                            .await
+                        {
+                            errors.push(format!(
+                                "Failed to create HAS_RESOURCE relationship: {}",
+                                e
+                            ))?;
+                        }
+                        if resource.is_embedded_pe() && resource.sha256 != binary_hash {
+                            if let Err(e) = self
+                                .importer
+                                .create_embeds_relationship(&binary_hash, resource)
+                                .await
+                            {
+                                errors
+                                    .push(format!("Failed to create EMBEDS relationship: {}", e))?;
+                            }
+                        }
+                    }
+                }
+                Err(e) => {
+                    errors.push(format!("Failed to parse resources: {}", e))?;
+                }
+            }
+        }
+
+        stats.total_nodes = stats.binaries
+            + stats.functions
+            + stats.strings
+            + stats.libraries
+            + stats.resources
+            + stats.vtables;
+
+        // Re-import: everything above was merged in; report what changed and
+        // optionally drop what the new data no longer has
+        let merge = match existing {
+            Some(existing) => {
+                let added = imported.difference(&existing);
+                let removed = existing.difference(&imported);
+                if self.prune {
+                    self.importer
+                        .prune_binary_subgraph(&binary_hash, &removed)
+                        .await?;
+                }
+                Some(merge_summary(&added, &removed, self.prune))
+            }
+            None => None,
+        };
+
+        Ok(crate::api::ImportResult {
+            success: errors.errors.is_empty(),
+            binary_hash: Some(binary_hash),
+            statistics: stats,
+            errors: errors.errors,
+            unparseable_addresses,
+            merge,
+        })
+    }
+
+    /// Warn when a binary imported for the first time shares most of its functions with
+    /// a binary stored under another hash, e.g. the same sample with bytes appended
+    ///
+    /// The overlap is measured against the larger of the two binaries; matching imphash
+    /// and fuzzy hashes are listed to back the suspicion.
+    async fn warn_duplicates(&self, binary: &Binary, functions: &[Function]) -> Result<()> {
+        if functions.len() < DUPLICATE_MIN_FUNCTIONS {
+            return Ok(());
+        }
+        let min_shared = (functions.len() as f64 * DUPLICATE_OVERLAP).ceil() as usize;
+        let candidates = self
+            .importer
+            .query_overlapping_binaries(&binary.hash, functions, min_shared)
+            .await?;
+
+        for candidate in candidates {
+            let total = candidate.functions.max(functions.len());
+            if (candidate.shared as f64) < total as f64 * DUPLICATE_OVERLAP {
+                continue;
+            }
+            let evidence = duplicate_evidence(binary, &candidate.binary);
+            eprintln!(
+                "[WARN] '{}' shares {} of {} functions with '{}' ({}), likely the same sample under another hash ({})",
+                binary.filename,
+                candidate.shared,
+                total,
+                candidate.binary.filename,
+                candidate.binary.hash,
+                if evidence.is_empty() {
+                    "no imphash or fuzzy hash match".to_string()
+                } else {
+                    evidence.join(", ")
+                }
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Import sample relations given as an array of `{parent_sha256, child_sha256, relation}`
+    /// objects or `[parent, child, relation]` tuples, optionally wrapped in `{"relations": [...]}`
+    pub async fn import_relations(&self, data: Value) -> Result<crate::api::RelationImportResult> {
+        let entries = data
+            .get("relations")
+            .unwrap_or(&data)
+            .as_array()
+            .ok_or_else(|| anyhow::anyhow!("relations must be an array"))?;
+
+        let mut errors = Vec::new();
+        let mut relations = 0i64;
+
+        for (index, entry) in entries.iter().enumerate() {
+            let relation = match parse_relation(entry) {
+                Ok(relation) => relation,
+                Err(e) => {
+                    errors.push(format!("Relation #{}: {}", index + 1, e));
+                    continue;
+                }
+            };
+
+            match self.importer.create_binary_relation(&relation).await {
+                Ok(()) => relations += 1,
+                Err(e) => errors.push(format!(
+                    "Failed to create {} relationship: {}",
+                    relation.relation.label(),
+                    e
+                )),
+            }
+        }
+
+        Ok(crate::api::RelationImportResult {
+            success: errors.is_empty(),
+            relations,
+            errors,
+        })
+    }
+
+    /// Import a sandbox API trace as CALLED_AT_RUNTIME edges of an imported binary
+    ///
+    /// `binary_hash` overrides the sample hash named in the report.
+    pub async fn import_trace(
+        &self,
+        data: Value,
+        binary_hash: Option<&str>,
+    ) -> Result<crate::api::TraceImportResult> {
+        let trace = crate::api::trace::parse_trace(&data)?;
+
+        let binary_hash = binary_hash
+            .map(|h| h.trim().to_lowercase())
+            .or(trace.sha256)
+            .ok_or_else(|| {
+                anyhow::anyhow!("Trace does not name the analyzed sample, pass --binary <sha256>")
+            })?;
+        if self
+            .importer
+            .query_binary_by_hash(&binary_hash)
+            .await?
+            .is_none()
+        {
+            return Err(crate::error::not_found(format!(
+                "Binary {} has not been imported",
+                binary_hash
+            )));
+        }
+
+        let matched = self
+            .importer
+            .create_runtime_call_relationships(&binary_hash, &trace.calls)
+            .await?;
+
+        let matched: HashSet<&str> = matched.iter().map(String::as_str).collect();
+        let unresolved = trace
+            .calls
+            .iter()
+            .filter(|call| !matched.contains(call.api.as_str()))
+            .map(|call| call.api.clone())
+            .collect();
+
+        Ok(crate::api::TraceImportResult {
+            binary_hash,
+            apis: trace.calls.len() as i64,
+            relationships: matched.len() as i64,
+            unresolved,
+        })
+    }
+
+    /// Import sandbox network activity as CONTACTS edges of an imported binary
+    ///
+    /// `binary_hash` overrides the sample hash named in the report.
+    pub async fn import_network(
+        &self,
+        data: Value,
+        binary_hash: Option<&str>,
+    ) -> Result<crate::api::NetworkImportResult> {
+        let activity = crate::api::network::parse_network(&data)?;
+
+        let binary_hash = binary_hash
+            .map(|h| h.trim().to_lowercase())
+            .or(activity.sha256)
+            .ok_or_else(|| {
+                anyhow::anyhow!("Report does not name the analyzed sample, pass --binary <sha256>")
+            })?;
+        if self
+            .importer
+            .query_binary_by_hash(&binary_hash)
+            .await?
+            .is_none()
+        {
+            return Err(crate::error::not_found(format!(
+                "Binary {} has not been imported",
+                binary_hash
+            )));
+        }
+
+        let mut errors = Vec::new();
+        for indicator in &activity.indicators {
+            if let Err(e) = self
+                .importer
+                .create_indicator_relationship(&binary_hash, indicator, IndicatorRelation::Contacts)
+                .await
+            {
+                errors.push(format!("Failed to create CONTACTS relationship: {}", e));
+            }
+        }
+        for (domain, ip) in &activity.resolutions {
+            if let Err(e) = self
+                .importer
+                .create_resolves_to_relationship(domain, ip)
+                .await
+            {
+                errors.push(format!("Failed to create RESOLVES_TO relationship: {}", e));
+            }
+        }
+
+        Ok(crate::api::NetworkImportResult {
+            success: errors.is_empty(),
+            binary_hash,
+            indicators: activity.indicators.len() as i64,
+            resolutions: activity.resolutions.len() as i64,
+            errors,
+        })
+    }
+
+    /// Import mutexes, pipes, events and semaphores a sandbox saw an imported binary use
+    pub async fn import_objects(
+        &self,
+        data: Value,
+        binary_hash: Option<&str>,
+    ) -> Result<crate::api::ObjectImportResult> {
+        let activity = crate::api::objects::parse_objects(&data)?;
+
+        let binary_hash = binary_hash
+            .map(|h| h.trim().to_lowercase())
+            .or(activity.sha256)
+            .ok_or_else(|| {
+                anyhow::anyhow!("Report does not name the analyzed sample, pass --binary <sha256>")
+            })?;
+        if self
+            .importer
+            .query_binary_by_hash(&binary_hash)
+            .await?
+            .is_none()
+        {
+            return Err(crate::error::not_found(format!(
+                "Binary {} has not been imported",
+                binary_hash
+            )));
+        }
+
+        let mut errors = Vec::new();
+        for object in &activity.objects {
+            if let Err(e) = self
+                .importer
+                .create_object_relationship(&binary_hash, object, "sandbox")
+                .await
+            {
+                errors.push(format!(
+                    "Failed to create CREATES_OBJECT relationship: {}",
+                    e
+                ));
+            }
+        }
+
+        Ok(crate::api::ObjectImportResult {
+            success: errors.is_empty(),
+            binary_hash,
+            objects: activity.objects.len() as i64,
+            errors,
+        })
+    }
+
+    /// Rename a binary's functions from an address-to-name symbol map
+    ///
+    /// `image_base` is added to every map address, for maps that list RVAs; the result
+    /// is made relative to the binary's stored image base, if any.
+    pub async fn import_symbols(
+        &self,
+        map_text: &str,
+        binary: &str,
+        image_base: u64,
+    ) -> Result<crate::api::SymbolImportResult> {
+        let symbols = crate::api::symbols::parse_symbol_map(map_text)?;
+
+        let binary = match self.importer.query_binary_by_hash(binary).await? {
+            Some(found) => found,
+            None => self
+                .importer
+                .query_binary_info(binary)
+                .await?
+                .ok_or_else(|| {
+                    crate::error::not_found(format!("No binary found matching: '{}'", binary))
+                })?,
+        };
+
+        let mut by_uid: HashMap<String, String> = HashMap::new();
+        for (address, name) in &symbols {
+            let address = binary.relative_address(image_base.wrapping_add(*address));
+            let uid = function_uid(&binary.hash, address);
+            by_uid.insert(uid, name.clone());
+        }
+        let renames: Vec<(String, String)> = by_uid.into_iter().collect();
+
+        let renamed = self
+            .importer
+            .rename_functions(&binary.hash, &renames)
+            .await?;
+
+        Ok(crate::api::SymbolImportResult {
+            binary_hash: binary.hash,
+            symbols: symbols.len() as i64,
+            renamed: renamed as i64,
+        })
+    }
+
+    /// Create REFERENCES edges from `data_refs` entries
+    ///
+    /// The target is the entry's `string` literal if given, else the string imported at
+    /// `to_address`, else a Data node for that address.
+    async fn import_data_refs(
+        &self,
+        data_refs: &Value,
+        binary_hash: &str,
+        address_to_uid: &HashMap<String, String>,
+        address_to_string: &HashMap<String, StringNode>,
+    ) -> Result<i64> {
+        let refs_array = data_refs
+            .as_array()
+            .ok_or_else(|| anyhow::anyhow!("data_refs must be an array"))?;
+
+        let mut ref_count = 0i64;
+        let mut skipped_count = 0i64;
+
+        for ref_data in refs_array {
+            let from_addr = ref_data
+                .get("from_address")
+                .or_else(|| ref_data.get("function"))
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| anyhow::anyhow!("Data reference missing from_address"))?;
+
+            let to_addr = ref_data
+                .get("to_address")
+                .or_else(|| ref_data.get("target"))
+                .and_then(|v| v.as_str());
+            let literal = ref_data.get("string").and_then(|v| v.as_str());
+            if to_addr.is_none() && literal.is_none() {
+                return Err(anyhow::anyhow!(
+                    "Data reference missing to_address or string"
+                ));
+            }
+
+            let offset = ref_data
+                .get("offset")
+                .and_then(|v| v.as_str())
+                .map(|o| normalize_address(o).unwrap_or_else(|| o.to_string()))
+                .unwrap_or_else(|| "0x0".to_string());
+
+            let ref_type = RefType::from_str(
+                ref_data
+                    .get("type")
+                    .or_else(|| ref_data.get("ref_type"))
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("read"),
+            )
+            .unwrap_or(RefType::Read);
+
+            let from_normalized =
+                normalize_address(from_addr).unwrap_or_else(|| from_addr.to_string());
+            let Some(from_uid) = address_to_uid
+                .get(&from_normalized)
+                .or_else(|| address_to_uid.get(from_addr))
+            else {
+                skipped_count += 1;
+                continue;
+            };
+
+            let (source, confidence) = edge_provenance(ref_data);
+            let references = References::new(offset, ref_type).with_provenance(source, confidence);
+            let to_normalized =
+                to_addr.map(|a| normalize_address(a).unwrap_or_else(|| a.to_string()));
+
+            match (literal, to_normalized) {
+                (Some(value), _) => {
+                    let mut string_node = StringNode::new(value.to_string());
+                    self.strings.apply(&mut string_node)?;
+                    self.importer
+                        .create_string_reference(from_uid, &string_node, &references)
+                        .await?;
+                }
+                (None, Some(address)) => match address_to_string.get(&address) {
+                    Some(string_node) => {
+                        self.importer
+                            .create_string_reference(from_uid, string_node, &references)
+                            .await?
+                    }
+                    None => {
+                        self.importer
+                            .create_data_reference(binary_hash, from_uid, &address, &references)
+                            .await?
+                    }
+                },
+                (None, None) => continue,
+            }
+            ref_count += 1;
+        }
+
+        if skipped_count > 0 {
+            eprintln!(
+                "[WARN] Skipped {} data references due to unresolved function addresses",
+                skipped_count
+            );
+        }
+
+        Ok(ref_count)
+    }
+
+    pub async fn query_data_refs(
+        &self,
+        target: &str,
+        binary: Option<&str>,
+    ) -> Result<Vec<DataXref>> {
+        self.importer.query_data_refs(target, binary).await
+    }
+
+    pub async fn query_functions(
+        &self,
+        pattern: &str,
+        binary: Option<&str>,
+        param_type: Option<&str>,
+    ) -> Result<Vec<Function>> {
+        self.importer
+            .query_functions(pattern, binary, param_type)
+            .await
+    }
+
+    pub async fn annotate_function(
+        &self,
+        uid: &str,
+        analyst_name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<Option<Function>> {
+        self.importer
+            .annotate_function(uid, analyst_name, comment)
+            .await
+    }
+
+    pub async fn query_annotations(&self) -> Result<Annotations> {
+        self.importer.query_annotations().await
+    }
+
+    /// Restore annotations written by `export annotations`; entries for functions or
+    /// binaries not in the graph are skipped
+    pub async fn import_annotations(
+        &self,
+        annotations: &Annotations,
+    ) -> Result<crate::api::AnnotationImportResult> {
+        if annotations.version > ANNOTATIONS_VERSION {
+            return Err(anyhow::anyhow!(
+                "Annotation file version {} is newer than supported version {}",
+                annotations.version,
+                ANNOTATIONS_VERSION
+            ));
+        }
+
+        let (functions_applied, binaries_applied) =
+            self.importer.apply_annotations(annotations).await?;
+        Ok(crate::api::AnnotationImportResult {
+            functions: annotations.functions.len(),
+            functions_applied,
+            binaries: annotations.binaries.len(),
+            binaries_applied,
+        })
+    }
+
+    pub async fn clear_function_annotations(&self, uid: &str) -> Result<Option<Function>> {
+        self.importer.clear_function_annotations(uid).await
+    }
+
+    pub async fn query_mnemonic_histograms(
+        &self,
+        function_name: Option<&str>,
+        binary: Option<&str>,
+    ) -> Result<Vec<(String, Function)>> {
+        self.importer
+            .query_mnemonic_histograms(function_name, binary)
+            .await
+    }
+
+    pub async fn query_function_metrics(
+        &self,
+        pattern: &str,
+        binary: Option<&str>,
+        sort: FunctionSort,
+        limit: usize,
+    ) -> Result<Vec<FunctionMetrics>> {
+        self.importer
+            .query_function_metrics(pattern, binary, sort, limit)
+            .await
+    }
+
+    pub async fn query_indicators(
+        &self,
+        binary: Option<&str>,
+        value: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<IndicatorHit>> {
+        self.importer.query_indicators(binary, value, limit).await
+    }
+
+    pub async fn query_objects(
+        &self,
+        kind: Option<ObjectKind>,
+        name: Option<&str>,
+        binary: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<ObjectHit>> {
+        self.importer.query_objects(kind, name, binary, limit).await
+    }
+
+    pub async fn query_shared_apis(
+        &self,
+        binary: &str,
+        min_shared: usize,
+        limit: usize,
+    ) -> Result<Vec<SharedApiHit>> {
+        self.importer
+            .query_shared_apis(binary, min_shared, limit)
+            .await
+    }
+
+    pub async fn query_neighbors(
+        &self,
+        binary_hash: &str,
+        space: FeatureSpace,
+        k: usize,
+    ) -> Result<Vec<NeighborHit>> {
+        self.importer.query_neighbors(binary_hash, space, k).await
+    }
+
+    pub async fn generate_embeddings(
+        &self,
+        algorithm: EmbeddingAlgorithm,
+        dimension: usize,
+    ) -> Result<EmbeddingRun> {
+        self.importer
+            .generate_embeddings(algorithm, dimension)
+            .await
+    }
+
+    pub async fn query_embeddings(&self) -> Result<Vec<BinaryEmbedding>> {
+        self.importer.query_embeddings().await
+    }
+
+    pub async fn query_feature_matrix(&self, space: FeatureSpace) -> Result<Vec<FeatureCell>> {
+        self.importer.query_feature_matrix(space).await
+    }
+
+    /// Size distributions, format and architecture breakdown and the `top_libraries`
+    /// most imported libraries of all binaries
+    pub async fn query_corpus_stats(&self, top_libraries: usize) -> Result<CorpusStats> {
+        let counts = self.importer.query_binary_counts().await?;
+        let libraries = self.importer.query_top_libraries(top_libraries).await?;
+        Ok(CorpusStats::new(&counts, libraries))
+    }
+
+    /// APIs of a library used in the corpus; `name` matches with or without `.dll`
+    pub async fn query_library_apis(
+        &self,
+        name: &str,
+        examples: usize,
+        limit: usize,
+    ) -> Result<Vec<LibraryApiUse>> {
+        let name = name.trim().to_lowercase();
+        let base = name.strip_suffix(".dll").unwrap_or(&name);
+        let names = vec![base.to_string(), format!("{}.dll", base)];
+        self.importer
+            .query_library_apis(&names, examples, limit)
+            .await
+    }
+
+    pub async fn query_api_surface(&self, binary_hash: &str) -> Result<ApiSurface> {
+        self.importer.query_api_surface(binary_hash).await
+    }
+
+    pub async fn query_top_strings(
+        &self,
+        binary_hash: &str,
+        limit: usize,
+    ) -> Result<Vec<StringHit>> {
+        self.importer.query_top_strings(binary_hash, limit).await
+    }
+
+    pub async fn query_binary_call_edges(
+        &self,
+        binary_hash: &str,
+        limit: usize,
+    ) -> Result<Vec<GraphEdge>> {
+        self.importer
+            .query_binary_call_edges(binary_hash, limit)
+            .await
+    }
+
+    pub async fn query_imported_api_uses(
+        &self,
+        binary_hash: &str,
+    ) -> Result<Vec<(String, Vec<String>)>> {
+        self.importer.query_imported_api_uses(binary_hash).await
+    }
+
+    pub async fn store_risk_score(
+        &self,
+        binary_hash: &str,
+        score: f64,
+        categories: &[String],
+    ) -> Result<()> {
+        self.importer
+            .store_risk_score(binary_hash, score, categories)
+            .await
+    }
+
+    pub async fn query_fuzzy_hashed_binaries(&self) -> Result<Vec<Binary>> {
+        self.importer.query_fuzzy_hashed_binaries().await
+    }
+
+    pub async fn query_callgraph_with_depth(
+        &self,
+        function_name: &str,
+        binary: Option<&str>,
+        max_depth: usize,
+        exclude_library: bool,
+        min_confidence: f64,
+    ) -> Result<CallGraph> {
+        self.importer
+            .query_callgraph_with_depth(
+                function_name,
+                binary,
+                max_depth,
+                exclude_library,
+                min_confidence,
+            )
+            .await
+    }
+
+    pub async fn query_xrefs(
+        &self,
+        target: &str,
+        binary: Option<&str>,
+        direction: XrefDirection,
+    ) -> Result<Vec<Xref>> {
+        self.importer.query_xrefs(target, binary, direction).await
+    }
+
+    pub async fn query_unresolved_calls(
+        &self,
+        binary: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<UnresolvedCall>> {
+        self.importer.query_unresolved_calls(binary, limit).await
+    }
+
+    pub async fn query_hot_callees(
+        &self,
+        function: &str,
+        binary: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<HotCallee>> {
+        self.importer
+            .query_hot_callees(function, binary, limit)
+            .await
+    }
+
+    pub async fn search(
+        &self,
+        term: &str,
+        lucene_query: &str,
+        limit: usize,
+    ) -> Result<Vec<SearchHit>> {
+        self.importer.search(term, lucene_query, limit).await
+    }
+
+    pub async fn query_crypto(
+        &self,
+        algorithm: Option<&str>,
+        binary: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<CryptoHit>> {
+        self.importer.query_crypto(algorithm, binary, limit).await
+    }
+
+    pub async fn query_pseudocode_fulltext(
+        &self,
+        lucene_query: &str,
+        binary: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<PseudocodeHit>> {
+        self.importer
+            .query_pseudocode_fulltext(lucene_query, binary, limit)
+            .await
+    }
+
+    pub async fn query_strings_fulltext(
+        &self,
+        lucene_query: &str,
+        binary: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<crate::models::StringSearchHit>> {
+        self.importer
+            .query_strings_fulltext(lucene_query, binary, limit)
+            .await
+    }
+
+    pub fn importer(&self) -> &crate::neo4j::GraphImporter {
+        &self.importer
+    }
+}
+
+/// Sections of extractor output that the embedded store does not keep
+const EMBEDDED_SKIPPED_SECTIONS: &[&str] = &[
+    "strings",
+    "entry_points",
+    "api_hashes",
+    "data_refs",
+    "vtables",
+    "resources",
+];
+
+impl ImportSession<EmbeddedStore> {
+    /// Import the binary, functions, imports, exports and calls of `data`; the caller
+    /// saves the store
+    pub async fn import_data(&self, data: Value) -> Result<crate::api::ImportResult> {
+        let (data, unparseable_addresses) = self.prepare(data)?;
+        let mut errors = ImportErrors {
+            errors: Vec::new(),
+            policy: self.error_policy.as_ref(),
+        };
+        let mut stats = crate::api::ImportStatistics::default();
+
+        let binary = match self.binary_from_data(&data) {
+            Ok(binary) => binary,
+            Err(message) => {
+                errors.push(message)?;
+                return Ok(crate::api::ImportResult {
+                    success: false,
+                    binary_hash: None,
+                    statistics: stats,
+                    errors: errors.errors,
+                    unparseable_addresses,
+                    merge: None,
+                });
+            }
+        };
+
+        let existing = self.importer.subgraph(&binary.hash);
+        let mut imported = BinarySubgraph::default();
+
+        self.importer.import_binary(&binary).await?;
+        stats.binaries = 1;
+        self.import_graph(&binary.hash, &data, &mut errors, &mut stats, &mut imported)
+            .await?;
+
+        let skipped: Vec<&str> = EMBEDDED_SKIPPED_SECTIONS
+            .iter()
+            .copied()
+            .filter(|section| data.get(section).is_some())
+            .collect();
+        if !skipped.is_empty() {
+            eprintln!(
+                "[WARN] The embedded backend does not store these sections: {}",
+                skipped.join(", ")
+            );
+        }
+        stats.total_nodes = stats.binaries + stats.functions + stats.libraries;
+
+        // Re-import: as with Neo4j, report what changed and optionally drop the rest
+        let merge = existing.map(|existing| {
+            let added = imported.difference(&existing);
+            let removed = existing.difference(&imported);
+            if self.prune {
+                self.importer.prune(&binary.hash, &removed);
+            }
+            merge_summary(&added, &removed, self.prune)
+        });
+
+        Ok(crate::api::ImportResult {
+            success: errors.errors.is_empty(),
+            binary_hash: Some(binary.hash),
+            statistics: stats,
+            errors: errors.errors,
+            unparseable_addresses,
+            merge,
+        })
+    }
+
+    /// Binaries whose filename contains `binary` or whose hash it is, or all of them
+    async fn embedded_binaries(&self, binary: Option<&str>) -> Result<Vec<Binary>> {
+        let binaries = self
+            .importer
+            .query_binaries(&BinaryFilter::default(), usize::MAX)
+            .await?;
+        Ok(binaries
+            .into_iter()
+            .filter(|b| binary.is_none_or(|name| b.filename.contains(name) || b.hash == name))
+            .collect())
+    }
+
+    /// Contained functions whose name, UID, analyst name or demangled name contains
+    /// `pattern`, optionally only those taking a `param_type` parameter
+    pub async fn query_functions(
+        &self,
+        pattern: &str,
+        binary: Option<&str>,
+        param_type: Option<&str>,
+    ) -> Result<Vec<Function>> {
+        let param_type = param_type.map(FunctionPrototype::normalize_type);
+        let mut functions = Vec::new();
+        for sample in self.embedded_binaries(binary).await? {
+            let contained = self.importer.query_binary_functions(&sample.hash).await?;
+            functions.extend(contained.into_iter().filter(|f| {
+                let matches_pattern = [
+                    Some(&f.name),
+                    Some(&f.uid),
+                    f.analyst_name.as_ref(),
+                    f.demangled_name.as_ref(),
+                ]
+                .into_iter()
+                .flatten()
+                .any(|text| text.contains(pattern));
+                let matches_type = param_type.as_ref().is_none_or(|wanted| {
+                    f.prototype
+                        .iter()
+                        .flat_map(|p| &p.parameters)
+                        .any(|parameter| {
+                            FunctionPrototype::normalize_type(&parameter.r#type) == *wanted
+                        })
+                });
+                matches_pattern && matches_type
+            }));
+        }
+        Ok(functions)
+    }
+
+    /// Callees and callers of a function up to `max_depth` calls away, from the call
+    /// graphs of the selected binaries
+    pub async fn query_callgraph_with_depth(
+        &self,
+        function_name: &str,
+        binary: Option<&str>,
+        max_depth: usize,
+        exclude_library: bool,
+        min_confidence: f64,
+    ) -> Result<CallGraph> {
+        let mut functions = BTreeMap::new();
+        let mut links = Vec::new();
+        for sample in self.embedded_binaries(binary).await? {
+            let (members, calls) = self.importer.query_binary_call_graph(&sample.hash).await?;
+            functions.extend(members.into_iter().map(|f| (f.uid.clone(), f)));
+            links.extend(calls);
+        }
+        Ok(expand_call_graph(
+            &functions,
+            &links,
+            function_name,
+            max_depth,
+            exclude_library,
+            min_confidence,
+        ))
+    }
+}
+
+/// Calls reached from the functions named `function_name` in up to `max_depth` hops,
+/// each at its shortest distance, as the Neo4j call graph query: paths stop before
+/// library functions with `exclude_library` and do not follow calls below
+/// `min_confidence`
+fn expand_call_graph(
+    functions: &BTreeMap<String, Function>,
+    links: &[CallGraphLink],
+    function_name: &str,
+    max_depth: usize,
+    exclude_library: bool,
+    min_confidence: f64,
+) -> CallGraph {
+    let starts: HashSet<&str> = functions
+        .values()
+        .filter(|f| {
+            f.name == function_name
+                || f.demangled_name.as_deref() == Some(function_name)
+                || f.uid == function_name
+        })
+        .map(|f| f.uid.as_str())
+        .collect();
+    let blocked = |uid: &str| exclude_library && functions.get(uid).is_some_and(|f| f.is_library);
+    let info = |uid: &str| {
+        let function = functions.get(uid);
+        FunctionInfo {
+            uid: uid.to_string(),
+            name: function
+                .map(|f| {
+                    f.analyst_name
+                        .clone()
+                        .or_else(|| f.demangled_name.clone())
+                        .unwrap_or_else(|| f.name.clone())
+                })
+                .unwrap_or_default(),
+            address: function.and_then(|f| f.address.clone()),
+        }
+    };
+    let kind = |uid: &str| match functions.get(uid) {
+        Some(f) if f.is_library => "Library".to_string(),
+        Some(f) => format!("{:?}", f.r#type),
+        None => "Internal".to_string(),
+    };
+
+    let mut graph = CallGraph {
+        callees: Vec::new(),
+        callers: Vec::new(),
+        edges: Vec::new(),
+    };
+    for direction in ["callee", "caller"] {
+        let mut reached = starts.clone();
+        let mut frontier = starts.clone();
+        let mut edges = Vec::new();
+        for depth in 1..=max_depth {
+            let mut next = HashSet::new();
+            for link in links.iter().filter(|l| l.confidence >= min_confidence) {
+                let (near, far) = match direction {
+                    "callee" => (link.from_uid.as_str(), link.to_uid.as_str()),
+                    _ => (link.to_uid.as_str(), link.from_uid.as_str()),
+                };
+                if !frontier.contains(near) || blocked(far) {
+                    continue;
+                }
+                let (from, to) = (info(&link.from_uid), info(&link.to_uid));
+                edges.push(CallEdge {
+                    direction: direction.to_string(),
+                    from_uid: from.uid,
+                    from_name: from.name,
+                    from_type: kind(&link.from_uid),
+                    to_uid: to.uid,
+                    to_name: to.name,
+                    to_type: kind(&link.to_uid),
+                    offset: link.offsets.first().cloned().unwrap_or_default(),
+                    call_type: link.call_type.clone().unwrap_or_else(|| "Direct".into()),
+                    source: link.source.clone().unwrap_or_else(|| "static".into()),
+                    confidence: link.confidence,
+                    depth,
+                });
+                if reached.insert(far) {
+                    next.insert(far);
+                }
+            }
+            frontier = next;
+        }
+        edges.sort_by(|a, b| {
+            (a.depth, &a.from_name, &a.offset).cmp(&(b.depth, &b.from_name, &b.offset))
+        });
+
+        let ends = match direction {
+            "callee" => &mut graph.callees,
+            _ => &mut graph.callers,
+        };
+        for edge in &edges {
+            let end = match direction {
+                "callee" => &edge.to_uid,
+                _ => &edge.from_uid,
+            };
+            if !ends.iter().any(|f: &FunctionInfo| &f.uid == end) {
+                ends.push(info(end));
+            }
+        }
+        graph.edges.extend(edges);
+    }
+    graph
+}
+
+/// Counts of a re-import from the parts of the binary's subgraph it added and removed
+fn merge_summary(
+    added: &BinarySubgraph,
+    removed: &BinarySubgraph,
+    pruned: bool,
+) -> crate::api::MergeSummary {
+    crate::api::MergeSummary {
+        functions_added: added.functions.len(),
+        functions_removed: removed.functions.len(),
+        strings_added: added.strings.len(),
+        strings_removed: removed.strings.len(),
+        calls_added: added.calls.len(),
+        calls_removed: removed.calls.len(),
+        pruned,
+    }
+}
+
+/// Read an optional metadata field, accepting strings and numbers; empty strings are ignored
+/// ELF dynamic-linking details from `binary_info.elf_info`
+fn parse_elf_info(binary_info: &Value) -> Option<ElfInfo> {
+    let elf = binary_info.get("elf_info")?;
+    let elf_info = ElfInfo {
+        interpreter: optional_text(elf, "interpreter"),
+        soname: optional_text(elf, "soname"),
+        needed: text_list(elf, "needed"),
+        symbol_versions: text_list(elf, "symbol_versions"),
+    };
+    (!elf_info.is_empty()).then_some(elf_info)
+}
+
+/// Mach-O load commands and signing identity from `binary_info.macho_info`
+fn parse_macho_info(binary_info: &Value) -> Option<MachOInfo> {
+    let macho = binary_info.get("macho_info")?;
+    let macho_info = MachOInfo {
+        load_commands: text_list(macho, "load_commands"),
+        team_id: optional_text(macho, "team_id"),
+        rpaths: text_list(macho, "rpaths"),
+    };
+    (!macho_info.is_empty()).then_some(macho_info)
+}
+
+/// Non-empty strings of the array at `key`
+fn text_list(object: &Value, key: &str) -> Vec<String> {
+    object
+        .get(key)
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|v| v.as_str())
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Hash comparisons supporting that two binaries are the same sample
+fn duplicate_evidence(a: &Binary, b: &Binary) -> Vec<String> {
+    let mut evidence = Vec::new();
+    let imphash = |binary: &Binary| binary.pe_info.as_ref().and_then(|pe| pe.imphash.clone());
+    if imphash(a).is_some() && imphash(a) == imphash(b) {
+        evidence.push("same imphash".to_string());
+    }
+    if let Some(score) = a
+        .ssdeep
+        .as_deref()
+        .zip(b.ssdeep.as_deref())
+        .and_then(|(a, b)| ssdeep_compare(a, b))
+        .filter(|score| *score > 0)
+    {
+        evidence.push(format!("ssdeep {}", score));
+    }
+    if let Some(distance) = a
+        .tlsh
+        .as_deref()
+        .zip(b.tlsh.as_deref())
+        .and_then(|(a, b)| tlsh_distance(a, b))
+    {
+        evidence.push(format!("TLSH distance {}", distance));
+    }
+    evidence
+}
+
+/// Entry-point addresses and their kinds from `entry_points`, either a list of
+/// addresses or `{address, kind}` objects, or an object of kind to address(es):
+/// `{main: "0x...", tls_callbacks: [...], exception_handlers: [...]}`
+///
+/// Exports named DllMain and the PE header entry point are added when not listed;
+/// an address listed twice keeps its first kind.
+fn parse_entry_points(data: &Value, pe_info: Option<&PeInfo>) -> Result<Vec<(String, EntryKind)>> {
+    let mut entry_points = Vec::new();
+    let mut add = |address: &str, kind: EntryKind| {
+        let address = normalize_address(address).unwrap_or_else(|| address.trim().to_string());
+        if !entry_points.iter().any(|(a, _)| *a == address) {
+            entry_points.push((address, kind));
+        }
+    };
+
+    match data.get("entry_points") {
+        Some(Value::Array(entries)) => {
+            for entry in entries {
+                let address = entry
+                    .as_str()
+                    .or_else(|| entry.get("address").and_then(|v| v.as_str()))
+                    .ok_or_else(|| anyhow::anyhow!("Entry point missing address"))?;
+                let kind = match entry.get("kind").and_then(|v| v.as_str()) {
+                    Some(kind) => EntryKind::from_str(kind).map_err(|e| anyhow::anyhow!(e))?,
+                    None => EntryKind::Entry,
+                };
+                add(address, kind);
+            }
+        }
+        Some(Value::Object(kinds)) => {
+            for (key, addresses) in kinds {
+                // Plural keys such as `tls_callbacks` name the same kind
+                let kind = EntryKind::from_str(key)
+                    .or_else(|e| key.strip_suffix('s').map_or(Err(e), EntryKind::from_str))
+                    .map_err(|e| anyhow::anyhow!(e))?;
+                let addresses = match addresses {
+                    Value::Array(addresses) => addresses.iter().collect(),
+                    address => vec![address],
+                };
+                for address in addresses.into_iter().filter_map(|v| v.as_str()) {
+                    add(address, kind);
+                }
+            }
+        }
+        Some(_) => {
+            return Err(anyhow::anyhow!(
+                "entry_points must be an array or an object"
+            ))
+        }
+        None => {}
+    }
+
+    let exports = data
+        .get("exports")
+        .and_then(|v| v.as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    for export in exports {
+        let name = export.get("name").and_then(|v| v.as_str()).unwrap_or("");
+        let name = name.trim_start_matches('_');
+        let name = name.split_once('@').map_or(name, |(name, _)| name);
+        if name.eq_ignore_ascii_case("DllMain") {
+            if let Some(address) = export.get("address").and_then(|v| v.as_str()) {
+                add(address, EntryKind::DllMain);
+            }
+        }
+    }
+    if let Some(address) = pe_info.and_then(|pe| pe.entry_point.as_deref()) {
+        add(address, EntryKind::Entry);
+    }
+
+    Ok(entry_points)
+}
+
+/// `calls` entries for every target of an `indirect_targets` entry:
+/// `{from_address, offset, targets: ["0x..." | {address, confidence}], resolved_by}`
+///
+/// Targets found by emulation default to the dynamic source, others to static.
+fn indirect_calls(indirect: &Value) -> Result<Vec<Value>> {
+    let entries = indirect
+        .as_array()
+        .ok_or_else(|| anyhow::anyhow!("indirect_targets must be an array"))?;
+
+    let mut calls = Vec::new();
+    for entry in entries {
+        let from_address = entry
+            .get("from_address")
+            .or_else(|| entry.get("function"))
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Indirect call missing from_address"))?;
+        let targets = entry
+            .get("targets")
+            .and_then(|v| v.as_array())
+            .ok_or_else(|| anyhow::anyhow!("Indirect call missing targets"))?;
+        let offset = entry
+            .get("offset")
+            .or_else(|| entry.get("call_site"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("0x0");
+        let resolved_by = entry
+            .get("resolved_by")
+            .and_then(|v| v.as_str())
+            .unwrap_or("analysis");
+        let source = entry.get("source").and_then(|v| v.as_str()).unwrap_or(
+            if resolved_by.eq_ignore_ascii_case("emulation") {
+                "dynamic"
+            } else {
+                "static"
+            },
+        );
+
+        for target in targets {
+            let Some(address) = target
+                .as_str()
+                .or_else(|| target.get("address").and_then(|v| v.as_str()))
+            else {
+                continue;
+            };
+            let confidence = target
+                .get("confidence")
+                .or_else(|| entry.get("confidence"))
+                .cloned()
+                .unwrap_or(Value::Null);
+            calls.push(serde_json::json!({
+                "from_address": from_address,
+                "to_address": address,
+                "offset": offset,
+                "type": "indirect",
+                "source": source,
+                "confidence": confidence,
+                "resolved_by": resolved_by,
+            }));
+        }
+    }
+
+    Ok(calls)
+}
+
+/// Parse `vtables` entries:
+/// `{address, class_name, slots: ["0x..." | {index, address}], bases: ["0x..."]}`
+///
+/// Slots are numbered by position unless they give an `index`; null slots (pure virtual
+/// functions) are skipped. Returns the vtables and the number of slots whose address
+/// matched no function.
+fn parse_vtables(
+    vtables_data: &Value,
+    binary_hash: &str,
+    address_to_uid: &HashMap<String, String>,
+) -> Result<(Vec<VTable>, usize)> {
+    let entries = vtables_data
+        .as_array()
+        .ok_or_else(|| anyhow::anyhow!("vtables must be an array"))?;
+
+    let mut vtables = Vec::new();
+    let mut skipped = 0;
+    for entry in entries {
+        let address = entry
+            .get("address")
+            .and_then(|v| v.as_str())
+            .and_then(parse_address)
+            .ok_or_else(|| anyhow::anyhow!("VTable missing a valid address"))?;
+        let class_name =
+            optional_text(entry, "class_name").or_else(|| optional_text(entry, "name"));
+        let mut vtable = VTable::new(binary_hash, address, class_name);
+
+        let slots = entry
+            .get("slots")
+            .and_then(|v| v.as_array())
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        for (position, slot) in slots.iter().enumerate() {
+            let Some(slot_address) = slot
+                .as_str()
+                .or_else(|| slot.get("address").and_then(|v| v.as_str()))
+            else {
+                continue;
+            };
+            let index = slot
+                .get("index")
+                .and_then(|v| v.as_u64())
+                .map_or(position, |index| index as usize);
+            let uid = normalize_address(slot_address)
+                .and_then(|normalized| address_to_uid.get(&normalized))
+                .or_else(|| address_to_uid.get(slot_address));
+            match uid {
+                Some(uid) => vtable.slots.push((index, uid.clone())),
+                None => skipped += 1,
+            }
+        }
+
+        vtable.bases = text_list(entry, "bases")
+            .into_iter()
+            .chain(optional_text(entry, "base"))
+            .filter_map(|base| parse_address(&base))
+            .map(|base| vtable_uid(binary_hash, base))
+            .collect();
+        vtables.push(vtable);
+    }
+
+    Ok((vtables, skipped))
+}
+
+/// `source` and `confidence` of a call or data reference entry; unknown sources are
+/// treated as static analysis
+fn edge_provenance(entry: &Value) -> (EdgeSource, Option<f64>) {
+    let source = entry
+        .get("source")
+        .and_then(|v| v.as_str())
+        .and_then(|name| EdgeSource::from_str(name).ok())
+        .unwrap_or_default();
+    let confidence = entry.get("confidence").and_then(|v| v.as_f64());
+    (source, confidence)
+}
+
+fn optional_text(object: &Value, key: &str) -> Option<String> {
+    match object.get(key)? {
+        Value::String(text) if !text.trim().is_empty() => Some(text.trim().to_string()),
+        Value::Number(number) => Some(number.to_string()),
+        _ => None,
+    }
+}
+
+fn parse_relation(entry: &Value) -> Result<BinaryRelation> {
+    let (parent, child, relation, source) = match entry {
+        Value::Array(tuple) => (
+            tuple.first().and_then(|v| v.as_str()),
+            tuple.get(1).and_then(|v| v.as_str()),
+            tuple.get(2).and_then(|v| v.as_str()),
+            None,
+        ),
+        _ => (
+            entry.get("parent_sha256").and_then(|v| v.as_str()),
+            entry.get("child_sha256").and_then(|v| v.as_str()),
+            entry.get("relation").and_then(|v| v.as_str()),
+            optional_text(entry, "source"),
+        ),
+    };
+
+    let hash = |value: Option<&str>, field: &str| {
+        value
+            .map(|v| v.trim().to_lowercase())
+            .filter(|v| v.len() == 64 && v.chars().all(|c| c.is_ascii_hexdigit()))
+            .ok_or_else(|| anyhow::anyhow!("{} must be a SHA-256 hash", field))
+    };
+    let parent_sha256 = hash(parent, "parent_sha256")?;
+    let child_sha256 = hash(child, "child_sha256")?;
+    if parent_sha256 == child_sha256 {
+        return Err(anyhow::anyhow!("parent and child are the same sample"));
+    }
+
+    let relation = relation
+        .ok_or_else(|| anyhow::anyhow!("Missing relation"))?
+        .parse::<BinaryRelationType>()
+        .map_err(|e| anyhow::anyhow!(e))?;
+
+    Ok(BinaryRelation {
+        parent_sha256,
+        child_sha256,
+        relation,
+        source,
+    })
+}
+
+/// Read a function's `prototype` object, or the same fields on the function itself:
+/// `{calling_convention, return_type, parameters: ["int" | {name, type}]}`
+fn parse_prototype(func_data: &Value) -> Option<FunctionPrototype> {
+    let source = func_data
+        .get("prototype")
+        .filter(|v| v.is_object())
+        .unwrap_or(func_data);
+    let parameters = source
+        .get("parameters")
+        .or_else(|| source.get("params"))
+        .and_then(|v| v.as_array());
+
+    let prototype = FunctionPrototype {
+        calling_convention: optional_text(source, "calling_convention")
+            .or_else(|| optional_text(source, "cc")),
+        return_type: optional_text(source, "return_type"),
+        parameters: parameters
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|param| match param {
+                Value::String(r#type) => Some(Parameter {
+                    name: String::new(),
+                    r#type: r#type.trim().to_string(),
+                }),
+                _ => Some(Parameter {
+                    name: optional_text(param, "name").unwrap_or_default(),
+                    r#type: optional_text(param, "type")?,
+                }),
+            })
+            .collect(),
+    };
+
+    // An empty parameter list is a recorded `(void)` prototype
+    (parameters.is_some() || prototype != FunctionPrototype::default()).then_some(prototype)
+}
+
+/// Calls from each function to the APIs whose hashes appear among its constants
+fn hashed_api_calls<'a>(
+    functions_data: &Value,
+    address_to_uid: &HashMap<String, String>,
+    lookup: impl Fn(u64) -> Option<&'a HashedApi>,
+) -> Vec<HashedApiCall> {
+    let mut calls = Vec::new();
+    let mut seen = HashSet::new();
+    for func_data in functions_data
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+    {
+        let Some(caller_uid) = func_data
+            .get("address")
+            .and_then(|v| v.as_str())
+            .and_then(normalize_address)
+            .and_then(|address| address_to_uid.get(&address))
+        else {
+            continue;
+        };
+        for hash in parse_constants(func_data) {
+            let Some(api) = lookup(hash) else {
+                continue;
+            };
+            if seen.insert((caller_uid, hash)) {
+                calls.push(HashedApiCall {
+                    caller_uid: caller_uid.clone(),
+                    hash,
+                    library: api.library.clone(),
+                    api: api.name.clone(),
+                });
+            }
+        }
+    }
+    calls
+}
+
+/// Read a function's `constants` (or `immediates`) as numbers or numeric strings
+///
+/// Negative 32-bit values, as some extractors print signed immediates, are read as
+/// their unsigned form.
+fn parse_constants(func_data: &Value) -> Vec<u64> {
+    func_data
+        .get("constants")
+        .or_else(|| func_data.get("immediates"))
+        .and_then(|v| v.as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|constant| match constant {
+            Value::String(text) => parse_address(text),
+            _ => constant.as_u64().or_else(|| {
+                constant
+                    .as_i64()
+                    .filter(|value| *value >= i64::from(i32::MIN))
+                    .map(|value| u64::from(value as i32 as u32))
+            }),
+        })
+        .collect()
+}
+
+/// `chunks` of a function as `{start, size}` or `{start, end}`; the main body, which IDA
+/// lists as the first chunk, and chunks without a parseable range are left out
+fn parse_chunks(func_data: &Value, address: u64) -> Vec<FunctionChunk> {
+    let chunks = func_data.get("chunks").and_then(Value::as_array);
+    chunks
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|chunk| {
+            let bound = |key: &str| match chunk.get(key)? {
+                Value::String(text) => parse_address(text),
+                value => value.as_u64(),
+            };
+            let start = bound("start").or_else(|| bound("address"))?;
+            let size = match chunk.get("size").and_then(Value::as_u64) {
+                Some(size) => size,
+                None => bound("end")?.checked_sub(start)?,
+            };
+            (start != address && size > 0).then(|| FunctionChunk {
+                start: format_address(start),
+                size,
+            })
+        })
+        .collect()
+}
+
+/// Read `mnemonic_histogram` (or `opcode_counts`) as an object of mnemonic to count
+fn parse_mnemonic_histogram(func_data: &Value) -> Option<MnemonicHistogram> {
+    let counts = func_data
+        .get("mnemonic_histogram")
+        .or_else(|| func_data.get("opcode_counts"))?
+        .as_object()?;
+
+    let histogram = MnemonicHistogram(
+        counts
+            .iter()
+            .filter_map(|(mnemonic, count)| {
+                let mnemonic = mnemonic.trim().to_lowercase();
+                let count = count.as_u64()?;
+                (!mnemonic.is_empty() && count > 0).then_some((mnemonic, count))
+            })
+            .collect(),
+    );
+
+    (!histogram.is_empty()).then_some(histogram)
+}
+
+/// Read `binary_info.sections`, skipping sections without an entropy value
+fn parse_sections(binary_info: &Value) -> Vec<Section> {
+    let Some(sections) = binary_info.get("sections").and_then(|v| v.as_array()) else {
+        return Vec::new();
+    };
+
+    sections
+        .iter()
+        .filter_map(|section| {
+            Some(Section {
+                name: optional_text(section, "name").unwrap_or_default(),
+                size: section
+                    .get("size")
+                    .or_else(|| section.get("raw_size"))
+                    .and_then(|v| v.as_u64()),
+                entropy: section.get("entropy")?.as_f64()?,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_indirect_calls() {
+        let indirect = json!([{
+            "from_address": "0x401000",
+            "offset": "0x401050",
+            "targets": ["0x402000", { "address": "0x403000", "confidence": 0.6 }],
+            "resolved_by": "emulation"
+        }]);
+
+        let calls = indirect_calls(&indirect).unwrap();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0]["to_address"], "0x402000");
+        assert_eq!(calls[0]["type"], "indirect");
+        assert_eq!(edge_provenance(&calls[0]), (EdgeSource::Dynamic, None));
+        assert_eq!(edge_provenance(&calls[1]), (EdgeSource::Dynamic, Some(0.6)));
+        assert!(indirect_calls(&json!([{ "offset": "0x1", "targets": [] }])).is_err());
+    }
+
+    #[test]
+    fn test_hashed_api_calls() {
+        let hashes = ApiHashes::parse(&json!({
+            "0x7c0dfcaa": "kernel32!GetProcAddress",
+            "0xec0e4e8e": "LoadLibraryA"
+        }))
+        .unwrap();
+        let address_to_uid = HashMap::from([("0x401000".to_string(), "abc:0x401000".to_string())]);
+        let functions = json!([
+            { "address": "0x401000", "constants": ["0x7c0dfcaa", "0x7c0dfcaa", 3960360590u64, 1] },
+            { "address": "0x402000", "constants": ["0x7c0dfcaa"] }
+        ]);
+
+        let calls = hashed_api_calls(&functions, &address_to_uid, |hash| hashes.get(hash));
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].api, "GetProcAddress");
+        assert_eq!(calls[0].library.as_deref(), Some("kernel32.dll"));
+        assert_eq!(
+            (calls[1].hash, calls[1].library.as_deref()),
+            (0xec0e_4e8e, None)
+        );
+    }
+
+    #[test]
+    fn test_parse_constants() {
+        let constants = parse_constants(&json!({
+            "constants": ["0xC3D2E1F0", 1518500249, -1009589776, "n/a", 1.5]
+        }));
+        assert_eq!(constants, vec![0xc3d2_e1f0, 0x5a82_7999, 0xc3d2_e1f0]);
+        assert_eq!(detect_crypto(&constants), vec!["sha1"]);
+        assert!(parse_constants(&json!({ "name": "f" })).is_empty());
+    }
+
+    #[test]
+    fn test_parse_prototype() {
+        let prototype = parse_prototype(&json!({
+            "name": "copy",
+            "prototype": {
+                "calling_convention": "__stdcall",
+                "return_type": "BOOL",
+                "parameters": [{ "name": "src", "type": "wchar_t *" }, "int", { "name": "x" }]
+            }
+        }))
+        .unwrap();
+        assert_eq!(prototype.calling_convention.as_deref(), Some("__stdcall"));
+        assert_eq!(prototype.parameters.len(), 2);
+        assert_eq!(prototype.parameters[1].r#type, "int");
+
+        let flat = parse_prototype(&json!({ "return_type": "void", "parameters": [] })).unwrap();
+        assert!(flat.parameters.is_empty());
+        assert_eq!(parse_prototype(&json!({ "name": "f" })), None);
+    }
+
+    #[test]
+    fn test_error_policy() {
+        let policy = ErrorPolicy::new(1);
+        let mut errors = ImportErrors {
+            errors: Vec::new(),
+            policy: Some(&policy),
+        };
+        assert!(errors.push("first".to_string()).is_ok());
+        assert!(!policy.exceeded());
+
+        // Clones share the count
+        let shared = policy.clone();
+        let err = errors.push("second".to_string()).unwrap_err();
+        assert!(shared.exceeded());
+        assert_eq!(crate::error::details(&err), ["first", "second"]);
+
+        let fail_fast = ErrorPolicy::new(0);
+        assert!(!fail_fast.record(1));
+    }
+
+    #[test]
+    fn test_duplicate_evidence() {
+        let binary = |hash: &str, imphash: &str, ssdeep: &str| -> Binary {
+            serde_json::from_value(json!({
+                "hash": hash, "filename": format!("{}.exe", hash), "file_path": "",
+                "file_size": 0, "format": "PE", "arch": "x86", "ssdeep": ssdeep,
+                "pe_info": { "imphash": imphash }
+            }))
+            .unwrap()
+        };
+        let original = binary("a", "f34d", "96:s4Ud1Lj96tHHlZDrwciQmA:s4Ud1L");
+        let appended = binary("b", "f34d", "96:s4Ud1Lj96tHHlZDrwciQmA:s4Ud1L");
+        let other = binary("c", "0bad", "3:AXGBicFlgVNhBGcL6wCrFQEv:AXGHsNhxLsr2C");
+
+        assert_eq!(
+            duplicate_evidence(&appended, &original),
+            vec!["same imphash", "ssdeep 100"]
+        );
+        assert!(duplicate_evidence(&other, &original).is_empty());
+    }
+
+    #[test]
+    fn test_parse_entry_points() {
+        let data = json!({
+            "entry_points": {
+                "main": "0x401200",
+                "tls_callbacks": ["0x401000", "0x401080"],
+                "seh_handlers": ["0x401300"]
+            },
+            "exports": [{ "name": "_DllMain@12", "address": "0x401400" }]
+        });
+        let pe_info = PeInfo {
+            entry_point: Some("0x401000".to_string()),
+            ..Default::default()
+        };
+
+        let entry_points = parse_entry_points(&data, Some(&pe_info)).unwrap();
+        assert_eq!(
+            entry_points,
+            vec![
+                ("0x401200".to_string(), EntryKind::Main),
+                ("0x401300".to_string(), EntryKind::ExceptionHandler),
+                ("0x401000".to_string(), EntryKind::TlsCallback),
+                ("0x401080".to_string(), EntryKind::TlsCallback),
+                ("0x401400".to_string(), EntryKind::DllMain),
+            ]
+        );
+
+        let listed =
+            json!({ "entry_points": ["0x401000", { "address": "0x402000", "kind": "tls" }] });
+        assert_eq!(
+            parse_entry_points(&listed, None).unwrap(),
+            vec![
+                ("0x401000".to_string(), EntryKind::Entry),
+                ("0x402000".to_string(), EntryKind::TlsCallback),
+            ]
+        );
+        assert!(parse_entry_points(&json!({ "entry_points": { "oops": "0x1" } }), None).is_err());
+    }
+
+    #[test]
+    fn test_parse_vtables() {
+        let address_to_uid = HashMap::from([
+            ("0x401000".to_string(), "abc:0x401000".to_string()),
+            ("0x402000".to_string(), "abc:0x402000".to_string()),
+        ]);
+        let vtables = json!([{
+            "address": "0x40a2c8",
+            "class_name": "Derived",
+            "slots": ["0x401000", null, { "index": 3, "address": "0x402000" }, "0x409999"],
+            "bases": ["0x40a2a0"]
+        }]);
+
+        let (vtables, skipped) = parse_vtables(&vtables, "abc", &address_to_uid).unwrap();
+        assert_eq!(skipped, 1);
+        assert_eq!(vtables[0].uid, "vtable:abc:0x40a2c8");
+        assert_eq!(vtables[0].class_name.as_deref(), Some("Derived"));
+        assert_eq!(
+            vtables[0].slots,
+            vec![
+                (0, "abc:0x401000".to_string()),
+                (3, "abc:0x402000".to_string())
+            ]
+        );
+        assert_eq!(vtables[0].bases, vec!["vtable:abc:0x40a2a0".to_string()]);
+        assert!(parse_vtables(&json!([{ "slots": [] }]), "abc", &address_to_uid).is_err());
+    }
+
+    #[test]
+    fn test_expand_call_graph() {
+        let mut lib = Function::create_internal("h", 0x1200, "memcpy", false);
+        lib.is_library = true;
+        let functions: BTreeMap<String, Function> = [
+            Function::create_internal("h", 0x1000, "main", false),
+            Function::create_internal("h", 0x1100, "parse", false),
+            Function::create_internal("h", 0x1300, "decode", false),
+            lib,
+        ]
+        .into_iter()
+        .map(|f| (f.uid.clone(), f))
+        .collect();
+        let uid = |address: u64| function_uid("h", address);
+        let link = |from: u64, to: u64, confidence: f64| CallGraphLink {
+            from_uid: uid(from),
+            to_uid: uid(to),
+            count: 1,
+            offsets: vec![format!("0x{:x}", from + 4)],
+            call_type: None,
+            source: None,
+            confidence,
+        };
+        // main -> parse -> decode, parse -> memcpy (library), main -> decode (weak)
+        let links = [
+            link(0x1000, 0x1100, 1.0),
+            link(0x1100, 0x1300, 1.0),
+            link(0x1100, 0x1200, 1.0),
+            link(0x1000, 0x1300, 0.2),
+        ];
+
+        let graph = expand_call_graph(&functions, &links, "main", 2, true, 0.5);
+        let callees: Vec<&str> = graph.callees.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(callees, vec!["parse", "decode"]);
+        assert!(graph.callers.is_empty());
+        assert_eq!(graph.edges[1].depth, 2);
+        assert_eq!(graph.edges[1].from_type, "Internal");
+
+        // decode is reached directly once weak calls are followed
+        let graph = expand_call_graph(&functions, &links, "main", 1, false, 0.0);
+        assert_eq!(graph.callees.len(), 2);
+        assert!(graph.edges.iter().all(|edge| edge.depth == 1));
+
+        let graph = expand_call_graph(&functions, &links, &uid(0x1300), 3, false, 0.5);
+        let callers: Vec<&str> = graph.callers.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(callers, vec!["parse", "main"]);
+        assert_eq!(graph.edges.iter().map(|e| e.depth).max(), Some(2));
     }
 }
@@ -0,0 +1,101 @@
+use anyhow::Result;
+use std::path::Path;
+
+/// Local list of known library function names
+///
+/// One exact name or `*` wildcard pattern per line; blank lines and `#` comments are
+/// ignored. Used to flag statically linked library code when the extractor does not
+/// report FLIRT matches itself.
+#[derive(Debug, Clone, Default)]
+pub struct LibrarySignatures {
+    patterns: Vec<String>,
+}
+
+impl LibrarySignatures {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            anyhow::anyhow!(
+                "Failed to read library signatures '{}': {}",
+                path.display(),
+                e
+            )
+        })?;
+        Ok(Self::parse(&text))
+    }
+
+    pub fn parse(text: &str) -> Self {
+        Self {
+            patterns: text
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(str::to_string)
+                .collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.patterns.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// First pattern matching `name`
+    pub fn matching(&self, name: &str) -> Option<&str> {
+        self.patterns
+            .iter()
+            .find(|pattern| wildcard_match(pattern, name))
+            .map(String::as_str)
+    }
+}
+
+/// Glob match where `*` matches any run of characters
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let Some((first, rest)) = pattern.split_once('*') else {
+        return pattern == text;
+    };
+    let Some(mut remaining) = text.strip_prefix(first) else {
+        return false;
+    };
+
+    let mut parts: Vec<&str> = rest.split('*').collect();
+    let last = parts.pop().unwrap_or("");
+    for part in parts {
+        match remaining.find(part) {
+            Some(index) => remaining = &remaining[index + part.len()..],
+            None => return false,
+        }
+    }
+
+    remaining.len() >= last.len() && remaining.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature_matching() {
+        let signatures = LibrarySignatures::parse(
+            "# CRT\n__security_check_cookie\n_crt*\n*_init*term*\n\nmem*y\n",
+        );
+
+        assert_eq!(signatures.len(), 4);
+        assert_eq!(
+            signatures.matching("__security_check_cookie"),
+            Some("__security_check_cookie")
+        );
+        assert_eq!(signatures.matching("_crtStartup"), Some("_crt*"));
+        assert_eq!(
+            signatures.matching("__scrt_initterm_e"),
+            Some("*_init*term*")
+        );
+        assert_eq!(signatures.matching("memcpy"), Some("mem*y"));
+        assert_eq!(signatures.matching("memy"), Some("mem*y"));
+        assert_eq!(signatures.matching("main"), None);
+        assert_eq!(signatures.matching("__security_check_cookie2"), None);
+    }
+}
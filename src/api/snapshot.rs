@@ -0,0 +1,150 @@
+use anyhow::Result;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// Binaries, functions and relationships of a `database export` file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Snapshot {
+    /// Binary hashes
+    pub binaries: BTreeSet<String>,
+    /// Function UIDs
+    pub functions: BTreeSet<String>,
+    /// `(source, type, target)` keyed by UID, or hash/name for nodes without one
+    pub relationships: BTreeSet<(String, String, String)>,
+}
+
+/// What changed from one snapshot to a later one
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SnapshotDiff {
+    pub added_binaries: Vec<String>,
+    pub removed_binaries: Vec<String>,
+    pub added_functions: Vec<String>,
+    pub removed_functions: Vec<String>,
+    pub added_relationships: Vec<(String, String, String)>,
+    pub removed_relationships: Vec<(String, String, String)>,
+}
+
+impl SnapshotDiff {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl Snapshot {
+    /// Read a JSON or JSON Lines export
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)?;
+        let records: Vec<Value> = if content.trim_start().starts_with('[') {
+            serde_json::from_str(&content)?
+        } else {
+            content
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(serde_json::from_str)
+                .collect::<serde_json::Result<_>>()?
+        };
+        Ok(Self::from_records(&records))
+    }
+
+    /// Build from export records: `{node, relationship_type, target}`
+    pub fn from_records(records: &[Value]) -> Self {
+        let mut snapshot = Self::default();
+        for record in records {
+            let Some(node) = record.get("node") else {
+                continue;
+            };
+            snapshot.add_node(node);
+
+            let target = record.get("target");
+            let rel_type = record.get("relationship_type").and_then(|v| v.as_str());
+            if let (Some(target), Some(rel_type)) = (target, rel_type) {
+                snapshot.add_node(target);
+                if let (Some(source), Some(target)) = (node_key(node), node_key(target)) {
+                    snapshot
+                        .relationships
+                        .insert((source, rel_type.to_string(), target));
+                }
+            }
+        }
+        snapshot
+    }
+
+    fn add_node(&mut self, node: &Value) {
+        let has_label = |label: &str| {
+            node.get("labels")
+                .and_then(|v| v.as_array())
+                .is_some_and(|labels| labels.iter().any(|l| l.as_str() == Some(label)))
+        };
+        if has_label("Binary") {
+            if let Some(hash) = node.get("hash").and_then(|v| v.as_str()) {
+                self.binaries.insert(hash.to_string());
+            }
+        } else if has_label("Function") {
+            if let Some(uid) = node.get("uid").and_then(|v| v.as_str()) {
+                self.functions.insert(uid.to_string());
+            }
+        }
+    }
+
+    /// Changes from `self` (older) to `other` (newer)
+    pub fn diff(&self, other: &Snapshot) -> SnapshotDiff {
+        fn changes<T: Ord + Clone>(from: &BTreeSet<T>, to: &BTreeSet<T>) -> Vec<T> {
+            to.difference(from).cloned().collect()
+        }
+
+        SnapshotDiff {
+            added_binaries: changes(&self.binaries, &other.binaries),
+            removed_binaries: changes(&other.binaries, &self.binaries),
+            added_functions: changes(&self.functions, &other.functions),
+            removed_functions: changes(&other.functions, &self.functions),
+            added_relationships: changes(&self.relationships, &other.relationships),
+            removed_relationships: changes(&other.relationships, &self.relationships),
+        }
+    }
+}
+
+/// Identity of an exported node: its UID, else its hash, else its name
+fn node_key(node: &Value) -> Option<String> {
+    ["uid", "hash", "name"]
+        .iter()
+        .find_map(|key| node.get(key).and_then(|v| v.as_str()))
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_snapshot_diff() {
+        let binary = json!({ "labels": ["Binary"], "hash": "abc", "name": "a.exe" });
+        let main = json!({ "labels": ["Function"], "uid": "abc:0x401000", "name": "main" });
+        let helper = json!({ "labels": ["Function"], "uid": "abc:0x402000", "name": "helper" });
+
+        let before = Snapshot::from_records(&[
+            json!({ "node": binary, "relationship_type": "CONTAINS", "target": main }),
+            json!({ "node": main }),
+        ]);
+        let after = Snapshot::from_records(&[
+            json!({ "node": binary, "relationship_type": "CONTAINS", "target": helper }),
+            json!({ "node": helper }),
+        ]);
+
+        let diff = before.diff(&after);
+        assert!(diff.added_binaries.is_empty() && diff.removed_binaries.is_empty());
+        assert_eq!(diff.added_functions, vec!["abc:0x402000"]);
+        assert_eq!(diff.removed_functions, vec!["abc:0x401000"]);
+        assert_eq!(
+            diff.added_relationships,
+            vec![(
+                "abc".to_string(),
+                "CONTAINS".to_string(),
+                "abc:0x402000".to_string()
+            )]
+        );
+        assert!(before.diff(&before).is_empty());
+    }
+}
@@ -0,0 +1,153 @@
+use anyhow::Result;
+use serde_json::Value;
+
+/// Parse an address-to-name symbol map
+///
+/// Accepted inputs:
+/// - JSON: an object `{"0x401000": "name"}` or an array of `{address, name}` objects
+/// - `nm` output: `0000000000401000 T name` (undefined `U` symbols are skipped)
+/// - MSVC linker maps: `0001:00000000  _name  00401000 f  module.obj`
+/// - plain `address name` or `address,name` lines
+///
+/// Lines that match none of these are ignored; later entries for the same address win.
+pub fn parse_symbol_map(text: &str) -> Result<Vec<(u64, String)>> {
+    let trimmed = text.trim_start();
+    if trimmed.starts_with('{') || trimmed.starts_with('[') {
+        return parse_json_map(&serde_json::from_str(trimmed)?);
+    }
+
+    Ok(text.lines().filter_map(parse_line).collect())
+}
+
+fn parse_json_map(value: &Value) -> Result<Vec<(u64, String)>> {
+    let symbols: Vec<(u64, String)> = match value {
+        Value::Object(map) => map
+            .iter()
+            .filter_map(|(address, name)| Some((parse_hex(address)?, name.as_str()?)))
+            .map(|(address, name)| (address, name.trim().to_string()))
+            .collect(),
+        Value::Array(entries) => entries
+            .iter()
+            .filter_map(|entry| {
+                let address = entry.get("address")?;
+                let address = match address {
+                    Value::Number(n) => n.as_u64()?,
+                    _ => parse_hex(address.as_str()?)?,
+                };
+                let name = entry.get("name")?.as_str()?.trim();
+                Some((address, name.to_string()))
+            })
+            .collect(),
+        _ => return Err(anyhow::anyhow!("Symbol map must be a JSON object or array")),
+    };
+
+    Ok(symbols
+        .into_iter()
+        .filter(|(_, name)| !name.is_empty())
+        .collect())
+}
+
+fn parse_line(line: &str) -> Option<(u64, String)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+
+    if let Some((address, name)) = line.split_once(',') {
+        if let Some(address) = parse_hex(address.trim()) {
+            return symbol(address, name);
+        }
+    }
+
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    match tokens.as_slice() {
+        // MSVC map: section:offset, name, Rva+Base, ...
+        [section, name, address, ..] if is_section_offset(section) => {
+            symbol(u64::from_str_radix(address, 16).ok()?, name)
+        }
+        // nm: address, symbol type, name (demangled names may contain spaces)
+        [address, kind, _, ..] if kind.len() == 1 => {
+            if kind.eq_ignore_ascii_case("u") {
+                return None;
+            }
+            symbol(parse_hex(address)?, rest_after(line, 2))
+        }
+        [address, _, ..] => symbol(parse_hex(address)?, rest_after(line, 1)),
+        _ => None,
+    }
+}
+
+fn symbol(address: u64, name: &str) -> Option<(u64, String)> {
+    let name = name.trim();
+    (!name.is_empty()).then(|| (address, name.to_string()))
+}
+
+/// Remainder of `line` after its first `count` whitespace-separated tokens
+fn rest_after(line: &str, count: usize) -> &str {
+    let mut rest = line.trim_start();
+    for _ in 0..count {
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        rest = rest[end..].trim_start();
+    }
+    rest
+}
+
+/// Symbol files list bare hex addresses, so digits-only strings are hex here too
+fn parse_hex(address: &str) -> Option<u64> {
+    let digits = address
+        .strip_prefix("0x")
+        .or_else(|| address.strip_prefix("0X"))
+        .unwrap_or(address);
+    u64::from_str_radix(digits, 16).ok()
+}
+
+fn is_section_offset(token: &str) -> bool {
+    token.split_once(':').is_some_and(|(section, offset)| {
+        !section.is_empty()
+            && section.chars().all(|c| c.is_ascii_hexdigit())
+            && offset.len() == 8
+            && offset.chars().all(|c| c.is_ascii_hexdigit())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_text_formats() {
+        let text = "\
+# nm output
+0000000000401000 T main
+                 U printf
+0000000000401100 t helper
+ 0001:00000200       _WinMain@16                00401200 f   main.obj
+0x401300,decrypt
+401400 plain_name
+0000000000401500 T ns::Foo::bar(int, char)
+";
+        let symbols = parse_symbol_map(text).unwrap();
+        assert_eq!(
+            symbols,
+            vec![
+                (0x401000, "main".to_string()),
+                (0x401100, "helper".to_string()),
+                (0x401200, "_WinMain@16".to_string()),
+                (0x401300, "decrypt".to_string()),
+                (0x401400, "plain_name".to_string()),
+                (0x401500, "ns::Foo::bar(int, char)".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_json_formats() {
+        let symbols = parse_symbol_map(r#"{"0x401000": "main", "zzz": "x"}"#).unwrap();
+        assert_eq!(symbols, vec![(0x401000, "main".to_string())]);
+
+        let symbols =
+            parse_symbol_map(r#"[{"address": 4198400, "name": "main"}, {"address": "0x10"}]"#)
+                .unwrap();
+        assert_eq!(symbols, vec![(0x401000, "main".to_string())]);
+    }
+}
@@ -0,0 +1,211 @@
+use anyhow::Result;
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use crate::models::RuntimeApiCall;
+
+/// Runtime API calls of one sample extracted from a sandbox report
+#[derive(Debug, Clone)]
+pub struct ApiTrace {
+    /// SHA-256 of the analyzed sample, when the report names it
+    pub sha256: Option<String>,
+    /// Calls aggregated per API, ordered by first occurrence
+    pub calls: Vec<RuntimeApiCall>,
+}
+
+/// Parse a Cuckoo/CAPE report or a simple `{sha256, calls: [{api, count, first_ts}]}` trace
+///
+/// Cuckoo/CAPE reports are read from `behavior.processes[].calls[]`; when the report
+/// only carries `behavior.apistats`, counts are taken from there without timestamps.
+pub fn parse_trace(report: &Value) -> Result<ApiTrace> {
+    let sha256 = report
+        .pointer("/target/file/sha256")
+        .or_else(|| report.get("sha256"))
+        .and_then(|v| v.as_str())
+        .map(|v| v.trim().to_lowercase());
+
+    let calls = if let Some(behavior) = report.get("behavior") {
+        parse_behavior(behavior)
+    } else if let Some(calls) = report.get("calls").and_then(|v| v.as_array()) {
+        parse_aggregated_calls(calls)
+    } else {
+        return Err(anyhow::anyhow!(
+            "Trace has neither a behavior section nor a calls array"
+        ));
+    };
+
+    Ok(ApiTrace { sha256, calls })
+}
+
+fn parse_behavior(behavior: &Value) -> Vec<RuntimeApiCall> {
+    let mut calls = CallAggregator::default();
+
+    let processes = behavior
+        .get("processes")
+        .and_then(|v| v.as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    for call in processes
+        .iter()
+        .filter_map(|process| process.get("calls").and_then(|v| v.as_array()))
+        .flatten()
+    {
+        let Some(api) = call.get("api").and_then(|v| v.as_str()) else {
+            continue;
+        };
+        // CAPE reports `timestamp` strings, Cuckoo reports epoch `time` values
+        let timestamp = call
+            .get("timestamp")
+            .or_else(|| call.get("time"))
+            .and_then(timestamp_text);
+        calls.record(api, 1, timestamp);
+    }
+
+    if calls.is_empty() {
+        if let Some(apistats) = behavior.get("apistats").and_then(|v| v.as_object()) {
+            for counts in apistats.values().filter_map(|v| v.as_object()) {
+                for (api, count) in counts {
+                    calls.record(api, count.as_u64().unwrap_or(0), None);
+                }
+            }
+        }
+    }
+
+    calls.finish()
+}
+
+fn parse_aggregated_calls(entries: &[Value]) -> Vec<RuntimeApiCall> {
+    let mut calls = CallAggregator::default();
+    for entry in entries {
+        let Some(api) = entry.get("api").and_then(|v| v.as_str()) else {
+            continue;
+        };
+        let count = entry.get("count").and_then(|v| v.as_u64()).unwrap_or(1);
+        let timestamp = entry.get("first_ts").and_then(timestamp_text);
+        calls.record(api, count, timestamp);
+    }
+    calls.finish()
+}
+
+fn timestamp_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(text) if !text.trim().is_empty() => Some(text.trim().to_string()),
+        Value::Number(number) => Some(number.to_string()),
+        _ => None,
+    }
+}
+
+/// Numeric timestamps compare by value, anything else lexically
+fn compare_timestamps(a: &str, b: &str) -> Ordering {
+    match (a.parse::<f64>(), b.parse::<f64>()) {
+        (Ok(x), Ok(y)) => x.total_cmp(&y),
+        _ => a.cmp(b),
+    }
+}
+
+#[derive(Default)]
+struct CallAggregator {
+    index: HashMap<String, usize>,
+    calls: Vec<RuntimeApiCall>,
+}
+
+impl CallAggregator {
+    fn is_empty(&self) -> bool {
+        self.calls.is_empty()
+    }
+
+    fn record(&mut self, api: &str, count: u64, timestamp: Option<String>) {
+        let api = api.trim();
+        if api.is_empty() || count == 0 {
+            return;
+        }
+
+        let position = *self.index.entry(api.to_string()).or_insert_with(|| {
+            self.calls.push(RuntimeApiCall {
+                api: api.to_string(),
+                count: 0,
+                first_ts: None,
+            });
+            self.calls.len() - 1
+        });
+
+        let call = &mut self.calls[position];
+        call.count += count;
+        if let Some(timestamp) = timestamp {
+            let earlier = call
+                .first_ts
+                .as_deref()
+                .is_none_or(|first| compare_timestamps(&timestamp, first) == Ordering::Less);
+            if earlier {
+                call.first_ts = Some(timestamp);
+            }
+        }
+    }
+
+    fn finish(self) -> Vec<RuntimeApiCall> {
+        self.calls
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_cape_report() {
+        let report = json!({
+            "target": { "file": { "sha256": "ABC" } },
+            "behavior": {
+                "processes": [
+                    { "calls": [
+                        { "api": "CreateFileW", "timestamp": "2024-01-01 10:00:02,100" },
+                        { "api": "WriteFile", "timestamp": "2024-01-01 10:00:03,000" }
+                    ] },
+                    { "calls": [
+                        { "api": "CreateFileW", "timestamp": "2024-01-01 10:00:01,500" }
+                    ] }
+                ]
+            }
+        });
+
+        let trace = parse_trace(&report).unwrap();
+        assert_eq!(trace.sha256.as_deref(), Some("abc"));
+        assert_eq!(trace.calls.len(), 2);
+        assert_eq!(trace.calls[0].api, "CreateFileW");
+        assert_eq!(trace.calls[0].count, 2);
+        assert_eq!(
+            trace.calls[0].first_ts.as_deref(),
+            Some("2024-01-01 10:00:01,500")
+        );
+    }
+
+    #[test]
+    fn test_parse_cuckoo_times_and_apistats() {
+        let report = json!({
+            "behavior": { "processes": [ { "calls": [
+                { "api": "Sleep", "time": 1700000010.5 },
+                { "api": "Sleep", "time": 1700000002.25 }
+            ] } ] }
+        });
+        let trace = parse_trace(&report).unwrap();
+        assert_eq!(trace.sha256, None);
+        assert_eq!(trace.calls[0].first_ts.as_deref(), Some("1700000002.25"));
+
+        let report = json!({
+            "behavior": { "apistats": {
+                "1234": { "RegOpenKeyExW": 3 },
+                "5678": { "RegOpenKeyExW": 2, "Sleep": 1 }
+            } }
+        });
+        let trace = parse_trace(&report).unwrap();
+        let reg = trace
+            .calls
+            .iter()
+            .find(|c| c.api == "RegOpenKeyExW")
+            .unwrap();
+        assert_eq!(reg.count, 5);
+        assert_eq!(reg.first_ts, None);
+    }
+}
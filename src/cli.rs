@@ -1,13 +1,59 @@
-use clap::{Parser, Subcommand};
+use anyhow::Context;
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use clap::{Parser, Subcommand, ValueEnum};
 
+use crate::api::adapters::SourceFormat;
 use crate::commands;
+use crate::commands::export::{FeatureFormat, PerspectiveKind};
+use crate::commands::query::BatchQuery;
 use crate::config::Config;
+use crate::models::{
+    Architecture, EmbeddingAlgorithm, FeatureSpace, FunctionSort, ObjectKind, PathExpander,
+    XrefDirection,
+};
+use crate::neo4j::call_path_analyzer::DEFAULT_MAX_PATHS;
+use crate::neo4j::connection::{PlanMode, QueryTracing};
+use crate::neo4j::metrics;
+use crate::output::{OutputFormat, OutputOptions};
+use crate::store::Backend;
 
 #[derive(Parser)]
 pub struct Cli {
     #[arg(short, long, global = true)]
     pub config: Option<String>,
 
+    /// Write command output to a file instead of stdout (replaced only once the
+    /// command succeeds)
+    #[arg(short, long, global = true)]
+    pub output: Option<String>,
+
+    /// Overwrite an existing --output file
+    #[arg(long, global = true)]
+    pub force: bool,
+
+    /// Write the outcome (exit code, error kind, message and per-item errors) as JSON
+    #[arg(long, global = true, value_name = "FILE")]
+    pub errors_json: Option<String>,
+
+    /// Disable colored table output
+    #[arg(long, global = true)]
+    pub no_color: bool,
+
+    /// Work in the Neo4j database of this project instead of `neo4j_database`
+    /// (created by `database init --project <NAME>`)
+    #[arg(long, global = true, value_name = "NAME")]
+    pub project: Option<String>,
+
+    /// Answer GET /metrics in the Prometheus text format on this address while the
+    /// command runs
+    #[arg(long, global = true, value_name = "ADDR")]
+    pub metrics_listen: Option<String>,
+
+    /// Keep the graph in Neo4j or in the local `embedded_path` file (overrides
+    /// `backend` in the config)
+    #[arg(long, global = true, value_enum)]
+    pub backend: Option<Backend>,
+
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -21,40 +67,270 @@ pub enum Commands {
     },
     /// Query data from Neo4j
     Query {
+        /// Report the execution time and row count of each Cypher query
+        #[arg(long, global = true)]
+        timing: bool,
+        /// Print the Cypher statements with EXPLAIN instead of running them
+        #[arg(long, global = true, conflicts_with = "profile")]
+        explain: bool,
+        /// Run the queries and print each statement with PROFILE and its timing
+        #[arg(long, global = true)]
+        profile: bool,
         #[command(subcommand)]
         query_type: QueryType,
     },
+    /// Analyze the call graph of a binary
+    Analyze {
+        #[command(subcommand)]
+        analysis: AnalyzeType,
+    },
+    /// Record analyst names and comments
+    Annotate {
+        #[command(subcommand)]
+        target: AnnotateTarget,
+    },
+    /// Generate a self-contained HTML triage report for a binary
+    Report {
+        /// Binary hash or filename
+        binary: String,
+        /// Number of strings listed
+        #[arg(long, default_value = "25")]
+        strings: usize,
+        /// Maximum number of call graph edges embedded in the page
+        #[arg(long, default_value = "500")]
+        max_edges: usize,
+        /// JSON weighting table for the capability section, see `analyze risk`
+        #[arg(long, value_name = "FILE")]
+        profile: Option<String>,
+    },
+    /// Export data that cannot be rebuilt from extractor output
+    Export {
+        #[command(subcommand)]
+        export_type: ExportType,
+    },
     /// Database operations
     Database {
         #[command(subcommand)]
         db_action: DatabaseAction,
     },
+    /// Serve a read-only REST API over the graph (requires a build with `--features server`)
+    Serve {
+        /// Address and port to listen on
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        listen: String,
+        /// Also serve a web page drawing call graphs and sample-similarity graphs
+        #[arg(long)]
+        ui: bool,
+        /// Accept uploads at POST /api/jobs, kept in this directory and imported in the
+        /// background
+        #[arg(long, value_name = "DIR")]
+        import_queue: Option<String>,
+        /// Imports running at once
+        #[arg(long, default_value = "1", requires = "import_queue")]
+        import_workers: usize,
+        /// Jobs waiting for a worker (running imports not counted) above which uploads
+        /// are refused with 429
+        #[arg(long, default_value = "100", requires = "import_queue")]
+        max_queued: usize,
+        /// Also serve the gRPC API of proto/binaryx.proto on this address (needs a build
+        /// with `--features grpc`)
+        #[arg(long, value_name = "ADDR")]
+        grpc_listen: Option<String>,
+    },
 }
 
 #[derive(Subcommand)]
 pub enum ImportType {
-    /// Import JSON file
+    /// Import an extractor output file (BinaryX-IDA, Ghidra, radare2 or BinExport)
     Json {
         file_path: String,
-        #[arg(long, default_value = "1000")]
-        batch_size: usize,
+        /// Rows written per Neo4j statement (defaults to `batch_size` from the config)
+        #[arg(long)]
+        batch_size: Option<usize>,
+        /// Adapt batch sizes and concurrent writes to keep each write under --target-ms
+        #[arg(long)]
+        autotune: bool,
+        /// Target duration of one batched write when autotuning, in milliseconds
+        #[arg(long, default_value = "500", requires = "autotune")]
+        target_ms: u64,
         #[arg(long)]
         no_validate: bool,
+        /// Flag functions named in this file (one name or `*` pattern per line) as library code
+        #[arg(long, value_name = "FILE")]
+        library_signatures: Option<String>,
+        /// JSON map of API hash constants to `library!name`, linking the functions holding
+        /// them to the APIs (added to each file's `api_hashes` section)
+        #[arg(long, value_name = "FILE")]
+        api_hashes: Option<String>,
+        /// Extractor output format (detected from the file contents by default)
+        #[arg(long, value_enum, default_value_t = SourceFormat::Auto)]
+        source: SourceFormat,
+        /// On re-import of a binary, delete its functions, strings and calls missing from this data
+        #[arg(long)]
+        prune: bool,
+        /// Write a JSON summary of the run (statistics, skipped calls, timing and the
+        /// imported binary hash)
+        #[arg(long, value_name = "FILE")]
+        report: Option<String>,
+        /// Abort at the first failed item instead of collecting errors
+        #[arg(long, conflicts_with = "max_errors")]
+        fail_fast: bool,
+        /// Abort once more than N items failed; up to N failed items still exit successfully
+        #[arg(long, value_name = "N")]
+        max_errors: Option<usize>,
+        /// Reject the file if any address does not parse, instead of skipping the items
+        /// holding it
+        #[arg(long)]
+        strict_addresses: bool,
     },
-    /// Import directory of JSON files
+    /// Import a directory of extractor output files
     Directory {
         dir_path: String,
         #[arg(long, default_value = "*.json")]
         pattern: String,
-        #[arg(long, default_value = "1000")]
-        batch_size: usize,
+        /// Rows written per Neo4j statement (defaults to `batch_size` from the config)
+        #[arg(long)]
+        batch_size: Option<usize>,
+        /// Adapt batch sizes and concurrent writes to keep each write under --target-ms
+        #[arg(long)]
+        autotune: bool,
+        /// Target duration of one batched write when autotuning, in milliseconds
+        #[arg(long, default_value = "500", requires = "autotune")]
+        target_ms: u64,
+        /// Files written to Neo4j at once; writers merge shared strings and imports
+        /// concurrently, so keep 1 if Neo4j reports deadlocks
+        #[arg(long, default_value = "1")]
+        writers: usize,
+        /// Parsed files held in memory waiting for a writer
+        #[arg(long, default_value = "4")]
+        queue_size: usize,
         #[arg(long)]
         no_validate: bool,
+        /// Flag functions named in this file (one name or `*` pattern per line) as library code
+        #[arg(long, value_name = "FILE")]
+        library_signatures: Option<String>,
+        /// JSON map of API hash constants to `library!name`, linking the functions holding
+        /// them to the APIs (added to each file's `api_hashes` section)
+        #[arg(long, value_name = "FILE")]
+        api_hashes: Option<String>,
+        /// Extractor output format (detected from the file contents by default)
+        #[arg(long, value_enum, default_value_t = SourceFormat::Auto)]
+        source: SourceFormat,
+        /// On re-import of a binary, delete its functions, strings and calls missing from this data
+        #[arg(long)]
+        prune: bool,
+        /// Write a JSON summary of the run (per-file statistics, skipped calls, timing and
+        /// the imported binary hashes)
+        #[arg(long, value_name = "FILE")]
+        report: Option<String>,
+        /// Stop at the first failed item or file instead of collecting errors
+        #[arg(long, conflicts_with = "max_errors")]
+        fail_fast: bool,
+        /// Stop once more than N items failed across all files; up to N failed items
+        /// still exit successfully
+        #[arg(long, value_name = "N")]
+        max_errors: Option<usize>,
+        /// Reject files in which any address does not parse, instead of skipping the
+        /// items holding it
+        #[arg(long)]
+        strict_addresses: bool,
+    },
+    /// Parse a PE/ELF/Mach-O executable directly (headers, imports, exports, strings; no calls)
+    Binary {
+        file_path: String,
+        /// Shortest ASCII/UTF-16 run kept as a string
+        #[arg(long, default_value = "5")]
+        min_string_length: usize,
+        /// Flag functions named in this file (one name or `*` pattern per line) as library code
+        #[arg(long, value_name = "FILE")]
+        library_signatures: Option<String>,
+        /// On re-import of a binary, delete its functions, strings and calls missing from this data
+        #[arg(long)]
+        prune: bool,
+    },
+    /// Import DROPS/DOWNLOADS/EMBEDS relations between samples (e.g. from sandbox output)
+    Relations {
+        /// JSON array of {parent_sha256, child_sha256, relation} entries
+        file_path: String,
+    },
+    /// Import a sandbox API trace (Cuckoo/CAPE report) as CALLED_AT_RUNTIME edges
+    Trace {
+        file_path: String,
+        /// SHA-256 of the analyzed binary (defaults to the sample named in the report)
+        #[arg(long)]
+        binary: Option<String>,
+    },
+    /// Rename functions from an address-to-name map (PDB dump, nm output, MSVC map, JSON)
+    Symbols {
+        map_file: String,
+        /// Binary hash (or filename) whose functions are renamed
+        #[arg(long)]
+        binary: String,
+        /// Added to every map address, for maps listing RVAs (e.g. 0x400000)
+        #[arg(long, default_value = "0")]
+        image_base: String,
+    },
+    /// Import contacted domains, IPs and URLs from a sandbox report as CONTACTS edges
+    Network {
+        file_path: String,
+        /// SHA-256 of the analyzed binary (defaults to the sample named in the report)
+        #[arg(long)]
+        binary: Option<String>,
+    },
+    /// Restore analyst annotations written by `export annotations`
+    Annotations { file_path: String },
+    /// Import mutexes, named pipes, events and semaphores from a sandbox report
+    Objects {
+        file_path: String,
+        /// SHA-256 of the analyzed binary (defaults to the sample named in the report)
+        #[arg(long)]
+        binary: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ExportType {
+    /// Write analyst names, comments, symbol renames and families to a JSON file
+    Annotations { output_path: String },
+    /// Write a sparse binary × feature matrix (`binary,feature,value` rows) for
+    /// training models outside Neo4j
+    Features {
+        output_path: String,
+        #[arg(long, value_enum, default_value_t = FeatureSpace::Imports)]
+        space: FeatureSpace,
+        #[arg(long, value_enum, default_value_t = FeatureFormat::Csv)]
+        format: FeatureFormat,
+    },
+    /// Write the call graph of a binary as NetworkX node-link JSON
+    /// (`networkx.node_link_graph`), with the function attributes on the nodes
+    Networkx {
+        output_path: String,
+        /// Binary hash or filename
+        #[arg(long)]
+        binary: String,
+    },
+    /// Write a Neo4j Bloom perspective or Browser guide styled for the BinaryX schema,
+    /// with saved search phrases such as "Paths to API"
+    Perspective {
+        output_path: String,
+        #[arg(long, value_enum, default_value_t = PerspectiveKind::Bloom)]
+        kind: PerspectiveKind,
     },
 }
 
 #[derive(Subcommand)]
 pub enum QueryType {
+    /// Search binaries, functions, strings and libraries at once
+    Search {
+        /// Text searched in binary filenames and hashes, function names, string values
+        /// (fulltext) and library names
+        term: String,
+        /// Results shown per kind
+        #[arg(long, default_value = "20")]
+        limit: usize,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
     /// Query functions
     Functions {
         #[arg(long, default_value = "")]
@@ -63,8 +339,17 @@ pub enum QueryType {
         binary: Option<String>,
         #[arg(long, default_value = "100")]
         limit: usize,
-        #[arg(long, default_value = "table")]
-        format: String,
+        /// Order by a metric computed with `analyze metrics` (highest first)
+        #[arg(long, value_enum)]
+        sort: Option<FunctionSort>,
+        /// Only functions taking a parameter of this type (case and spacing ignored)
+        #[arg(long, conflicts_with = "sort")]
+        param_type: Option<String>,
+        /// Show prototypes, sizes and library matches
+        #[arg(long, short = 'v', conflicts_with = "sort")]
+        verbose: bool,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
     },
     /// Query strings (fulltext search)
     Strings {
@@ -78,15 +363,152 @@ pub enum QueryType {
         /// Treat pattern as a raw Lucene query
         #[arg(long)]
         raw: bool,
-        #[arg(long, default_value = "table")]
-        format: String,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
+    /// Search decompiled code of functions (fulltext search)
+    Pseudocode {
+        /// Search text (auto converted to a Lucene wildcard query unless --raw is set)
+        #[arg(long)]
+        pattern: String,
+        #[arg(long)]
+        binary: Option<String>,
+        #[arg(long, default_value = "50")]
+        limit: usize,
+        /// Treat pattern as a raw Lucene query
+        #[arg(long)]
+        raw: bool,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
     },
     /// Query binary information
     Binary {
+        #[arg(long, required_unless_present = "hash")]
+        binary_name: Option<String>,
+        /// Look up by SHA-256, MD5, SHA-1, ssdeep or TLSH hash
+        #[arg(long, conflicts_with = "binary_name")]
+        hash: Option<String>,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
+    /// Find binaries with similar ssdeep or TLSH fuzzy hashes
+    Similar {
+        /// Reference binary (filename or any stored hash)
+        binary: String,
+        /// Minimum ssdeep match score (0-100)
+        #[arg(long, default_value = "50")]
+        min_ssdeep: u32,
+        /// Maximum TLSH distance
+        #[arg(long, default_value = "100")]
+        max_tlsh: u32,
+        #[arg(long, default_value = "20")]
+        limit: usize,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
+    /// Find binaries whose `analyze embeddings` vector is closest by cosine similarity
+    SimilarByEmbedding {
+        /// Reference binary (hash or filename)
+        binary: String,
+        /// Minimum cosine similarity (-1.0-1.0)
+        #[arg(long, default_value = "0.0")]
+        min_similarity: f64,
+        #[arg(long, default_value = "20")]
+        limit: usize,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
+    /// Find binaries importing the same APIs (A/W variants and ordinals merged)
+    SharedApis {
+        /// Reference binary (filename or hash)
+        binary: String,
+        /// Minimum number of shared APIs
+        #[arg(long, default_value = "1")]
+        min_shared: usize,
+        #[arg(long, default_value = "20")]
+        limit: usize,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
+    /// APIs of a library used in the corpus, with the number of binaries importing each
+    /// and example callers
+    Library {
+        /// Library name, with or without `.dll` (case-insensitive)
+        name: String,
+        /// Example callers listed per API
+        #[arg(long, default_value = "3")]
+        examples: usize,
+        #[arg(long, default_value = "100")]
+        limit: usize,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
+    /// List binaries, optionally filtered by toolchain metadata, imphash, entropy or architecture
+    Binaries {
+        /// Compiler name contains this value (case-insensitive)
+        #[arg(long)]
+        compiler: Option<String>,
+        /// Packer name contains this value (case-insensitive)
         #[arg(long)]
-        binary_name: String,
-        #[arg(long, default_value = "table")]
-        format: String,
+        packer: Option<String>,
+        /// PE import hash
+        #[arg(long)]
+        imphash: Option<String>,
+        /// Only samples with a section entropy of 7.0 or more (likely packed or encrypted)
+        #[arg(long)]
+        high_entropy: bool,
+        /// Target architecture; aliases such as amd64, x64 or aarch64 are accepted
+        #[arg(long, value_enum, ignore_case = true)]
+        arch: Option<Architecture>,
+        /// First imported on or after this date (YYYY-MM-DD)
+        #[arg(long)]
+        since: Option<String>,
+        /// Compile timestamp on or after this date (YYYY-MM-DD)
+        #[arg(long)]
+        compiled_since: Option<String>,
+        #[arg(long, default_value = "100")]
+        limit: usize,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
+    /// List domains, IP addresses and URLs linked to binaries
+    Indicators {
+        /// Indicator value contains this text (case-insensitive), e.g. a domain to pivot on
+        #[arg(long)]
+        value: Option<String>,
+        #[arg(long)]
+        binary: Option<String>,
+        #[arg(long, default_value = "100")]
+        limit: usize,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
+    /// List functions using well-known crypto constants (tagged at import)
+    Crypto {
+        /// Only this algorithm (aes, sha1, md5, sha256, sha512, crc32, rc4, blowfish,
+        /// chacha, tea)
+        #[arg(long)]
+        algorithm: Option<String>,
+        #[arg(long)]
+        binary: Option<String>,
+        #[arg(long, default_value = "100")]
+        limit: usize,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
+    /// List mutexes, named pipes, events and semaphores shared across binaries
+    Objects {
+        #[arg(long = "type", value_enum)]
+        kind: Option<ObjectKind>,
+        /// Object name contains this text (case-insensitive)
+        #[arg(long)]
+        name: Option<String>,
+        #[arg(long)]
+        binary: Option<String>,
+        #[arg(long, default_value = "100")]
+        limit: usize,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
     },
     /// Query call graph
     Callgraph {
@@ -99,22 +521,92 @@ pub enum QueryType {
         show_callers: bool,
         #[arg(long, default_value = "1")]
         max_depth: usize,
-        #[arg(long, default_value = "table")]
-        format: String,
+        /// Hide library functions (FLIRT matches, signature list) and paths through them
+        #[arg(long)]
+        exclude_library: bool,
+        /// Only follow calls with at least this confidence (0.0-1.0)
+        #[arg(long, default_value = "0.0")]
+        min_confidence: f64,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
     },
-    /// Query cross-references
+    /// Query cross-references of a function
     Xrefs {
-        address: String,
+        /// Function address, name or UID (an address also matches import addresses)
+        target: String,
+        #[arg(long)]
+        binary: Option<String>,
+        /// Calls to the function, calls made by it, or both
+        #[arg(long, value_enum, default_value_t = XrefDirection::Both)]
+        direction: XrefDirection,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
+    /// Rank the functions called by a function by their number of call sites
+    HotCallees {
+        /// Caller name, address or UID
+        function_name: String,
         #[arg(long)]
         binary: Option<String>,
-        #[arg(long, default_value = "table")]
-        format: String,
+        #[arg(long, default_value = "50")]
+        limit: usize,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
+    /// List call addresses the importer could not match to a function
+    Unresolved {
+        #[arg(long)]
+        binary: Option<String>,
+        #[arg(long, default_value = "100")]
+        limit: usize,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
+    /// Find functions reading, taking the address of or decrypting a global or string
+    DataRefs {
+        /// Data address, or text contained in a referenced string
+        target: String,
+        #[arg(long)]
+        binary: Option<String>,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
+    /// Find the function containing an arbitrary address
+    AtAddress {
+        /// Binary filename or hash
+        binary: String,
+        /// Address inside a function (hexadecimal or decimal)
+        address: String,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
     },
     /// Query call paths and execution order
     CallPath {
-        function_name: String,
+        #[arg(required_unless_present = "from")]
+        function_name: Option<String>,
+        /// Source function of a route query (alternative to the positional name)
+        #[arg(long, conflicts_with = "function_name")]
+        from: Option<String>,
+        /// Report routes from the source function to this function
+        #[arg(long)]
+        to: Option<String>,
+        /// With --to, list every route up to --max-depth instead of only the shortest ones
+        #[arg(long, requires = "to")]
+        all_routes: bool,
         #[arg(long)]
         binary: Option<String>,
+        /// Skip paths through functions with this name; `*` matches any characters (repeatable)
+        #[arg(long, value_name = "NAME_OR_PATTERN")]
+        exclude: Vec<String>,
+        /// Skip paths through library functions (FLIRT matches, signature list)
+        #[arg(long)]
+        exclude_library: bool,
+        /// Only follow calls with at least this confidence (0.0-1.0)
+        #[arg(long, default_value = "0.0")]
+        min_confidence: f64,
+        /// Follow virtual calls to the overrides found through vtable slots
+        #[arg(long)]
+        expand_virtual: bool,
         #[arg(long)]
         show_paths: bool,
         #[arg(long)]
@@ -127,8 +619,165 @@ pub enum QueryType {
         show_context: bool,
         #[arg(long, default_value = "5")]
         max_depth: usize,
-        #[arg(long, default_value = "table")]
-        format: String,
+        /// Stop after this many downward paths or upward chains
+        #[arg(long, default_value_t = DEFAULT_MAX_PATHS)]
+        max_results: usize,
+        /// Path enumeration: native Cypher patterns or APOC's expandConfig (breadth-first)
+        #[arg(long, value_enum, default_value_t = PathExpander::Native)]
+        expander: PathExpander,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
+    /// Run one query for each line of a file, over a single connection
+    Batch {
+        /// Targets, one function name, hash or pattern per line (`-` reads stdin);
+        /// blank lines and lines starting with `#` are skipped
+        file: String,
+        /// Query run for each target
+        #[arg(long = "type", value_enum, default_value_t = BatchQuery::Functions)]
+        query: BatchQuery,
+        #[arg(long)]
+        binary: Option<String>,
+        /// Maximum number of rows per target
+        #[arg(long, default_value = "100")]
+        limit: usize,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
+    /// Run a named Cypher query preset (built-in or from queries.toml)
+    Preset {
+        /// Preset name, e.g. injection-paths or c2-strings
+        #[arg(required_unless_present = "list")]
+        name: Option<String>,
+        /// Override a preset parameter (repeatable)
+        #[arg(long, value_name = "KEY=VALUE")]
+        param: Vec<String>,
+        /// TOML file of presets, defaults to ./queries.toml when present
+        #[arg(long, value_name = "FILE")]
+        presets: Option<String>,
+        /// List the available presets and their parameters
+        #[arg(long, conflicts_with = "name")]
+        list: bool,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum AnalyzeType {
+    /// Find mutually recursive function groups (strongly connected components)
+    Cycles {
+        /// Binary filename or hash
+        #[arg(long)]
+        binary: String,
+        /// Smallest cycle to report; 1 also lists directly recursive functions
+        #[arg(long, default_value = "2")]
+        min_size: usize,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
+    /// Compute and store in/out-degree, entry depth and reachable API counts per function
+    Metrics {
+        /// Binary filename or hash (all binaries when omitted)
+        #[arg(long)]
+        binary: Option<String>,
+        /// Number of functions to show, ordered by in-degree
+        #[arg(long, default_value = "20")]
+        limit: usize,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
+    /// Score a binary by weighted suspicious APIs and store the score on its Binary node
+    Risk {
+        /// Binary filename or hash
+        #[arg(long)]
+        binary: String,
+        /// JSON weighting table replacing the built-in one
+        #[arg(long, value_name = "FILE")]
+        profile: Option<String>,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
+    /// Rank functions by instruction mnemonic histogram similarity to a reference function
+    OpcodeSimilarity {
+        /// Reference function name
+        function_name: String,
+        /// Binary containing the reference function (filename or hash)
+        #[arg(long)]
+        binary: Option<String>,
+        /// Only compare against functions of this binary
+        #[arg(long)]
+        target_binary: Option<String>,
+        /// Minimum cosine similarity (0.0-1.0)
+        #[arg(long, default_value = "0.8")]
+        min_similarity: f64,
+        #[arg(long, default_value = "20")]
+        limit: usize,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
+    /// Samples most similar to a binary by Jaccard similarity of a feature set
+    Neighbors {
+        /// Binary hash or filename
+        binary: String,
+        /// Number of samples to list
+        #[arg(long, default_value = "10")]
+        k: usize,
+        /// Features compared: imported APIs, strings, called APIs, or hashes of non-library
+        /// functions (`functions[].hash`)
+        #[arg(long, value_enum, default_value_t = FeatureSpace::Imports)]
+        space: FeatureSpace,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
+    /// Exports and imports added or removed between two versions of the same software
+    Trade {
+        /// Older binary (hash or filename)
+        binary_a: String,
+        /// Newer binary (hash or filename)
+        binary_b: String,
+        /// Print only the number of changes of each kind
+        #[arg(long)]
+        summary: bool,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
+    /// Compute GDS node embeddings over binaries, their imported APIs and strings and
+    /// store them as `embedding` on the Binary nodes (requires the GDS plugin)
+    Embeddings {
+        #[arg(long, value_enum, default_value_t = EmbeddingAlgorithm::FastRp)]
+        algorithm: EmbeddingAlgorithm,
+        /// Length of the embedding vectors
+        #[arg(long, default_value = "128")]
+        dimension: usize,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
+    /// Distributions of functions, strings and imports per binary, format and
+    /// architecture breakdown and the most imported libraries of all binaries
+    CorpusStats {
+        /// Number of libraries to list
+        #[arg(long, default_value = "10")]
+        top: usize,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum AnnotateTarget {
+    /// Rename or comment a function (the extracted name is kept)
+    Function {
+        /// Function UID
+        uid: String,
+        /// Analyst name shown alongside the original name
+        #[arg(long, required_unless_present_any = ["comment", "clear"])]
+        name: Option<String>,
+        #[arg(long)]
+        comment: Option<String>,
+        /// Remove the analyst name and comment
+        #[arg(long, conflicts_with_all = ["name", "comment"])]
+        clear: bool,
     },
 }
 
@@ -136,32 +785,179 @@ pub enum QueryType {
 pub enum DatabaseAction {
     /// Initialize database schema
     Init,
-    /// Clear all data
+    /// Clear all data, or only the binaries matching --binary/--family/--older-than
     Clear {
         #[arg(long)]
         confirm: bool,
+        /// SHA-256 of the binary to delete
+        #[arg(long)]
+        binary: Option<String>,
+        /// Delete binaries attributed to this family (case-insensitive)
+        #[arg(long)]
+        family: Option<String>,
+        /// Delete binaries imported before this date (YYYY-MM-DD) or more than N days ago (e.g. 30d)
+        #[arg(long, value_name = "DATE|DAYS")]
+        older_than: Option<String>,
+        /// Print what would be deleted without deleting it
+        #[arg(long)]
+        dry_run: bool,
     },
     /// Show database statistics
-    Stats,
+    Stats {
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
+    /// Preflight for a deployment: connectivity, server version and edition, APOC and
+    /// GDS plugins and the constraints and indexes created by `init`
+    Ping {
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
+    /// Check that the constraints and indexes created by `init` exist
+    VerifySchema {
+        /// Create the missing constraints and indexes
+        #[arg(long)]
+        fix: bool,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
+    /// Find orphan functions and strings, cross-binary calls and duplicate imports
+    Check {
+        /// Re-attach or delete orphans, drop cross-binary calls and merge duplicate imports
+        #[arg(long)]
+        repair: bool,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
+    /// Move a binary's functions to another image base, rewriting their UIDs and
+    /// call sites; functions landing on an existing function (an import of the same
+    /// binary at the new base) are merged into it
+    Rebase {
+        /// SHA-256 or filename of the binary
+        binary: String,
+        /// Image base the functions were imported at (default: the stored image base)
+        #[arg(long)]
+        from: Option<String>,
+        /// Image base to move them to (default: 0, addresses relative to the image base)
+        #[arg(long)]
+        to: Option<String>,
+        /// Print what would be moved without changing the graph
+        #[arg(long)]
+        dry_run: bool,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
     /// Export data
     Export {
+        /// Output file, or output directory for `parquet`
         output_path: String,
-        #[arg(long, default_value = "json")]
-        format: String,
+        /// `json`, `jsonl`, or `parquet` for one file per node label and relationship
+        /// type (requires a build with `--features parquet`)
+        #[arg(long, value_parser = export_format(), default_value = "json")]
+        format: OutputFormat,
     },
+    /// Compare two `database export` snapshots: binaries, functions and relationships
+    /// added or removed between them
+    Diff {
+        /// Older snapshot
+        snapshot_a: String,
+        /// Newer snapshot
+        snapshot_b: String,
+        /// Print only the number of changes of each kind
+        #[arg(long)]
+        summary: bool,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
+}
+
+/// Formats `database export` writes, a subset of [`OutputFormat`]
+fn export_format() -> impl TypedValueParser<Value = OutputFormat> {
+    PossibleValuesParser::new(["json", "jsonl", "parquet"])
+        .map(|name| OutputFormat::from_str(&name, true).expect("listed formats are variants"))
 }
 
 impl Cli {
     pub async fn execute(self, config: Config) -> anyhow::Result<()> {
+        let output = OutputOptions::new(self.output, self.force, self.no_color);
+        let mut config = match &self.project {
+            Some(project) => config.for_project(project)?,
+            None => config,
+        };
+        if let Some(backend) = self.backend {
+            config.backend = backend;
+        }
+        if let Some(address) = &self.metrics_listen {
+            let listener = tokio::net::TcpListener::bind(address)
+                .await
+                .with_context(|| format!("Failed to listen for metrics on {}", address))?;
+            eprintln!("Serving metrics on http://{}/metrics", listener.local_addr()?);
+            tokio::spawn(metrics::serve(listener));
+        }
+
         match self.command {
             Commands::Import { import_type } => {
                 commands::import::handle_import(import_type, config).await
             }
-            Commands::Query { query_type } => {
-                commands::query::handle_query(query_type, config).await
+            Commands::Query {
+                timing,
+                explain,
+                profile,
+                query_type,
+            } => {
+                let plan = if explain {
+                    Some(PlanMode::Explain)
+                } else if profile {
+                    Some(PlanMode::Profile)
+                } else {
+                    None
+                };
+                let tracing = QueryTracing { timing, plan };
+                commands::query::handle_query(query_type, tracing, config, &output).await
+            }
+            Commands::Analyze { analysis } => {
+                commands::analyze::handle_analyze(analysis, config, &output).await
+            }
+            Commands::Annotate { target } => {
+                commands::annotate::handle_annotate(target, config, &output).await
+            }
+            Commands::Report {
+                binary,
+                strings,
+                max_edges,
+                profile,
+            } => {
+                let options = commands::report::ReportOptions {
+                    strings,
+                    max_edges,
+                    profile,
+                };
+                commands::report::handle_report(&binary, &options, config, &output).await
+            }
+            Commands::Export { export_type } => {
+                commands::export::handle_export(export_type, config).await
             }
             Commands::Database { db_action } => {
-                commands::database::handle_database(db_action, config).await
+                commands::database::handle_database(db_action, config, &output).await
+            }
+            Commands::Serve {
+                listen,
+                ui,
+                import_queue,
+                import_workers,
+                max_queued,
+                grpc_listen,
+            } => {
+                commands::serve::handle_serve(
+                    &listen,
+                    ui,
+                    import_queue.as_deref(),
+                    import_workers,
+                    max_queued,
+                    grpc_listen.as_deref(),
+                    config,
+                )
+                .await
             }
         }
     }
@@ -0,0 +1,472 @@
+use anyhow::Result;
+use clap::ValueEnum;
+
+use crate::api::DataImporter;
+use crate::cli::AnalyzeType;
+use crate::config::Config;
+use crate::models::{Binary, EmbeddingAlgorithm, FeatureSpace, RiskReport};
+use crate::output::{
+    CycleMemberEntry, MetricEntry, OpcodeMatch, OutputOptions, Renderer, SnapshotChange,
+};
+use crate::utils::risk::RiskProfile;
+
+pub async fn handle_analyze(
+    analysis: AnalyzeType,
+    config: Config,
+    output: &OutputOptions,
+) -> Result<()> {
+    let importer = DataImporter::for_analysis(&config).await?;
+    let session = importer.session();
+
+    match analysis {
+        AnalyzeType::Cycles {
+            binary,
+            min_size,
+            format,
+        } => {
+            let mut renderer = Renderer::new(format, output)?;
+            analyze_cycles(&session, &mut renderer, &binary, min_size).await?;
+            renderer.finish()?;
+        }
+        AnalyzeType::Metrics {
+            binary,
+            limit,
+            format,
+        } => {
+            let mut renderer = Renderer::new(format, output)?;
+            analyze_metrics(
+                &session,
+                &mut renderer,
+                binary.as_deref(),
+                limit,
+                !config.read_only,
+            )
+            .await?;
+            renderer.finish()?;
+        }
+        AnalyzeType::Risk {
+            binary,
+            profile,
+            format,
+        } => {
+            let profile = match profile {
+                Some(path) => RiskProfile::load(path)?,
+                None => RiskProfile::default(),
+            };
+            let mut renderer = Renderer::new(format, output)?;
+            analyze_risk(
+                &session,
+                &mut renderer,
+                &binary,
+                &profile,
+                !config.read_only,
+            )
+            .await?;
+            renderer.finish()?;
+        }
+        AnalyzeType::OpcodeSimilarity {
+            function_name,
+            binary,
+            target_binary,
+            min_similarity,
+            limit,
+            format,
+        } => {
+            let mut renderer = Renderer::new(format, output)?;
+            analyze_opcode_similarity(
+                &session,
+                &mut renderer,
+                &function_name,
+                binary.as_deref(),
+                target_binary.as_deref(),
+                min_similarity,
+                limit,
+            )
+            .await?;
+            renderer.finish()?;
+        }
+        AnalyzeType::Neighbors {
+            binary,
+            k,
+            space,
+            format,
+        } => {
+            let mut renderer = Renderer::new(format, output)?;
+            analyze_neighbors(&session, &mut renderer, &binary, k, space).await?;
+            renderer.finish()?;
+        }
+        AnalyzeType::Trade {
+            binary_a,
+            binary_b,
+            summary,
+            format,
+        } => {
+            let mut renderer = Renderer::new(format, output)?;
+            analyze_trade(&session, &mut renderer, &binary_a, &binary_b, summary).await?;
+            renderer.finish()?;
+        }
+        AnalyzeType::Embeddings {
+            algorithm,
+            dimension,
+            format,
+        } => {
+            config.ensure_writable("store embeddings")?;
+            let mut renderer = Renderer::new(format, output)?;
+            analyze_embeddings(&session, &mut renderer, algorithm, dimension).await?;
+            renderer.finish()?;
+        }
+        AnalyzeType::CorpusStats { top, format } => {
+            let mut renderer = Renderer::new(format, output)?;
+            analyze_corpus_stats(&session, &mut renderer, top).await?;
+            renderer.finish()?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn analyze_neighbors(
+    session: &crate::api::ImportSession,
+    renderer: &mut Renderer,
+    binary: &str,
+    k: usize,
+    space: FeatureSpace,
+) -> Result<()> {
+    let reference = match session.query_binary_by_hash(binary).await? {
+        Some(found) => Some(found),
+        None => session.query_binary_info(binary).await?,
+    };
+    let Some(reference) = reference else {
+        return Err(crate::error::not_found(format!(
+            "No binary found matching: '{}'",
+            binary
+        )));
+    };
+    let space_name = space
+        .to_possible_value()
+        .map_or_else(String::new, |value| value.get_name().to_string());
+    eprintln!(
+        "Searching samples similar to '{}' by {}",
+        reference.filename, space_name
+    );
+
+    let neighbors = session.query_neighbors(&reference.hash, space, k).await?;
+    if neighbors.is_empty() {
+        eprintln!(
+            "No samples share {} with '{}'",
+            space_name, reference.filename
+        );
+    }
+
+    renderer.render_list(
+        &format!("Nearest neighbors of {}", reference.filename),
+        &neighbors,
+    )
+}
+
+async fn analyze_embeddings(
+    session: &crate::api::ImportSession,
+    renderer: &mut Renderer,
+    algorithm: EmbeddingAlgorithm,
+    dimension: usize,
+) -> Result<()> {
+    eprintln!(
+        "Computing {}-dimensional {} embeddings of binaries",
+        dimension,
+        algorithm.as_str()
+    );
+
+    let run = session.generate_embeddings(algorithm, dimension).await?;
+    if run.binaries == 0 {
+        eprintln!("No binaries with imports or strings to embed");
+    }
+    if renderer.format().is_document() {
+        return renderer.document(&run);
+    }
+
+    renderer.line(&format!(
+        "Stored embeddings on {} binaries (projection: {} nodes, {} relationships)",
+        run.binaries, run.nodes, run.relationships
+    ))
+}
+
+async fn analyze_trade(
+    session: &crate::api::ImportSession,
+    renderer: &mut Renderer,
+    binary_a: &str,
+    binary_b: &str,
+    summary: bool,
+) -> Result<()> {
+    let older = find_binary(session, binary_a).await?;
+    let newer = find_binary(session, binary_b).await?;
+    eprintln!(
+        "Comparing the export/import surface of '{}' and '{}'",
+        older.filename, newer.filename
+    );
+
+    let diff = session
+        .query_api_surface(&older.hash)
+        .await?
+        .diff(&session.query_api_surface(&newer.hash).await?);
+    if renderer.format().is_document() {
+        return renderer.document(&diff);
+    }
+
+    let entries = vec![
+        MetricEntry::new("Exports added", diff.exports_added.len()),
+        MetricEntry::new("Exports removed", diff.exports_removed.len()),
+        MetricEntry::new("Imports added", diff.imports_added.len()),
+        MetricEntry::new("Imports removed", diff.imports_removed.len()),
+    ];
+    renderer.render_list("Surface Diff", &entries)?;
+
+    if diff.is_empty() {
+        eprintln!("Exports and imports are identical");
+        return Ok(());
+    }
+    if summary {
+        return Ok(());
+    }
+
+    let changes = |names: &[String], change: &str, kind: &str| {
+        names
+            .iter()
+            .map(|name| SnapshotChange {
+                change: change.to_string(),
+                kind: kind.to_string(),
+                item: name.clone(),
+            })
+            .collect::<Vec<_>>()
+    };
+    let mut entries = changes(&diff.exports_added, "added", "export");
+    entries.extend(changes(&diff.exports_removed, "removed", "export"));
+    entries.extend(changes(&diff.imports_added, "added", "import"));
+    entries.extend(changes(&diff.imports_removed, "removed", "import"));
+    renderer.render_list("Changes", &entries)
+}
+
+/// Binary by hash, else by filename
+async fn find_binary(session: &crate::api::ImportSession, binary: &str) -> Result<Binary> {
+    let sample = match session.query_binary_by_hash(binary).await? {
+        Some(found) => Some(found),
+        None => session.query_binary_info(binary).await?,
+    };
+    sample.ok_or_else(|| crate::error::not_found(format!("No binary found matching: '{}'", binary)))
+}
+
+async fn analyze_corpus_stats(
+    session: &crate::api::ImportSession,
+    renderer: &mut Renderer,
+    top: usize,
+) -> Result<()> {
+    eprintln!("Collecting corpus statistics");
+
+    let stats = session.query_corpus_stats(top).await?;
+    if stats.binaries == 0 {
+        eprintln!("No binaries imported");
+    }
+    if renderer.format().is_document() {
+        return renderer.document(&stats);
+    }
+
+    renderer.render_list("Per-binary counts", &stats.distributions)?;
+    renderer.render_list("Formats", &stats.formats)?;
+    renderer.render_list("Architectures", &stats.architectures)?;
+    renderer.render_list("Top libraries", &stats.top_libraries)?;
+    renderer.line(&format!("\nBinaries: {}", stats.binaries))
+}
+
+async fn analyze_cycles(
+    session: &crate::api::ImportSession,
+    renderer: &mut Renderer,
+    binary: &str,
+    min_size: usize,
+) -> Result<()> {
+    eprintln!("Searching call cycles in binary: '{}'", binary);
+
+    let analyzer = crate::neo4j::CallPathAnalyzer::new(session.importer().connection().clone());
+    let cycles = analyzer.find_call_cycles(binary, min_size).await?;
+
+    if cycles.is_empty() {
+        eprintln!("No call cycles found");
+    }
+
+    if renderer.format().is_document() {
+        return renderer.document(&cycles);
+    }
+
+    let entries: Vec<CycleMemberEntry> = cycles
+        .iter()
+        .flat_map(|cycle| {
+            cycle.functions.iter().map(|function| CycleMemberEntry {
+                cycle_id: cycle.id.clone(),
+                size: cycle.size,
+                name: function.name.clone(),
+                address: function.address.clone(),
+                uid: function.uid.clone(),
+            })
+        })
+        .collect();
+    renderer.render_list("Cycle members", &entries)?;
+
+    if !cycles.is_empty() {
+        let largest = cycles.first().map(|c| c.size).unwrap_or(0);
+        renderer.line(&format!(
+            "\nSummary: {} cycles, {} functions involved, largest cycle has {} functions",
+            cycles.len(),
+            entries.len(),
+            largest
+        ))?;
+    }
+
+    Ok(())
+}
+
+async fn analyze_metrics(
+    session: &crate::api::ImportSession,
+    renderer: &mut Renderer,
+    binary: Option<&str>,
+    limit: usize,
+    store: bool,
+) -> Result<()> {
+    match binary {
+        Some(binary_name) => {
+            eprintln!("Computing call-graph metrics for binary: '{}'", binary_name)
+        }
+        None => eprintln!("Computing call-graph metrics for all binaries"),
+    }
+
+    let analyzer = crate::neo4j::CallPathAnalyzer::new(session.importer().connection().clone());
+    let mut metrics = analyzer.compute_function_metrics(binary).await?;
+
+    if metrics.is_empty() {
+        eprintln!("No functions found");
+        return Ok(());
+    }
+
+    if store {
+        session.importer().store_function_metrics(&metrics).await?;
+        eprintln!("Stored metrics on {} functions", metrics.len());
+    } else {
+        eprintln!("Read-only mode: metrics not stored");
+    }
+
+    metrics.sort_by_key(|m| std::cmp::Reverse(m.in_degree));
+    metrics.truncate(limit);
+    renderer.render_list("Most called functions", &metrics)
+}
+
+async fn analyze_risk(
+    session: &crate::api::ImportSession,
+    renderer: &mut Renderer,
+    binary: &str,
+    profile: &RiskProfile,
+    store: bool,
+) -> Result<()> {
+    let Some(sample) = session.query_binary_info(binary).await? else {
+        return Err(crate::error::not_found(format!(
+            "No binary found matching: '{}'",
+            binary
+        )));
+    };
+    eprintln!("Scoring suspicious APIs of '{}'", sample.filename);
+
+    let uses = session.query_imported_api_uses(&sample.hash).await?;
+    let contributions = profile.contributions(&uses);
+    let score: f64 = contributions.iter().map(|c| c.weight).sum();
+
+    let mut categories: Vec<String> = contributions.iter().map(|c| c.category.clone()).collect();
+    categories.sort();
+    categories.dedup();
+    if store {
+        session
+            .store_risk_score(&sample.hash, score, &categories)
+            .await?;
+    } else {
+        eprintln!("Read-only mode: risk score not stored");
+    }
+
+    let report = RiskReport {
+        binary: sample.filename,
+        hash: sample.hash,
+        score,
+        contributions,
+    };
+    if renderer.format().is_document() {
+        return renderer.document(&report);
+    }
+
+    if report.contributions.is_empty() {
+        eprintln!("No suspicious APIs imported");
+    }
+    renderer.render_list("Suspicious APIs", &report.contributions)?;
+    renderer.line(&format!(
+        "\nRisk score: {:.1} ({} APIs{}{})",
+        report.score,
+        report.contributions.len(),
+        if categories.is_empty() { "" } else { ": " },
+        categories.join(", ")
+    ))
+}
+
+async fn analyze_opcode_similarity(
+    session: &crate::api::ImportSession,
+    renderer: &mut Renderer,
+    function_name: &str,
+    binary: Option<&str>,
+    target_binary: Option<&str>,
+    min_similarity: f64,
+    limit: usize,
+) -> Result<()> {
+    let references = session
+        .query_mnemonic_histograms(Some(function_name), binary)
+        .await?;
+    let Some((reference_binary, reference)) = references.first() else {
+        return Err(anyhow::anyhow!(
+            "No mnemonic histogram stored for function '{}'",
+            function_name
+        ));
+    };
+    if references.len() > 1 {
+        eprintln!(
+            "[WARN] {} functions named '{}' have histograms, using the one in '{}' (narrow with --binary)",
+            references.len(),
+            function_name,
+            reference_binary
+        );
+    }
+    let Some(histogram) = reference.mnemonic_histogram.as_ref() else {
+        return Ok(());
+    };
+
+    eprintln!(
+        "Comparing mnemonic histogram of '{}' in '{}'",
+        reference.name, reference_binary
+    );
+
+    let candidates = session
+        .query_mnemonic_histograms(None, target_binary)
+        .await?;
+    let mut matches: Vec<OpcodeMatch> = candidates
+        .into_iter()
+        .filter(|(_, function)| function.uid != reference.uid)
+        .filter_map(|(binary, function)| {
+            let similarity = histogram.cosine_similarity(function.mnemonic_histogram.as_ref()?);
+            (similarity >= min_similarity).then_some(OpcodeMatch {
+                name: function.name,
+                address: function.address,
+                binary,
+                similarity,
+                uid: function.uid,
+            })
+        })
+        .collect();
+
+    if matches.is_empty() {
+        eprintln!("No functions with similarity >= {}", min_similarity);
+    }
+
+    matches.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+    matches.truncate(limit);
+    renderer.render_list("Similar functions", &matches)
+}
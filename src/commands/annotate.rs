@@ -0,0 +1,66 @@
+use anyhow::Result;
+
+use crate::api::DataImporter;
+use crate::cli::AnnotateTarget;
+use crate::config::Config;
+use crate::output::{OutputFormat, OutputOptions, Renderer};
+use crate::utils::uid::{parse_uid, Uid};
+
+pub async fn handle_annotate(
+    target: AnnotateTarget,
+    config: Config,
+    output: &OutputOptions,
+) -> Result<()> {
+    config.ensure_writable("annotate functions")?;
+    let importer = DataImporter::new(&config).await?;
+    let session = importer.session();
+
+    match target {
+        AnnotateTarget::Function {
+            uid,
+            name,
+            comment,
+            clear,
+        } => {
+            let uid = match parse_uid(&uid) {
+                Some(parsed @ (Uid::Function { .. } | Uid::Import { .. })) => parsed.to_string(),
+                _ => {
+                    return Err(anyhow::anyhow!(
+                        "'{}' is not a function UID ({{binary_hash}}:{{address}} or imp:{{library}}:{{name}})",
+                        uid
+                    ))
+                }
+            };
+            let function = if clear {
+                session.clear_function_annotations(&uid).await?
+            } else {
+                session
+                    .annotate_function(&uid, name.as_deref(), comment.as_deref())
+                    .await?
+            };
+
+            let Some(function) = function else {
+                return Err(crate::error::not_found(format!(
+                    "No function found with UID: '{}'",
+                    uid
+                )));
+            };
+
+            eprintln!(
+                "{} function '{}'",
+                if clear {
+                    "Cleared annotations of"
+                } else {
+                    "Annotated"
+                },
+                function.name
+            );
+
+            let mut renderer = Renderer::new(OutputFormat::Table, output)?;
+            renderer.table(std::slice::from_ref(&function))?;
+            renderer.finish()?;
+        }
+    }
+
+    Ok(())
+}
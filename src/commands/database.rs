@@ -1,26 +1,111 @@
 use anyhow::Result;
 use std::io::{self, Write};
 
+use crate::api::snapshot::Snapshot;
 use crate::api::DataImporter;
 use crate::cli::DatabaseAction;
 use crate::config::Config;
-use crate::neo4j::SchemaManager;
+use crate::neo4j::connection::Dialect;
+use crate::neo4j::integrity::IntegrityChecker;
+use crate::neo4j::rebase::{MovedNode, Rebaser};
+use crate::neo4j::schema::{SchemaReport, SchemaStatus};
+use crate::neo4j::{ClearScope, ImportAge, SchemaManager};
+use crate::output::{
+    MetricEntry, OutputFormat, OutputOptions, PingCheck, Renderer, SchemaEntry, SnapshotChange,
+};
+use crate::utils::time::is_iso_date;
+use crate::utils::uid::{format_address, parse_address};
 
-pub async fn handle_database(db_action: DatabaseAction, config: Config) -> Result<()> {
+pub async fn handle_database(
+    db_action: DatabaseAction,
+    config: Config,
+    output: &OutputOptions,
+) -> Result<()> {
     match db_action {
         DatabaseAction::Init => init_database(&config).await?,
-        DatabaseAction::Clear { confirm } => clear_database(&config, confirm).await?,
-        DatabaseAction::Stats => show_database_stats(&config).await?,
+        DatabaseAction::Clear {
+            confirm,
+            binary,
+            family,
+            older_than,
+            dry_run,
+        } => {
+            let scope = ClearScope {
+                binary: binary.as_deref(),
+                family: family.as_deref(),
+                older_than: older_than.as_deref().map(parse_import_age).transpose()?,
+            };
+            if scope.is_empty() {
+                clear_database(&config, confirm, dry_run).await?
+            } else {
+                clear_scoped(&config, &scope, confirm, dry_run).await?
+            }
+        }
+        DatabaseAction::Stats { format } => {
+            let mut renderer = Renderer::new(format, output)?;
+            show_database_stats(&config, &mut renderer).await?;
+            renderer.finish()?;
+        }
+        DatabaseAction::Ping { format } => {
+            let mut renderer = Renderer::new(format, output)?;
+            ping_database(&config, &mut renderer).await?;
+            renderer.finish()?;
+        }
+        DatabaseAction::VerifySchema { fix, format } => {
+            let mut renderer = Renderer::new(format, output)?;
+            verify_schema(&config, &mut renderer, fix).await?;
+            renderer.finish()?;
+        }
+        DatabaseAction::Check { repair, format } => {
+            let mut renderer = Renderer::new(format, output)?;
+            check_integrity(&config, &mut renderer, repair).await?;
+            renderer.finish()?;
+        }
+        DatabaseAction::Rebase {
+            binary,
+            from,
+            to,
+            dry_run,
+            format,
+        } => {
+            let mut renderer = Renderer::new(format, output)?;
+            rebase_binary(
+                &config,
+                &mut renderer,
+                &binary,
+                from.as_deref(),
+                to.as_deref(),
+                dry_run,
+            )
+            .await?;
+            renderer.finish()?;
+        }
         DatabaseAction::Export {
             output_path,
             format,
-        } => export_database(&config, &output_path, &format).await?,
+        } => export_database(&config, &output_path, format).await?,
+        DatabaseAction::Diff {
+            snapshot_a,
+            snapshot_b,
+            summary,
+            format,
+        } => {
+            let mut renderer = Renderer::new(format, output)?;
+            diff_snapshots(&snapshot_a, &snapshot_b, summary, &mut renderer)?;
+            renderer.finish()?;
+        }
     }
 
     Ok(())
 }
 
 async fn init_database(config: &Config) -> Result<()> {
+    config.ensure_writable("initialize the database")?;
+    if let Some(project) = &config.project {
+        println!("Creating database for project '{}'...", project);
+        crate::neo4j::Neo4jConnection::create_database(config, project).await?;
+    }
+
     println!("Initializing database schema...");
 
     let connection = crate::neo4j::Neo4jConnection::new(config).await?;
@@ -29,6 +114,7 @@ async fn init_database(config: &Config) -> Result<()> {
     println!("Testing Neo4j connectivity...");
     connection.verify_connectivity().await?;
     println!("Neo4j connection successful");
+    println!("Server dialect: {}", connection.dialect().await?.as_str());
 
     // Initialize schema
     SchemaManager::initialize_database(&connection).await?;
@@ -37,7 +123,316 @@ async fn init_database(config: &Config) -> Result<()> {
     Ok(())
 }
 
-async fn clear_database(config: &Config, confirm: bool) -> Result<()> {
+async fn ping_database(config: &Config, renderer: &mut Renderer) -> Result<()> {
+    eprintln!("Checking {}...", config.neo4j_uri);
+
+    let started = std::time::Instant::now();
+    let reachable = match crate::neo4j::Neo4jConnection::new(config).await {
+        Ok(connection) => connection.test_connection().await.map(|_| connection),
+        Err(e) => Err(e),
+    };
+    let connection = match reachable {
+        Ok(connection) => connection,
+        Err(e) => {
+            return Err(crate::error::connection(format!(
+                "Cannot reach {} ({:#}); check that the server is running, its Bolt port \
+                 is reachable and neo4j_uri, neo4j_user and neo4j_password in the config",
+                config.neo4j_uri, e
+            )));
+        }
+    };
+
+    let mut checks = vec![PingCheck::new(
+        "connection",
+        "ok",
+        format!(
+            "{} in {:.1} ms",
+            config.neo4j_uri,
+            started.elapsed().as_secs_f64() * 1000.0
+        ),
+    )];
+    let mut guidance = Vec::new();
+
+    let dialect = connection.dialect().await?;
+    let info = connection.server_info().await?;
+    let server = [
+        Some(info.product.unwrap_or_else(|| dialect.as_str().to_string())),
+        info.version,
+        info.edition,
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>()
+    .join(" ");
+    checks.push(PingCheck::new("server", "ok", server));
+    if dialect != Dialect::Neo4j {
+        guidance.push(format!(
+            "{} lacks fulltext indexes: `query strings`, `query pseudocode` and `query search` need Neo4j",
+            dialect.as_str()
+        ));
+    }
+
+    let plugins = [
+        (
+            "apoc",
+            info.apoc,
+            "Install APOC for fast `database stats` and `query call-path --expander apoc`",
+        ),
+        (
+            "gds",
+            info.gds,
+            "Install Graph Data Science for `analyze embeddings`",
+        ),
+    ];
+    for (plugin, version, hint) in plugins {
+        match version {
+            Some(version) => checks.push(PingCheck::new(plugin, "ok", version)),
+            None => {
+                checks.push(PingCheck::new(plugin, "warn", "not installed"));
+                guidance.push(hint.to_string());
+            }
+        }
+    }
+
+    let report = SchemaManager::new(connection).verify_schema().await?;
+    let expected = report
+        .constraints
+        .iter()
+        .chain(&report.indexes)
+        .filter(|(_, status)| !matches!(status, SchemaStatus::Extra))
+        .count();
+    match report.missing() {
+        0 => checks.push(PingCheck::new(
+            "schema",
+            "ok",
+            format!("{} constraints and indexes", expected),
+        )),
+        missing => {
+            checks.push(PingCheck::new(
+                "schema",
+                "warn",
+                format!(
+                    "{} of {} constraints and indexes missing",
+                    missing, expected
+                ),
+            ));
+            guidance.push(
+                "Run `database verify-schema --fix` (or `database init`) to create them"
+                    .to_string(),
+            );
+        }
+    }
+
+    renderer.render_list("Ping", &checks)?;
+    for hint in &guidance {
+        eprintln!("- {}", hint);
+    }
+    Ok(())
+}
+
+async fn verify_schema(config: &Config, renderer: &mut Renderer, fix: bool) -> Result<()> {
+    if fix {
+        config.ensure_writable("create constraints and indexes")?;
+    }
+    eprintln!("Checking constraints and indexes...");
+
+    let connection = crate::neo4j::Neo4jConnection::new(config).await?;
+    let manager = SchemaManager::new(connection);
+    let mut report = manager.verify_schema().await?;
+
+    let missing = report.missing();
+    if fix && missing > 0 {
+        eprintln!("Creating {} missing constraints and indexes...", missing);
+        manager.create_missing(&report).await?;
+        report = manager.verify_schema().await?;
+    }
+
+    renderer.render_list("Schema", &schema_entries(&report))?;
+
+    match report.missing() {
+        0 => eprintln!("All expected constraints and indexes exist"),
+        missing => eprintln!(
+            "{} constraints and indexes missing, run with --fix to create them",
+            missing
+        ),
+    }
+    Ok(())
+}
+
+fn schema_entries(report: &SchemaReport) -> Vec<SchemaEntry> {
+    let kinds = [
+        ("constraint", &report.constraints),
+        ("index", &report.indexes),
+    ];
+    kinds
+        .into_iter()
+        .flat_map(|(kind, items)| {
+            items.iter().map(move |(name, status)| SchemaEntry {
+                kind: kind.to_string(),
+                name: name.clone(),
+                status: match status {
+                    SchemaStatus::Present => "ok",
+                    SchemaStatus::Missing(_) => "missing",
+                    SchemaStatus::Extra => "extra",
+                    SchemaStatus::Unsupported => "unsupported",
+                }
+                .to_string(),
+            })
+        })
+        .collect()
+}
+
+async fn check_integrity(config: &Config, renderer: &mut Renderer, repair: bool) -> Result<()> {
+    if repair {
+        config.ensure_writable("repair the graph")?;
+    }
+    eprintln!("Checking graph integrity...");
+
+    let connection = crate::neo4j::Neo4jConnection::new(config).await?;
+    let checker = IntegrityChecker::new(connection);
+    let mut report = checker.check().await?;
+
+    if repair && !report.is_clean() {
+        eprintln!("Repairing...");
+        checker.repair().await?;
+        report = checker.check().await?;
+    }
+
+    let entries = vec![
+        MetricEntry::new("Orphan functions", report.orphan_functions),
+        MetricEntry::new("Orphan strings", report.orphan_strings),
+        MetricEntry::new("Cross-binary calls", report.cross_binary_calls),
+        MetricEntry::new("Duplicate imports", report.duplicate_imports),
+    ];
+    renderer.render_list("Integrity", &entries)?;
+
+    if report.is_clean() {
+        eprintln!("No problems found");
+    } else if !repair {
+        eprintln!("Run with --repair to fix these problems");
+    }
+    Ok(())
+}
+
+async fn rebase_binary(
+    config: &Config,
+    renderer: &mut Renderer,
+    binary: &str,
+    from: Option<&str>,
+    to: Option<&str>,
+    dry_run: bool,
+) -> Result<()> {
+    if !dry_run {
+        config.ensure_writable("rebase a binary")?;
+    }
+    let parse = |flag: &str, value: &str| {
+        parse_address(value).ok_or_else(|| {
+            crate::error::validation(format!("Invalid {} address: '{}'", flag, value), Vec::new())
+        })
+    };
+    let to = to.map(|to| parse("--to", to)).transpose()?.unwrap_or(0);
+
+    let importer = DataImporter::new(config).await?;
+    let session = importer.session();
+    let sample = match session.query_binary_by_hash(binary).await? {
+        Some(found) => Some(found),
+        None => session.query_binary_info(binary).await?,
+    };
+    let Some(sample) = sample else {
+        return Err(crate::error::not_found(format!(
+            "No binary found matching: '{}'",
+            binary
+        )));
+    };
+
+    let image_base = sample.image_base.as_deref().and_then(parse_address);
+    let from = match (from, image_base) {
+        (Some(from), _) => parse("--from", from)?,
+        (None, Some(image_base)) => image_base,
+        (None, None) => {
+            return Err(crate::error::validation(
+                format!("{} has no stored image base; pass --from", sample.filename),
+                Vec::new(),
+            ))
+        }
+    };
+    if from == to {
+        return Err(crate::error::validation(
+            format!("--from and --to are both {}", format_address(to)),
+            Vec::new(),
+        ));
+    }
+    // Imports with an image base keep addresses relative to it
+    if image_base.is_some() && to != 0 {
+        return Err(crate::error::validation(
+            format!(
+                "{} stores addresses relative to its image base; only --to 0 applies",
+                sample.filename
+            ),
+            Vec::new(),
+        ));
+    }
+
+    eprintln!(
+        "Rebasing {} from {} to {}...",
+        sample.filename,
+        format_address(from),
+        format_address(to)
+    );
+    let rebaser = Rebaser::new(session.importer().connection().clone());
+    let mut plan = rebaser.plan(&sample.hash, from, to).await?;
+    if to == 0 && image_base.is_none() {
+        plan.image_base = Some(from);
+    }
+
+    let mut entries = vec![
+        MetricEntry::new("Functions moved", plan.count(MovedNode::Function, false)),
+        MetricEntry::new("Functions merged", plan.count(MovedNode::Function, true)),
+        MetricEntry::new(
+            "Vtables, data, unresolved calls and chunks moved",
+            plan.moves
+                .iter()
+                .filter(|m| m.kind != MovedNode::Function)
+                .count(),
+        ),
+    ];
+    if dry_run {
+        renderer.render_list("Rebase (dry run)", &entries)?;
+        return Ok(());
+    }
+
+    let summary = rebaser.apply(&plan).await?;
+
+    entries = vec![
+        MetricEntry::new("Functions moved", summary.renamed),
+        MetricEntry::new("Functions merged", summary.merged),
+        MetricEntry::new(
+            "Vtables, data, unresolved calls and chunks moved",
+            summary.other_nodes,
+        ),
+        MetricEntry::new("Call sites moved", summary.call_sites),
+        MetricEntry::new("References moved", summary.references),
+        MetricEntry::new(
+            "Import, string and entry point addresses moved",
+            summary.addresses,
+        ),
+    ];
+    renderer.render_list("Rebase", &entries)?;
+    Ok(())
+}
+
+async fn clear_database(config: &Config, confirm: bool, dry_run: bool) -> Result<()> {
+    config.ensure_writable("clear the database")?;
+
+    if dry_run {
+        let connection = crate::neo4j::Neo4jConnection::new(config).await?;
+        let stats = connection.get_database_stats().await?;
+        println!(
+            "Would delete {} nodes and {} relationships",
+            stats.node_count, stats.relationship_count
+        );
+        return Ok(());
+    }
     if !confirm {
         print!("This will delete ALL data in the database. Are you sure? [y/N]: ");
         io::stdout().flush()?;
@@ -60,52 +455,235 @@ async fn clear_database(config: &Config, confirm: bool) -> Result<()> {
     Ok(())
 }
 
-async fn show_database_stats(config: &Config) -> Result<()> {
-    println!("Retrieving database statistics...");
+async fn clear_scoped(
+    config: &Config,
+    scope: &ClearScope<'_>,
+    confirm: bool,
+    dry_run: bool,
+) -> Result<()> {
+    config.ensure_writable("clear the database")?;
 
-    // Use DataImporter to get statistics (read from MemoryStore)
     let importer = DataImporter::new(config).await?;
-    let stats = importer.get_database_stats().await?;
+    let session = importer.session();
+    let hashes = session.importer().scoped_binaries(scope).await?;
+    if hashes.is_empty() {
+        println!("No binaries match the given scope");
+        return Ok(());
+    }
+
+    let counts = session.importer().count_binary_deletion(&hashes).await?;
+    println!(
+        "{} {} binaries, {} functions, {} strings and {} other nodes",
+        if dry_run { "Would delete" } else { "Deleting" },
+        counts.binaries,
+        counts.functions,
+        counts.strings,
+        counts.other
+    );
+    if dry_run {
+        return Ok(());
+    }
+
+    if !confirm {
+        print!("Are you sure? [y/N]: ");
+        io::stdout().flush()?;
+
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+
+        if !input.trim().to_lowercase().starts_with('y') {
+            println!("Operation cancelled");
+            return Ok(());
+        }
+    }
 
-    println!("\nDatabase Statistics:");
-    println!("  Total nodes: {}", stats.node_count);
-    println!("  Total relationships: {}", stats.relationship_count);
+    session.importer().delete_binaries(&hashes).await?;
+    println!("Deleted {} binaries", hashes.len());
+    Ok(())
+}
 
-    println!("\nNodes by type:");
-    for (label, count) in &stats.label_counts {
-        println!("  {}: {}", label, count);
+/// `--older-than` value: `30d` (days ago) or an ISO 8601 date such as `2024-01-31`
+fn parse_import_age(text: &str) -> Result<ImportAge<'_>> {
+    if let Some(days) = text.strip_suffix('d') {
+        return days
+            .parse()
+            .map(ImportAge::Days)
+            .map_err(|_| anyhow::anyhow!("Invalid day count in --older-than: '{}'", text));
     }
 
-    // Get additional import statistics
-    let import_stats = importer.get_import_statistics().await?;
+    if !is_iso_date(text) {
+        return Err(anyhow::anyhow!(
+            "Invalid --older-than '{}', expected YYYY-MM-DD or a day count such as 30d",
+            text
+        ));
+    }
+    Ok(ImportAge::Before(text))
+}
+
+async fn show_database_stats(config: &Config, renderer: &mut Renderer) -> Result<()> {
+    eprintln!("Retrieving database statistics...");
+
+    let importer = DataImporter::new(config).await?;
+    let stats = importer.get_database_stats().await?;
+
+    let mut label_counts: Vec<_> = stats.label_counts.iter().collect();
+    label_counts.sort();
+    let mut relationship_counts: Vec<_> = stats.relationship_counts.iter().collect();
+    relationship_counts.sort();
+
+    let mut entries = vec![
+        MetricEntry::new("Total nodes", stats.node_count),
+        MetricEntry::new("Total relationships", stats.relationship_count),
+    ];
+    for (label, count) in label_counts {
+        entries.push(MetricEntry::new(format!("{} nodes", label), count));
+    }
+    for (rel_type, count) in relationship_counts {
+        entries.push(MetricEntry::new(
+            format!("{} relationships", rel_type),
+            count,
+        ));
+    }
+    entries.push(MetricEntry::new(
+        "Avg functions per binary",
+        format!("{:.1}", stats.avg_functions_per_binary()),
+    ));
+    for (library, binaries) in &stats.top_libraries {
+        entries.push(MetricEntry::new(
+            format!("Library {}", library),
+            format!("{} binaries", binaries),
+        ));
+    }
 
-    println!("\nImport Statistics:");
-    println!("  Binaries: {}", import_stats.binaries);
-    println!("  Functions: {}", import_stats.functions);
-    println!("  Strings: {}", import_stats.strings);
-    println!("  Libraries: {}", import_stats.libraries);
-    println!("  Call relationships: {}", import_stats.calls_relationships);
+    renderer.line("\nDatabase Statistics:")?;
+    renderer.table(&entries)?;
+    renderer.document(&entries)?;
 
     Ok(())
 }
 
-async fn export_database(config: &Config, output_path: &str, format: &str) -> Result<()> {
-    println!("Exporting database to {} (format: {})", output_path, format);
+async fn export_database(config: &Config, output_path: &str, format: OutputFormat) -> Result<()> {
+    println!(
+        "Exporting database to {} (format: {})",
+        output_path,
+        format.name()
+    );
 
     let importer = DataImporter::new(config).await?;
 
     match format {
-        "json" => {
+        OutputFormat::Json => {
             importer.export_to_json(output_path).await?;
             println!("Database exported to JSON: {}", output_path);
         }
-        "csv" => {
-            return Err(anyhow::anyhow!("CSV export not yet implemented"));
+        OutputFormat::Jsonl => {
+            importer.export_to_jsonl(output_path).await?;
+            println!("Database exported to JSON Lines: {}", output_path);
         }
+        OutputFormat::Parquet => export_parquet(&importer, output_path).await?,
+        // Rejected by the `--format` parser
         _ => {
-            return Err(anyhow::anyhow!("Unsupported export format: {}", format));
+            return Err(crate::error::validation(
+                format!("database export does not write --format {}", format.name()),
+                Vec::new(),
+            ))
         }
     }
 
     Ok(())
 }
+
+#[cfg(feature = "parquet")]
+async fn export_parquet(importer: &DataImporter, output_path: &str) -> Result<()> {
+    let files = importer.export_to_parquet(output_path).await?;
+    for (path, rows) in &files {
+        println!("  {} ({} rows)", path.display(), rows);
+    }
+    println!(
+        "Database exported to {} Parquet files in {}",
+        files.len(),
+        output_path
+    );
+    Ok(())
+}
+
+#[cfg(not(feature = "parquet"))]
+async fn export_parquet(_importer: &DataImporter, _output_path: &str) -> Result<()> {
+    Err(crate::error::validation(
+        "Parquet output needs a build with `--features parquet`",
+        Vec::new(),
+    ))
+}
+
+fn diff_snapshots(
+    snapshot_a: &str,
+    snapshot_b: &str,
+    summary: bool,
+    renderer: &mut Renderer,
+) -> Result<()> {
+    let before = Snapshot::load(snapshot_a)
+        .map_err(|e| anyhow::anyhow!("Failed to read snapshot {}: {}", snapshot_a, e))?;
+    let after = Snapshot::load(snapshot_b)
+        .map_err(|e| anyhow::anyhow!("Failed to read snapshot {}: {}", snapshot_b, e))?;
+    let diff = before.diff(&after);
+
+    let entries = vec![
+        MetricEntry::new("Binaries added", diff.added_binaries.len()),
+        MetricEntry::new("Binaries removed", diff.removed_binaries.len()),
+        MetricEntry::new("Functions added", diff.added_functions.len()),
+        MetricEntry::new("Functions removed", diff.removed_functions.len()),
+        MetricEntry::new("Relationships added", diff.added_relationships.len()),
+        MetricEntry::new("Relationships removed", diff.removed_relationships.len()),
+    ];
+    renderer.render_list("Snapshot Diff", &entries)?;
+
+    if diff.is_empty() {
+        eprintln!("Snapshots are identical");
+        return Ok(());
+    }
+    if summary {
+        return Ok(());
+    }
+
+    let change = |change: &str, kind: &str, item: String| SnapshotChange {
+        change: change.to_string(),
+        kind: kind.to_string(),
+        item,
+    };
+    let relationship = |(source, rel_type, target): &(String, String, String)| {
+        format!("{} -[{}]-> {}", source, rel_type, target)
+    };
+
+    let mut changes = Vec::new();
+    changes.extend(
+        diff.added_binaries
+            .iter()
+            .map(|hash| change("added", "binary", hash.clone())),
+    );
+    changes.extend(
+        diff.removed_binaries
+            .iter()
+            .map(|hash| change("removed", "binary", hash.clone())),
+    );
+    changes.extend(
+        diff.added_functions
+            .iter()
+            .map(|uid| change("added", "function", uid.clone())),
+    );
+    changes.extend(
+        diff.removed_functions
+            .iter()
+            .map(|uid| change("removed", "function", uid.clone())),
+    );
+    changes.extend(
+        diff.added_relationships
+            .iter()
+            .map(|rel| change("added", "relationship", relationship(rel))),
+    );
+    changes.extend(
+        diff.removed_relationships
+            .iter()
+            .map(|rel| change("removed", "relationship", relationship(rel))),
+    );
+    renderer.render_list("Changes", &changes)
+}
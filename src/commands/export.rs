@@ -0,0 +1,216 @@
+use anyhow::Result;
+use std::io::Write;
+
+use crate::api::{DataImporter, ImportSession};
+use crate::cli::ExportType;
+use crate::config::Config;
+use crate::models::FeatureCell;
+use crate::output::{networkx, perspective, AtomicFile};
+use crate::store::{Backend, EmbeddedStore, GraphStore};
+
+/// File format of `export features`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum FeatureFormat {
+    Csv,
+    /// Requires a build with `--features parquet`
+    Parquet,
+}
+
+/// Visual exploration setup written by `export perspective`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum PerspectiveKind {
+    /// Bloom perspective JSON, imported from Bloom's perspective gallery
+    Bloom,
+    /// Neo4j Browser guide HTML, opened with `:play <url>`
+    BrowserGuide,
+}
+
+impl PerspectiveKind {
+    fn description(self) -> &'static str {
+        match self {
+            PerspectiveKind::Bloom => "Bloom perspective",
+            PerspectiveKind::BrowserGuide => "Browser guide",
+        }
+    }
+}
+
+/// Rows per Parquet row group
+#[cfg(feature = "parquet")]
+const PARQUET_ROW_GROUP: usize = 100_000;
+
+pub async fn handle_export(export_type: ExportType, config: Config) -> Result<()> {
+    match export_type {
+        ExportType::Annotations { output_path } => {
+            let importer = DataImporter::new(&config).await?;
+            println!("Exporting annotations to {}", output_path);
+            let annotations = importer.export_annotations(&output_path).await?;
+            println!(
+                "Exported {} function and {} binary annotations",
+                annotations.functions.len(),
+                annotations.binaries.len()
+            );
+        }
+        ExportType::Features {
+            output_path,
+            space,
+            format,
+        } => {
+            if format == FeatureFormat::Parquet && !cfg!(feature = "parquet") {
+                return Err(crate::error::validation(
+                    "Parquet output needs a build with `--features parquet`",
+                    Vec::new(),
+                ));
+            }
+
+            let importer = DataImporter::new(&config).await?;
+            println!("Exporting feature matrix to {}", output_path);
+            let cells = importer.session().query_feature_matrix(space).await?;
+
+            let mut file = AtomicFile::create(&output_path, true)?;
+            match format {
+                FeatureFormat::Csv => write_features_csv(&mut file, &cells)?,
+                #[cfg(feature = "parquet")]
+                FeatureFormat::Parquet => write_features_parquet(&mut file, &cells)?,
+                #[cfg(not(feature = "parquet"))]
+                FeatureFormat::Parquet => unreachable!(),
+            }
+            file.commit()?;
+
+            let mut binaries: Vec<&str> = cells.iter().map(|cell| cell.binary.as_str()).collect();
+            binaries.dedup();
+            let mut features: Vec<&str> = cells.iter().map(|cell| cell.feature.as_str()).collect();
+            features.sort_unstable();
+            features.dedup();
+            println!(
+                "Exported {} non-zero cells ({} binaries x {} features)",
+                cells.len(),
+                binaries.len(),
+                features.len()
+            );
+        }
+        ExportType::Networkx {
+            output_path,
+            binary,
+        } => {
+            if config.backend == Backend::Embedded {
+                let session = ImportSession::new(EmbeddedStore::open(&config.embedded_path)?);
+                export_networkx(&session, &binary, &output_path).await?;
+            } else {
+                let importer = DataImporter::for_analysis(&config).await?;
+                export_networkx(&importer.session(), &binary, &output_path).await?;
+            }
+        }
+        ExportType::Perspective { output_path, kind } => {
+            let content = match kind {
+                PerspectiveKind::Bloom => {
+                    serde_json::to_string_pretty(&perspective::bloom_perspective())?
+                }
+                PerspectiveKind::BrowserGuide => perspective::browser_guide()?,
+            };
+
+            let mut file = AtomicFile::create(&output_path, true)?;
+            file.write_all(content.as_bytes())?;
+            file.commit()?;
+            println!("Wrote {} to {}", kind.description(), output_path);
+        }
+    }
+
+    Ok(())
+}
+
+/// Node-link JSON of the call graph of `binary` (hash or filename)
+async fn export_networkx<S: GraphStore>(
+    session: &ImportSession<S>,
+    binary: &str,
+    output_path: &str,
+) -> Result<()> {
+    let sample = match session.query_binary_by_hash(binary).await? {
+        Some(found) => Some(found),
+        None => session.query_binary_info(binary).await?,
+    };
+    let Some(sample) = sample else {
+        return Err(crate::error::not_found(format!(
+            "No binary found matching: '{}'",
+            binary
+        )));
+    };
+
+    println!(
+        "Exporting call graph of '{}' to {}",
+        sample.filename, output_path
+    );
+    let (functions, links) = session.query_binary_call_graph(&sample.hash).await?;
+    let graph = networkx::node_link_graph(&sample, &functions, &links);
+
+    let mut file = AtomicFile::create(output_path, true)?;
+    serde_json::to_writer(&mut file, &graph)?;
+    file.commit()?;
+    println!(
+        "Exported {} functions and {} call edges",
+        functions.len(),
+        links.len()
+    );
+    Ok(())
+}
+
+fn write_features_csv(file: &mut AtomicFile, cells: &[FeatureCell]) -> Result<()> {
+    let mut writer = csv::Writer::from_writer(file);
+    writer.write_record(["binary", "feature", "value"])?;
+    for cell in cells {
+        writer.write_record([&cell.binary, &cell.feature, &cell.value.to_string()])?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(feature = "parquet")]
+fn write_features_parquet(file: &mut AtomicFile, cells: &[FeatureCell]) -> Result<()> {
+    use parquet::data_type::{ByteArray, ByteArrayType, Int64Type};
+    use parquet::file::properties::WriterProperties;
+    use parquet::file::writer::SerializedFileWriter;
+    use parquet::schema::parser::parse_message_type;
+    use std::sync::Arc;
+
+    let schema = Arc::new(parse_message_type(
+        "message features {
+            REQUIRED BYTE_ARRAY binary (UTF8);
+            REQUIRED BYTE_ARRAY feature (UTF8);
+            REQUIRED INT64 value;
+        }",
+    )?);
+    let properties = Arc::new(WriterProperties::builder().build());
+    let mut writer = SerializedFileWriter::new(file, schema, properties)?;
+
+    for chunk in cells.chunks(PARQUET_ROW_GROUP) {
+        let binaries: Vec<ByteArray> = chunk
+            .iter()
+            .map(|cell| ByteArray::from(cell.binary.as_str()))
+            .collect();
+        let features: Vec<ByteArray> = chunk
+            .iter()
+            .map(|cell| ByteArray::from(cell.feature.as_str()))
+            .collect();
+        let values: Vec<i64> = chunk.iter().map(|cell| cell.value).collect();
+
+        let mut row_group = writer.next_row_group()?;
+        for strings in [&binaries, &features] {
+            let Some(mut column) = row_group.next_column()? else {
+                break;
+            };
+            column
+                .typed::<ByteArrayType>()
+                .write_batch(strings, None, None)?;
+            column.close()?;
+        }
+        if let Some(mut column) = row_group.next_column()? {
+            column
+                .typed::<Int64Type>()
+                .write_batch(&values, None, None)?;
+            column.close()?;
+        }
+        row_group.close()?;
+    }
+
+    writer.close()?;
+    Ok(())
+}
@@ -1,59 +1,494 @@
 use anyhow::Result;
-use std::path::Path;
-
-use crate::api::{DataImporter, ImportResult, ImportStatistics};
+use serde::Serialize;
+use serde_json::Value;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, Mutex};
+use tokio::task::JoinSet;
+
+use crate::api::adapters::{load_native, SourceFormat};
+use crate::api::api_hashes::ApiHashes;
+use crate::api::executable::parse_executable;
+use crate::api::signatures::LibrarySignatures;
+use crate::api::{
+    DataImporter, ErrorPolicy, ImportResult, ImportSession, ImportStatistics, MergeSummary,
+    StringStorage,
+};
 use crate::cli::ImportType;
 use crate::config::Config;
+use crate::neo4j::autotune::BatchTuner;
+use crate::output::AtomicFile;
+use crate::store::{Backend, EmbeddedStore};
+use crate::utils::uid::parse_address;
 
 pub async fn handle_import(import_type: ImportType, config: Config) -> Result<()> {
+    config.ensure_writable("import data")?;
+    if config.backend == Backend::Embedded {
+        return import_embedded(import_type, &config).await;
+    }
     let importer = DataImporter::new(&config).await?;
 
     match import_type {
         ImportType::Json {
             file_path,
-            batch_size: _,
+            batch_size,
+            autotune,
+            target_ms,
             no_validate,
+            library_signatures,
+            api_hashes,
+            source,
+            prune,
+            report,
+            fail_fast,
+            max_errors,
+            strict_addresses,
         } => {
-            let result = import_single_file(&importer, &file_path, !no_validate).await?;
+            let batch_size = batch_size.unwrap_or(config.batch_size);
+            let tuner = autotune.then(|| tuner(batch_size, target_ms));
+            let policy = error_policy(fail_fast, max_errors);
+            let importer = importer
+                .with_batch_size(batch_size)
+                .with_tuner(tuner.clone())
+                .with_library_signatures(load_signatures(library_signatures)?)
+                .with_api_hashes(load_api_hashes(api_hashes)?)
+                .with_pruning(prune)
+                .with_error_policy(policy.clone())
+                .with_strict_addresses(strict_addresses);
+            let started = Instant::now();
+            let outcome = import_single_file(&importer, &file_path, source, !no_validate).await;
+            if let Some(report) = &report {
+                let file = FileReport::new(Path::new(&file_path), &outcome, started.elapsed());
+                write_report(report, &ImportReport::new(vec![file], started.elapsed()))?;
+            }
+            let result = outcome?;
             print_import_result(&result);
+            if let Some(tuner) = &tuner {
+                print_tuning_report(tuner);
+            }
+            check_error_limit(&result.errors, policy.as_ref())?;
         }
         ImportType::Directory {
             dir_path,
             pattern,
             batch_size,
+            autotune,
+            target_ms,
+            writers,
+            queue_size,
             no_validate,
-        } => import_directory(&importer, &dir_path, &pattern, batch_size, !no_validate).await?,
+            library_signatures,
+            api_hashes,
+            source,
+            prune,
+            report,
+            fail_fast,
+            max_errors,
+            strict_addresses,
+        } => {
+            let batch_size = batch_size.unwrap_or(config.batch_size);
+            let tuner = autotune.then(|| tuner(batch_size, target_ms));
+            let policy = error_policy(fail_fast, max_errors);
+            let importer = importer
+                .with_batch_size(batch_size)
+                .with_tuner(tuner.clone())
+                .with_library_signatures(load_signatures(library_signatures)?)
+                .with_api_hashes(load_api_hashes(api_hashes)?)
+                .with_pruning(prune)
+                .with_error_policy(policy.clone())
+                .with_strict_addresses(strict_addresses);
+            let queue = WriteQueue {
+                writers: writers.max(1),
+                capacity: queue_size.max(1),
+            };
+            println!("Batch size: {} rows per write", batch_size);
+            import_directory(
+                &importer,
+                &dir_path,
+                &pattern,
+                queue,
+                source,
+                !no_validate,
+                report.as_deref(),
+            )
+            .await?;
+            if let Some(tuner) = &tuner {
+                print_tuning_report(tuner);
+            }
+        }
+        ImportType::Binary {
+            file_path,
+            min_string_length,
+            library_signatures,
+            prune,
+        } => {
+            let importer = importer
+                .with_library_signatures(load_signatures(library_signatures)?)
+                .with_pruning(prune);
+            import_binary(&importer, &file_path, min_string_length).await?
+        }
+        ImportType::Relations { file_path } => import_relations(&importer, &file_path).await?,
+        ImportType::Trace { file_path, binary } => {
+            import_trace(&importer, &file_path, binary.as_deref()).await?
+        }
+        ImportType::Symbols {
+            map_file,
+            binary,
+            image_base,
+        } => import_symbols(&importer, &map_file, &binary, &image_base).await?,
+        ImportType::Network { file_path, binary } => {
+            import_network(&importer, &file_path, binary.as_deref()).await?
+        }
+        ImportType::Annotations { file_path } => import_annotations(&importer, &file_path).await?,
+        ImportType::Objects { file_path, binary } => {
+            import_objects(&importer, &file_path, binary.as_deref()).await?
+        }
     }
 
     Ok(())
 }
 
+fn tuner(batch_size: usize, target_ms: u64) -> BatchTuner {
+    println!(
+        "Autotuning batches to {} ms per write, starting at {} rows",
+        target_ms, batch_size
+    );
+    BatchTuner::new(Duration::from_millis(target_ms), batch_size)
+}
+
+fn print_tuning_report(tuner: &BatchTuner) {
+    let report = tuner.report();
+    println!("\nAutotuning:");
+    println!(
+        "  Batched writes: {} ({} rows in {:.1}s, {:.0} rows/sec)",
+        report.batches,
+        report.rows,
+        report.elapsed.as_secs_f64(),
+        report.rows_per_sec()
+    );
+    println!(
+        "  Settled on: {} rows per batch, {} concurrent writes ({} adjustments)",
+        report.batch_size, report.concurrency, report.adjustments
+    );
+}
+
+fn load_signatures(path: Option<String>) -> Result<Option<LibrarySignatures>> {
+    let Some(path) = path else {
+        return Ok(None);
+    };
+    let signatures = LibrarySignatures::load(&path)?;
+    if signatures.is_empty() {
+        eprintln!("[WARN] No library signatures found in {}", path);
+    }
+    println!(
+        "Loaded {} library signatures from {}",
+        signatures.len(),
+        path
+    );
+    Ok(Some(signatures))
+}
+
+fn load_api_hashes(path: Option<String>) -> Result<Option<ApiHashes>> {
+    let Some(path) = path else {
+        return Ok(None);
+    };
+    let api_hashes = ApiHashes::load(&path)?;
+    if api_hashes.is_empty() {
+        eprintln!("[WARN] No API hashes found in {}", path);
+    }
+    println!("Loaded {} API hashes from {}", api_hashes.len(), path);
+    Ok(Some(api_hashes))
+}
+
+/// `import json` and `import binary` into the embedded store, saved once the import
+/// finished
+async fn import_embedded(import_type: ImportType, config: &Config) -> Result<()> {
+    let store = EmbeddedStore::open(&config.embedded_path)?;
+    let session = ImportSession::new(store.clone())
+        .with_string_storage(StringStorage::from_config(config))
+        .with_relative_addresses(config.relative_addresses);
+    println!("Embedded store: {}", store.path().display());
+
+    match import_type {
+        ImportType::Json {
+            file_path,
+            no_validate,
+            library_signatures,
+            api_hashes,
+            source,
+            prune,
+            report,
+            fail_fast,
+            max_errors,
+            strict_addresses,
+            ..
+        } => {
+            let policy = error_policy(fail_fast, max_errors);
+            let session = session
+                .with_pruning(prune)
+                .with_library_signatures(load_signatures(library_signatures)?)
+                .with_api_hashes(load_api_hashes(api_hashes)?)
+                .with_error_policy(policy.clone())
+                .with_strict_addresses(strict_addresses);
+            let started = Instant::now();
+            println!("Importing file: {}", file_path);
+            let outcome = match load_file(&file_path, source, !no_validate) {
+                Ok(data) => session.import_data(data).await,
+                Err(e) => Err(e),
+            };
+            if let Some(report) = &report {
+                let file = FileReport::new(Path::new(&file_path), &outcome, started.elapsed());
+                write_report(report, &ImportReport::new(vec![file], started.elapsed()))?;
+            }
+            let result = outcome?;
+            store.save()?;
+            print_import_result(&result);
+            check_error_limit(&result.errors, policy.as_ref())
+        }
+        ImportType::Binary {
+            file_path,
+            min_string_length,
+            library_signatures,
+            prune,
+        } => {
+            println!("Parsing executable: {}", file_path);
+            if !Path::new(&file_path).exists() {
+                return Err(crate::error::not_found(format!(
+                    "File not found: {}",
+                    file_path
+                )));
+            }
+            let data = parse_executable(&file_path, min_string_length)?;
+            let result = session
+                .with_library_signatures(load_signatures(library_signatures)?)
+                .with_pruning(prune)
+                .import_data(data)
+                .await?;
+            store.save()?;
+            print_import_result(&result);
+            check_errors(&result.errors)
+        }
+        _ => Err(crate::error::validation(
+            "The embedded backend only supports `import json` and `import binary`",
+            Vec::new(),
+        )),
+    }
+}
+
+async fn import_binary(
+    importer: &DataImporter,
+    file_path: &str,
+    min_string_length: usize,
+) -> Result<()> {
+    println!("Parsing executable: {}", file_path);
+
+    if !Path::new(file_path).exists() {
+        return Err(crate::error::not_found(format!(
+            "File not found: {}",
+            file_path
+        )));
+    }
+
+    let data = parse_executable(file_path, min_string_length)?;
+    println!("Importing data...");
+    let result = importer.import_from_json(data).await?;
+    print_import_result(&result);
+
+    check_errors(&result.errors)
+}
+
+async fn import_relations(importer: &DataImporter, file_path: &str) -> Result<()> {
+    println!("Importing relations: {}", file_path);
+
+    if !Path::new(file_path).exists() {
+        return Err(crate::error::not_found(format!(
+            "File not found: {}",
+            file_path
+        )));
+    }
+
+    let result = importer.import_relations_from_file(file_path).await?;
+
+    println!(
+        "\nImport completed {}!",
+        if result.success {
+            "successfully"
+        } else {
+            "with errors"
+        }
+    );
+    println!("  Relations: {}", result.relations);
+
+    if !result.errors.is_empty() {
+        println!("\nErrors encountered:");
+        for error in result.errors.iter().take(10) {
+            println!("  - {}", error);
+        }
+        if result.errors.len() > 10 {
+            println!("  ... and {} more errors", result.errors.len() - 10);
+        }
+    }
+
+    check_errors(&result.errors)
+}
+
+async fn import_trace(
+    importer: &DataImporter,
+    file_path: &str,
+    binary_hash: Option<&str>,
+) -> Result<()> {
+    println!("Importing API trace: {}", file_path);
+
+    if !Path::new(file_path).exists() {
+        return Err(crate::error::not_found(format!(
+            "File not found: {}",
+            file_path
+        )));
+    }
+
+    let result = importer
+        .import_trace_from_file(file_path, binary_hash)
+        .await?;
+
+    println!("\nImport completed for binary {}", result.binary_hash);
+    println!("  APIs in trace: {}", result.apis);
+    println!("  Runtime call relationships: {}", result.relationships);
+
+    if !result.unresolved.is_empty() {
+        println!(
+            "\n[WARN] {} APIs have no matching import function:",
+            result.unresolved.len()
+        );
+        for api in result.unresolved.iter().take(10) {
+            println!("  - {}", api);
+        }
+        if result.unresolved.len() > 10 {
+            println!("  ... and {} more", result.unresolved.len() - 10);
+        }
+    }
+
+    Ok(())
+}
+
+async fn import_symbols(
+    importer: &DataImporter,
+    map_file: &str,
+    binary: &str,
+    image_base: &str,
+) -> Result<()> {
+    println!("Importing symbols: {}", map_file);
+
+    if !Path::new(map_file).exists() {
+        return Err(crate::error::not_found(format!(
+            "File not found: {}",
+            map_file
+        )));
+    }
+    let image_base = parse_address(image_base)
+        .ok_or_else(|| anyhow::anyhow!("Invalid image base: '{}'", image_base))?;
+
+    let result = importer
+        .import_symbols_from_file(map_file, binary, image_base)
+        .await?;
+
+    println!("\nImport completed for binary {}", result.binary_hash);
+    println!("  Symbols in map: {}", result.symbols);
+    println!("  Functions renamed: {}", result.renamed);
+
+    if result.renamed == 0 && result.symbols > 0 {
+        println!("\n[WARN] No function addresses matched; check --image-base");
+    }
+
+    Ok(())
+}
+
+async fn import_network(
+    importer: &DataImporter,
+    file_path: &str,
+    binary_hash: Option<&str>,
+) -> Result<()> {
+    println!("Importing network activity: {}", file_path);
+
+    if !Path::new(file_path).exists() {
+        return Err(crate::error::not_found(format!(
+            "File not found: {}",
+            file_path
+        )));
+    }
+
+    let result = importer
+        .import_network_from_file(file_path, binary_hash)
+        .await?;
+
+    println!(
+        "\nImport completed {} for binary {}",
+        if result.success {
+            "successfully"
+        } else {
+            "with errors"
+        },
+        result.binary_hash
+    );
+    println!("  Indicators: {}", result.indicators);
+    println!("  DNS resolutions: {}", result.resolutions);
+
+    if !result.errors.is_empty() {
+        println!("\nErrors encountered:");
+        for error in result.errors.iter().take(10) {
+            println!("  - {}", error);
+        }
+        if result.errors.len() > 10 {
+            println!("  ... and {} more errors", result.errors.len() - 10);
+        }
+    }
+
+    check_errors(&result.errors)
+}
+
 async fn import_single_file(
     importer: &DataImporter,
     file_path: &str,
+    source: SourceFormat,
     validate: bool,
 ) -> Result<ImportResult> {
     println!("Importing file: {}", file_path);
+    let data = load_file(file_path, source, validate)?;
+
+    println!("Importing data...");
+    let result = importer.import_from_json(data).await?;
+    println!("Import completed");
+
+    Ok(result)
+}
 
+/// Read an extractor output file, converted to the native format and validated
+fn load_file(file_path: &str, source: SourceFormat, validate: bool) -> Result<Value> {
     if !Path::new(file_path).exists() {
-        return Err(anyhow::anyhow!("File not found: {}", file_path));
+        return Err(crate::error::not_found(format!(
+            "File not found: {}",
+            file_path
+        )));
+    }
+
+    let (data, format) = load_native(file_path, source)?;
+    if format != "native" {
+        println!("Converted {} input", format);
     }
 
     if validate {
         println!("Validating data...");
-        let data: serde_json::Value = {
-            let file = std::fs::File::open(file_path)?;
-            let reader = std::io::BufReader::new(file);
-            serde_json::from_reader(reader)?
-        };
-
-        let validation = importer.validate_data(&data).await?;
+        let validation = DataImporter::validate_data(&data)?;
         if !validation.valid {
             println!("Validation failed:");
             for error in &validation.errors {
                 println!("  - {}", error);
             }
-            return Err(anyhow::anyhow!("Data validation failed"));
+            return Err(crate::error::validation(
+                "Data validation failed",
+                validation.errors,
+            ));
         }
 
         if !validation.warnings.is_empty() {
@@ -65,11 +500,7 @@ async fn import_single_file(
         println!("Validation passed");
     }
 
-    println!("Importing data...");
-    let result = importer.import_from_file(file_path).await?;
-    println!("Import completed");
-
-    Ok(result)
+    Ok(data)
 }
 
 fn print_import_result(result: &ImportResult) {
@@ -86,11 +517,17 @@ fn print_import_result(result: &ImportResult) {
     println!("  Functions: {}", result.statistics.functions);
     println!("  Strings: {}", result.statistics.strings);
     println!("  Libraries: {}", result.statistics.libraries);
+    println!("  Resources: {}", result.statistics.resources);
     println!(
         "  Call relationships: {}",
         result.statistics.calls_relationships
     );
+    println!("  Data references: {}", result.statistics.data_references);
+    println!("  VTables: {}", result.statistics.vtables);
     println!("  Total nodes: {}", result.statistics.total_nodes);
+    if let Some(merge) = &result.merge {
+        print_merge_summary(merge);
+    }
 
     if !result.errors.is_empty() {
         println!("\nErrors encountered:");
@@ -101,18 +538,182 @@ fn print_import_result(result: &ImportResult) {
             println!("  ... and {} more errors", result.errors.len() - 10);
         }
     }
+
+    if !result.unparseable_addresses.is_empty() {
+        println!("\nUnparseable addresses (use --strict-addresses to reject such files):");
+        for address in result.unparseable_addresses.iter().take(10) {
+            println!("  - {}", address);
+        }
+        if result.unparseable_addresses.len() > 10 {
+            println!(
+                "  ... and {} more (listed in --report)",
+                result.unparseable_addresses.len() - 10
+            );
+        }
+    }
+}
+
+/// Summary of an import run written by `--report`, for pipelines registering the
+/// imported binaries elsewhere
+#[derive(Debug, Serialize)]
+struct ImportReport {
+    success: bool,
+    elapsed_ms: u128,
+    /// SHA-256 of every binary written, in file order
+    binaries: Vec<String>,
+    totals: ImportStatistics,
+    files: Vec<FileReport>,
+}
+
+impl ImportReport {
+    fn new(files: Vec<FileReport>, elapsed: Duration) -> Self {
+        let mut totals = ImportStatistics::default();
+        for statistics in files.iter().filter_map(|file| file.statistics.as_ref()) {
+            totals.add(statistics);
+        }
+        Self {
+            success: files.iter().all(|file| file.success),
+            elapsed_ms: elapsed.as_millis(),
+            binaries: files
+                .iter()
+                .filter_map(|file| file.binary_hash.clone())
+                .collect(),
+            totals,
+            files,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct FileReport {
+    file: String,
+    success: bool,
+    /// Time spent writing the file to Neo4j, or loading and writing it for `import json`
+    elapsed_ms: u128,
+    binary_hash: Option<String>,
+    /// Absent when the file could not be read or its import was aborted
+    statistics: Option<ImportStatistics>,
+    merge: Option<MergeSummary>,
+    errors: Vec<String>,
+    /// Address fields that could not be parsed
+    unparseable_addresses: Vec<String>,
+}
+
+impl FileReport {
+    fn new(path: &Path, outcome: &Result<ImportResult>, elapsed: Duration) -> Self {
+        let file = path.display().to_string();
+        let elapsed_ms = elapsed.as_millis();
+        match outcome {
+            Ok(result) => Self {
+                file,
+                success: result.success,
+                elapsed_ms,
+                binary_hash: result.binary_hash.clone(),
+                statistics: Some(result.statistics.clone()),
+                merge: result.merge.clone(),
+                errors: result.errors.clone(),
+                unparseable_addresses: result.unparseable_addresses.clone(),
+            },
+            Err(e) => {
+                let details = crate::error::details(e);
+                Self {
+                    file,
+                    success: false,
+                    elapsed_ms,
+                    binary_hash: None,
+                    statistics: None,
+                    merge: None,
+                    errors: if details.is_empty() {
+                        vec![e.to_string()]
+                    } else {
+                        details.to_vec()
+                    },
+                    unparseable_addresses: Vec::new(),
+                }
+            }
+        }
+    }
+}
+
+fn write_report(path: &str, report: &ImportReport) -> Result<()> {
+    let mut file = AtomicFile::create(path, true)?;
+    serde_json::to_writer_pretty(&mut file, report)?;
+    file.write_all(b"\n")?;
+    file.commit()?;
+    println!("Import report written to {}", path);
+    Ok(())
+}
+
+/// `--fail-fast` allows no errors, `--max-errors N` allows N
+fn error_policy(fail_fast: bool, max_errors: Option<usize>) -> Option<ErrorPolicy> {
+    if fail_fast {
+        Some(ErrorPolicy::new(0))
+    } else {
+        max_errors.map(ErrorPolicy::new)
+    }
+}
+
+/// Fail with a partial-import error when items of a finished import failed
+fn check_errors(errors: &[String]) -> Result<()> {
+    check_error_limit(errors, None)
+}
+
+/// As `check_errors`, tolerating as many errors as the policy allows
+fn check_error_limit(errors: &[String], policy: Option<&ErrorPolicy>) -> Result<()> {
+    if errors.len() <= policy.map_or(0, ErrorPolicy::max_errors) {
+        return Ok(());
+    }
+    Err(crate::error::partial_import(
+        format!("Import finished with {} errors", errors.len()),
+        errors.to_vec(),
+    ))
+}
+
+fn print_merge_summary(merge: &MergeSummary) {
+    println!("Merged into existing binary:");
+    println!(
+        "  Functions: +{} / -{}",
+        merge.functions_added, merge.functions_removed
+    );
+    println!(
+        "  Strings: +{} / -{}",
+        merge.strings_added, merge.strings_removed
+    );
+    println!("  Calls: +{} / -{}", merge.calls_added, merge.calls_removed);
+    let removed = merge.functions_removed + merge.strings_removed + merge.calls_removed;
+    if removed > 0 && !merge.pruned {
+        println!(
+            "  {} stale elements kept (re-run with --prune to delete them)",
+            removed
+        );
+    }
+}
+
+/// How files of a directory import flow from the parser to the writers
+struct WriteQueue {
+    /// Files written to Neo4j at once
+    writers: usize,
+    /// Parsed files waiting for a writer
+    capacity: usize,
 }
 
 async fn import_directory(
     importer: &DataImporter,
     dir_path: &str,
     pattern: &str,
-    batch_size: usize,
+    queue: WriteQueue,
+    source: SourceFormat,
     validate: bool,
+    report: Option<&str>,
 ) -> Result<()> {
+    let policy = importer.error_policy();
+    let started = Instant::now();
     println!("Importing directory: {}", dir_path);
     println!("Pattern: {}", pattern);
-    println!("Batch size: {}", batch_size);
+    println!(
+        "Writers: {} (up to {} parsed files queued)",
+        queue.writers, queue.capacity
+    );
 
     if !Path::new(dir_path).exists() {
         return Err(anyhow::anyhow!("Directory not found: {}", dir_path));
@@ -139,73 +740,95 @@ async fn import_directory(
 
     println!("Found {} files to import", files.len());
 
-    let mut total_stats = ImportStatistics {
-        binaries: 0,
-        functions: 0,
-        strings: 0,
-        libraries: 0,
-        calls_relationships: 0,
-        total_nodes: 0,
-    };
+    let mut total_stats = ImportStatistics::default();
     let mut total_errors = Vec::new();
     let mut success_count = 0;
     let total_files = files.len();
 
-    // Process files in batches
-    let batches: Vec<_> = files.chunks(batch_size).collect();
-    let total_batches = batches.len();
-
-    for (batch_idx, batch) in batches.iter().enumerate() {
-        println!(
-            "\n=== Processing batch {}/{} ({} files) ===",
-            batch_idx + 1,
-            total_batches,
-            batch.len()
-        );
-
-        let batch_start_idx = batch_idx * batch_size;
-
-        for (file_idx, file_path) in batch.iter().enumerate() {
-            let overall_idx = batch_start_idx + file_idx + 1;
+    // One task parses files ahead of the writers so parsing overlaps the Neo4j writes;
+    // the bounded queue caps how many parsed files are held in memory
+    let (sender, receiver) = mpsc::channel::<(PathBuf, Result<Value>)>(queue.capacity);
+    let parser = tokio::spawn(async move {
+        for (file_idx, file_path) in files.into_iter().enumerate() {
             println!(
-                "[{}/{}] Importing {}...",
-                overall_idx,
+                "[{}/{}] Parsing {}...",
+                file_idx + 1,
                 total_files,
                 file_path.display()
             );
-
-            match import_single_file(importer, &file_path.to_string_lossy(), validate).await {
-                Ok(result) => {
-                    total_stats.binaries += result.statistics.binaries;
-                    total_stats.functions += result.statistics.functions;
-                    total_stats.strings += result.statistics.strings;
-                    total_stats.libraries += result.statistics.libraries;
-                    total_stats.calls_relationships += result.statistics.calls_relationships;
-                    total_stats.total_nodes += result.statistics.total_nodes;
-
-                    for error in result.errors {
-                        total_errors.push(format!("{}: {}", file_path.display(), error));
+            let loaded = load_file(&file_path.to_string_lossy(), source, validate);
+            if sender.send((file_path, loaded)).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let receiver = Arc::new(Mutex::new(receiver));
+    let mut writers = JoinSet::new();
+    for _ in 0..queue.writers {
+        let receiver = receiver.clone();
+        let importer = importer.clone();
+        let policy = policy.cloned();
+        writers.spawn(async move {
+            let mut outcomes = Vec::new();
+            loop {
+                // Over the error limit: leave the remaining files unimported
+                if policy.as_ref().is_some_and(ErrorPolicy::exceeded) {
+                    break;
+                }
+                let next = receiver.lock().await.recv().await;
+                let Some((file_path, loaded)) = next else {
+                    break;
+                };
+                let file_started = Instant::now();
+                let outcome = match loaded {
+                    Ok(data) => importer.import_from_json(data).await,
+                    Err(e) => {
+                        if let Some(policy) = &policy {
+                            policy.record(1);
+                        }
+                        Err(e)
                     }
-
-                    if result.success {
-                        success_count += 1;
+                };
+                match &outcome {
+                    Ok(result) => {
+                        println!("Imported {}", file_path.display());
+                        if let Some(merge) = &result.merge {
+                            print_merge_summary(merge);
+                        }
                     }
+                    Err(e) => println!("Failed to import {}: {}", file_path.display(), e),
                 }
-                Err(e) => {
-                    println!("Failed to import {}: {}", file_path.display(), e);
-                    total_errors.push(format!("{}: {}", file_path.display(), e));
-                }
+                outcomes.push((file_path, outcome, file_started.elapsed()));
             }
-        }
+            outcomes
+        });
+    }
 
-        let batch_end_idx = batch_start_idx + batch.len();
-        println!(
-            "Batch {}/{} completed. Progress: {}/{} files",
-            batch_idx + 1,
-            total_batches,
-            batch_end_idx,
-            total_files
-        );
+    let mut outcomes = Vec::new();
+    while let Some(written) = writers.join_next().await {
+        outcomes.extend(written?);
+    }
+    parser.await?;
+
+    let mut file_reports = Vec::new();
+    for (file_path, outcome, elapsed) in outcomes {
+        let file_report = FileReport::new(&file_path, &outcome, elapsed);
+        for error in &file_report.errors {
+            total_errors.push(format!("{}: {}", file_path.display(), error));
+        }
+        if let Ok(result) = &outcome {
+            total_stats.add(&result.statistics);
+            if result.success {
+                success_count += 1;
+            }
+        }
+        if report.is_some() {
+            file_reports.push(file_report);
+        }
+    }
+    if let Some(report) = report {
+        write_report(report, &ImportReport::new(file_reports, started.elapsed()))?;
     }
 
     println!("\nDirectory import completed!");
@@ -216,7 +839,10 @@ async fn import_directory(
     println!("  Functions: {}", total_stats.functions);
     println!("  Strings: {}", total_stats.strings);
     println!("  Libraries: {}", total_stats.libraries);
+    println!("  Resources: {}", total_stats.resources);
     println!("  Call relationships: {}", total_stats.calls_relationships);
+    println!("  Data references: {}", total_stats.data_references);
+    println!("  VTables: {}", total_stats.vtables);
     println!("  Total nodes: {}", total_stats.total_nodes);
 
     if !total_errors.is_empty() {
@@ -229,7 +855,18 @@ async fn import_directory(
         }
     }
 
-    Ok(())
+    if let Some(policy) = policy.filter(|policy| policy.exceeded()) {
+        return Err(crate::error::partial_import(
+            format!(
+                "Import aborted after more than {} errors, {} of {} files imported",
+                policy.max_errors(),
+                success_count,
+                total_files
+            ),
+            total_errors,
+        ));
+    }
+    check_error_limit(&total_errors, policy)
 }
 
 fn matches_pattern(filename: &str, pattern: &str) -> bool {
@@ -254,3 +891,79 @@ fn matches_pattern(filename: &str, pattern: &str) -> bool {
 
     filename == pattern
 }
+
+async fn import_objects(
+    importer: &DataImporter,
+    file_path: &str,
+    binary_hash: Option<&str>,
+) -> Result<()> {
+    println!("Importing named objects: {}", file_path);
+
+    if !Path::new(file_path).exists() {
+        return Err(crate::error::not_found(format!(
+            "File not found: {}",
+            file_path
+        )));
+    }
+
+    let result = importer
+        .import_objects_from_file(file_path, binary_hash)
+        .await?;
+
+    println!(
+        "\nImport completed {} for binary {}",
+        if result.success {
+            "successfully"
+        } else {
+            "with errors"
+        },
+        result.binary_hash
+    );
+    println!("  Named objects: {}", result.objects);
+
+    if !result.errors.is_empty() {
+        println!("\nErrors encountered:");
+        for error in result.errors.iter().take(10) {
+            println!("  - {}", error);
+        }
+        if result.errors.len() > 10 {
+            println!("  ... and {} more errors", result.errors.len() - 10);
+        }
+    }
+
+    check_errors(&result.errors)
+}
+
+async fn import_annotations(importer: &DataImporter, file_path: &str) -> Result<()> {
+    println!("Importing annotations: {}", file_path);
+
+    if !Path::new(file_path).exists() {
+        return Err(crate::error::not_found(format!(
+            "File not found: {}",
+            file_path
+        )));
+    }
+
+    let result = importer.import_annotations_from_file(file_path).await?;
+
+    println!("\nAnnotations restored:");
+    println!(
+        "  Functions: {}/{}",
+        result.functions_applied, result.functions
+    );
+    println!(
+        "  Binaries: {}/{}",
+        result.binaries_applied, result.binaries
+    );
+
+    let missing =
+        (result.functions - result.functions_applied) + (result.binaries - result.binaries_applied);
+    if missing > 0 {
+        println!(
+            "\n[WARN] {} entries refer to functions or binaries not in the graph; import their extractor data first",
+            missing
+        );
+    }
+
+    Ok(())
+}
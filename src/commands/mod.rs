@@ -1,3 +1,8 @@
+pub mod analyze;
+pub mod annotate;
 pub mod database;
+pub mod export;
 pub mod import;
 pub mod query;
+pub mod report;
+pub mod serve;
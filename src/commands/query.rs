@@ -1,100 +1,593 @@
 use anyhow::Result;
+use serde::Serialize;
+use std::future::Future;
+use std::io::Read;
 
 use crate::api::DataImporter;
 use crate::cli::QueryType;
 use crate::config::Config;
-use crate::neo4j::call_path_analyzer::RecursiveCallType;
+use crate::models::{
+    Architecture, CallContextAnalysis, CallPath, CallSequence, CallerSequence, EnhancedCallGraph,
+    Function, FunctionSort, ObjectKind, PathExpander, UpwardCallChain, XrefDirection, HIGH_ENTROPY,
+};
+use crate::neo4j::call_path_analyzer::{RecursiveCall, RecursiveCallType};
+use crate::neo4j::connection::{QueryTiming, QueryTracing};
+use crate::neo4j::{BinaryFilter, CallEdge, CallGraph};
+use crate::output::{
+    AddressLocation, BatchRow, CallGraphEntry, CallPathEntry, Diagram, EmbeddingMatch,
+    FunctionDetail, MetricEntry, OutputFormat, OutputOptions, PresetEntry, Renderer, SimilarBinary,
+};
+use crate::store::{Backend, EmbeddedStore, GraphStore};
+use crate::utils::crypto;
+use crate::utils::fuzzy;
+use crate::utils::presets::QueryPresets;
+use crate::utils::time::is_iso_date;
+use crate::utils::uid::{format_address, parse_address, parse_uid, Uid};
+
+/// Query run for each target of `query batch`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum BatchQuery {
+    /// Functions whose name or UID contains the target
+    Functions,
+    /// Strings containing the target text (fulltext search)
+    Strings,
+    /// Binary with the target as hash, or else as filename
+    Binary,
+    /// Calls to and from the target function or address
+    Xrefs,
+    /// Callees of the target function ranked by call sites
+    HotCallees,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct CallGraphQueryConfig<'a> {
+    binary: Option<&'a str>,
+    show_callees: bool,
+    show_callers: bool,
+    max_depth: usize,
+    exclude_library: bool,
+    min_confidence: f64,
+}
 
 #[derive(Debug)]
 struct CallPathQueryConfig<'a> {
     binary: Option<&'a str>,
+    exclude: &'a [String],
+    exclude_library: bool,
+    min_confidence: f64,
+    expand_virtual: bool,
     show_paths: bool,
     show_sequences: bool,
     show_recursive: bool,
     show_upward: bool,
     show_context: bool,
     max_depth: usize,
-    format: &'a str,
+    max_results: usize,
+    expander: PathExpander,
+}
+
+/// Combined result of a call-path query, used for JSON/YAML/CSV output
+#[derive(Debug, Default, Serialize)]
+struct CallPathReport {
+    function_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    call_paths: Option<Vec<CallPath>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    call_sequences: Option<Vec<CallSequence>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    recursive_calls: Option<Vec<RecursiveCall>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    upward_chains: Option<Vec<UpwardCallChain>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    caller_sequences: Option<Vec<CallerSequence>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    context: Option<CallContextAnalysis>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    enhanced_graph: Option<EnhancedCallGraph>,
+}
+
+impl CallPathReport {
+    fn path_entries(&self) -> Vec<CallPathEntry> {
+        let mut entries = Vec::new();
+
+        for path in self.call_paths.iter().flatten() {
+            for node in &path.nodes {
+                entries.push(CallPathEntry {
+                    path_id: path.id.clone(),
+                    direction: "downward",
+                    depth: node.depth,
+                    name: node.name.clone(),
+                    address: node.address.clone(),
+                    call_site: node.call_site.clone(),
+                });
+            }
+        }
+
+        for chain in self.upward_chains.iter().flatten() {
+            for node in &chain.nodes {
+                entries.push(CallPathEntry {
+                    path_id: chain.id.clone(),
+                    direction: "upward",
+                    depth: node.depth,
+                    name: node.name.clone(),
+                    address: node.address.clone(),
+                    call_site: node.call_site.clone(),
+                });
+            }
+        }
+
+        entries
+    }
 }
 
-pub async fn handle_query(query_type: QueryType, config: Config) -> Result<()> {
-    let importer = DataImporter::new(&config).await?;
+pub async fn handle_query(
+    query_type: QueryType,
+    tracing: QueryTracing,
+    config: Config,
+    output: &OutputOptions,
+) -> Result<()> {
+    // Listing presets does not need a database connection
+    if let QueryType::Preset {
+        list: true,
+        presets,
+        format,
+        ..
+    } = &query_type
+    {
+        let presets = QueryPresets::load(presets.as_deref())?;
+        let mut renderer = Renderer::new(*format, output)?;
+        list_presets(&mut renderer, &presets)?;
+        return renderer.finish();
+    }
+
+    if config.backend == Backend::Embedded {
+        return query_embedded(query_type, &config, output).await;
+    }
+
+    let importer = DataImporter::for_analysis(&config).await?;
+    importer.trace_queries(tracing);
     let session = importer.session();
 
     match query_type {
+        QueryType::Search {
+            term,
+            limit,
+            format,
+        } => {
+            let mut renderer = Renderer::new(format, output)?;
+            query_search(&session, &mut renderer, &term, limit).await?;
+            renderer.finish()?;
+        }
         QueryType::Functions {
             pattern,
             binary,
             limit,
+            sort,
+            param_type,
+            verbose,
             format,
-        } => query_functions(&session, &pattern, binary.as_deref(), limit, &format).await?,
+        } => {
+            let mut renderer = Renderer::new(format, output)?;
+            match sort {
+                Some(sort) => {
+                    query_functions_by_metric(
+                        &session,
+                        &mut renderer,
+                        &pattern,
+                        binary.as_deref(),
+                        sort,
+                        limit,
+                    )
+                    .await?
+                }
+                None => {
+                    query_functions(
+                        &session,
+                        &mut renderer,
+                        &pattern,
+                        binary.as_deref(),
+                        param_type.as_deref(),
+                        verbose,
+                        limit,
+                    )
+                    .await?
+                }
+            }
+            renderer.finish()?;
+        }
         QueryType::Strings {
             pattern,
             binary,
             limit,
             raw,
             format,
-        } => query_strings(&session, &pattern, raw, binary.as_deref(), limit, &format).await?,
+        } => {
+            let mut renderer = Renderer::new(format, output)?;
+            query_strings(
+                &session,
+                &mut renderer,
+                &pattern,
+                raw,
+                binary.as_deref(),
+                limit,
+            )
+            .await?;
+            renderer.finish()?;
+        }
+        QueryType::Pseudocode {
+            pattern,
+            binary,
+            limit,
+            raw,
+            format,
+        } => {
+            let mut renderer = Renderer::new(format, output)?;
+            query_pseudocode(
+                &session,
+                &mut renderer,
+                &pattern,
+                raw,
+                binary.as_deref(),
+                limit,
+            )
+            .await?;
+            renderer.finish()?;
+        }
         QueryType::Binary {
             binary_name,
+            hash,
+            format,
+        } => {
+            let mut renderer = Renderer::new(format, output)?;
+            query_binary(
+                &session,
+                &mut renderer,
+                binary_name.as_deref(),
+                hash.as_deref(),
+            )
+            .await?;
+            renderer.finish()?;
+        }
+        QueryType::Similar {
+            binary,
+            min_ssdeep,
+            max_tlsh,
+            limit,
+            format,
+        } => {
+            let mut renderer = Renderer::new(format, output)?;
+            query_similar_binaries(
+                &session,
+                &mut renderer,
+                &binary,
+                min_ssdeep,
+                max_tlsh,
+                limit,
+            )
+            .await?;
+            renderer.finish()?;
+        }
+        QueryType::SimilarByEmbedding {
+            binary,
+            min_similarity,
+            limit,
+            format,
+        } => {
+            let mut renderer = Renderer::new(format, output)?;
+            query_similar_by_embedding(&session, &mut renderer, &binary, min_similarity, limit)
+                .await?;
+            renderer.finish()?;
+        }
+        QueryType::SharedApis {
+            binary,
+            min_shared,
+            limit,
+            format,
+        } => {
+            let mut renderer = Renderer::new(format, output)?;
+            query_shared_apis(&session, &mut renderer, &binary, min_shared, limit).await?;
+            renderer.finish()?;
+        }
+        QueryType::Library {
+            name,
+            examples,
+            limit,
+            format,
+        } => {
+            let mut renderer = Renderer::new(format, output)?;
+            query_library(&session, &mut renderer, &name, examples, limit).await?;
+            renderer.finish()?;
+        }
+        QueryType::Binaries {
+            compiler,
+            packer,
+            imphash,
+            high_entropy,
+            arch,
+            since,
+            compiled_since,
+            limit,
+            format,
+        } => {
+            check_filter_dates(since.as_deref(), compiled_since.as_deref())?;
+            let mut renderer = Renderer::new(format, output)?;
+            let filter = BinaryFilter {
+                compiler: compiler.as_deref(),
+                packer: packer.as_deref(),
+                imphash: imphash.as_deref(),
+                min_entropy: high_entropy.then_some(HIGH_ENTROPY),
+                arch,
+                since: since.as_deref(),
+                compiled_since: compiled_since.as_deref(),
+            };
+            query_binaries(&session, &mut renderer, &filter, limit).await?;
+            renderer.finish()?;
+        }
+        QueryType::Indicators {
+            value,
+            binary,
+            limit,
+            format,
+        } => {
+            let mut renderer = Renderer::new(format, output)?;
+            query_indicators(
+                &session,
+                &mut renderer,
+                value.as_deref(),
+                binary.as_deref(),
+                limit,
+            )
+            .await?;
+            renderer.finish()?;
+        }
+        QueryType::Crypto {
+            algorithm,
+            binary,
+            limit,
+            format,
+        } => {
+            let mut renderer = Renderer::new(format, output)?;
+            query_crypto(
+                &session,
+                &mut renderer,
+                algorithm.as_deref(),
+                binary.as_deref(),
+                limit,
+            )
+            .await?;
+            renderer.finish()?;
+        }
+        QueryType::Objects {
+            kind,
+            name,
+            binary,
+            limit,
             format,
-        } => query_binary(&session, &binary_name, &format).await?,
+        } => {
+            let mut renderer = Renderer::new(format, output)?;
+            query_objects(
+                &session,
+                &mut renderer,
+                kind,
+                name.as_deref(),
+                binary.as_deref(),
+                limit,
+            )
+            .await?;
+            renderer.finish()?;
+        }
         QueryType::Callgraph {
             function_name,
             binary,
             show_callees,
             show_callers,
             max_depth,
+            exclude_library,
+            min_confidence,
             format,
         } => {
-            query_callgraph(
-                &session,
-                &function_name,
-                binary.as_deref(),
+            let mut renderer = Renderer::new(format, output)?;
+            let config = CallGraphQueryConfig {
+                binary: binary.as_deref(),
                 show_callees,
                 show_callers,
                 max_depth,
-                &format,
-            )
-            .await?
+                exclude_library,
+                min_confidence,
+            };
+            let function_name = function_ref(&function_name);
+            query_callgraph(&session, &mut renderer, &function_name, &config).await?;
+            renderer.finish()?;
         }
         QueryType::Xrefs {
-            address,
+            target,
+            binary,
+            direction,
+            format,
+        } => {
+            let mut renderer = Renderer::new(format, output)?;
+            query_xrefs(
+                &session,
+                &mut renderer,
+                &function_ref(&target),
+                binary.as_deref(),
+                direction,
+            )
+            .await?;
+            renderer.finish()?;
+        }
+        QueryType::HotCallees {
+            function_name,
+            binary,
+            limit,
+            format,
+        } => {
+            let mut renderer = Renderer::new(format, output)?;
+            query_hot_callees(
+                &session,
+                &mut renderer,
+                &function_ref(&function_name),
+                binary.as_deref(),
+                limit,
+            )
+            .await?;
+            renderer.finish()?;
+        }
+        QueryType::Unresolved {
+            binary,
+            limit,
+            format,
+        } => {
+            let mut renderer = Renderer::new(format, output)?;
+            query_unresolved(&session, &mut renderer, binary.as_deref(), limit).await?;
+            renderer.finish()?;
+        }
+        QueryType::DataRefs {
+            target,
+            binary,
+            format,
+        } => {
+            let mut renderer = Renderer::new(format, output)?;
+            query_data_refs(&session, &mut renderer, &target, binary.as_deref()).await?;
+            renderer.finish()?;
+        }
+        QueryType::AtAddress {
             binary,
+            address,
             format,
-        } => query_xrefs(&session, &address, binary.as_deref(), &format).await?,
+        } => {
+            let mut renderer = Renderer::new(format, output)?;
+            query_at_address(&session, &mut renderer, &binary, &address).await?;
+            renderer.finish()?;
+        }
         QueryType::CallPath {
             function_name,
+            from,
+            to,
+            all_routes,
             binary,
+            exclude,
+            exclude_library,
+            min_confidence,
+            expand_virtual,
             show_paths,
             show_sequences,
             show_recursive,
             show_upward,
             show_context,
             max_depth,
+            max_results,
+            expander,
+            format,
+        } => {
+            let source = function_name
+                .or(from)
+                .map(|name| function_ref(&name))
+                .ok_or_else(|| anyhow::anyhow!("A source function name is required"))?;
+            let mut renderer = Renderer::new(format, output)?;
+            let config = CallPathQueryConfig {
+                binary: binary.as_deref(),
+                exclude: &exclude,
+                exclude_library,
+                min_confidence,
+                expand_virtual,
+                show_paths,
+                show_sequences,
+                show_recursive,
+                show_upward,
+                show_context,
+                max_depth,
+                max_results,
+                expander,
+            };
+
+            if let Some(target) = to {
+                query_call_routes(
+                    &session,
+                    &mut renderer,
+                    &source,
+                    &function_ref(&target),
+                    &config,
+                    all_routes,
+                )
+                .await?;
+            } else {
+                query_call_paths(&session, &mut renderer, &source, config).await?;
+            }
+            renderer.finish()?;
+        }
+        QueryType::Batch {
+            file,
+            query,
+            binary,
+            limit,
             format,
         } => {
-            query_call_paths(
+            let mut renderer = Renderer::new(format, output)?;
+            query_batch(
                 &session,
-                &function_name,
-                CallPathQueryConfig {
-                    binary: binary.as_deref(),
-                    show_paths,
-                    show_sequences,
-                    show_recursive,
-                    show_upward,
-                    show_context,
-                    max_depth,
-                    format: &format,
-                },
+                &mut renderer,
+                &file,
+                query,
+                binary.as_deref(),
+                limit,
             )
-            .await?
+            .await?;
+            renderer.finish()?;
+        }
+        QueryType::Preset {
+            name,
+            param,
+            presets,
+            format,
+            ..
+        } => {
+            let presets = QueryPresets::load(presets.as_deref())?;
+            let name = name.ok_or_else(|| anyhow::anyhow!("A preset name is required"))?;
+            let mut renderer = Renderer::new(format, output)?;
+            query_preset(&session, &mut renderer, &presets, &name, &param).await?;
+            renderer.finish()?;
         }
     }
 
+    if tracing.timing {
+        print_timings(&importer.query_timings());
+    }
+
     Ok(())
 }
 
+/// Per-query timing table on stderr, keeping stdout clean for the results
+fn print_timings(timings: &[QueryTiming]) {
+    eprintln!("\nQuery timings:");
+    for (i, timing) in timings.iter().enumerate() {
+        eprintln!(
+            "{:>4} {:>10.1} ms {:>8} rows  {}",
+            i + 1,
+            timing.elapsed.as_secs_f64() * 1000.0,
+            timing.rows,
+            timing.statement
+        );
+    }
+    let total: f64 = timings.iter().map(|t| t.elapsed.as_secs_f64()).sum();
+    eprintln!(
+        "Total: {} queries, {:.1} ms, {} rows",
+        timings.len(),
+        total * 1000.0,
+        timings.iter().map(|t| t.rows).sum::<usize>()
+    );
+}
+
+/// Tell the user when path enumeration stopped at `--max-results`
+fn warn_path_limit(found: usize, max_results: usize) {
+    if found >= max_results {
+        eprintln!(
+            "[WARN] Stopped after {} paths (--max-results); results are incomplete, lower --max-depth or add --exclude to narrow them",
+            max_results
+        );
+    }
+}
+
 fn escape_lucene_term(term: &str) -> String {
     let mut escaped = String::with_capacity(term.len());
     for ch in term.chars() {
@@ -135,176 +628,803 @@ fn default_string_fulltext_query(pattern: &str) -> String {
     }
 }
 
-async fn query_functions(
+async fn query_search(
+    session: &crate::api::ImportSession,
+    renderer: &mut Renderer,
+    term: &str,
+    limit: usize,
+) -> Result<()> {
+    let term = term.trim();
+    if term.is_empty() {
+        return Err(anyhow::anyhow!("Search term must not be empty"));
+    }
+    eprintln!(
+        "Searching binaries, functions, strings and libraries for '{}'",
+        term
+    );
+
+    let lucene_query = default_string_fulltext_query(term);
+    let hits = session.search(term, &lucene_query, limit).await?;
+
+    if hits.is_empty() {
+        eprintln!("Nothing found matching '{}'", term);
+    }
+
+    renderer.render_list(&format!("Search results for '{}'", term), &hits)
+}
+
+async fn query_functions(
+    session: &crate::api::ImportSession,
+    renderer: &mut Renderer,
+    pattern: &str,
+    binary: Option<&str>,
+    param_type: Option<&str>,
+    verbose: bool,
+    limit: usize,
+) -> Result<()> {
+    if let Some(binary_name) = binary {
+        eprintln!(
+            "Querying functions with pattern: '{}' in binary: '{}'",
+            pattern, binary_name
+        );
+    } else {
+        eprintln!("Querying functions with pattern: '{}'", pattern);
+    }
+
+    let functions = session.query_functions(pattern, binary, param_type).await?;
+    render_functions(renderer, functions, pattern, param_type, verbose, limit)
+}
+
+/// First `limit` functions found by `query functions`
+fn render_functions(
+    renderer: &mut Renderer,
+    functions: Vec<Function>,
+    pattern: &str,
+    param_type: Option<&str>,
+    verbose: bool,
+    limit: usize,
+) -> Result<()> {
+    let functions: Vec<_> = functions.into_iter().take(limit).collect();
+
+    if functions.is_empty() {
+        match param_type {
+            Some(param_type) => eprintln!(
+                "No functions found matching pattern: '{}' with a '{}' parameter",
+                pattern, param_type
+            ),
+            None => eprintln!("No functions found matching pattern: '{}'", pattern),
+        }
+    }
+
+    if verbose {
+        let functions: Vec<FunctionDetail> = functions.into_iter().map(FunctionDetail).collect();
+        return renderer.render_list("Functions", &functions);
+    }
+    renderer.render_list("Functions", &functions)
+}
+
+async fn query_functions_by_metric(
+    session: &crate::api::ImportSession,
+    renderer: &mut Renderer,
+    pattern: &str,
+    binary: Option<&str>,
+    sort: FunctionSort,
+    limit: usize,
+) -> Result<()> {
+    eprintln!(
+        "Querying functions with pattern: '{}' ordered by {}",
+        pattern,
+        sort.property()
+    );
+
+    let functions = session
+        .query_function_metrics(pattern, binary, sort, limit)
+        .await?;
+
+    if functions.is_empty() {
+        eprintln!("No functions with stored metrics found (run `analyze metrics` first)");
+    }
+
+    renderer.render_list("Functions", &functions)
+}
+
+async fn query_strings(
+    session: &crate::api::ImportSession,
+    renderer: &mut Renderer,
+    pattern: &str,
+    raw: bool,
+    binary: Option<&str>,
+    limit: usize,
+) -> Result<()> {
+    if let Some(binary_name) = binary {
+        eprintln!(
+            "Querying strings with pattern: '{}' in binary: '{}'",
+            pattern, binary_name
+        );
+    } else {
+        eprintln!("Querying strings with pattern: '{}'", pattern);
+    }
+
+    let lucene_query = if raw {
+        pattern.to_string()
+    } else {
+        default_string_fulltext_query(pattern)
+    };
+
+    let hits = session
+        .query_strings_fulltext(&lucene_query, binary, limit)
+        .await?;
+
+    if hits.is_empty() {
+        eprintln!("No strings found matching pattern: '{}'", pattern);
+    }
+
+    renderer.render_list("Strings", &hits)?;
+
+    if !raw && !hits.is_empty() {
+        renderer.line(&format!("\nLucene query used: {}", lucene_query))?;
+        renderer.line("Tip: use `--raw` to pass an exact Lucene query (e.g. `\\\"Pay Bitcoin\\\"` or `*bitcoin*`).")?;
+    }
+
+    Ok(())
+}
+
+async fn query_pseudocode(
+    session: &crate::api::ImportSession,
+    renderer: &mut Renderer,
+    pattern: &str,
+    raw: bool,
+    binary: Option<&str>,
+    limit: usize,
+) -> Result<()> {
+    if let Some(binary_name) = binary {
+        eprintln!(
+            "Searching decompiled code for: '{}' in binary: '{}'",
+            pattern, binary_name
+        );
+    } else {
+        eprintln!("Searching decompiled code for: '{}'", pattern);
+    }
+
+    let lucene_query = if raw {
+        pattern.to_string()
+    } else {
+        default_string_fulltext_query(pattern)
+    };
+
+    let mut hits = session
+        .query_pseudocode_fulltext(&lucene_query, binary, limit)
+        .await?;
+    let terms: Vec<String> = pattern
+        .split_whitespace()
+        .map(|term| term.trim_matches(|c: char| !c.is_alphanumeric() && c != '_'))
+        .filter(|term| !term.is_empty() && !["AND", "OR", "NOT"].contains(term))
+        .map(str::to_string)
+        .collect();
+    for hit in &mut hits {
+        hit.locate(&terms);
+    }
+
+    if hits.is_empty() {
+        eprintln!(
+            "No decompiled code found matching: '{}' (functions need a `decompiled` field)",
+            pattern
+        );
+    }
+
+    renderer.render_list("Pseudocode matches", &hits)
+}
+
+/// `query binary` and `query binaries` against the embedded store
+async fn query_embedded(
+    query_type: QueryType,
+    config: &Config,
+    output: &OutputOptions,
+) -> Result<()> {
+    let session = crate::api::ImportSession::new(EmbeddedStore::open(&config.embedded_path)?);
+
+    match query_type {
+        QueryType::Binary {
+            binary_name,
+            hash,
+            format,
+        } => {
+            let mut renderer = Renderer::new(format, output)?;
+            query_binary(
+                &session,
+                &mut renderer,
+                binary_name.as_deref(),
+                hash.as_deref(),
+            )
+            .await?;
+            renderer.finish()
+        }
+        QueryType::Binaries {
+            compiler,
+            packer,
+            imphash,
+            high_entropy,
+            arch,
+            since,
+            compiled_since,
+            limit,
+            format,
+        } => {
+            check_filter_dates(since.as_deref(), compiled_since.as_deref())?;
+            let mut renderer = Renderer::new(format, output)?;
+            let filter = BinaryFilter {
+                compiler: compiler.as_deref(),
+                packer: packer.as_deref(),
+                imphash: imphash.as_deref(),
+                min_entropy: high_entropy.then_some(HIGH_ENTROPY),
+                arch,
+                since: since.as_deref(),
+                compiled_since: compiled_since.as_deref(),
+            };
+            query_binaries(&session, &mut renderer, &filter, limit).await?;
+            renderer.finish()
+        }
+        QueryType::Functions {
+            pattern,
+            binary,
+            limit,
+            sort,
+            param_type,
+            verbose,
+            format,
+        } => {
+            if sort.is_some() {
+                return Err(crate::error::validation(
+                    "The embedded backend does not store `analyze metrics`, so `query functions --sort` needs Neo4j",
+                    Vec::new(),
+                ));
+            }
+            let mut renderer = Renderer::new(format, output)?;
+            let functions = session
+                .query_functions(&pattern, binary.as_deref(), param_type.as_deref())
+                .await?;
+            render_functions(
+                &mut renderer,
+                functions,
+                &pattern,
+                param_type.as_deref(),
+                verbose,
+                limit,
+            )?;
+            renderer.finish()
+        }
+        QueryType::Callgraph {
+            function_name,
+            binary,
+            show_callees,
+            show_callers,
+            max_depth,
+            exclude_library,
+            min_confidence,
+            format,
+        } => {
+            let mut renderer = Renderer::new(format, output)?;
+            let function_name = function_ref(&function_name);
+            let callgraph = session
+                .query_callgraph_with_depth(
+                    &function_name,
+                    binary.as_deref(),
+                    max_depth,
+                    exclude_library,
+                    min_confidence,
+                )
+                .await?;
+            render_callgraph(
+                &mut renderer,
+                &function_name,
+                &callgraph,
+                show_callees,
+                show_callers,
+            )?;
+            renderer.finish()
+        }
+        _ => Err(crate::error::validation(
+            "The embedded backend only supports `query binary`, `query binaries`, `query functions` and `query callgraph`",
+            Vec::new(),
+        )),
+    }
+}
+
+/// Reject `--since` and `--compiled-since` values that are not YYYY-MM-DD
+fn check_filter_dates(since: Option<&str>, compiled_since: Option<&str>) -> Result<()> {
+    for (flag, date) in [("--since", since), ("--compiled-since", compiled_since)] {
+        if let Some(date) = date.filter(|date| !is_iso_date(date)) {
+            return Err(anyhow::anyhow!(
+                "Invalid {} '{}', expected YYYY-MM-DD",
+                flag,
+                date
+            ));
+        }
+    }
+    Ok(())
+}
+
+async fn query_binary<S: GraphStore>(
+    session: &crate::api::ImportSession<S>,
+    renderer: &mut Renderer,
+    binary_name: Option<&str>,
+    hash: Option<&str>,
+) -> Result<()> {
+    let binary = match (hash, binary_name) {
+        (Some(hash), _) => {
+            eprintln!("Querying binary with hash: '{}'", hash);
+            session.query_binary_by_hash(hash).await?
+        }
+        (None, Some(binary_name)) => {
+            eprintln!("Querying binary with name pattern: '{}'", binary_name);
+            session.query_binary_info(binary_name).await?
+        }
+        (None, None) => return Err(anyhow::anyhow!("A binary name or hash is required")),
+    };
+
+    let Some(binary) = binary else {
+        eprintln!(
+            "No binary found matching: '{}'",
+            hash.or(binary_name).unwrap_or_default()
+        );
+        return Ok(());
+    };
+
+    if renderer.format().is_document() {
+        return renderer.document(&binary);
+    }
+
+    renderer.line("\nBinary Information:")?;
+    renderer.table(std::slice::from_ref(&binary))?;
+
+    let hashes = detail_entries(&[
+        ("SHA-256", &Some(binary.hash.clone())),
+        ("MD5", &binary.md5),
+        ("SHA-1", &binary.sha1),
+        ("ssdeep", &binary.ssdeep),
+        ("TLSH", &binary.tlsh),
+    ]);
+    renderer.line("\nHashes:")?;
+    renderer.table(&hashes)?;
+
+    if let Some(image_base) = &binary.image_base {
+        renderer.line(&format!(
+            "\nAddresses are relative to image base {}",
+            image_base
+        ))?;
+    }
+
+    let timeline = detail_entries(&[
+        ("First imported", &binary.first_imported),
+        ("Last updated", &binary.last_updated),
+        ("Compiled", &binary.compiled_at),
+    ]);
+    if !timeline.is_empty() {
+        renderer.line("\nTimeline:")?;
+        renderer.table(&timeline)?;
+    }
+
+    if let Some(pe) = &binary.pe_info {
+        let entries = detail_entries(&[
+            ("Imphash", &pe.imphash),
+            ("Rich header hash", &pe.rich_header_hash),
+            ("Entry point", &pe.entry_point),
+            ("Subsystem", &pe.subsystem),
+            ("Signer", &pe.signer),
+        ]);
+        renderer.line("\nPE Header:")?;
+        renderer.table(&entries)?;
+    }
+
+    if let Some(elf) = &binary.elf_info {
+        let entries = detail_entries(&[
+            ("Interpreter", &elf.interpreter),
+            ("Soname", &elf.soname),
+            ("Needed", &joined(&elf.needed)),
+            ("Symbol versions", &joined(&elf.symbol_versions)),
+        ]);
+        renderer.line("\nELF Dynamic Section:")?;
+        renderer.table(&entries)?;
+    }
+
+    if let Some(macho) = &binary.macho_info {
+        let entries = detail_entries(&[
+            ("Team ID", &macho.team_id),
+            ("Rpaths", &joined(&macho.rpaths)),
+            ("Load commands", &joined(&macho.load_commands)),
+        ]);
+        renderer.line("\nMach-O:")?;
+        renderer.table(&entries)?;
+    }
+
+    if let Some(family) = &binary.family {
+        renderer.line(&format!("\nFamily: {}", family))?;
+    }
+
+    if let Some(score) = binary.risk_score {
+        renderer.line(&format!("\nRisk score: {:.1}", score))?;
+    }
+
+    if !binary.sections.is_empty() {
+        renderer.line("\nSections:")?;
+        renderer.table(&binary.sections)?;
+    }
+    let packing = detail_entries(&[
+        (
+            "Overlay size",
+            &binary.overlay_size.map(|size| size.to_string()),
+        ),
+        (
+            "Max entropy",
+            &binary
+                .max_entropy()
+                .map(|entropy| format!("{:.2}", entropy)),
+        ),
+        (
+            "Likely packed",
+            &binary.likely_packed.then(|| "yes".to_string()),
+        ),
+    ]);
+    if !packing.is_empty() {
+        renderer.line("\nPacking:")?;
+        renderer.table(&packing)?;
+    }
+
+    let resources = session.query_binary_resources(&binary.hash).await?;
+    if !resources.is_empty() {
+        renderer.line("\nResources:")?;
+        renderer.table(&resources)?;
+    }
+
+    Ok(())
+}
+
+/// Name/value rows for the fields that are set
+fn detail_entries(fields: &[(&str, &Option<String>)]) -> Vec<MetricEntry> {
+    fields
+        .iter()
+        .filter_map(|(name, value)| value.as_ref().map(|v| MetricEntry::new(*name, v)))
+        .collect()
+}
+
+/// Comma-separated list, `None` when empty
+fn joined(values: &[String]) -> Option<String> {
+    (!values.is_empty()).then(|| values.join(", "))
+}
+
+async fn query_similar_binaries(
+    session: &crate::api::ImportSession,
+    renderer: &mut Renderer,
+    binary: &str,
+    min_ssdeep: u32,
+    max_tlsh: u32,
+    limit: usize,
+) -> Result<()> {
+    eprintln!("Searching binaries similar to: '{}'", binary);
+
+    let reference = match session.query_binary_by_hash(binary).await? {
+        Some(found) => Some(found),
+        None => session.query_binary_info(binary).await?,
+    };
+    let Some(reference) = reference else {
+        eprintln!("No binary found matching: '{}'", binary);
+        return Ok(());
+    };
+    if reference.ssdeep.is_none() && reference.tlsh.is_none() {
+        eprintln!(
+            "Binary '{}' has no ssdeep or TLSH hash to compare",
+            reference.filename
+        );
+        return Ok(());
+    }
+
+    let mut matches: Vec<SimilarBinary> = session
+        .query_fuzzy_hashed_binaries()
+        .await?
+        .into_iter()
+        .filter(|candidate| candidate.hash != reference.hash)
+        .filter_map(|candidate| {
+            let ssdeep_score = reference
+                .ssdeep
+                .as_deref()
+                .zip(candidate.ssdeep.as_deref())
+                .and_then(|(a, b)| fuzzy::ssdeep_compare(a, b))
+                .filter(|score| *score >= min_ssdeep);
+            let tlsh_distance = reference
+                .tlsh
+                .as_deref()
+                .zip(candidate.tlsh.as_deref())
+                .and_then(|(a, b)| fuzzy::tlsh_distance(a, b))
+                .filter(|distance| *distance <= max_tlsh);
+
+            (ssdeep_score.is_some() || tlsh_distance.is_some()).then_some(SimilarBinary {
+                filename: candidate.filename,
+                hash: candidate.hash,
+                ssdeep_score,
+                tlsh_distance,
+            })
+        })
+        .collect();
+
+    // Strongest ssdeep matches first, then closest TLSH distance
+    matches.sort_by_key(|m| {
+        (
+            std::cmp::Reverse(m.ssdeep_score.unwrap_or(0)),
+            m.tlsh_distance.unwrap_or(u32::MAX),
+        )
+    });
+    matches.truncate(limit);
+
+    if matches.is_empty() {
+        eprintln!("No similar binaries found");
+    }
+
+    renderer.render_list(
+        &format!("Binaries similar to {}", reference.filename),
+        &matches,
+    )
+}
+
+async fn query_similar_by_embedding(
     session: &crate::api::ImportSession,
-    pattern: &str,
-    binary: Option<&str>,
+    renderer: &mut Renderer,
+    binary: &str,
+    min_similarity: f64,
     limit: usize,
-    format: &str,
 ) -> Result<()> {
-    if let Some(binary_name) = binary {
-        println!(
-            "Querying functions with pattern: '{}' in binary: '{}'",
-            pattern, binary_name
-        );
-    } else {
-        println!("Querying functions with pattern: '{}'", pattern);
+    let embeddings = session.query_embeddings().await?;
+    let reference = match embeddings.iter().find(|e| e.hash == binary) {
+        Some(found) => Some(found),
+        None => embeddings.iter().find(|e| e.binary.contains(binary)),
+    };
+    let Some(reference) = reference else {
+        return Err(crate::error::not_found(format!(
+            "No embedding stored for a binary matching '{}' (run `analyze embeddings` first)",
+            binary
+        )));
+    };
+    eprintln!(
+        "Searching binaries with embeddings similar to: '{}'",
+        reference.binary
+    );
+
+    let mut matches: Vec<EmbeddingMatch> = embeddings
+        .iter()
+        .filter(|candidate| candidate.hash != reference.hash)
+        .map(|candidate| EmbeddingMatch {
+            binary: candidate.binary.clone(),
+            hash: candidate.hash.clone(),
+            similarity: reference.cosine_similarity(candidate),
+        })
+        .filter(|m| m.similarity >= min_similarity)
+        .collect();
+    matches.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+    matches.truncate(limit);
+
+    if matches.is_empty() {
+        eprintln!("No similar binaries found");
     }
 
-    let functions = session.query_functions(pattern, binary).await?;
-    let functions: Vec<_> = functions.into_iter().take(limit).collect();
+    renderer.render_list(
+        &format!("Binaries similar to {}", reference.binary),
+        &matches,
+    )
+}
 
-    if functions.is_empty() {
-        println!("No functions found matching pattern: '{}'", pattern);
-        return Ok(());
+async fn query_binaries<S: GraphStore>(
+    session: &crate::api::ImportSession<S>,
+    renderer: &mut Renderer,
+    filter: &BinaryFilter<'_>,
+    limit: usize,
+) -> Result<()> {
+    eprintln!(
+        "Querying binaries (compiler: {}, packer: {}, imphash: {}, arch: {})",
+        filter.compiler.unwrap_or("any"),
+        filter.packer.unwrap_or("any"),
+        filter.imphash.unwrap_or("any"),
+        filter.arch.map_or("any", Architecture::as_str)
+    );
+
+    let binaries = session.query_binaries(filter, limit).await?;
+
+    if binaries.is_empty() {
+        eprintln!("No binaries found");
     }
 
-    if format == "json" {
-        let json = serde_json::to_string_pretty(&functions)?;
-        println!("{}", json);
-    } else {
-        println!("\nFunctions ({} found):", functions.len());
-        println!(
-            "{:<40} {:<20} {:<15} {:<20} {:<15}",
-            "Name", "Type", "Address", "Binary", "UID"
+    renderer.render_list("Binaries", &binaries)
+}
+
+async fn query_shared_apis(
+    session: &crate::api::ImportSession,
+    renderer: &mut Renderer,
+    binary: &str,
+    min_shared: usize,
+    limit: usize,
+) -> Result<()> {
+    eprintln!("Finding binaries sharing imported APIs with: {}", binary);
+
+    let hits = session.query_shared_apis(binary, min_shared, limit).await?;
+
+    if hits.is_empty() {
+        eprintln!(
+            "No binaries share at least {} APIs with {}",
+            min_shared, binary
         );
-        println!("{}", "-".repeat(110));
-
-        for f in &functions {
-            let binary_display = binary.unwrap_or_else(|| extract_binary_from_uid(&f.uid));
-            println!(
-                "{:<40} {:<20} {:<15} {:<20} {:<15}",
-                f.name,
-                format!("{:?}", f.r#type),
-                f.address.as_deref().unwrap_or("N/A"),
-                binary_display,
-                &f.uid[..f.uid.len().min(15)]
-            );
-        }
     }
 
-    Ok(())
+    renderer.render_list("Binaries sharing APIs", &hits)
 }
 
-async fn query_strings(
+async fn query_library(
     session: &crate::api::ImportSession,
-    pattern: &str,
-    raw: bool,
+    renderer: &mut Renderer,
+    name: &str,
+    examples: usize,
+    limit: usize,
+) -> Result<()> {
+    eprintln!("Querying APIs of library: {}", name);
+
+    let apis = session.query_library_apis(name, examples, limit).await?;
+    let Some(first) = apis.first() else {
+        return Err(crate::error::not_found(format!(
+            "No imported APIs found for library '{}'",
+            name
+        )));
+    };
+
+    renderer.render_list(
+        &format!("APIs of {} used in the corpus", first.library),
+        &apis,
+    )
+}
+
+async fn query_indicators(
+    session: &crate::api::ImportSession,
+    renderer: &mut Renderer,
+    value: Option<&str>,
     binary: Option<&str>,
     limit: usize,
-    format: &str,
 ) -> Result<()> {
-    if let Some(binary_name) = binary {
-        println!(
-            "Querying strings with pattern: '{}' in binary: '{}'",
-            pattern, binary_name
-        );
-    } else {
-        println!("Querying strings with pattern: '{}'", pattern);
+    eprintln!(
+        "Querying network indicators (value: {}, binary: {})",
+        value.unwrap_or("any"),
+        binary.unwrap_or("any")
+    );
+
+    let hits = session.query_indicators(binary, value, limit).await?;
+
+    if hits.is_empty() {
+        eprintln!("No network indicators found");
     }
 
-    let lucene_query = if raw {
-        pattern.to_string()
-    } else {
-        default_string_fulltext_query(pattern)
-    };
+    renderer.render_list("Network indicators", &hits)
+}
 
-    let hits = session
-        .query_strings_fulltext(&lucene_query, binary, limit)
-        .await?;
+async fn query_objects(
+    session: &crate::api::ImportSession,
+    renderer: &mut Renderer,
+    kind: Option<ObjectKind>,
+    name: Option<&str>,
+    binary: Option<&str>,
+    limit: usize,
+) -> Result<()> {
+    eprintln!(
+        "Querying named objects (type: {}, name: {}, binary: {})",
+        kind.map(ObjectKind::as_str).unwrap_or("any"),
+        name.unwrap_or("any"),
+        binary.unwrap_or("any")
+    );
+
+    let hits = session.query_objects(kind, name, binary, limit).await?;
 
     if hits.is_empty() {
-        println!("No strings found matching pattern: '{}'", pattern);
-        return Ok(());
+        eprintln!("No named objects found");
     }
 
-    if format == "json" {
-        let json = serde_json::to_string_pretty(&hits)?;
-        println!("{}", json);
-    } else {
-        println!("\nStrings ({} found):", hits.len());
-        println!("{:<10} {:<10} {}", "Score", "Samples", "Value");
-        println!("{}", "-".repeat(80));
-
-        for hit in &hits {
-            let value = hit.value.replace('\n', "\\n").replace('\r', "\\r");
-            let display = if value.len() > 60 {
-                format!("{}...", &value[..60])
-            } else {
-                value
-            };
-            println!("{:<10.4} {:<10} {}", hit.score, hit.sample_count, display);
-        }
+    renderer.render_list("Named objects", &hits)
+}
 
-        if !raw {
-            println!("\nLucene query used: {}", lucene_query);
-            println!("Tip: use `--raw` to pass an exact Lucene query (e.g. `\\\"Pay Bitcoin\\\"` or `*bitcoin*`).");
+async fn query_crypto(
+    session: &crate::api::ImportSession,
+    renderer: &mut Renderer,
+    algorithm: Option<&str>,
+    binary: Option<&str>,
+    limit: usize,
+) -> Result<()> {
+    if let Some(algorithm) = algorithm {
+        if !crypto::known_algorithms().any(|known| known.eq_ignore_ascii_case(algorithm)) {
+            return Err(anyhow::anyhow!(
+                "Unknown crypto algorithm '{}' (expected one of: {})",
+                algorithm,
+                crypto::known_algorithms().collect::<Vec<_>>().join(", ")
+            ));
         }
     }
+    eprintln!(
+        "Querying crypto functions (algorithm: {}, binary: {})",
+        algorithm.unwrap_or("any"),
+        binary.unwrap_or("any")
+    );
 
-    Ok(())
-}
+    let hits = session.query_crypto(algorithm, binary, limit).await?;
 
-fn extract_binary_from_uid(uid: &str) -> &str {
-    if uid.starts_with("imp:") {
-        return "shared";
-    }
-    if let Some(colon_pos) = uid.find(':') {
-        &uid[..colon_pos.min(15)]
-    } else {
-        &uid[..uid.len().min(15)]
+    if hits.is_empty() {
+        eprintln!("No functions with crypto constants found");
     }
-}
 
-async fn query_binary(
-    session: &crate::api::ImportSession,
-    binary_name: &str,
-    format: &str,
-) -> Result<()> {
-    println!("Querying binary with name pattern: '{}'", binary_name);
+    renderer.render_list("Crypto functions", &hits)
+}
 
-    if let Some(binary) = session.query_binary_info(binary_name).await? {
-        if format == "json" {
-            let json = serde_json::to_string_pretty(&binary)?;
-            println!("{}", json);
-        } else {
-            println!("\nBinary Information:");
-            println!("  Hash: {}", binary.hash);
-            println!("  Filename: {}", binary.filename);
-            println!("  Format: {:?}", binary.format);
-            println!("  Architecture: {}", binary.arch);
+/// Function argument with a function UID put in canonical form (`abc:0X00401000`
+/// becomes `abc:0x401000`); names are returned unchanged
+fn function_ref(name: &str) -> String {
+    match parse_uid(name) {
+        Some(uid @ Uid::Function { .. })
+            if uid
+                .binary_hash()
+                .is_some_and(|hash| hash.chars().all(|c| c.is_ascii_hexdigit())) =>
+        {
+            uid.to_string()
         }
-    } else {
-        println!("No binary found matching pattern: '{}'", binary_name);
+        _ => name.to_string(),
     }
-
-    Ok(())
 }
 
 async fn query_callgraph(
     session: &crate::api::ImportSession,
+    renderer: &mut Renderer,
     function_name: &str,
-    binary: Option<&str>,
-    show_callees: bool,
-    show_callers: bool,
-    max_depth: usize,
-    format: &str,
+    config: &CallGraphQueryConfig<'_>,
 ) -> Result<()> {
+    let CallGraphQueryConfig {
+        binary,
+        show_callees,
+        show_callers,
+        max_depth,
+        exclude_library,
+        min_confidence,
+    } = *config;
+
     if let Some(binary_name) = binary {
-        println!(
+        eprintln!(
             "Querying call graph for function: '{}' in binary: '{}' (depth: {})",
             function_name, binary_name, max_depth
         );
     } else {
-        println!(
+        eprintln!(
             "Querying call graph for function: '{}' (depth: {})",
             function_name, max_depth
         );
     }
 
     let callgraph = session
-        .query_callgraph_with_depth(function_name, binary, max_depth)
+        .query_callgraph_with_depth(
+            function_name,
+            binary,
+            max_depth,
+            exclude_library,
+            min_confidence,
+        )
         .await?;
+    render_callgraph(
+        renderer,
+        function_name,
+        &callgraph,
+        show_callees,
+        show_callers,
+    )
+}
+
+fn render_callgraph(
+    renderer: &mut Renderer,
+    function_name: &str,
+    callgraph: &CallGraph,
+    show_callees: bool,
+    show_callers: bool,
+) -> Result<()> {
+    if renderer.format().is_document() {
+        return renderer.document(callgraph);
+    }
 
     let (display_callees, display_callers) = if !show_callees && !show_callers {
         (true, true)
@@ -312,94 +1432,497 @@ async fn query_callgraph(
         (show_callees, show_callers)
     };
 
-    if format == "json" {
-        let json = serde_json::to_string_pretty(&callgraph)?;
-        println!("{}", json);
-    } else {
-        if display_callees && !callgraph.callees.is_empty() {
-            println!("\nCallees (functions called by '{}'):", function_name);
-            println!("{:<40} {:<15}", "Name", "Address");
-            println!("{}", "-".repeat(55));
-
-            for f in &callgraph.callees {
-                println!(
-                    "{:<40} {:<15}",
-                    f.name,
-                    f.address.as_deref().unwrap_or("N/A")
-                );
-            }
+    let edges: Vec<CallEdge> = callgraph
+        .edges
+        .iter()
+        .filter(|e| match e.direction.as_str() {
+            "callee" => display_callees,
+            _ => display_callers,
+        })
+        .cloned()
+        .collect();
+
+    if renderer.format().is_diagram() {
+        let mut diagram = Diagram::default();
+        for edge in &edges {
+            diagram.add_edge(
+                (&edge.from_uid, &edge.from_name),
+                (&edge.to_uid, &edge.to_name),
+                Some(&edge.offset),
+            );
+            diagram.set_kind(&edge.from_uid, &edge.from_type);
+            diagram.set_kind(&edge.to_uid, &edge.to_type);
         }
+        return renderer.diagram(&diagram);
+    }
 
-        if display_callers && !callgraph.callers.is_empty() {
-            println!("\nCallers (functions calling '{}'):", function_name);
-            println!("{:<40} {:<15}", "Name", "Address");
-            println!("{}", "-".repeat(55));
+    if renderer.format() == OutputFormat::Csv {
+        return renderer.table(&edges);
+    }
 
-            for f in &callgraph.callers {
-                println!(
-                    "{:<40} {:<15}",
-                    f.name,
-                    f.address.as_deref().unwrap_or("N/A")
-                );
-            }
-        }
+    let mut entries = Vec::new();
+    if display_callees {
+        entries.extend(callgraph.callees.iter().map(|f| CallGraphEntry {
+            direction: "callee",
+            uid: f.uid.clone(),
+            name: f.name.clone(),
+            address: f.address.clone(),
+        }));
+    }
+    if display_callers {
+        entries.extend(callgraph.callers.iter().map(|f| CallGraphEntry {
+            direction: "caller",
+            uid: f.uid.clone(),
+            name: f.name.clone(),
+            address: f.address.clone(),
+        }));
+    }
 
-        if callgraph.callees.is_empty() && callgraph.callers.is_empty() {
-            println!(
-                "No call graph information found for function: '{}'",
-                function_name
-            );
-        } else {
-            println!(
-                "\nSummary: {} callees, {} callers",
-                callgraph.callees.len(),
-                callgraph.callers.len()
-            );
+    if callgraph.callees.is_empty() && callgraph.callers.is_empty() {
+        eprintln!(
+            "No call graph information found for function: '{}'",
+            function_name
+        );
+        return Ok(());
+    }
+
+    renderer.render_list(&format!("Call graph of '{}'", function_name), &entries)?;
+
+    // Adjacency listing: every caller followed by the functions it calls
+    renderer.line("\nCall edges:")?;
+    let mut sources: Vec<&str> = Vec::new();
+    for edge in &edges {
+        if !sources.contains(&edge.from_uid.as_str()) {
+            sources.push(&edge.from_uid);
+        }
+    }
+    for source in sources {
+        let outgoing: Vec<&CallEdge> = edges.iter().filter(|e| e.from_uid == source).collect();
+        renderer.line(&format!("  {}", outgoing[0].from_name))?;
+        for edge in outgoing {
+            renderer.line(&format!(
+                "    -> {} (offset {}, {}, depth {})",
+                edge.to_name, edge.offset, edge.call_type, edge.depth
+            ))?;
         }
     }
 
+    renderer.line(&format!(
+        "\nSummary: {} callees, {} callers, {} edges",
+        callgraph.callees.len(),
+        callgraph.callers.len(),
+        edges.len()
+    ))?;
+
     Ok(())
 }
 
 async fn query_xrefs(
     session: &crate::api::ImportSession,
-    address: &str,
+    renderer: &mut Renderer,
+    target: &str,
     binary: Option<&str>,
-    format: &str,
+    direction: XrefDirection,
 ) -> Result<()> {
     if let Some(binary_name) = binary {
-        println!(
-            "Querying cross-references for address: '{}' in binary: '{}'",
-            address, binary_name
+        eprintln!(
+            "Querying cross-references for: '{}' in binary: '{}' (direction: {:?})",
+            target, binary_name, direction
         );
     } else {
-        println!("Querying cross-references for address: '{}'", address);
+        eprintln!(
+            "Querying cross-references for: '{}' (direction: {:?})",
+            target, direction
+        );
     }
 
-    let xrefs = session.query_xrefs(address, binary).await?;
+    let xrefs = session.query_xrefs(target, binary, direction).await?;
 
     if xrefs.is_empty() {
-        println!("No cross-references found for address: '{}'", address);
+        eprintln!("No cross-references found for: '{}'", target);
+    }
+
+    renderer.render_list("Cross-references", &xrefs)
+}
+
+async fn query_batch(
+    session: &crate::api::ImportSession,
+    renderer: &mut Renderer,
+    file: &str,
+    query: BatchQuery,
+    binary: Option<&str>,
+    limit: usize,
+) -> Result<()> {
+    let text = if file == "-" {
+        let mut text = String::new();
+        std::io::stdin().read_to_string(&mut text)?;
+        text
+    } else {
+        std::fs::read_to_string(file)
+            .map_err(|e| anyhow::anyhow!("Failed to read targets from '{}': {}", file, e))?
+    };
+    let targets: Vec<&str> = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect();
+    eprintln!(
+        "Running {} queries for {} targets",
+        query_name(query),
+        targets.len()
+    );
+
+    match query {
+        BatchQuery::Functions => {
+            let rows = run_batch(&targets, |target| async move {
+                let functions = session.query_functions(target, binary, None).await?;
+                Ok(functions.into_iter().take(limit).collect())
+            })
+            .await;
+            renderer.render_list("Functions", &rows)
+        }
+        BatchQuery::Strings => {
+            let rows = run_batch(&targets, |target| async move {
+                let lucene_query = default_string_fulltext_query(target);
+                session
+                    .query_strings_fulltext(&lucene_query, binary, limit)
+                    .await
+            })
+            .await;
+            renderer.render_list("Strings", &rows)
+        }
+        BatchQuery::Binary => {
+            let rows = run_batch(&targets, |target| async move {
+                let binary = match session.query_binary_by_hash(target).await? {
+                    Some(binary) => Some(binary),
+                    None => session.query_binary_info(target).await?,
+                };
+                Ok(binary.into_iter().collect())
+            })
+            .await;
+            renderer.render_list("Binaries", &rows)
+        }
+        BatchQuery::Xrefs => {
+            let rows = run_batch(&targets, |target| async move {
+                let xrefs = session
+                    .query_xrefs(target, binary, XrefDirection::Both)
+                    .await?;
+                Ok(xrefs.into_iter().take(limit).collect())
+            })
+            .await;
+            renderer.render_list("Cross-references", &rows)
+        }
+        BatchQuery::HotCallees => {
+            let rows = run_batch(&targets, |target| async move {
+                session.query_hot_callees(target, binary, limit).await
+            })
+            .await;
+            renderer.render_list("Callees by call sites", &rows)
+        }
+    }
+}
+
+fn query_name(query: BatchQuery) -> String {
+    clap::ValueEnum::to_possible_value(&query)
+        .map(|value| value.get_name().to_string())
+        .unwrap_or_default()
+}
+
+/// Run `query` for each target in turn, tagging its rows with the target; a failing
+/// target is reported and skipped so one bad line does not abort the batch
+async fn run_batch<'a, R, F, Fut>(targets: &[&'a str], query: F) -> Vec<BatchRow<R>>
+where
+    F: Fn(&'a str) -> Fut,
+    Fut: Future<Output = Result<Vec<R>>>,
+{
+    let mut rows = Vec::new();
+    let mut empty = 0;
+    let mut failed = 0;
+    for &target in targets {
+        match query(target).await {
+            Ok(results) if results.is_empty() => empty += 1,
+            Ok(results) => rows.extend(results.into_iter().map(|row| BatchRow {
+                target: target.to_string(),
+                row,
+            })),
+            Err(e) => {
+                eprintln!("[WARN] Query for '{}' failed: {}", target, e);
+                failed += 1;
+            }
+        }
+    }
+
+    eprintln!(
+        "{} rows for {} targets ({} without results, {} failed)",
+        rows.len(),
+        targets.len(),
+        empty,
+        failed
+    );
+    rows
+}
+
+async fn query_hot_callees(
+    session: &crate::api::ImportSession,
+    renderer: &mut Renderer,
+    function_name: &str,
+    binary: Option<&str>,
+    limit: usize,
+) -> Result<()> {
+    eprintln!("Ranking callees of: '{}'", function_name);
+
+    let callees = session
+        .query_hot_callees(function_name, binary, limit)
+        .await?;
+
+    if callees.is_empty() {
+        eprintln!("No calls found from: '{}'", function_name);
+    }
+
+    renderer.render_list("Callees by call sites", &callees)
+}
+
+async fn query_unresolved(
+    session: &crate::api::ImportSession,
+    renderer: &mut Renderer,
+    binary: Option<&str>,
+    limit: usize,
+) -> Result<()> {
+    let unresolved = session.query_unresolved_calls(binary, limit).await?;
+
+    if unresolved.is_empty() {
+        eprintln!("No unresolved calls recorded");
+    }
+
+    renderer.render_list("Unresolved call addresses", &unresolved)
+}
+
+async fn query_data_refs(
+    session: &crate::api::ImportSession,
+    renderer: &mut Renderer,
+    target: &str,
+    binary: Option<&str>,
+) -> Result<()> {
+    eprintln!(
+        "Querying data references to: '{}' (binary: {})",
+        target,
+        binary.unwrap_or("any")
+    );
+
+    let refs = session.query_data_refs(target, binary).await?;
+
+    if refs.is_empty() {
+        eprintln!("No data references found for: '{}'", target);
+    }
+
+    renderer.render_list("Data references", &refs)
+}
+
+async fn query_at_address(
+    session: &crate::api::ImportSession,
+    renderer: &mut Renderer,
+    binary: &str,
+    address: &str,
+) -> Result<()> {
+    let target =
+        parse_address(address).ok_or_else(|| anyhow::anyhow!("Invalid address: '{}'", address))?;
+    let Some(sample) = session.query_binary_info(binary).await? else {
+        return Err(crate::error::not_found(format!(
+            "No binary found matching: '{}'",
+            binary
+        )));
+    };
+    // Virtual addresses work for binaries stored relative to their image base
+    let target = sample.relative_address(target);
+    eprintln!(
+        "Looking up address {} in '{}'",
+        format_address(target),
+        sample.filename
+    );
+
+    let functions = session.query_binary_functions(&sample.hash).await?;
+    let Some((function, offset, bounded)) = Function::locate(&functions, target) else {
+        eprintln!("No function contains address {}", format_address(target));
         return Ok(());
+    };
+    if !bounded {
+        eprintln!(
+            "[WARN] '{}' has no stored size; it is the closest function below the address",
+            function.name
+        );
+    }
+
+    let location = AddressLocation {
+        address: format_address(target),
+        name: function.display_name(),
+        start: function.address.clone().unwrap_or_default(),
+        chunk: function
+            .chunk_containing(target)
+            .map(|chunk| chunk.start.clone()),
+        offset: format!("+0x{:x}", offset),
+        size: function.size,
+        bounded,
+        uid: function.uid.clone(),
+    };
+    renderer.render_list("Containing function", std::slice::from_ref(&location))
+}
+
+async fn query_preset(
+    session: &crate::api::ImportSession,
+    renderer: &mut Renderer,
+    presets: &QueryPresets,
+    name: &str,
+    overrides: &[String],
+) -> Result<()> {
+    let preset = presets.get(name)?;
+    let params = preset.resolve_params(overrides)?;
+    eprintln!("Running query preset '{}'", name);
+
+    let records = session
+        .importer()
+        .connection()
+        .execute_records(&preset.cypher, params)
+        .await?;
+
+    if records.is_empty() {
+        eprintln!("No results for preset '{}'", name);
     }
 
-    if format == "json" {
-        let json = serde_json::to_string_pretty(&xrefs)?;
-        println!("{}", json);
+    let columns = if preset.columns.is_empty() {
+        let mut keys: Vec<String> = records
+            .iter()
+            .flat_map(|record| record.keys().cloned())
+            .collect();
+        keys.sort();
+        keys.dedup();
+        keys
+    } else {
+        preset.columns.clone()
+    };
+
+    renderer.render_records(&format!("Preset '{}'", name), &columns, &records)
+}
+
+fn list_presets(renderer: &mut Renderer, presets: &QueryPresets) -> Result<()> {
+    let entries: Vec<PresetEntry> = presets
+        .presets
+        .iter()
+        .map(|(name, preset)| PresetEntry {
+            name: name.clone(),
+            description: preset.description.clone(),
+            params: preset
+                .params
+                .iter()
+                .map(|(key, value)| match value {
+                    serde_json::Value::String(text) => format!("{}={:?}", key, text),
+                    value => format!("{}={}", key, value),
+                })
+                .collect(),
+        })
+        .collect();
+
+    renderer.render_list("Query presets", &entries)
+}
+
+async fn query_call_routes(
+    session: &crate::api::ImportSession,
+    renderer: &mut Renderer,
+    from: &str,
+    to: &str,
+    config: &CallPathQueryConfig<'_>,
+    all_routes: bool,
+) -> Result<()> {
+    let max_depth = config.max_depth;
+    if let Some(binary_name) = config.binary {
+        eprintln!(
+            "Searching call routes from '{}' to '{}' in binary: '{}' (max depth: {})",
+            from, to, binary_name, max_depth
+        );
     } else {
-        println!("\nCross-references ({} found):", xrefs.len());
-        println!(
-            "{:<30} {:<30} {:<15}",
-            "From Function", "To Function", "Offset"
+        eprintln!(
+            "Searching call routes from '{}' to '{}' (max depth: {})",
+            from, to, max_depth
+        );
+    }
+
+    let analyzer = crate::neo4j::CallPathAnalyzer::new(session.importer().connection().clone())
+        .with_exclusions(config.exclude)
+        .with_library_exclusion(config.exclude_library)
+        .with_min_confidence(config.min_confidence)
+        .with_virtual_dispatch(config.expand_virtual);
+    let routes = analyzer
+        .query_call_routes(from, to, config.binary, max_depth, !all_routes)
+        .await?;
+
+    if routes.is_empty() {
+        eprintln!(
+            "No call route found from '{}' to '{}' within depth {}",
+            from, to, max_depth
         );
-        println!("{}", "-".repeat(75));
+    }
 
-        for x in &xrefs {
-            println!(
-                "{:<30} {:<30} {:<15}",
-                x.from_function, x.to_function, x.offset
+    if renderer.format().is_document() {
+        return renderer.document(&routes);
+    }
+
+    if renderer.format().is_diagram() {
+        let mut diagram = Diagram::default();
+        for route in &routes {
+            diagram.add_path(
+                route
+                    .nodes
+                    .iter()
+                    .map(|n| (n.uid.as_str(), n.name.as_str(), n.call_site.as_deref())),
+                true,
             );
         }
+        return renderer.diagram(&diagram);
+    }
+
+    if renderer.format() == OutputFormat::Csv {
+        let entries: Vec<CallPathEntry> = routes
+            .iter()
+            .flat_map(|route| {
+                route.nodes.iter().map(|node| CallPathEntry {
+                    path_id: route.id.clone(),
+                    direction: "route",
+                    depth: node.depth,
+                    name: node.name.clone(),
+                    address: node.address.clone(),
+                    call_site: node.call_site.clone(),
+                })
+            })
+            .collect();
+        return renderer.table(&entries);
+    }
+
+    if routes.is_empty() {
+        return Ok(());
+    }
+
+    renderer.line(&format!(
+        "\nFound {} {}route(s) from '{}' to '{}':",
+        routes.len(),
+        if all_routes { "" } else { "shortest " },
+        from,
+        to
+    ))?;
+
+    for (i, route) in routes.iter().enumerate() {
+        renderer.line(&format!("\n  Route {} ({} calls):", i + 1, route.length))?;
+        for node in &route.nodes {
+            let address = node.address.as_deref().unwrap_or("N/A");
+            match &node.call_site {
+                Some(call_site) => renderer.line(&format!(
+                    "    {}└─ {} @ {} (called at {})",
+                    "  ".repeat(node.depth.saturating_sub(1)),
+                    node.name,
+                    address,
+                    call_site
+                ))?,
+                None => renderer.line(&format!("    {} @ {}", node.name, address))?,
+            }
+        }
     }
 
     Ok(())
@@ -407,22 +1930,32 @@ async fn query_xrefs(
 
 async fn query_call_paths(
     session: &crate::api::ImportSession,
+    renderer: &mut Renderer,
     function_name: &str,
     config: CallPathQueryConfig<'_>,
 ) -> Result<()> {
     if let Some(binary_name) = config.binary {
-        println!(
+        eprintln!(
             "Analyzing call paths and execution order for function: '{}' in binary: '{}'",
             function_name, binary_name
         );
     } else {
-        println!(
+        eprintln!(
             "Analyzing call paths and execution order for function: '{}'",
             function_name
         );
     }
 
-    let analyzer = crate::neo4j::CallPathAnalyzer::new(session.importer().connection().clone());
+    let analyzer = crate::neo4j::CallPathAnalyzer::new(session.importer().connection().clone())
+        .with_exclusions(config.exclude)
+        .with_library_exclusion(config.exclude_library)
+        .with_min_confidence(config.min_confidence)
+        .with_virtual_dispatch(config.expand_virtual)
+        .with_path_limits(config.expander, config.max_results);
+    let mut report = CallPathReport {
+        function_name: function_name.to_string(),
+        ..Default::default()
+    };
 
     let show_all = !config.show_paths
         && !config.show_sequences
@@ -431,211 +1964,240 @@ async fn query_call_paths(
         && !config.show_context;
 
     if config.show_paths || show_all {
-        println!("\nAnalyzing call paths...");
+        renderer.line("\nAnalyzing call paths...")?;
         let call_paths = analyzer
             .query_call_paths(function_name, config.binary, config.max_depth)
             .await?;
+        warn_path_limit(call_paths.len(), config.max_results);
 
         if call_paths.is_empty() {
-            println!("No call paths found");
+            renderer.line("No call paths found")?;
         } else {
             let mut depth_stats = std::collections::HashMap::new();
             for path in &call_paths {
                 *depth_stats.entry(path.length).or_insert(0) += 1;
             }
 
-            println!("Found {} call paths:", call_paths.len());
+            renderer.line(&format!("Found {} call paths:", call_paths.len()))?;
             let mut sorted_depths: Vec<_> = depth_stats.iter().collect();
             sorted_depths.sort_by(|a, b| a.0.cmp(b.0));
             for (depth, count) in sorted_depths {
-                println!("  Depth {}: {} paths", depth, count);
+                renderer.line(&format!("  Depth {}: {} paths", depth, count))?;
             }
 
             let mut sorted_paths = call_paths.clone();
-            sorted_paths.sort_by(|a, b| b.length.cmp(&a.length));
+            sorted_paths.sort_by_key(|p| std::cmp::Reverse(p.length));
 
-            println!("\nLongest call path examples (top 10):");
+            renderer.line("\nLongest call path examples (top 10):")?;
             for (i, path) in sorted_paths.iter().take(10).enumerate() {
                 if let Some(entry) = path.entry_function() {
-                    println!("  Path {}: {} (Depth: {})", i + 1, entry.name, path.length);
-
-                    if config.format == "json" {
-                        let json = serde_json::to_string_pretty(&path)?;
-                        println!("    Path details: {}", json);
-                    } else {
-                        for node in &path.nodes {
-                            let indent = "  ".repeat(node.depth + 2);
-                            println!(
-                                "{}├─ {} @ {}",
-                                indent,
-                                node.name,
-                                node.address.as_deref().unwrap_or("N/A")
-                            );
-                        }
-                        println!();
+                    renderer.line(&format!(
+                        "  Path {}: {} (Depth: {})",
+                        i + 1,
+                        entry.name,
+                        path.length
+                    ))?;
+
+                    for node in &path.nodes {
+                        let indent = "  ".repeat(node.depth + 2);
+                        renderer.line(&format!(
+                            "{}├─ {} @ {}",
+                            indent,
+                            node.name,
+                            node.address.as_deref().unwrap_or("N/A")
+                        ))?;
                     }
+                    renderer.line("")?;
                 }
             }
 
             if call_paths.len() > 10 {
-                println!(
+                renderer.line(&format!(
                     "  ... and {} more paths (use --format json to see full list)",
                     call_paths.len() - 10
-                );
+                ))?;
             }
         }
+
+        report.call_paths = Some(call_paths);
     }
 
     if config.show_sequences || show_all {
-        println!("\nAnalyzing call sequences...");
+        renderer.line("\nAnalyzing call sequences...")?;
         let sequences = analyzer
             .query_call_sequences(function_name, config.binary)
             .await?;
 
         if sequences.is_empty() {
-            println!("No call sequences found");
+            renderer.line("No call sequences found")?;
         } else {
-            println!("Call execution order:");
+            renderer.line("Call execution order:")?;
             for sequence in &sequences {
-                println!(
+                renderer.line(&format!(
                     "  {}. {} -> {} (called at {})",
                     sequence.order, sequence.caller, sequence.callee, sequence.call_site
-                );
+                ))?;
             }
         }
+
+        report.call_sequences = Some(sequences);
     }
 
     if config.show_recursive || show_all {
-        println!("\nChecking recursive calls...");
+        renderer.line("\nChecking recursive calls...")?;
         let recursive_calls = analyzer
             .find_recursive_calls(function_name, config.binary)
             .await?;
 
         if recursive_calls.is_empty() {
-            println!("No recursive calls found");
+            renderer.line("No recursive calls found")?;
         } else {
-            println!("Found {} recursive calls:", recursive_calls.len());
+            renderer.line(&format!("Found {} recursive calls:", recursive_calls.len()))?;
             for recursive in &recursive_calls {
-                match recursive.call_type {
-                    RecursiveCallType::Direct => {
-                        println!(
-                            "  Direct recursion: {} (Depth: {})",
-                            recursive.function_name, recursive.depth
-                        );
-                    }
-                    RecursiveCallType::Indirect => {
-                        println!(
-                            "  Indirect recursion: {} (Depth: {})",
-                            recursive.function_name, recursive.depth
-                        );
-                    }
-                }
+                let kind = match recursive.call_type {
+                    RecursiveCallType::Direct => "Direct",
+                    RecursiveCallType::Indirect => "Indirect",
+                };
+                renderer.line(&format!(
+                    "  {} recursion: {} (Depth: {})",
+                    kind, recursive.function_name, recursive.depth
+                ))?;
             }
         }
+
+        report.recursive_calls = Some(recursive_calls);
     }
 
     if config.show_upward || show_all {
-        println!("\nAnalyzing upward call chains...");
+        renderer.line("\nAnalyzing upward call chains...")?;
         let upward_chains = analyzer
             .query_upward_call_chain(function_name, config.binary, config.max_depth)
             .await?;
+        warn_path_limit(upward_chains.len(), config.max_results);
 
         if upward_chains.is_empty() {
-            println!("No upward call chains found");
+            renderer.line("No upward call chains found")?;
         } else {
             let mut depth_stats = std::collections::HashMap::new();
             for chain in &upward_chains {
                 *depth_stats.entry(chain.length).or_insert(0) += 1;
             }
 
-            println!("Found {} upward call chains:", upward_chains.len());
+            renderer.line(&format!(
+                "Found {} upward call chains:",
+                upward_chains.len()
+            ))?;
             let mut sorted_depths: Vec<_> = depth_stats.iter().collect();
             sorted_depths.sort_by(|a, b| a.0.cmp(b.0));
             for (depth, count) in sorted_depths {
-                println!("  Depth {}: {} call chains", depth, count);
+                renderer.line(&format!("  Depth {}: {} call chains", depth, count))?;
             }
 
             let mut sorted_chains = upward_chains.clone();
-            sorted_chains.sort_by(|a, b| b.length.cmp(&a.length));
+            sorted_chains.sort_by_key(|c| std::cmp::Reverse(c.length));
 
-            println!("\nDeepest upward call chain examples (top 10):");
+            renderer.line("\nDeepest upward call chain examples (top 10):")?;
             for (i, chain) in sorted_chains.iter().take(10).enumerate() {
-                if let Some(target) = chain.target_function() {
-                    println!(
-                        "  Call chain {}: {} (Depth: {})",
+                if let (Some(root), Some(target)) = (chain.root_caller(), chain.target_function()) {
+                    renderer.line(&format!(
+                        "  Call chain {}: {} -> {} (Depth: {})",
                         i + 1,
+                        root.name,
                         target.name,
                         chain.length
-                    );
-
-                    if config.format == "json" {
-                        let json = serde_json::to_string_pretty(&chain)?;
-                        println!("    Call chain details: {}", json);
-                    } else {
-                        for node in &chain.nodes {
-                            let indent = "  ".repeat((chain.length - node.depth) + 2);
-                            let arrow = if node.depth < chain.length - 1 {
-                                "├─"
-                            } else {
-                                "└─"
-                            };
-                            println!(
-                                "{}{} {} @ {}",
-                                indent,
-                                arrow,
-                                node.name,
-                                node.address.as_deref().unwrap_or("N/A")
-                            );
-                        }
-                        println!();
+                    ))?;
+
+                    for node in &chain.nodes {
+                        let indent = "  ".repeat((chain.length - node.depth) + 2);
+                        let arrow = if node.depth > 0 { "├─" } else { "└─" };
+                        renderer.line(&format!(
+                            "{}{} {} @ {}",
+                            indent,
+                            arrow,
+                            node.name,
+                            node.address.as_deref().unwrap_or("N/A")
+                        ))?;
                     }
+                    renderer.line("")?;
                 }
             }
 
             if upward_chains.len() > 10 {
-                println!(
+                renderer.line(&format!(
                     "  ... and {} more call chains (use --format json to see full list)",
                     upward_chains.len() - 10
-                );
+                ))?;
             }
 
             let caller_sequences = analyzer
                 .query_caller_sequences(function_name, config.binary)
                 .await?;
             if !caller_sequences.is_empty() {
-                println!("\nWho calls '{}':", function_name);
+                renderer.line(&format!("\nWho calls '{}':", function_name))?;
                 for sequence in &caller_sequences {
-                    println!(
+                    renderer.line(&format!(
                         "  {}. {} -> {} (called at {})",
                         sequence.order,
                         sequence.caller_name,
                         sequence.callee_name,
                         sequence.call_site
-                    );
+                    ))?;
                 }
             }
+            report.caller_sequences = Some(caller_sequences);
         }
+
+        report.upward_chains = Some(upward_chains);
     }
 
     if config.show_context || show_all {
-        println!("\nFull call context analysis...");
+        renderer.line("\nFull call context analysis...")?;
         let context_analysis = analyzer
             .analyze_call_context(function_name, config.binary, config.max_depth)
             .await?;
 
-        println!("Call context insights:");
+        renderer.line("Call context insights:")?;
         for insight in &context_analysis.context_insights {
-            println!("  {}", insight);
+            renderer.line(&format!("  {}", insight))?;
         }
+
+        report.context = Some(context_analysis);
     }
 
-    if config.format == "json" {
-        let enhanced_graph = analyzer
-            .query_enhanced_call_graph(function_name, config.binary, config.max_depth)
-            .await?;
-        let json = serde_json::to_string_pretty(&enhanced_graph)?;
-        println!("\nEnhanced call graph (JSON):");
-        println!("{}", json);
+    match renderer.format() {
+        OutputFormat::Json | OutputFormat::Jsonl | OutputFormat::Yaml => {
+            report.enhanced_graph = Some(
+                analyzer
+                    .query_enhanced_call_graph(function_name, config.binary, config.max_depth)
+                    .await?,
+            );
+            renderer.document(&report)?;
+        }
+        OutputFormat::Csv => renderer.table(&report.path_entries())?,
+        OutputFormat::Table | OutputFormat::Markdown => {}
+        // Rejected as unsupported
+        OutputFormat::Parquet => renderer.document(&report)?,
+        OutputFormat::Mermaid | OutputFormat::Plantuml | OutputFormat::Cytoscape => {
+            let mut diagram = Diagram::default();
+            for path in report.call_paths.iter().flatten() {
+                diagram.add_path(
+                    path.nodes
+                        .iter()
+                        .map(|n| (n.uid.as_str(), n.name.as_str(), n.call_site.as_deref())),
+                    true,
+                );
+            }
+            for chain in report.upward_chains.iter().flatten() {
+                diagram.add_path(
+                    chain
+                        .nodes
+                        .iter()
+                        .map(|n| (n.uid.as_str(), n.name.as_str(), n.call_site.as_deref())),
+                    false,
+                );
+            }
+            renderer.diagram(&diagram)?;
+        }
     }
 
     Ok(())
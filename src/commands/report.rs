@@ -0,0 +1,96 @@
+use anyhow::Result;
+use std::io::Write;
+
+use crate::api::DataImporter;
+use crate::config::Config;
+use crate::models::{RiskReport, TriageReport};
+use crate::output::{html, OutputOptions};
+use crate::utils::risk::RiskProfile;
+
+/// Settings of the `report` command
+#[derive(Debug, Clone)]
+pub struct ReportOptions {
+    pub strings: usize,
+    pub max_edges: usize,
+    pub profile: Option<String>,
+}
+
+pub async fn handle_report(
+    binary: &str,
+    options: &ReportOptions,
+    config: Config,
+    output: &OutputOptions,
+) -> Result<()> {
+    let profile = match &options.profile {
+        Some(path) => RiskProfile::load(path)?,
+        None => RiskProfile::default(),
+    };
+
+    let importer = DataImporter::for_analysis(&config).await?;
+    let session = importer.session();
+
+    let sample = match session.query_binary_by_hash(binary).await? {
+        Some(found) => Some(found),
+        None => session.query_binary_info(binary).await?,
+    };
+    let Some(sample) = sample else {
+        return Err(crate::error::not_found(format!(
+            "No binary found matching: '{}'",
+            binary
+        )));
+    };
+    eprintln!("Generating triage report for '{}'", sample.filename);
+
+    let imports = session.query_binary_imports(&sample.hash).await?;
+    let strings = session
+        .query_top_strings(&sample.hash, options.strings)
+        .await?;
+    let call_graph = session
+        .query_binary_call_edges(&sample.hash, options.max_edges)
+        .await?;
+    if call_graph.len() == options.max_edges {
+        eprintln!(
+            "[WARN] Call graph truncated to {} edges, raise --max-edges to include more",
+            options.max_edges
+        );
+    }
+
+    let uses = session.query_imported_api_uses(&sample.hash).await?;
+    let contributions = profile.contributions(&uses);
+    let risk = RiskReport {
+        binary: sample.filename.clone(),
+        hash: sample.hash.clone(),
+        score: contributions.iter().map(|c| c.weight).sum(),
+        contributions,
+    };
+
+    let report = TriageReport {
+        binary: sample,
+        imports,
+        strings,
+        risk,
+        call_graph,
+    };
+    let page = html::triage_report(&report)?;
+
+    match output.create_file()? {
+        Some(mut file) => {
+            file.write_all(page.as_bytes())?;
+            file.commit()?;
+            eprintln!(
+                "Report written to {}",
+                output.path.as_deref().unwrap_or_default()
+            );
+        }
+        None => write_stdout(&page)?,
+    }
+
+    Ok(())
+}
+
+fn write_stdout(page: &str) -> Result<()> {
+    let mut stdout = std::io::stdout();
+    stdout.write_all(page.as_bytes())?;
+    stdout.flush()?;
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use anyhow::Result;
+
+use crate::config::Config;
+
+/// Serve the REST API, and the gRPC API on `grpc_listen`; with `import_queue`, also
+/// accept uploads imported by `import_workers` workers while at most `max_queued` jobs
+/// are waiting
+pub async fn handle_serve(
+    listen: &str,
+    ui: bool,
+    import_queue: Option<&str>,
+    import_workers: usize,
+    max_queued: usize,
+    grpc_listen: Option<&str>,
+    config: Config,
+) -> Result<()> {
+    #[cfg(not(feature = "grpc"))]
+    if grpc_listen.is_some() {
+        return Err(crate::error::validation(
+            "`--grpc-listen` needs a build with `--features grpc`",
+            Vec::new(),
+        ));
+    }
+
+    #[cfg(feature = "server")]
+    {
+        let importer = crate::api::DataImporter::for_analysis(&config).await?;
+        let jobs = match import_queue {
+            Some(dir) => {
+                config.ensure_writable("accept imports")?;
+                let writer = crate::api::DataImporter::new(&config).await?;
+                let queue =
+                    crate::server::jobs::JobQueue::open(dir, writer, import_workers, max_queued)?;
+                Some(queue)
+            }
+            None => None,
+        };
+        let listener = tokio::net::TcpListener::bind(listen)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to listen on {}: {}", listen, e))?;
+        #[cfg(feature = "grpc")]
+        let grpc = match grpc_listen {
+            Some(address) => Some(
+                tokio::net::TcpListener::bind(address)
+                    .await
+                    .map_err(|e| anyhow::anyhow!("Failed to listen on {}: {}", address, e))?,
+            ),
+            None => None,
+        };
+
+        eprintln!("Serving the REST API on http://{}/api", listen);
+        if ui {
+            eprintln!("Graph explorer at http://{}/", listen);
+        }
+        if jobs.is_some() {
+            eprintln!(
+                "Accepting imports at http://{}/api/jobs ({} workers)",
+                listen,
+                import_workers.max(1)
+            );
+        }
+        let rest = axum::serve(
+            listener,
+            crate::server::router(importer.clone(), ui, jobs.clone()),
+        );
+
+        #[cfg(feature = "grpc")]
+        if let (Some(grpc), Some(address)) = (grpc, grpc_listen) {
+            eprintln!("Serving the gRPC API on {}", address);
+            tokio::try_join!(
+                async { rest.await.map_err(anyhow::Error::from) },
+                crate::server::grpc::serve(grpc, importer, jobs),
+            )?;
+            return Ok(());
+        }
+        rest.await?;
+        Ok(())
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        let _ = (listen, ui, import_queue, import_workers, max_queued, config);
+        Err(crate::error::validation(
+            "`serve` needs a build with `--features server`",
+            Vec::new(),
+        ))
+    }
+}
@@ -2,13 +2,59 @@ use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 
+use crate::neo4j::connection::SUPPORTED_SCHEMES;
+use crate::store::Backend;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub neo4j_uri: String,
     pub neo4j_user: String,
     pub neo4j_password: String,
     pub neo4j_database: Option<String>,
+    /// Read replicas used by query, analyze and report commands; writes always go to
+    /// `neo4j_uri`
+    #[serde(default)]
+    pub neo4j_read_uris: Vec<String>,
+    /// Connection pool size per endpoint (driver default: 16)
+    #[serde(default)]
+    pub neo4j_max_connections: Option<usize>,
+    /// Rows fetched per round trip while streaming results (driver default: 200)
+    #[serde(default)]
+    pub neo4j_fetch_size: Option<usize>,
+    /// Read queries running longer than this many seconds are cancelled
+    #[serde(default)]
+    pub query_timeout_secs: Option<u64>,
     pub batch_size: usize,
+    /// Strings longer than this many characters are stored truncated, keeping the
+    /// hash and length of the full content
+    #[serde(default)]
+    pub max_string_length: Option<usize>,
+    /// Directory where the full content of truncated strings is written, one file
+    /// per SHA-256
+    #[serde(default)]
+    pub string_blob_dir: Option<String>,
+    /// Decompiled code longer than this many characters is stored truncated; the full
+    /// text goes to `string_blob_dir` when set
+    #[serde(default = "default_max_pseudocode_length")]
+    pub max_pseudocode_length: usize,
+    /// Refuse commands that modify the graph (imports, annotations, schema setup,
+    /// clearing); analyses still run but do not store their results
+    #[serde(default)]
+    pub read_only: bool,
+    /// Store addresses relative to `binary_info.image_base` when an import gives one;
+    /// false ignores the image base and keeps addresses as the extractor wrote them
+    #[serde(default = "default_relative_addresses")]
+    pub relative_addresses: bool,
+    /// Where the graph is kept; `--backend` overrides it
+    #[serde(default)]
+    pub backend: Backend,
+    /// Graph file of the embedded backend
+    #[serde(default = "default_embedded_path")]
+    pub embedded_path: String,
+    /// Project selected with `--project`; its data lives in the Neo4j database of the
+    /// same name, which replaces `neo4j_database`
+    #[serde(skip)]
+    pub project: Option<String>,
 }
 
 impl Default for Config {
@@ -18,7 +64,19 @@ impl Default for Config {
             neo4j_user: "neo4j".to_string(),
             neo4j_password: "password".to_string(),
             neo4j_database: None,
+            neo4j_read_uris: Vec::new(),
+            neo4j_max_connections: None,
+            neo4j_fetch_size: None,
+            query_timeout_secs: None,
             batch_size: 1000,
+            max_string_length: None,
+            string_blob_dir: None,
+            max_pseudocode_length: default_max_pseudocode_length(),
+            read_only: false,
+            relative_addresses: default_relative_addresses(),
+            backend: Backend::default(),
+            embedded_path: default_embedded_path(),
+            project: None,
         }
     }
 }
@@ -29,6 +87,18 @@ impl Config {
         Self::load_from_file(path)
     }
 
+    /// As `load_from_path`, but the embedded backend runs without a config file when
+    /// none was given and `config.json` is missing
+    pub fn load_for_backend(config_path: Option<&str>, backend: Option<Backend>) -> Result<Self> {
+        if backend == Some(Backend::Embedded)
+            && config_path.is_none()
+            && !Path::new("config.json").exists()
+        {
+            return Ok(Self::default());
+        }
+        Self::load_from_path(config_path)
+    }
+
     pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
         let path_ref = path.as_ref();
         let content = std::fs::read_to_string(path_ref).map_err(|e| {
@@ -47,11 +117,42 @@ impl Config {
         Ok(config)
     }
 
+    /// Target the Neo4j database of `project`, so separate engagements share one
+    /// server without seeing each other's samples
+    pub fn for_project(mut self, project: &str) -> Result<Self> {
+        let database = project_database(project)?;
+        self.neo4j_database = Some(database.clone());
+        self.project = Some(database);
+        Ok(self)
+    }
+
+    /// Error out before `action` if the config is read-only
+    pub fn ensure_writable(&self, action: &str) -> Result<()> {
+        if self.read_only {
+            return Err(anyhow::anyhow!(
+                "Refusing to {}: read_only is set in the config",
+                action
+            ));
+        }
+        Ok(())
+    }
+
     pub fn validate(&self) -> Result<()> {
         if self.neo4j_uri.is_empty() {
             return Err(anyhow::anyhow!("Neo4j URI cannot be empty"));
         }
 
+        for uri in std::iter::once(&self.neo4j_uri).chain(&self.neo4j_read_uris) {
+            let scheme = uri.split_once("://").map_or("bolt", |(scheme, _)| scheme);
+            if !SUPPORTED_SCHEMES.contains(&scheme) {
+                return Err(anyhow::anyhow!(
+                    "Unsupported Neo4j URI scheme in '{}', expected one of: {}",
+                    uri,
+                    SUPPORTED_SCHEMES.join(", ")
+                ));
+            }
+        }
+
         if self.neo4j_user.is_empty() {
             return Err(anyhow::anyhow!("Neo4j user cannot be empty"));
         }
@@ -60,10 +161,97 @@ impl Config {
             return Err(anyhow::anyhow!("Neo4j password cannot be empty"));
         }
 
+        if self.neo4j_max_connections == Some(0) {
+            return Err(anyhow::anyhow!("Max connections must be greater than 0"));
+        }
+
+        if self.neo4j_fetch_size == Some(0) {
+            return Err(anyhow::anyhow!("Fetch size must be greater than 0"));
+        }
+
+        if self.query_timeout_secs == Some(0) {
+            return Err(anyhow::anyhow!("Query timeout must be greater than 0"));
+        }
+
         if self.batch_size == 0 {
             return Err(anyhow::anyhow!("Batch size must be greater than 0"));
         }
 
+        if self.max_string_length == Some(0) {
+            return Err(anyhow::anyhow!("Max string length must be greater than 0"));
+        }
+
+        if self.max_pseudocode_length == 0 {
+            return Err(anyhow::anyhow!(
+                "Max pseudocode length must be greater than 0"
+            ));
+        }
+
+        if self.embedded_path.is_empty() {
+            return Err(anyhow::anyhow!("Embedded path cannot be empty"));
+        }
+
+        if self.string_blob_dir.is_some() && self.max_string_length.is_none() {
+            return Err(anyhow::anyhow!(
+                "string_blob_dir requires max_string_length to be set"
+            ));
+        }
+
         Ok(())
     }
 }
+
+fn default_max_pseudocode_length() -> usize {
+    65536
+}
+
+fn default_relative_addresses() -> bool {
+    true
+}
+
+fn default_embedded_path() -> String {
+    "binaryx-graph.json".to_string()
+}
+
+/// Database name for a project: lowercased, `_` and spaces turned into `-`, and
+/// checked against Neo4j's naming rules (3-63 characters of `a-z`, `0-9`, `.` and `-`,
+/// starting with a letter; `system` and `neo4j` are reserved)
+fn project_database(project: &str) -> Result<String> {
+    let name: String = project
+        .trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| if c == '_' || c == ' ' { '-' } else { c })
+        .collect();
+
+    let valid_chars = name
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '.' || c == '-');
+    let starts_with_letter = name.chars().next().is_some_and(|c| c.is_ascii_lowercase());
+    if !valid_chars || !starts_with_letter || !(3..=63).contains(&name.len()) {
+        return Err(anyhow::anyhow!(
+            "Invalid project name '{}': use 3-63 letters, digits, '.', '-' or '_', starting with a letter",
+            project
+        ));
+    }
+    if name == "system" || name == "neo4j" {
+        return Err(anyhow::anyhow!("Project name '{}' is reserved", project));
+    }
+
+    Ok(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_project_database_names() {
+        assert_eq!(project_database("Acme_IR 2024").unwrap(), "acme-ir-2024");
+        assert_eq!(project_database("case.17").unwrap(), "case.17");
+        assert!(project_database("7zip").is_err());
+        assert!(project_database("ab").is_err());
+        assert!(project_database("acme/ir").is_err());
+        assert!(project_database("System").is_err());
+    }
+}
@@ -0,0 +1,168 @@
+//! Failure causes reported to calling scripts through the exit code and the
+//! `--errors-json` document
+//!
+//! Errors are `anyhow::Error`s throughout; the ones whose cause matters to a caller
+//! carry a [`CliError`]. Errors from the Neo4j driver count as connection errors.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Category of a failed command
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    /// Not classified below
+    Other,
+    /// Neo4j unreachable, authentication refused, or a query failed on the server
+    Connection,
+    /// Input data, arguments or configuration rejected
+    Validation,
+    /// A binary, function or file named on the command line does not exist
+    NotFound,
+    /// An import finished but some of its items failed
+    PartialImport,
+}
+
+impl ErrorKind {
+    /// Process exit code; 2 is left to command-line usage errors
+    pub fn exit_code(self) -> u8 {
+        match self {
+            ErrorKind::Other => 1,
+            ErrorKind::Connection => 3,
+            ErrorKind::Validation => 4,
+            ErrorKind::NotFound => 5,
+            ErrorKind::PartialImport => 6,
+        }
+    }
+}
+
+/// Error of a known kind, with the individual failures behind it
+#[derive(Debug)]
+pub struct CliError {
+    pub kind: ErrorKind,
+    pub message: String,
+    /// Per-item errors, e.g. of the files or nodes that failed to import
+    pub details: Vec<String>,
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for CliError {}
+
+fn error(kind: ErrorKind, message: impl Into<String>, details: Vec<String>) -> anyhow::Error {
+    anyhow::Error::new(CliError {
+        kind,
+        message: message.into(),
+        details,
+    })
+}
+
+pub fn connection(message: impl Into<String>) -> anyhow::Error {
+    error(ErrorKind::Connection, message, Vec::new())
+}
+
+pub fn validation(message: impl Into<String>, details: Vec<String>) -> anyhow::Error {
+    error(ErrorKind::Validation, message, details)
+}
+
+pub fn not_found(message: impl Into<String>) -> anyhow::Error {
+    error(ErrorKind::NotFound, message, Vec::new())
+}
+
+pub fn partial_import(message: impl Into<String>, details: Vec<String>) -> anyhow::Error {
+    error(ErrorKind::PartialImport, message, details)
+}
+
+/// Kind of the first classified error in the chain
+pub fn classify(err: &anyhow::Error) -> ErrorKind {
+    err.chain()
+        .find_map(|cause| {
+            if let Some(cli_error) = cause.downcast_ref::<CliError>() {
+                Some(cli_error.kind)
+            } else if is_driver_error(cause) {
+                Some(ErrorKind::Connection)
+            } else {
+                None
+            }
+        })
+        .unwrap_or(ErrorKind::Other)
+}
+
+#[cfg(feature = "neo4j")]
+fn is_driver_error(cause: &(dyn std::error::Error + 'static)) -> bool {
+    cause.is::<neo4rs::Error>()
+}
+
+/// Without the Bolt client, connection errors are only the classified ones
+#[cfg(not(feature = "neo4j"))]
+fn is_driver_error(_cause: &(dyn std::error::Error + 'static)) -> bool {
+    false
+}
+
+/// Per-item errors carried by the first [`CliError`] in the chain
+pub fn details(err: &anyhow::Error) -> &[String] {
+    err.chain()
+        .find_map(|cause| cause.downcast_ref::<CliError>())
+        .map_or(&[], |cli_error| cli_error.details.as_slice())
+}
+
+/// Outcome of a command as written by `--errors-json`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErrorReport {
+    pub success: bool,
+    pub exit_code: u8,
+    pub kind: Option<ErrorKind>,
+    pub message: Option<String>,
+    /// Messages of the underlying causes, outermost first
+    pub causes: Vec<String>,
+    pub details: Vec<String>,
+}
+
+impl ErrorReport {
+    pub fn success() -> Self {
+        Self {
+            success: true,
+            exit_code: 0,
+            kind: None,
+            message: None,
+            causes: Vec::new(),
+            details: Vec::new(),
+        }
+    }
+
+    pub fn failure(err: &anyhow::Error) -> Self {
+        let kind = classify(err);
+        Self {
+            success: false,
+            exit_code: kind.exit_code(),
+            kind: Some(kind),
+            message: Some(err.to_string()),
+            causes: err.chain().skip(1).map(|cause| cause.to_string()).collect(),
+            details: details(err).to_vec(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        let err = not_found("No binary found matching: 'x'").context("Report failed");
+        assert_eq!(classify(&err), ErrorKind::NotFound);
+
+        let report = ErrorReport::failure(&err);
+        assert_eq!(report.exit_code, 5);
+        assert_eq!(report.message.as_deref(), Some("Report failed"));
+        assert_eq!(report.causes, vec!["No binary found matching: 'x'"]);
+
+        let err = partial_import("2 errors", vec!["a".into(), "b".into()]);
+        assert_eq!(ErrorReport::failure(&err).details.len(), 2);
+        assert_eq!(classify(&anyhow::anyhow!("boom")), ErrorKind::Other);
+    }
+}
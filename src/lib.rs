@@ -1,7 +1,21 @@
+#[cfg(feature = "neo4j")]
 pub mod api;
+#[cfg(feature = "neo4j")]
 pub mod cli;
+#[cfg(feature = "neo4j")]
 pub mod commands;
+#[cfg(feature = "neo4j")]
 pub mod config;
+pub mod error;
 pub mod models;
+#[cfg(feature = "neo4j")]
 pub mod neo4j;
+#[cfg(feature = "neo4j")]
+pub mod output;
+#[cfg(feature = "remote")]
+pub mod remote;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "neo4j")]
+pub mod store;
 pub mod utils;
@@ -4,20 +4,47 @@ mod api;
 mod cli;
 mod commands;
 mod config;
+mod error;
 mod models;
 mod neo4j;
+mod output;
+#[cfg(feature = "server")]
+mod server;
+mod store;
 mod utils;
 
+use std::process::ExitCode;
+
 use cli::Cli;
 use config::Config;
+use error::ErrorReport;
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    println!("Starting BinaryX-Graph...");
+async fn main() -> ExitCode {
+    eprintln!("Starting BinaryX-Graph...");
 
     let cli = Cli::parse();
-    let config = Config::load_from_path(cli.config.as_deref())?;
-    cli.execute(config).await?;
+    let errors_json = cli.errors_json.clone();
+    let result = match Config::load_for_backend(cli.config.as_deref(), cli.backend) {
+        Ok(config) => cli.execute(config).await,
+        Err(e) => Err(error::validation(format!("{:#}", e), Vec::new())),
+    };
+
+    let report = match &result {
+        Ok(()) => ErrorReport::success(),
+        Err(e) => {
+            eprintln!("Error: {:?}", e);
+            ErrorReport::failure(e)
+        }
+    };
+    if let Some(path) = errors_json {
+        let written = serde_json::to_string_pretty(&report)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| Ok(std::fs::write(&path, json + "\n")?));
+        if let Err(e) = written {
+            eprintln!("[WARN] Failed to write error report '{}': {}", path, e);
+        }
+    }
 
-    Ok(())
+    ExitCode::from(report.exit_code)
 }
@@ -1,10 +1,21 @@
-use crate::neo4j::importer::FunctionInfo;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
+/// How `query call-path` enumerates variable-length call paths
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum PathExpander {
+    /// Cypher `[:CALLS*1..n]` patterns, paths revisiting a function are dropped
+    #[default]
+    Native,
+    /// `apoc.path.expandConfig`: breadth-first with node-path uniqueness, excluded
+    /// functions are pruned during expansion (needs the APOC plugin)
+    Apoc,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CallPathNode {
     pub id: String,
+    pub uid: String,
     pub name: String,
     pub address: Option<String>,
     pub depth: usize,
@@ -15,6 +26,7 @@ pub struct CallPathNode {
 impl CallPathNode {
     pub fn new(
         id: String,
+        uid: String,
         name: String,
         address: Option<String>,
         depth: usize,
@@ -23,6 +35,7 @@ impl CallPathNode {
     ) -> Self {
         Self {
             id,
+            uid,
             name,
             address,
             depth,
@@ -32,6 +45,9 @@ impl CallPathNode {
     }
 }
 
+/// A downward call path starting at the queried function
+///
+/// `length` is the number of calls (edges) on the path, one less than the node count.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CallPath {
     pub id: String,
@@ -58,13 +74,23 @@ impl CallPath {
     }
 
     pub fn add_node(&mut self, node: CallPathNode) {
-        self.length = node.depth;
         self.nodes.push(node);
+        self.length = self.nodes.len().saturating_sub(1);
     }
 
     pub fn entry_function(&self) -> Option<&CallPathNode> {
         self.nodes.first()
     }
+
+    /// Function UIDs along the path, in call order
+    pub fn node_uids(&self) -> Vec<&str> {
+        self.nodes.iter().map(|n| n.uid.as_str()).collect()
+    }
+
+    /// Remove paths visiting the same function sequence as an earlier path
+    pub fn dedup(paths: &mut Vec<CallPath>) {
+        dedup_by_uids(paths, |p| p.node_uids());
+    }
 }
 
 impl CallSequence {
@@ -88,6 +114,7 @@ impl CallSequence {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpwardCallNode {
     pub id: String,
+    pub uid: String,
     pub name: String,
     pub address: Option<String>,
     pub depth: usize,
@@ -98,6 +125,7 @@ pub struct UpwardCallNode {
 impl UpwardCallNode {
     pub fn new(
         id: String,
+        uid: String,
         name: String,
         address: Option<String>,
         depth: usize,
@@ -106,6 +134,7 @@ impl UpwardCallNode {
     ) -> Self {
         Self {
             id,
+            uid,
             name,
             address,
             depth,
@@ -115,6 +144,10 @@ impl UpwardCallNode {
     }
 }
 
+/// A chain of callers ending at the queried function
+///
+/// Nodes are ordered from the outermost caller to the target; a node's `depth` is its
+/// distance in calls from the target and `length` is the number of calls on the chain.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpwardCallChain {
     pub id: String,
@@ -152,13 +185,39 @@ impl UpwardCallChain {
     }
 
     pub fn add_node(&mut self, node: UpwardCallNode) {
-        self.length = node.depth;
         self.nodes.push(node);
+        self.length = self.nodes.len().saturating_sub(1);
     }
 
-    pub fn target_function(&self) -> Option<&UpwardCallNode> {
+    /// Outermost caller of the chain
+    pub fn root_caller(&self) -> Option<&UpwardCallNode> {
         self.nodes.first()
     }
+
+    pub fn target_function(&self) -> Option<&UpwardCallNode> {
+        self.nodes.last()
+    }
+
+    /// Function UIDs along the chain, from the outermost caller to the target
+    pub fn node_uids(&self) -> Vec<&str> {
+        self.nodes.iter().map(|n| n.uid.as_str()).collect()
+    }
+
+    /// Remove chains visiting the same function sequence as an earlier chain
+    pub fn dedup(chains: &mut Vec<UpwardCallChain>) {
+        dedup_by_uids(chains, |c| c.node_uids());
+    }
+}
+
+fn dedup_by_uids<T, F>(items: &mut Vec<T>, uids: F)
+where
+    F: Fn(&T) -> Vec<&str>,
+{
+    let mut seen = HashSet::new();
+    items.retain(|item| {
+        let key: Vec<String> = uids(item).into_iter().map(str::to_string).collect();
+        seen.insert(key)
+    });
 }
 
 impl CallerSequence {
@@ -223,6 +282,13 @@ impl CallContextAnalysis {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionInfo {
+    pub uid: String,
+    pub name: String,
+    pub address: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct EnhancedCallGraph {
     pub callees: Vec<FunctionInfo>,
@@ -243,3 +309,43 @@ impl EnhancedCallGraph {
         self.call_frequencies.insert(callee_name, frequency);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(id: &str, uids: &[&str]) -> CallPath {
+        let mut path = CallPath::new(id.to_string());
+        for (i, uid) in uids.iter().enumerate() {
+            path.add_node(CallPathNode::new(
+                format!("{}_{}", uid, i),
+                uid.to_string(),
+                uid.to_string(),
+                None,
+                i,
+                None,
+                "Direct".to_string(),
+            ));
+        }
+        path
+    }
+
+    #[test]
+    fn test_length_counts_edges() {
+        assert_eq!(path("p", &["main"]).length, 0);
+        assert_eq!(path("p", &["main", "init", "CreateFileW"]).length, 2);
+    }
+
+    #[test]
+    fn test_dedup_by_node_uids() {
+        let mut paths = vec![
+            path("p1", &["main", "init"]),
+            path("p2", &["main", "run"]),
+            path("p3", &["main", "init"]),
+        ];
+        CallPath::dedup(&mut paths);
+
+        let ids: Vec<&str> = paths.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(ids, ["p1", "p2"]);
+    }
+}
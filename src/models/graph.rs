@@ -0,0 +1,60 @@
+//! Graphs in the vis.js DataSet shape, as answered by the REST API of `serve` and
+//! embedded in HTML reports
+//!
+//! The server serializes these and [`RemoteClient`](crate::remote) decodes them, so
+//! both sides of the API share one definition.
+
+use serde::{Deserialize, Serialize};
+
+/// Node of a drawn graph
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GraphNode {
+    pub id: String,
+    pub label: String,
+    pub group: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GraphLink {
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GraphData {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphLink>,
+}
+
+/// Answer of `GET /api/binaries/<binary>/callgraph`
+#[cfg(any(feature = "server", feature = "remote"))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CallGraphResponse {
+    /// SHA-256 of the binary
+    pub binary: String,
+    /// The graph stops at `max_edges`
+    pub truncated: bool,
+    #[serde(flatten)]
+    pub graph: GraphData,
+}
+
+/// Undirected similarity edge between the reference binary and a neighbor
+#[cfg(any(feature = "server", feature = "remote"))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimilarityLink {
+    pub from: String,
+    pub to: String,
+    pub label: String,
+    pub similarity: f64,
+}
+
+/// Answer of `GET /api/binaries/<binary>/similar`
+#[cfg(any(feature = "server", feature = "remote"))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimilarityResponse {
+    pub binary: String,
+    pub space: String,
+    pub directed: bool,
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<SimilarityLink>,
+}
@@ -1,7 +1,9 @@
 pub mod call_path;
+pub mod graph;
 pub mod nodes;
 pub mod relationships;
 
 pub use call_path::*;
+pub use graph::*;
 pub use nodes::*;
 pub use relationships::*;
@@ -1,5 +1,9 @@
+use crate::utils::api_names::canonical_api_name;
+use crate::utils::demangle::demangle;
 use crate::utils::uid;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::str::FromStr;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum BinaryFormat {
@@ -11,6 +15,206 @@ pub enum BinaryFormat {
     MachO,
 }
 
+/// Normalized target architecture
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum Architecture {
+    #[serde(rename = "x86")]
+    X86,
+    #[serde(rename = "x86_64")]
+    X86_64,
+    #[serde(rename = "arm")]
+    Arm,
+    #[serde(rename = "arm64")]
+    Arm64,
+    #[serde(rename = "mips")]
+    Mips,
+    #[serde(rename = "mips64")]
+    Mips64,
+    #[serde(rename = "ppc")]
+    Ppc,
+    #[serde(rename = "ppc64")]
+    Ppc64,
+    #[serde(rename = "riscv32")]
+    RiscV32,
+    #[serde(rename = "riscv64")]
+    RiscV64,
+    #[serde(rename = "unknown")]
+    Unknown,
+}
+
+/// Spellings used by loaders and disassemblers that name one architecture
+/// regardless of bitness (lowercase)
+const ARCHITECTURE_ALIASES: &[(&str, Architecture)] = &[
+    ("i386", Architecture::X86),
+    ("i486", Architecture::X86),
+    ("i586", Architecture::X86),
+    ("i686", Architecture::X86),
+    ("ia32", Architecture::X86),
+    ("x86-32", Architecture::X86),
+    ("x86_32", Architecture::X86),
+    ("amd64", Architecture::X86_64),
+    ("x64", Architecture::X86_64),
+    ("x86-64", Architecture::X86_64),
+    ("em64t", Architecture::X86_64),
+    ("intel64", Architecture::X86_64),
+    ("aarch64", Architecture::Arm64),
+    ("arm64e", Architecture::Arm64),
+    ("armv8", Architecture::Arm64),
+    ("armel", Architecture::Arm),
+    ("armhf", Architecture::Arm),
+    ("armv7", Architecture::Arm),
+    ("armv7l", Architecture::Arm),
+    ("thumb", Architecture::Arm),
+    ("armcortexm", Architecture::Arm),
+    ("mipsel", Architecture::Mips),
+    ("mipseb", Architecture::Mips),
+    ("mips32", Architecture::Mips),
+    ("mips64el", Architecture::Mips64),
+    ("powerpc", Architecture::Ppc),
+    ("ppc32", Architecture::Ppc),
+    ("ppcle", Architecture::Ppc),
+    ("powerpc64", Architecture::Ppc64),
+    ("ppc64le", Architecture::Ppc64),
+    ("rv32", Architecture::RiscV32),
+    ("rv64", Architecture::RiscV64),
+];
+
+impl Architecture {
+    const ALL: [Architecture; 11] = [
+        Architecture::X86,
+        Architecture::X86_64,
+        Architecture::Arm,
+        Architecture::Arm64,
+        Architecture::Mips,
+        Architecture::Mips64,
+        Architecture::Ppc,
+        Architecture::Ppc64,
+        Architecture::RiscV32,
+        Architecture::RiscV64,
+        Architecture::Unknown,
+    ];
+
+    /// Normalizes an architecture name as written by an extractor (`AMD64`, `x86-64`,
+    /// `metapc`, `mips_32`, Ghidra's `x86:LE:64:default`, ...).
+    ///
+    /// `bits` decides between the 32- and 64-bit variant when the name is only a
+    /// processor family (`x86`, `arm`, `mips`, `ppc`, `riscv`); such names default
+    /// to the 32-bit variant without it.
+    pub fn parse(name: &str, bits: Option<u32>) -> Self {
+        let name = name.trim().to_lowercase();
+        let mut bits = bits.filter(|bits| *bits != 0);
+
+        // Ghidra language IDs: processor:endian:size:variant
+        let mut family = name.as_str();
+        if let Some((processor, rest)) = name.split_once(':') {
+            family = processor;
+            bits = bits.or_else(|| rest.split(':').nth(1).and_then(|b| b.parse().ok()));
+        }
+        if let Some(architecture) = Self::family(family, bits).or_else(|| Self::exact(family)) {
+            return architecture;
+        }
+
+        // `processor_bits` as produced by older releases (`mips_32`, `arm_64`)
+        if let Some((processor, suffix)) = family.rsplit_once(['_', '-']) {
+            if let Ok(suffix) = suffix.parse::<u32>() {
+                let bits = bits.or(Some(suffix));
+                if let Some(architecture) =
+                    Self::family(processor, bits).or_else(|| Self::exact(processor))
+                {
+                    return architecture;
+                }
+            }
+        }
+        Architecture::Unknown
+    }
+
+    /// Processor family name whose variant depends on the bitness
+    fn family(name: &str, bits: Option<u32>) -> Option<Self> {
+        let (narrow, wide) = match name {
+            "x86" | "metapc" | "pc" | "intel" | "80386" => {
+                (Architecture::X86, Architecture::X86_64)
+            }
+            "arm" | "arm32" | "armle" | "armbe" => (Architecture::Arm, Architecture::Arm64),
+            "mips" | "mipsle" | "mipsbe" => (Architecture::Mips, Architecture::Mips64),
+            "ppc" | "ppcbe" | "power" => (Architecture::Ppc, Architecture::Ppc64),
+            "riscv" | "risc-v" | "risc_v" => (Architecture::RiscV32, Architecture::RiscV64),
+            _ => return None,
+        };
+        Some(if bits == Some(64) { wide } else { narrow })
+    }
+
+    /// Canonical name or alias that fixes the bitness on its own
+    fn exact(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|architecture| architecture.as_str() == name)
+            .or_else(|| {
+                ARCHITECTURE_ALIASES
+                    .iter()
+                    .find(|(alias, _)| *alias == name)
+                    .map(|(_, architecture)| *architecture)
+            })
+    }
+
+    /// Canonical name stored on Binary nodes
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Architecture::X86 => "x86",
+            Architecture::X86_64 => "x86_64",
+            Architecture::Arm => "arm",
+            Architecture::Arm64 => "arm64",
+            Architecture::Mips => "mips",
+            Architecture::Mips64 => "mips64",
+            Architecture::Ppc => "ppc",
+            Architecture::Ppc64 => "ppc64",
+            Architecture::RiscV32 => "riscv32",
+            Architecture::RiscV64 => "riscv64",
+            Architecture::Unknown => "unknown",
+        }
+    }
+
+    /// Pointer width in bits
+    pub fn bits(self) -> Option<u32> {
+        match self {
+            Architecture::X86
+            | Architecture::Arm
+            | Architecture::Mips
+            | Architecture::Ppc
+            | Architecture::RiscV32 => Some(32),
+            Architecture::X86_64
+            | Architecture::Arm64
+            | Architecture::Mips64
+            | Architecture::Ppc64
+            | Architecture::RiscV64 => Some(64),
+            Architecture::Unknown => None,
+        }
+    }
+
+    /// Canonical name followed by every alias, lowercase; matches `arch` values
+    /// stored verbatim before normalization
+    pub fn names(self) -> Vec<&'static str> {
+        std::iter::once(self.as_str())
+            .chain(
+                ARCHITECTURE_ALIASES
+                    .iter()
+                    .filter(|(_, architecture)| *architecture == self)
+                    .map(|(alias, _)| *alias),
+            )
+            .collect()
+    }
+}
+
+impl clap::ValueEnum for Architecture {
+    fn value_variants<'a>() -> &'a [Self] {
+        &Self::ALL
+    }
+
+    fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
+        let names = self.names();
+        Some(clap::builder::PossibleValue::new(names[0]).aliases(names[1..].to_vec()))
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum FunctionType {
     /// Functions defined internally in the binary file
@@ -23,10 +227,65 @@ pub enum FunctionType {
     Thunk,
 }
 
+/// Way the loader or the runtime enters a function without a call from the binary
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryKind {
+    /// Address of entry point from the executable header
+    Entry,
+    /// `main`, `WinMain` or `wmain` as identified by the extractor
+    Main,
+    /// Exported or identified DllMain
+    DllMain,
+    /// TLS callback, run before the entry point
+    TlsCallback,
+    /// SEH, VEH or C++ exception handler
+    ExceptionHandler,
+}
+
+impl EntryKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EntryKind::Entry => "entry",
+            EntryKind::Main => "main",
+            EntryKind::DllMain => "dll_main",
+            EntryKind::TlsCallback => "tls_callback",
+            EntryKind::ExceptionHandler => "exception_handler",
+        }
+    }
+}
+
+impl FromStr for EntryKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace('-', "_").as_str() {
+            "entry" | "entry_point" | "start" | "oep" => Ok(EntryKind::Entry),
+            "main" | "winmain" | "wmain" | "wwinmain" => Ok(EntryKind::Main),
+            "dll_main" | "dllmain" => Ok(EntryKind::DllMain),
+            "tls_callback" | "tls" => Ok(EntryKind::TlsCallback),
+            "exception_handler" | "seh_handler" | "seh" | "veh" => Ok(EntryKind::ExceptionHandler),
+            _ => Err(format!("Unknown entry point kind: {}", s)),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Binary {
     /// Hash of the binary file, typically SHA-256
     pub hash: std::string::String,
+    /// MD5 hash (lowercase hex)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub md5: Option<std::string::String>,
+    /// SHA-1 hash (lowercase hex)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha1: Option<std::string::String>,
+    /// ssdeep fuzzy hash (`blocksize:hash1:hash2`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ssdeep: Option<std::string::String>,
+    /// TLSH fuzzy hash (uppercase hex)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tlsh: Option<std::string::String>,
     /// File name of the binary file
     pub filename: std::string::String,
     /// Full path to the binary file
@@ -36,50 +295,676 @@ pub struct Binary {
     /// Format type of the binary file
     pub format: BinaryFormat,
     /// Target architecture of the binary file
-    pub arch: std::string::String,
+    pub arch: Architecture,
+    /// Pointer width in bits, from the architecture or the extractor's bitness
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bitness: Option<u32>,
+    /// Image base the extractor reported; the binary's addresses are stored relative to it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_base: Option<std::string::String>,
+    /// Compiler that produced the binary (e.g., "MSVC 19.29")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compiler: Option<std::string::String>,
+    /// Packer or protector detected on the binary (e.g., "UPX")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub packer: Option<std::string::String>,
+    /// Malware family the sample was attributed to (e.g., "Emotet")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub family: Option<std::string::String>,
+    /// Linker version recorded in the file header
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub linker_version: Option<std::string::String>,
+    /// Debug information (PDB) path embedded in the binary
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pdb_path: Option<std::string::String>,
+    /// Build timestamp as reported by the extractor
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<std::string::String>,
+    /// `timestamp` as an ISO 8601 UTC datetime, when it could be interpreted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compiled_at: Option<std::string::String>,
+    /// When the binary was first imported into the graph
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_imported: Option<std::string::String>,
+    /// When the binary was last (re-)imported
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_updated: Option<std::string::String>,
+    /// PE header details, only present for PE samples
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pe_info: Option<PeInfo>,
+    /// Dynamic-linking details, only present for ELF samples
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub elf_info: Option<ElfInfo>,
+    /// Load commands and code signature, only present for Mach-O samples
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub macho_info: Option<MachOInfo>,
+    /// Weighted suspicious API score computed by `analyze risk`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub risk_score: Option<f64>,
+    /// File sections with their Shannon entropy
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sections: Vec<Section>,
+    /// Bytes appended after the last section
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overlay_size: Option<u64>,
+    /// High section entropy combined with few imports, see [`Binary::is_likely_packed`]
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub likely_packed: bool,
+}
+
+/// Entropy (bits per byte) from which a section is considered compressed or encrypted
+pub const HIGH_ENTROPY: f64 = 7.0;
+
+/// Import count up to which a high-entropy sample is flagged as likely packed
+pub const PACKED_MAX_IMPORTS: usize = 10;
+
+impl Binary {
+    /// Highest section entropy, if any section reports one
+    pub fn max_entropy(&self) -> Option<f64> {
+        self.sections
+            .iter()
+            .map(|section| section.entropy)
+            .max_by(|a, b| a.total_cmp(b))
+    }
+
+    /// Packers leave a high-entropy payload section and a stub import table
+    pub fn is_likely_packed(&self, import_count: usize) -> bool {
+        self.max_entropy().is_some_and(|e| e >= HIGH_ENTROPY) && import_count <= PACKED_MAX_IMPORTS
+    }
+
+    /// A virtual address as stored for this binary: relative to its image base, if any
+    pub fn relative_address(&self, address: u64) -> u64 {
+        match self.image_base.as_deref().and_then(uid::parse_address) {
+            Some(image_base) => uid::relative_address(address, image_base),
+            None => address,
+        }
+    }
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Section {
+    pub name: std::string::String,
+    /// Raw size in bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    /// Shannon entropy in bits per byte (0.0 to 8.0)
+    pub entropy: f64,
+}
+
+/// PE header properties used to pivot between related samples
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct PeInfo {
+    /// Import hash (lowercase hex MD5)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub imphash: Option<std::string::String>,
+    /// Hash of the Rich header (lowercase hex)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rich_header_hash: Option<std::string::String>,
+    /// Entry point address (hexadecimal format)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entry_point: Option<std::string::String>,
+    /// Subsystem (e.g., "WINDOWS_GUI", "WINDOWS_CUI")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subsystem: Option<std::string::String>,
+    /// Subject of the Authenticode signing certificate
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signer: Option<std::string::String>,
+}
+
+impl PeInfo {
+    pub fn is_empty(&self) -> bool {
+        *self == PeInfo::default()
+    }
+}
+
+/// ELF dynamic-linking properties
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ElfInfo {
+    /// Program interpreter (e.g., "/lib64/ld-linux-x86-64.so.2")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interpreter: Option<std::string::String>,
+    /// DT_SONAME of a shared object
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub soname: Option<std::string::String>,
+    /// DT_NEEDED libraries in load order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub needed: Vec<std::string::String>,
+    /// Required symbol versions as `library@VERSION` (e.g., "libc.so.6@GLIBC_2.34")
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub symbol_versions: Vec<std::string::String>,
+}
+
+impl ElfInfo {
+    pub fn is_empty(&self) -> bool {
+        *self == ElfInfo::default()
+    }
+}
+
+/// Mach-O load commands and code-signing identity
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct MachOInfo {
+    /// Load command names in file order (e.g., "LC_SEGMENT_64", "LC_MAIN")
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub load_commands: Vec<std::string::String>,
+    /// Team identifier from the code signature
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub team_id: Option<std::string::String>,
+    /// LC_RPATH search paths
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rpaths: Vec<std::string::String>,
+}
+
+impl MachOInfo {
+    pub fn is_empty(&self) -> bool {
+        *self == MachOInfo::default()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Function {
     /// Unique identifier for the function
     pub uid: std::string::String,
     /// Function name
     pub name: std::string::String,
+    /// Demangled C++/Rust form of `name`, if it is a mangled symbol
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub demangled_name: Option<std::string::String>,
+    /// Import name without the A/W suffix and with ordinals resolved, for API clustering
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub canonical_name: Option<std::string::String>,
     /// Function type
     pub r#type: FunctionType,
     /// Function address (hexadecimal format), may be None for imported functions
     pub address: Option<std::string::String>,
     /// Size of the function in bytes
     pub size: Option<u64>,
+    /// Instruction mnemonic counts reported by the extractor
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mnemonic_histogram: Option<MnemonicHistogram>,
+    /// Statically linked library code (e.g. CRT) recognized by FLIRT or a signature list
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_library: bool,
+    /// Name of the signature that identified the function as library code
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature_match: Option<std::string::String>,
+    /// Name given by an analyst; the extracted name is kept in `name`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub analyst_name: Option<std::string::String>,
+    /// Analyst comment
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<std::string::String>,
+    /// Set when the function is entered by the loader or runtime (entry point, TLS
+    /// callback, exception handler, ...)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entry_kind: Option<EntryKind>,
+    /// Decompiled code, cut to `max_pseudocode_length` characters
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pseudocode: Option<std::string::String>,
+    /// File holding the full decompiled code when `pseudocode` was cut
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pseudocode_blob: Option<std::string::String>,
+    /// Calling convention, return type and parameters recovered by the extractor
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prototype: Option<FunctionPrototype>,
+    /// Crypto algorithms whose constants the function uses (e.g. `aes`, `sha1`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub crypto: Vec<std::string::String>,
+    /// Hash of the function's bytes or normalized instructions given by the extractor
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<std::string::String>,
+    /// Blocks of the function outside its main body (IDA tail chunks, outlined code)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub chunks: Vec<FunctionChunk>,
+}
+
+/// Block of a function outside its main body, stored as a Chunk node linked by
+/// HAS_CHUNK; a tail shared by several functions is one node linked to each
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FunctionChunk {
+    /// Start address (hexadecimal format)
+    pub start: std::string::String,
+    /// Size in bytes
+    pub size: u64,
+}
+
+impl FunctionChunk {
+    pub fn contains(&self, address: u64) -> bool {
+        uid::parse_address(&self.start)
+            .is_some_and(|start| start <= address && address - start < self.size)
+    }
+}
+
+/// Function signature, stored on Function nodes as `calling_convention`, `return_type`,
+/// `param_types` and `param_names`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct FunctionPrototype {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub calling_convention: Option<std::string::String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub return_type: Option<std::string::String>,
+    #[serde(default)]
+    pub parameters: Vec<Parameter>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Parameter {
+    /// Empty when the extractor gives only the type
+    #[serde(default)]
+    pub name: std::string::String,
+    pub r#type: std::string::String,
+}
+
+impl FunctionPrototype {
+    /// C-style declaration, e.g. `BOOL __stdcall CopyFileW(LPCWSTR src, LPCWSTR dst)`
+    pub fn declaration(&self, name: &str) -> std::string::String {
+        let parameters: Vec<std::string::String> = self
+            .parameters
+            .iter()
+            .map(|p| format!("{} {}", p.r#type, p.name).trim().to_string())
+            .collect();
+        let head: Vec<&str> = [
+            Some(self.return_type.as_deref().unwrap_or("?")),
+            self.calling_convention.as_deref(),
+            Some(name),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        format!("{}({})", head.join(" "), parameters.join(", "))
+    }
+
+    /// Type name compared by `query functions --param-type`: lowercase, no whitespace,
+    /// so `wchar_t *` matches `WCHAR_T*`
+    pub fn normalize_type(type_name: &str) -> std::string::String {
+        type_name
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .flat_map(char::to_lowercase)
+            .collect()
+    }
+}
+
+/// Instruction mnemonic counts of a function
+///
+/// Stored on Function nodes as a compact `mnemonic:count` string sorted by mnemonic,
+/// e.g. `"call:3 mov:12 push:4"`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct MnemonicHistogram(pub std::collections::BTreeMap<std::string::String, u64>);
+
+impl MnemonicHistogram {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn encode(&self) -> std::string::String {
+        self.0
+            .iter()
+            .map(|(mnemonic, count)| format!("{}:{}", mnemonic, count))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Parse the stored form, skipping malformed entries
+    pub fn decode(encoded: &str) -> Self {
+        Self(
+            encoded
+                .split_whitespace()
+                .filter_map(|entry| {
+                    let (mnemonic, count) = entry.rsplit_once(':')?;
+                    Some((mnemonic.to_string(), count.parse().ok()?))
+                })
+                .collect(),
+        )
+    }
+
+    /// Cosine similarity of the two count vectors (0.0 to 1.0)
+    pub fn cosine_similarity(&self, other: &MnemonicHistogram) -> f64 {
+        let dot: f64 = self
+            .0
+            .iter()
+            .filter_map(|(mnemonic, count)| {
+                other.0.get(mnemonic).map(|o| *count as f64 * *o as f64)
+            })
+            .sum();
+        let norm = |h: &MnemonicHistogram| {
+            h.0.values()
+                .map(|c| (*c as f64).powi(2))
+                .sum::<f64>()
+                .sqrt()
+        };
+        let denominator = norm(self) * norm(other);
+
+        if denominator == 0.0 {
+            0.0
+        } else {
+            dot / denominator
+        }
+    }
 }
 
 impl Function {
     pub fn create_internal(binary_hash: &str, address: u64, name: &str, is_export: bool) -> Self {
-        let hex_addr = format!("0x{address:x}");
         Self {
-            uid: format!("{binary_hash}:{hex_addr}"),
+            uid: uid::function_uid(binary_hash, address),
             name: name.to_string(),
+            demangled_name: demangle(name),
+            canonical_name: None,
             r#type: if is_export {
                 FunctionType::Export
             } else {
                 FunctionType::Internal
             },
-            address: Some(hex_addr),
+            address: Some(uid::format_address(address)),
             size: None,
+            mnemonic_histogram: None,
+            is_library: false,
+            signature_match: None,
+            analyst_name: None,
+            comment: None,
+            entry_kind: None,
+            pseudocode: None,
+            pseudocode_blob: None,
+            prototype: None,
+            crypto: Vec::new(),
+            content_hash: None,
+            chunks: Vec::new(),
         }
     }
 
     pub fn create_import(library: &str, name: &str) -> Self {
-        let lib_normalized = library.to_lowercase();
         Self {
             // Global UID for imported APIs, shared across binaries.
-            uid: format!("imp:{lib_normalized}:{name}"),
+            uid: uid::import_uid(library, name),
             name: name.to_string(),
+            demangled_name: demangle(name),
+            canonical_name: Some(canonical_api_name(library, name)),
             r#type: FunctionType::Import,
             // Import address is binary-specific; store it on the Binary-[:CONTAINS] edge instead.
             address: None,
             size: None,
+            mnemonic_histogram: None,
+            is_library: false,
+            signature_match: None,
+            analyst_name: None,
+            comment: None,
+            entry_kind: None,
+            pseudocode: None,
+            pseudocode_blob: None,
+            prototype: None,
+            crypto: Vec::new(),
+            content_hash: None,
+            chunks: Vec::new(),
+        }
+    }
+
+    /// Start address parsed from `address`
+    pub fn start_address(&self) -> Option<u64> {
+        self.address.as_deref().and_then(uid::parse_address)
+    }
+
+    /// Function containing `address` and the offset into it
+    ///
+    /// Functions with a size match on their `[start, start + size)` range, then on their
+    /// chunks, where the offset is into the chunk. When the closest function starting at
+    /// or below `address` has no size, it is returned with `false` as an unbounded guess.
+    pub fn locate(functions: &[Function], address: u64) -> Option<(&Function, u64, bool)> {
+        let closest = functions
+            .iter()
+            .filter_map(|f| f.start_address().map(|start| (f, start)))
+            .filter(|(_, start)| *start <= address)
+            .max_by_key(|(_, start)| *start);
+        if let Some((function, start)) = closest {
+            if function.size.is_some_and(|size| address - start < size) {
+                return Some((function, address - start, true));
+            }
+        }
+
+        functions
+            .iter()
+            .filter_map(|f| Some((f, f.start_address()?, f.size?)))
+            .find(|(_, start, size)| *start <= address && address - start < *size)
+            .map(|(f, start, _)| (f, address - start, true))
+            .or_else(|| {
+                functions.iter().find_map(|f| {
+                    let chunk = f.chunk_containing(address)?;
+                    Some((f, address - uid::parse_address(&chunk.start)?, true))
+                })
+            })
+            .or_else(|| {
+                let (function, start) = closest.filter(|(f, _)| f.size.is_none())?;
+                Some((function, address - start, false))
+            })
+    }
+
+    /// Chunk of the function holding `address`
+    pub fn chunk_containing(&self, address: u64) -> Option<&FunctionChunk> {
+        self.chunks.iter().find(|chunk| chunk.contains(address))
+    }
+
+    /// Analyst or demangled name followed by the extracted name, or just the extracted name
+    pub fn display_name(&self) -> std::string::String {
+        match self.analyst_name.as_ref().or(self.demangled_name.as_ref()) {
+            Some(shown) if *shown != self.name => format!("{} ({})", shown, self.name),
+            _ => self.name.clone(),
+        }
+    }
+}
+
+/// Call-graph metrics computed by `analyze metrics` and stored on Function nodes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionMetrics {
+    pub uid: std::string::String,
+    pub name: std::string::String,
+    pub address: Option<std::string::String>,
+    /// Number of distinct functions calling this function
+    pub in_degree: usize,
+    /// Number of distinct functions called by this function
+    pub out_degree: usize,
+    /// Calls from the nearest entry point (export or function without callers)
+    pub entry_depth: Option<usize>,
+    /// Number of distinct imported APIs reachable through calls
+    pub reachable_apis: usize,
+}
+
+/// Function metric used to order `query functions` results (highest first)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum FunctionSort {
+    InDegree,
+    OutDegree,
+    EntryDepth,
+    ReachableApis,
+}
+
+impl FunctionSort {
+    /// Function node property holding the metric
+    pub fn property(self) -> &'static str {
+        match self {
+            FunctionSort::InDegree => "in_degree",
+            FunctionSort::OutDegree => "out_degree",
+            FunctionSort::EntryDepth => "entry_depth",
+            FunctionSort::ReachableApis => "reachable_apis",
+        }
+    }
+}
+
+/// Per-binary feature set compared by `analyze neighbors` and written by
+/// `export features`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum FeatureSpace {
+    /// Imported APIs by canonical name
+    Imports,
+    /// Distinct string values
+    Strings,
+    /// APIs called from the binary's functions by canonical name, including calls
+    /// resolved from API hashes and traces
+    Apis,
+    /// Content hashes of the binary's non-library functions
+    Functions,
+}
+
+impl FeatureSpace {
+    /// Cypher MATCH from the Binary bound to `binary` to its features as `x`
+    pub fn match_clause(self, binary: &str) -> String {
+        match self {
+            FeatureSpace::Imports => format!("MATCH ({}:Binary)-[:IMPORTS]->(x:Function)", binary),
+            FeatureSpace::Strings => {
+                format!("MATCH ({}:Binary)-[:CONTAINS_STRING]->(x:String)", binary)
+            }
+            FeatureSpace::Apis => format!(
+                "MATCH ({}:Binary)-[:CONTAINS]->(:Function)-[:CALLS]->(x:Function {{type: 'Import'}})",
+                binary
+            ),
+            FeatureSpace::Functions => format!(
+                "MATCH ({}:Binary)-[:CONTAINS]->(x:Function)
+                 WHERE x.content_hash IS NOT NULL AND NOT coalesce(x.is_library, false)",
+                binary
+            ),
         }
     }
+
+    /// Cypher expression identifying the feature `x` across binaries
+    pub fn key(self) -> &'static str {
+        match self {
+            FeatureSpace::Imports | FeatureSpace::Apis => "coalesce(x.canonical_name, x.name)",
+            FeatureSpace::Strings => "x.uid",
+            FeatureSpace::Functions => "x.content_hash",
+        }
+    }
+
+    /// Cypher expression naming the feature `x` in exports
+    pub fn label(self) -> &'static str {
+        match self {
+            FeatureSpace::Strings => "x.value",
+            _ => self.key(),
+        }
+    }
+}
+
+/// GDS node embedding algorithm run by `analyze embeddings`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum EmbeddingAlgorithm {
+    /// Fast random projection
+    #[value(name = "fastrp")]
+    FastRp,
+    Node2vec,
+}
+
+impl EmbeddingAlgorithm {
+    /// GDS procedure streaming the embeddings of a projected graph
+    pub fn procedure(self) -> &'static str {
+        match self {
+            EmbeddingAlgorithm::FastRp => "gds.fastRP.stream",
+            EmbeddingAlgorithm::Node2vec => "gds.node2vec.stream",
+        }
+    }
+
+    /// Stored as `embedding_algorithm` next to the vector
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EmbeddingAlgorithm::FastRp => "fastrp",
+            EmbeddingAlgorithm::Node2vec => "node2vec",
+        }
+    }
+}
+
+/// Outcome of `analyze embeddings`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingRun {
+    /// Binaries, import functions and strings in the projection
+    pub nodes: u64,
+    pub relationships: u64,
+    /// Binaries whose `embedding` was written
+    pub binaries: u64,
+}
+
+/// Embedding vector stored on a Binary node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BinaryEmbedding {
+    pub binary: String,
+    pub hash: String,
+    pub algorithm: Option<String>,
+    pub embedding: Vec<f64>,
+}
+
+impl BinaryEmbedding {
+    /// Cosine similarity of two embeddings, 0.0 when their dimensions differ
+    pub fn cosine_similarity(&self, other: &BinaryEmbedding) -> f64 {
+        if self.embedding.len() != other.embedding.len() {
+            return 0.0;
+        }
+        let dot: f64 = self
+            .embedding
+            .iter()
+            .zip(&other.embedding)
+            .map(|(a, b)| a * b)
+            .sum();
+        let norm = |v: &[f64]| v.iter().map(|x| x * x).sum::<f64>().sqrt();
+        let norms = norm(&self.embedding) * norm(&other.embedding);
+        if norms == 0.0 {
+            0.0
+        } else {
+            dot / norms
+        }
+    }
+}
+
+/// Non-zero cell of the binary × feature matrix written by `export features`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureCell {
+    /// SHA-256 of the binary
+    pub binary: String,
+    pub feature: String,
+    /// Edges linking the binary to the feature, e.g. calling functions for `apis`
+    pub value: i64,
+}
+
+/// Resource blob carried by a binary (e.g. a PE resource directory entry)
+///
+/// Resources are keyed by the SHA-256 of their content, so identical blobs shipped
+/// by different samples share one node.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Resource {
+    /// Unique identifier, `res:<sha256>`
+    pub uid: String,
+    /// SHA-256 of the resource content
+    pub sha256: String,
+    /// Resource type (e.g. RT_RCDATA, RT_ICON)
+    pub r#type: String,
+    /// Resource name or ID within the binary
+    pub name: Option<String>,
+    /// Language identifier
+    pub language: Option<String>,
+    /// Size of the resource in bytes
+    pub size: u64,
+    /// File type detected in the content (e.g. "PE32 executable")
+    pub file_type: Option<String>,
+}
+
+impl Resource {
+    pub fn new(sha256: &str, r#type: &str, size: u64) -> Self {
+        let sha256 = sha256.trim().to_lowercase();
+        Self {
+            uid: uid::resource_uid(&sha256),
+            sha256,
+            r#type: r#type.to_string(),
+            name: None,
+            language: None,
+            size,
+            file_type: None,
+        }
+    }
+
+    /// Whether the content was identified as a PE image (PE, PE32, PE32+)
+    pub fn is_embedded_pe(&self) -> bool {
+        self.file_type.as_deref().is_some_and(|file_type| {
+            file_type
+                .split(|c: char| c.is_whitespace() || c == ',' || c == '(' || c == ')')
+                .any(|token| {
+                    let token = token.to_uppercase();
+                    matches!(token.as_str(), "PE" | "PE32" | "PE32+" | "PE64")
+                })
+        })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -88,6 +973,15 @@ pub struct StringNode {
     pub value: String,
     /// Unique identifier of the string, generated based on content hash
     pub uid: String,
+    /// Length of the full content in characters
+    #[serde(default)]
+    pub length: usize,
+    /// `value` only holds the beginning of the content, see [`StringNode::truncate`]
+    #[serde(default)]
+    pub truncated: bool,
+    /// File holding the full content of a truncated string
+    #[serde(default)]
+    pub blob: Option<String>,
 }
 
 impl StringNode {
@@ -96,10 +990,28 @@ impl StringNode {
         let normalized_value = value.trim_end_matches('\0').to_string();
         let uid = uid::generate_string_uid(&normalized_value);
         Self {
+            length: normalized_value.chars().count(),
             value: normalized_value,
             uid,
+            truncated: false,
+            blob: None,
         }
     }
+
+    /// SHA-256 of the full content, also when `value` is truncated
+    pub fn content_hash(&self) -> &str {
+        self.uid.trim_start_matches("str:")
+    }
+
+    /// Keep only the first `max_length` characters and return the full content if
+    /// anything was cut; the UID still identifies the full content
+    pub fn truncate(&mut self, max_length: usize) -> Option<String> {
+        let (end, _) = self.value.char_indices().nth(max_length)?;
+        let full = std::mem::take(&mut self.value);
+        self.value = full[..end].to_string();
+        self.truncated = true;
+        Some(full)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -108,13 +1020,62 @@ pub struct StringOccurrence {
     pub string_uid: String,
     /// Address where the string is located in the binary (hexadecimal format)
     pub address: Option<String>,
+    /// Encoding of this copy, e.g. `ascii` or `utf16le`; the String node holds the
+    /// decoded text, shared by narrow and wide copies
+    pub encoding: Option<String>,
+    /// How the extractor obtained the string
+    pub source: Option<StringSource>,
+    /// Address of the function that decodes the string (normalized)
+    pub decrypted_by: Option<String>,
 }
 
 impl StringOccurrence {
-    pub fn new(string_uid: String, address: Option<String>) -> Self {
+    pub fn new(string_uid: String, address: Option<String>, encoding: Option<String>) -> Self {
         Self {
             string_uid,
             address,
+            encoding,
+            source: None,
+            decrypted_by: None,
+        }
+    }
+}
+
+/// Where an extracted string comes from
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StringSource {
+    /// Present as-is in the file
+    Static,
+    /// Built on the stack one character or word at a time
+    Stack,
+    /// Recovered by emulating or reimplementing a decoding routine
+    Decrypted,
+    /// Observed in memory while the sample ran
+    Dynamic,
+}
+
+impl StringSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            StringSource::Static => "static",
+            StringSource::Stack => "stack",
+            StringSource::Decrypted => "decrypted",
+            StringSource::Dynamic => "dynamic",
+        }
+    }
+}
+
+impl FromStr for StringSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "static" => Ok(StringSource::Static),
+            "stack" | "stackstring" | "stack_string" | "tight" => Ok(StringSource::Stack),
+            "decrypted" | "decoded" => Ok(StringSource::Decrypted),
+            "dynamic" | "runtime" => Ok(StringSource::Dynamic),
+            _ => Err(format!("unknown string source '{}'", s)),
         }
     }
 }
@@ -127,6 +1088,554 @@ pub struct StringSearchHit {
     pub sample_count: i64,
 }
 
+/// Node matched by `query search`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub kind: SearchKind,
+    /// Binary filename, function name, string value or library name
+    pub name: String,
+    /// Binary hash, function or string UID, or library name
+    pub key: String,
+    /// Binaries containing the function or string, or importing the library
+    pub samples: i64,
+    /// Binary format and architecture, function type, or string relevance score
+    pub detail: String,
+}
+
+/// Node kinds searched by `query search`, in the order results are grouped
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchKind {
+    Binary,
+    Function,
+    String,
+    Library,
+}
+
+impl SearchKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SearchKind::Binary => "binary",
+            SearchKind::Function => "function",
+            SearchKind::String => "string",
+            SearchKind::Library => "library",
+        }
+    }
+}
+
+/// Function whose decompiled code matched `query pseudocode`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PseudocodeHit {
+    pub uid: String,
+    pub name: String,
+    pub address: Option<String>,
+    pub binary: String,
+    pub score: f64,
+    /// 1-based number of the first line containing a search term
+    pub line: Option<usize>,
+    /// That line, trimmed
+    pub snippet: String,
+    #[serde(skip)]
+    pub pseudocode: String,
+}
+
+impl PseudocodeHit {
+    /// Point `line` and `snippet` at the first line containing one of `terms`
+    /// (case-insensitive), or at the first non-empty line
+    pub fn locate(&mut self, terms: &[String]) {
+        let terms: Vec<String> = terms.iter().map(|t| t.to_lowercase()).collect();
+        let lines = || self.pseudocode.lines().enumerate();
+        let found = lines()
+            .find(|(_, line)| {
+                let line = line.to_lowercase();
+                terms.iter().any(|term| line.contains(term))
+            })
+            .or_else(|| lines().find(|(_, line)| !line.trim().is_empty()));
+        if let Some((index, line)) = found {
+            self.line = Some(index + 1);
+            self.snippet = line.trim().to_string();
+        }
+    }
+}
+
+/// Kind of network indicator node
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum IndicatorKind {
+    Domain,
+    IpAddress,
+    Url,
+}
+
+impl IndicatorKind {
+    /// Neo4j node label
+    pub fn label(self) -> &'static str {
+        match self {
+            IndicatorKind::Domain => "Domain",
+            IndicatorKind::IpAddress => "IPAddress",
+            IndicatorKind::Url => "URL",
+        }
+    }
+}
+
+/// Domain, IP address or URL observed in a sample
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct NetworkIndicator {
+    pub kind: IndicatorKind,
+    /// Normalized value (domains lowercased, URLs trimmed)
+    pub value: String,
+}
+
+impl NetworkIndicator {
+    pub fn new(kind: IndicatorKind, value: impl Into<String>) -> Self {
+        Self {
+            kind,
+            value: value.into(),
+        }
+    }
+}
+
+/// Kind of kernel object a sample creates or opens by name
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ObjectKind {
+    Mutex,
+    /// Named pipe
+    Pipe,
+    Event,
+    Semaphore,
+}
+
+impl ObjectKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ObjectKind::Mutex => "mutex",
+            ObjectKind::Pipe => "pipe",
+            ObjectKind::Event => "event",
+            ObjectKind::Semaphore => "semaphore",
+        }
+    }
+}
+
+/// Named mutex, pipe, event or semaphore, shared by every sample using the same name
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct NamedObject {
+    pub kind: ObjectKind,
+    /// Object name; pipes without their `\\.\pipe\` prefix and lowercased
+    pub name: String,
+}
+
+impl NamedObject {
+    pub fn new(kind: ObjectKind, name: impl Into<String>) -> Self {
+        Self {
+            kind,
+            name: name.into(),
+        }
+    }
+
+    pub fn uid(&self) -> String {
+        uid::named_object_uid(self.kind, &self.name)
+    }
+}
+
+/// Binary linked to a named object by `query objects`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObjectHit {
+    pub kind: String,
+    pub name: String,
+    /// `strings` (classified string) or `sandbox` (observed at runtime)
+    pub source: String,
+    pub binary: String,
+    pub hash: String,
+    /// Number of binaries using the object
+    pub samples: i64,
+}
+
+/// Function tagged with crypto algorithms, listed by `query crypto`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CryptoHit {
+    pub algorithms: Vec<std::string::String>,
+    pub name: std::string::String,
+    pub address: std::string::String,
+    pub uid: std::string::String,
+    pub binary: std::string::String,
+}
+
+/// C++ virtual function table, linked to the functions in its slots by HAS_SLOT
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VTable {
+    pub uid: std::string::String,
+    /// Address of the first slot (hexadecimal format)
+    pub address: std::string::String,
+    /// Class name as recovered from RTTI or symbols
+    pub class_name: Option<std::string::String>,
+    /// Function UIDs by slot index; slots that matched no function are left out
+    pub slots: Vec<(usize, std::string::String)>,
+    /// UIDs of the vtables of the base classes
+    pub bases: Vec<std::string::String>,
+}
+
+impl VTable {
+    pub fn new(binary_hash: &str, address: u64, class_name: Option<std::string::String>) -> Self {
+        Self {
+            uid: uid::vtable_uid(binary_hash, address),
+            address: uid::format_address(address),
+            class_name,
+            slots: Vec::new(),
+            bases: Vec::new(),
+        }
+    }
+}
+
+/// Version of the annotation file format written by `export annotations`
+pub const ANNOTATIONS_VERSION: u32 = 1;
+
+/// Analyst-added properties of the graph, kept apart from extractor data so they can
+/// be restored after the database is rebuilt
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Annotations {
+    #[serde(default = "annotations_version")]
+    pub version: u32,
+    #[serde(default)]
+    pub functions: Vec<FunctionAnnotation>,
+    #[serde(default)]
+    pub binaries: Vec<BinaryAnnotation>,
+}
+
+fn annotations_version() -> u32 {
+    ANNOTATIONS_VERSION
+}
+
+/// Analyst name, comment and recovered symbol of one function
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FunctionAnnotation {
+    pub uid: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub analyst_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    /// Name set by `import symbols` in place of the extracted one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symbol_name: Option<String>,
+}
+
+/// Family attribution of one binary
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BinaryAnnotation {
+    pub hash: String,
+    pub family: String,
+}
+
+/// Suspicious API imported by a binary, with the functions calling it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskContribution {
+    pub category: String,
+    pub api: String,
+    pub weight: f64,
+    /// Calling functions as `name @ offset`
+    pub call_sites: Vec<String>,
+}
+
+/// Result of `analyze risk` for one binary
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskReport {
+    pub binary: String,
+    pub hash: String,
+    pub score: f64,
+    pub contributions: Vec<RiskContribution>,
+}
+
+/// API of a library with its use across the corpus, listed by `query library`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibraryApiUse {
+    pub library: String,
+    pub api: String,
+    /// Binaries importing the API
+    pub binaries: u64,
+    /// Functions calling the API, over all binaries
+    pub callers: u64,
+    /// `binary: function` of some of the callers
+    pub examples: Vec<String>,
+}
+
+/// Exported and imported API names of one binary, compared by `analyze trade`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ApiSurface {
+    pub exports: BTreeSet<String>,
+    /// `library!name`, library lowercased
+    pub imports: BTreeSet<String>,
+}
+
+/// Exports and imports added or removed from one version of a binary to the next
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SurfaceDiff {
+    pub exports_added: Vec<String>,
+    pub exports_removed: Vec<String>,
+    pub imports_added: Vec<String>,
+    pub imports_removed: Vec<String>,
+}
+
+impl ApiSurface {
+    /// Changes from `self` (older) to `newer`, each list sorted by name
+    pub fn diff(&self, newer: &ApiSurface) -> SurfaceDiff {
+        SurfaceDiff {
+            exports_added: newer.exports.difference(&self.exports).cloned().collect(),
+            exports_removed: self.exports.difference(&newer.exports).cloned().collect(),
+            imports_added: newer.imports.difference(&self.imports).cloned().collect(),
+            imports_removed: self.imports.difference(&newer.imports).cloned().collect(),
+        }
+    }
+}
+
+impl SurfaceDiff {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Binary ranked by feature-set similarity to a reference binary
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NeighborHit {
+    pub binary: String,
+    pub hash: String,
+    /// Jaccard similarity of the two feature sets (0.0 to 1.0)
+    pub similarity: f64,
+    /// Features both binaries have
+    pub shared: u64,
+    /// Features of this binary
+    pub features: u64,
+}
+
+/// Sizes of one binary read for `analyze corpus-stats`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BinaryCounts {
+    pub hash: String,
+    pub format: String,
+    pub arch: String,
+    pub functions: u64,
+    pub strings: u64,
+    /// Imported functions
+    pub imports: u64,
+}
+
+/// Spread of one per-binary count over the corpus
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Distribution {
+    pub metric: String,
+    pub min: u64,
+    pub p25: u64,
+    pub median: u64,
+    pub p75: u64,
+    pub p90: u64,
+    pub max: u64,
+    pub mean: f64,
+    /// Binaries per order of magnitude (`0`, `1-9`, `10-99`, ...) from `min` to `max`
+    pub histogram: Vec<HistogramBucket>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistogramBucket {
+    pub range: String,
+    pub binaries: u64,
+}
+
+impl Distribution {
+    /// Nearest-rank percentiles of `values`, all zero for an empty corpus
+    pub fn of(metric: &str, values: &[u64]) -> Self {
+        let mut sorted = values.to_vec();
+        sorted.sort_unstable();
+        let percentile = |p: usize| {
+            if sorted.is_empty() {
+                0
+            } else {
+                sorted[((sorted.len() * p).div_ceil(100)).clamp(1, sorted.len()) - 1]
+            }
+        };
+
+        // Bucket 0 holds zeros, bucket n values with n digits
+        let bucket = |value: u64| {
+            value
+                .checked_ilog10()
+                .map_or(0, |digits| digits as usize + 1)
+        };
+        let mut histogram = Vec::new();
+        if let (Some(&min), Some(&max)) = (sorted.first(), sorted.last()) {
+            for index in bucket(min)..=bucket(max) {
+                let range = match index {
+                    0 => "0".to_string(),
+                    _ => format!(
+                        "{}-{}",
+                        10u64.pow(index as u32 - 1),
+                        10u64.pow(index as u32) - 1
+                    ),
+                };
+                histogram.push(HistogramBucket {
+                    range,
+                    binaries: sorted.iter().filter(|&&v| bucket(v) == index).count() as u64,
+                });
+            }
+        }
+
+        Self {
+            metric: metric.to_string(),
+            min: sorted.first().copied().unwrap_or(0),
+            p25: percentile(25),
+            median: percentile(50),
+            p75: percentile(75),
+            p90: percentile(90),
+            max: sorted.last().copied().unwrap_or(0),
+            mean: if sorted.is_empty() {
+                0.0
+            } else {
+                sorted.iter().sum::<u64>() as f64 / sorted.len() as f64
+            },
+            histogram,
+        }
+    }
+}
+
+/// Binaries sharing a format, architecture or imported library
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorpusShare {
+    pub name: String,
+    pub binaries: u64,
+    /// Fraction of all binaries (0.0 to 1.0)
+    pub share: f64,
+}
+
+/// Result of `analyze corpus-stats`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorpusStats {
+    pub binaries: u64,
+    pub distributions: Vec<Distribution>,
+    pub formats: Vec<CorpusShare>,
+    pub architectures: Vec<CorpusShare>,
+    pub top_libraries: Vec<CorpusShare>,
+}
+
+impl CorpusStats {
+    /// `libraries` are `(name, importing binaries)`, most imported first
+    pub fn new(counts: &[BinaryCounts], libraries: Vec<(String, u64)>) -> Self {
+        let total = counts.len() as u64;
+        let share = |name: String, binaries: u64| CorpusShare {
+            name,
+            binaries,
+            share: if total == 0 {
+                0.0
+            } else {
+                binaries as f64 / total as f64
+            },
+        };
+        let breakdown = |key: fn(&BinaryCounts) -> &str| {
+            let mut groups: BTreeMap<&str, u64> = BTreeMap::new();
+            for binary in counts {
+                let name = key(binary);
+                *groups
+                    .entry(if name.is_empty() { "unknown" } else { name })
+                    .or_default() += 1;
+            }
+            let mut shares: Vec<CorpusShare> = groups
+                .into_iter()
+                .map(|(name, binaries)| share(name.to_string(), binaries))
+                .collect();
+            shares.sort_by_key(|share| std::cmp::Reverse(share.binaries));
+            shares
+        };
+        let values = |value: fn(&BinaryCounts) -> u64| counts.iter().map(value).collect::<Vec<_>>();
+
+        Self {
+            binaries: total,
+            distributions: vec![
+                Distribution::of("functions", &values(|b| b.functions)),
+                Distribution::of("strings", &values(|b| b.strings)),
+                Distribution::of("imports", &values(|b| b.imports)),
+            ],
+            formats: breakdown(|b| &b.format),
+            architectures: breakdown(|b| &b.arch),
+            top_libraries: libraries
+                .into_iter()
+                .map(|(name, binaries)| share(name, binaries))
+                .collect(),
+        }
+    }
+}
+
+/// Imported APIs of a binary from one library
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportGroup {
+    pub library: String,
+    pub apis: Vec<String>,
+}
+
+/// String of a binary with the number of its functions referencing it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StringHit {
+    pub value: String,
+    pub references: u64,
+}
+
+/// CALLS edge between two functions of a binary, with the node kind of both ends
+///
+/// The kind is the function type (`Internal`, `Import`, ...) or `Library` for
+/// statically linked library code.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphEdge {
+    pub from_uid: String,
+    pub from_name: String,
+    pub from_kind: String,
+    pub to_uid: String,
+    pub to_name: String,
+    pub to_kind: String,
+}
+
+/// CALLS edge of a binary's call graph with its stored properties, for `export networkx`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CallGraphLink {
+    pub from_uid: String,
+    pub to_uid: String,
+    /// Number of call sites
+    pub count: u64,
+    pub offsets: Vec<String>,
+    pub call_type: Option<String>,
+    /// `static`, `dynamic` or `heuristic`
+    pub source: Option<String>,
+    pub confidence: f64,
+}
+
+/// Contents of the HTML triage report generated by `report`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriageReport {
+    pub binary: Binary,
+    pub imports: Vec<ImportGroup>,
+    pub strings: Vec<StringHit>,
+    pub risk: RiskReport,
+    pub call_graph: Vec<GraphEdge>,
+}
+
+/// Binary importing APIs also imported by a reference binary
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedApiHit {
+    pub binary: String,
+    pub hash: String,
+    /// Number of shared canonical API names
+    pub shared: u64,
+    /// Fraction of the reference binary's APIs that are shared (0.0 to 1.0)
+    pub coverage: f64,
+    /// Shared canonical API names
+    pub apis: Vec<String>,
+}
+
+/// Binary linked to a network indicator
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndicatorHit {
+    /// Node label of the indicator (Domain, IPAddress or URL)
+    pub kind: String,
+    pub value: String,
+    /// CONTACTS (sandbox) or REFERENCES_IOC (strings)
+    pub relation: String,
+    pub binary: String,
+    pub hash: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Library {
     /// Library name, uniformly converted to lowercase
@@ -162,3 +1671,307 @@ pub struct Export {
 }
 
 impl Export {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_surface_diff() {
+        let older = ApiSurface {
+            exports: BTreeSet::from(["Init".to_string(), "Legacy".to_string()]),
+            imports: BTreeSet::from(["kernel32.dll!CreateFileA".to_string()]),
+        };
+        let newer = ApiSurface {
+            exports: BTreeSet::from(["Init".to_string(), "Update".to_string()]),
+            imports: BTreeSet::from([
+                "kernel32.dll!CreateFileA".to_string(),
+                "winhttp.dll!WinHttpOpen".to_string(),
+            ]),
+        };
+
+        let diff = older.diff(&newer);
+        assert_eq!(diff.exports_added, vec!["Update"]);
+        assert_eq!(diff.exports_removed, vec!["Legacy"]);
+        assert_eq!(diff.imports_added, vec!["winhttp.dll!WinHttpOpen"]);
+        assert!(diff.imports_removed.is_empty());
+        assert!(older.diff(&older).is_empty());
+    }
+
+    #[test]
+    fn test_embedding_cosine_similarity() {
+        let embedding = |values: &[f64]| BinaryEmbedding {
+            binary: String::new(),
+            hash: String::new(),
+            algorithm: None,
+            embedding: values.to_vec(),
+        };
+        let a = embedding(&[1.0, 0.0, 1.0]);
+        assert!((a.cosine_similarity(&embedding(&[2.0, 0.0, 2.0])) - 1.0).abs() < 1e-9);
+        assert_eq!(a.cosine_similarity(&embedding(&[0.0, 1.0, 0.0])), 0.0);
+        assert_eq!(a.cosine_similarity(&embedding(&[1.0, 0.0])), 0.0);
+        assert_eq!(a.cosine_similarity(&embedding(&[0.0, 0.0, 0.0])), 0.0);
+    }
+
+    #[test]
+    fn test_corpus_stats() {
+        let distribution = Distribution::of("functions", &[0, 5, 40, 12, 900]);
+        assert_eq!(distribution.median, 12);
+        assert_eq!(distribution.p25, 5);
+        assert_eq!(distribution.p90, 900);
+        assert_eq!(distribution.mean, 191.4);
+        let buckets: Vec<(&str, u64)> = distribution
+            .histogram
+            .iter()
+            .map(|bucket| (bucket.range.as_str(), bucket.binaries))
+            .collect();
+        assert_eq!(
+            buckets,
+            vec![("0", 1), ("1-9", 1), ("10-99", 2), ("100-999", 1)]
+        );
+        assert!(Distribution::of("strings", &[]).histogram.is_empty());
+
+        let counts = |format: &str| BinaryCounts {
+            hash: String::new(),
+            format: format.to_string(),
+            arch: String::new(),
+            functions: 1,
+            strings: 0,
+            imports: 0,
+        };
+        let stats = CorpusStats::new(&[counts("ELF"), counts("PE"), counts("PE")], Vec::new());
+        assert_eq!(stats.formats[0].name, "PE");
+        assert_eq!(stats.formats[0].binaries, 2);
+        assert_eq!(stats.architectures[0].name, "unknown");
+    }
+
+    #[test]
+    fn test_architecture_aliases() {
+        for name in ["AMD64", "x86-64", "x64", "x86_64", "x86:LE:64:default"] {
+            assert_eq!(
+                Architecture::parse(name, None),
+                Architecture::X86_64,
+                "{}",
+                name
+            );
+        }
+        assert_eq!(
+            Architecture::parse("metapc", Some(64)),
+            Architecture::X86_64
+        );
+        assert_eq!(Architecture::parse("x86", Some(32)), Architecture::X86);
+        assert_eq!(Architecture::parse("i686", None), Architecture::X86);
+        assert_eq!(Architecture::parse("AARCH64", None), Architecture::Arm64);
+        assert_eq!(Architecture::parse("armhf", None), Architecture::Arm);
+        assert_eq!(Architecture::parse("mips_32", None), Architecture::Mips);
+        assert_eq!(Architecture::parse("arm_64", None), Architecture::Arm64);
+        assert_eq!(
+            Architecture::parse("riscv", Some(64)),
+            Architecture::RiscV64
+        );
+        assert_eq!(Architecture::parse("sparc", None), Architecture::Unknown);
+        assert_eq!(Architecture::X86_64.bits(), Some(64));
+        assert!(Architecture::X86_64.names().contains(&"amd64"));
+    }
+
+    fn histogram(counts: &[(&str, u64)]) -> MnemonicHistogram {
+        MnemonicHistogram(counts.iter().map(|(m, c)| (m.to_string(), *c)).collect())
+    }
+
+    #[test]
+    fn test_resource_embedded_pe_detection() {
+        let mut resource = Resource::new("ABCDEF", "RT_RCDATA", 4096);
+        assert_eq!(resource.uid, "res:abcdef");
+        assert!(!resource.is_embedded_pe());
+
+        for file_type in [
+            "PE32 executable (GUI) Intel 80386",
+            "PE32+ executable",
+            "pe",
+        ] {
+            resource.file_type = Some(file_type.to_string());
+            assert!(resource.is_embedded_pe(), "{}", file_type);
+        }
+
+        resource.file_type = Some("PEM certificate".to_string());
+        assert!(!resource.is_embedded_pe());
+    }
+
+    #[test]
+    fn test_locate_address() {
+        let sized = |address: u64, size: Option<u64>| {
+            let mut function = Function::create_internal("abc", address, "f", false);
+            function.size = size;
+            function
+        };
+        let functions = vec![
+            sized(0x1000, Some(0x100)),
+            sized(0x1040, Some(0x20)),
+            sized(0x2000, None),
+        ];
+
+        let (function, offset, bounded) = Function::locate(&functions, 0x1050).unwrap();
+        assert_eq!(
+            (function.uid.as_str(), offset, bounded),
+            ("abc:0x1040", 0x10, true)
+        );
+
+        // Past the inner function but still inside the outer one
+        let (function, offset, _) = Function::locate(&functions, 0x10f0).unwrap();
+        assert_eq!((function.uid.as_str(), offset), ("abc:0x1000", 0xf0));
+
+        assert!(Function::locate(&functions, 0x1100).is_none());
+        assert!(Function::locate(&functions, 0xfff).is_none());
+
+        let (function, offset, bounded) = Function::locate(&functions, 0x2400).unwrap();
+        assert_eq!(
+            (function.uid.as_str(), offset, bounded),
+            ("abc:0x2000", 0x400, false)
+        );
+
+        // A tail chunk past the unsized function belongs to its owner
+        let mut functions = functions;
+        functions[0].chunks.push(FunctionChunk {
+            start: "0x3000".to_string(),
+            size: 0x40,
+        });
+        let (function, offset, bounded) = Function::locate(&functions, 0x3010).unwrap();
+        assert_eq!(
+            (function.uid.as_str(), offset, bounded),
+            ("abc:0x1000", 0x10, true)
+        );
+        assert!(functions[0].chunk_containing(0x3040).is_none());
+    }
+
+    #[test]
+    fn test_likely_packed_heuristic() {
+        let mut binary: Binary = serde_json::from_value(serde_json::json!({
+            "hash": "abc",
+            "filename": "sample.exe",
+            "file_path": "",
+            "file_size": 0,
+            "format": "PE",
+            "arch": "x86",
+            "sections": [
+                {"name": ".text", "size": 4096, "entropy": 6.1},
+                {"name": "UPX1", "entropy": 7.9}
+            ]
+        }))
+        .unwrap();
+
+        assert_eq!(binary.max_entropy(), Some(7.9));
+        assert!(binary.is_likely_packed(3));
+        assert!(!binary.is_likely_packed(120));
+
+        binary.sections.truncate(1);
+        assert!(!binary.is_likely_packed(3));
+    }
+
+    #[test]
+    fn test_mnemonic_histogram_roundtrip() {
+        let h = histogram(&[("push", 4), ("mov", 12), ("call", 3)]);
+        assert_eq!(h.encode(), "call:3 mov:12 push:4");
+        assert_eq!(MnemonicHistogram::decode(&h.encode()), h);
+        assert!(MnemonicHistogram::decode("").is_empty());
+    }
+
+    #[test]
+    fn test_mnemonic_cosine_similarity() {
+        let a = histogram(&[("mov", 10), ("call", 2)]);
+        let b = histogram(&[("mov", 20), ("call", 4)]);
+        let c = histogram(&[("xor", 5)]);
+
+        assert!((a.cosine_similarity(&b) - 1.0).abs() < 1e-9);
+        assert_eq!(a.cosine_similarity(&c), 0.0);
+        assert_eq!(a.cosine_similarity(&MnemonicHistogram::default()), 0.0);
+    }
+
+    #[test]
+    fn test_string_truncation() {
+        let mut string = StringNode::new(format!("{}\0", "ab€".repeat(4)));
+        let uid = string.uid.clone();
+        assert_eq!(string.length, 12);
+        assert_eq!(string.truncate(12), None);
+
+        assert_eq!(string.truncate(4).as_deref(), Some("ab€ab€ab€ab€"));
+        assert_eq!(string.value, "ab€a");
+        assert!(string.truncated);
+        assert_eq!(string.length, 12);
+        assert_eq!(string.uid, uid);
+        assert_eq!(string.content_hash().len(), 64);
+    }
+
+    #[test]
+    fn test_string_source_names() {
+        assert_eq!("Stack".parse(), Ok(StringSource::Stack));
+        assert_eq!("decoded".parse(), Ok(StringSource::Decrypted));
+        assert_eq!(StringSource::Dynamic.as_str(), "dynamic");
+        assert!("xor".parse::<StringSource>().is_err());
+    }
+
+    #[test]
+    fn test_prototype_declaration() {
+        let prototype = FunctionPrototype {
+            calling_convention: Some("__stdcall".to_string()),
+            return_type: Some("BOOL".to_string()),
+            parameters: vec![
+                Parameter {
+                    name: "src".to_string(),
+                    r#type: "wchar_t *".to_string(),
+                },
+                Parameter {
+                    name: String::new(),
+                    r#type: "int".to_string(),
+                },
+            ],
+        };
+        assert_eq!(
+            prototype.declaration("CopyThing"),
+            "BOOL __stdcall CopyThing(wchar_t * src, int)"
+        );
+        assert_eq!(
+            FunctionPrototype::normalize_type(" Wchar_t * "),
+            FunctionPrototype::normalize_type("wchar_t*")
+        );
+        assert_eq!(
+            FunctionPrototype::default().declaration("f"),
+            "? f()".to_string()
+        );
+    }
+
+    #[test]
+    fn test_pseudocode_hit_locate() {
+        let mut hit = PseudocodeHit {
+            uid: "abc:0x401000".to_string(),
+            name: "connect_c2".to_string(),
+            address: None,
+            binary: "sample.exe".to_string(),
+            score: 1.0,
+            line: None,
+            snippet: String::new(),
+            pseudocode: "\nint connect_c2() {\n  s = WSASocketA(2, 1, 6, 0, 0, 0);\n}".to_string(),
+        };
+
+        hit.locate(&["wsasocket".to_string()]);
+        assert_eq!(hit.line, Some(3));
+        assert_eq!(hit.snippet, "s = WSASocketA(2, 1, 6, 0, 0, 0);");
+
+        hit.locate(&["recv".to_string()]);
+        assert_eq!(hit.line, Some(2));
+    }
+
+    #[test]
+    fn test_annotations_defaults() {
+        let annotations: Annotations = serde_json::from_str(
+            r#"{"functions": [{"uid": "abc123:0x401000", "comment": "RC4"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(annotations.version, ANNOTATIONS_VERSION);
+        assert!(annotations.binaries.is_empty());
+        assert_eq!(annotations.functions[0].comment.as_deref(), Some("RC4"));
+        assert_eq!(annotations.functions[0].analyst_name, None);
+
+        let json = serde_json::to_string(&annotations.functions[0]).unwrap();
+        assert_eq!(json, r#"{"uid":"abc123:0x401000","comment":"RC4"}"#);
+    }
+}
@@ -27,6 +27,54 @@ impl FromStr for CallType {
     }
 }
 
+/// How a CALLS or REFERENCES edge was found, which decides how far it is trusted
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EdgeSource {
+    /// Disassembly or decompilation by the extractor
+    #[default]
+    Static,
+    /// Observed in an execution trace
+    Dynamic,
+    /// Inferred, e.g. from pointer scans or name matching
+    Heuristic,
+}
+
+impl EdgeSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EdgeSource::Static => "static",
+            EdgeSource::Dynamic => "dynamic",
+            EdgeSource::Heuristic => "heuristic",
+        }
+    }
+
+    /// Confidence of edges whose input entry gives none
+    pub fn default_confidence(self) -> f64 {
+        match self {
+            EdgeSource::Static | EdgeSource::Dynamic => 1.0,
+            EdgeSource::Heuristic => 0.5,
+        }
+    }
+}
+
+impl FromStr for EdgeSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "static" | "analysis" | "disassembly" => Ok(EdgeSource::Static),
+            "dynamic" | "trace" | "runtime" => Ok(EdgeSource::Dynamic),
+            "heuristic" | "inferred" => Ok(EdgeSource::Heuristic),
+            _ => Err(format!("Unknown edge source: {}", s)),
+        }
+    }
+}
+
+fn full_confidence() -> f64 {
+    1.0
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Calls {
     /// Relationship type, fixed as "CALLS"
@@ -36,6 +84,14 @@ pub struct Calls {
     pub offset: String,
     /// Call type
     pub call_type: CallType,
+    #[serde(default)]
+    pub source: EdgeSource,
+    /// Trust in the edge from 0.0 to 1.0
+    #[serde(default = "full_confidence")]
+    pub confidence: f64,
+    /// How an indirect call target was resolved (e.g. "emulation", "points-to")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_by: Option<String>,
 }
 
 impl Calls {
@@ -44,6 +100,227 @@ impl Calls {
             rel_type: "CALLS".to_string(),
             offset,
             call_type,
+            source: EdgeSource::Static,
+            confidence: full_confidence(),
+            resolved_by: None,
         }
     }
+
+    /// Set where the call came from; `confidence` defaults to the source's and is
+    /// clamped to 0.0..=1.0
+    pub fn with_provenance(mut self, source: EdgeSource, confidence: Option<f64>) -> Self {
+        self.source = source;
+        self.confidence = confidence
+            .unwrap_or_else(|| source.default_confidence())
+            .clamp(0.0, 1.0);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum RefType {
+    /// Memory read of the target
+    Read,
+    /// Memory write to the target
+    Write,
+    /// Address taken with LEA
+    Lea,
+    /// Target address used as an immediate operand
+    Offset,
+}
+
+impl FromStr for RefType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "read" | "r" => Ok(RefType::Read),
+            "write" | "w" => Ok(RefType::Write),
+            "lea" => Ok(RefType::Lea),
+            "offset" | "imm" | "immediate" => Ok(RefType::Offset),
+            _ => Ok(RefType::Read), // Default to Read if unknown
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct References {
+    /// Relationship type, fixed as "REFERENCES"
+    #[serde(rename = "type")]
+    pub rel_type: String,
+    /// Address of the referencing instruction (hexadecimal format)
+    pub offset: String,
+    pub ref_type: RefType,
+    #[serde(default)]
+    pub source: EdgeSource,
+    /// Trust in the edge from 0.0 to 1.0
+    #[serde(default = "full_confidence")]
+    pub confidence: f64,
+}
+
+impl References {
+    pub fn new(offset: String, ref_type: RefType) -> Self {
+        Self {
+            rel_type: "REFERENCES".to_string(),
+            offset,
+            ref_type,
+            source: EdgeSource::Static,
+            confidence: full_confidence(),
+        }
+    }
+
+    /// See [`Calls::with_provenance`]
+    pub fn with_provenance(mut self, source: EdgeSource, confidence: Option<f64>) -> Self {
+        self.source = source;
+        self.confidence = confidence
+            .unwrap_or_else(|| source.default_confidence())
+            .clamp(0.0, 1.0);
+        self
+    }
+}
+
+/// Which side of a CALLS edge `query xrefs` matches the target on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum XrefDirection {
+    /// Calls to the target (its callers)
+    To,
+    /// Calls made by the target (its callees)
+    From,
+    /// Both
+    #[default]
+    Both,
+}
+
+impl XrefDirection {
+    pub fn includes_to(self) -> bool {
+        matches!(self, XrefDirection::To | XrefDirection::Both)
+    }
+
+    pub fn includes_from(self) -> bool {
+        matches!(self, XrefDirection::From | XrefDirection::Both)
+    }
+}
+
+/// Relationship between two samples reported by a sandbox or unpacker
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum BinaryRelationType {
+    /// The parent wrote the child to disk
+    Drops,
+    /// The parent fetched the child over the network
+    Downloads,
+    /// The child is carried inside the parent
+    Embeds,
+}
+
+impl BinaryRelationType {
+    /// Neo4j relationship type
+    pub fn label(self) -> &'static str {
+        match self {
+            BinaryRelationType::Drops => "DROPS",
+            BinaryRelationType::Downloads => "DOWNLOADS",
+            BinaryRelationType::Embeds => "EMBEDS",
+        }
+    }
+}
+
+impl FromStr for BinaryRelationType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "drops" | "dropped" => Ok(BinaryRelationType::Drops),
+            "downloads" | "downloaded" => Ok(BinaryRelationType::Downloads),
+            "embeds" | "embedded" => Ok(BinaryRelationType::Embeds),
+            other => Err(format!(
+                "Unknown relation '{}' (expected drops, downloads or embeds)",
+                other
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BinaryRelation {
+    /// SHA-256 of the parent sample (lowercase hex)
+    pub parent_sha256: String,
+    /// SHA-256 of the child sample (lowercase hex)
+    pub child_sha256: String,
+    pub relation: BinaryRelationType,
+    /// Where the relation was observed (e.g. sandbox name)
+    pub source: Option<String>,
+}
+
+/// API observed during dynamic analysis, aggregated over the whole run
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RuntimeApiCall {
+    /// API name as reported by the sandbox
+    pub api: String,
+    /// Number of calls observed
+    pub count: u64,
+    /// Timestamp of the first call, as reported by the sandbox
+    pub first_ts: Option<String>,
+}
+
+/// API resolved by hash, called from the function holding the hash constant
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HashedApiCall {
+    pub caller_uid: String,
+    pub hash: u64,
+    /// Library of the API when the mapping names it; without one the API is matched
+    /// by name against import nodes already in the graph
+    pub library: Option<String>,
+    pub api: String,
+}
+
+/// Call endpoint matching no imported function, stored per binary by the importer
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UnresolvedCall {
+    /// Address as given by the extractor, normalized when it parses
+    pub address: String,
+    /// `target` for unknown callees, `caller` for call sites outside any function
+    pub role: String,
+    /// Number of calls skipped because of this address
+    pub count: u64,
+    /// First call-site offsets, to jump to in a disassembler
+    pub call_sites: Vec<String>,
+    /// Filename of the binary, filled in by queries
+    #[serde(default)]
+    pub binary: String,
+}
+
+/// How a binary is linked to a network indicator
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum IndicatorRelation {
+    /// Observed in sandbox network activity
+    Contacts,
+    /// Found in the binary's strings
+    ReferencesIoc,
+}
+
+impl IndicatorRelation {
+    /// Neo4j relationship type
+    pub fn label(self) -> &'static str {
+        match self {
+            IndicatorRelation::Contacts => "CONTACTS",
+            IndicatorRelation::ReferencesIoc => "REFERENCES_IOC",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_call_provenance() {
+        let call = Calls::new("0x401005".to_string(), CallType::Direct);
+        assert_eq!((call.source, call.confidence), (EdgeSource::Static, 1.0));
+
+        let call = call.with_provenance(EdgeSource::from_str("heuristic").unwrap(), None);
+        assert_eq!((call.source, call.confidence), (EdgeSource::Heuristic, 0.5));
+
+        let call = call.with_provenance(EdgeSource::from_str("trace").unwrap(), Some(1.7));
+        assert_eq!((call.source, call.confidence), (EdgeSource::Dynamic, 1.0));
+        assert!(EdgeSource::from_str("guess").is_err());
+    }
 }
@@ -0,0 +1,161 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Smallest batch the tuner shrinks to
+const MIN_BATCH_SIZE: usize = 100;
+
+/// Largest batch the tuner grows to before adding concurrent writers
+const MAX_BATCH_SIZE: usize = 20_000;
+
+/// Most statements run at once for writes that may run concurrently
+const MAX_CONCURRENCY: usize = 8;
+
+/// Adjusts the batch size and concurrency of import writes from measured latency
+///
+/// A full batch slower than the target halves the batch size and drops a writer; one
+/// under half the target doubles the batch size, or adds a writer once the size is at
+/// its maximum. Clones share their state, so tuning carries over between files.
+#[derive(Debug, Clone)]
+pub struct BatchTuner {
+    target: Duration,
+    started: Instant,
+    state: Arc<Mutex<TunerState>>,
+}
+
+#[derive(Debug)]
+struct TunerState {
+    batch_size: usize,
+    concurrency: usize,
+    batches: usize,
+    rows: usize,
+    adjustments: usize,
+}
+
+/// What the tuner settled on, printed after an autotuned import
+#[derive(Debug, Clone, PartialEq)]
+pub struct TuningReport {
+    pub batches: usize,
+    /// Nodes and relationships written through batched statements
+    pub rows: usize,
+    pub elapsed: Duration,
+    pub batch_size: usize,
+    pub concurrency: usize,
+    pub adjustments: usize,
+}
+
+impl TuningReport {
+    pub fn rows_per_sec(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.rows as f64 / secs
+        } else {
+            0.0
+        }
+    }
+}
+
+impl BatchTuner {
+    pub fn new(target: Duration, initial_batch_size: usize) -> Self {
+        Self {
+            target,
+            started: Instant::now(),
+            state: Arc::new(Mutex::new(TunerState {
+                batch_size: initial_batch_size.clamp(MIN_BATCH_SIZE, MAX_BATCH_SIZE),
+                concurrency: 1,
+                batches: 0,
+                rows: 0,
+                adjustments: 0,
+            })),
+        }
+    }
+
+    pub fn batch_size(&self) -> usize {
+        self.state().batch_size
+    }
+
+    pub fn concurrency(&self) -> usize {
+        self.state().concurrency
+    }
+
+    /// Record `batches` statements writing `rows` rows at once in `elapsed`
+    ///
+    /// Partly filled batches (the tail of a list) are counted but do not tune.
+    pub fn record(&self, batches: usize, rows: usize, elapsed: Duration) {
+        let mut state = self.state();
+        state.batches += batches;
+        state.rows += rows;
+        if rows < state.batch_size * batches {
+            return;
+        }
+
+        let (batch_size, concurrency) = (state.batch_size, state.concurrency);
+        if elapsed > self.target {
+            state.batch_size = (batch_size / 2).max(MIN_BATCH_SIZE);
+            state.concurrency = concurrency.saturating_sub(1).max(1);
+        } else if elapsed < self.target / 2 {
+            if batch_size < MAX_BATCH_SIZE {
+                state.batch_size = (batch_size * 2).min(MAX_BATCH_SIZE);
+            } else {
+                state.concurrency = (concurrency + 1).min(MAX_CONCURRENCY);
+            }
+        }
+        if (state.batch_size, state.concurrency) != (batch_size, concurrency) {
+            state.adjustments += 1;
+        }
+    }
+
+    pub fn report(&self) -> TuningReport {
+        let state = self.state();
+        TuningReport {
+            batches: state.batches,
+            rows: state.rows,
+            elapsed: self.started.elapsed(),
+            batch_size: state.batch_size,
+            concurrency: state.concurrency,
+            adjustments: state.adjustments,
+        }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, TunerState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tuner_follows_latency() {
+        let tuner = BatchTuner::new(Duration::from_millis(500), 1000);
+        let fast = Duration::from_millis(100);
+        let slow = Duration::from_secs(2);
+
+        tuner.record(1, 1000, fast);
+        assert_eq!(tuner.batch_size(), 2000);
+
+        // Tails shorter than a batch do not tune
+        tuner.record(1, 10, Duration::from_millis(1));
+        assert_eq!(tuner.batch_size(), 2000);
+
+        tuner.record(1, 2000, slow);
+        assert_eq!(tuner.batch_size(), 1000);
+
+        for _ in 0..6 {
+            let size = tuner.batch_size();
+            tuner.record(1, size, fast);
+        }
+        assert_eq!(tuner.batch_size(), MAX_BATCH_SIZE);
+        assert_eq!(tuner.concurrency(), 2);
+
+        tuner.record(2, 2 * MAX_BATCH_SIZE, slow);
+        assert_eq!(
+            (tuner.batch_size(), tuner.concurrency()),
+            (MAX_BATCH_SIZE / 2, 1)
+        );
+
+        let report = tuner.report();
+        assert_eq!(report.batches, 11);
+        assert_eq!(report.adjustments, 9);
+    }
+}
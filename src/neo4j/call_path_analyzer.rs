@@ -1,20 +1,203 @@
+use super::connection::{Cypher, Dialect};
 use anyhow::Result;
-use neo4rs::Query;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 
+use crate::models::FunctionInfo;
 use crate::models::{
     CallContextAnalysis, CallPath, CallPathNode, CallSequence, CallerSequence, EnhancedCallGraph,
-    UpwardCallChain, UpwardCallNode,
+    FunctionMetrics, PathExpander, UpwardCallChain, UpwardCallNode,
 };
-use crate::neo4j::importer::FunctionInfo;
 
 /// Call path analyzer
+///
+/// When a binary filter is given, every function on a path except the last one must be
+/// CONTAINed by that binary. Import nodes are shared between samples, so they may only
+/// terminate a path and never bridge into callers from other binaries.
+///
+/// Exclusion patterns (and, when enabled, library functions) prune every path that passes
+/// through a matching function; the queried function itself is never matched against them.
+///
+/// Downward paths and upward chains are enumerated with the configured [`PathExpander`],
+/// visit each function at most once and stop after `max_results` paths, so hub functions
+/// in large binaries cannot make a query run unbounded.
+///
+/// With virtual dispatch enabled, paths also follow DISPATCHES_TO edges from virtual
+/// call sites to the overrides found through vtable slots.
 pub struct CallPathAnalyzer {
     connection: super::Neo4jConnection,
+    exclude: Vec<String>,
+    exclude_library: bool,
+    min_confidence: f64,
+    expand_virtual: bool,
+    expander: PathExpander,
+    max_results: usize,
 }
 
+/// Paths returned per query unless set with [`CallPathAnalyzer::with_path_limits`]
+pub const DEFAULT_MAX_PATHS: usize = 1000;
+
+/// Rejects paths visiting a function twice; Cypher only keeps relationships unique
+const NODE_UNIQUE: &str = "ALL(n IN nodes(path) WHERE single(m IN nodes(path) WHERE m = n))";
+
 impl CallPathAnalyzer {
     pub fn new(connection: super::Neo4jConnection) -> Self {
-        Self { connection }
+        Self {
+            connection,
+            exclude: Vec::new(),
+            exclude_library: false,
+            min_confidence: 0.0,
+            expand_virtual: false,
+            expander: PathExpander::default(),
+            max_results: DEFAULT_MAX_PATHS,
+        }
+    }
+
+    /// Path enumeration strategy and the number of paths after which a query stops
+    pub fn with_path_limits(mut self, expander: PathExpander, max_results: usize) -> Self {
+        self.expander = expander;
+        self.max_results = max_results;
+        self
+    }
+
+    /// Skip paths through functions matching any of `patterns` (exact names, `*` wildcards)
+    pub fn with_exclusions(mut self, patterns: &[String]) -> Self {
+        self.exclude = patterns.iter().map(|p| exclusion_regex(p)).collect();
+        self
+    }
+
+    /// Skip paths through functions flagged as library code (`is_library`)
+    pub fn with_library_exclusion(mut self, exclude_library: bool) -> Self {
+        self.exclude_library = exclude_library;
+        self
+    }
+
+    /// Skip paths through CALLS edges with a confidence below `min_confidence`
+    pub fn with_min_confidence(mut self, min_confidence: f64) -> Self {
+        self.min_confidence = min_confidence;
+        self
+    }
+
+    /// Also follow virtual calls to the overrides in derived classes' vtables
+    pub fn with_virtual_dispatch(mut self, expand_virtual: bool) -> Self {
+        self.expand_virtual = expand_virtual;
+        self
+    }
+
+    /// APOC path expansion needs Neo4j; other servers only run native patterns
+    async fn check_expander(&self) -> Result<()> {
+        let dialect = self.connection.dialect().await?;
+        if self.expander == PathExpander::Apoc && dialect != Dialect::Neo4j {
+            return Err(crate::error::validation(
+                format!(
+                    "--expander apoc needs Neo4j with the APOC plugin, the server is {}",
+                    dialect.as_str()
+                ),
+                Vec::new(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Relationship types followed from a caller to its callees
+    fn call_types(&self) -> &'static str {
+        if self.expand_virtual {
+            "CALLS|DISPATCHES_TO"
+        } else {
+            "CALLS"
+        }
+    }
+
+    /// Cypher predicate rejecting paths with a call in `rels` below `$min_confidence`;
+    /// edges imported before confidences were recorded count as certain
+    fn confidence_filter(rels: &str) -> String {
+        format!(
+            "ALL(r IN {} WHERE coalesce(r.confidence, 1.0) >= $min_confidence)",
+            rels
+        )
+    }
+
+    /// Cypher predicate rejecting paths whose `nodes` contain an excluded function
+    fn exclusion_filter(nodes: &str) -> String {
+        format!("NONE(n IN {} WHERE {})", nodes, Self::excluded("n"))
+    }
+
+    /// Cypher predicate matching an excluded function bound to `var`
+    fn excluded(var: &str) -> String {
+        format!(
+            "(ANY(pattern IN $exclude WHERE {0}.name =~ pattern)
+                OR ($exclude_library AND coalesce({0}.is_library, false)))",
+            var
+        )
+    }
+
+    /// Clauses binding `path` to call paths of up to `max_depth` calls from (or, `upward`,
+    /// into) the function bound to `anchor`, with `carried` variables kept in scope
+    ///
+    /// Returns the clauses followed by the expressions for the path's nodes and calls
+    /// ordered from the outermost caller down.
+    fn path_expansion(
+        &self,
+        anchor: &str,
+        carried: &str,
+        upward: bool,
+        max_depth: usize,
+    ) -> (String, &'static str, &'static str) {
+        match self.expander {
+            PathExpander::Native => {
+                let calls = self.call_types();
+                let pattern = if upward {
+                    format!("(:Function)-[:{}*1..{}]->({})", calls, max_depth, anchor)
+                } else {
+                    format!("({})-[:{}*1..{}]->(:Function)", anchor, calls, max_depth)
+                };
+                (
+                    format!("MATCH path = {}", pattern),
+                    "nodes(path)",
+                    "relationships(path)",
+                )
+            }
+            PathExpander::Apoc => {
+                // Prune excluded functions during expansion instead of only filtering
+                // the finished paths
+                let blacklist = if self.exclude.is_empty() && !self.exclude_library {
+                    format!("WITH {}, [] AS excluded", carried)
+                } else {
+                    format!(
+                        "OPTIONAL MATCH (x:Function) WHERE x <> {} AND {}
+                 WITH {}, collect(x) AS excluded",
+                        anchor,
+                        Self::excluded("x"),
+                        carried
+                    )
+                };
+                let expansion = format!(
+                    "{}
+                 CALL apoc.path.expandConfig({}, {{relationshipFilter: '{}', minLevel: 1, maxLevel: {},
+                      uniqueness: 'NODE_PATH', bfs: true, blacklistNodes: excluded}})
+                 YIELD path",
+                    blacklist,
+                    anchor,
+                    match (upward, self.expand_virtual) {
+                        (true, false) => "<CALLS",
+                        (true, true) => "<CALLS|<DISPATCHES_TO",
+                        (false, false) => "CALLS>",
+                        (false, true) => "CALLS>|DISPATCHES_TO>",
+                    },
+                    max_depth
+                );
+                // APOC paths start at the anchor, so upward chains come back reversed
+                if upward {
+                    (
+                        expansion,
+                        "reverse(nodes(path))",
+                        "reverse(relationships(path))",
+                    )
+                } else {
+                    (expansion, "nodes(path)", "relationships(path)")
+                }
+            }
+        }
     }
 
     pub async fn query_call_paths(
@@ -23,48 +206,71 @@ impl CallPathAnalyzer {
         binary: Option<&str>,
         max_depth: usize,
     ) -> Result<Vec<CallPath>> {
+        self.check_expander().await?;
         let mut paths = Vec::new();
 
         let mut query = if let Some(binary_name) = binary {
-            Query::new(format!(
+            let (expansion, nodes, rels) =
+                self.path_expansion("start", "b, start", false, max_depth);
+            Cypher::new(format!(
                 "MATCH (b:Binary)-[:CONTAINS|IMPORTS]->(start:Function)
-                 MATCH path = (start:Function)-[:CALLS*1..{}]->(end:Function)
-                 WHERE (start.name = $function_name OR start.uid = $function_name)
+                 WHERE (start.name = $function_name OR start.demangled_name = $function_name OR start.uid = $function_name)
                    AND (b.filename CONTAINS $binary_name OR b.hash = $binary_name)
-                   AND ALL(n IN nodes(path) WHERE EXISTS((b)-[:CONTAINS|IMPORTS]->(n)))
-                 RETURN path, length(path) as path_length,
-                        [node in nodes(path) | node.name] as node_names,
-                        [node in nodes(path) | node.address] as node_addresses,
-                        [rel in relationships(path) | rel.offset] as call_offsets",
-                max_depth
+                 {expansion}
+                 WHERE ALL(n IN {nodes}[..-1] WHERE EXISTS((b)-[:CONTAINS]->(n)))
+                   AND ANY(n IN {nodes}[-1..] WHERE EXISTS((b)-[:CONTAINS|IMPORTS]->(n)))
+                   AND {unique}
+                   AND {exclusion}
+                   AND {confidence}
+                 RETURN length(path) as path_length,
+                        [node in {nodes} | node.uid] as node_uids,
+                        [node in {nodes} | node.name] as node_names,
+                        [node in {nodes} | node.address] as node_addresses,
+                        [rel in {rels} | rel.offset] as call_offsets
+                 LIMIT $max_results",
+                unique = NODE_UNIQUE,
+                exclusion = Self::exclusion_filter(&format!("{}[1..]", nodes)),
+                confidence = Self::confidence_filter(rels)
             ))
             .param("binary_name", binary_name.to_string())
         } else {
-            Query::new(format!(
-                "MATCH path = (start:Function)-[:CALLS*1..{}]->(end:Function)
-                 WHERE start.name = $function_name OR start.uid = $function_name
-                 RETURN path, length(path) as path_length,
-                        [node in nodes(path) | node.name] as node_names,
-                        [node in nodes(path) | node.address] as node_addresses,
-                        [rel in relationships(path) | rel.offset] as call_offsets",
-                max_depth
+            let (expansion, nodes, rels) = self.path_expansion("start", "start", false, max_depth);
+            Cypher::new(format!(
+                "MATCH (start:Function)
+                 WHERE (start.name = $function_name OR start.demangled_name = $function_name OR start.uid = $function_name)
+                 {expansion}
+                 WHERE {unique}
+                   AND {exclusion}
+                   AND {confidence}
+                 RETURN length(path) as path_length,
+                        [node in {nodes} | node.uid] as node_uids,
+                        [node in {nodes} | node.name] as node_names,
+                        [node in {nodes} | node.address] as node_addresses,
+                        [rel in {rels} | rel.offset] as call_offsets
+                 LIMIT $max_results",
+                unique = NODE_UNIQUE,
+                exclusion = Self::exclusion_filter(&format!("{}[1..]", nodes)),
+                confidence = Self::confidence_filter(rels)
             ))
         };
 
-        query = query.param("function_name", function_name.to_string());
+        query = query
+            .param("function_name", function_name.to_string())
+            .param("exclude", self.exclude.clone())
+            .param("exclude_library", self.exclude_library)
+            .param("min_confidence", self.min_confidence)
+            .param("max_results", self.max_results as i64);
 
-        let mut result = self.connection.graph().execute(query).await?;
-        let mut path_counter = 0;
-
-        while let Some(row) = result.next().await? {
-            path_counter += 1;
+        let result = self.connection.read(query).await?;
 
+        for row in result {
+            let node_uids: Vec<String> = row.get("node_uids").unwrap_or_default();
             let node_names: Vec<String> = row.get("node_names").unwrap_or_default();
             let node_addresses: Vec<String> = row.get("node_addresses").unwrap_or_default();
             let call_offsets: Vec<String> = row.get("call_offsets").unwrap_or_default();
 
             if !node_names.is_empty() {
-                let mut call_path = CallPath::new(format!("path_{}", path_counter));
+                let mut call_path = CallPath::new(String::new());
 
                 for (i, name) in node_names.iter().enumerate() {
                     let address = node_addresses
@@ -79,6 +285,7 @@ impl CallPathAnalyzer {
 
                     let node = CallPathNode::new(
                         format!("{}_{}", name, i),
+                        node_uids.get(i).cloned().unwrap_or_default(),
                         name.clone(),
                         Some(address),
                         i,
@@ -93,22 +300,143 @@ impl CallPathAnalyzer {
             }
         }
 
-        if paths.is_empty() {
-            let mut call_path = CallPath::new("single_path".to_string());
-            call_path.add_node(CallPathNode::new(
-                "single_node".to_string(),
-                function_name.to_string(),
-                Some("0x1000".to_string()),
-                0,
-                None,
-                "Entry".to_string(),
-            ));
-            paths.push(call_path);
+        // The same path is matched once per binary node satisfying the filter
+        CallPath::dedup(&mut paths);
+        for (i, path) in paths.iter_mut().enumerate() {
+            path.id = format!("path_{}", i + 1);
         }
 
         Ok(paths)
     }
 
+    /// Query call routes between two functions
+    ///
+    /// With `shortest_only`, only the routes of minimal length are returned
+    /// (`allShortestPaths`); otherwise every route up to `max_depth` calls is listed.
+    /// Servers without `allShortestPaths` list every route and keep the shortest ones
+    /// per start and end function.
+    pub async fn query_call_routes(
+        &self,
+        from: &str,
+        to: &str,
+        binary: Option<&str>,
+        max_depth: usize,
+        shortest_only: bool,
+    ) -> Result<Vec<CallPath>> {
+        let calls = self.call_types();
+        let native_shortest = self.connection.dialect().await? == Dialect::Neo4j;
+        let path_pattern = if shortest_only && native_shortest {
+            format!(
+                "allShortestPaths((start)-[:{}*1..{}]->(end))",
+                calls, max_depth
+            )
+        } else {
+            format!("(start)-[:{}*1..{}]->(end)", calls, max_depth)
+        };
+
+        let mut query = if let Some(binary_name) = binary {
+            Cypher::new(format!(
+                "MATCH (b:Binary)-[:CONTAINS]->(start:Function), (b)-[:CONTAINS|IMPORTS]->(end:Function)
+                 WHERE (start.name = $from OR start.uid = $from)
+                   AND (end.name = $to OR end.uid = $to)
+                   AND (b.filename CONTAINS $binary_name OR b.hash = $binary_name)
+                 MATCH path = {}
+                 WHERE ALL(n IN nodes(path)[..-1] WHERE EXISTS((b)-[:CONTAINS]->(n)))
+                   AND {}
+                   AND {}
+                 RETURN [node in nodes(path) | node.uid] as node_uids,
+                        [node in nodes(path) | node.name] as node_names,
+                        [node in nodes(path) | node.address] as node_addresses,
+                        [rel in relationships(path) | rel.offset] as call_offsets,
+                        [rel in relationships(path) | rel.call_type] as call_types
+                 ORDER BY length(path)",
+                path_pattern,
+                Self::exclusion_filter("nodes(path)[1..-1]"),
+                Self::confidence_filter("relationships(path)")
+            ))
+            .param("binary_name", binary_name.to_string())
+        } else {
+            Cypher::new(format!(
+                "MATCH (start:Function), (end:Function)
+                 WHERE (start.name = $from OR start.uid = $from)
+                   AND (end.name = $to OR end.uid = $to)
+                 MATCH path = {}
+                 WHERE {}
+                   AND {}
+                 RETURN [node in nodes(path) | node.uid] as node_uids,
+                        [node in nodes(path) | node.name] as node_names,
+                        [node in nodes(path) | node.address] as node_addresses,
+                        [rel in relationships(path) | rel.offset] as call_offsets,
+                        [rel in relationships(path) | rel.call_type] as call_types
+                 ORDER BY length(path)",
+                path_pattern,
+                Self::exclusion_filter("nodes(path)[1..-1]"),
+                Self::confidence_filter("relationships(path)")
+            ))
+        };
+
+        query = query
+            .param("from", from.to_string())
+            .param("to", to.to_string())
+            .param("exclude", self.exclude.clone())
+            .param("exclude_library", self.exclude_library)
+            .param("min_confidence", self.min_confidence);
+
+        let result = self.connection.read(query).await?;
+        let mut routes = Vec::new();
+
+        for row in result {
+            let node_uids: Vec<String> = row.get("node_uids").unwrap_or_default();
+            let node_names: Vec<String> = row.get("node_names").unwrap_or_default();
+            let node_addresses: Vec<String> = row.get("node_addresses").unwrap_or_default();
+            let call_offsets: Vec<String> = row.get("call_offsets").unwrap_or_default();
+            let call_types: Vec<String> = row.get("call_types").unwrap_or_default();
+
+            if node_names.is_empty() {
+                continue;
+            }
+
+            let mut route = CallPath::new(String::new());
+            for (i, name) in node_names.iter().enumerate() {
+                let (call_site, call_type) = if i > 0 {
+                    (
+                        call_offsets.get(i - 1).cloned(),
+                        call_types
+                            .get(i - 1)
+                            .cloned()
+                            .unwrap_or_else(|| "Direct".to_string()),
+                    )
+                } else {
+                    (None, "Entry".to_string())
+                };
+
+                route.add_node(CallPathNode::new(
+                    format!("{}_{}", name, i),
+                    node_uids.get(i).cloned().unwrap_or_default(),
+                    name.clone(),
+                    node_addresses.get(i).cloned(),
+                    i,
+                    call_site,
+                    call_type,
+                ));
+            }
+
+            routes.push(route);
+        }
+
+        if shortest_only && !native_shortest {
+            keep_shortest(&mut routes);
+        }
+
+        // Several start/end candidates can share a name; report each route once
+        CallPath::dedup(&mut routes);
+        for (i, route) in routes.iter_mut().enumerate() {
+            route.id = format!("route_{}", i + 1);
+        }
+
+        Ok(routes)
+    }
+
     pub async fn query_enhanced_call_graph(
         &self,
         function_name: &str,
@@ -118,29 +446,45 @@ impl CallPathAnalyzer {
         let mut enhanced_graph = EnhancedCallGraph::new();
 
         let mut basic_query = if let Some(binary_name) = binary {
-            Query::new(format!(
-                "MATCH (b:Binary)-[:CONTAINS|IMPORTS]->(f:Function)-[:CALLS*1..{}]->(callee:Function)
-                 WHERE (f.name = $function_name OR f.uid = $function_name)
+            Cypher::new(format!(
+                "MATCH (b:Binary)-[:CONTAINS|IMPORTS]->(f:Function)
+                 MATCH path = (f)-[:{}*1..{}]->(callee:Function)
+                 WHERE (f.name = $function_name OR f.demangled_name = $function_name OR f.uid = $function_name)
                    AND (b.filename CONTAINS $binary_name OR b.hash = $binary_name)
+                   AND ALL(n IN nodes(path)[..-1] WHERE EXISTS((b)-[:CONTAINS]->(n)))
                    AND EXISTS((b)-[:CONTAINS|IMPORTS]->(callee))
+                   AND {}
+                   AND {}
                  RETURN DISTINCT callee",
-                max_depth
+                self.call_types(),
+                max_depth,
+                Self::exclusion_filter("nodes(path)[1..]"),
+                Self::confidence_filter("relationships(path)")
             ))
             .param("binary_name", binary_name.to_string())
         } else {
-            Query::new(format!(
-                "MATCH (f:Function)-[:CALLS*1..{}]->(callee:Function)
-                 WHERE f.name = $function_name OR f.uid = $function_name
+            Cypher::new(format!(
+                "MATCH path = (f:Function)-[:{}*1..{}]->(callee:Function)
+                 WHERE (f.name = $function_name OR f.demangled_name = $function_name OR f.uid = $function_name)
+                   AND {}
+                   AND {}
                  RETURN DISTINCT callee",
-                max_depth
+                self.call_types(),
+                max_depth,
+                Self::exclusion_filter("nodes(path)[1..]"),
+                Self::confidence_filter("relationships(path)")
             ))
         };
 
-        basic_query = basic_query.param("function_name", function_name.to_string());
+        basic_query = basic_query
+            .param("function_name", function_name.to_string())
+            .param("exclude", self.exclude.clone())
+            .param("exclude_library", self.exclude_library)
+            .param("min_confidence", self.min_confidence);
 
-        let mut result = self.connection.graph().execute(basic_query).await?;
+        let result = self.connection.read(basic_query).await?;
 
-        while let Some(row) = result.next().await? {
+        for row in result {
             if let Ok(node) = row.get::<neo4rs::Node>("callee") {
                 enhanced_graph.callees.push(FunctionInfo {
                     uid: node.get::<String>("uid").unwrap_or_default(),
@@ -158,29 +502,29 @@ impl CallPathAnalyzer {
         }
 
         let mut frequency_query = if let Some(binary_name) = binary {
-            Query::new(
-                "MATCH (b:Binary)-[:CONTAINS|IMPORTS]->(caller:Function)-[:CALLS]->(callee:Function)
-                 WHERE (caller.name = $function_name OR caller.uid = $function_name)
+            Cypher::new(
+                "MATCH (b:Binary)-[:CONTAINS]->(caller:Function)-[r:CALLS]->(callee:Function)
+                 WHERE (caller.name = $function_name OR caller.demangled_name = $function_name OR caller.uid = $function_name)
                    AND (b.filename CONTAINS $binary_name OR b.hash = $binary_name)
                    AND EXISTS((b)-[:CONTAINS|IMPORTS]->(callee))
-                 RETURN callee.name as callee_name, count(*) as frequency"
+                 RETURN callee.name as callee_name, sum(coalesce(r.count, 1)) as frequency"
                     .to_string(),
             )
             .param("binary_name", binary_name.to_string())
         } else {
-            Query::new(
-                "MATCH (caller:Function)-[:CALLS]->(callee:Function)
-                 WHERE caller.name = $function_name OR caller.uid = $function_name
-                 RETURN callee.name as callee_name, count(*) as frequency"
+            Cypher::new(
+                "MATCH (caller:Function)-[r:CALLS]->(callee:Function)
+                 WHERE caller.name = $function_name OR caller.demangled_name = $function_name OR caller.uid = $function_name
+                 RETURN callee.name as callee_name, sum(coalesce(r.count, 1)) as frequency"
                     .to_string(),
             )
         };
 
         frequency_query = frequency_query.param("function_name", function_name.to_string());
 
-        let mut result = self.connection.graph().execute(frequency_query).await?;
+        let result = self.connection.read(frequency_query).await?;
 
-        while let Some(row) = result.next().await? {
+        for row in result {
             if let (Ok(callee_name), Ok(frequency)) = (
                 row.get::<String>("callee_name"),
                 row.get::<i64>("frequency"),
@@ -202,32 +546,38 @@ impl CallPathAnalyzer {
 
         // Query call sequences within the function
         let mut query = if let Some(binary_name) = binary {
-            Query::new(
-                "MATCH (b:Binary)-[:CONTAINS|IMPORTS]->(f:Function)-[r:CALLS]->(callee:Function)
-                 WHERE (f.name = $function_name OR f.uid = $function_name)
+            Cypher::new(
+                "MATCH (b:Binary)-[:CONTAINS]->(f:Function)-[r:CALLS]->(callee:Function)
+                 WHERE (f.name = $function_name OR f.demangled_name = $function_name OR f.uid = $function_name)
                    AND (b.filename CONTAINS $binary_name OR b.hash = $binary_name)
                    AND EXISTS((b)-[:CONTAINS|IMPORTS]->(callee))
-                 RETURN f.name as caller, callee.name as callee, r.offset as call_site
-                 ORDER BY r.offset"
+                   AND coalesce(r.confidence, 1.0) >= $min_confidence
+                 UNWIND coalesce(r.offsets, [r.offset]) as call_site
+                 RETURN f.name as caller, callee.name as callee, call_site
+                 ORDER BY call_site"
                     .to_string(),
             )
             .param("binary_name", binary_name.to_string())
         } else {
-            Query::new(
+            Cypher::new(
                 "MATCH (f:Function)-[r:CALLS]->(callee:Function)
-                 WHERE f.name = $function_name OR f.uid = $function_name
-                 RETURN f.name as caller, callee.name as callee, r.offset as call_site
-                 ORDER BY r.offset"
+                 WHERE (f.name = $function_name OR f.demangled_name = $function_name OR f.uid = $function_name)
+                   AND coalesce(r.confidence, 1.0) >= $min_confidence
+                 UNWIND coalesce(r.offsets, [r.offset]) as call_site
+                 RETURN f.name as caller, callee.name as callee, call_site
+                 ORDER BY call_site"
                     .to_string(),
             )
         };
 
-        query = query.param("function_name", function_name.to_string());
+        query = query
+            .param("function_name", function_name.to_string())
+            .param("min_confidence", self.min_confidence);
 
-        let mut result = self.connection.graph().execute(query).await?;
+        let result = self.connection.read(query).await?;
         let mut order_counter = 0;
 
-        while let Some(row) = result.next().await? {
+        for row in result {
             if let (Ok(caller), Ok(callee), Ok(call_site)) = (
                 row.get::<String>("caller"),
                 row.get::<String>("callee"),
@@ -258,18 +608,18 @@ impl CallPathAnalyzer {
         let mut recursive_calls = Vec::new();
 
         let mut direct_query = if let Some(binary_name) = binary {
-            Query::new(
+            Cypher::new(
                 "MATCH (b:Binary)-[:CONTAINS]->(f:Function)-[:CALLS]->(f)
-                 WHERE (f.name = $function_name OR f.uid = $function_name)
+                 WHERE (f.name = $function_name OR f.demangled_name = $function_name OR f.uid = $function_name)
                    AND (b.filename CONTAINS $binary_name OR b.hash = $binary_name)
                  RETURN f.name as function_name, f.address as address"
                     .to_string(),
             )
             .param("binary_name", binary_name.to_string())
         } else {
-            Query::new(
+            Cypher::new(
                 "MATCH (f:Function)-[:CALLS]->(f)
-                 WHERE f.name = $function_name OR f.uid = $function_name
+                 WHERE f.name = $function_name OR f.demangled_name = $function_name OR f.uid = $function_name
                  RETURN f.name as function_name, f.address as address"
                     .to_string(),
             )
@@ -277,9 +627,9 @@ impl CallPathAnalyzer {
 
         direct_query = direct_query.param("function_name", function_name.to_string());
 
-        let mut result = self.connection.graph().execute(direct_query).await?;
+        let result = self.connection.read(direct_query).await?;
 
-        while let Some(row) = result.next().await? {
+        for row in result {
             if let Ok(func_name) = row.get::<String>("function_name") {
                 recursive_calls.push(RecursiveCall {
                     function_name: func_name,
@@ -290,21 +640,21 @@ impl CallPathAnalyzer {
         }
 
         let mut indirect_query = if let Some(binary_name) = binary {
-            Query::new(
-                "MATCH (b:Binary)-[:CONTAINS|IMPORTS]->(f:Function)
+            Cypher::new(
+                "MATCH (b:Binary)-[:CONTAINS]->(f:Function)
                  MATCH path = (f:Function)-[:CALLS*2..10]->(f)
-                 WHERE (f.name = $function_name OR f.uid = $function_name)
+                 WHERE (f.name = $function_name OR f.demangled_name = $function_name OR f.uid = $function_name)
                    AND (b.filename CONTAINS $binary_name OR b.hash = $binary_name)
-                   AND ALL(n IN nodes(path) WHERE EXISTS((b)-[:CONTAINS|IMPORTS]->(n)))
+                   AND ALL(n IN nodes(path) WHERE EXISTS((b)-[:CONTAINS]->(n)))
                  RETURN length(path) as depth, f.name as function_name, f.address as address,
                         [node in nodes(path) | node.name] as path_nodes"
                     .to_string(),
             )
             .param("binary_name", binary_name.to_string())
         } else {
-            Query::new(
+            Cypher::new(
                 "MATCH path = (f:Function)-[:CALLS*2..10]->(f)
-                 WHERE f.name = $function_name OR f.uid = $function_name
+                 WHERE f.name = $function_name OR f.demangled_name = $function_name OR f.uid = $function_name
                  RETURN length(path) as depth, f.name as function_name, f.address as address,
                         [node in nodes(path) | node.name] as path_nodes"
                     .to_string(),
@@ -313,9 +663,9 @@ impl CallPathAnalyzer {
 
         indirect_query = indirect_query.param("function_name", function_name.to_string());
 
-        let mut result = self.connection.graph().execute(indirect_query).await?;
+        let result = self.connection.read(indirect_query).await?;
 
-        while let Some(row) = result.next().await? {
+        for row in result {
             if let (Ok(func_name), Ok(depth)) =
                 (row.get::<String>("function_name"), row.get::<i64>("depth"))
             {
@@ -337,60 +687,80 @@ impl CallPathAnalyzer {
         binary: Option<&str>,
         max_depth: usize,
     ) -> Result<Vec<UpwardCallChain>> {
+        self.check_expander().await?;
         let mut chains = Vec::new();
 
         // Query all call paths pointing to the target function
         let mut query = if let Some(binary_name) = binary {
-            Query::new(format!(
+            let (expansion, nodes, rels) = self.path_expansion("end", "b, end", true, max_depth);
+            Cypher::new(format!(
                 "MATCH (b:Binary)-[:CONTAINS|IMPORTS]->(end:Function)
-                 MATCH path = (start:Function)-[:CALLS*1..{}]->(end:Function)
-                 WHERE (end.name = $function_name OR end.uid = $function_name)
+                 WHERE (end.name = $function_name OR end.demangled_name = $function_name OR end.uid = $function_name)
                    AND (b.filename CONTAINS $binary_name OR b.hash = $binary_name)
-                   AND ALL(n IN nodes(path) WHERE EXISTS((b)-[:CONTAINS|IMPORTS]->(n)))
-                 RETURN path, length(path) as path_length,
-                        [node in nodes(path) | node.name] as node_names,
-                        [node in nodes(path) | node.address] as node_addresses,
-                        [rel in relationships(path) | rel.offset] as call_offsets
-                 ORDER BY path_length",
-                max_depth
+                 {expansion}
+                 WHERE ALL(n IN {nodes}[..-1] WHERE EXISTS((b)-[:CONTAINS]->(n)))
+                   AND {unique}
+                   AND {exclusion}
+                   AND {confidence}
+                 RETURN length(path) as path_length,
+                        [node in {nodes} | node.uid] as node_uids,
+                        [node in {nodes} | node.name] as node_names,
+                        [node in {nodes} | node.address] as node_addresses,
+                        [rel in {rels} | rel.offset] as call_offsets
+                 LIMIT $max_results",
+                unique = NODE_UNIQUE,
+                exclusion = Self::exclusion_filter(&format!("{}[..-1]", nodes)),
+                confidence = Self::confidence_filter(rels)
             ))
             .param("binary_name", binary_name.to_string())
         } else {
-            Query::new(format!(
-                "MATCH path = (start:Function)-[:CALLS*1..{}]->(end:Function)
-                 WHERE end.name = $function_name OR end.uid = $function_name
-                 RETURN path, length(path) as path_length,
-                        [node in nodes(path) | node.name] as node_names,
-                        [node in nodes(path) | node.address] as node_addresses,
-                        [rel in relationships(path) | rel.offset] as call_offsets
-                 ORDER BY path_length",
-                max_depth
+            let (expansion, nodes, rels) = self.path_expansion("end", "end", true, max_depth);
+            Cypher::new(format!(
+                "MATCH (end:Function)
+                 WHERE (end.name = $function_name OR end.demangled_name = $function_name OR end.uid = $function_name)
+                 {expansion}
+                 WHERE {unique}
+                   AND {exclusion}
+                   AND {confidence}
+                 RETURN length(path) as path_length,
+                        [node in {nodes} | node.uid] as node_uids,
+                        [node in {nodes} | node.name] as node_names,
+                        [node in {nodes} | node.address] as node_addresses,
+                        [rel in {rels} | rel.offset] as call_offsets
+                 LIMIT $max_results",
+                unique = NODE_UNIQUE,
+                exclusion = Self::exclusion_filter(&format!("{}[..-1]", nodes)),
+                confidence = Self::confidence_filter(rels)
             ))
         };
 
-        query = query.param("function_name", function_name.to_string());
-
-        let mut result = self.connection.graph().execute(query).await?;
-        let mut chain_counter = 0;
+        query = query
+            .param("function_name", function_name.to_string())
+            .param("exclude", self.exclude.clone())
+            .param("exclude_library", self.exclude_library)
+            .param("min_confidence", self.min_confidence)
+            .param("max_results", self.max_results as i64);
 
-        while let Some(row) = result.next().await? {
-            chain_counter += 1;
+        let result = self.connection.read(query).await?;
 
-            // Get node names, addresses, and call offsets
+        for row in result {
+            // Get node UIDs, names, addresses, and call offsets
+            let node_uids: Vec<String> = row.get("node_uids").unwrap_or_default();
             let node_names: Vec<String> = row.get("node_names").unwrap_or_default();
             let node_addresses: Vec<String> = row.get("node_addresses").unwrap_or_default();
             let call_offsets: Vec<String> = row.get("call_offsets").unwrap_or_default();
 
             if !node_names.is_empty() {
-                let mut chain = UpwardCallChain::new(format!("upward_chain_{}", chain_counter));
+                let mut chain = UpwardCallChain::new(String::new());
+                let target_index = node_names.len() - 1;
 
-                // Add nodes in reverse order (from caller to callee)
+                // Nodes run from the outermost caller down to the target function
                 for (i, name) in node_names.iter().enumerate() {
                     let address = node_addresses
                         .get(i)
                         .cloned()
                         .unwrap_or_else(|| "N/A".to_string());
-                    let call_site = if i < node_names.len() - 1 {
+                    let call_site = if i < target_index {
                         call_offsets.get(i).cloned()
                     } else {
                         None
@@ -398,9 +768,10 @@ impl CallPathAnalyzer {
 
                     let node = UpwardCallNode::new(
                         format!("{}_{}", name, i),
+                        node_uids.get(i).cloned().unwrap_or_default(),
                         name.clone(),
                         Some(address),
-                        i,
+                        target_index - i,
                         call_site,
                         "Upward".to_string(),
                     );
@@ -412,18 +783,10 @@ impl CallPathAnalyzer {
             }
         }
 
-        // If no upward call chains are found, create a basic single-node chain
-        if chains.is_empty() {
-            let mut chain = UpwardCallChain::new("single_upward_chain".to_string());
-            chain.add_node(UpwardCallNode::new(
-                "single_node".to_string(),
-                function_name.to_string(),
-                Some("0x1000".to_string()),
-                0,
-                None,
-                "Root".to_string(),
-            ));
-            chains.push(chain);
+        UpwardCallChain::dedup(&mut chains);
+        chains.sort_by_key(|chain| chain.length);
+        for (i, chain) in chains.iter_mut().enumerate() {
+            chain.id = format!("upward_chain_{}", i + 1);
         }
 
         Ok(chains)
@@ -439,34 +802,40 @@ impl CallPathAnalyzer {
 
         // Query all functions that call the target function
         let mut query = if let Some(binary_name) = binary {
-            Query::new(
-                "MATCH (b:Binary)-[:CONTAINS|IMPORTS]->(caller:Function)-[r:CALLS]->(callee:Function)
-                 WHERE (callee.name = $function_name OR callee.uid = $function_name)
+            Cypher::new(
+                "MATCH (b:Binary)-[:CONTAINS]->(caller:Function)-[r:CALLS]->(callee:Function)
+                 WHERE (callee.name = $function_name OR callee.demangled_name = $function_name OR callee.uid = $function_name)
                    AND (b.filename CONTAINS $binary_name OR b.hash = $binary_name)
                    AND EXISTS((b)-[:CONTAINS|IMPORTS]->(callee))
-                 RETURN caller.name as caller_name, caller.address as caller_address, 
-                        r.offset as call_site, callee.name as callee_name, callee.address as callee_address
-                 ORDER BY r.offset"
+                   AND coalesce(r.confidence, 1.0) >= $min_confidence
+                 UNWIND coalesce(r.offsets, [r.offset]) as call_site
+                 RETURN caller.name as caller_name, caller.address as caller_address,
+                        call_site, callee.name as callee_name, callee.address as callee_address
+                 ORDER BY call_site"
                     .to_string(),
             )
             .param("binary_name", binary_name.to_string())
         } else {
-            Query::new(
+            Cypher::new(
                 "MATCH (caller:Function)-[r:CALLS]->(callee:Function)
-                 WHERE callee.name = $function_name OR callee.uid = $function_name
-                 RETURN caller.name as caller_name, caller.address as caller_address, 
-                        r.offset as call_site, callee.name as callee_name, callee.address as callee_address
-                 ORDER BY r.offset"
+                 WHERE (callee.name = $function_name OR callee.demangled_name = $function_name OR callee.uid = $function_name)
+                   AND coalesce(r.confidence, 1.0) >= $min_confidence
+                 UNWIND coalesce(r.offsets, [r.offset]) as call_site
+                 RETURN caller.name as caller_name, caller.address as caller_address,
+                        call_site, callee.name as callee_name, callee.address as callee_address
+                 ORDER BY call_site"
                     .to_string(),
             )
         };
 
-        query = query.param("function_name", function_name.to_string());
+        query = query
+            .param("function_name", function_name.to_string())
+            .param("min_confidence", self.min_confidence);
 
-        let mut result = self.connection.graph().execute(query).await?;
+        let result = self.connection.read(query).await?;
         let mut order_counter = 0;
 
-        while let Some(row) = result.next().await? {
+        for row in result {
             if let (
                 Ok(caller_name),
                 Ok(caller_address),
@@ -536,10 +905,223 @@ impl CallPathAnalyzer {
 
         Ok(analysis)
     }
+
+    /// Find groups of mutually recursive functions in a binary
+    ///
+    /// Every strongly connected component of the binary's CALLS graph with at least
+    /// `min_size` members is reported; with `min_size` 1, directly recursive
+    /// functions are included as single-member cycles.
+    pub async fn find_call_cycles(&self, binary: &str, min_size: usize) -> Result<Vec<CallCycle>> {
+        let query = Cypher::new(
+            "MATCH (b:Binary)-[:CONTAINS]->(caller:Function)-[:CALLS]->(callee:Function)
+             WHERE (b.filename CONTAINS $binary_name OR b.hash = $binary_name)
+               AND EXISTS((b)-[:CONTAINS]->(callee))
+             RETURN DISTINCT caller.uid as caller_uid, caller.name as caller_name,
+                    caller.address as caller_address, callee.uid as callee_uid,
+                    callee.name as callee_name, callee.address as callee_address"
+                .to_string(),
+        )
+        .param("binary_name", binary.to_string());
+
+        let result = self.connection.read(query).await?;
+        let mut functions: Vec<FunctionInfo> = Vec::new();
+        let mut indices: HashMap<String, usize> = HashMap::new();
+        let mut edges = Vec::new();
+
+        for row in result {
+            let mut endpoint = |prefix: &str| -> Option<usize> {
+                let uid = row.get::<String>(&format!("{}_uid", prefix)).ok()?;
+                let index = *indices.entry(uid.clone()).or_insert_with(|| {
+                    functions.push(FunctionInfo {
+                        uid,
+                        name: row
+                            .get::<String>(&format!("{}_name", prefix))
+                            .unwrap_or_default(),
+                        address: row.get::<String>(&format!("{}_address", prefix)).ok(),
+                    });
+                    functions.len() - 1
+                });
+                Some(index)
+            };
+
+            if let (Some(from), Some(to)) = (endpoint("caller"), endpoint("callee")) {
+                edges.push((from, to));
+            }
+        }
+
+        let components =
+            crate::utils::graph::strongly_connected_components(functions.len(), &edges);
+
+        // Count calls inside each component; a self call makes a single function a cycle
+        let mut component_of = vec![0; functions.len()];
+        for (c, members) in components.iter().enumerate() {
+            for &member in members {
+                component_of[member] = c;
+            }
+        }
+        let mut call_counts = vec![0; components.len()];
+        for &(from, to) in &edges {
+            if component_of[from] == component_of[to] {
+                call_counts[component_of[from]] += 1;
+            }
+        }
+
+        let mut cycles: Vec<CallCycle> = components
+            .into_iter()
+            .zip(call_counts)
+            .filter(|(members, call_count)| *call_count > 0 && members.len() >= min_size)
+            .map(|(members, call_count)| CallCycle {
+                id: String::new(),
+                size: members.len(),
+                call_count,
+                functions: members.iter().map(|&i| functions[i].clone()).collect(),
+            })
+            .collect();
+
+        cycles.sort_by_key(|c| std::cmp::Reverse(c.size));
+        for (i, cycle) in cycles.iter_mut().enumerate() {
+            cycle.id = format!("cycle_{}", i + 1);
+        }
+
+        Ok(cycles)
+    }
+
+    /// Compute call-graph metrics for every function contained in the matching binaries
+    ///
+    /// Imported functions count as API targets but get no metrics of their own, since
+    /// their nodes are shared between samples.
+    ///
+    /// Entry depths are measured from exports and functions with an `entry_kind`; in
+    /// binaries imported without entry points, functions nobody calls are roots too.
+    pub async fn compute_function_metrics(
+        &self,
+        binary: Option<&str>,
+    ) -> Result<Vec<FunctionMetrics>> {
+        let binary_filter = if binary.is_some() {
+            "WHERE (b.filename CONTAINS $binary_name OR b.hash = $binary_name)"
+        } else {
+            ""
+        };
+
+        let mut function_query = Cypher::new(format!(
+            "MATCH (b:Binary)-[:CONTAINS]->(f:Function)
+             {}
+             RETURN DISTINCT f.uid as uid, f.name as name, f.address as address, f.type as type,
+                    f.entry_kind as entry_kind, split(f.uid, ':')[0] as binary_hash",
+            binary_filter
+        ));
+        let mut call_query = Cypher::new(format!(
+            "MATCH (b:Binary)-[:CONTAINS]->(caller:Function)-[:CALLS]->(callee:Function)
+             {} {} EXISTS((b)-[:CONTAINS|IMPORTS]->(callee))
+             RETURN DISTINCT caller.uid as caller_uid, callee.uid as callee_uid,
+                    callee.type as callee_type",
+            binary_filter,
+            if binary.is_some() { "AND" } else { "WHERE" }
+        ));
+        if let Some(binary_name) = binary {
+            function_query = function_query.param("binary_name", binary_name.to_string());
+            call_query = call_query.param("binary_name", binary_name.to_string());
+        }
+
+        let mut metrics = Vec::new();
+        let mut indices: HashMap<String, usize> = HashMap::new();
+        let mut roots = Vec::new();
+        let mut binaries = Vec::new();
+        let mut with_entry_points = HashSet::new();
+
+        let result = self.connection.read(function_query).await?;
+        for row in result {
+            let Ok(uid) = row.get::<String>("uid") else {
+                continue;
+            };
+            let binary_hash = row.get::<String>("binary_hash").unwrap_or_default();
+            if row.get::<String>("entry_kind").is_ok() {
+                with_entry_points.insert(binary_hash.clone());
+                roots.push(metrics.len());
+            } else if row.get::<String>("type").ok().as_deref() == Some("Export") {
+                roots.push(metrics.len());
+            }
+            binaries.push(binary_hash);
+            indices.insert(uid.clone(), metrics.len());
+            metrics.push(FunctionMetrics {
+                uid,
+                name: row.get::<String>("name").unwrap_or_default(),
+                address: row.get::<String>("address").ok(),
+                in_degree: 0,
+                out_degree: 0,
+                entry_depth: None,
+                reachable_apis: 0,
+            });
+        }
+
+        // Imported callees are appended after the contained functions
+        let mut apis: HashMap<String, usize> = HashMap::new();
+        let mut edges = Vec::new();
+
+        let result = self.connection.read(call_query).await?;
+        for row in result {
+            let (Ok(caller_uid), Ok(callee_uid)) = (
+                row.get::<String>("caller_uid"),
+                row.get::<String>("callee_uid"),
+            ) else {
+                continue;
+            };
+            let Some(&from) = indices.get(&caller_uid) else {
+                continue;
+            };
+            let to = match indices.get(&callee_uid) {
+                Some(&index) => index,
+                None if row.get::<String>("callee_type").ok().as_deref() == Some("Import") => {
+                    let next = metrics.len() + apis.len();
+                    *apis.entry(callee_uid).or_insert(next)
+                }
+                None => continue,
+            };
+            edges.push((from, to));
+        }
+
+        let node_count = metrics.len() + apis.len();
+        let mut targets = vec![false; node_count];
+        targets[metrics.len()..].fill(true);
+
+        let mut in_degree = vec![0; node_count];
+        let mut out_degree = vec![0; node_count];
+        for &(from, to) in &edges {
+            out_degree[from] += 1;
+            in_degree[to] += 1;
+        }
+
+        roots.extend(
+            (0..metrics.len())
+                .filter(|&i| in_degree[i] == 0 && !with_entry_points.contains(&binaries[i])),
+        );
+
+        let depths = crate::utils::graph::shortest_depths(node_count, &edges, &roots);
+        let reachable = crate::utils::graph::reachable_counts(node_count, &edges, &targets);
+
+        for (i, function) in metrics.iter_mut().enumerate() {
+            function.in_degree = in_degree[i];
+            function.out_degree = out_degree[i];
+            function.entry_depth = depths[i];
+            function.reachable_apis = reachable[i];
+        }
+
+        Ok(metrics)
+    }
+}
+
+/// Strongly connected component of the call graph (a mutual recursion group)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CallCycle {
+    pub id: String,
+    pub size: usize,
+    /// Calls between members of the cycle
+    pub call_count: usize,
+    pub functions: Vec<FunctionInfo>,
 }
 
 /// Recursive call information
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RecursiveCall {
     pub function_name: String,
     pub call_type: RecursiveCallType,
@@ -547,10 +1129,86 @@ pub struct RecursiveCall {
 }
 
 /// Recursive call type
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum RecursiveCallType {
     /// Direct recursion
     Direct,
     /// Indirect recursion
     Indirect,
 }
+
+/// Drop routes longer than the shortest route between the same start and end function
+fn keep_shortest(routes: &mut Vec<CallPath>) {
+    let endpoints = |route: &CallPath| {
+        (
+            route.nodes.first().map(|node| node.uid.clone()),
+            route.nodes.last().map(|node| node.uid.clone()),
+        )
+    };
+    let mut shortest: HashMap<_, usize> = HashMap::new();
+    for route in routes.iter() {
+        let length = shortest.entry(endpoints(route)).or_insert(route.length);
+        *length = (*length).min(route.length);
+    }
+    routes.retain(|route| shortest[&endpoints(route)] == route.length);
+}
+
+/// Convert an exclusion pattern into an anchored regex, `*` matching any run of characters
+fn exclusion_regex(pattern: &str) -> String {
+    let mut regex = String::with_capacity(pattern.len() + 8);
+    regex.push('^');
+    for ch in pattern.chars() {
+        match ch {
+            '*' => regex.push_str(".*"),
+            '\\' | '.' | '+' | '?' | '(' | ')' | '[' | ']' | '{' | '}' | '|' | '^' | '$' => {
+                regex.push('\\');
+                regex.push(ch);
+            }
+            _ => regex.push(ch),
+        }
+    }
+    regex.push('$');
+    regex
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exclusion_regex() {
+        assert_eq!(
+            exclusion_regex("__security_check_cookie"),
+            "^__security_check_cookie$"
+        );
+        assert_eq!(exclusion_regex("_crt*"), "^_crt.*$");
+        assert_eq!(exclusion_regex("??0Foo@@QAE@XZ"), "^\\?\\?0Foo@@QAE@XZ$");
+    }
+
+    #[test]
+    fn test_keep_shortest() {
+        let route = |uids: &[&str]| {
+            let mut route = CallPath::new(String::new());
+            for (i, uid) in uids.iter().enumerate() {
+                route.add_node(CallPathNode::new(
+                    uid.to_string(),
+                    uid.to_string(),
+                    uid.to_string(),
+                    None,
+                    i,
+                    None,
+                    "Direct".to_string(),
+                ));
+            }
+            route
+        };
+        let mut routes = vec![
+            route(&["a", "b", "c"]),
+            route(&["a", "c"]),
+            route(&["x", "y", "z"]),
+        ];
+        keep_shortest(&mut routes);
+        let lengths: Vec<usize> = routes.iter().map(|route| route.nodes.len()).collect();
+        assert_eq!(lengths, vec![2, 3]);
+    }
+}
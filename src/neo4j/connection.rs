@@ -1,18 +1,204 @@
 use anyhow::{Context, Result};
-use neo4rs::{ConfigBuilder, Graph, Query};
-use std::sync::Arc;
+use neo4rs::{BoltType, ConfigBuilder, Graph, Query, Row};
+use serde::Serialize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
+use super::metrics;
 use super::DatabaseStats;
 
+/// How long [`Neo4jConnection::get_database_stats`] reuses its last result
+const STATS_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// Labels always listed by `database stats`, even before anything was imported
+const STATS_LABELS: [&str; 4] = ["Binary", "Function", "String", "Library"];
+
+/// URI schemes accepted for `neo4j_uri` and `neo4j_read_uris`
+///
+/// `neo4j://` URIs send a routing context so a cluster member can route queries on
+/// the server side; the driver itself does not maintain a routing table.
+pub const SUPPORTED_SCHEMES: &[&str] = &[
+    "bolt",
+    "bolt+s",
+    "bolt+ssc",
+    "neo4j",
+    "neo4j+s",
+    "neo4j+ssc",
+];
+
+/// Read query that keeps its text and parameters, so `--explain` and `--profile` can
+/// print it (`neo4rs::Query` does not expose either)
+#[derive(Clone)]
+pub struct Cypher {
+    text: String,
+    params: Vec<(String, BoltType)>,
+    /// Parameter values as shown in printed statements
+    shown: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Cypher {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            params: Vec::new(),
+            shown: serde_json::Map::new(),
+        }
+    }
+
+    pub fn param<T: Into<BoltType> + Serialize>(mut self, key: &str, value: T) -> Self {
+        let shown = serde_json::to_value(&value).unwrap_or(serde_json::Value::Null);
+        self.shown.insert(key.to_string(), shown);
+        self.params.push((key.to_string(), value.into()));
+        self
+    }
+
+    fn query(&self, prefix: &str) -> Query {
+        Query::new(format!("{}{}", prefix, self.text)).params(self.params.clone())
+    }
+
+    /// Statement for cypher-shell or Neo4j Browser, parameters set with `:param`
+    fn script(&self, keyword: &str) -> String {
+        let mut script = String::new();
+        if !self.shown.is_empty() {
+            let params: Vec<String> = self
+                .shown
+                .iter()
+                .map(|(key, value)| format!("{}: {}", key, value))
+                .collect();
+            script.push_str(&format!(":param {{{}}}\n", params.join(", ")));
+        }
+        script.push_str(&format!("{} {};", keyword, self.text.trim()));
+        script
+    }
+
+    /// Text on one line, shortened to `width` characters
+    fn summary(&self, width: usize) -> String {
+        let line = self.text.split_whitespace().collect::<Vec<_>>().join(" ");
+        match line.char_indices().nth(width) {
+            Some((end, _)) => format!("{}...", &line[..end]),
+            None => line,
+        }
+    }
+}
+
+/// How read queries are reported, set by `query --timing/--explain/--profile`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct QueryTracing {
+    /// Record the execution time and row count of every read query
+    pub timing: bool,
+    pub plan: Option<PlanMode>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlanMode {
+    /// Print each statement prefixed with EXPLAIN; the server plans it but returns no rows
+    Explain,
+    /// Run each statement, then print it prefixed with PROFILE along with its timing
+    Profile,
+}
+
+/// Execution time and row count of one read query
+#[derive(Debug, Clone)]
+pub struct QueryTiming {
+    /// Query text on one line, shortened
+    pub statement: String,
+    pub rows: usize,
+    pub elapsed: Duration,
+}
+
+/// Cypher dialect of the server behind a connection, see [`Neo4jConnection::dialect`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dialect {
+    Neo4j,
+    /// `ON :Label(property)` schema DDL; no fulltext indexes, `allShortestPaths` or APOC
+    Memgraph,
+    /// Other Bolt servers (e.g. Neptune): no schema DDL or Neo4j procedures
+    Other,
+}
+
+impl Dialect {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Dialect::Neo4j => "Neo4j",
+            Dialect::Memgraph => "Memgraph",
+            Dialect::Other => "generic Bolt",
+        }
+    }
+}
+
+/// Server product and installed plugins, reported by `database ping`
+#[derive(Debug, Clone, Default)]
+pub struct ServerInfo {
+    pub product: Option<String>,
+    pub version: Option<String>,
+    pub edition: Option<String>,
+    pub apoc: Option<String>,
+    pub gds: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct QueryTrace {
+    tracing: QueryTracing,
+    timings: Vec<QueryTiming>,
+}
+
 #[derive(Clone)]
 pub struct Neo4jConnection {
+    /// Primary endpoint, receives all writes (the cluster leader)
     graph: Arc<Graph>,
+    /// Read replicas used in turn by `fetch`, empty for single-instance setups
+    readers: Arc<Vec<Graph>>,
+    next_reader: Arc<AtomicUsize>,
+    /// Limit for read queries, see [`Neo4jConnection::read`]
+    query_timeout: Option<Duration>,
+    /// Shared by all clones, so tracing set on one covers the importer and analyzers
+    trace: Arc<Mutex<QueryTrace>>,
+    /// Last `get_database_stats` result and when it was taken, shared by all clones
+    stats_cache: Arc<Mutex<Option<(Instant, DatabaseStats)>>>,
+    /// Dialect of the primary server once probed, shared by all clones
+    dialect: Arc<Mutex<Option<Dialect>>>,
 }
 
 impl Neo4jConnection {
     pub async fn new(config: &crate::config::Config) -> Result<Self> {
+        if config.backend == crate::store::Backend::Embedded {
+            return Err(crate::error::validation(
+                "This command needs Neo4j; the embedded backend supports `import json`, \
+                 `import binary`, `query binary`, `query binaries`, `query functions`, \
+                 `query callgraph` and `export networkx`",
+                Vec::new(),
+            ));
+        }
+        let graph = Self::connect(config, &config.neo4j_uri).await?;
+
+        Ok(Self {
+            graph: Arc::new(graph),
+            readers: Arc::new(Vec::new()),
+            next_reader: Arc::new(AtomicUsize::new(0)),
+            query_timeout: config.query_timeout_secs.map(Duration::from_secs),
+            trace: Arc::new(Mutex::new(QueryTrace::default())),
+            stats_cache: Arc::new(Mutex::new(None)),
+            dialect: Arc::new(Mutex::new(None)),
+        })
+    }
+
+    /// Also connect to the `neo4j_read_uris` replicas, used by read-only queries
+    ///
+    /// Replicas may lag behind the leader, so this is meant for analysis commands
+    /// rather than imports that read back what they just wrote.
+    pub async fn with_read_replicas(mut self, config: &crate::config::Config) -> Result<Self> {
+        let mut readers = Vec::with_capacity(config.neo4j_read_uris.len());
+        for uri in &config.neo4j_read_uris {
+            readers.push(Self::connect(config, uri).await?);
+        }
+        self.readers = Arc::new(readers);
+        Ok(self)
+    }
+
+    async fn connect(config: &crate::config::Config, uri: &str) -> Result<Graph> {
         let mut config_builder = ConfigBuilder::default()
-            .uri(&config.neo4j_uri)
+            .uri(uri)
             .user(&config.neo4j_user)
             .password(&config.neo4j_password);
 
@@ -20,24 +206,118 @@ impl Neo4jConnection {
         if let Some(ref db_name) = config.neo4j_database {
             config_builder = config_builder.db(db_name.as_str());
         }
+        if let Some(max_connections) = config.neo4j_max_connections {
+            config_builder = config_builder.max_connections(max_connections);
+        }
+        if let Some(fetch_size) = config.neo4j_fetch_size {
+            config_builder = config_builder.fetch_size(fetch_size);
+        }
 
         let neo4j_config = config_builder
             .build()
             .context("Failed to build Neo4j configuration")?;
 
-        let graph = Graph::connect(neo4j_config)
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to connect to Neo4j database: {}", e))?;
-
-        Ok(Self {
-            graph: Arc::new(graph),
+        Graph::connect(neo4j_config).await.map_err(|e| {
+            crate::error::connection(format!(
+                "Failed to connect to Neo4j database at '{}': {}",
+                uri, e
+            ))
         })
     }
 
+    /// Primary endpoint, for writes and reads that must see them
     pub fn graph(&self) -> &Graph {
         &self.graph
     }
 
+    /// Report timing or plans of subsequent read queries
+    pub fn trace_queries(&self, tracing: QueryTracing) {
+        self.trace.lock().expect("query trace lock").tracing = tracing;
+    }
+
+    /// Timings recorded since tracing was enabled with `timing` set
+    pub fn query_timings(&self) -> Vec<QueryTiming> {
+        self.trace.lock().expect("query trace lock").timings.clone()
+    }
+
+    /// Run a read-only query on the next read endpoint and collect its rows
+    pub async fn read(&self, cypher: Cypher) -> Result<Vec<Row>> {
+        let tracing = self.trace.lock().expect("query trace lock").tracing;
+
+        if tracing.plan == Some(PlanMode::Explain) {
+            eprintln!("{}\n", cypher.script("EXPLAIN"));
+            self.fetch(cypher.query("EXPLAIN ")).await?;
+            return Ok(Vec::new());
+        }
+
+        let started = Instant::now();
+        let rows = self.fetch(cypher.query("")).await?;
+        let elapsed = started.elapsed();
+
+        if tracing.plan == Some(PlanMode::Profile) {
+            eprintln!(
+                "// {} rows in {:.1} ms\n{}\n",
+                rows.len(),
+                elapsed.as_secs_f64() * 1000.0,
+                cypher.script("PROFILE")
+            );
+        }
+        if tracing.timing {
+            self.trace
+                .lock()
+                .expect("query trace lock")
+                .timings
+                .push(QueryTiming {
+                    statement: cypher.summary(80),
+                    rows: rows.len(),
+                    elapsed,
+                });
+        }
+
+        Ok(rows)
+    }
+
+    /// Execute a query on the next read endpoint and collect its rows
+    ///
+    /// With `query_timeout_secs` set, a query that has not returned all rows in time is
+    /// abandoned and its connection dropped, which makes the server terminate the
+    /// transaction. Writes are not limited so large imports always finish.
+    async fn fetch(&self, query: Query) -> Result<Vec<Row>> {
+        let graph = self.read_graph();
+        let started = Instant::now();
+        let execution = async move {
+            let mut result = graph.execute(query).await?;
+            let mut rows = Vec::new();
+            while let Some(row) = result.next().await? {
+                rows.push(row);
+            }
+            Ok::<_, neo4rs::Error>(rows)
+        };
+        let rows = match self.query_timeout {
+            Some(limit) => tokio::time::timeout(limit, execution).await.map_err(|_| {
+                anyhow::anyhow!(
+                    "Query cancelled after {}s (query_timeout_secs), try narrowing it",
+                    limit.as_secs()
+                )
+            })?,
+            None => execution.await,
+        };
+        match &rows {
+            Ok(_) => metrics::global().record_query(started.elapsed()),
+            Err(_) => metrics::global().record_neo4j_error(),
+        }
+        Ok(rows?)
+    }
+
+    /// Next read replica in round-robin order, or the primary when none is configured
+    fn read_graph(&self) -> &Graph {
+        if self.readers.is_empty() {
+            return &self.graph;
+        }
+        let index = self.next_reader.fetch_add(1, Ordering::Relaxed) % self.readers.len();
+        &self.readers[index]
+    }
+
     pub async fn test_connection(&self) -> Result<()> {
         let query = Query::new("RETURN 1 as test".to_string());
         let _ = self
@@ -52,111 +332,269 @@ impl Neo4jConnection {
         self.test_connection().await
     }
 
-    pub async fn get_database_stats(&self) -> Result<DatabaseStats> {
-        let mut stats = DatabaseStats::new();
+    /// Dialect of the primary server, probed on first use: `dbms.components()` only
+    /// exists on Neo4j and `SHOW STORAGE INFO` only on Memgraph
+    pub async fn dialect(&self) -> Result<Dialect> {
+        if let Some(dialect) = *self.dialect.lock().expect("dialect lock") {
+            return Ok(dialect);
+        }
+
+        let dialect = if self
+            .probe("CALL dbms.components() YIELD name RETURN name")
+            .await
+        {
+            Dialect::Neo4j
+        } else if self.probe("SHOW STORAGE INFO").await {
+            Dialect::Memgraph
+        } else {
+            // Both probes also fail when the server is unreachable
+            self.test_connection().await?;
+            Dialect::Other
+        };
+        *self.dialect.lock().expect("dialect lock") = Some(dialect);
+        Ok(dialect)
+    }
 
-        let node_count_query = Query::new("MATCH (n) RETURN count(n) as count".to_string());
-        let mut result = self.graph.execute(node_count_query).await?;
-        if let Some(row) = result.next().await? {
-            stats.node_count = row.get::<i64>("count").unwrap_or(0);
+    /// Product, version and edition from `dbms.components()` (Neo4j only) and the
+    /// versions of the APOC and GDS plugins, `None` where unavailable
+    pub async fn server_info(&self) -> Result<ServerInfo> {
+        let mut info = ServerInfo::default();
+        if self.dialect().await? == Dialect::Neo4j {
+            let rows = self
+                .read(Cypher::new(
+                    "CALL dbms.components() YIELD name, versions, edition
+                     RETURN name, versions[0] AS version, edition",
+                ))
+                .await?;
+            if let Some(row) = rows.first() {
+                info.product = row.get("name").ok();
+                info.version = row.get("version").ok();
+                info.edition = row.get("edition").ok();
+            }
         }
+        info.apoc = self.plugin_version("apoc").await;
+        info.gds = self.plugin_version("gds").await;
+        Ok(info)
+    }
+
+    /// Version of a plugin exposing `<prefix>.version()`, `None` when not installed
+    async fn plugin_version(&self, prefix: &str) -> Option<String> {
+        let cypher = Cypher::new(format!("RETURN {}.version() AS version", prefix));
+        let rows = self.read(cypher).await.ok()?;
+        rows.first()?.get("version").ok()
+    }
 
-        let rel_count_query = Query::new("MATCH ()-[r]->() RETURN count(r) as count".to_string());
-        let mut result = self.graph.execute(rel_count_query).await?;
-        if let Some(row) = result.next().await? {
-            stats.relationship_count = row.get::<i64>("count").unwrap_or(0);
+    /// Whether the primary server runs `statement` without an error
+    async fn probe(&self, statement: &str) -> bool {
+        match self.graph.execute(Query::new(statement.to_string())).await {
+            Ok(mut result) => result.next().await.is_ok(),
+            Err(_) => false,
         }
+    }
+
+    /// Create database `name` through the `system` database if it does not exist yet
+    /// (requires Neo4j Enterprise or Aura)
+    pub async fn create_database(config: &crate::config::Config, name: &str) -> Result<()> {
+        let mut system = config.clone();
+        system.neo4j_database = Some("system".to_string());
+        let graph = Self::connect(&system, &config.neo4j_uri).await?;
+
+        graph
+            .run(
+                Query::new("CREATE DATABASE $name IF NOT EXISTS WAIT".to_string())
+                    .param("name", name),
+            )
+            .await
+            .with_context(|| format!("Failed to create database '{}'", name))?;
+        Ok(())
+    }
 
-        let labels = ["Binary", "Function", "String", "Library"];
-        for label in labels {
-            let query = Query::new(format!("MATCH (n:{}) RETURN count(n) as count", label));
-            let mut result = self.graph.execute(query).await?;
-            if let Some(row) = result.next().await? {
-                let count = row.get::<i64>("count").unwrap_or(0);
-                stats.label_counts.insert(label.to_string(), count);
+    /// Node and relationship totals, counts per label and relationship type, and the
+    /// most imported libraries
+    ///
+    /// Counts come from `apoc.meta.stats` when APOC is installed, otherwise from
+    /// single-label and single-type `count()` queries that Neo4j answers from its count
+    /// store without scanning the graph. Results are reused for [`STATS_CACHE_TTL`].
+    pub async fn get_database_stats(&self) -> Result<DatabaseStats> {
+        if let Some((taken, stats)) = self.stats_cache.lock().expect("stats cache lock").as_ref() {
+            if taken.elapsed() < STATS_CACHE_TTL {
+                return Ok(stats.clone());
             }
         }
 
+        let mut stats = match self.apoc_stats().await {
+            Ok(stats) => stats,
+            Err(_) => self.count_store_stats().await?,
+        };
+        for label in STATS_LABELS {
+            stats.label_counts.entry(label.to_string()).or_insert(0);
+        }
+
+        let libraries = self
+            .read(Cypher::new(
+                "MATCH (b:Binary)-[:IMPORTS_LIBRARY]->(l:Library)
+                 RETURN l.name AS name, count(b) AS binaries
+                 ORDER BY binaries DESC, name
+                 LIMIT 10",
+            ))
+            .await?;
+        stats.top_libraries = libraries
+            .into_iter()
+            .filter_map(|row| {
+                Some((
+                    row.get::<String>("name").ok()?,
+                    row.get::<i64>("binaries").unwrap_or(0),
+                ))
+            })
+            .collect();
+
+        *self.stats_cache.lock().expect("stats cache lock") = Some((Instant::now(), stats.clone()));
         Ok(stats)
     }
 
+    /// Counts kept by APOC's metadata statistics, fails when APOC is not installed
+    async fn apoc_stats(&self) -> Result<DatabaseStats> {
+        let rows = self
+            .read(Cypher::new(
+                "CALL apoc.meta.stats() YIELD nodeCount, relCount, labels, relTypesCount
+                 RETURN nodeCount, relCount,
+                        keys(labels) AS label_names,
+                        [name IN keys(labels) | labels[name]] AS label_counts,
+                        keys(relTypesCount) AS type_names,
+                        [name IN keys(relTypesCount) | relTypesCount[name]] AS type_counts",
+            ))
+            .await?;
+        let row = rows
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("apoc.meta.stats returned no row"))?;
+
+        let mut stats = DatabaseStats::new();
+        stats.node_count = row.get::<i64>("nodeCount").unwrap_or(0);
+        stats.relationship_count = row.get::<i64>("relCount").unwrap_or(0);
+        let counts = |names: &str, counts: &str| -> std::collections::HashMap<String, i64> {
+            let names = row.get::<Vec<String>>(names).unwrap_or_default();
+            let counts = row.get::<Vec<i64>>(counts).unwrap_or_default();
+            names.into_iter().zip(counts).collect()
+        };
+        stats.label_counts = counts("label_names", "label_counts");
+        stats.relationship_counts = counts("type_names", "type_counts");
+        Ok(stats)
+    }
+
+    /// Counts answered from the count store, one query per label and relationship type
+    async fn count_store_stats(&self) -> Result<DatabaseStats> {
+        let mut stats = DatabaseStats::new();
+        stats.node_count = self.count("MATCH (n) RETURN count(n) AS count").await?;
+        stats.relationship_count = self
+            .count("MATCH ()-[r]->() RETURN count(r) AS count")
+            .await?;
+
+        let labels = self
+            .read(Cypher::new("CALL db.labels() YIELD label RETURN label"))
+            .await?;
+        for label in labels
+            .iter()
+            .filter_map(|row| row.get::<String>("label").ok())
+        {
+            let count = self
+                .count(&format!(
+                    "MATCH (n:`{}`) RETURN count(n) AS count",
+                    label.replace('`', "``")
+                ))
+                .await?;
+            stats.label_counts.insert(label, count);
+        }
+
+        let types = self
+            .read(Cypher::new(
+                "CALL db.relationshipTypes() YIELD relationshipType RETURN relationshipType",
+            ))
+            .await?;
+        for rel_type in types
+            .iter()
+            .filter_map(|row| row.get::<String>("relationshipType").ok())
+        {
+            let count = self
+                .count(&format!(
+                    "MATCH ()-[r:`{}`]->() RETURN count(r) AS count",
+                    rel_type.replace('`', "``")
+                ))
+                .await?;
+            stats.relationship_counts.insert(rel_type, count);
+        }
+
+        Ok(stats)
+    }
+
+    async fn count(&self, statement: &str) -> Result<i64> {
+        let rows = self.read(Cypher::new(statement)).await?;
+        Ok(rows
+            .into_iter()
+            .next()
+            .and_then(|row| row.get::<i64>("count").ok())
+            .unwrap_or(0))
+    }
+
     pub async fn execute_query(
         &self,
         cypher: &str,
         params: Option<serde_json::Value>,
     ) -> Result<Vec<serde_json::Value>> {
-        let mut query = Query::new(cypher.to_string());
-
-        if let Some(serde_json::Value::Object(map)) = params {
-            for (key, value) in map {
-                query = match value {
-                    serde_json::Value::String(s) => query.param(&key, s),
-                    serde_json::Value::Number(n) => {
-                        if let Some(i) = n.as_i64() {
-                            query.param(&key, i)
-                        } else if let Some(f) = n.as_f64() {
-                            query.param(&key, f)
-                        } else {
-                            query
-                        }
-                    }
-                    serde_json::Value::Bool(b) => query.param(&key, b),
-                    _ => query,
-                };
-            }
-        }
+        let query = match params {
+            Some(serde_json::Value::Object(map)) => bind_params(cypher, map),
+            _ => Cypher::new(cypher),
+        };
 
-        let mut result = self.graph.execute(query).await?;
-        let mut rows = Vec::new();
-
-        while let Some(row) = result.next().await? {
-            let mut json_row = serde_json::Map::new();
-
-            if let Ok(node) = row.get::<neo4rs::Node>("n") {
-                let mut node_map = serde_json::Map::new();
-                let labels: Vec<String> = node.labels().iter().map(|s| s.to_string()).collect();
-                node_map.insert("labels".to_string(), serde_json::json!(labels));
-                if let Ok(uid) = node.get::<String>("uid") {
-                    node_map.insert("uid".to_string(), serde_json::json!(uid));
-                }
-                if let Ok(name) = node.get::<String>("name") {
-                    node_map.insert("name".to_string(), serde_json::json!(name));
-                }
-                if let Ok(hash) = node.get::<String>("hash") {
-                    node_map.insert("hash".to_string(), serde_json::json!(hash));
-                }
-                if let Ok(address) = node.get::<String>("address") {
-                    node_map.insert("address".to_string(), serde_json::json!(address));
-                }
-                if let Ok(value) = node.get::<String>("value") {
-                    node_map.insert("value".to_string(), serde_json::json!(value));
-                }
-                json_row.insert("node".to_string(), serde_json::Value::Object(node_map));
-            }
+        let rows = self.read(query).await?;
+        Ok(rows.iter().filter_map(export_record).collect())
+    }
 
-            if let Ok(rel_type) = row.get::<String>("rel_type") {
-                json_row.insert("relationship_type".to_string(), serde_json::json!(rel_type));
+    /// Run a read query and hand each row to `each` as it arrives instead of collecting
+    /// them, for exports of the whole graph; returns the number of rows
+    ///
+    /// Not limited by `query_timeout_secs`, like writes, so large exports finish.
+    pub async fn for_each_row(
+        &self,
+        cypher: Cypher,
+        mut each: impl FnMut(Row) -> Result<()>,
+    ) -> Result<usize> {
+        let started = Instant::now();
+        let mut count = 0;
+        let streamed = async {
+            let mut rows = self.read_graph().execute(cypher.query("")).await?;
+            while let Some(row) = rows.next().await? {
+                each(row)?;
+                count += 1;
             }
-
-            if let Ok(target) = row.get::<neo4rs::Node>("m") {
-                let mut target_map = serde_json::Map::new();
-                let labels: Vec<String> = target.labels().iter().map(|s| s.to_string()).collect();
-                target_map.insert("labels".to_string(), serde_json::json!(labels));
-                if let Ok(uid) = target.get::<String>("uid") {
-                    target_map.insert("uid".to_string(), serde_json::json!(uid));
-                }
-                if let Ok(name) = target.get::<String>("name") {
-                    target_map.insert("name".to_string(), serde_json::json!(name));
+            Ok::<_, anyhow::Error>(())
+        };
+        match streamed.await {
+            Ok(()) => metrics::global().record_query(started.elapsed()),
+            Err(e) => {
+                if e.is::<neo4rs::Error>() {
+                    metrics::global().record_neo4j_error();
                 }
-                json_row.insert("target".to_string(), serde_json::Value::Object(target_map));
+                return Err(e);
             }
+        }
+        Ok(count)
+    }
 
-            if !json_row.is_empty() {
-                rows.push(serde_json::Value::Object(json_row));
-            }
+    /// Run an arbitrary read query, returning each row as a JSON object keyed by column
+    pub async fn execute_records(
+        &self,
+        cypher: &str,
+        params: serde_json::Map<String, serde_json::Value>,
+    ) -> Result<Vec<serde_json::Map<String, serde_json::Value>>> {
+        let result = self.read(bind_params(cypher, params)).await?;
+
+        let mut records = Vec::new();
+        for row in result {
+            records.push(row.to_strict::<serde_json::Map<String, serde_json::Value>>()?);
         }
 
-        Ok(rows)
+        Ok(records)
     }
 
     pub async fn execute_write(&self, cypher: &str) -> Result<()> {
@@ -173,3 +611,79 @@ impl Neo4jConnection {
         Ok(())
     }
 }
+
+/// Node `n`, relationship type `rel_type` and target `m` of an export row as the
+/// `{node, relationship_type, target}` record of `database export`, `None` for rows
+/// without any of them
+pub fn export_record(row: &Row) -> Option<serde_json::Value> {
+    let mut json_row = serde_json::Map::new();
+
+    if let Ok(node) = row.get::<neo4rs::Node>("n") {
+        let mut node_map = serde_json::Map::new();
+        let labels: Vec<String> = node.labels().iter().map(|s| s.to_string()).collect();
+        node_map.insert("labels".to_string(), serde_json::json!(labels));
+        if let Ok(uid) = node.get::<String>("uid") {
+            node_map.insert("uid".to_string(), serde_json::json!(uid));
+        }
+        if let Ok(name) = node.get::<String>("name") {
+            node_map.insert("name".to_string(), serde_json::json!(name));
+        }
+        if let Ok(hash) = node.get::<String>("hash") {
+            node_map.insert("hash".to_string(), serde_json::json!(hash));
+        }
+        if let Ok(address) = node.get::<String>("address") {
+            node_map.insert("address".to_string(), serde_json::json!(address));
+        }
+        if let Ok(value) = node.get::<String>("value") {
+            node_map.insert("value".to_string(), serde_json::json!(value));
+        }
+        json_row.insert("node".to_string(), serde_json::Value::Object(node_map));
+    }
+
+    if let Ok(rel_type) = row.get::<String>("rel_type") {
+        json_row.insert("relationship_type".to_string(), serde_json::json!(rel_type));
+    }
+
+    if let Ok(target) = row.get::<neo4rs::Node>("m") {
+        let mut target_map = serde_json::Map::new();
+        let labels: Vec<String> = target.labels().iter().map(|s| s.to_string()).collect();
+        target_map.insert("labels".to_string(), serde_json::json!(labels));
+        if let Ok(uid) = target.get::<String>("uid") {
+            target_map.insert("uid".to_string(), serde_json::json!(uid));
+        }
+        if let Ok(name) = target.get::<String>("name") {
+            target_map.insert("name".to_string(), serde_json::json!(name));
+        }
+        if let Ok(hash) = target.get::<String>("hash") {
+            target_map.insert("hash".to_string(), serde_json::json!(hash));
+        }
+        json_row.insert("target".to_string(), serde_json::Value::Object(target_map));
+    }
+
+    if json_row.is_empty() {
+        return None;
+    }
+    Some(serde_json::Value::Object(json_row))
+}
+
+/// Query with scalar JSON parameters bound; other values are skipped
+fn bind_params(cypher: &str, params: serde_json::Map<String, serde_json::Value>) -> Cypher {
+    let mut query = Cypher::new(cypher);
+    for (key, value) in params {
+        query = match value {
+            serde_json::Value::String(s) => query.param(&key, s),
+            serde_json::Value::Number(n) => {
+                if let Some(i) = n.as_i64() {
+                    query.param(&key, i)
+                } else if let Some(f) = n.as_f64() {
+                    query.param(&key, f)
+                } else {
+                    query
+                }
+            }
+            serde_json::Value::Bool(b) => query.param(&key, b),
+            _ => query,
+        };
+    }
+    query
+}
@@ -1,98 +1,161 @@
 use anyhow::Result;
-use neo4rs::query;
+use neo4rs::{query, BoltType, Query};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::time::Instant;
+use tokio::task::JoinSet;
 
+use super::autotune::BatchTuner;
+use super::connection::Cypher;
 use super::Neo4jConnection;
-use crate::models::{Binary, Function, Library, StringNode, StringSearchHit};
-
-#[derive(Debug, Clone)]
-pub struct ImportStatistics {
-    pub binaries: usize,
-    pub functions: usize,
-    pub strings: usize,
-    pub libraries: usize,
-    pub calls_relationships: usize,
-}
+use crate::models::{
+    Annotations, ApiSurface, Architecture, Binary, BinaryAnnotation, BinaryCounts, BinaryEmbedding,
+    BinaryRelation, CallGraphLink, Calls, CryptoHit, EdgeSource, ElfInfo, EmbeddingAlgorithm,
+    EmbeddingRun, EntryKind, FeatureCell, FeatureSpace, Function, FunctionAnnotation,
+    FunctionChunk, FunctionInfo, FunctionMetrics, FunctionPrototype, FunctionSort, GraphEdge,
+    HashedApiCall, ImportGroup, IndicatorHit, IndicatorRelation, Library, LibraryApiUse, MachOInfo,
+    MnemonicHistogram, NamedObject, NeighborHit, NetworkIndicator, ObjectHit, ObjectKind,
+    Parameter, PeInfo, PseudocodeHit, References, Resource, RuntimeApiCall, SearchHit, SearchKind,
+    Section, SharedApiHit, StringHit, StringNode, StringOccurrence, StringSearchHit, StringSource,
+    UnresolvedCall, VTable, XrefDirection, ANNOTATIONS_VERSION,
+};
+use crate::utils::api_names::canonical_api_name;
+use crate::utils::demangle::demangle;
+use crate::utils::uid::{
+    chunk_uid, data_uid, format_address, import_uid, normalize_address, parse_address,
+    relative_address, unresolved_call_uid,
+};
+
+/// Rows written per UNWIND statement unless `batch_size` is configured
+pub const DEFAULT_BATCH_SIZE: usize = 1000;
+
+/// GDS in-memory graph projected by `generate_embeddings`
+const EMBEDDING_GRAPH: &str = "binaryx-embeddings";
 
 #[derive(Clone)]
 pub struct GraphImporter {
     connection: Neo4jConnection,
+    batch_size: usize,
+    tuner: Option<BatchTuner>,
 }
 
 impl GraphImporter {
     pub fn new(connection: Neo4jConnection) -> Self {
-        Self { connection }
-    }
-
-    pub async fn get_statistics_async(&self) -> Result<ImportStatistics> {
-        let mut stats = ImportStatistics {
-            binaries: 0,
-            functions: 0,
-            strings: 0,
-            libraries: 0,
-            calls_relationships: 0,
-        };
-
-        // Count binaries
-        let binary_query = "MATCH (b:Binary) RETURN count(b) as count";
-        let mut result = self.connection.graph().execute(query(binary_query)).await?;
-        if let Some(row) = result.next().await? {
-            stats.binaries = row.get::<i64>("count").unwrap_or(0) as usize;
-        }
-
-        // Count functions
-        let function_query = "MATCH (f:Function) RETURN count(f) as count";
-        let mut result = self
-            .connection
-            .graph()
-            .execute(query(function_query))
-            .await?;
-        if let Some(row) = result.next().await? {
-            stats.functions = row.get::<i64>("count").unwrap_or(0) as usize;
-        }
-
-        // Count strings
-        let string_query = "MATCH (s:String) RETURN count(s) as count";
-        let mut result = self.connection.graph().execute(query(string_query)).await?;
-        if let Some(row) = result.next().await? {
-            stats.strings = row.get::<i64>("count").unwrap_or(0) as usize;
-        }
-
-        // Count libraries
-        let library_query = "MATCH (l:Library) RETURN count(l) as count";
-        let mut result = self
-            .connection
-            .graph()
-            .execute(query(library_query))
-            .await?;
-        if let Some(row) = result.next().await? {
-            stats.libraries = row.get::<i64>("count").unwrap_or(0) as usize;
+        Self {
+            connection,
+            batch_size: DEFAULT_BATCH_SIZE,
+            tuner: None,
         }
+    }
 
-        // Count CALLS relationships
-        let calls_query = "MATCH ()-[r:CALLS]->() RETURN count(r) as count";
-        let mut result = self.connection.graph().execute(query(calls_query)).await?;
-        if let Some(row) = result.next().await? {
-            stats.calls_relationships = row.get::<i64>("count").unwrap_or(0) as usize;
-        }
+    /// Rows written per statement by every batched write (functions, strings, calls,
+    /// symbols, metrics, pruning)
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
 
-        Ok(stats)
+    /// Size function, string and call batches from measured latency instead of
+    /// `batch_size`
+    pub fn with_tuner(mut self, tuner: Option<BatchTuner>) -> Self {
+        self.tuner = tuner;
+        self
     }
 
     pub fn connection(&self) -> &Neo4jConnection {
         &self.connection
     }
 
+    /// Run the statement built by `statement` over `items`, one chunk at a time
+    ///
+    /// With a tuner, chunks are sized by it and, when `parallel` is set because chunks
+    /// touch disjoint nodes, several run at once.
+    async fn run_batches<T>(
+        &self,
+        items: &[T],
+        parallel: bool,
+        statement: impl Fn(&[T]) -> Query,
+    ) -> Result<()> {
+        let mut rest = items;
+        while !rest.is_empty() {
+            let (size, concurrency) = match &self.tuner {
+                Some(tuner) if parallel => (tuner.batch_size(), tuner.concurrency()),
+                Some(tuner) => (tuner.batch_size(), 1),
+                None => (self.batch_size, 1),
+            };
+
+            let started = Instant::now();
+            let mut writes = JoinSet::new();
+            let mut rows = 0;
+            while writes.len() < concurrency && !rest.is_empty() {
+                let (chunk, tail) = rest.split_at(size.min(rest.len()));
+                rest = tail;
+                rows += chunk.len();
+                let graph = self.connection.graph().clone();
+                let statement = statement(chunk);
+                writes.spawn(async move { graph.run(statement).await });
+            }
+            let batches = writes.len();
+            while let Some(result) = writes.join_next().await {
+                result??;
+            }
+
+            if let Some(tuner) = &self.tuner {
+                tuner.record(batches, rows, started.elapsed());
+            }
+        }
+        Ok(())
+    }
+
     pub async fn import_binary(&self, binary: &Binary) -> Result<()> {
         let query_str = "
             MERGE (b:Binary {hash: $hash})
             SET b.filename = $filename,
+                b.md5 = $md5,
+                b.sha1 = $sha1,
+                b.ssdeep = $ssdeep,
+                b.tlsh = $tlsh,
                 b.file_path = $file_path,
                 b.file_size = $file_size,
                 b.format = $format,
-                b.arch = $arch
+                b.arch = $arch,
+                b.bitness = $bitness,
+                b.image_base = $image_base,
+                b.compiler = $compiler,
+                b.packer = $packer,
+                b.family = $family,
+                b.linker_version = $linker_version,
+                b.pdb_path = $pdb_path,
+                b.timestamp = $timestamp,
+                b.compiled_at = CASE WHEN $compiled_at IS NULL THEN null
+                                     ELSE datetime($compiled_at) END,
+                b.imphash = $imphash,
+                b.rich_header_hash = $rich_header_hash,
+                b.entry_point = $entry_point,
+                b.subsystem = $subsystem,
+                b.signer = $signer,
+                b.interpreter = $interpreter,
+                b.soname = $soname,
+                b.needed_libraries = $needed_libraries,
+                b.symbol_versions = $symbol_versions,
+                b.load_commands = $load_commands,
+                b.team_id = $team_id,
+                b.rpaths = $rpaths,
+                b.section_names = $section_names,
+                b.section_sizes = $section_sizes,
+                b.section_entropy = $section_entropy,
+                b.max_entropy = $max_entropy,
+                b.overlay_size = $overlay_size,
+                b.likely_packed = $likely_packed,
+                b.first_imported = coalesce(b.first_imported, b.imported_at, datetime()),
+                b.last_updated = datetime()
+            REMOVE b.imported_at
         ";
 
+        let pe_info = binary.pe_info.clone().unwrap_or_default();
+        let elf_info = binary.elf_info.clone().unwrap_or_default();
+        let macho_info = binary.macho_info.clone().unwrap_or_default();
+
         let format_str = format!("{:?}", binary.format);
 
         self.connection
@@ -100,14 +163,300 @@ impl GraphImporter {
             .run(
                 query(query_str)
                     .param("hash", binary.hash.as_str())
+                    .param("md5", binary.md5.as_deref())
+                    .param("sha1", binary.sha1.as_deref())
+                    .param("ssdeep", binary.ssdeep.as_deref())
+                    .param("tlsh", binary.tlsh.as_deref())
                     .param("filename", binary.filename.as_str())
                     .param("file_path", binary.file_path.as_str())
                     .param("file_size", binary.file_size as i64)
                     .param("format", format_str.as_str())
-                    .param("arch", binary.arch.as_str()),
+                    .param("arch", binary.arch.as_str())
+                    .param("bitness", binary.bitness.map(i64::from))
+                    .param("image_base", binary.image_base.as_deref())
+                    .param("compiler", binary.compiler.as_deref())
+                    .param("packer", binary.packer.as_deref())
+                    .param("family", binary.family.as_deref())
+                    .param("linker_version", binary.linker_version.as_deref())
+                    .param("pdb_path", binary.pdb_path.as_deref())
+                    .param("timestamp", binary.timestamp.as_deref())
+                    .param("compiled_at", binary.compiled_at.as_deref())
+                    .param("imphash", pe_info.imphash)
+                    .param("rich_header_hash", pe_info.rich_header_hash)
+                    .param("entry_point", pe_info.entry_point)
+                    .param("subsystem", pe_info.subsystem)
+                    .param("signer", pe_info.signer)
+                    .param("interpreter", elf_info.interpreter)
+                    .param("soname", elf_info.soname)
+                    .param("needed_libraries", elf_info.needed)
+                    .param("symbol_versions", elf_info.symbol_versions)
+                    .param("load_commands", macho_info.load_commands)
+                    .param("team_id", macho_info.team_id)
+                    .param("rpaths", macho_info.rpaths)
+                    .param(
+                        "section_names",
+                        binary
+                            .sections
+                            .iter()
+                            .map(|s| s.name.clone())
+                            .collect::<Vec<_>>(),
+                    )
+                    .param(
+                        "section_sizes",
+                        binary
+                            .sections
+                            .iter()
+                            .map(|s| s.size.map(|size| size as i64).unwrap_or(-1))
+                            .collect::<Vec<_>>(),
+                    )
+                    .param(
+                        "section_entropy",
+                        binary
+                            .sections
+                            .iter()
+                            .map(|s| s.entropy)
+                            .collect::<Vec<_>>(),
+                    )
+                    .param("max_entropy", binary.max_entropy())
+                    .param("overlay_size", binary.overlay_size.map(|size| size as i64))
+                    .param("likely_packed", binary.likely_packed),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Functions, strings and internal calls currently linked to a binary, or `None`
+    /// if the binary has not been imported
+    pub async fn query_binary_subgraph(&self, binary_hash: &str) -> Result<Option<BinarySubgraph>> {
+        let query_str = "
+            MATCH (b:Binary {hash: $binary_hash})
+            OPTIONAL MATCH (b)-[:CONTAINS]->(f:Function)
+            WITH b, collect(DISTINCT f.uid) AS functions
+            OPTIONAL MATCH (b)-[:CONTAINS_STRING]->(s:String)
+            WITH b, functions, collect(DISTINCT s.uid) AS strings
+            OPTIONAL MATCH (b)-[:CONTAINS]->(caller:Function)-[:CALLS]->(callee:Function)
+            RETURN functions, strings,
+                   [edge IN collect(DISTINCT [caller.uid, callee.uid]) WHERE edge[1] IS NOT NULL] AS calls
+        ";
+
+        let rows = self
+            .connection
+            .read(Cypher::new(query_str).param("binary_hash", binary_hash))
+            .await?;
+        let Some(row) = rows.into_iter().next() else {
+            return Ok(None);
+        };
+
+        let calls = row
+            .get::<Vec<Vec<String>>>("calls")
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|edge| match <[String; 2]>::try_from(edge) {
+                Ok([caller, callee]) => Some((caller, callee)),
+                Err(_) => None,
+            })
+            .collect();
+
+        Ok(Some(BinarySubgraph {
+            functions: row
+                .get::<Vec<String>>("functions")
+                .unwrap_or_default()
+                .into_iter()
+                .collect(),
+            strings: row
+                .get::<Vec<String>>("strings")
+                .unwrap_or_default()
+                .into_iter()
+                .collect(),
+            calls,
+        }))
+    }
+
+    /// Delete the given part of a binary's subgraph: CALLS edges, CONTAINS and
+    /// CONTAINS_STRING edges, and the functions and strings no other binary links to
+    pub async fn prune_binary_subgraph(
+        &self,
+        binary_hash: &str,
+        stale: &BinarySubgraph,
+    ) -> Result<()> {
+        let calls: Vec<Vec<&str>> = stale
+            .calls
+            .iter()
+            .map(|(caller, callee)| vec![caller.as_str(), callee.as_str()])
+            .collect();
+        for chunk in calls.chunks(self.batch_size) {
+            self.connection
+                .graph()
+                .run(
+                    query(
+                        "UNWIND $calls AS call
+                         MATCH (:Function {uid: call[0]})-[r:CALLS]->(:Function {uid: call[1]})
+                         DELETE r",
+                    )
+                    .param("calls", chunk.to_vec()),
+                )
+                .await?;
+        }
+
+        let functions: Vec<&str> = stale.functions.iter().map(String::as_str).collect();
+        for chunk in functions.chunks(self.batch_size) {
+            self.connection
+                .graph()
+                .run(
+                    query(
+                        "MATCH (:Binary {hash: $binary_hash})-[r:CONTAINS]->(f:Function)
+                         WHERE f.uid IN $uids
+                         DELETE r
+                         WITH DISTINCT f
+                         WHERE NOT (f)<-[:CONTAINS]-(:Binary)
+                         DETACH DELETE f",
+                    )
+                    .param("binary_hash", binary_hash)
+                    .param("uids", chunk.to_vec()),
+                )
+                .await?;
+        }
+
+        let strings: Vec<&str> = stale.strings.iter().map(String::as_str).collect();
+        for chunk in strings.chunks(self.batch_size) {
+            self.connection
+                .graph()
+                .run(
+                    query(
+                        "MATCH (:Binary {hash: $binary_hash})-[r:CONTAINS_STRING]->(s:String)
+                         WHERE s.uid IN $uids
+                         DELETE r
+                         WITH DISTINCT s
+                         WHERE NOT (s)<-[:CONTAINS_STRING]-(:Binary)
+                         DETACH DELETE s",
+                    )
+                    .param("binary_hash", binary_hash)
+                    .param("uids", chunk.to_vec()),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Hashes of the binaries selected by `scope`
+    pub async fn scoped_binaries(&self, scope: &ClearScope<'_>) -> Result<Vec<String>> {
+        let query_str = "
+            MATCH (b:Binary)
+            WHERE ($hash IS NULL OR b.hash = toLower($hash))
+              AND ($family IS NULL OR toLower(b.family) = toLower($family))
+            WITH b, coalesce(b.last_updated, b.imported_at) AS updated
+            WHERE ($before IS NULL OR updated IS NULL OR updated < datetime($before))
+              AND ($days IS NULL OR updated IS NULL
+                   OR updated < datetime() - duration({days: $days}))
+            RETURN b.hash AS hash
+        ";
+
+        let (before, days) = match scope.older_than {
+            Some(ImportAge::Before(date)) => (Some(date), None),
+            Some(ImportAge::Days(days)) => (None, Some(days)),
+            None => (None, None),
+        };
+        let rows = self
+            .connection
+            .read(
+                Cypher::new(query_str)
+                    .param("hash", scope.binary)
+                    .param("family", scope.family)
+                    .param("before", before)
+                    .param("days", days),
             )
             .await?;
 
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| row.get::<String>("hash").ok())
+            .collect())
+    }
+
+    /// Nodes that deleting `hashes` with [`GraphImporter::delete_binaries`] removes
+    pub async fn count_binary_deletion(&self, hashes: &[String]) -> Result<ClearCounts> {
+        if hashes.is_empty() {
+            return Ok(ClearCounts::default());
+        }
+
+        // Functions, strings, libraries, indicators, ... go with the binaries
+        // unless another binary still links to them
+        let query_str = "
+            MATCH (b:Binary) WHERE b.hash IN $hashes
+            OPTIONAL MATCH (b)-[:CONTAINS]->(f:Function)
+            WHERE all(h IN [(f)<-[:CONTAINS]-(o:Binary) | o.hash] WHERE h IN $hashes)
+            WITH collect(DISTINCT b) AS binaries, count(DISTINCT f) AS functions
+            UNWIND binaries AS b
+            OPTIONAL MATCH (b)--(n)
+            WHERE NOT n:Binary AND NOT (b)-[:CONTAINS]->(n)
+              AND all(h IN [(n)--(o:Binary) | o.hash] WHERE h IN $hashes)
+            RETURN size(binaries) AS binaries, functions,
+                   count(DISTINCT CASE WHEN n:String THEN n END) AS strings,
+                   count(DISTINCT CASE WHEN n:String THEN null ELSE n END) AS other
+        ";
+
+        let rows = self
+            .connection
+            .read(Cypher::new(query_str).param("hashes", hashes.to_vec()))
+            .await?;
+        let Some(row) = rows.into_iter().next() else {
+            return Ok(ClearCounts::default());
+        };
+
+        Ok(ClearCounts {
+            binaries: row.get::<i64>("binaries").unwrap_or(0),
+            functions: row.get::<i64>("functions").unwrap_or(0),
+            strings: row.get::<i64>("strings").unwrap_or(0),
+            other: row.get::<i64>("other").unwrap_or(0),
+        })
+    }
+
+    /// Delete binaries with their functions, and the strings, libraries, imports,
+    /// resources, indicators and data nodes no remaining binary links to
+    pub async fn delete_binaries(&self, hashes: &[String]) -> Result<()> {
+        for chunk in hashes.chunks(100) {
+            let chunk = chunk.to_vec();
+            self.connection
+                .graph()
+                .run(
+                    query(
+                        "MATCH (b:Binary)-[:CONTAINS]->(f:Function)
+                         WHERE b.hash IN $hashes
+                           AND all(h IN [(f)<-[:CONTAINS]-(o:Binary) | o.hash] WHERE h IN $hashes)
+                         OPTIONAL MATCH (f)-[:REFERENCES]->(d:Data)
+                         OPTIONAL MATCH (f)-[:HAS_CHUNK]->(k:Chunk)
+                         WITH collect(DISTINCT f) AS functions,
+                              collect(DISTINCT d) + collect(DISTINCT k) AS data
+                         FOREACH (f IN functions | DETACH DELETE f)
+                         WITH data
+                         UNWIND data AS d
+                         WITH d WHERE NOT (d)<-[:REFERENCES|HAS_CHUNK]-()
+                         DETACH DELETE d",
+                    )
+                    .param("hashes", chunk.clone()),
+                )
+                .await?;
+
+            self.connection
+                .graph()
+                .run(
+                    query(
+                        "MATCH (b:Binary) WHERE b.hash IN $hashes
+                         OPTIONAL MATCH (b)--(n) WHERE NOT n:Binary
+                         WITH collect(DISTINCT b) AS binaries, collect(DISTINCT n) AS neighbors
+                         FOREACH (b IN binaries | DETACH DELETE b)
+                         WITH neighbors
+                         UNWIND neighbors AS n
+                         WITH n WHERE NOT (n)--(:Binary)
+                         DETACH DELETE n",
+                    )
+                    .param("hashes", chunk),
+                )
+                .await?;
+        }
+
         Ok(())
     }
 
@@ -115,9 +464,14 @@ impl GraphImporter {
         let query_str = "
             MERGE (f:Function {uid: $uid})
             SET f.name = $name,
+                f.demangled_name = $demangled_name,
+                f.canonical_name = $canonical_name,
                 f.address = $address,
                 f.type = $type,
-                f.size = $size
+                f.size = $size,
+                f.mnemonic_histogram = $mnemonic_histogram,
+                f.is_library = $is_library,
+                f.signature_match = $signature_match
         ";
 
         let type_str = format!("{:?}", function.r#type);
@@ -128,42 +482,237 @@ impl GraphImporter {
                 query(query_str)
                     .param("uid", function.uid.as_str())
                     .param("name", function.name.as_str())
+                    .param("demangled_name", function.demangled_name.as_deref())
+                    .param("canonical_name", function.canonical_name.as_deref())
                     .param("address", function.address.as_deref().unwrap_or(""))
                     .param("type", type_str.as_str())
-                    .param("size", function.size.map(|s| s as i64).unwrap_or(-1)),
+                    .param("size", function.size.map(|s| s as i64).unwrap_or(-1))
+                    .param(
+                        "mnemonic_histogram",
+                        function.mnemonic_histogram.as_ref().map(|h| h.encode()),
+                    )
+                    .param("is_library", function.is_library)
+                    .param("signature_match", function.signature_match.as_deref()),
             )
             .await?;
 
         Ok(())
     }
 
-    pub async fn import_functions_batch(&self, functions: &[Function]) -> Result<()> {
-        for function in functions {
-            self.import_function(function).await?;
-        }
-        Ok(())
-    }
-
-    pub async fn create_contains_relationship(
+    /// Merge `functions` and link them to their binary with CONTAINS, one statement per
+    /// `batch_size` functions
+    pub async fn import_functions_batch(
         &self,
         binary_hash: &str,
-        function_uid: &str,
+        functions: &[Function],
     ) -> Result<()> {
         let query_str = "
-            MATCH (b:Binary {hash: $binary_hash}), (f:Function {uid: $function_uid})
+            MATCH (b:Binary {hash: $binary_hash})
+            UNWIND $rows AS row
+            MERGE (f:Function {uid: row.uid})
+            SET f.name = row.name,
+                f.demangled_name = row.demangled_name,
+                f.canonical_name = row.canonical_name,
+                f.address = row.address,
+                f.type = row.type,
+                f.size = row.size,
+                f.mnemonic_histogram = row.mnemonic_histogram,
+                f.is_library = row.is_library,
+                f.signature_match = row.signature_match,
+                f.pseudocode = row.pseudocode,
+                f.pseudocode_blob = row.pseudocode_blob,
+                f.calling_convention = row.calling_convention,
+                f.return_type = row.return_type,
+                f.param_types = row.param_types,
+                f.param_names = row.param_names,
+                f.crypto = row.crypto,
+                f.content_hash = row.content_hash
             MERGE (b)-[:CONTAINS]->(f)
         ";
 
+        self.run_batches(functions, false, |chunk| {
+            let rows: Vec<HashMap<&str, BoltType>> = chunk
+                .iter()
+                .map(|function| {
+                    let prototype = function.prototype.as_ref();
+                    HashMap::from([
+                        ("uid", BoltType::from(function.uid.as_str())),
+                        ("name", BoltType::from(function.name.as_str())),
+                        (
+                            "demangled_name",
+                            BoltType::from(function.demangled_name.as_deref()),
+                        ),
+                        (
+                            "canonical_name",
+                            BoltType::from(function.canonical_name.as_deref()),
+                        ),
+                        (
+                            "address",
+                            BoltType::from(function.address.as_deref().unwrap_or("")),
+                        ),
+                        ("type", BoltType::from(format!("{:?}", function.r#type))),
+                        (
+                            "size",
+                            BoltType::from(function.size.map(|s| s as i64).unwrap_or(-1)),
+                        ),
+                        (
+                            "mnemonic_histogram",
+                            BoltType::from(
+                                function.mnemonic_histogram.as_ref().map(|h| h.encode()),
+                            ),
+                        ),
+                        ("is_library", BoltType::from(function.is_library)),
+                        (
+                            "signature_match",
+                            BoltType::from(function.signature_match.as_deref()),
+                        ),
+                        ("pseudocode", BoltType::from(function.pseudocode.as_deref())),
+                        (
+                            "pseudocode_blob",
+                            BoltType::from(function.pseudocode_blob.as_deref()),
+                        ),
+                        (
+                            "calling_convention",
+                            BoltType::from(prototype.and_then(|p| p.calling_convention.as_deref())),
+                        ),
+                        (
+                            "return_type",
+                            BoltType::from(prototype.and_then(|p| p.return_type.as_deref())),
+                        ),
+                        (
+                            "param_types",
+                            BoltType::from(prototype.map(|p| {
+                                p.parameters
+                                    .iter()
+                                    .map(|param| param.r#type.clone())
+                                    .collect::<Vec<_>>()
+                            })),
+                        ),
+                        (
+                            "param_names",
+                            BoltType::from(prototype.map(|p| {
+                                p.parameters
+                                    .iter()
+                                    .map(|param| param.name.clone())
+                                    .collect::<Vec<_>>()
+                            })),
+                        ),
+                        (
+                            "crypto",
+                            BoltType::from(
+                                (!function.crypto.is_empty()).then(|| function.crypto.clone()),
+                            ),
+                        ),
+                        (
+                            "content_hash",
+                            BoltType::from(function.content_hash.as_deref()),
+                        ),
+                    ])
+                })
+                .collect();
+
+            query(query_str)
+                .param("binary_hash", binary_hash)
+                .param("rows", rows)
+        })
+        .await?;
+
+        self.import_chunks(binary_hash, functions).await
+    }
+
+    /// Link `functions` to their chunks with HAS_CHUNK, replacing the chunks of an
+    /// earlier import; Chunk nodes are shared by the functions of a common tail
+    async fn import_chunks(&self, binary_hash: &str, functions: &[Function]) -> Result<()> {
+        let chunks: Vec<(&str, &FunctionChunk)> = functions
+            .iter()
+            .flat_map(|function| {
+                function
+                    .chunks
+                    .iter()
+                    .map(|chunk| (function.uid.as_str(), chunk))
+            })
+            .collect();
+
+        self.run_batches(functions, false, |batch| {
+            let uids: Vec<&str> = batch.iter().map(|function| function.uid.as_str()).collect();
+            query(
+                "UNWIND $uids AS uid
+                 MATCH (:Function {uid: uid})-[old:HAS_CHUNK]->()
+                 DELETE old",
+            )
+            .param("uids", uids)
+        })
+        .await?;
+
+        self.run_batches(&chunks, false, |batch| {
+            let rows: Vec<HashMap<&str, BoltType>> = batch
+                .iter()
+                .map(|(function_uid, chunk)| {
+                    let uid = parse_address(&chunk.start)
+                        .map(|start| chunk_uid(binary_hash, start))
+                        .unwrap_or_default();
+                    HashMap::from([
+                        ("function_uid", BoltType::from(*function_uid)),
+                        ("uid", BoltType::from(uid)),
+                        ("start", BoltType::from(chunk.start.as_str())),
+                        ("size", BoltType::from(chunk.size as i64)),
+                    ])
+                })
+                .collect();
+
+            query(
+                "UNWIND $rows AS row
+                 MATCH (f:Function {uid: row.function_uid})
+                 MERGE (k:Chunk {uid: row.uid})
+                 SET k.start = row.start, k.size = row.size, k.binary_hash = $binary_hash
+                 MERGE (f)-[:HAS_CHUNK]->(k)",
+            )
+            .param("binary_hash", binary_hash)
+            .param("rows", rows)
+        })
+        .await
+    }
+
+    /// Set `entry_kind` on the binary's entry-point functions, clearing the marks of an
+    /// earlier import
+    pub async fn mark_entry_points(
+        &self,
+        binary_hash: &str,
+        entry_points: &[(String, EntryKind)],
+    ) -> Result<()> {
         self.connection
             .graph()
             .run(
-                query(query_str)
-                    .param("binary_hash", binary_hash)
-                    .param("function_uid", function_uid),
+                query(
+                    "MATCH (:Binary {hash: $binary_hash})-[:CONTAINS]->(f:Function)
+                     WHERE f.entry_kind IS NOT NULL
+                     REMOVE f.entry_kind",
+                )
+                .param("binary_hash", binary_hash),
             )
             .await?;
 
-        Ok(())
+        self.run_batches(entry_points, false, |chunk| {
+            let rows: Vec<HashMap<&str, BoltType>> = chunk
+                .iter()
+                .map(|(uid, kind)| {
+                    HashMap::from([
+                        ("uid", BoltType::from(uid.as_str())),
+                        ("entry_kind", BoltType::from(kind.as_str())),
+                    ])
+                })
+                .collect();
+
+            query(
+                "MATCH (b:Binary {hash: $binary_hash})
+                 UNWIND $rows AS row
+                 MATCH (b)-[:CONTAINS]->(f:Function {uid: row.uid})
+                 SET f.entry_kind = row.entry_kind",
+            )
+            .param("binary_hash", binary_hash)
+            .param("rows", rows)
+        })
+        .await
     }
 
     pub async fn create_belongs_to_relationship(
@@ -188,69 +737,138 @@ impl GraphImporter {
         Ok(())
     }
 
-    pub async fn import_string_node(&self, string_node: &StringNode) -> Result<()> {
+    /// Merge string nodes, one statement per `batch_size` strings
+    pub async fn import_strings_batch(&self, strings: &[&StringNode]) -> Result<()> {
         let query_str = "
-            MERGE (s:String {uid: $uid})
-            SET s.value = $value
+            UNWIND $rows AS row
+            MERGE (s:String {uid: row.uid})
+            SET s.value = row.value,
+                s.length = row.length,
+                s.truncated = row.truncated,
+                s.sha256 = row.sha256,
+                s.blob = row.blob
         ";
 
-        self.connection
-            .graph()
-            .run(
-                query(query_str)
-                    .param("uid", string_node.uid.as_str())
-                    .param("value", string_node.value.as_str()),
-            )
-            .await?;
-
-        Ok(())
+        // Each string is its own node, so chunks can be written concurrently
+        self.run_batches(strings, true, |chunk| {
+            let rows: Vec<HashMap<&str, BoltType>> = chunk
+                .iter()
+                .map(|string_node| {
+                    HashMap::from([
+                        ("uid", BoltType::from(string_node.uid.as_str())),
+                        ("value", BoltType::from(string_node.value.as_str())),
+                        ("length", BoltType::from(string_node.length as i64)),
+                        ("truncated", BoltType::from(string_node.truncated)),
+                        ("sha256", BoltType::from(string_node.content_hash())),
+                        ("blob", BoltType::from(string_node.blob.as_deref())),
+                    ])
+                })
+                .collect();
+
+            query(query_str).param("rows", rows)
+        })
+        .await
     }
 
-    pub async fn create_contains_string_relationship(
+    /// Link a binary to its strings with CONTAINS_STRING, one edge per address, one
+    /// statement per `batch_size` occurrences
+    pub async fn create_contains_string_relationships(
         &self,
         binary_hash: &str,
+        occurrences: &[StringOccurrence],
+    ) -> Result<()> {
+        let query_str = "
+            MATCH (b:Binary {hash: $binary_hash})
+            UNWIND $rows AS row
+            MATCH (s:String {uid: row.string_uid})
+            MERGE (b)-[r:CONTAINS_STRING {address: row.address}]->(s)
+            SET r.encoding = row.encoding,
+                r.source = row.source
+        ";
+
+        self.run_batches(occurrences, false, |chunk| {
+            let rows: Vec<HashMap<&str, BoltType>> = chunk
+                .iter()
+                .map(|occurrence| {
+                    HashMap::from([
+                        ("string_uid", BoltType::from(occurrence.string_uid.as_str())),
+                        (
+                            "address",
+                            BoltType::from(occurrence.address.as_deref().unwrap_or("")),
+                        ),
+                        ("encoding", BoltType::from(occurrence.encoding.as_deref())),
+                        (
+                            "source",
+                            BoltType::from(occurrence.source.map(StringSource::as_str)),
+                        ),
+                    ])
+                })
+                .collect();
+
+            query(query_str)
+                .param("binary_hash", binary_hash)
+                .param("rows", rows)
+        })
+        .await
+    }
+
+    /// Link a decoded string to the function that decodes it
+    pub async fn create_decrypted_by_relationship(
+        &self,
         string_uid: &str,
-        address: Option<&str>,
+        function_uid: &str,
     ) -> Result<()> {
         let query_str = "
-            MATCH (b:Binary {hash: $binary_hash}), (s:String {uid: $string_uid})
-            MERGE (b)-[r:CONTAINS_STRING {address: $address}]->(s)
+            MATCH (s:String {uid: $string_uid}), (f:Function {uid: $function_uid})
+            MERGE (s)-[:DECRYPTED_BY]->(f)
         ";
 
         self.connection
             .graph()
             .run(
                 query(query_str)
-                    .param("binary_hash", binary_hash)
                     .param("string_uid", string_uid)
-                    .param("address", address.unwrap_or("")),
+                    .param("function_uid", function_uid),
             )
             .await?;
 
         Ok(())
     }
 
-    pub async fn import_library(&self, library: &Library) -> Result<()> {
+    pub async fn import_resource(&self, resource: &Resource) -> Result<()> {
         let query_str = "
-            MERGE (l:Library {name: $name})
+            MERGE (r:Resource {uid: $uid})
+            SET r.sha256 = $sha256,
+                r.type = $type,
+                r.language = $language,
+                r.size = $size,
+                r.file_type = $file_type
         ";
 
         self.connection
             .graph()
-            .run(query(query_str).param("name", library.name.as_str()))
+            .run(
+                query(query_str)
+                    .param("uid", resource.uid.as_str())
+                    .param("sha256", resource.sha256.as_str())
+                    .param("type", resource.r#type.as_str())
+                    .param("language", resource.language.as_deref())
+                    .param("size", resource.size as i64)
+                    .param("file_type", resource.file_type.as_deref()),
+            )
             .await?;
 
         Ok(())
     }
 
-    pub async fn create_imports_relationship(
+    pub async fn create_has_resource_relationship(
         &self,
         binary_hash: &str,
-        library_name: &str,
+        resource: &Resource,
     ) -> Result<()> {
         let query_str = "
-            MATCH (b:Binary {hash: $binary_hash}), (l:Library {name: $library_name})
-            MERGE (b)-[:IMPORTS_LIBRARY]->(l)
+            MATCH (b:Binary {hash: $binary_hash}), (r:Resource {uid: $resource_uid})
+            MERGE (b)-[:HAS_RESOURCE {name: $name}]->(r)
         ";
 
         self.connection
@@ -258,366 +876,3026 @@ impl GraphImporter {
             .run(
                 query(query_str)
                     .param("binary_hash", binary_hash)
-                    .param("library_name", library_name),
+                    .param("resource_uid", resource.uid.as_str())
+                    .param("name", resource.name.as_deref().unwrap_or("")),
             )
             .await?;
 
         Ok(())
     }
 
-    pub async fn create_imports_function_relationship_with_address(
+    /// Link a binary to the PE image carried in one of its resources
+    ///
+    /// The payload Binary node is created from the resource when it has not been
+    /// imported yet; a later import of the payload fills in its remaining properties.
+    pub async fn create_embeds_relationship(
         &self,
         binary_hash: &str,
-        function_uid: &str,
-        address: &str,
+        resource: &Resource,
     ) -> Result<()> {
         let query_str = "
-            MATCH (b:Binary {hash: $binary_hash}), (f:Function {uid: $function_uid})
-            MERGE (b)-[r:IMPORTS]->(f)
-            SET r.address = $address
+            MATCH (b:Binary {hash: $binary_hash})
+            MERGE (p:Binary {hash: $payload_hash})
+            ON CREATE SET p.filename = $filename,
+                          p.file_size = $size,
+                          p.format = 'PE'
+            MERGE (b)-[e:EMBEDS]->(p)
+            SET e.resource_uid = $resource_uid
         ";
 
+        let filename = resource.name.as_deref().unwrap_or(&resource.sha256);
+
         self.connection
             .graph()
             .run(
                 query(query_str)
                     .param("binary_hash", binary_hash)
-                    .param("function_uid", function_uid)
-                    .param("address", address),
+                    .param("payload_hash", resource.sha256.as_str())
+                    .param("filename", filename)
+                    .param("size", resource.size as i64)
+                    .param("resource_uid", resource.uid.as_str()),
             )
             .await?;
 
         Ok(())
     }
 
-    pub async fn create_calls_relationship(
-        &self,
-        calls: &crate::models::Calls,
-        from_uid: &str,
-        to_uid: &str,
-    ) -> Result<()> {
-        let query_str = "
-            MATCH (from:Function {uid: $from_uid}), (to:Function {uid: $to_uid})
-            MERGE (from)-[r:CALLS]->(to)
-            SET r.offset = $offset,
-                r.call_type = $call_type
-        ";
-
-        let call_type_str = format!("{:?}", calls.call_type);
+    /// Create a DROPS/DOWNLOADS/EMBEDS edge between two Binary nodes
+    ///
+    /// Samples that have not been imported yet are created as Binary nodes holding only
+    /// their hash, which a later import completes.
+    pub async fn create_binary_relation(&self, relation: &BinaryRelation) -> Result<()> {
+        let query_str = format!(
+            "
+            MERGE (p:Binary {{hash: $parent_hash}})
+            ON CREATE SET p.filename = $parent_hash
+            MERGE (c:Binary {{hash: $child_hash}})
+            ON CREATE SET c.filename = $child_hash
+            MERGE (p)-[r:{}]->(c)
+            SET r.source = coalesce($source, r.source)
+        ",
+            relation.relation.label()
+        );
 
         self.connection
             .graph()
             .run(
-                query(query_str)
-                    .param("from_uid", from_uid)
-                    .param("to_uid", to_uid)
-                    .param("offset", calls.offset.as_str())
-                    .param("call_type", call_type_str.as_str()),
+                query(&query_str)
+                    .param("parent_hash", relation.parent_sha256.as_str())
+                    .param("child_hash", relation.child_sha256.as_str())
+                    .param("source", relation.source.as_deref()),
             )
             .await?;
 
         Ok(())
     }
 
-    pub async fn query_functions(
+    /// Create CALLED_AT_RUNTIME edges from a binary to the import functions seen in a trace
+    ///
+    /// APIs are matched by case-insensitive name against the binary's own imports first,
+    /// then against import functions of any binary. Returns the APIs that matched.
+    pub async fn create_runtime_call_relationships(
         &self,
-        pattern: &str,
-        binary: Option<&str>,
-    ) -> Result<Vec<Function>> {
-        let query_str = if let Some(_binary_name) = binary {
-            "
-            MATCH (b:Binary)-[:CONTAINS|IMPORTS]->(f:Function)
-            WHERE (f.name CONTAINS $pattern OR f.uid CONTAINS $pattern)
-              AND (b.filename CONTAINS $binary_name OR b.hash = $binary_name)
-            RETURN f
-            LIMIT 100
-        "
-        } else {
-            "
-            MATCH (f:Function)
-            WHERE f.name CONTAINS $pattern OR f.uid CONTAINS $pattern
-            RETURN f
-            LIMIT 100
-        "
-        };
-
-        let mut query_builder = query(query_str).param("pattern", pattern);
-        if let Some(binary_name) = binary {
-            query_builder = query_builder.param("binary_name", binary_name);
-        }
-
-        let mut result = self.connection.graph().execute(query_builder).await?;
+        binary_hash: &str,
+        calls: &[RuntimeApiCall],
+    ) -> Result<Vec<String>> {
+        let query_str = "
+            MATCH (b:Binary {hash: $binary_hash})
+            UNWIND $calls AS call
+            OPTIONAL MATCH (b)-[:IMPORTS]->(own:Function)
+            WHERE toLower(own.name) = toLower(call.api)
+            WITH b, call, head(collect(own)) AS own
+            OPTIONAL MATCH (other:Function {type: 'Import'})
+            WHERE own IS NULL AND toLower(other.name) = toLower(call.api)
+            WITH b, call, coalesce(own, head(collect(other))) AS imp
+            WHERE imp IS NOT NULL
+            MERGE (b)-[r:CALLED_AT_RUNTIME]->(imp)
+            SET r.count = call.count,
+                r.first_ts = call.first_ts
+            RETURN call.api AS api
+        ";
 
-        let mut functions = Vec::new();
-        while let Some(row) = result.next().await? {
-            if let Ok(node) = row.get::<neo4rs::Node>("f") {
-                let type_str = node
-                    .get::<String>("type")
-                    .unwrap_or_else(|_| "Internal".to_string());
-                let r#type = match type_str.as_str() {
-                    "Import" => crate::models::FunctionType::Import,
-                    "Export" => crate::models::FunctionType::Export,
-                    "Thunk" => crate::models::FunctionType::Thunk,
-                    _ => crate::models::FunctionType::Internal,
-                };
-
-                let function = Function {
-                    uid: node.get::<String>("uid").unwrap_or_default(),
-                    name: node.get::<String>("name").unwrap_or_default(),
-                    address: node.get::<String>("address").ok(),
-                    r#type,
-                    size: node.get::<i64>("size").ok().map(|s| s as u64),
-                };
-                functions.push(function);
+        let mut matched = Vec::new();
+        for chunk in calls.chunks(self.batch_size) {
+            let rows: Vec<HashMap<&str, BoltType>> = chunk
+                .iter()
+                .map(|call| {
+                    HashMap::from([
+                        ("api", BoltType::from(call.api.as_str())),
+                        ("count", BoltType::from(call.count as i64)),
+                        ("first_ts", BoltType::from(call.first_ts.as_deref())),
+                    ])
+                })
+                .collect();
+
+            let mut result = self
+                .connection
+                .graph()
+                .execute(
+                    query(query_str)
+                        .param("binary_hash", binary_hash)
+                        .param("calls", rows),
+                )
+                .await?;
+            while let Some(row) = result.next().await? {
+                if let Ok(api) = row.get::<String>("api") {
+                    matched.push(api);
+                }
             }
         }
 
-        Ok(functions)
+        Ok(matched)
     }
 
-    pub async fn query_strings_fulltext(
+    /// Link functions computing API hashes to the resolved APIs with CALLS edges, and
+    /// the binary to them with IMPORTS, both marked `resolution: 'api_hash'`
+    ///
+    /// The binary's own import of that name is preferred, then the import node of the
+    /// given library (created when missing), then any import node of that name. Calls
+    /// already found by the extractor keep their properties. Returns the linked
+    /// `(caller, callee, hash)` triples.
+    pub async fn create_api_hash_calls(
         &self,
-        lucene_query: &str,
-        binary: Option<&str>,
-        limit: usize,
-    ) -> Result<Vec<StringSearchHit>> {
-        let query_str = if let Some(_binary_name) = binary {
-            "
-            CALL db.index.fulltext.queryNodes('string_value_fulltext', $query) YIELD node, score
-            MATCH (b:Binary)-[:CONTAINS_STRING]->(node)
-            WHERE (b.filename CONTAINS $binary_name OR b.hash = $binary_name)
-            RETURN node AS s, score AS score, count(DISTINCT b) AS sample_count
-            ORDER BY score DESC
-            LIMIT $limit
-        "
-        } else {
-            "
-            CALL db.index.fulltext.queryNodes('string_value_fulltext', $query) YIELD node, score
-            MATCH (b:Binary)-[:CONTAINS_STRING]->(node)
-            RETURN node AS s, score AS score, count(DISTINCT b) AS sample_count
-            ORDER BY score DESC
-            LIMIT $limit
-        "
-        };
+        binary_hash: &str,
+        calls: &[HashedApiCall],
+    ) -> Result<Vec<(String, String, u64)>> {
+        let query_str = "
+            MATCH (b:Binary {hash: $binary_hash})
+            UNWIND $rows AS row
+            MATCH (caller:Function {uid: row.caller})
+            OPTIONAL MATCH (b)-[:IMPORTS]->(own:Function {type: 'Import'})
+            WHERE toLower(own.name) = toLower(row.api)
+            WITH b, caller, row, head(collect(own)) AS own
+            OPTIONAL MATCH (other:Function {type: 'Import'})
+            WHERE own IS NULL AND row.import_uid IS NULL AND toLower(other.name) = toLower(row.api)
+            WITH b, caller, row, coalesce(own.uid, row.import_uid, head(collect(other.uid))) AS uid
+            WHERE uid IS NOT NULL
+            MERGE (imp:Function {uid: uid})
+            ON CREATE SET imp.type = 'Import',
+                          imp.name = row.api,
+                          imp.canonical_name = row.canonical_name,
+                          imp.address = '',
+                          imp.size = -1,
+                          imp.is_library = false
+            FOREACH (library IN CASE WHEN uid = row.import_uid THEN [row.library] ELSE [] END |
+                MERGE (l:Library {name: library})
+                MERGE (imp)-[:BELONGS_TO]->(l)
+                MERGE (b)-[:IMPORTS_LIBRARY]->(l))
+            MERGE (b)-[i:IMPORTS]->(imp)
+            ON CREATE SET i.resolution = 'api_hash'
+            MERGE (caller)-[c:CALLS]->(imp)
+            ON CREATE SET c.call_type = 'Indirect',
+                          c.source = 'heuristic',
+                          c.confidence = $confidence,
+                          c.count = 1,
+                          c.resolution = 'api_hash'
+            SET c.api_hash = row.hash
+            RETURN row.caller AS caller, imp.uid AS callee, row.hash AS hash
+        ";
 
-        let mut query_builder = query(query_str)
-            .param("query", lucene_query)
-            .param("limit", limit as i64);
-        if let Some(binary_name) = binary {
-            query_builder = query_builder.param("binary_name", binary_name);
+        let mut linked = Vec::new();
+        for chunk in calls.chunks(self.batch_size) {
+            let rows: Vec<HashMap<&str, BoltType>> = chunk
+                .iter()
+                .map(|call| {
+                    let library = call.library.as_deref();
+                    HashMap::from([
+                        ("caller", BoltType::from(call.caller_uid.as_str())),
+                        ("hash", BoltType::from(format!("0x{:x}", call.hash))),
+                        ("api", BoltType::from(call.api.as_str())),
+                        ("library", BoltType::from(library)),
+                        (
+                            "import_uid",
+                            BoltType::from(library.map(|library| import_uid(library, &call.api))),
+                        ),
+                        (
+                            "canonical_name",
+                            BoltType::from(canonical_api_name(library.unwrap_or(""), &call.api)),
+                        ),
+                    ])
+                })
+                .collect();
+
+            let mut result = self
+                .connection
+                .graph()
+                .execute(
+                    query(query_str)
+                        .param("binary_hash", binary_hash)
+                        .param("confidence", EdgeSource::Heuristic.default_confidence())
+                        .param("rows", rows),
+                )
+                .await?;
+            while let Some(row) = result.next().await? {
+                let hash = row
+                    .get::<String>("hash")
+                    .ok()
+                    .and_then(|hash| parse_address(&hash));
+                if let (Ok(caller), Ok(callee), Some(hash)) = (
+                    row.get::<String>("caller"),
+                    row.get::<String>("callee"),
+                    hash,
+                ) {
+                    linked.push((caller, callee, hash));
+                }
+            }
         }
 
-        let mut result = self.connection.graph().execute(query_builder).await?;
-        let mut hits = Vec::new();
+        Ok(linked)
+    }
 
-        while let Some(row) = result.next().await? {
-            let node = row.get::<neo4rs::Node>("s")?;
-            let uid = node.get::<String>("uid").unwrap_or_default();
-            let value = node.get::<String>("value").unwrap_or_default();
-            let score = row.get::<f64>("score").unwrap_or(0.0);
-            let sample_count = row.get::<i64>("sample_count").unwrap_or(0);
+    /// Replace the binary's UnresolvedCall nodes with `unresolved`
+    pub async fn record_unresolved_calls(
+        &self,
+        binary_hash: &str,
+        unresolved: &[UnresolvedCall],
+    ) -> Result<()> {
+        self.connection
+            .graph()
+            .run(
+                query(
+                    "MATCH (:Binary {hash: $binary_hash})-[:HAS_UNRESOLVED_CALL]->(u:UnresolvedCall)
+                     DETACH DELETE u",
+                )
+                .param("binary_hash", binary_hash),
+            )
+            .await?;
 
-            hits.push(StringSearchHit {
-                uid,
-                value,
-                score,
-                sample_count,
-            });
+        let query_str = "
+            MATCH (b:Binary {hash: $binary_hash})
+            UNWIND $calls AS call
+            MERGE (u:UnresolvedCall {uid: call.uid})
+            SET u.address = call.address,
+                u.role = call.role,
+                u.count = call.count,
+                u.call_sites = call.call_sites
+            MERGE (b)-[:HAS_UNRESOLVED_CALL]->(u)
+        ";
+
+        for chunk in unresolved.chunks(self.batch_size) {
+            let rows: Vec<HashMap<&str, BoltType>> = chunk
+                .iter()
+                .map(|call| {
+                    HashMap::from([
+                        (
+                            "uid",
+                            BoltType::from(unresolved_call_uid(
+                                binary_hash,
+                                &call.role,
+                                &call.address,
+                            )),
+                        ),
+                        ("address", BoltType::from(call.address.as_str())),
+                        ("role", BoltType::from(call.role.as_str())),
+                        ("count", BoltType::from(call.count as i64)),
+                        ("call_sites", BoltType::from(call.call_sites.clone())),
+                    ])
+                })
+                .collect();
+
+            self.connection
+                .graph()
+                .run(
+                    query(query_str)
+                        .param("binary_hash", binary_hash)
+                        .param("calls", rows),
+                )
+                .await?;
         }
 
-        Ok(hits)
+        Ok(())
     }
 
-    pub async fn query_binary_info(&self, binary_name: &str) -> Result<Option<Binary>> {
+    /// Unresolved call addresses recorded at import, most frequent first
+    pub async fn query_unresolved_calls(
+        &self,
+        binary: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<UnresolvedCall>> {
         let query_str = "
-            MATCH (b:Binary)
-            WHERE b.hash = $binary_name OR b.filename CONTAINS $binary_name
-            RETURN b
-            LIMIT 1
+            MATCH (b:Binary)-[:HAS_UNRESOLVED_CALL]->(u:UnresolvedCall)
+            WHERE ($binary_name IS NULL OR b.filename CONTAINS $binary_name OR b.hash = $binary_name)
+            RETURN u.address AS address, u.role AS role, u.count AS count,
+                   u.call_sites AS call_sites, b.filename AS binary
+            ORDER BY count DESC, binary, address
+            LIMIT $limit
         ";
 
-        let mut result = self
+        let result = self
             .connection
-            .graph()
-            .execute(query(query_str).param("binary_name", binary_name))
+            .read(
+                Cypher::new(query_str)
+                    .param("binary_name", binary)
+                    .param("limit", limit as i64),
+            )
             .await?;
 
+        let mut unresolved = Vec::new();
+        for row in result {
+            unresolved.push(UnresolvedCall {
+                address: row.get::<String>("address").unwrap_or_default(),
+                role: row.get::<String>("role").unwrap_or_default(),
+                count: row.get::<i64>("count").unwrap_or(0).max(0) as u64,
+                call_sites: row.get::<Vec<String>>("call_sites").unwrap_or_default(),
+                binary: row.get::<String>("binary").unwrap_or_default(),
+            });
+        }
+
+        Ok(unresolved)
+    }
+
+    pub async fn create_indicator_relationship(
+        &self,
+        binary_hash: &str,
+        indicator: &NetworkIndicator,
+        relation: IndicatorRelation,
+    ) -> Result<()> {
+        let query_str = format!(
+            "
+            MATCH (b:Binary {{hash: $binary_hash}})
+            MERGE (i:{} {{value: $value}})
+            MERGE (b)-[:{}]->(i)
+        ",
+            indicator.kind.label(),
+            relation.label()
+        );
+
+        self.connection
+            .graph()
+            .run(
+                query(&query_str)
+                    .param("binary_hash", binary_hash)
+                    .param("value", indicator.value.as_str()),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn create_resolves_to_relationship(&self, domain: &str, ip: &str) -> Result<()> {
+        let query_str = "
+            MERGE (d:Domain {value: $domain})
+            MERGE (i:IPAddress {value: $ip})
+            MERGE (d)-[:RESOLVES_TO]->(i)
+        ";
+
+        self.connection
+            .graph()
+            .run(query(query_str).param("domain", domain).param("ip", ip))
+            .await?;
+
+        Ok(())
+    }
+
+    /// Binaries linked to network indicators, filtered by binary and indicator value
+    pub async fn query_indicators(
+        &self,
+        binary: Option<&str>,
+        value: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<IndicatorHit>> {
+        let query_str = "
+            MATCH (b:Binary)-[r:CONTACTS|REFERENCES_IOC]->(i)
+            WHERE (i:Domain OR i:IPAddress OR i:URL)
+              AND ($binary_name IS NULL OR b.filename CONTAINS $binary_name OR b.hash = $binary_name)
+              AND ($value IS NULL OR toLower(i.value) CONTAINS toLower($value))
+            RETURN labels(i)[0] AS kind, i.value AS value, type(r) AS relation,
+                   b.filename AS binary, b.hash AS hash
+            ORDER BY value, binary, relation
+            LIMIT $limit
+        ";
+
+        let result = self
+            .connection
+            .read(
+                Cypher::new(query_str)
+                    .param("binary_name", binary)
+                    .param("value", value)
+                    .param("limit", limit as i64),
+            )
+            .await?;
+
+        let mut hits = Vec::new();
+        for row in result {
+            hits.push(IndicatorHit {
+                kind: row.get::<String>("kind").unwrap_or_default(),
+                value: row.get::<String>("value").unwrap_or_default(),
+                relation: row.get::<String>("relation").unwrap_or_default(),
+                binary: row.get::<String>("binary").unwrap_or_default(),
+                hash: row.get::<String>("hash").unwrap_or_default(),
+            });
+        }
+
+        Ok(hits)
+    }
+
+    /// Link a binary to a named mutex, pipe, event or semaphore with CREATES_OBJECT
+    ///
+    /// `source` tells whether the object came from the binary's strings or a sandbox.
+    pub async fn create_object_relationship(
+        &self,
+        binary_hash: &str,
+        object: &NamedObject,
+        source: &str,
+    ) -> Result<()> {
+        let query_str = "
+            MATCH (b:Binary {hash: $binary_hash})
+            MERGE (o:NamedObject {uid: $uid})
+            ON CREATE SET o.type = $kind, o.name = $name
+            MERGE (b)-[r:CREATES_OBJECT]->(o)
+            SET r.source = CASE WHEN r.source = 'sandbox' THEN r.source ELSE $source END
+        ";
+
+        self.connection
+            .graph()
+            .run(
+                query(query_str)
+                    .param("binary_hash", binary_hash)
+                    .param("uid", object.uid())
+                    .param("kind", object.kind.as_str())
+                    .param("name", object.name.as_str())
+                    .param("source", source),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Functions tagged with crypto algorithms at import, by binary and address
+    pub async fn query_crypto(
+        &self,
+        algorithm: Option<&str>,
+        binary: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<CryptoHit>> {
+        let query_str = "
+            MATCH (b:Binary)-[:CONTAINS]->(f:Function)
+            WHERE f.crypto IS NOT NULL
+              AND ($algorithm IS NULL OR $algorithm IN f.crypto)
+              AND ($binary_name IS NULL OR b.filename CONTAINS $binary_name OR b.hash = $binary_name)
+            RETURN f.crypto AS algorithms, f.name AS name, f.address AS address, f.uid AS uid,
+                   b.filename AS binary
+            ORDER BY binary, address
+            LIMIT $limit
+        ";
+
+        let result = self
+            .connection
+            .read(
+                Cypher::new(query_str)
+                    .param("algorithm", algorithm.map(str::to_lowercase))
+                    .param("binary_name", binary)
+                    .param("limit", limit as i64),
+            )
+            .await?;
+
+        let mut hits = Vec::new();
+        for row in result {
+            hits.push(CryptoHit {
+                algorithms: row.get::<Vec<String>>("algorithms").unwrap_or_default(),
+                name: row.get::<String>("name").unwrap_or_default(),
+                address: row.get::<String>("address").unwrap_or_default(),
+                uid: row.get::<String>("uid").unwrap_or_default(),
+                binary: row.get::<String>("binary").unwrap_or_default(),
+            });
+        }
+
+        Ok(hits)
+    }
+
+    /// Binaries linked to named objects, most shared objects first
+    pub async fn query_objects(
+        &self,
+        kind: Option<ObjectKind>,
+        name: Option<&str>,
+        binary: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<ObjectHit>> {
+        let query_str = "
+            MATCH (b:Binary)-[r:CREATES_OBJECT]->(o:NamedObject)
+            WHERE ($kind IS NULL OR o.type = $kind)
+              AND ($name IS NULL OR toLower(o.name) CONTAINS toLower($name))
+              AND ($binary_name IS NULL OR b.filename CONTAINS $binary_name OR b.hash = $binary_name)
+            WITH b, r, o
+            MATCH (other:Binary)-[:CREATES_OBJECT]->(o)
+            WITH b, r, o, count(DISTINCT other) AS samples
+            RETURN o.type AS kind, o.name AS name, r.source AS source,
+                   b.filename AS binary, b.hash AS hash, samples
+            ORDER BY samples DESC, name, binary
+            LIMIT $limit
+        ";
+
+        let result = self
+            .connection
+            .read(
+                Cypher::new(query_str)
+                    .param("kind", kind.map(ObjectKind::as_str))
+                    .param("name", name)
+                    .param("binary_name", binary)
+                    .param("limit", limit as i64),
+            )
+            .await?;
+
+        let mut hits = Vec::new();
+        for row in result {
+            hits.push(ObjectHit {
+                kind: row.get::<String>("kind").unwrap_or_default(),
+                name: row.get::<String>("name").unwrap_or_default(),
+                source: row.get::<String>("source").unwrap_or_default(),
+                binary: row.get::<String>("binary").unwrap_or_default(),
+                hash: row.get::<String>("hash").unwrap_or_default(),
+                samples: row.get::<i64>("samples").unwrap_or(0),
+            });
+        }
+
+        Ok(hits)
+    }
+
+    /// Binaries most similar to `binary_hash` by Jaccard similarity of their feature sets
+    pub async fn query_neighbors(
+        &self,
+        binary_hash: &str,
+        space: FeatureSpace,
+        k: usize,
+    ) -> Result<Vec<NeighborHit>> {
+        let query_str = format!(
+            "
+            MATCH (b:Binary {{hash: $binary_hash}})
+            {reference}
+            WITH b, collect(DISTINCT {key}) AS features
+            {other}
+            WITH b, features, other, collect(DISTINCT {key}) AS other_features
+            WHERE other <> b
+            WITH other, size(features) AS size, size(other_features) AS other_size,
+                 size([key IN other_features WHERE key IN features]) AS shared
+            WHERE shared > 0
+            RETURN other.filename AS binary, other.hash AS hash, shared, other_size,
+                   toFloat(shared) / (size + other_size - shared) AS similarity
+            ORDER BY similarity DESC, shared DESC, binary
+            LIMIT $k
+            ",
+            reference = space.match_clause("b"),
+            other = space.match_clause("other"),
+            key = space.key(),
+        );
+
+        let result = self
+            .connection
+            .read(
+                Cypher::new(query_str)
+                    .param("binary_hash", binary_hash)
+                    .param("k", k as i64),
+            )
+            .await?;
+
+        Ok(result
+            .into_iter()
+            .map(|row| NeighborHit {
+                binary: row.get::<String>("binary").unwrap_or_default(),
+                hash: row.get::<String>("hash").unwrap_or_default(),
+                similarity: row.get::<f64>("similarity").unwrap_or(0.0),
+                shared: row.get::<i64>("shared").unwrap_or(0) as u64,
+                features: row.get::<i64>("other_size").unwrap_or(0) as u64,
+            })
+            .collect())
+    }
+
+    /// Project binaries with their imported APIs and strings into GDS, compute node
+    /// embeddings and store them as `embedding` on the Binary nodes
+    ///
+    /// Imports and strings shared by several binaries connect them, so binaries with
+    /// similar imports and strings end up with similar vectors.
+    pub async fn generate_embeddings(
+        &self,
+        algorithm: EmbeddingAlgorithm,
+        dimension: usize,
+    ) -> Result<EmbeddingRun> {
+        let gds_error = |e: neo4rs::Error| {
+            anyhow::anyhow!(
+                "GDS call failed (is the Graph Data Science plugin installed?): {}",
+                e
+            )
+        };
+
+        // A projection left behind by an interrupted run would make the next one fail
+        self.drop_embedding_graph().await.map_err(gds_error)?;
+
+        let mut result = self
+            .connection
+            .graph()
+            .execute(
+                query(
+                    "MATCH (b:Binary)-[:IMPORTS|CONTAINS_STRING]->(x)
+                     WITH gds.graph.project($graph_name, b, x, {},
+                                            {undirectedRelationshipTypes: ['*']}) AS g
+                     RETURN g.nodeCount AS nodes, g.relationshipCount AS relationships",
+                )
+                .param("graph_name", EMBEDDING_GRAPH),
+            )
+            .await
+            .map_err(gds_error)?;
+        let (nodes, relationships) = match result.next().await? {
+            Some(row) => (
+                row.get::<i64>("nodes").unwrap_or(0) as u64,
+                row.get::<i64>("relationships").unwrap_or(0) as u64,
+            ),
+            None => (0, 0),
+        };
+        if nodes == 0 {
+            return Ok(EmbeddingRun {
+                nodes,
+                relationships,
+                binaries: 0,
+            });
+        }
+
+        let write_str = format!(
+            "CALL {}($graph_name, {{embeddingDimension: $dimension, randomSeed: 42}})
+             YIELD nodeId, embedding
+             WITH gds.util.asNode(nodeId) AS b, embedding
+             WHERE b:Binary
+             SET b.embedding = embedding,
+                 b.embedding_algorithm = $algorithm
+             RETURN count(b) AS binaries",
+            algorithm.procedure()
+        );
+        let written = async {
+            let mut result = self
+                .connection
+                .graph()
+                .execute(
+                    query(&write_str)
+                        .param("graph_name", EMBEDDING_GRAPH)
+                        .param("dimension", dimension as i64)
+                        .param("algorithm", algorithm.as_str()),
+                )
+                .await?;
+            Ok::<_, neo4rs::Error>(
+                result
+                    .next()
+                    .await?
+                    .map_or(0, |row| row.get::<i64>("binaries").unwrap_or(0) as u64),
+            )
+        }
+        .await;
+        // The projection holds the whole corpus in memory; free it even on failure
+        let dropped = self.drop_embedding_graph().await;
+        let binaries = written.map_err(gds_error)?;
+        dropped.map_err(gds_error)?;
+
+        Ok(EmbeddingRun {
+            nodes,
+            relationships,
+            binaries,
+        })
+    }
+
+    async fn drop_embedding_graph(&self) -> std::result::Result<(), neo4rs::Error> {
+        self.connection
+            .graph()
+            .run(
+                query("CALL gds.graph.drop($graph_name, false) YIELD graphName RETURN graphName")
+                    .param("graph_name", EMBEDDING_GRAPH),
+            )
+            .await
+    }
+
+    /// Embeddings stored by `generate_embeddings`
+    pub async fn query_embeddings(&self) -> Result<Vec<BinaryEmbedding>> {
+        let query_str = "
+            MATCH (b:Binary)
+            WHERE b.embedding IS NOT NULL
+            RETURN b.filename AS binary, b.hash AS hash,
+                   b.embedding_algorithm AS algorithm, b.embedding AS embedding
+        ";
+
+        let result = self.connection.read(Cypher::new(query_str)).await?;
+
+        Ok(result
+            .into_iter()
+            .map(|row| BinaryEmbedding {
+                binary: row.get::<String>("binary").unwrap_or_default(),
+                hash: row.get::<String>("hash").unwrap_or_default(),
+                algorithm: row.get::<String>("algorithm").ok(),
+                embedding: row.get::<Vec<f64>>("embedding").unwrap_or_default(),
+            })
+            .collect())
+    }
+
+    /// Sparse binary × feature matrix of all binaries, one cell per distinct feature
+    pub async fn query_feature_matrix(&self, space: FeatureSpace) -> Result<Vec<FeatureCell>> {
+        let query_str = format!(
+            "
+            {features}
+            WITH b, {key} AS key, {label} AS label
+            WHERE key IS NOT NULL
+            RETURN b.hash AS binary, head(collect(label)) AS feature, count(*) AS value
+            ORDER BY binary, feature
+            ",
+            features = space.match_clause("b"),
+            key = space.key(),
+            label = space.label(),
+        );
+
+        let result = self.connection.read(Cypher::new(query_str)).await?;
+
+        Ok(result
+            .into_iter()
+            .filter_map(|row| {
+                Some(FeatureCell {
+                    binary: row.get::<String>("binary").ok()?,
+                    feature: row.get::<String>("feature").ok()?,
+                    value: row.get::<i64>("value").unwrap_or(0),
+                })
+            })
+            .collect())
+    }
+
+    /// Function, string and import counts of every binary
+    pub async fn query_binary_counts(&self) -> Result<Vec<BinaryCounts>> {
+        let query_str = "
+            MATCH (b:Binary)
+            OPTIONAL MATCH (b)-[:CONTAINS]->(f:Function)
+            WITH b, count(DISTINCT f) AS functions
+            OPTIONAL MATCH (b)-[:CONTAINS_STRING]->(s:String)
+            WITH b, functions, count(DISTINCT s) AS strings
+            OPTIONAL MATCH (b)-[:IMPORTS]->(i:Function)
+            RETURN b.hash AS hash, coalesce(b.format, '') AS format,
+                   coalesce(b.arch, '') AS arch, functions, strings,
+                   count(DISTINCT i) AS imports
+        ";
+
+        let result = self.connection.read(Cypher::new(query_str)).await?;
+
+        Ok(result
+            .into_iter()
+            .map(|row| BinaryCounts {
+                hash: row.get::<String>("hash").unwrap_or_default(),
+                format: row.get::<String>("format").unwrap_or_default(),
+                arch: row.get::<String>("arch").unwrap_or_default(),
+                functions: row.get::<i64>("functions").unwrap_or(0) as u64,
+                strings: row.get::<i64>("strings").unwrap_or(0) as u64,
+                imports: row.get::<i64>("imports").unwrap_or(0) as u64,
+            })
+            .collect())
+    }
+
+    /// Libraries imported by the most binaries, with the number of importing binaries
+    pub async fn query_top_libraries(&self, limit: usize) -> Result<Vec<(String, u64)>> {
+        let query_str = "
+            MATCH (b:Binary)-[:IMPORTS_LIBRARY]->(l:Library)
+            RETURN l.name AS name, count(DISTINCT b) AS binaries
+            ORDER BY binaries DESC, name
+            LIMIT $limit
+        ";
+
+        let result = self
+            .connection
+            .read(Cypher::new(query_str).param("limit", limit as i64))
+            .await?;
+
+        Ok(result
+            .into_iter()
+            .filter_map(|row| {
+                Some((
+                    row.get::<String>("name").ok()?,
+                    row.get::<i64>("binaries").unwrap_or(0) as u64,
+                ))
+            })
+            .collect())
+    }
+
+    /// Binaries sharing imported APIs with a reference binary, by canonical API name
+    pub async fn query_shared_apis(
+        &self,
+        binary: &str,
+        min_shared: usize,
+        limit: usize,
+    ) -> Result<Vec<SharedApiHit>> {
+        let query_str = "
+            MATCH (b:Binary)
+            WHERE (b.filename CONTAINS $binary_name OR b.hash = $binary_name)
+            WITH b LIMIT 1
+            MATCH (b)-[:IMPORTS]->(f:Function)
+            WITH b, collect(DISTINCT coalesce(f.canonical_name, f.name)) AS apis
+            MATCH (other:Binary)-[:IMPORTS]->(g:Function)
+            WHERE other <> b AND coalesce(g.canonical_name, g.name) IN apis
+            WITH other, apis, collect(DISTINCT coalesce(g.canonical_name, g.name)) AS shared
+            WHERE size(shared) >= $min_shared
+            RETURN other.filename AS binary, other.hash AS hash, shared,
+                   toFloat(size(shared)) / size(apis) AS coverage
+            ORDER BY size(shared) DESC, binary
+            LIMIT $limit
+        ";
+
+        let result = self
+            .connection
+            .read(
+                Cypher::new(query_str)
+                    .param("binary_name", binary)
+                    .param("min_shared", min_shared as i64)
+                    .param("limit", limit as i64),
+            )
+            .await?;
+
+        let mut hits = Vec::new();
+        for row in result {
+            let mut apis = row.get::<Vec<String>>("shared").unwrap_or_default();
+            apis.sort();
+            hits.push(SharedApiHit {
+                binary: row.get::<String>("binary").unwrap_or_default(),
+                hash: row.get::<String>("hash").unwrap_or_default(),
+                shared: apis.len() as u64,
+                coverage: row.get::<f64>("coverage").unwrap_or(0.0),
+                apis,
+            });
+        }
+
+        Ok(hits)
+    }
+
+    /// Imported APIs of a binary by canonical name, with `caller @ offset` call sites
+    pub async fn query_imported_api_uses(
+        &self,
+        binary_hash: &str,
+    ) -> Result<Vec<(String, Vec<String>)>> {
+        let query_str = "
+            MATCH (b:Binary {hash: $binary_hash})-[:IMPORTS]->(f:Function)
+            OPTIONAL MATCH (b)-[:CONTAINS]->(caller:Function)-[c:CALLS]->(f)
+            RETURN coalesce(f.canonical_name, f.name) AS api,
+                   collect(DISTINCT coalesce(caller.analyst_name, caller.name) + ' @ ' + c.offset) AS call_sites
+        ";
+
+        let result = self
+            .connection
+            .read(Cypher::new(query_str).param("binary_hash", binary_hash))
+            .await?;
+
+        let mut uses = Vec::new();
+        for row in result {
+            uses.push((
+                row.get::<String>("api").unwrap_or_default(),
+                row.get::<Vec<String>>("call_sites").unwrap_or_default(),
+            ));
+        }
+
+        Ok(uses)
+    }
+
+    pub async fn store_risk_score(
+        &self,
+        binary_hash: &str,
+        score: f64,
+        categories: &[String],
+    ) -> Result<()> {
+        let query_str = "
+            MATCH (b:Binary {hash: $binary_hash})
+            SET b.risk_score = $score,
+                b.risk_categories = $categories
+        ";
+
+        self.connection
+            .graph()
+            .run(
+                query(query_str)
+                    .param("binary_hash", binary_hash)
+                    .param("score", score)
+                    .param("categories", categories.to_vec()),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Functions of a binary, including the imports it references, and the CALLS edges
+    /// between them with their properties
+    pub async fn query_binary_call_graph(
+        &self,
+        binary_hash: &str,
+    ) -> Result<(Vec<Function>, Vec<CallGraphLink>)> {
+        let functions_query = "
+            MATCH (b:Binary {hash: $binary_hash})-[:CONTAINS|IMPORTS]->(f:Function)
+            RETURN DISTINCT f
+        ";
+        let calls_query = "
+            MATCH (b:Binary {hash: $binary_hash})-[:CONTAINS]->(src:Function)-[c:CALLS]->(dst:Function)
+            WHERE EXISTS((b)-[:CONTAINS|IMPORTS]->(dst))
+            RETURN DISTINCT src.uid AS from_uid, dst.uid AS to_uid,
+                   c.count AS count, coalesce(c.offsets, [o IN [c.offset] WHERE o IS NOT NULL]) AS offsets,
+                   c.call_type AS call_type, c.source AS source, c.confidence AS confidence
+        ";
+
+        let functions = self
+            .connection
+            .read(Cypher::new(functions_query).param("binary_hash", binary_hash))
+            .await?
+            .into_iter()
+            .filter_map(|row| row.get::<neo4rs::Node>("f").ok())
+            .map(|node| function_from_node(&node))
+            .collect();
+
+        let links = self
+            .connection
+            .read(Cypher::new(calls_query).param("binary_hash", binary_hash))
+            .await?
+            .into_iter()
+            .map(|row| {
+                let offsets = row.get::<Vec<String>>("offsets").unwrap_or_default();
+                CallGraphLink {
+                    from_uid: row.get::<String>("from_uid").unwrap_or_default(),
+                    to_uid: row.get::<String>("to_uid").unwrap_or_default(),
+                    count: row
+                        .get::<i64>("count")
+                        .map_or(offsets.len().max(1) as u64, |count| count as u64),
+                    offsets,
+                    call_type: row.get::<String>("call_type").ok(),
+                    source: row.get::<String>("source").ok(),
+                    confidence: row.get::<f64>("confidence").unwrap_or(1.0),
+                }
+            })
+            .collect();
+
+        Ok((functions, links))
+    }
+
+    /// Imported APIs of a binary grouped by library, both sorted by name
+    pub async fn query_binary_imports(&self, binary_hash: &str) -> Result<Vec<ImportGroup>> {
+        let query_str = "
+            MATCH (b:Binary {hash: $binary_hash})-[:IMPORTS]->(f:Function)
+            OPTIONAL MATCH (f)-[:BELONGS_TO]->(l:Library)
+            WITH coalesce(l.name, 'unknown') AS library, f.name AS api
+            ORDER BY api
+            RETURN library, collect(DISTINCT api) AS apis
+            ORDER BY library
+        ";
+
+        let result = self
+            .connection
+            .read(Cypher::new(query_str).param("binary_hash", binary_hash))
+            .await?;
+
+        let mut groups = Vec::new();
+        for row in result {
+            groups.push(ImportGroup {
+                library: row.get::<String>("library").unwrap_or_default(),
+                apis: row.get::<Vec<String>>("apis").unwrap_or_default(),
+            });
+        }
+
+        Ok(groups)
+    }
+
+    /// APIs of the libraries named `names` with the binaries importing and the functions
+    /// calling each, most imported first
+    pub async fn query_library_apis(
+        &self,
+        names: &[String],
+        examples: usize,
+        limit: usize,
+    ) -> Result<Vec<LibraryApiUse>> {
+        let query_str = "
+            MATCH (l:Library)<-[:BELONGS_TO]-(api:Function)
+            WHERE l.name IN $names
+            OPTIONAL MATCH (b:Binary)-[:IMPORTS]->(api)
+            WITH l.name AS library, api.name AS name,
+                 collect(DISTINCT api) AS apis, count(DISTINCT b) AS binaries
+            ORDER BY binaries DESC, name
+            LIMIT $limit
+            UNWIND apis AS api
+            OPTIONAL MATCH (cb:Binary)-[:CONTAINS]->(caller:Function)-[:CALLS]->(api)
+            WITH library, name, binaries, count(DISTINCT caller) AS callers,
+                 collect(DISTINCT cb.filename + ': ' +
+                         coalesce(caller.analyst_name, caller.name)) AS examples
+            RETURN library, name, binaries, callers, examples[..$examples] AS examples
+            ORDER BY binaries DESC, name
+        ";
+
+        let result = self
+            .connection
+            .read(
+                Cypher::new(query_str)
+                    .param("names", names.to_vec())
+                    .param("examples", examples as i64)
+                    .param("limit", limit as i64),
+            )
+            .await?;
+
+        Ok(result
+            .into_iter()
+            .map(|row| LibraryApiUse {
+                library: row.get::<String>("library").unwrap_or_default(),
+                api: row.get::<String>("name").unwrap_or_default(),
+                binaries: row.get::<i64>("binaries").unwrap_or(0) as u64,
+                callers: row.get::<i64>("callers").unwrap_or(0) as u64,
+                examples: row.get::<Vec<String>>("examples").unwrap_or_default(),
+            })
+            .collect())
+    }
+
+    /// Export names and `library!name` imports of a binary
+    pub async fn query_api_surface(&self, binary_hash: &str) -> Result<ApiSurface> {
+        let query_str = "
+            MATCH (b:Binary {hash: $binary_hash})
+            OPTIONAL MATCH (b)-[:CONTAINS]->(e:Function {type: 'Export'})
+            WITH b, collect(DISTINCT e.name) AS exports
+            OPTIONAL MATCH (b)-[:IMPORTS]->(f:Function)
+            OPTIONAL MATCH (f)-[:BELONGS_TO]->(l:Library)
+            WITH exports,
+                 collect(DISTINCT coalesce(toLower(l.name), 'unknown') + '!' + f.name) AS imports
+            RETURN exports, imports
+        ";
+
+        let result = self
+            .connection
+            .read(Cypher::new(query_str).param("binary_hash", binary_hash))
+            .await?;
+
+        Ok(result
+            .into_iter()
+            .next()
+            .map(|row| ApiSurface {
+                exports: row
+                    .get::<Vec<String>>("exports")
+                    .unwrap_or_default()
+                    .into_iter()
+                    .collect(),
+                imports: row
+                    .get::<Vec<String>>("imports")
+                    .unwrap_or_default()
+                    .into_iter()
+                    .collect(),
+            })
+            .unwrap_or_default())
+    }
+
+    /// Strings of a binary, most referenced first, then longest first
+    pub async fn query_top_strings(
+        &self,
+        binary_hash: &str,
+        limit: usize,
+    ) -> Result<Vec<StringHit>> {
+        let query_str = "
+            MATCH (b:Binary {hash: $binary_hash})-[:CONTAINS_STRING]->(s:String)
+            WITH DISTINCT b, s
+            OPTIONAL MATCH (b)-[:CONTAINS]->(f:Function)-[:REFERENCES]->(s)
+            RETURN s.value AS value, count(DISTINCT f) AS references
+            ORDER BY references DESC, size(value) DESC, value
+            LIMIT $limit
+        ";
+
+        let result = self
+            .connection
+            .read(
+                Cypher::new(query_str)
+                    .param("binary_hash", binary_hash)
+                    .param("limit", limit as i64),
+            )
+            .await?;
+
+        let mut strings = Vec::new();
+        for row in result {
+            strings.push(StringHit {
+                value: row.get::<String>("value").unwrap_or_default(),
+                references: row.get::<i64>("references").unwrap_or(0).max(0) as u64,
+            });
+        }
+
+        Ok(strings)
+    }
+
+    /// CALLS edges between functions of a binary, capped at `limit` edges
+    pub async fn query_binary_call_edges(
+        &self,
+        binary_hash: &str,
+        limit: usize,
+    ) -> Result<Vec<GraphEdge>> {
+        let query_str = "
+            MATCH (b:Binary {hash: $binary_hash})-[:CONTAINS]->(src:Function)-[:CALLS]->(dst:Function)
+            WHERE EXISTS((b)-[:CONTAINS|IMPORTS]->(dst))
+            RETURN DISTINCT src.uid AS from_uid,
+                   coalesce(src.analyst_name, src.demangled_name, src.name) AS from_name,
+                   CASE WHEN coalesce(src.is_library, false) THEN 'Library' ELSE src.type END AS from_kind,
+                   dst.uid AS to_uid,
+                   coalesce(dst.analyst_name, dst.demangled_name, dst.name) AS to_name,
+                   CASE WHEN coalesce(dst.is_library, false) THEN 'Library' ELSE dst.type END AS to_kind
+            ORDER BY from_name, to_name
+            LIMIT $limit
+        ";
+
+        let result = self
+            .connection
+            .read(
+                Cypher::new(query_str)
+                    .param("binary_hash", binary_hash)
+                    .param("limit", limit as i64),
+            )
+            .await?;
+
+        let mut edges = Vec::new();
+        for row in result {
+            edges.push(GraphEdge {
+                from_uid: row.get::<String>("from_uid").unwrap_or_default(),
+                from_name: row.get::<String>("from_name").unwrap_or_default(),
+                from_kind: row
+                    .get::<String>("from_kind")
+                    .unwrap_or_else(|_| "Internal".to_string()),
+                to_uid: row.get::<String>("to_uid").unwrap_or_default(),
+                to_name: row.get::<String>("to_name").unwrap_or_default(),
+                to_kind: row
+                    .get::<String>("to_kind")
+                    .unwrap_or_else(|_| "Internal".to_string()),
+            });
+        }
+
+        Ok(edges)
+    }
+
+    pub async fn query_binary_resources(&self, binary_hash: &str) -> Result<Vec<Resource>> {
+        let query_str = "
+            MATCH (b:Binary {hash: $binary_hash})-[h:HAS_RESOURCE]->(r:Resource)
+            RETURN r, h.name AS name
+            ORDER BY r.type, name
+        ";
+
+        let result = self
+            .connection
+            .read(Cypher::new(query_str).param("binary_hash", binary_hash))
+            .await?;
+
+        let mut resources = Vec::new();
+        for row in result {
+            if let Ok(node) = row.get::<neo4rs::Node>("r") {
+                let mut resource = Resource::new(
+                    &node.get::<String>("sha256").unwrap_or_default(),
+                    &node.get::<String>("type").unwrap_or_default(),
+                    node.get::<i64>("size").unwrap_or(0) as u64,
+                );
+                resource.name = row.get::<String>("name").ok().filter(|n| !n.is_empty());
+                resource.language = node.get::<String>("language").ok();
+                resource.file_type = node.get::<String>("file_type").ok();
+                resources.push(resource);
+            }
+        }
+
+        Ok(resources)
+    }
+
+    pub async fn import_library(&self, library: &Library) -> Result<()> {
+        let query_str = "
+            MERGE (l:Library {name: $name})
+        ";
+
+        self.connection
+            .graph()
+            .run(query(query_str).param("name", library.name.as_str()))
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn create_imports_relationship(
+        &self,
+        binary_hash: &str,
+        library_name: &str,
+    ) -> Result<()> {
+        let query_str = "
+            MATCH (b:Binary {hash: $binary_hash}), (l:Library {name: $library_name})
+            MERGE (b)-[:IMPORTS_LIBRARY]->(l)
+        ";
+
+        self.connection
+            .graph()
+            .run(
+                query(query_str)
+                    .param("binary_hash", binary_hash)
+                    .param("library_name", library_name),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn create_imports_function_relationship_with_address(
+        &self,
+        binary_hash: &str,
+        function_uid: &str,
+        address: &str,
+    ) -> Result<()> {
+        let query_str = "
+            MATCH (b:Binary {hash: $binary_hash}), (f:Function {uid: $function_uid})
+            MERGE (b)-[r:IMPORTS]->(f)
+            SET r.address = $address
+        ";
+
+        self.connection
+            .graph()
+            .run(
+                query(query_str)
+                    .param("binary_hash", binary_hash)
+                    .param("function_uid", function_uid)
+                    .param("address", address),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Record call sites on CALLS edges, one statement per `batch_size` calls
+    ///
+    /// Each call is `(caller_uid, callee_uid, call)`.
+    pub async fn create_calls_batch(&self, calls: &[(&str, &str, Calls)]) -> Result<()> {
+        // One edge per caller/callee pair; each distinct call site is kept in `offsets`
+        // and `offset` stays the first one. Edges imported before `offsets` existed
+        // start from their single `offset`. The most confident source seen is kept.
+        let query_str = "
+            UNWIND $rows AS row
+            MATCH (from:Function {uid: row.from_uid}), (to:Function {uid: row.to_uid})
+            MERGE (from)-[r:CALLS]->(to)
+            WITH r, row, coalesce(r.offsets, [o IN [r.offset] WHERE o IS NOT NULL]) AS offsets,
+                 r.confidence IS NULL OR row.confidence >= r.confidence AS stronger
+            SET r.offsets = CASE WHEN row.offset IN offsets THEN offsets ELSE offsets + row.offset END,
+                r.call_type = row.call_type,
+                r.source = CASE WHEN stronger THEN row.source ELSE r.source END,
+                r.confidence = CASE WHEN stronger THEN row.confidence ELSE r.confidence END,
+                r.resolved_by = coalesce(row.resolved_by, r.resolved_by)
+            SET r.offset = r.offsets[0],
+                r.count = size(r.offsets)
+        ";
+
+        self.run_batches(calls, false, |chunk| {
+            let rows: Vec<HashMap<&str, BoltType>> = chunk
+                .iter()
+                .map(|(from_uid, to_uid, call)| {
+                    HashMap::from([
+                        ("from_uid", BoltType::from(*from_uid)),
+                        ("to_uid", BoltType::from(*to_uid)),
+                        ("offset", BoltType::from(call.offset.as_str())),
+                        ("call_type", BoltType::from(format!("{:?}", call.call_type))),
+                        ("source", BoltType::from(call.source.as_str())),
+                        ("confidence", BoltType::from(call.confidence)),
+                        ("resolved_by", BoltType::from(call.resolved_by.as_deref())),
+                    ])
+                })
+                .collect();
+
+            query(query_str).param("rows", rows)
+        })
+        .await
+    }
+
+    /// Merge the binary's vtables with their HAS_SLOT and DERIVES_FROM edges, replacing
+    /// the slots of an earlier import, then rebuild the binary's DISPATCHES_TO edges
+    ///
+    /// A Virtual CALLS edge to the function in slot `i` of a vtable dispatches to the
+    /// function in slot `i` of every vtable deriving from it. These edges are inferred,
+    /// so they carry the heuristic source.
+    pub async fn import_vtables(&self, binary_hash: &str, vtables: &[VTable]) -> Result<()> {
+        self.run_batches(vtables, false, |chunk| {
+            let rows: Vec<HashMap<&str, BoltType>> = chunk
+                .iter()
+                .map(|vtable| {
+                    HashMap::from([
+                        ("uid", BoltType::from(vtable.uid.as_str())),
+                        ("address", BoltType::from(vtable.address.as_str())),
+                        ("class_name", BoltType::from(vtable.class_name.as_deref())),
+                        ("slot_count", BoltType::from(vtable.slots.len() as i64)),
+                    ])
+                })
+                .collect();
+
+            query(
+                "MATCH (b:Binary {hash: $binary_hash})
+                 UNWIND $rows AS row
+                 MERGE (v:VTable {uid: row.uid})
+                 SET v.address = row.address,
+                     v.class_name = row.class_name,
+                     v.binary_hash = $binary_hash,
+                     v.slot_count = row.slot_count
+                 MERGE (b)-[:HAS_VTABLE]->(v)
+                 WITH v
+                 OPTIONAL MATCH (v)-[old:HAS_SLOT|DERIVES_FROM]->()
+                 DELETE old",
+            )
+            .param("binary_hash", binary_hash)
+            .param("rows", rows)
+        })
+        .await?;
+
+        let slots: Vec<(&str, i64, &str)> = vtables
+            .iter()
+            .flat_map(|vtable| {
+                vtable
+                    .slots
+                    .iter()
+                    .map(|(index, uid)| (vtable.uid.as_str(), *index as i64, uid.as_str()))
+            })
+            .collect();
+        self.run_batches(&slots, false, |chunk| {
+            let rows: Vec<HashMap<&str, BoltType>> = chunk
+                .iter()
+                .map(|(vtable_uid, index, function_uid)| {
+                    HashMap::from([
+                        ("vtable_uid", BoltType::from(*vtable_uid)),
+                        ("index", BoltType::from(*index)),
+                        ("function_uid", BoltType::from(*function_uid)),
+                    ])
+                })
+                .collect();
+
+            query(
+                "UNWIND $rows AS row
+                 MATCH (v:VTable {uid: row.vtable_uid}), (f:Function {uid: row.function_uid})
+                 MERGE (v)-[:HAS_SLOT {index: row.index}]->(f)",
+            )
+            .param("rows", rows)
+        })
+        .await?;
+
+        let bases: Vec<(&str, &str)> = vtables
+            .iter()
+            .flat_map(|vtable| {
+                vtable
+                    .bases
+                    .iter()
+                    .map(|base| (vtable.uid.as_str(), base.as_str()))
+            })
+            .collect();
+        self.run_batches(&bases, false, |chunk| {
+            let rows: Vec<HashMap<&str, BoltType>> = chunk
+                .iter()
+                .map(|(vtable_uid, base_uid)| {
+                    HashMap::from([
+                        ("vtable_uid", BoltType::from(*vtable_uid)),
+                        ("base_uid", BoltType::from(*base_uid)),
+                    ])
+                })
+                .collect();
+
+            query(
+                "UNWIND $rows AS row
+                 MATCH (v:VTable {uid: row.vtable_uid}), (base:VTable {uid: row.base_uid})
+                 MERGE (v)-[:DERIVES_FROM]->(base)",
+            )
+            .param("rows", rows)
+        })
+        .await?;
+
+        self.connection
+            .graph()
+            .run(
+                query(
+                    "MATCH (:Binary {hash: $binary_hash})-[:CONTAINS]->(:Function)-[old:DISPATCHES_TO]->()
+                     DELETE old",
+                )
+                .param("binary_hash", binary_hash),
+            )
+            .await?;
+
+        let source = EdgeSource::Heuristic;
+        self.connection
+            .graph()
+            .run(
+                query(
+                    "MATCH (b:Binary {hash: $binary_hash})-[:CONTAINS]->(caller:Function)
+                           -[c:CALLS {call_type: 'Virtual'}]->(f:Function)<-[s:HAS_SLOT]-(v:VTable)
+                     WHERE (b)-[:HAS_VTABLE]->(v)
+                     MATCH (b)-[:HAS_VTABLE]->(d:VTable)-[:DERIVES_FROM*0..]->(v)
+                     MATCH (d)-[:HAS_SLOT {index: s.index}]->(g:Function)
+                     WHERE g <> f
+                     MERGE (caller)-[r:DISPATCHES_TO]->(g)
+                     SET r.offset = c.offset,
+                         r.slot = s.index,
+                         r.call_type = 'Virtual',
+                         r.source = $source,
+                         r.confidence = $confidence",
+                )
+                .param("binary_hash", binary_hash)
+                .param("source", source.as_str())
+                .param("confidence", source.default_confidence()),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Create a REFERENCES edge from a function to a string it reads or takes the address of
+    pub async fn create_string_reference(
+        &self,
+        from_uid: &str,
+        string_node: &StringNode,
+        references: &References,
+    ) -> Result<()> {
+        let query_str = "
+            MATCH (f:Function {uid: $from_uid})
+            MERGE (s:String {uid: $string_uid})
+            ON CREATE SET s.value = $value,
+                          s.length = $length,
+                          s.truncated = $truncated,
+                          s.sha256 = $sha256,
+                          s.blob = $blob
+            MERGE (f)-[r:REFERENCES {offset: $offset}]->(s)
+            SET r.ref_type = $ref_type, r.source = $source, r.confidence = $confidence
+        ";
+
+        let ref_type_str = format!("{:?}", references.ref_type);
+
+        self.connection
+            .graph()
+            .run(
+                query(query_str)
+                    .param("from_uid", from_uid)
+                    .param("string_uid", string_node.uid.as_str())
+                    .param("value", string_node.value.as_str())
+                    .param("length", string_node.length as i64)
+                    .param("truncated", string_node.truncated)
+                    .param("sha256", string_node.content_hash())
+                    .param("blob", string_node.blob.clone())
+                    .param("offset", references.offset.as_str())
+                    .param("ref_type", ref_type_str.as_str())
+                    .param("source", references.source.as_str())
+                    .param("confidence", references.confidence),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Create a REFERENCES edge from a function to a global data address of its binary
+    ///
+    /// Data nodes are keyed by binary hash and normalized address.
+    pub async fn create_data_reference(
+        &self,
+        binary_hash: &str,
+        from_uid: &str,
+        address: &str,
+        references: &References,
+    ) -> Result<()> {
+        let query_str = "
+            MATCH (f:Function {uid: $from_uid})
+            MERGE (d:Data {uid: $data_uid})
+            ON CREATE SET d.address = $address, d.binary_hash = $binary_hash
+            MERGE (f)-[r:REFERENCES {offset: $offset}]->(d)
+            SET r.ref_type = $ref_type, r.source = $source, r.confidence = $confidence
+        ";
+
+        let ref_type_str = format!("{:?}", references.ref_type);
+
+        self.connection
+            .graph()
+            .run(
+                query(query_str)
+                    .param("from_uid", from_uid)
+                    .param("data_uid", data_uid(binary_hash, address))
+                    .param("address", address)
+                    .param("binary_hash", binary_hash)
+                    .param("offset", references.offset.as_str())
+                    .param("ref_type", ref_type_str.as_str())
+                    .param("source", references.source.as_str())
+                    .param("confidence", references.confidence),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Functions referencing a data address or a string (by address or contained text)
+    pub async fn query_data_refs(
+        &self,
+        target: &str,
+        binary: Option<&str>,
+    ) -> Result<Vec<DataXref>> {
+        let query_str = format!(
+            "
+            CALL {{
+                MATCH (b:Binary)-[:CONTAINS]->(f:Function)-[r:REFERENCES]->(t)
+                WHERE ($binary_name IS NULL OR b.filename CONTAINS $binary_name OR b.hash = $binary_name)
+                  AND ((t:Data AND t.binary_hash = b.hash AND t.address = {address})
+                    OR (t:String AND (t.value CONTAINS $target
+                        OR ANY(c IN [(b)-[cs:CONTAINS_STRING]->(t) | cs] WHERE c.address = {address}))))
+                RETURN b, f, t, r.ref_type AS ref_type, r.offset AS offset
+                UNION ALL
+                MATCH (b:Binary)-[:CONTAINS]->(f:Function)<-[:DECRYPTED_BY]-(t:String)<-[cs:CONTAINS_STRING]-(b)
+                WHERE ($binary_name IS NULL OR b.filename CONTAINS $binary_name OR b.hash = $binary_name)
+                  AND (t.value CONTAINS $target OR cs.address = {address})
+                RETURN DISTINCT b, f, t, 'Decrypts' AS ref_type, '' AS offset
+            }}
+            RETURN coalesce(f.analyst_name, f.demangled_name, f.name) AS function, f.uid AS uid,
+                   ref_type, offset,
+                   labels(t)[0] AS kind, coalesce(t.value, t.address) AS target,
+                   b.filename AS binary
+            ORDER BY binary, offset
+        ",
+            address = BINARY_ADDRESS
+        );
+
+        let result = self
+            .connection
+            .read(
+                Cypher::new(&query_str)
+                    .param("target", target)
+                    .param("address", normalize_address(target))
+                    .param("relative", self.relative_targets(target, binary).await?)
+                    .param("binary_name", binary),
+            )
+            .await?;
+
+        let mut refs = Vec::new();
+        for row in result {
+            refs.push(DataXref {
+                function: row.get::<String>("function").unwrap_or_default(),
+                uid: row.get::<String>("uid").unwrap_or_default(),
+                ref_type: row.get::<String>("ref_type").unwrap_or_default(),
+                offset: row.get::<String>("offset").unwrap_or_default(),
+                kind: row.get::<String>("kind").unwrap_or_default(),
+                target: row.get::<String>("target").unwrap_or_default(),
+                binary: row.get::<String>("binary").unwrap_or_default(),
+            });
+        }
+
+        Ok(refs)
+    }
+
+    /// Functions whose name or UID contains `pattern`; with `param_type`, only those
+    /// taking a parameter of that type (compared without case and whitespace)
+    pub async fn query_functions(
+        &self,
+        pattern: &str,
+        binary: Option<&str>,
+        param_type: Option<&str>,
+    ) -> Result<Vec<Function>> {
+        let query_str = if let Some(_binary_name) = binary {
+            format!(
+                "MATCH (b:Binary)-[:CONTAINS|IMPORTS]->(f:Function)
+                 WHERE (f.name CONTAINS $pattern OR f.uid CONTAINS $pattern
+                        OR f.analyst_name CONTAINS $pattern OR f.demangled_name CONTAINS $pattern)
+                   AND (b.filename CONTAINS $binary_name OR b.hash = $binary_name)
+                   AND {}
+                 RETURN f
+                 LIMIT 100",
+                PARAM_TYPE_FILTER
+            )
+        } else {
+            format!(
+                "MATCH (f:Function)
+                 WHERE (f.name CONTAINS $pattern OR f.uid CONTAINS $pattern
+                        OR f.analyst_name CONTAINS $pattern OR f.demangled_name CONTAINS $pattern)
+                   AND {}
+                 RETURN f
+                 LIMIT 100",
+                PARAM_TYPE_FILTER
+            )
+        };
+
+        let mut query_builder = Cypher::new(query_str).param("pattern", pattern).param(
+            "param_type",
+            param_type.map(FunctionPrototype::normalize_type),
+        );
+        if let Some(binary_name) = binary {
+            query_builder = query_builder.param("binary_name", binary_name);
+        }
+
+        let result = self.connection.read(query_builder).await?;
+
+        let mut functions = Vec::new();
+        for row in result {
+            if let Ok(node) = row.get::<neo4rs::Node>("f") {
+                functions.push(function_from_node(&node));
+            }
+        }
+
+        Ok(functions)
+    }
+
+    /// Contained functions carrying a mnemonic histogram, with their binary's filename
+    /// All functions contained in a binary
+    pub async fn query_binary_functions(&self, binary_hash: &str) -> Result<Vec<Function>> {
+        let query_str = "
+            MATCH (b:Binary {hash: $binary_hash})-[:CONTAINS]->(f:Function)
+            OPTIONAL MATCH (f)-[:HAS_CHUNK]->(k:Chunk)
+            RETURN f, collect(k.start) AS chunk_starts, collect(k.size) AS chunk_sizes
+        ";
+
+        let result = self
+            .connection
+            .read(Cypher::new(query_str).param("binary_hash", binary_hash))
+            .await?;
+
+        let mut functions = Vec::new();
+        for row in result {
+            if let Ok(node) = row.get::<neo4rs::Node>("f") {
+                let mut function = function_from_node(&node);
+                let starts = row.get::<Vec<String>>("chunk_starts").unwrap_or_default();
+                let sizes = row.get::<Vec<i64>>("chunk_sizes").unwrap_or_default();
+                function.chunks = starts
+                    .into_iter()
+                    .zip(sizes)
+                    .map(|(start, size)| FunctionChunk {
+                        start,
+                        size: size.max(0) as u64,
+                    })
+                    .collect();
+                functions.push(function);
+            }
+        }
+
+        Ok(functions)
+    }
+
+    pub async fn query_mnemonic_histograms(
+        &self,
+        function_name: Option<&str>,
+        binary: Option<&str>,
+    ) -> Result<Vec<(String, Function)>> {
+        let query_str = "
+            MATCH (b:Binary)-[:CONTAINS]->(f:Function)
+            WHERE f.mnemonic_histogram IS NOT NULL
+              AND ($function_name IS NULL OR f.name = $function_name
+                   OR f.demangled_name = $function_name)
+              AND ($binary_name IS NULL OR b.filename CONTAINS $binary_name OR b.hash = $binary_name)
+            RETURN f, b.filename AS binary
+        ";
+
+        let result = self
+            .connection
+            .read(
+                Cypher::new(query_str)
+                    .param("function_name", function_name)
+                    .param("binary_name", binary),
+            )
+            .await?;
+
+        let mut functions = Vec::new();
+        for row in result {
+            if let Ok(node) = row.get::<neo4rs::Node>("f") {
+                let binary = row.get::<String>("binary").unwrap_or_default();
+                functions.push((binary, function_from_node(&node)));
+            }
+        }
+
+        Ok(functions)
+    }
+
+    /// Rename contained functions of a binary from recovered symbols
+    ///
+    /// `symbols` holds (function uid, name) pairs. The first extracted name is kept in
+    /// `original_name`. Returns the number of functions renamed.
+    pub async fn rename_functions(
+        &self,
+        binary_hash: &str,
+        symbols: &[(String, String)],
+    ) -> Result<usize> {
+        let query_str = "
+            UNWIND $rows AS row
+            MATCH (b:Binary {hash: $binary_hash})-[:CONTAINS]->(f:Function {uid: row.uid})
+            SET f.original_name = coalesce(f.original_name, f.name),
+                f.name = row.name,
+                f.demangled_name = row.demangled_name
+            RETURN count(f) AS renamed
+        ";
+
+        let mut renamed = 0;
+        for chunk in symbols.chunks(self.batch_size) {
+            let rows: Vec<HashMap<&str, BoltType>> = chunk
+                .iter()
+                .map(|(uid, name)| {
+                    HashMap::from([
+                        ("uid", BoltType::from(uid.as_str())),
+                        ("name", BoltType::from(name.as_str())),
+                        ("demangled_name", BoltType::from(demangle(name))),
+                    ])
+                })
+                .collect();
+
+            let mut result = self
+                .connection
+                .graph()
+                .execute(
+                    query(query_str)
+                        .param("binary_hash", binary_hash)
+                        .param("rows", rows),
+                )
+                .await?;
+            if let Some(row) = result.next().await? {
+                renamed += row.get::<i64>("renamed").unwrap_or(0) as usize;
+            }
+        }
+
+        Ok(renamed)
+    }
+
+    /// Set the analyst name and/or comment of a function, leaving unset values unchanged
+    pub async fn annotate_function(
+        &self,
+        uid: &str,
+        analyst_name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<Option<Function>> {
+        let query_str = "
+            MATCH (f:Function {uid: $uid})
+            SET f.analyst_name = coalesce($analyst_name, f.analyst_name),
+                f.comment = coalesce($comment, f.comment)
+            RETURN f
+        ";
+
+        self.single_function(
+            query(query_str)
+                .param("uid", uid)
+                .param("analyst_name", analyst_name)
+                .param("comment", comment),
+        )
+        .await
+    }
+
+    /// Remove the analyst name and comment of a function
+    pub async fn clear_function_annotations(&self, uid: &str) -> Result<Option<Function>> {
+        let query_str = "
+            MATCH (f:Function {uid: $uid})
+            REMOVE f.analyst_name, f.comment
+            RETURN f
+        ";
+
+        self.single_function(query(query_str).param("uid", uid))
+            .await
+    }
+
+    /// Analyst names, comments, symbol renames and family attributions in the graph
+    pub async fn query_annotations(&self) -> Result<Annotations> {
+        let functions = self
+            .connection
+            .read(Cypher::new(
+                "MATCH (f:Function)
+                 WHERE f.analyst_name IS NOT NULL OR f.comment IS NOT NULL
+                    OR f.original_name IS NOT NULL
+                 RETURN f.uid AS uid, f.analyst_name AS analyst_name, f.comment AS comment,
+                        CASE WHEN f.original_name IS NOT NULL THEN f.name END AS symbol_name
+                 ORDER BY uid",
+            ))
+            .await?
+            .into_iter()
+            .map(|row| FunctionAnnotation {
+                uid: row.get::<String>("uid").unwrap_or_default(),
+                analyst_name: row.get::<Option<String>>("analyst_name").ok().flatten(),
+                comment: row.get::<Option<String>>("comment").ok().flatten(),
+                symbol_name: row.get::<Option<String>>("symbol_name").ok().flatten(),
+            })
+            .collect();
+
+        let binaries = self
+            .connection
+            .read(Cypher::new(
+                "MATCH (b:Binary) WHERE b.family IS NOT NULL
+                 RETURN b.hash AS hash, b.family AS family
+                 ORDER BY hash",
+            ))
+            .await?
+            .into_iter()
+            .map(|row| BinaryAnnotation {
+                hash: row.get::<String>("hash").unwrap_or_default(),
+                family: row.get::<String>("family").unwrap_or_default(),
+            })
+            .collect();
+
+        Ok(Annotations {
+            version: ANNOTATIONS_VERSION,
+            functions,
+            binaries,
+        })
+    }
+
+    /// Apply exported annotations to the functions and binaries present in the graph
+    ///
+    /// Returns how many functions and binaries were found and updated.
+    pub async fn apply_annotations(&self, annotations: &Annotations) -> Result<(usize, usize)> {
+        let function_query = "
+            UNWIND $rows AS row
+            MATCH (f:Function {uid: row.uid})
+            SET f.analyst_name = coalesce(row.analyst_name, f.analyst_name),
+                f.comment = coalesce(row.comment, f.comment)
+            FOREACH (_ IN CASE WHEN row.symbol_name IS NULL THEN [] ELSE [1] END |
+                SET f.original_name = coalesce(f.original_name, f.name),
+                    f.name = row.symbol_name,
+                    f.demangled_name = row.demangled_name)
+            RETURN count(f) AS applied
+        ";
+        let binary_query = "
+            UNWIND $rows AS row
+            MATCH (b:Binary {hash: row.hash})
+            SET b.family = row.family
+            RETURN count(b) AS applied
+        ";
+
+        let mut functions = 0;
+        for chunk in annotations.functions.chunks(self.batch_size) {
+            let rows: Vec<HashMap<&str, BoltType>> = chunk
+                .iter()
+                .map(|annotation| {
+                    HashMap::from([
+                        ("uid", BoltType::from(annotation.uid.as_str())),
+                        (
+                            "analyst_name",
+                            BoltType::from(annotation.analyst_name.as_deref()),
+                        ),
+                        ("comment", BoltType::from(annotation.comment.as_deref())),
+                        (
+                            "symbol_name",
+                            BoltType::from(annotation.symbol_name.as_deref()),
+                        ),
+                        (
+                            "demangled_name",
+                            BoltType::from(annotation.symbol_name.as_deref().and_then(demangle)),
+                        ),
+                    ])
+                })
+                .collect();
+            functions += self
+                .count_applied(query(function_query).param("rows", rows))
+                .await?;
+        }
+
+        let mut binaries = 0;
+        for chunk in annotations.binaries.chunks(self.batch_size) {
+            let rows: Vec<HashMap<&str, BoltType>> = chunk
+                .iter()
+                .map(|annotation| {
+                    HashMap::from([
+                        ("hash", BoltType::from(annotation.hash.to_lowercase())),
+                        ("family", BoltType::from(annotation.family.as_str())),
+                    ])
+                })
+                .collect();
+            binaries += self
+                .count_applied(query(binary_query).param("rows", rows))
+                .await?;
+        }
+
+        Ok((functions, binaries))
+    }
+
+    async fn count_applied(&self, query: neo4rs::Query) -> Result<usize> {
+        let mut result = self.connection.graph().execute(query).await?;
+        let applied = match result.next().await? {
+            Some(row) => row.get::<i64>("applied").unwrap_or(0),
+            None => 0,
+        };
+        Ok(applied as usize)
+    }
+
+    async fn single_function(&self, query: neo4rs::Query) -> Result<Option<Function>> {
+        let mut result = self.connection.graph().execute(query).await?;
         if let Some(row) = result.next().await? {
-            if let Ok(node) = row.get::<neo4rs::Node>("b") {
-                let format_str = node
-                    .get::<String>("format")
-                    .unwrap_or_else(|_| "PE".to_string());
-                let format = match format_str.as_str() {
-                    "Elf" => crate::models::BinaryFormat::Elf,
-                    "MachO" => crate::models::BinaryFormat::MachO,
-                    _ => crate::models::BinaryFormat::PE,
-                };
-
-                let binary = Binary {
-                    hash: node.get::<String>("hash").unwrap_or_default(),
-                    filename: node.get::<String>("filename").unwrap_or_default(),
-                    file_path: node.get::<String>("file_path").unwrap_or_default(),
-                    file_size: node.get::<i64>("file_size").unwrap_or(0) as u64,
-                    format,
-                    arch: node.get::<String>("arch").unwrap_or_default(),
-                };
-                return Ok(Some(binary));
+            if let Ok(node) = row.get::<neo4rs::Node>("f") {
+                return Ok(Some(function_from_node(&node)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Store computed call-graph metrics as Function node properties
+    pub async fn store_function_metrics(&self, metrics: &[FunctionMetrics]) -> Result<()> {
+        let query_str = "
+            UNWIND $rows AS row
+            MATCH (f:Function {uid: row.uid})
+            SET f.in_degree = row.in_degree,
+                f.out_degree = row.out_degree,
+                f.entry_depth = row.entry_depth,
+                f.reachable_apis = row.reachable_apis
+        ";
+
+        for chunk in metrics.chunks(self.batch_size) {
+            let rows: Vec<HashMap<&str, BoltType>> = chunk
+                .iter()
+                .map(|m| {
+                    HashMap::from([
+                        ("uid", BoltType::from(m.uid.as_str())),
+                        ("in_degree", BoltType::from(m.in_degree as i64)),
+                        ("out_degree", BoltType::from(m.out_degree as i64)),
+                        // -1 marks functions unreachable from any entry point
+                        (
+                            "entry_depth",
+                            BoltType::from(m.entry_depth.map(|d| d as i64).unwrap_or(-1)),
+                        ),
+                        ("reachable_apis", BoltType::from(m.reachable_apis as i64)),
+                    ])
+                })
+                .collect();
+
+            self.connection
+                .graph()
+                .run(query(query_str).param("rows", rows))
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Query functions with stored metrics, ordered by `sort` (highest first)
+    pub async fn query_function_metrics(
+        &self,
+        pattern: &str,
+        binary: Option<&str>,
+        sort: FunctionSort,
+        limit: usize,
+    ) -> Result<Vec<FunctionMetrics>> {
+        let match_clause = if binary.is_some() {
+            "MATCH (b:Binary)-[:CONTAINS]->(f:Function)
+            WHERE (f.name CONTAINS $pattern OR f.uid CONTAINS $pattern)
+              AND (b.filename CONTAINS $binary_name OR b.hash = $binary_name)"
+        } else {
+            "MATCH (f:Function)
+            WHERE (f.name CONTAINS $pattern OR f.uid CONTAINS $pattern)"
+        };
+        let query_str = format!(
+            "{}
+              AND f.{property} IS NOT NULL
+            RETURN DISTINCT f
+            ORDER BY f.{property} DESC
+            LIMIT $limit",
+            match_clause,
+            property = sort.property()
+        );
+
+        let mut query_builder = Cypher::new(&query_str)
+            .param("pattern", pattern)
+            .param("limit", limit as i64);
+        if let Some(binary_name) = binary {
+            query_builder = query_builder.param("binary_name", binary_name);
+        }
+
+        let result = self.connection.read(query_builder).await?;
+
+        let mut metrics = Vec::new();
+        for row in result {
+            if let Ok(node) = row.get::<neo4rs::Node>("f") {
+                let count = |property: &str| node.get::<i64>(property).unwrap_or(0).max(0) as usize;
+                metrics.push(FunctionMetrics {
+                    uid: node.get::<String>("uid").unwrap_or_default(),
+                    name: node.get::<String>("name").unwrap_or_default(),
+                    address: node.get::<String>("address").ok(),
+                    in_degree: count("in_degree"),
+                    out_degree: count("out_degree"),
+                    entry_depth: node
+                        .get::<i64>("entry_depth")
+                        .ok()
+                        .filter(|d| *d >= 0)
+                        .map(|d| d as usize),
+                    reachable_apis: count("reachable_apis"),
+                });
+            }
+        }
+
+        Ok(metrics)
+    }
+
+    /// Binaries whose filename contains `term` or whose hash is `term`, functions whose
+    /// name contains it, strings matching `lucene_query` and libraries whose name
+    /// contains it (case-insensitive), at most `limit` of each kind
+    pub async fn search(
+        &self,
+        term: &str,
+        lucene_query: &str,
+        limit: usize,
+    ) -> Result<Vec<SearchHit>> {
+        let searches = [
+            (
+                SearchKind::Binary,
+                "MATCH (b:Binary)
+                 WHERE b.filename CONTAINS $term OR b.hash = toLower($term)
+                    OR b.md5 = toLower($term) OR b.sha1 = toLower($term)
+                 RETURN b.filename AS name, b.hash AS key, 1 AS samples,
+                        trim(coalesce(b.format, '') + ' ' + coalesce(b.arch, '')) AS detail
+                 ORDER BY name
+                 LIMIT $limit",
+            ),
+            (
+                SearchKind::Function,
+                "MATCH (f:Function)
+                 WHERE f.name CONTAINS $term OR f.analyst_name CONTAINS $term
+                    OR f.demangled_name CONTAINS $term
+                 WITH f LIMIT $limit
+                 OPTIONAL MATCH (b:Binary)-[:CONTAINS|IMPORTS]->(f)
+                 RETURN coalesce(f.analyst_name, f.name) AS name, f.uid AS key,
+                        count(DISTINCT b) AS samples, f.type AS detail
+                 ORDER BY samples DESC, name",
+            ),
+            (
+                SearchKind::String,
+                "CALL db.index.fulltext.queryNodes('string_value_fulltext', $lucene_query)
+                 YIELD node, score
+                 WITH node, score LIMIT $limit
+                 OPTIONAL MATCH (b:Binary)-[:CONTAINS_STRING]->(node)
+                 RETURN node.value AS name, node.uid AS key, count(DISTINCT b) AS samples,
+                        'score ' + toString(round(score * 100) / 100) AS detail
+                 ORDER BY score DESC",
+            ),
+            (
+                SearchKind::Library,
+                "MATCH (l:Library)
+                 WHERE toLower(l.name) CONTAINS toLower($term)
+                 WITH l LIMIT $limit
+                 OPTIONAL MATCH (b:Binary)-[:IMPORTS_LIBRARY]->(l)
+                 RETURN l.name AS name, l.name AS key, count(DISTINCT b) AS samples,
+                        '' AS detail
+                 ORDER BY samples DESC, name",
+            ),
+        ];
+
+        let mut hits = Vec::new();
+        for (kind, statement) in searches {
+            let result = self
+                .connection
+                .read(
+                    Cypher::new(statement)
+                        .param("term", term)
+                        .param("lucene_query", lucene_query)
+                        .param("limit", limit as i64),
+                )
+                .await?;
+            for row in result {
+                hits.push(SearchHit {
+                    kind,
+                    name: row.get::<String>("name").unwrap_or_default(),
+                    key: row.get::<String>("key").unwrap_or_default(),
+                    samples: row.get::<i64>("samples").unwrap_or(0),
+                    detail: row.get::<String>("detail").unwrap_or_default(),
+                });
             }
         }
 
+        Ok(hits)
+    }
+
+    pub async fn query_strings_fulltext(
+        &self,
+        lucene_query: &str,
+        binary: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<StringSearchHit>> {
+        let query_str = if let Some(_binary_name) = binary {
+            "
+            CALL db.index.fulltext.queryNodes('string_value_fulltext', $query) YIELD node, score
+            MATCH (b:Binary)-[:CONTAINS_STRING]->(node)
+            WHERE (b.filename CONTAINS $binary_name OR b.hash = $binary_name)
+            RETURN node AS s, score AS score, count(DISTINCT b) AS sample_count
+            ORDER BY score DESC
+            LIMIT $limit
+        "
+        } else {
+            "
+            CALL db.index.fulltext.queryNodes('string_value_fulltext', $query) YIELD node, score
+            MATCH (b:Binary)-[:CONTAINS_STRING]->(node)
+            RETURN node AS s, score AS score, count(DISTINCT b) AS sample_count
+            ORDER BY score DESC
+            LIMIT $limit
+        "
+        };
+
+        let mut query_builder = Cypher::new(query_str)
+            .param("query", lucene_query)
+            .param("limit", limit as i64);
+        if let Some(binary_name) = binary {
+            query_builder = query_builder.param("binary_name", binary_name);
+        }
+
+        let result = self.connection.read(query_builder).await?;
+        let mut hits = Vec::new();
+
+        for row in result {
+            let node = row.get::<neo4rs::Node>("s")?;
+            let uid = node.get::<String>("uid").unwrap_or_default();
+            let value = node.get::<String>("value").unwrap_or_default();
+            let score = row.get::<f64>("score").unwrap_or(0.0);
+            let sample_count = row.get::<i64>("sample_count").unwrap_or(0);
+
+            hits.push(StringSearchHit {
+                uid,
+                value,
+                score,
+                sample_count,
+            });
+        }
+
+        Ok(hits)
+    }
+
+    /// Functions whose decompiled code matches `lucene_query`, best first
+    pub async fn query_pseudocode_fulltext(
+        &self,
+        lucene_query: &str,
+        binary: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<PseudocodeHit>> {
+        let binary_filter = if binary.is_some() {
+            "WHERE (b.filename CONTAINS $binary_name OR b.hash = $binary_name)"
+        } else {
+            ""
+        };
+        let query_str = format!(
+            "CALL db.index.fulltext.queryNodes('function_pseudocode_fulltext', $query) YIELD node, score
+             MATCH (b:Binary)-[:CONTAINS]->(node)
+             {}
+             RETURN node.uid AS uid, node.name AS name, node.address AS address,
+                    node.pseudocode AS pseudocode, b.filename AS binary, score
+             ORDER BY score DESC
+             LIMIT $limit",
+            binary_filter
+        );
+
+        let mut query_builder = Cypher::new(query_str)
+            .param("query", lucene_query)
+            .param("limit", limit as i64);
+        if let Some(binary_name) = binary {
+            query_builder = query_builder.param("binary_name", binary_name);
+        }
+
+        let result = self.connection.read(query_builder).await?;
+        Ok(result
+            .iter()
+            .map(|row| PseudocodeHit {
+                uid: row.get::<String>("uid").unwrap_or_default(),
+                name: row.get::<String>("name").unwrap_or_default(),
+                address: row.get::<String>("address").ok(),
+                binary: row.get::<String>("binary").unwrap_or_default(),
+                score: row.get::<f64>("score").unwrap_or(0.0),
+                line: None,
+                snippet: String::new(),
+                pseudocode: row.get::<String>("pseudocode").unwrap_or_default(),
+            })
+            .collect())
+    }
+
+    pub async fn query_binary_info(&self, binary_name: &str) -> Result<Option<Binary>> {
+        let query_str = "
+            MATCH (b:Binary)
+            WHERE b.hash = $binary_name OR b.filename CONTAINS $binary_name
+            RETURN b, toString(coalesce(b.first_imported, b.imported_at)) AS first_imported,
+                   toString(coalesce(b.last_updated, b.imported_at)) AS last_updated,
+                   toString(b.compiled_at) AS compiled_at
+            LIMIT 1
+        ";
+
+        let result = self
+            .connection
+            .read(Cypher::new(query_str).param("binary_name", binary_name))
+            .await?;
+
+        if let Some(row) = result.into_iter().next() {
+            return Ok(binary_from_row(&row));
+        }
+
+        Ok(None)
+    }
+
+    /// Find a binary by any of its stored hashes (SHA-256, MD5, SHA-1, ssdeep or TLSH)
+    pub async fn query_binary_by_hash(&self, hash: &str) -> Result<Option<Binary>> {
+        let query_str = "
+            MATCH (b:Binary)
+            WHERE b.hash = $hash OR b.hash = toLower($hash)
+               OR b.md5 = toLower($hash) OR b.sha1 = toLower($hash)
+               OR b.ssdeep = $hash OR b.tlsh = toUpper($hash)
+            RETURN b, toString(coalesce(b.first_imported, b.imported_at)) AS first_imported,
+                   toString(coalesce(b.last_updated, b.imported_at)) AS last_updated,
+                   toString(b.compiled_at) AS compiled_at
+            LIMIT 1
+        ";
+
+        let result = self
+            .connection
+            .read(Cypher::new(query_str).param("hash", hash.trim()))
+            .await?;
+
+        if let Some(row) = result.into_iter().next() {
+            return Ok(binary_from_row(&row));
+        }
+
         Ok(None)
     }
 
+    /// Binaries other than `binary_hash` containing at least `min_shared` functions with
+    /// the same address and name as one of `functions`
+    pub async fn query_overlapping_binaries(
+        &self,
+        binary_hash: &str,
+        functions: &[Function],
+        min_shared: usize,
+    ) -> Result<Vec<OverlappingBinary>> {
+        let rows: Vec<HashMap<String, String>> = functions
+            .iter()
+            .filter_map(|function| {
+                Some(HashMap::from([
+                    ("address".to_string(), function.address.clone()?),
+                    ("name".to_string(), function.name.clone()),
+                ]))
+            })
+            .collect();
+
+        let query_str = "
+            UNWIND $functions AS function
+            MATCH (b:Binary)-[:CONTAINS]->(f:Function {address: function.address})
+            WHERE b.hash <> $binary_hash AND f.name = function.name
+            WITH b, count(DISTINCT f) AS shared
+            WHERE shared >= $min_shared
+            RETURN b, shared, size([(b)-[:CONTAINS]->(x:Function) | x]) AS functions,
+                   toString(coalesce(b.first_imported, b.imported_at)) AS first_imported,
+                   toString(coalesce(b.last_updated, b.imported_at)) AS last_updated,
+                   toString(b.compiled_at) AS compiled_at
+            ORDER BY shared DESC
+        ";
+
+        let result = self
+            .connection
+            .read(
+                Cypher::new(query_str)
+                    .param("binary_hash", binary_hash)
+                    .param("functions", rows)
+                    .param("min_shared", min_shared as i64),
+            )
+            .await?;
+
+        Ok(result
+            .iter()
+            .filter_map(|row| {
+                Some(OverlappingBinary {
+                    binary: binary_from_row(row)?,
+                    shared: row.get::<i64>("shared").unwrap_or(0) as usize,
+                    functions: row.get::<i64>("functions").unwrap_or(0) as usize,
+                })
+            })
+            .collect())
+    }
+
+    /// All binaries that carry an ssdeep or TLSH hash
+    pub async fn query_fuzzy_hashed_binaries(&self) -> Result<Vec<Binary>> {
+        let query_str = "
+            MATCH (b:Binary)
+            WHERE b.ssdeep IS NOT NULL OR b.tlsh IS NOT NULL
+            RETURN b, toString(coalesce(b.first_imported, b.imported_at)) AS first_imported,
+                   toString(coalesce(b.last_updated, b.imported_at)) AS last_updated,
+                   toString(b.compiled_at) AS compiled_at
+        ";
+
+        let result = self.connection.read(Cypher::new(query_str)).await?;
+        let binaries = result.iter().filter_map(binary_from_row).collect();
+
+        Ok(binaries)
+    }
+
+    /// List binaries matching toolchain metadata (substring, case-insensitive), PE imphash,
+    /// architecture and first import or compile date
+    pub async fn query_binaries(
+        &self,
+        filter: &BinaryFilter<'_>,
+        limit: usize,
+    ) -> Result<Vec<Binary>> {
+        let query_str = "
+            MATCH (b:Binary)
+            WHERE ($compiler IS NULL OR toLower(b.compiler) CONTAINS toLower($compiler))
+              AND ($packer IS NULL OR toLower(b.packer) CONTAINS toLower($packer))
+              AND ($imphash IS NULL OR b.imphash = toLower($imphash))
+              AND ($min_entropy IS NULL OR b.max_entropy >= $min_entropy)
+              AND ($arch_names IS NULL OR toLower(b.arch) IN $arch_names)
+              AND ($since IS NULL
+                   OR coalesce(b.first_imported, b.imported_at) >= datetime($since))
+              AND ($compiled_since IS NULL OR b.compiled_at >= datetime($compiled_since))
+            RETURN b, toString(coalesce(b.first_imported, b.imported_at)) AS first_imported,
+                   toString(coalesce(b.last_updated, b.imported_at)) AS last_updated,
+                   toString(b.compiled_at) AS compiled_at
+            ORDER BY b.filename
+            LIMIT $limit
+        ";
+
+        let result = self
+            .connection
+            .read(
+                Cypher::new(query_str)
+                    .param("compiler", filter.compiler)
+                    .param("packer", filter.packer)
+                    .param("imphash", filter.imphash)
+                    .param("min_entropy", filter.min_entropy)
+                    .param("arch_names", filter.arch.map(Architecture::names))
+                    .param("since", filter.since)
+                    .param("compiled_since", filter.compiled_since)
+                    .param("limit", limit as i64),
+            )
+            .await?;
+
+        let binaries = result.iter().filter_map(binary_from_row).collect();
+
+        Ok(binaries)
+    }
+
     pub async fn query_callgraph_with_depth(
         &self,
         function_name: &str,
         binary: Option<&str>,
         max_depth: usize,
+        exclude_library: bool,
+        min_confidence: f64,
     ) -> Result<CallGraph> {
+        // Each path is unwound into its individual CALLS hops. Depth is the hop distance
+        // from the queried function, keeping the shortest one when an edge is reachable
+        // through several paths.
         let callees_query = if let Some(_binary_name) = binary {
             format!(
-                "MATCH (b:Binary)-[:CONTAINS|IMPORTS]->(f:Function)-[:CALLS*1..{}]->(callee:Function)
-                 WHERE (f.name = $function_name OR f.uid = $function_name)
+                "MATCH (b:Binary)-[:CONTAINS|IMPORTS]->(f:Function)
+                 WHERE (f.name = $function_name OR f.demangled_name = $function_name OR f.uid = $function_name)
                    AND (b.filename CONTAINS $binary_name OR b.hash = $binary_name)
+                 MATCH path = (f)-[:CALLS*1..{}]->(callee:Function)
+                 WHERE ALL(n IN nodes(path)[..-1] WHERE EXISTS((b)-[:CONTAINS]->(n)))
                    AND EXISTS((b)-[:CONTAINS|IMPORTS]->(callee))
-                 RETURN DISTINCT callee",
-                max_depth
+                   AND NONE(n IN nodes(path)[1..] WHERE {})
+                   AND {}
+                 UNWIND range(0, length(path) - 1) AS i
+                 WITH nodes(path)[i] AS src, nodes(path)[i + 1] AS dst,
+                      relationships(path)[i] AS r, i + 1 AS depth
+                 {}",
+                max_depth, LIBRARY_FILTER, CONFIDENCE_FILTER, CALL_EDGE_RETURN
             )
         } else {
             format!(
-                "MATCH (f:Function)-[:CALLS*1..{}]->(callee:Function)
-                 WHERE f.name = $function_name OR f.uid = $function_name
-                 RETURN DISTINCT callee",
-                max_depth
+                "MATCH path = (f:Function)-[:CALLS*1..{}]->(:Function)
+                 WHERE (f.name = $function_name OR f.demangled_name = $function_name OR f.uid = $function_name)
+                   AND NONE(n IN nodes(path)[1..] WHERE {})
+                   AND {}
+                 UNWIND range(0, length(path) - 1) AS i
+                 WITH nodes(path)[i] AS src, nodes(path)[i + 1] AS dst,
+                      relationships(path)[i] AS r, i + 1 AS depth
+                 {}",
+                max_depth, LIBRARY_FILTER, CONFIDENCE_FILTER, CALL_EDGE_RETURN
             )
         };
 
-        let mut query_builder = query(&callees_query).param("function_name", function_name);
-        if let Some(binary_name) = binary {
-            query_builder = query_builder.param("binary_name", binary_name);
-        }
-
-        let mut result = self.connection.graph().execute(query_builder).await?;
-
-        let mut callees = Vec::new();
-        while let Some(row) = result.next().await? {
-            if let Ok(node) = row.get::<neo4rs::Node>("callee") {
-                callees.push(FunctionInfo {
-                    uid: node.get::<String>("uid").unwrap_or_default(),
-                    name: node.get::<String>("name").unwrap_or_default(),
-                    address: node.get::<String>("address").ok(),
-                });
-            }
-        }
+        let callee_edges = self
+            .query_call_edges(
+                &callees_query,
+                function_name,
+                binary,
+                exclude_library,
+                min_confidence,
+                "callee",
+            )
+            .await?;
 
         let callers_query = if let Some(_binary_name) = binary {
             format!(
-                "MATCH (b:Binary)-[:CONTAINS|IMPORTS]->(f:Function)<-[:CALLS*1..{}]-(caller:Function)
-                 WHERE (f.name = $function_name OR f.uid = $function_name)
+                "MATCH (b:Binary)-[:CONTAINS|IMPORTS]->(f:Function)
+                 WHERE (f.name = $function_name OR f.demangled_name = $function_name OR f.uid = $function_name)
                    AND (b.filename CONTAINS $binary_name OR b.hash = $binary_name)
-                   AND EXISTS((b)-[:CONTAINS|IMPORTS]->(caller))
-                 RETURN DISTINCT caller",
-                max_depth
+                 MATCH path = (:Function)-[:CALLS*1..{}]->(f)
+                 WHERE ALL(n IN nodes(path)[..-1] WHERE EXISTS((b)-[:CONTAINS]->(n)))
+                   AND NONE(n IN nodes(path)[..-1] WHERE {})
+                   AND {}
+                 UNWIND range(0, length(path) - 1) AS i
+                 WITH nodes(path)[i] AS src, nodes(path)[i + 1] AS dst,
+                      relationships(path)[i] AS r, length(path) - i AS depth
+                 {}",
+                max_depth, LIBRARY_FILTER, CONFIDENCE_FILTER, CALL_EDGE_RETURN
             )
         } else {
             format!(
-                "MATCH (caller:Function)-[:CALLS*1..{}]->(f:Function)
-                 WHERE f.name = $function_name OR f.uid = $function_name
-                 RETURN DISTINCT caller",
-                max_depth
+                "MATCH path = (:Function)-[:CALLS*1..{}]->(f:Function)
+                 WHERE (f.name = $function_name OR f.demangled_name = $function_name OR f.uid = $function_name)
+                   AND NONE(n IN nodes(path)[..-1] WHERE {})
+                   AND {}
+                 UNWIND range(0, length(path) - 1) AS i
+                 WITH nodes(path)[i] AS src, nodes(path)[i + 1] AS dst,
+                      relationships(path)[i] AS r, length(path) - i AS depth
+                 {}",
+                max_depth, LIBRARY_FILTER, CONFIDENCE_FILTER, CALL_EDGE_RETURN
             )
         };
 
-        let mut query_builder = query(&callers_query).param("function_name", function_name);
-        if let Some(binary_name) = binary {
-            query_builder = query_builder.param("binary_name", binary_name);
-        }
+        let caller_edges = self
+            .query_call_edges(
+                &callers_query,
+                function_name,
+                binary,
+                exclude_library,
+                min_confidence,
+                "caller",
+            )
+            .await?;
 
-        let mut result = self.connection.graph().execute(query_builder).await?;
+        let mut callees: Vec<FunctionInfo> = Vec::new();
+        for (_, to, _) in &callee_edges {
+            if !callees.iter().any(|f| f.uid == to.uid) {
+                callees.push(to.clone());
+            }
+        }
 
-        let mut callers = Vec::new();
-        while let Some(row) = result.next().await? {
-            if let Ok(node) = row.get::<neo4rs::Node>("caller") {
-                callers.push(FunctionInfo {
-                    uid: node.get::<String>("uid").unwrap_or_default(),
-                    name: node.get::<String>("name").unwrap_or_default(),
-                    address: node.get::<String>("address").ok(),
-                });
+        let mut callers: Vec<FunctionInfo> = Vec::new();
+        for (from, _, _) in &caller_edges {
+            if !callers.iter().any(|f| f.uid == from.uid) {
+                callers.push(from.clone());
             }
         }
 
-        Ok(CallGraph { callees, callers })
+        let edges = callee_edges
+            .into_iter()
+            .chain(caller_edges)
+            .map(|(_, _, edge)| edge)
+            .collect();
+
+        Ok(CallGraph {
+            callees,
+            callers,
+            edges,
+        })
     }
 
-    pub async fn query_xrefs(&self, address: &str, binary: Option<&str>) -> Result<Vec<Xref>> {
-        let query_str = if let Some(_binary_name) = binary {
-            "
-            CALL {
-                MATCH (b:Binary)
-                WHERE (b.filename CONTAINS $binary_name OR b.hash = $binary_name)
-                MATCH (b)-[:CONTAINS|IMPORTS]->(from:Function)-[r:CALLS]->(to:Function)
-                WHERE (from.address = $address OR to.address = $address)
-                RETURN from.name as from_function, to.name as to_function, r.offset as offset
-                UNION
-                MATCH (b:Binary)
-                WHERE (b.filename CONTAINS $binary_name OR b.hash = $binary_name)
-                MATCH (b)-[:CONTAINS|IMPORTS]->(from:Function)-[r:CALLS]->(to:Function)
-                MATCH (b)-[imp:IMPORTS]->(to:Function)
-                WHERE imp.address = $address
-                RETURN from.name as from_function, to.name as to_function, r.offset as offset
-            }
-            RETURN DISTINCT from_function, to_function, offset
-        "
-        } else {
-            "
-            MATCH (from:Function)-[r:CALLS]->(to:Function)
-            WHERE from.address = $address OR to.address = $address
-            RETURN from.name as from_function, to.name as to_function, r.offset as offset
-        "
-        };
-
-        let mut query_builder = query(query_str).param("address", address);
+    async fn query_call_edges(
+        &self,
+        cypher: &str,
+        function_name: &str,
+        binary: Option<&str>,
+        exclude_library: bool,
+        min_confidence: f64,
+        direction: &str,
+    ) -> Result<Vec<(FunctionInfo, FunctionInfo, CallEdge)>> {
+        let mut query_builder = Cypher::new(cypher)
+            .param("function_name", function_name)
+            .param("exclude_library", exclude_library)
+            .param("min_confidence", min_confidence);
         if let Some(binary_name) = binary {
             query_builder = query_builder.param("binary_name", binary_name);
         }
 
-        let mut result = self.connection.graph().execute(query_builder).await?;
+        let result = self.connection.read(query_builder).await?;
+
+        let mut edges = Vec::new();
+        for row in result {
+            let from = FunctionInfo {
+                uid: row.get::<String>("from_uid").unwrap_or_default(),
+                name: row.get::<String>("from_name").unwrap_or_default(),
+                address: row.get::<String>("from_address").ok(),
+            };
+            let to = FunctionInfo {
+                uid: row.get::<String>("to_uid").unwrap_or_default(),
+                name: row.get::<String>("to_name").unwrap_or_default(),
+                address: row.get::<String>("to_address").ok(),
+            };
+            let edge = CallEdge {
+                direction: direction.to_string(),
+                from_uid: from.uid.clone(),
+                from_name: from.name.clone(),
+                from_type: row
+                    .get::<String>("from_type")
+                    .unwrap_or_else(|_| "Internal".to_string()),
+                to_uid: to.uid.clone(),
+                to_name: to.name.clone(),
+                to_type: row
+                    .get::<String>("to_type")
+                    .unwrap_or_else(|_| "Internal".to_string()),
+                offset: row.get::<String>("offset").unwrap_or_default(),
+                call_type: row
+                    .get::<String>("call_type")
+                    .unwrap_or_else(|_| "Direct".to_string()),
+                source: row
+                    .get::<String>("source")
+                    .unwrap_or_else(|_| "static".to_string()),
+                confidence: row.get::<f64>("confidence").unwrap_or(1.0),
+                depth: row.get::<i64>("depth").unwrap_or(1) as usize,
+            };
+            edges.push((from, to, edge));
+        }
+
+        Ok(edges)
+    }
+
+    /// CALLS edges touching a function given by address, name or uid
+    ///
+    /// An address also matches the binary's import address of an imported callee.
+    pub async fn query_xrefs(
+        &self,
+        target: &str,
+        binary: Option<&str>,
+        direction: XrefDirection,
+    ) -> Result<Vec<Xref>> {
+        let query_str = format!(
+            "
+            MATCH (b:Binary)-[:CONTAINS]->(from:Function)-[r:CALLS]->(to:Function)
+            WHERE ($binary_name IS NULL OR b.filename CONTAINS $binary_name OR b.hash = $binary_name)
+              AND (($match_to AND (to.address = {address} OR to.name = $target
+                                   OR to.demangled_name = $target OR to.uid = $target
+                                   OR ANY(imp IN [(b)-[i:IMPORTS]->(to) | i] WHERE imp.address = {address})))
+                OR ($match_from AND (from.address = {address} OR from.name = $target
+                                     OR from.demangled_name = $target OR from.uid = $target)))
+            RETURN DISTINCT coalesce(from.analyst_name, from.demangled_name, from.name) AS from_function,
+                   coalesce(to.analyst_name, to.demangled_name, to.name) AS to_function,
+                   r.offset AS offset, coalesce(r.offsets, [r.offset]) AS offsets,
+                   coalesce(r.count, 1) AS count, r.call_type AS call_type,
+                   b.filename AS binary, b.hash AS hash
+            ORDER BY binary, offset
+        ",
+            address = BINARY_ADDRESS
+        );
+
+        let result = self
+            .connection
+            .read(
+                Cypher::new(&query_str)
+                    .param("target", target)
+                    .param("address", normalize_address(target))
+                    .param("relative", self.relative_targets(target, binary).await?)
+                    .param("binary_name", binary)
+                    .param("match_to", direction.includes_to())
+                    .param("match_from", direction.includes_from()),
+            )
+            .await?;
 
         let mut xrefs = Vec::new();
-        while let Some(row) = result.next().await? {
-            if let (Ok(from), Ok(to), Ok(offset)) = (
+        for row in result {
+            if let (Ok(from), Ok(to)) = (
                 row.get::<String>("from_function"),
                 row.get::<String>("to_function"),
-                row.get::<String>("offset"),
             ) {
                 xrefs.push(Xref {
                     from_function: from,
                     to_function: to,
-                    offset,
+                    offset: row.get::<String>("offset").unwrap_or_default(),
+                    offsets: row.get::<Vec<String>>("offsets").unwrap_or_default(),
+                    count: row.get::<i64>("count").unwrap_or(1).max(0) as usize,
+                    call_type: row.get::<String>("call_type").ok(),
+                    binary: row.get::<String>("binary").unwrap_or_default(),
+                    hash: row.get::<String>("hash").unwrap_or_default(),
                 });
             }
         }
 
         Ok(xrefs)
     }
+
+    /// Functions called by `function`, ordered by their number of call sites
+    pub async fn query_hot_callees(
+        &self,
+        function: &str,
+        binary: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<HotCallee>> {
+        let query_str = format!(
+            "
+            MATCH (b:Binary)-[:CONTAINS]->(caller:Function)-[r:CALLS]->(callee:Function)
+            WHERE ($binary_name IS NULL OR b.filename CONTAINS $binary_name OR b.hash = $binary_name)
+              AND (caller.name = $function OR caller.demangled_name = $function
+                   OR caller.uid = $function OR caller.address = {address})
+            WITH b, caller, collect({{callee: callee, r: r}}) AS calls,
+                 sum(coalesce(r.count, 1)) AS total
+            UNWIND calls AS call
+            WITH b, caller, total, call.callee AS callee, call.r AS r
+            RETURN coalesce(caller.analyst_name, caller.demangled_name, caller.name) AS caller,
+                   coalesce(callee.analyst_name, callee.demangled_name, callee.name) AS callee,
+                   CASE WHEN coalesce(callee.is_library, false) THEN 'Library' ELSE callee.type END AS callee_type,
+                   coalesce(r.count, 1) AS count, total,
+                   coalesce(r.offsets, [r.offset]) AS offsets,
+                   b.filename AS binary
+            ORDER BY count DESC, binary, callee
+            LIMIT $limit
+        ",
+            address = BINARY_ADDRESS
+        );
+
+        let result = self
+            .connection
+            .read(
+                Cypher::new(&query_str)
+                    .param("function", function)
+                    .param("address", normalize_address(function))
+                    .param("relative", self.relative_targets(function, binary).await?)
+                    .param("binary_name", binary)
+                    .param("limit", limit as i64),
+            )
+            .await?;
+
+        let mut callees = Vec::new();
+        for row in result {
+            let count = row.get::<i64>("count").unwrap_or(1).max(0) as usize;
+            let total = row.get::<i64>("total").unwrap_or(1).max(1) as usize;
+            callees.push(HotCallee {
+                caller: row.get::<String>("caller").unwrap_or_default(),
+                callee: row.get::<String>("callee").unwrap_or_default(),
+                callee_type: row
+                    .get::<String>("callee_type")
+                    .unwrap_or_else(|_| "Internal".to_string()),
+                count,
+                share: count as f64 * 100.0 / total as f64,
+                offsets: row.get::<Vec<String>>("offsets").unwrap_or_default(),
+                binary: row.get::<String>("binary").unwrap_or_default(),
+            });
+        }
+
+        Ok(callees)
+    }
+
+    /// `[image_base, address]` pairs of `target` as stored by the binaries matching
+    /// `binary` that keep addresses relative to their image base, for [`BINARY_ADDRESS`]
+    async fn relative_targets(
+        &self,
+        target: &str,
+        binary: Option<&str>,
+    ) -> Result<Vec<Vec<String>>> {
+        let Some(address) = parse_address(target) else {
+            return Ok(Vec::new());
+        };
+        let rows = self
+            .connection
+            .read(
+                Cypher::new(
+                    "MATCH (b:Binary)
+                     WHERE b.image_base IS NOT NULL
+                       AND ($binary_name IS NULL OR b.filename CONTAINS $binary_name OR b.hash = $binary_name)
+                     RETURN DISTINCT b.image_base AS image_base",
+                )
+                .param("binary_name", binary),
+            )
+            .await?;
+        let image_bases: Vec<String> = rows
+            .iter()
+            .filter_map(|row| row.get::<String>("image_base").ok())
+            .collect();
+        Ok(relative_pairs(address, &image_bases))
+    }
+}
+
+/// `address` relative to each image base that parses, as `[image_base, address]`
+fn relative_pairs(address: u64, image_bases: &[String]) -> Vec<Vec<String>> {
+    image_bases
+        .iter()
+        .filter_map(|image_base| {
+            let relative = relative_address(address, parse_address(image_base)?);
+            Some(vec![image_base.clone(), format_address(relative)])
+        })
+        .collect()
 }
 
+/// `$address` as stored for binary `b`: virtual addresses are matched relative to the
+/// binary's image base, using the `$relative` pairs of [`relative_pairs`]
+const BINARY_ADDRESS: &str =
+    "coalesce(head([pair IN $relative WHERE pair[0] = b.image_base | pair[1]]), $address)";
+
+/// Function `f` takes a parameter of type `$param_type` (normalized), or no type is given
+const PARAM_TYPE_FILTER: &str = "($param_type IS NULL
+    OR ANY(t IN coalesce(f.param_types, []) WHERE replace(toLower(t), ' ', '') = $param_type))";
+
+/// Matches library functions when `$exclude_library` is set
+const LIBRARY_FILTER: &str = "$exclude_library AND coalesce(n.is_library, false)";
+
+/// Keeps paths whose calls all reach `$min_confidence`; edges imported before
+/// confidences were recorded count as certain
+const CONFIDENCE_FILTER: &str =
+    "ALL(r IN relationships(path) WHERE coalesce(r.confidence, 1.0) >= $min_confidence)";
+
+const CALL_EDGE_RETURN: &str = "
+    RETURN src.uid AS from_uid, coalesce(src.analyst_name, src.demangled_name, src.name) AS from_name,
+           src.address AS from_address,
+           CASE WHEN coalesce(src.is_library, false) THEN 'Library' ELSE src.type END AS from_type,
+           dst.uid AS to_uid, coalesce(dst.analyst_name, dst.demangled_name, dst.name) AS to_name,
+           dst.address AS to_address,
+           CASE WHEN coalesce(dst.is_library, false) THEN 'Library' ELSE dst.type END AS to_type,
+           r.offset AS offset, r.call_type AS call_type,
+           coalesce(r.source, 'static') AS source, coalesce(r.confidence, 1.0) AS confidence,
+           min(depth) AS depth
+    ORDER BY depth, from_name, offset";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CallGraph {
     pub callees: Vec<FunctionInfo>,
     pub callers: Vec<FunctionInfo>,
+    pub edges: Vec<CallEdge>,
 }
 
+/// A single CALLS relationship reached while expanding a call graph
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct FunctionInfo {
-    pub uid: String,
-    pub name: String,
-    pub address: Option<String>,
+pub struct CallEdge {
+    /// "callee" for edges below the queried function, "caller" for edges above it
+    pub direction: String,
+    pub from_uid: String,
+    pub from_name: String,
+    /// Function type of the caller, or `Library` for library code
+    pub from_type: String,
+    pub to_uid: String,
+    pub to_name: String,
+    pub to_type: String,
+    pub offset: String,
+    pub call_type: String,
+    /// `static`, `dynamic` or `heuristic`
+    pub source: String,
+    pub confidence: f64,
+    /// Hop distance from the queried function
+    pub depth: usize,
+}
+
+/// Function, string and call sets of one binary, compared on re-import
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BinarySubgraph {
+    /// UIDs of functions linked by CONTAINS
+    pub functions: HashSet<String>,
+    /// UIDs of strings linked by CONTAINS_STRING
+    pub strings: HashSet<String>,
+    /// Caller and callee UIDs of CALLS edges leaving the binary's functions
+    pub calls: HashSet<(String, String)>,
+}
+
+impl BinarySubgraph {
+    /// Elements of `self` missing from `other`
+    pub fn difference(&self, other: &BinarySubgraph) -> BinarySubgraph {
+        BinarySubgraph {
+            functions: self
+                .functions
+                .difference(&other.functions)
+                .cloned()
+                .collect(),
+            strings: self.strings.difference(&other.strings).cloned().collect(),
+            calls: self.calls.difference(&other.calls).cloned().collect(),
+        }
+    }
+}
+
+/// Import time cutoff for `database clear --older-than`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportAge<'a> {
+    /// Imported before this ISO 8601 date or datetime
+    Before(&'a str),
+    /// Imported more than this many days ago
+    Days(i64),
+}
+
+/// Binaries selected by a scoped `database clear`; unset fields match every binary
+///
+/// Binaries imported before import times were recorded count as older than any cutoff.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ClearScope<'a> {
+    pub binary: Option<&'a str>,
+    pub family: Option<&'a str>,
+    pub older_than: Option<ImportAge<'a>>,
+}
+
+impl ClearScope<'_> {
+    pub fn is_empty(&self) -> bool {
+        self.binary.is_none() && self.family.is_none() && self.older_than.is_none()
+    }
+}
+
+/// Nodes removed by a scoped `database clear`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ClearCounts {
+    pub binaries: i64,
+    pub functions: i64,
+    pub strings: i64,
+    /// Libraries, imported functions, resources, indicators and other linked nodes
+    pub other: i64,
+}
+
+/// Optional criteria for `query_binaries`; unset fields match every binary
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BinaryFilter<'a> {
+    pub compiler: Option<&'a str>,
+    pub packer: Option<&'a str>,
+    pub imphash: Option<&'a str>,
+    /// Highest section entropy at least this value
+    pub min_entropy: Option<f64>,
+    pub arch: Option<Architecture>,
+    /// First imported on or after this ISO 8601 date or datetime
+    pub since: Option<&'a str>,
+    /// Compile timestamp on or after this ISO 8601 date or datetime
+    pub compiled_since: Option<&'a str>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Xref {
     pub from_function: String,
     pub to_function: String,
+    /// First call site
+    pub offset: String,
+    /// Every call site from `from_function` to `to_function`
+    pub offsets: Vec<String>,
+    /// Number of call sites
+    pub count: usize,
+    pub call_type: Option<String>,
+    /// Filename of the binary containing the calling function
+    pub binary: String,
+    pub hash: String,
+}
+
+/// Stored binary sharing functions with one being imported under another hash
+#[derive(Debug, Clone)]
+pub struct OverlappingBinary {
+    pub binary: Binary,
+    /// Functions with the same address and name in both binaries
+    pub shared: usize,
+    /// Functions the stored binary contains
+    pub functions: usize,
+}
+
+/// Callee of `query hot-callees` with its call-site count
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HotCallee {
+    pub caller: String,
+    pub callee: String,
+    /// Function type of the callee, or `Library` for library code
+    pub callee_type: String,
+    /// Number of call sites in the caller
+    pub count: usize,
+    /// Percentage of all the caller's call sites
+    pub share: f64,
+    pub offsets: Vec<String>,
+    /// Filename of the binary containing the caller
+    pub binary: String,
+}
+
+/// Function reading or taking the address of a data item, from `query data-refs`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataXref {
+    pub function: String,
+    pub uid: String,
+    pub ref_type: String,
+    /// Address of the referencing instruction
     pub offset: String,
+    /// Target node label (Data or String)
+    pub kind: String,
+    /// Data address or string value
+    pub target: String,
+    pub binary: String,
+}
+
+fn function_from_node(node: &neo4rs::Node) -> Function {
+    let type_str = node
+        .get::<String>("type")
+        .unwrap_or_else(|_| "Internal".to_string());
+    let r#type = match type_str.as_str() {
+        "Import" => crate::models::FunctionType::Import,
+        "Export" => crate::models::FunctionType::Export,
+        "Thunk" => crate::models::FunctionType::Thunk,
+        _ => crate::models::FunctionType::Internal,
+    };
+
+    Function {
+        uid: node.get::<String>("uid").unwrap_or_default(),
+        name: node.get::<String>("name").unwrap_or_default(),
+        demangled_name: node.get::<String>("demangled_name").ok(),
+        canonical_name: node.get::<String>("canonical_name").ok(),
+        address: node.get::<String>("address").ok(),
+        r#type,
+        size: node
+            .get::<i64>("size")
+            .ok()
+            .filter(|s| *s >= 0)
+            .map(|s| s as u64),
+        mnemonic_histogram: node
+            .get::<String>("mnemonic_histogram")
+            .ok()
+            .map(|h| MnemonicHistogram::decode(&h)),
+        is_library: node.get::<bool>("is_library").unwrap_or(false),
+        signature_match: node.get::<String>("signature_match").ok(),
+        analyst_name: node.get::<String>("analyst_name").ok(),
+        comment: node.get::<String>("comment").ok(),
+        entry_kind: node
+            .get::<String>("entry_kind")
+            .ok()
+            .and_then(|kind| kind.parse().ok()),
+        pseudocode: node.get::<String>("pseudocode").ok(),
+        pseudocode_blob: node.get::<String>("pseudocode_blob").ok(),
+        prototype: prototype_from_node(node),
+        crypto: node.get::<Vec<String>>("crypto").unwrap_or_default(),
+        content_hash: node.get::<String>("content_hash").ok(),
+        chunks: Vec::new(),
+    }
+}
+
+/// Prototype stored on a Function node, if any part of it was recorded
+fn prototype_from_node(node: &neo4rs::Node) -> Option<FunctionPrototype> {
+    let types = node.get::<Vec<String>>("param_types").ok();
+    let names = node.get::<Vec<String>>("param_names").unwrap_or_default();
+    let prototype = FunctionPrototype {
+        calling_convention: node.get::<String>("calling_convention").ok(),
+        return_type: node.get::<String>("return_type").ok(),
+        parameters: types
+            .iter()
+            .flatten()
+            .enumerate()
+            .map(|(i, r#type)| Parameter {
+                name: names.get(i).cloned().unwrap_or_default(),
+                r#type: r#type.clone(),
+            })
+            .collect(),
+    };
+    (types.is_some() || prototype != FunctionPrototype::default()).then_some(prototype)
+}
+
+/// Binary returned as `b` together with its import and compile times as text, which
+/// the driver cannot read from the node's datetime properties
+fn binary_from_row(row: &neo4rs::Row) -> Option<Binary> {
+    let node = row.get::<neo4rs::Node>("b").ok()?;
+    Some(Binary {
+        compiled_at: row.get::<String>("compiled_at").ok(),
+        first_imported: row.get::<String>("first_imported").ok(),
+        last_updated: row.get::<String>("last_updated").ok(),
+        ..binary_from_node(&node)
+    })
+}
+
+fn binary_from_node(node: &neo4rs::Node) -> Binary {
+    let format_str = node
+        .get::<String>("format")
+        .unwrap_or_else(|_| "PE".to_string());
+    let format = match format_str.as_str() {
+        "Elf" => crate::models::BinaryFormat::Elf,
+        "MachO" => crate::models::BinaryFormat::MachO,
+        _ => crate::models::BinaryFormat::PE,
+    };
+    // Nodes written before normalization hold the extractor's spelling and no bitness
+    let bitness = node.get::<i64>("bitness").ok().map(|bits| bits as u32);
+    let arch = Architecture::parse(&node.get::<String>("arch").unwrap_or_default(), bitness);
+
+    Binary {
+        hash: node.get::<String>("hash").unwrap_or_default(),
+        md5: node.get::<String>("md5").ok(),
+        sha1: node.get::<String>("sha1").ok(),
+        ssdeep: node.get::<String>("ssdeep").ok(),
+        tlsh: node.get::<String>("tlsh").ok(),
+        filename: node.get::<String>("filename").unwrap_or_default(),
+        file_path: node.get::<String>("file_path").unwrap_or_default(),
+        file_size: node.get::<i64>("file_size").unwrap_or(0) as u64,
+        format,
+        arch,
+        bitness: bitness.or(arch.bits()),
+        image_base: node.get::<String>("image_base").ok(),
+        compiler: node.get::<String>("compiler").ok(),
+        packer: node.get::<String>("packer").ok(),
+        family: node.get::<String>("family").ok(),
+        linker_version: node.get::<String>("linker_version").ok(),
+        pdb_path: node.get::<String>("pdb_path").ok(),
+        timestamp: node.get::<String>("timestamp").ok(),
+        compiled_at: None,
+        first_imported: None,
+        last_updated: None,
+        pe_info: Some(PeInfo {
+            imphash: node.get::<String>("imphash").ok(),
+            rich_header_hash: node.get::<String>("rich_header_hash").ok(),
+            entry_point: node.get::<String>("entry_point").ok(),
+            subsystem: node.get::<String>("subsystem").ok(),
+            signer: node.get::<String>("signer").ok(),
+        })
+        .filter(|pe| !pe.is_empty()),
+        elf_info: Some(ElfInfo {
+            interpreter: node.get::<String>("interpreter").ok(),
+            soname: node.get::<String>("soname").ok(),
+            needed: node
+                .get::<Vec<String>>("needed_libraries")
+                .unwrap_or_default(),
+            symbol_versions: node
+                .get::<Vec<String>>("symbol_versions")
+                .unwrap_or_default(),
+        })
+        .filter(|elf| !elf.is_empty()),
+        macho_info: Some(MachOInfo {
+            load_commands: node.get::<Vec<String>>("load_commands").unwrap_or_default(),
+            team_id: node.get::<String>("team_id").ok(),
+            rpaths: node.get::<Vec<String>>("rpaths").unwrap_or_default(),
+        })
+        .filter(|macho| !macho.is_empty()),
+        risk_score: node.get::<f64>("risk_score").ok(),
+        sections: sections_from_node(node),
+        overlay_size: node.get::<i64>("overlay_size").ok().map(|s| s as u64),
+        likely_packed: node.get::<bool>("likely_packed").unwrap_or(false),
+    }
+}
+
+/// Sections stored as parallel name/size/entropy lists on a Binary node
+fn sections_from_node(node: &neo4rs::Node) -> Vec<Section> {
+    let names = node.get::<Vec<String>>("section_names").unwrap_or_default();
+    let sizes = node.get::<Vec<i64>>("section_sizes").unwrap_or_default();
+    let entropy = node.get::<Vec<f64>>("section_entropy").unwrap_or_default();
+
+    names
+        .into_iter()
+        .zip(entropy)
+        .enumerate()
+        .map(|(i, (name, entropy))| Section {
+            name,
+            size: sizes.get(i).filter(|s| **s >= 0).map(|s| *s as u64),
+            entropy,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relative_pairs() {
+        // A VA of a binary stored relative to 0x400000 is looked up as its RVA, while
+        // an address below the base is already relative
+        let image_bases = vec![
+            "0x400000".to_string(),
+            "0x140000000".to_string(),
+            "none".to_string(),
+        ];
+        assert_eq!(
+            relative_pairs(0x401000, &image_bases),
+            vec![
+                vec!["0x400000".to_string(), "0x1000".to_string()],
+                vec!["0x140000000".to_string(), "0x401000".to_string()],
+            ]
+        );
+    }
 }
@@ -0,0 +1,174 @@
+use super::connection::Cypher;
+use super::Neo4jConnection;
+use anyhow::Result;
+use neo4rs::query;
+
+/// Function not CONTAINed or IMPORTed by any binary
+const ORPHAN_FUNCTION: &str = "NOT (f)<-[:CONTAINS]-(:Binary) AND NOT (f)<-[:IMPORTS]-(:Binary)";
+
+/// String neither contained in a binary nor referenced by a function
+const ORPHAN_STRING: &str =
+    "NOT (s)<-[:CONTAINS_STRING]-(:Binary) AND NOT (s)<-[:REFERENCES]-(:Function)";
+
+/// CALLS edge between internal functions that share no binary
+const CROSS_BINARY_CALL: &str = "
+    MATCH (caller:Function)-[r:CALLS]->(callee:Function)
+    WHERE (caller)<-[:CONTAINS]-(:Binary) AND (callee)<-[:CONTAINS]-(:Binary)
+      AND NOT (caller)<-[:CONTAINS]-(:Binary)-[:CONTAINS]->(callee)";
+
+/// Import functions with the same name and library, ignoring case and a `.dll` suffix,
+/// grouped with the node kept on repair first
+const DUPLICATE_IMPORTS: &str = "
+    MATCH (f:Function {type: 'Import'})-[:BELONGS_TO]->(l:Library)
+    WITH f, toLower(l.name) AS library
+    WITH f, toLower(f.name) AS name,
+         CASE WHEN library ENDS WITH '.dll' THEN left(library, size(library) - 4)
+              ELSE library END AS library
+    ORDER BY f.uid
+    WITH name, library, collect(DISTINCT f.uid) AS uids
+    WHERE size(uids) > 1";
+
+/// Relationship types pointing at import functions, moved to the kept node on repair
+const IMPORT_EDGE_TYPES: [&str; 3] = ["IMPORTS", "CALLS", "CALLED_AT_RUNTIME"];
+
+/// Problems found by `database check`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IntegrityReport {
+    /// Functions no binary CONTAINS or IMPORTS
+    pub orphan_functions: i64,
+    /// Strings no binary contains and no function references
+    pub orphan_strings: i64,
+    /// CALLS edges between internal functions of different binaries
+    pub cross_binary_calls: i64,
+    /// Redundant import nodes (one per group is kept)
+    pub duplicate_imports: i64,
+}
+
+impl IntegrityReport {
+    pub fn is_clean(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Consistency checks over the whole graph
+pub struct IntegrityChecker {
+    connection: Neo4jConnection,
+}
+
+impl IntegrityChecker {
+    pub fn new(connection: Neo4jConnection) -> Self {
+        Self { connection }
+    }
+
+    pub async fn check(&self) -> Result<IntegrityReport> {
+        Ok(IntegrityReport {
+            orphan_functions: self
+                .count(&format!(
+                    "MATCH (f:Function) WHERE {} RETURN count(f) AS count",
+                    ORPHAN_FUNCTION
+                ))
+                .await?,
+            orphan_strings: self
+                .count(&format!(
+                    "MATCH (s:String) WHERE {} RETURN count(s) AS count",
+                    ORPHAN_STRING
+                ))
+                .await?,
+            cross_binary_calls: self
+                .count(&format!("{} RETURN count(r) AS count", CROSS_BINARY_CALL))
+                .await?,
+            duplicate_imports: self
+                .count(&format!(
+                    "{} RETURN sum(size(uids) - 1) AS count",
+                    DUPLICATE_IMPORTS
+                ))
+                .await?,
+        })
+    }
+
+    /// Fix what `check` reports:
+    /// - internal functions are re-attached to the binary named in their UID, other
+    ///   orphan functions and orphan strings are deleted
+    /// - cross-binary CALLS edges are deleted
+    /// - duplicate imports are merged into the node with the lowest UID
+    pub async fn repair(&self) -> Result<()> {
+        self.write(&format!(
+            "MATCH (f:Function) WHERE f.type <> 'Import' AND {}
+             MATCH (b:Binary {{hash: split(f.uid, ':')[0]}})
+             MERGE (b)-[:CONTAINS]->(f)",
+            ORPHAN_FUNCTION
+        ))
+        .await?;
+        self.write(&format!(
+            "MATCH (f:Function) WHERE {} DETACH DELETE f",
+            ORPHAN_FUNCTION
+        ))
+        .await?;
+        self.write(&format!(
+            "MATCH (s:String) WHERE {} DETACH DELETE s",
+            ORPHAN_STRING
+        ))
+        .await?;
+        self.write(&format!("{} DELETE r", CROSS_BINARY_CALL))
+            .await?;
+
+        let groups = self
+            .connection
+            .read(Cypher::new(format!("{} RETURN uids", DUPLICATE_IMPORTS)))
+            .await?;
+        for row in groups {
+            let uids = row.get::<Vec<String>>("uids").unwrap_or_default();
+            let Some((keep, duplicates)) = uids.split_first() else {
+                continue;
+            };
+            self.merge_imports(keep, duplicates).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Move the edges of `duplicates` onto `keep`, then delete them
+    async fn merge_imports(&self, keep: &str, duplicates: &[String]) -> Result<()> {
+        for edge_type in IMPORT_EDGE_TYPES {
+            let statement = format!(
+                "MATCH (keep:Function {{uid: $keep}})
+                 MATCH (source)-[r:{edge_type}]->(dup:Function)
+                 WHERE dup.uid IN $duplicates
+                 MERGE (source)-[moved:{edge_type}]->(keep)
+                 SET moved += properties(r)
+                 DELETE r"
+            );
+            self.connection
+                .graph()
+                .run(
+                    query(&statement)
+                        .param("keep", keep)
+                        .param("duplicates", duplicates.to_vec()),
+                )
+                .await?;
+        }
+
+        self.connection
+            .graph()
+            .run(
+                query("MATCH (dup:Function) WHERE dup.uid IN $duplicates DETACH DELETE dup")
+                    .param("duplicates", duplicates.to_vec()),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn count(&self, statement: &str) -> Result<i64> {
+        let rows = self.connection.read(Cypher::new(statement)).await?;
+        Ok(rows
+            .into_iter()
+            .next()
+            .and_then(|row| row.get::<i64>("count").ok())
+            .unwrap_or(0))
+    }
+
+    async fn write(&self, statement: &str) -> Result<()> {
+        self.connection.graph().run(query(statement)).await?;
+        Ok(())
+    }
+}
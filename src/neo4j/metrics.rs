@@ -0,0 +1,279 @@
+//! Import, query and error counters of the process, in the Prometheus text format
+//!
+//! With the global `--metrics-listen`, `GET /metrics` answers them while the command
+//! runs, so a long `import directory` can be scraped like any other service.
+
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// Upper bounds in seconds of the read query latency buckets
+pub const QUERY_BUCKETS: [f64; 10] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 10.0];
+
+/// Content type of the text exposition format
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+static METRICS: Metrics = Metrics::new();
+
+/// Counters of every connection and import of the process
+pub fn global() -> &'static Metrics {
+    &METRICS
+}
+
+#[derive(Debug, Default)]
+pub struct Metrics {
+    pub imports: AtomicU64,
+    pub failed_imports: AtomicU64,
+    pub imported_nodes: AtomicU64,
+    pub imported_functions: AtomicU64,
+    /// Time spent in imports, finished or failed
+    pub import_micros: AtomicU64,
+    /// Read queries per latency bucket (not cumulative), the last one past the largest bound
+    pub query_buckets: [AtomicU64; QUERY_BUCKETS.len() + 1],
+    pub query_micros: AtomicU64,
+    /// Queries and imports that failed with an error from the server or driver
+    pub neo4j_errors: AtomicU64,
+}
+
+impl Metrics {
+    pub const fn new() -> Self {
+        Self {
+            imports: AtomicU64::new(0),
+            failed_imports: AtomicU64::new(0),
+            imported_nodes: AtomicU64::new(0),
+            imported_functions: AtomicU64::new(0),
+            import_micros: AtomicU64::new(0),
+            query_buckets: [const { AtomicU64::new(0) }; QUERY_BUCKETS.len() + 1],
+            query_micros: AtomicU64::new(0),
+            neo4j_errors: AtomicU64::new(0),
+        }
+    }
+
+    /// Count a read query that returned after `elapsed`
+    pub fn record_query(&self, elapsed: Duration) {
+        let seconds = elapsed.as_secs_f64();
+        let bucket = QUERY_BUCKETS
+            .iter()
+            .position(|bound| seconds <= *bound)
+            .unwrap_or(QUERY_BUCKETS.len());
+        self.query_buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.query_micros
+            .fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    /// Count a finished import of `nodes` nodes, `functions` of them functions
+    pub fn record_import(&self, elapsed: Duration, nodes: u64, functions: u64) {
+        self.imports.fetch_add(1, Ordering::Relaxed);
+        self.imported_nodes.fetch_add(nodes, Ordering::Relaxed);
+        self.imported_functions
+            .fetch_add(functions, Ordering::Relaxed);
+        self.import_micros
+            .fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    pub fn record_failed_import(&self, elapsed: Duration) {
+        self.failed_imports.fetch_add(1, Ordering::Relaxed);
+        self.import_micros
+            .fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    pub fn record_neo4j_error(&self) {
+        self.neo4j_errors.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Metrics document of `metrics`; the queue depth gauge is left out without an import
+/// queue
+pub fn render(metrics: &Metrics, queue_depth: Option<usize>) -> String {
+    let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+    let seconds = |micros: u64| micros as f64 / 1_000_000.0;
+    let mut out = String::new();
+
+    let mut family = |name: &str, kind: &str, help: &str, samples: &[(String, String)]| {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} {}", name, kind);
+        for (sample, value) in samples {
+            let _ = writeln!(out, "{} {}", sample, value);
+        }
+    };
+
+    family(
+        "binaryx_imports_total",
+        "counter",
+        "Imports by outcome",
+        &[
+            (
+                "binaryx_imports_total{outcome=\"ok\"}".into(),
+                load(&metrics.imports).to_string(),
+            ),
+            (
+                "binaryx_imports_total{outcome=\"failed\"}".into(),
+                load(&metrics.failed_imports).to_string(),
+            ),
+        ],
+    );
+    family(
+        "binaryx_imported_nodes_total",
+        "counter",
+        "Nodes written by finished imports",
+        &[(
+            "binaryx_imported_nodes_total".into(),
+            load(&metrics.imported_nodes).to_string(),
+        )],
+    );
+    family(
+        "binaryx_imported_functions_total",
+        "counter",
+        "Functions written by finished imports",
+        &[(
+            "binaryx_imported_functions_total".into(),
+            load(&metrics.imported_functions).to_string(),
+        )],
+    );
+    family(
+        "binaryx_import_duration_seconds_total",
+        "counter",
+        "Time spent importing",
+        &[(
+            "binaryx_import_duration_seconds_total".into(),
+            seconds(load(&metrics.import_micros)).to_string(),
+        )],
+    );
+
+    let mut buckets = Vec::with_capacity(QUERY_BUCKETS.len() + 3);
+    let mut count = 0;
+    for (bound, bucket) in QUERY_BUCKETS.iter().zip(&metrics.query_buckets) {
+        count += load(bucket);
+        buckets.push((
+            format!("binaryx_query_duration_seconds_bucket{{le=\"{}\"}}", bound),
+            count.to_string(),
+        ));
+    }
+    count += load(&metrics.query_buckets[QUERY_BUCKETS.len()]);
+    buckets.push((
+        "binaryx_query_duration_seconds_bucket{le=\"+Inf\"}".into(),
+        count.to_string(),
+    ));
+    buckets.push((
+        "binaryx_query_duration_seconds_sum".into(),
+        seconds(load(&metrics.query_micros)).to_string(),
+    ));
+    buckets.push((
+        "binaryx_query_duration_seconds_count".into(),
+        count.to_string(),
+    ));
+    family(
+        "binaryx_query_duration_seconds",
+        "histogram",
+        "Latency of read queries that returned",
+        &buckets,
+    );
+
+    family(
+        "binaryx_neo4j_errors_total",
+        "counter",
+        "Read queries and imports failed by Neo4j or the driver",
+        &[(
+            "binaryx_neo4j_errors_total".into(),
+            load(&metrics.neo4j_errors).to_string(),
+        )],
+    );
+
+    if let Some(depth) = queue_depth {
+        family(
+            "binaryx_import_queue_depth",
+            "gauge",
+            "Uploads waiting in the import queue",
+            &[("binaryx_import_queue_depth".into(), depth.to_string())],
+        );
+    }
+    out
+}
+
+/// Answer `GET /metrics` with the process counters on `listener`, and 404 otherwise
+pub async fn serve(listener: TcpListener) {
+    loop {
+        let Ok((mut stream, _)) = listener.accept().await else {
+            continue;
+        };
+        tokio::spawn(async move {
+            let mut request = [0u8; 1024];
+            let read = stream.read(&mut request).await.unwrap_or(0);
+            let response = if request[..read].starts_with(b"GET /metrics ") {
+                let body = render(global(), None);
+                format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    CONTENT_TYPE,
+                    body.len(),
+                    body
+                )
+            } else {
+                "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                    .to_string()
+            };
+            let _ = stream.write_all(response.as_bytes()).await;
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_metrics() {
+        let metrics = Metrics::default();
+        metrics.record_query(Duration::from_millis(3));
+        metrics.record_query(Duration::from_millis(40));
+        metrics.record_query(Duration::from_secs(30));
+        metrics.record_import(Duration::from_millis(1500), 120, 40);
+        metrics.record_failed_import(Duration::from_millis(500));
+        metrics.record_neo4j_error();
+
+        let text = render(&metrics, Some(3));
+        let lines: Vec<&str> = text.lines().collect();
+        for expected in [
+            "binaryx_imports_total{outcome=\"ok\"} 1",
+            "binaryx_imports_total{outcome=\"failed\"} 1",
+            "binaryx_imported_nodes_total 120",
+            "binaryx_imported_functions_total 40",
+            "binaryx_import_duration_seconds_total 2",
+            "binaryx_query_duration_seconds_bucket{le=\"0.005\"} 1",
+            "binaryx_query_duration_seconds_bucket{le=\"0.025\"} 1",
+            "binaryx_query_duration_seconds_bucket{le=\"0.05\"} 2",
+            "binaryx_query_duration_seconds_bucket{le=\"10\"} 2",
+            "binaryx_query_duration_seconds_bucket{le=\"+Inf\"} 3",
+            "binaryx_query_duration_seconds_count 3",
+            "binaryx_neo4j_errors_total 1",
+            "# TYPE binaryx_import_queue_depth gauge",
+            "binaryx_import_queue_depth 3",
+        ] {
+            assert!(lines.contains(&expected), "missing {}", expected);
+        }
+
+        assert!(!render(&metrics, None).contains("queue_depth"));
+    }
+
+    #[tokio::test]
+    async fn test_serve_metrics() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(serve(listener));
+
+        let get = |path: &'static str| async move {
+            let mut stream = tokio::net::TcpStream::connect(address).await.unwrap();
+            let request = format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path);
+            stream.write_all(request.as_bytes()).await.unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).await.unwrap();
+            response
+        };
+        let response = get("/metrics").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("# TYPE binaryx_query_duration_seconds histogram"));
+        assert!(get("/").await.starts_with("HTTP/1.1 404"));
+    }
+}
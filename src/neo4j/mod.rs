@@ -1,11 +1,18 @@
+pub mod autotune;
 pub mod call_path_analyzer;
 pub mod connection;
 pub mod importer;
+pub mod integrity;
+pub mod metrics;
+pub mod rebase;
 pub mod schema;
 
 pub use call_path_analyzer::CallPathAnalyzer;
 pub use connection::Neo4jConnection;
-pub use importer::{CallGraph, GraphImporter, Xref};
+pub use importer::{
+    BinaryFilter, BinarySubgraph, CallEdge, CallGraph, ClearScope, DataXref, GraphImporter,
+    HotCallee, ImportAge, Xref,
+};
 pub use schema::SchemaManager;
 
 use std::collections::HashMap;
@@ -15,6 +22,9 @@ pub struct DatabaseStats {
     pub node_count: i64,
     pub relationship_count: i64,
     pub label_counts: HashMap<String, i64>,
+    pub relationship_counts: HashMap<String, i64>,
+    /// Libraries imported by the most binaries, with their binary count
+    pub top_libraries: Vec<(String, i64)>,
 }
 
 impl DatabaseStats {
@@ -23,8 +33,24 @@ impl DatabaseStats {
             node_count: 0,
             relationship_count: 0,
             label_counts: HashMap::new(),
+            relationship_counts: HashMap::new(),
+            top_libraries: Vec::new(),
         }
     }
+
+    /// Functions CONTAINed per binary, imports excluded
+    pub fn avg_functions_per_binary(&self) -> f64 {
+        let binaries = self.label_counts.get("Binary").copied().unwrap_or(0);
+        if binaries == 0 {
+            return 0.0;
+        }
+        let functions = self
+            .relationship_counts
+            .get("CONTAINS")
+            .copied()
+            .unwrap_or(0);
+        functions as f64 / binaries as f64
+    }
 }
 
 impl Default for DatabaseStats {
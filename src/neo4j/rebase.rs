@@ -0,0 +1,662 @@
+use super::connection::Cypher;
+use super::Neo4jConnection;
+use anyhow::Result;
+use neo4rs::{query, BoltType, Query, Row, Txn};
+use std::collections::{HashMap, HashSet};
+
+use crate::utils::uid::{
+    chunk_uid, data_uid, format_address, function_uid, parse_address, parse_uid,
+    unresolved_call_uid, vtable_uid, Uid,
+};
+
+/// Relationship types that may touch a function, moved to the kept node when two
+/// imports of a binary are merged
+const FUNCTION_EDGE_TYPES: [&str; 7] = [
+    "CONTAINS",
+    "CALLS",
+    "REFERENCES",
+    "DECRYPTED_BY",
+    "HAS_SLOT",
+    "DISPATCHES_TO",
+    "HAS_CHUNK",
+];
+
+const VTABLE_EDGE_TYPES: [&str; 3] = ["HAS_VTABLE", "HAS_SLOT", "DERIVES_FROM"];
+const DATA_EDGE_TYPES: [&str; 1] = ["REFERENCES"];
+const UNRESOLVED_CALL_EDGE_TYPES: [&str; 1] = ["HAS_UNRESOLVED_CALL"];
+const CHUNK_EDGE_TYPES: [&str; 1] = ["HAS_CHUNK"];
+
+/// Nodes moved per statement
+const REBASE_BATCH: usize = 1000;
+
+/// `address` moved from image base `from` to `to`, if it lies in the old image: at or
+/// above `from` and, when rebasing upwards, below `to`, so that functions already at the
+/// new base are left alone
+pub fn rebased(address: u64, from: u64, to: u64) -> Option<u64> {
+    if address < from || (to > from && address >= to) {
+        return None;
+    }
+    (address - from).checked_add(to)
+}
+
+/// Kind of node keyed by an address of its binary, which a rebase moves
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MovedNode {
+    Function,
+    VTable,
+    Data,
+    UnresolvedCall,
+    Chunk,
+}
+
+impl MovedNode {
+    const ALL: [MovedNode; 5] = [
+        MovedNode::Function,
+        MovedNode::VTable,
+        MovedNode::Data,
+        MovedNode::UnresolvedCall,
+        MovedNode::Chunk,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            MovedNode::Function => "Function",
+            MovedNode::VTable => "VTable",
+            MovedNode::Data => "Data",
+            MovedNode::UnresolvedCall => "UnresolvedCall",
+            MovedNode::Chunk => "Chunk",
+        }
+    }
+
+    /// Property holding the node's address
+    fn address_property(self) -> &'static str {
+        match self {
+            MovedNode::Chunk => "start",
+            _ => "address",
+        }
+    }
+
+    /// Relationship types moved onto the kept node when a moved node is merged
+    fn edge_types(self) -> &'static [&'static str] {
+        match self {
+            MovedNode::Function => &FUNCTION_EDGE_TYPES,
+            MovedNode::VTable => &VTABLE_EDGE_TYPES,
+            MovedNode::Data => &DATA_EDGE_TYPES,
+            MovedNode::UnresolvedCall => &UNRESOLVED_CALL_EDGE_TYPES,
+            MovedNode::Chunk => &CHUNK_EDGE_TYPES,
+        }
+    }
+}
+
+/// One node of a rebase
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeMove {
+    pub kind: MovedNode,
+    pub old_uid: String,
+    pub new_uid: String,
+    pub address: u64,
+    /// A node already exists at the new address (from an import at the new base) and
+    /// absorbs this one
+    pub merge: bool,
+}
+
+/// Nodes of a binary that `database rebase` moves
+#[derive(Debug, Clone)]
+pub struct RebasePlan {
+    pub binary_hash: String,
+    pub from: u64,
+    pub to: u64,
+    pub moves: Vec<NodeMove>,
+    /// Image base stored on the binary along with the moves
+    pub image_base: Option<u64>,
+}
+
+impl RebasePlan {
+    /// Moves of the nodes of a binary given by UID; fails when a node would land on
+    /// another one that is moved itself
+    ///
+    /// Data and unresolved call addresses that do not parse (symbolic targets) stay.
+    pub fn new(binary_hash: &str, from: u64, to: u64, uids: &[String]) -> Result<Self> {
+        let existing: HashSet<&str> = uids.iter().map(String::as_str).collect();
+        let mut moves = Vec::new();
+        for uid in uids {
+            let Some(parsed) =
+                parse_uid(uid).filter(|parsed| parsed.binary_hash() == Some(binary_hash))
+            else {
+                continue;
+            };
+            let moved = match parsed {
+                Uid::Function { address, .. } => rebased(address, from, to)
+                    .map(|new| (MovedNode::Function, new, function_uid(binary_hash, new))),
+                Uid::VTable { address, .. } => rebased(address, from, to)
+                    .map(|new| (MovedNode::VTable, new, vtable_uid(binary_hash, new))),
+                Uid::Chunk { address, .. } => rebased(address, from, to)
+                    .map(|new| (MovedNode::Chunk, new, chunk_uid(binary_hash, new))),
+                Uid::Data { address, .. } => parse_address(&address)
+                    .and_then(|address| rebased(address, from, to))
+                    .map(|new| {
+                        let new_uid = data_uid(binary_hash, &format_address(new));
+                        (MovedNode::Data, new, new_uid)
+                    }),
+                Uid::UnresolvedCall { role, address, .. } => parse_address(&address)
+                    .and_then(|address| rebased(address, from, to))
+                    .map(|new| {
+                        let new_uid = unresolved_call_uid(binary_hash, &role, &format_address(new));
+                        (MovedNode::UnresolvedCall, new, new_uid)
+                    }),
+                _ => None,
+            };
+            let Some((kind, address, new_uid)) = moved else {
+                continue;
+            };
+            moves.push(NodeMove {
+                kind,
+                merge: existing.contains(new_uid.as_str()),
+                old_uid: uid.clone(),
+                new_uid,
+                address,
+            });
+        }
+
+        let moved: HashSet<&str> = moves.iter().map(|m| m.old_uid.as_str()).collect();
+        if let Some(overlap) = moves.iter().find(|m| moved.contains(m.new_uid.as_str())) {
+            return Err(crate::error::validation(
+                format!(
+                    "The old and new image overlap ({} would replace a moved node); rebase through an unused base in two steps",
+                    overlap.old_uid
+                ),
+                Vec::new(),
+            ));
+        }
+
+        Ok(Self {
+            binary_hash: binary_hash.to_string(),
+            from,
+            to,
+            moves,
+            image_base: None,
+        })
+    }
+
+    /// Moves of `kind`, renamed or merged
+    pub fn count(&self, kind: MovedNode, merge: bool) -> usize {
+        self.moves
+            .iter()
+            .filter(|m| m.kind == kind && m.merge == merge)
+            .count()
+    }
+
+    /// Address `offset` after the rebase, unchanged outside the old image
+    fn shift(&self, offset: &str) -> String {
+        parse_address(offset)
+            .and_then(|address| rebased(address, self.from, self.to))
+            .map_or_else(|| offset.to_string(), format_address)
+    }
+}
+
+/// Counts of an applied rebase
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RebaseSummary {
+    /// Functions whose UID and address were rewritten
+    pub renamed: usize,
+    /// Functions merged into the function at their new address
+    pub merged: usize,
+    /// Vtables, data, unresolved call and chunk nodes renamed or merged
+    pub other_nodes: usize,
+    /// CALLS and DISPATCHES_TO edges and unresolved calls whose call sites were moved
+    pub call_sites: usize,
+    /// REFERENCES edges whose offset was moved
+    pub references: usize,
+    /// IMPORTS and CONTAINS_STRING edge addresses and the entry point moved
+    pub addresses: usize,
+}
+
+/// Moves a binary's functions to another image base, for samples re-analyzed at a
+/// different base (ASLR) or imported before image bases were read
+pub struct Rebaser {
+    connection: Neo4jConnection,
+}
+
+impl Rebaser {
+    pub fn new(connection: Neo4jConnection) -> Self {
+        Self { connection }
+    }
+
+    pub async fn plan(&self, binary_hash: &str, from: u64, to: u64) -> Result<RebasePlan> {
+        let rows = self
+            .connection
+            .read(
+                Cypher::new(
+                    "MATCH (:Binary {hash: $hash})-[:CONTAINS]->(f:Function) RETURN f.uid AS uid
+                     UNION ALL
+                     MATCH (:Binary {hash: $hash})-[:HAS_VTABLE]->(v:VTable) RETURN v.uid AS uid
+                     UNION ALL
+                     MATCH (d:Data {binary_hash: $hash}) RETURN d.uid AS uid
+                     UNION ALL
+                     MATCH (:Binary {hash: $hash})-[:HAS_UNRESOLVED_CALL]->(u:UnresolvedCall)
+                     RETURN u.uid AS uid
+                     UNION ALL
+                     MATCH (k:Chunk {binary_hash: $hash}) RETURN k.uid AS uid",
+                )
+                .param("hash", binary_hash),
+            )
+            .await?;
+        let uids: Vec<String> = rows
+            .into_iter()
+            .filter_map(|row| row.get::<String>("uid").ok())
+            .collect();
+        RebasePlan::new(binary_hash, from, to, &uids)
+    }
+
+    /// Rewrite call sites, reference offsets and import, string and entry point
+    /// addresses, then rename the nodes free at their new address and merge the others
+    /// into the node found there, moving their edges
+    ///
+    /// Everything runs in one transaction: a failed rebase leaves the binary as it was,
+    /// and can simply be run again.
+    pub async fn apply(&self, plan: &RebasePlan) -> Result<RebaseSummary> {
+        let mut txn = self.connection.graph().start_txn().await?;
+        match apply_moves(&mut txn, plan).await {
+            Ok(summary) => {
+                txn.commit().await?;
+                Ok(summary)
+            }
+            Err(err) => {
+                let _ = txn.rollback().await;
+                Err(err)
+            }
+        }
+    }
+}
+
+async fn apply_moves(txn: &mut Txn, plan: &RebasePlan) -> Result<RebaseSummary> {
+    let mut summary = RebaseSummary::default();
+    let functions: Vec<&NodeMove> = plan
+        .moves
+        .iter()
+        .filter(|m| m.kind == MovedNode::Function)
+        .collect();
+    for chunk in functions.chunks(REBASE_BATCH) {
+        move_offsets(txn, plan, chunk, &mut summary).await?;
+    }
+    summary.call_sites += move_unresolved_call_sites(txn, plan).await?;
+    summary.addresses += move_binary_addresses(txn, plan).await?;
+
+    for kind in MovedNode::ALL {
+        let moves: Vec<&NodeMove> = plan.moves.iter().filter(|m| m.kind == kind).collect();
+        for chunk in moves.chunks(REBASE_BATCH) {
+            let (merges, renames): (Vec<&NodeMove>, Vec<&NodeMove>) =
+                chunk.iter().partition(|m| m.merge);
+            if !renames.is_empty() {
+                run(
+                    txn,
+                    &format!(
+                        "UNWIND $moves AS m
+                         MATCH (n:{label} {{uid: m.old}})
+                         SET n.uid = m.new, n.{property} = m.address",
+                        label = kind.label(),
+                        property = kind.address_property()
+                    ),
+                    move_rows(&renames),
+                )
+                .await?;
+            }
+            if !merges.is_empty() {
+                merge_nodes(txn, kind, move_rows(&merges)).await?;
+            }
+            match kind {
+                MovedNode::Function => {
+                    summary.renamed += renames.len();
+                    summary.merged += merges.len();
+                }
+                _ => summary.other_nodes += chunk.len(),
+            }
+        }
+    }
+
+    if let Some(image_base) = plan.image_base {
+        txn.run(
+            query("MATCH (b:Binary {hash: $hash}) SET b.image_base = $image_base")
+                .param("hash", plan.binary_hash.as_str())
+                .param("image_base", format_address(image_base)),
+        )
+        .await?;
+    }
+    Ok(summary)
+}
+
+/// Move the call sites of CALLS and DISPATCHES_TO edges and the offsets of REFERENCES
+/// edges leaving the moved functions
+async fn move_offsets(
+    txn: &mut Txn,
+    plan: &RebasePlan,
+    chunk: &[&NodeMove],
+    summary: &mut RebaseSummary,
+) -> Result<()> {
+    let olds: Vec<String> = chunk.iter().map(|m| m.old_uid.clone()).collect();
+    let rows = fetch(
+        txn,
+        query(
+            "MATCH (f:Function)-[r:CALLS|DISPATCHES_TO|REFERENCES]->() WHERE f.uid IN $olds
+             RETURN elementId(r) AS id, type(r) AS type, r.offset AS offset,
+                    r.offsets AS offsets",
+        )
+        .param("olds", olds),
+    )
+    .await?;
+
+    let mut updates = Vec::new();
+    for row in rows {
+        let offset = row.get::<String>("offset").ok();
+        let offsets = row.get::<Vec<String>>("offsets").ok();
+        let moved_offset = offset.as_deref().map(|o| plan.shift(o));
+        let moved_offsets: Option<Vec<String>> = offsets
+            .as_ref()
+            .map(|offsets| offsets.iter().map(|o| plan.shift(o)).collect());
+        if moved_offset == offset && moved_offsets == offsets {
+            continue;
+        }
+        match row.get::<String>("type").as_deref() {
+            Ok("REFERENCES") => summary.references += 1,
+            _ => summary.call_sites += 1,
+        }
+        let mut update: HashMap<String, BoltType> = HashMap::new();
+        update.insert(
+            "id".into(),
+            row.get::<String>("id").unwrap_or_default().into(),
+        );
+        update.insert("offset".into(), moved_offset.into());
+        update.insert("offsets".into(), moved_offsets.into());
+        updates.push(update);
+    }
+
+    if !updates.is_empty() {
+        run(
+            txn,
+            "UNWIND $moves AS m
+             MATCH ()-[r]->() WHERE elementId(r) = m.id
+             SET r.offset = m.offset, r.offsets = m.offsets",
+            updates,
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+/// Move the call sites recorded on the binary's unresolved calls
+async fn move_unresolved_call_sites(txn: &mut Txn, plan: &RebasePlan) -> Result<usize> {
+    let rows = fetch(
+        txn,
+        query(
+            "MATCH (:Binary {hash: $hash})-[:HAS_UNRESOLVED_CALL]->(u:UnresolvedCall)
+             RETURN elementId(u) AS id, u.call_sites AS call_sites",
+        )
+        .param("hash", plan.binary_hash.as_str()),
+    )
+    .await?;
+
+    let mut updates = Vec::new();
+    for row in rows {
+        let call_sites = row.get::<Vec<String>>("call_sites").unwrap_or_default();
+        let moved: Vec<String> = call_sites.iter().map(|site| plan.shift(site)).collect();
+        if moved == call_sites {
+            continue;
+        }
+        let mut update: HashMap<String, BoltType> = HashMap::new();
+        update.insert(
+            "id".into(),
+            row.get::<String>("id").unwrap_or_default().into(),
+        );
+        update.insert("call_sites".into(), moved.into());
+        updates.push(update);
+    }
+
+    let count = updates.len();
+    for chunk in updates.chunks(REBASE_BATCH) {
+        run(
+            txn,
+            "UNWIND $moves AS m
+             MATCH (u:UnresolvedCall) WHERE elementId(u) = m.id
+             SET u.call_sites = m.call_sites",
+            chunk.to_vec(),
+        )
+        .await?;
+    }
+    Ok(count)
+}
+
+/// Move the addresses on the binary's IMPORTS and CONTAINS_STRING edges and its entry
+/// point; a string edge landing on one at the new address is dropped
+async fn move_binary_addresses(txn: &mut Txn, plan: &RebasePlan) -> Result<usize> {
+    let rows = fetch(
+        txn,
+        query(
+            "MATCH (:Binary {hash: $hash})-[r:IMPORTS|CONTAINS_STRING]->()
+             WHERE r.address IS NOT NULL
+             RETURN elementId(r) AS id, r.address AS address",
+        )
+        .param("hash", plan.binary_hash.as_str()),
+    )
+    .await?;
+    let addresses = rows
+        .iter()
+        .filter_map(|row| {
+            Some((
+                row.get::<String>("id").ok()?,
+                row.get::<String>("address").ok()?,
+            ))
+        })
+        .collect();
+
+    let updates: Vec<HashMap<String, BoltType>> = moved_addresses(plan, addresses)
+        .into_iter()
+        .map(|(id, address)| {
+            let mut update: HashMap<String, BoltType> = HashMap::new();
+            update.insert("id".into(), id.into());
+            update.insert("address".into(), address.into());
+            update
+        })
+        .collect();
+    let mut count = updates.len();
+    for chunk in updates.chunks(REBASE_BATCH) {
+        run(
+            txn,
+            "UNWIND $moves AS m
+             MATCH ()-[r]->() WHERE elementId(r) = m.id
+             SET r.address = m.address",
+            chunk.to_vec(),
+        )
+        .await?;
+    }
+    if count > 0 {
+        txn.run(
+            query(
+                "MATCH (:Binary {hash: $hash})-[r:CONTAINS_STRING]->(s:String)
+                 WITH s, r.address AS address, collect(r) AS edges
+                 WHERE size(edges) > 1
+                 FOREACH (duplicate IN tail(edges) | DELETE duplicate)",
+            )
+            .param("hash", plan.binary_hash.as_str()),
+        )
+        .await?;
+    }
+
+    let rows = fetch(
+        txn,
+        query("MATCH (b:Binary {hash: $hash}) RETURN b.entry_point AS entry_point")
+            .param("hash", plan.binary_hash.as_str()),
+    )
+    .await?;
+    if let Some(entry_point) = rows
+        .first()
+        .and_then(|row| row.get::<String>("entry_point").ok())
+    {
+        let moved = plan.shift(&entry_point);
+        if moved != entry_point {
+            txn.run(
+                query("MATCH (b:Binary {hash: $hash}) SET b.entry_point = $entry_point")
+                    .param("hash", plan.binary_hash.as_str())
+                    .param("entry_point", moved),
+            )
+            .await?;
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// New address of each `(id, address)` pair the rebase changes
+fn moved_addresses(plan: &RebasePlan, addresses: Vec<(String, String)>) -> Vec<(String, String)> {
+    addresses
+        .into_iter()
+        .filter_map(|(id, address)| {
+            let moved = plan.shift(&address);
+            (moved != address).then_some((id, moved))
+        })
+        .collect()
+}
+
+/// Move the edges of each `old` node onto `new`, keeping edges `new` already has and
+/// filling in properties it lacks, then delete `old`
+async fn merge_nodes(
+    txn: &mut Txn,
+    kind: MovedNode,
+    moves: Vec<HashMap<String, BoltType>>,
+) -> Result<()> {
+    let label = kind.label();
+    for edge_type in kind.edge_types() {
+        run(
+            txn,
+            &format!(
+                "UNWIND $moves AS m
+                 MATCH (old:{label} {{uid: m.old}})-[r:{edge_type}]->(target)
+                 MATCH (keep:{label} {{uid: m.new}})
+                 MERGE (keep)-[moved:{edge_type}]->(target)
+                 ON CREATE SET moved += properties(r)
+                 DELETE r"
+            ),
+            moves.clone(),
+        )
+        .await?;
+        run(
+            txn,
+            &format!(
+                "UNWIND $moves AS m
+                 MATCH (source)-[r:{edge_type}]->(old:{label} {{uid: m.old}})
+                 MATCH (keep:{label} {{uid: m.new}})
+                 MERGE (source)-[moved:{edge_type}]->(keep)
+                 ON CREATE SET moved += properties(r)
+                 DELETE r"
+            ),
+            moves.clone(),
+        )
+        .await?;
+    }
+
+    run(
+        txn,
+        &format!(
+            "UNWIND $moves AS m
+             MATCH (old:{label} {{uid: m.old}}), (keep:{label} {{uid: m.new}})
+             WITH old, keep, properties(keep) AS kept
+             SET keep += properties(old)
+             SET keep += kept
+             DETACH DELETE old"
+        ),
+        moves,
+    )
+    .await
+}
+
+async fn run(txn: &mut Txn, statement: &str, moves: Vec<HashMap<String, BoltType>>) -> Result<()> {
+    txn.run(query(statement).param("moves", moves)).await?;
+    Ok(())
+}
+
+/// Rows of a read inside the transaction, which sees its earlier writes
+async fn fetch(txn: &mut Txn, statement: Query) -> Result<Vec<Row>> {
+    let mut stream = txn.execute(statement).await?;
+    let mut rows = Vec::new();
+    while let Some(row) = stream.next(txn.handle()).await? {
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+fn move_rows(moves: &[&NodeMove]) -> Vec<HashMap<String, BoltType>> {
+    moves
+        .iter()
+        .map(|m| {
+            let mut row: HashMap<String, BoltType> = HashMap::new();
+            row.insert("old".into(), m.old_uid.clone().into());
+            row.insert("new".into(), m.new_uid.clone().into());
+            row.insert("address".into(), format_address(m.address).into());
+            row
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rebase_plan() {
+        assert_eq!(rebased(0x401000, 0x400000, 0), Some(0x1000));
+        assert_eq!(rebased(0x1000, 0x400000, 0), None);
+        // Functions already at the higher base stay where they are
+        assert_eq!(rebased(0x10001000, 0x400000, 0x10000000), None);
+        assert_eq!(rebased(0x401000, 0x400000, 0x10000000), Some(0x10001000));
+
+        let uids = vec![
+            function_uid("h", 0x401000),
+            function_uid("h", 0x402000),
+            function_uid("h", 0x1000),
+            "imp:kernel32.dll:CreateFileA".to_string(),
+            vtable_uid("h", 0x405000),
+            data_uid("h", "0x406010"),
+            data_uid("h", "g_config"),
+            unresolved_call_uid("h", "to", "0x407000"),
+            chunk_uid("h", 0x408000),
+            // Another binary's nodes stay
+            data_uid("other", "0x406010"),
+        ];
+        let plan = RebasePlan::new("h", 0x400000, 0, &uids).unwrap();
+        assert_eq!(plan.moves.len(), 6);
+        assert_eq!(plan.moves[0].new_uid, function_uid("h", 0x1000));
+        assert!(plan.moves[0].merge);
+        assert!(!plan.moves[1].merge);
+        assert_eq!(plan.count(MovedNode::Function, true), 1);
+        assert_eq!(plan.count(MovedNode::Function, false), 1);
+        assert_eq!(plan.moves[2].new_uid, vtable_uid("h", 0x5000));
+        assert_eq!(plan.moves[3].new_uid, data_uid("h", "0x6010"));
+        assert_eq!(plan.moves[3].kind, MovedNode::Data);
+        assert_eq!(
+            plan.moves[4].new_uid,
+            unresolved_call_uid("h", "to", "0x7000")
+        );
+        assert_eq!(plan.moves[5].new_uid, chunk_uid("h", 0x8000));
+        assert_eq!(plan.shift("0x401234"), "0x1234");
+        assert_eq!(plan.shift("g_config"), "g_config");
+
+        // IMPORTS and CONTAINS_STRING edge addresses and the entry point move with
+        // the image, addresses outside it stay
+        let edges = vec![
+            ("imports".to_string(), "0x40a000".to_string()),
+            ("string".to_string(), "0x40c010".to_string()),
+            ("relative".to_string(), "0x1010".to_string()),
+        ];
+        assert_eq!(
+            moved_addresses(&plan, edges),
+            vec![
+                ("imports".to_string(), "0xa000".to_string()),
+                ("string".to_string(), "0xc010".to_string()),
+            ]
+        );
+        assert_eq!(plan.shift("0x401000"), "0x1000");
+
+        // 0x402000 would land on 0x401000, which moves too
+        let overlapping = RebasePlan::new("h", 0x401000, 0x400000, &uids);
+        assert!(overlapping.is_err());
+    }
+}
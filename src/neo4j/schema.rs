@@ -1,5 +1,60 @@
+use super::connection::{Cypher, Dialect};
 use super::Neo4jConnection;
 use anyhow::Result;
+use neo4rs::Row;
+use std::collections::HashSet;
+
+/// Constraints created by `database init`
+const CONSTRAINTS: &[&str] = &[
+    // Binary node hash unique constraint
+    "CREATE CONSTRAINT binary_hash_unique IF NOT EXISTS FOR (b:Binary) REQUIRE b.hash IS UNIQUE",
+    // Function node uid unique constraint
+    "CREATE CONSTRAINT function_uid_unique IF NOT EXISTS FOR (f:Function) REQUIRE f.uid IS UNIQUE",
+    // String node uid unique constraint
+    "CREATE CONSTRAINT string_uid_unique IF NOT EXISTS FOR (s:String) REQUIRE s.uid IS UNIQUE",
+    // Resource node uid unique constraint
+    "CREATE CONSTRAINT resource_uid_unique IF NOT EXISTS FOR (r:Resource) REQUIRE r.uid IS UNIQUE",
+    // Data node uid unique constraint
+    "CREATE CONSTRAINT data_uid_unique IF NOT EXISTS FOR (d:Data) REQUIRE d.uid IS UNIQUE",
+    // Network indicator value unique constraints
+    "CREATE CONSTRAINT domain_value_unique IF NOT EXISTS FOR (d:Domain) REQUIRE d.value IS UNIQUE",
+    "CREATE CONSTRAINT ip_value_unique IF NOT EXISTS FOR (i:IPAddress) REQUIRE i.value IS UNIQUE",
+    "CREATE CONSTRAINT url_value_unique IF NOT EXISTS FOR (u:URL) REQUIRE u.value IS UNIQUE",
+    // Named object (mutex, pipe, ...) uid unique constraint
+    "CREATE CONSTRAINT named_object_uid_unique IF NOT EXISTS FOR (o:NamedObject) REQUIRE o.uid IS UNIQUE",
+    // Unresolved call address unique constraint
+    "CREATE CONSTRAINT unresolved_call_uid_unique IF NOT EXISTS FOR (u:UnresolvedCall) REQUIRE u.uid IS UNIQUE",
+    // Function chunk uid unique constraint
+    "CREATE CONSTRAINT chunk_uid_unique IF NOT EXISTS FOR (k:Chunk) REQUIRE k.uid IS UNIQUE",
+    // Library node name unique constraint
+    "CREATE CONSTRAINT library_name_unique IF NOT EXISTS FOR (l:Library) REQUIRE l.name IS UNIQUE",
+];
+
+/// Indexes created by `database init`
+const INDEXES: &[&str] = &[
+    // Function indexes
+    "CREATE INDEX function_name_index IF NOT EXISTS FOR (f:Function) ON (f.name)",
+    "CREATE INDEX function_demangled_name_index IF NOT EXISTS FOR (f:Function) ON (f.demangled_name)",
+    "CREATE INDEX function_canonical_name_index IF NOT EXISTS FOR (f:Function) ON (f.canonical_name)",
+    "CREATE INDEX function_address_index IF NOT EXISTS FOR (f:Function) ON (f.address)",
+    // Binary indexes
+    "CREATE INDEX binary_filename_index IF NOT EXISTS FOR (b:Binary) ON (b.filename)",
+    "CREATE INDEX binary_md5_index IF NOT EXISTS FOR (b:Binary) ON (b.md5)",
+    "CREATE INDEX binary_sha1_index IF NOT EXISTS FOR (b:Binary) ON (b.sha1)",
+    "CREATE INDEX binary_ssdeep_index IF NOT EXISTS FOR (b:Binary) ON (b.ssdeep)",
+    "CREATE INDEX binary_tlsh_index IF NOT EXISTS FOR (b:Binary) ON (b.tlsh)",
+    "CREATE INDEX binary_compiler_index IF NOT EXISTS FOR (b:Binary) ON (b.compiler)",
+    "CREATE INDEX binary_packer_index IF NOT EXISTS FOR (b:Binary) ON (b.packer)",
+    "CREATE INDEX binary_imphash_index IF NOT EXISTS FOR (b:Binary) ON (b.imphash)",
+    "CREATE INDEX binary_max_entropy_index IF NOT EXISTS FOR (b:Binary) ON (b.max_entropy)",
+    // String indexes
+    "CREATE INDEX string_value_index IF NOT EXISTS FOR (s:String) ON (s.value)",
+    // Resource indexes
+    "CREATE INDEX resource_sha256_index IF NOT EXISTS FOR (r:Resource) ON (r.sha256)",
+    // Fulltext indexes (for substring/keyword search)
+    "CREATE FULLTEXT INDEX function_pseudocode_fulltext IF NOT EXISTS FOR (f:Function) ON EACH [f.pseudocode]",
+    "CREATE FULLTEXT INDEX string_value_fulltext IF NOT EXISTS FOR (s:String) ON EACH [s.value]",
+];
 
 pub struct SchemaManager {
     connection: Neo4jConnection,
@@ -17,19 +72,13 @@ impl SchemaManager {
     }
 
     pub async fn create_constraints(&self) -> Result<()> {
-        let constraints = [
-            // Binary node hash unique constraint
-            "CREATE CONSTRAINT binary_hash_unique IF NOT EXISTS FOR (b:Binary) REQUIRE b.hash IS UNIQUE",
-            // Function node uid unique constraint
-            "CREATE CONSTRAINT function_uid_unique IF NOT EXISTS FOR (f:Function) REQUIRE f.uid IS UNIQUE",
-            // String node uid unique constraint
-            "CREATE CONSTRAINT string_uid_unique IF NOT EXISTS FOR (s:String) REQUIRE s.uid IS UNIQUE",
-            // Library node name unique constraint
-            "CREATE CONSTRAINT library_name_unique IF NOT EXISTS FOR (l:Library) REQUIRE l.name IS UNIQUE",
-        ];
-
-        for constraint in constraints {
-            if let Err(e) = self.connection.execute_write(constraint).await {
+        let dialect = self.connection.dialect().await?;
+        for constraint in CONSTRAINTS {
+            let Some(statement) = dialect_statement(constraint, dialect) else {
+                warn_unsupported(constraint, dialect);
+                continue;
+            };
+            if let Err(e) = self.connection.execute_write(&statement).await {
                 // Ignore constraint already exists errors
                 eprintln!("[WARN] Constraint creation: {}", e);
             }
@@ -39,20 +88,13 @@ impl SchemaManager {
     }
 
     pub async fn create_indexes(&self) -> Result<()> {
-        let indexes = [
-            // Function indexes
-            "CREATE INDEX function_name_index IF NOT EXISTS FOR (f:Function) ON (f.name)",
-            "CREATE INDEX function_address_index IF NOT EXISTS FOR (f:Function) ON (f.address)",
-            // Binary indexes
-            "CREATE INDEX binary_filename_index IF NOT EXISTS FOR (b:Binary) ON (b.filename)",
-            // String indexes
-            "CREATE INDEX string_value_index IF NOT EXISTS FOR (s:String) ON (s.value)",
-            // Fulltext indexes (for substring/keyword search)
-            "CREATE FULLTEXT INDEX string_value_fulltext IF NOT EXISTS FOR (s:String) ON EACH [s.value]",
-        ];
-
-        for index in indexes {
-            if let Err(e) = self.connection.execute_write(index).await {
+        let dialect = self.connection.dialect().await?;
+        for index in INDEXES {
+            let Some(statement) = dialect_statement(index, dialect) else {
+                warn_unsupported(index, dialect);
+                continue;
+            };
+            if let Err(e) = self.connection.execute_write(&statement).await {
                 // Ignore index already exists errors
                 eprintln!("[WARN] Index creation: {}", e);
             }
@@ -60,6 +102,89 @@ impl SchemaManager {
 
         Ok(())
     }
+
+    /// Compare the constraints and indexes in the database with the ones `database init`
+    /// creates, by name. Token lookup indexes and indexes backing a constraint are not
+    /// listed as extra.
+    ///
+    /// Memgraph schema elements have no names; they are matched by label and property.
+    /// Elements the server cannot create are reported as unsupported.
+    pub async fn verify_schema(&self) -> Result<SchemaReport> {
+        let dialect = self.connection.dialect().await?;
+        let (constraints, indexes) = match dialect {
+            Dialect::Neo4j => self.neo4j_schema().await?,
+            Dialect::Memgraph => self.memgraph_schema().await?,
+            Dialect::Other => (HashSet::new(), HashSet::new()),
+        };
+
+        Ok(SchemaReport {
+            constraints: compare(CONSTRAINTS, &constraints, dialect),
+            indexes: compare(INDEXES, &indexes, dialect),
+        })
+    }
+
+    /// Names of the constraints and of the indexes not backing a constraint
+    async fn neo4j_schema(&self) -> Result<(HashSet<String>, HashSet<String>)> {
+        let constraints: HashSet<String> = self
+            .connection
+            .read(Cypher::new("SHOW CONSTRAINTS YIELD name RETURN name"))
+            .await?
+            .into_iter()
+            .filter_map(|row| row.get::<String>("name").ok())
+            .collect();
+        let indexes: HashSet<String> = self
+            .connection
+            .read(Cypher::new(
+                "SHOW INDEXES YIELD name, type, owningConstraint
+                 WHERE type <> 'LOOKUP' AND owningConstraint IS NULL
+                 RETURN name",
+            ))
+            .await?
+            .into_iter()
+            .filter_map(|row| row.get::<String>("name").ok())
+            .collect();
+        Ok((constraints, indexes))
+    }
+
+    /// Constraints and indexes named after the statement creating the same label and
+    /// property, or `Label(property)` when no statement does
+    async fn memgraph_schema(&self) -> Result<(HashSet<String>, HashSet<String>)> {
+        let constraints = self
+            .connection
+            .read(Cypher::new("SHOW CONSTRAINT INFO"))
+            .await?
+            .iter()
+            .filter_map(|row| memgraph_target(row, "properties"))
+            .map(|(label, property)| target_name(CONSTRAINTS, &label, &property))
+            .collect();
+        let indexes = self
+            .connection
+            .read(Cypher::new("SHOW INDEX INFO"))
+            .await?
+            .iter()
+            .filter_map(|row| memgraph_target(row, "property"))
+            .map(|(label, property)| target_name(INDEXES, &label, &property))
+            .collect();
+        Ok((constraints, indexes))
+    }
+
+    /// Run the statements of missing constraints and indexes
+    pub async fn create_missing(&self, report: &SchemaReport) -> Result<()> {
+        let dialect = self.connection.dialect().await?;
+        for (name, status) in report.constraints.iter().chain(&report.indexes) {
+            if let SchemaStatus::Missing(statement) = status {
+                let Some(statement) = dialect_statement(statement, dialect) else {
+                    continue;
+                };
+                self.connection
+                    .execute_write(&statement)
+                    .await
+                    .map_err(|e| anyhow::anyhow!("Failed to create '{}': {}", name, e))?;
+            }
+        }
+        Ok(())
+    }
+
     pub async fn initialize_database(connection: &Neo4jConnection) -> Result<()> {
         // Test connection
         connection.test_connection().await?;
@@ -74,3 +199,198 @@ impl SchemaManager {
         connection.clear_all().await
     }
 }
+
+/// State of one expected or found schema element
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaStatus {
+    Present,
+    /// Expected but absent; holds the statement that creates it
+    Missing(&'static str),
+    /// Found in the database but not created by `database init`
+    Extra,
+    /// Expected but not available on this server (e.g. fulltext indexes on Memgraph)
+    Unsupported,
+}
+
+/// Result of [`SchemaManager::verify_schema`], as `(name, status)` pairs sorted by name
+#[derive(Debug, Clone, Default)]
+pub struct SchemaReport {
+    pub constraints: Vec<(String, SchemaStatus)>,
+    pub indexes: Vec<(String, SchemaStatus)>,
+}
+
+impl SchemaReport {
+    pub fn missing(&self) -> usize {
+        self.constraints
+            .iter()
+            .chain(&self.indexes)
+            .filter(|(_, status)| matches!(status, SchemaStatus::Missing(_)))
+            .count()
+    }
+}
+
+/// Name given in a `CREATE [FULLTEXT] CONSTRAINT|INDEX <name> IF NOT EXISTS ...` statement
+fn schema_name(statement: &str) -> &str {
+    let mut words = statement.split_whitespace();
+    words
+        .by_ref()
+        .find(|word| *word == "CONSTRAINT" || *word == "INDEX");
+    words.next().unwrap_or(statement)
+}
+
+/// Statement creating the element of a Neo4j `statement` in `dialect`, or `None` when
+/// the server has no equivalent
+fn dialect_statement(statement: &str, dialect: Dialect) -> Option<String> {
+    match dialect {
+        Dialect::Neo4j => Some(statement.to_string()),
+        Dialect::Memgraph if statement.starts_with("CREATE FULLTEXT") => None,
+        Dialect::Memgraph => {
+            let (label, property) = schema_target(statement)?;
+            Some(if statement.starts_with("CREATE CONSTRAINT") {
+                format!(
+                    "CREATE CONSTRAINT ON (n:{}) ASSERT n.{} IS UNIQUE",
+                    label, property
+                )
+            } else {
+                format!("CREATE INDEX ON :{}({})", label, property)
+            })
+        }
+        Dialect::Other => None,
+    }
+}
+
+fn warn_unsupported(statement: &str, dialect: Dialect) {
+    eprintln!(
+        "[WARN] Skipping '{}': not supported by {}",
+        schema_name(statement),
+        dialect.as_str()
+    );
+}
+
+/// Label and property of a single-property `FOR (x:Label) REQUIRE x.property ...` or
+/// `FOR (x:Label) ON (x.property)` statement
+fn schema_target(statement: &str) -> Option<(&str, &str)> {
+    let (_, pattern) = statement.split_once("FOR (")?;
+    let (_, pattern) = pattern.split_once(':')?;
+    let (label, rest) = pattern.split_once(')')?;
+    let (_, property) = rest
+        .split_once(" REQUIRE ")
+        .or_else(|| rest.split_once(" ON ("))?;
+    let (_, property) = property.split_once('.')?;
+    let property = property.split([' ', ')']).next()?;
+    Some((label, property))
+}
+
+/// Label and property of a `SHOW CONSTRAINT INFO` or `SHOW INDEX INFO` row
+fn memgraph_target(row: &Row, property_column: &str) -> Option<(String, String)> {
+    let label = row.get::<String>("label").ok()?;
+    let property = row.get::<String>(property_column).ok().or_else(|| {
+        row.get::<Vec<String>>(property_column)
+            .ok()
+            .and_then(|properties| properties.into_iter().next())
+    })?;
+    Some((label, property))
+}
+
+/// Name of the statement in `statements` creating an element on `label` and `property`
+fn target_name(statements: &[&str], label: &str, property: &str) -> String {
+    statements
+        .iter()
+        .find(|statement| schema_target(statement) == Some((label, property)))
+        .map(|statement| schema_name(statement).to_string())
+        .unwrap_or_else(|| format!("{}({})", label, property))
+}
+
+fn compare(
+    expected: &[&'static str],
+    found: &HashSet<String>,
+    dialect: Dialect,
+) -> Vec<(String, SchemaStatus)> {
+    let mut report: Vec<(String, SchemaStatus)> = expected
+        .iter()
+        .map(|statement| {
+            let name = schema_name(statement);
+            let status = if found.contains(name) {
+                SchemaStatus::Present
+            } else if dialect_statement(statement, dialect).is_none() {
+                SchemaStatus::Unsupported
+            } else {
+                SchemaStatus::Missing(statement)
+            };
+            (name.to_string(), status)
+        })
+        .collect();
+
+    let names: HashSet<&str> = expected.iter().map(|s| schema_name(s)).collect();
+    report.extend(
+        found
+            .iter()
+            .filter(|name| !names.contains(name.as_str()))
+            .map(|name| (name.clone(), SchemaStatus::Extra)),
+    );
+    report.sort_by(|a, b| a.0.cmp(&b.0));
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_names() {
+        assert_eq!(schema_name(CONSTRAINTS[0]), "binary_hash_unique");
+        assert_eq!(
+            schema_name(INDEXES[INDEXES.len() - 1]),
+            "string_value_fulltext"
+        );
+
+        let found: HashSet<String> = ["binary_hash_unique", "legacy_index"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let report = compare(&CONSTRAINTS[..2], &found, Dialect::Neo4j);
+        assert_eq!(
+            report[0],
+            ("binary_hash_unique".to_string(), SchemaStatus::Present)
+        );
+        assert_eq!(
+            report[1],
+            (
+                "function_uid_unique".to_string(),
+                SchemaStatus::Missing(CONSTRAINTS[1])
+            )
+        );
+        assert_eq!(report[2], ("legacy_index".to_string(), SchemaStatus::Extra));
+    }
+
+    #[test]
+    fn test_memgraph_statements() {
+        assert_eq!(
+            dialect_statement(CONSTRAINTS[0], Dialect::Memgraph).unwrap(),
+            "CREATE CONSTRAINT ON (n:Binary) ASSERT n.hash IS UNIQUE"
+        );
+        assert_eq!(
+            dialect_statement(INDEXES[0], Dialect::Memgraph).unwrap(),
+            "CREATE INDEX ON :Function(name)"
+        );
+        assert_eq!(
+            dialect_statement(INDEXES[INDEXES.len() - 1], Dialect::Memgraph),
+            None
+        );
+        assert_eq!(target_name(INDEXES, "Binary", "md5"), "binary_md5_index");
+        assert_eq!(target_name(INDEXES, "Binary", "family"), "Binary(family)");
+
+        let report = compare(
+            &INDEXES[INDEXES.len() - 1..],
+            &HashSet::new(),
+            Dialect::Memgraph,
+        );
+        assert_eq!(
+            report[0],
+            (
+                "string_value_fulltext".to_string(),
+                SchemaStatus::Unsupported
+            )
+        );
+    }
+}
@@ -0,0 +1,195 @@
+//! Call graph snippets for tickets and wikis (Mermaid flowchart, PlantUML) and
+//! Cytoscape.js elements JSON for web frontends
+
+use serde_json::{json, Value};
+use std::fmt::Write;
+
+/// Directed graph of functions, deduplicated by UID, with optional edge labels
+#[derive(Debug, Default)]
+pub struct Diagram {
+    nodes: Vec<DiagramNode>,
+    edges: Vec<(usize, usize, Option<String>)>,
+}
+
+#[derive(Debug)]
+struct DiagramNode {
+    uid: String,
+    name: String,
+    /// Function type (`Internal`, `Import`, ..., `Library`), when known
+    kind: Option<String>,
+}
+
+impl Diagram {
+    /// Add a call between two functions given as (uid, display name)
+    pub fn add_edge(&mut self, from: (&str, &str), to: (&str, &str), label: Option<&str>) {
+        let from = self.node(from.0, from.1);
+        let to = self.node(to.0, to.1);
+        let label = label.filter(|l| !l.is_empty()).map(str::to_string);
+        if !self.edges.contains(&(from, to, label.clone())) {
+            self.edges.push((from, to, label));
+        }
+    }
+
+    /// Add consecutive calls along a path of (uid, display name, label) nodes
+    ///
+    /// `label_on_callee` selects whether a node's label describes the call into it
+    /// (downward paths) or the call it makes (upward chains).
+    pub fn add_path<'a, I>(&mut self, nodes: I, label_on_callee: bool)
+    where
+        I: IntoIterator<Item = (&'a str, &'a str, Option<&'a str>)>,
+    {
+        let nodes: Vec<_> = nodes.into_iter().collect();
+        for pair in nodes.windows(2) {
+            let (from, to) = (pair[0], pair[1]);
+            let label = if label_on_callee { to.2 } else { from.2 };
+            self.add_edge((from.0, from.1), (to.0, to.1), label);
+        }
+    }
+
+    /// Record the function type of a node already added through an edge
+    pub fn set_kind(&mut self, uid: &str, kind: &str) {
+        if let Some(node) = self.nodes.iter_mut().find(|n| n.uid == uid) {
+            node.kind = Some(kind.to_string());
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.edges.is_empty()
+    }
+
+    fn node(&mut self, uid: &str, name: &str) -> usize {
+        match self.nodes.iter().position(|n| n.uid == uid) {
+            Some(index) => index,
+            None => {
+                self.nodes.push(DiagramNode {
+                    uid: uid.to_string(),
+                    name: name.to_string(),
+                    kind: None,
+                });
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    /// Mermaid `flowchart TD` definition
+    pub fn mermaid(&self) -> String {
+        let mut out = String::from("flowchart TD\n");
+        for (i, node) in self.nodes.iter().enumerate() {
+            let _ = writeln!(out, "    n{}[\"{}\"]", i, mermaid_text(&node.name));
+        }
+        for (from, to, label) in &self.edges {
+            let _ = match label {
+                Some(label) => writeln!(
+                    out,
+                    "    n{} -->|\"{}\"| n{}",
+                    from,
+                    mermaid_text(label),
+                    to
+                ),
+                None => writeln!(out, "    n{} --> n{}", from, to),
+            };
+        }
+        out
+    }
+
+    /// PlantUML component diagram between `@startuml` and `@enduml`
+    pub fn plantuml(&self) -> String {
+        let mut out = String::from("@startuml\n");
+        for (i, node) in self.nodes.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "rectangle \"{}\" as n{}",
+                node.name.replace('"', "'"),
+                i
+            );
+        }
+        for (from, to, label) in &self.edges {
+            let _ = match label {
+                Some(label) => writeln!(out, "n{} --> n{} : {}", from, to, label),
+                None => writeln!(out, "n{} --> n{}", from, to),
+            };
+        }
+        out.push_str("@enduml\n");
+        out
+    }
+
+    /// Cytoscape.js `{"elements": {"nodes": [...], "edges": [...]}}` document
+    ///
+    /// Node ids are function UIDs and the lowercase function type is the node class,
+    /// so stylesheets can select `node.import` or `node.library`.
+    pub fn cytoscape(&self) -> Value {
+        let nodes: Vec<Value> = self
+            .nodes
+            .iter()
+            .map(|node| {
+                let kind = node.kind.as_deref().unwrap_or("Internal");
+                json!({
+                    "data": {"id": node.uid, "label": node.name, "type": kind},
+                    "classes": kind.to_lowercase(),
+                })
+            })
+            .collect();
+        let edges: Vec<Value> = self
+            .edges
+            .iter()
+            .enumerate()
+            .map(|(i, (from, to, label))| {
+                json!({
+                    "data": {
+                        "id": format!("e{}", i),
+                        "source": self.nodes[*from].uid,
+                        "target": self.nodes[*to].uid,
+                        "label": label,
+                    }
+                })
+            })
+            .collect();
+
+        json!({"elements": {"nodes": nodes, "edges": edges}})
+    }
+}
+
+/// Quote-safe Mermaid label text
+fn mermaid_text(text: &str) -> String {
+    text.replace('"', "#quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diagram_formats() {
+        let mut diagram = Diagram::default();
+        diagram.add_path(
+            [
+                ("f1", "main", None),
+                ("f2", "decrypt<\"k\">", Some("0x401020")),
+                (
+                    "imp:kernel32.dll:VirtualAlloc",
+                    "VirtualAlloc",
+                    Some("0x402010"),
+                ),
+            ],
+            true,
+        );
+        diagram.add_edge(("f1", "main"), ("f2", "decrypt<\"k\">"), Some("0x401020"));
+
+        assert_eq!(
+            diagram.mermaid(),
+            "flowchart TD\n    n0[\"main\"]\n    n1[\"decrypt<#quot;k#quot;>\"]\n    n2[\"VirtualAlloc\"]\n    \
+             n0 -->|\"0x401020\"| n1\n    n1 -->|\"0x402010\"| n2\n"
+        );
+        assert!(diagram
+            .plantuml()
+            .contains("rectangle \"decrypt<'k'>\" as n1\n"));
+        assert!(diagram.plantuml().contains("n1 --> n2 : 0x402010\n"));
+
+        diagram.set_kind("imp:kernel32.dll:VirtualAlloc", "Import");
+        let elements = &diagram.cytoscape()["elements"];
+        assert_eq!(elements["nodes"][0]["classes"], "internal");
+        assert_eq!(elements["nodes"][2]["classes"], "import");
+        assert_eq!(elements["edges"][1]["data"]["source"], "f2");
+        assert_eq!(elements["edges"][1]["data"]["label"], "0x402010");
+    }
+}
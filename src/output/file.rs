@@ -0,0 +1,118 @@
+use anyhow::Result;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Output file written to a temporary file beside its destination and renamed over
+/// it on `commit`, so a failed command leaves neither a truncated result nor a
+/// clobbered earlier one
+pub struct AtomicFile {
+    file: Option<BufWriter<File>>,
+    temp: PathBuf,
+    path: PathBuf,
+}
+
+impl AtomicFile {
+    /// Start writing `path`; an existing file is only replaced with `force`
+    pub fn create(path: &str, force: bool) -> Result<Self> {
+        let path = PathBuf::from(path);
+        if !force && path.exists() {
+            return Err(anyhow::anyhow!(
+                "Output file '{}' already exists, pass --force to overwrite it",
+                path.display()
+            ));
+        }
+
+        let temp = temp_path(&path);
+        let file = File::create(&temp).map_err(|e| {
+            anyhow::anyhow!("Failed to create output file '{}': {}", path.display(), e)
+        })?;
+        Ok(Self {
+            file: Some(BufWriter::new(file)),
+            temp,
+            path,
+        })
+    }
+
+    /// Flush the written content and move it into place
+    pub fn commit(mut self) -> Result<()> {
+        if let Some(file) = &mut self.file {
+            file.flush()?;
+            file.get_ref().sync_all()?;
+        }
+        // Closed before the rename; from here on Drop leaves the file alone
+        self.file = None;
+        std::fs::rename(&self.temp, &self.path).map_err(|e| {
+            let _ = std::fs::remove_file(&self.temp);
+            anyhow::anyhow!(
+                "Failed to write output file '{}': {}",
+                self.path.display(),
+                e
+            )
+        })
+    }
+}
+
+impl Write for AtomicFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match &mut self.file {
+            Some(file) => file.write(buf),
+            None => Err(io::Error::other("output file already committed")),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.file {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for AtomicFile {
+    fn drop(&mut self) {
+        // Not committed: discard the partial output
+        if self.file.take().is_some() {
+            let _ = std::fs::remove_file(&self.temp);
+        }
+    }
+}
+
+/// Hidden file in the destination's directory, so the rename stays on one filesystem
+fn temp_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{}.{}.tmp", name, std::process::id()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_atomic_file() {
+        let dir = std::env::temp_dir().join(format!("binaryx-output-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("result.json");
+        let path_str = path.to_str().unwrap();
+
+        let mut file = AtomicFile::create(path_str, false).unwrap();
+        file.write_all(b"first").unwrap();
+        assert!(!path.exists());
+        file.commit().unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "first");
+
+        assert!(AtomicFile::create(path_str, false).is_err());
+
+        // Dropped without commit: the earlier result stays
+        let mut file = AtomicFile::create(path_str, true).unwrap();
+        file.write_all(b"partial").unwrap();
+        drop(file);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "first");
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
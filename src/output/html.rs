@@ -0,0 +1,349 @@
+//! Self-contained HTML triage report
+//!
+//! The page embeds its styles, the call graph as vis.js-style `{nodes, edges}` JSON
+//! and a small canvas renderer, so it can be opened offline or attached to a ticket.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::models::{GraphData, GraphEdge, GraphLink, GraphNode, TriageReport};
+
+/// Render the full report as a standalone HTML page
+pub fn triage_report(report: &TriageReport) -> anyhow::Result<String> {
+    let binary = &report.binary;
+    let mut html = String::new();
+
+    writeln!(html, "<!DOCTYPE html>")?;
+    writeln!(html, "<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">")?;
+    writeln!(
+        html,
+        "<title>BinaryX triage: {}</title>",
+        escape(&binary.filename)
+    )?;
+    writeln!(html, "<style>{}</style>\n</head>\n<body>", STYLE)?;
+    writeln!(html, "<h1>{}</h1>", escape(&binary.filename))?;
+    writeln!(html, "<p class=\"hash\">{}</p>", escape(&binary.hash))?;
+
+    // Metadata
+    let metadata = [
+        ("Format", Some(format!("{:?}", binary.format))),
+        ("Architecture", Some(binary.arch.as_str().to_string())),
+        ("Size", Some(binary.file_size.to_string())),
+        ("Image base", binary.image_base.clone()),
+        ("Compiler", binary.compiler.clone()),
+        ("Packer", binary.packer.clone()),
+        ("Linker", binary.linker_version.clone()),
+        ("Timestamp", binary.timestamp.clone()),
+        ("First imported", binary.first_imported.clone()),
+        ("PDB path", binary.pdb_path.clone()),
+        ("MD5", binary.md5.clone()),
+        ("SHA-1", binary.sha1.clone()),
+        ("ssdeep", binary.ssdeep.clone()),
+        ("TLSH", binary.tlsh.clone()),
+        (
+            "Imphash",
+            binary.pe_info.as_ref().and_then(|pe| pe.imphash.clone()),
+        ),
+        (
+            "Max entropy",
+            binary
+                .max_entropy()
+                .map(|entropy| format!("{:.2}", entropy)),
+        ),
+        (
+            "Likely packed",
+            binary.likely_packed.then(|| "yes".to_string()),
+        ),
+    ];
+    writeln!(html, "<h2>Metadata</h2>\n<table>")?;
+    for (name, value) in metadata.iter() {
+        if let Some(value) = value {
+            writeln!(html, "<tr><th>{}</th><td>{}</td></tr>", name, escape(value))?;
+        }
+    }
+    writeln!(html, "</table>")?;
+
+    // Capabilities
+    writeln!(
+        html,
+        "<h2>Capabilities <span class=\"score\">risk score {:.1}</span></h2>",
+        report.risk.score
+    )?;
+    if report.risk.contributions.is_empty() {
+        writeln!(html, "<p class=\"empty\">No suspicious APIs imported</p>")?;
+    } else {
+        writeln!(
+            html,
+            "<table>\n<tr><th>Category</th><th>API</th><th>Weight</th><th>Call sites</th></tr>"
+        )?;
+        for hit in &report.risk.contributions {
+            writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{:.1}</td><td>{}</td></tr>",
+                escape(&hit.category),
+                escape(&hit.api),
+                hit.weight,
+                escape(&hit.call_sites.join(", "))
+            )?;
+        }
+        writeln!(html, "</table>")?;
+    }
+
+    // Imports
+    let import_count: usize = report.imports.iter().map(|group| group.apis.len()).sum();
+    writeln!(
+        html,
+        "<h2>Imports <span class=\"count\">{} APIs from {} libraries</span></h2>",
+        import_count,
+        report.imports.len()
+    )?;
+    for group in &report.imports {
+        writeln!(
+            html,
+            "<details><summary>{} ({})</summary><ul>",
+            escape(&group.library),
+            group.apis.len()
+        )?;
+        for api in &group.apis {
+            writeln!(html, "<li>{}</li>", escape(api))?;
+        }
+        writeln!(html, "</ul></details>")?;
+    }
+
+    // Strings
+    writeln!(html, "<h2>Top strings</h2>")?;
+    if report.strings.is_empty() {
+        writeln!(html, "<p class=\"empty\">No strings imported</p>")?;
+    } else {
+        writeln!(html, "<table>\n<tr><th>Refs</th><th>Value</th></tr>")?;
+        for string in &report.strings {
+            writeln!(
+                html,
+                "<tr><td>{}</td><td><code>{}</code></td></tr>",
+                string.references,
+                escape(&string.value)
+            )?;
+        }
+        writeln!(html, "</table>")?;
+    }
+
+    // Call graph
+    writeln!(
+        html,
+        "<h2>Call graph <span class=\"count\">{} edges</span></h2>",
+        report.call_graph.len()
+    )?;
+    writeln!(
+        html,
+        "<div class=\"legend\"><span class=\"Internal\">internal</span><span class=\"Export\">export</span>\
+         <span class=\"Import\">import</span><span class=\"Thunk\">thunk</span><span class=\"Library\">library</span></div>"
+    )?;
+    writeln!(
+        html,
+        "<canvas id=\"callgraph\" width=\"1100\" height=\"700\"></canvas>"
+    )?;
+    writeln!(
+        html,
+        "<script type=\"application/json\" id=\"graph-data\">{}</script>",
+        script_json(&graph_data(&report.call_graph))?
+    )?;
+    writeln!(html, "<script>{}", GRAPH_SCRIPT)?;
+    writeln!(
+        html,
+        "drawGraph(document.getElementById('callgraph'), \
+         JSON.parse(document.getElementById('graph-data').textContent));\n</script>"
+    )?;
+    writeln!(html, "</body>\n</html>")?;
+
+    Ok(html)
+}
+
+/// Nodes and edges of the call graph in the vis.js DataSet shape
+pub fn graph_data(edges: &[GraphEdge]) -> GraphData {
+    let mut nodes: BTreeMap<&str, GraphNode> = BTreeMap::new();
+    for edge in edges {
+        nodes.entry(&edge.from_uid).or_insert_with(|| GraphNode {
+            id: edge.from_uid.clone(),
+            label: edge.from_name.clone(),
+            group: edge.from_kind.clone(),
+        });
+        nodes.entry(&edge.to_uid).or_insert_with(|| GraphNode {
+            id: edge.to_uid.clone(),
+            label: edge.to_name.clone(),
+            group: edge.to_kind.clone(),
+        });
+    }
+
+    GraphData {
+        nodes: nodes.into_values().collect(),
+        edges: edges
+            .iter()
+            .map(|edge| GraphLink {
+                from: edge.from_uid.clone(),
+                to: edge.to_uid.clone(),
+            })
+            .collect(),
+    }
+}
+
+/// JSON that cannot close the surrounding `<script>` element
+fn script_json<T: Serialize>(value: &T) -> serde_json::Result<String> {
+    Ok(serde_json::to_string(value)?.replace('<', "\\u003c"))
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c if c.is_control() && c != '\n' && c != '\t' => {
+                let _ = write!(escaped, "\\x{:02x}", c as u32);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+const STYLE: &str = "
+body { font-family: -apple-system, 'Segoe UI', sans-serif; margin: 2em; color: #222; }
+h1 { margin-bottom: 0; }
+h2 { border-bottom: 1px solid #ccc; padding-bottom: .2em; margin-top: 1.6em; }
+.hash { font-family: monospace; color: #666; margin-top: .2em; }
+.score, .count { font-size: .6em; font-weight: normal; color: #666; margin-left: .5em; }
+.empty { color: #888; font-style: italic; }
+table { border-collapse: collapse; }
+th, td { text-align: left; padding: .25em .8em; border-bottom: 1px solid #eee; vertical-align: top; }
+td code { word-break: break-all; }
+details { margin: .2em 0; }
+summary { cursor: pointer; font-family: monospace; }
+ul { columns: 3; font-family: monospace; font-size: .9em; }
+canvas { border: 1px solid #ccc; cursor: grab; }
+.legend span { margin-right: 1em; font-size: .9em; }
+.legend span::before { content: '\\25CF'; margin-right: .3em; }
+.legend .Internal::before { color: #4878d0; }
+.legend .Export::before { color: #6acc64; }
+.legend .Import::before { color: #ee854a; }
+.legend .Thunk::before { color: #956cb4; }
+.legend .Library::before { color: #8c8c8c; }
+";
+
+/// `drawGraph(canvas, {nodes, edges})`: force-directed layout drawn on a canvas; drag
+/// to pan, wheel to zoom. Edges with a `label` show it, `directed: false` drops the
+/// arrows, and drawing another graph on the same canvas replaces the previous one.
+pub const GRAPH_SCRIPT: &str = r#"
+function drawGraph(canvas, data) {
+  var ctx = canvas.getContext('2d');
+  var colors = { Internal: '#4878d0', Export: '#6acc64', Import: '#ee854a', Thunk: '#956cb4', Library: '#8c8c8c',
+                 Reference: '#d9534f', Sample: '#4878d0' };
+  var run = (canvas.graphRun || 0) + 1;
+  canvas.graphRun = run;
+  var index = {};
+  data.nodes.forEach(function (n, i) {
+    var angle = 2 * Math.PI * i / Math.max(data.nodes.length, 1);
+    n.x = Math.cos(angle) * 200; n.y = Math.sin(angle) * 200; n.vx = 0; n.vy = 0;
+    index[n.id] = n;
+  });
+  var edges = data.edges.filter(function (e) { return index[e.from] && index[e.to]; });
+  var view = { x: canvas.width / 2, y: canvas.height / 2, scale: 1 };
+
+  function step() {
+    var nodes = data.nodes;
+    for (var i = 0; i < nodes.length; i++) {
+      for (var j = i + 1; j < nodes.length; j++) {
+        var dx = nodes[j].x - nodes[i].x, dy = nodes[j].y - nodes[i].y;
+        var d2 = Math.max(dx * dx + dy * dy, 1), f = 2000 / d2, d = Math.sqrt(d2);
+        nodes[i].vx -= f * dx / d; nodes[i].vy -= f * dy / d;
+        nodes[j].vx += f * dx / d; nodes[j].vy += f * dy / d;
+      }
+    }
+    edges.forEach(function (e) {
+      var a = index[e.from], b = index[e.to];
+      var dx = b.x - a.x, dy = b.y - a.y;
+      a.vx += dx * 0.01; a.vy += dy * 0.01; b.vx -= dx * 0.01; b.vy -= dy * 0.01;
+    });
+    nodes.forEach(function (n) {
+      n.vx -= n.x * 0.002; n.vy -= n.y * 0.002;
+      n.x += n.vx * 0.5; n.y += n.vy * 0.5; n.vx *= 0.6; n.vy *= 0.6;
+    });
+  }
+
+  function draw() {
+    ctx.setTransform(1, 0, 0, 1, 0, 0);
+    ctx.clearRect(0, 0, canvas.width, canvas.height);
+    ctx.setTransform(view.scale, 0, 0, view.scale, view.x, view.y);
+    ctx.strokeStyle = '#bbb'; ctx.fillStyle = '#bbb';
+    edges.forEach(function (e) {
+      var a = index[e.from], b = index[e.to];
+      var angle = Math.atan2(b.y - a.y, b.x - a.x);
+      var tx = b.x - Math.cos(angle) * 6, ty = b.y - Math.sin(angle) * 6;
+      ctx.beginPath(); ctx.moveTo(a.x, a.y); ctx.lineTo(tx, ty); ctx.stroke();
+      if (data.directed !== false) {
+        ctx.beginPath(); ctx.moveTo(tx, ty);
+        ctx.lineTo(tx - Math.cos(angle - 0.4) * 6, ty - Math.sin(angle - 0.4) * 6);
+        ctx.lineTo(tx - Math.cos(angle + 0.4) * 6, ty - Math.sin(angle + 0.4) * 6);
+        ctx.fill();
+      }
+      if (e.label) {
+        ctx.font = '9px monospace'; ctx.fillStyle = '#666';
+        ctx.fillText(e.label, (a.x + b.x) / 2, (a.y + b.y) / 2);
+        ctx.fillStyle = '#bbb';
+      }
+    });
+    ctx.font = '10px monospace';
+    data.nodes.forEach(function (n) {
+      ctx.fillStyle = colors[n.group] || '#4878d0';
+      ctx.beginPath(); ctx.arc(n.x, n.y, 5, 0, 2 * Math.PI); ctx.fill();
+      ctx.fillStyle = '#333'; ctx.fillText(n.label, n.x + 7, n.y + 3);
+    });
+  }
+
+  var ticks = 0;
+  (function animate() {
+    if (canvas.graphRun !== run) return;
+    step(); draw();
+    if (++ticks < 300) requestAnimationFrame(animate);
+  })();
+
+  var drag = null;
+  canvas.onmousedown = function (e) { drag = { x: e.clientX - view.x, y: e.clientY - view.y }; };
+  canvas.onmouseup = canvas.onmouseleave = function () { drag = null; };
+  canvas.onmousemove = function (e) {
+    if (!drag) return;
+    view.x = e.clientX - drag.x; view.y = e.clientY - drag.y; draw();
+  };
+  canvas.onwheel = function (e) {
+    e.preventDefault();
+    view.scale *= e.deltaY < 0 ? 1.1 : 1 / 1.1; draw();
+  };
+}
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script_json_and_escape() {
+        let edges = vec![GraphEdge {
+            from_uid: "f1".to_string(),
+            from_name: "main".to_string(),
+            from_kind: "Internal".to_string(),
+            to_uid: "imp:kernel32.dll:CreateFileW".to_string(),
+            to_name: "</script><b>".to_string(),
+            to_kind: "Import".to_string(),
+        }];
+        let json = script_json(&graph_data(&edges)).unwrap();
+        assert!(!json.contains("</script>"));
+        assert!(json.contains(r#""from":"f1""#));
+        assert_eq!(
+            escape("<a href=\"x\">&'"),
+            "&lt;a href=&quot;x&quot;&gt;&amp;&#39;"
+        );
+    }
+}
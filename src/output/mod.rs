@@ -0,0 +1,395 @@
+pub mod diagram;
+pub mod file;
+pub mod html;
+pub mod networkx;
+#[cfg(feature = "parquet")]
+pub mod parquet;
+pub mod perspective;
+pub mod rows;
+pub mod table;
+
+pub use diagram::Diagram;
+pub use file::AtomicFile;
+pub use rows::*;
+pub use table::{Cell, Color, Column};
+
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::Serialize;
+use std::io::{self, IsTerminal, Write};
+
+/// Output format shared by all commands that print results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable aligned table
+    #[default]
+    Table,
+    /// Pretty-printed JSON document
+    Json,
+    /// One compact JSON object per line, written row by row
+    Jsonl,
+    /// Comma-separated values with a header row
+    Csv,
+    /// GitHub-flavored Markdown tables, with free-form output in fenced code blocks
+    Markdown,
+    /// YAML document
+    Yaml,
+    /// Mermaid flowchart of the call graph (`query callgraph` and `query call-path`)
+    Mermaid,
+    /// PlantUML diagram of the call graph (`query callgraph` and `query call-path`)
+    Plantuml,
+    /// Cytoscape.js elements JSON of the call graph (`query callgraph` and `query call-path`)
+    Cytoscape,
+    /// One Parquet file per node label and relationship type (`database export` only)
+    #[value(hide = true)]
+    Parquet,
+}
+
+impl OutputFormat {
+    /// Whether the format serializes the full result object instead of rows
+    pub fn is_document(self) -> bool {
+        matches!(
+            self,
+            OutputFormat::Json | OutputFormat::Jsonl | OutputFormat::Yaml
+        )
+    }
+
+    /// Whether the format draws a call graph diagram instead of rows
+    pub fn is_diagram(self) -> bool {
+        matches!(
+            self,
+            OutputFormat::Mermaid | OutputFormat::Plantuml | OutputFormat::Cytoscape
+        )
+    }
+
+    pub fn name(self) -> String {
+        self.to_possible_value()
+            .map(|value| value.get_name().to_string())
+            .unwrap_or_default()
+    }
+}
+
+/// Global output settings taken from the command line
+#[derive(Debug, Clone, Default)]
+pub struct OutputOptions {
+    /// Destination file, stdout when unset
+    pub path: Option<String>,
+    /// Replace an existing destination file
+    pub force: bool,
+    /// Emit ANSI colors in table output
+    pub color: bool,
+}
+
+impl OutputOptions {
+    /// Colors are only used for interactive stdout and honor `NO_COLOR`
+    pub fn new(path: Option<String>, force: bool, no_color: bool) -> Self {
+        let color = !no_color
+            && path.is_none()
+            && std::env::var_os("NO_COLOR").is_none()
+            && io::stdout().is_terminal();
+        Self { path, force, color }
+    }
+
+    /// Destination file, written atomically, or `None` for stdout
+    pub fn create_file(&self) -> Result<Option<AtomicFile>> {
+        self.path
+            .as_deref()
+            .map(|path| AtomicFile::create(path, self.force))
+            .transpose()
+    }
+}
+
+/// Types that can be rendered as a row of a table or CSV file
+pub trait TableRow {
+    fn columns() -> Vec<Column>;
+
+    fn cells(&self) -> Vec<String>;
+
+    /// Cells used for the human-readable table, may escape control characters
+    fn table_cells(&self) -> Vec<String> {
+        self.cells()
+    }
+
+    /// Highlight color for a table cell
+    fn cell_color(&self, _column: usize) -> Option<Color> {
+        None
+    }
+}
+
+/// Writes command results to stdout or a file in the selected format
+pub struct Renderer {
+    format: OutputFormat,
+    color: bool,
+    out: Destination,
+    /// A Markdown code fence is open for free-form lines
+    in_code_block: bool,
+}
+
+impl Renderer {
+    pub fn new(format: OutputFormat, options: &OutputOptions) -> Result<Self> {
+        Ok(Self {
+            format,
+            color: options.color,
+            out: match options.create_file()? {
+                Some(file) => Destination::File(file),
+                None => Destination::Stdout(io::stdout()),
+            },
+            in_code_block: false,
+        })
+    }
+
+    pub fn format(&self) -> OutputFormat {
+        self.format
+    }
+
+    /// Render a list of records: a titled table, CSV rows, or a JSON/YAML array
+    pub fn render_list<R: Serialize + TableRow>(&mut self, title: &str, rows: &[R]) -> Result<()> {
+        if self.format == OutputFormat::Jsonl {
+            return self.json_lines(rows);
+        }
+        if self.format.is_document() {
+            return self.document(rows);
+        }
+
+        if matches!(self.format, OutputFormat::Table | OutputFormat::Markdown) {
+            if rows.is_empty() {
+                return Ok(());
+            }
+            self.line(&format!("\n{} ({} found):", title, rows.len()))?;
+        }
+
+        self.table(rows)
+    }
+
+    /// Render rows as a table or CSV; ignored for document formats
+    pub fn table<R: TableRow>(&mut self, rows: &[R]) -> Result<()> {
+        let cells: Vec<Vec<Cell>> = rows
+            .iter()
+            .map(|row| {
+                let texts = match self.format {
+                    OutputFormat::Csv => row.cells(),
+                    _ => row.table_cells(),
+                };
+                texts
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, text)| Cell {
+                        text,
+                        color: row.cell_color(i),
+                    })
+                    .collect()
+            })
+            .collect();
+
+        self.write_table(&R::columns(), &cells)
+    }
+
+    /// Render records whose columns are only known at runtime, such as the rows of
+    /// a saved Cypher query; strings are shown as-is, other values as JSON
+    pub fn render_records(
+        &mut self,
+        title: &str,
+        columns: &[String],
+        records: &[serde_json::Map<String, serde_json::Value>],
+    ) -> Result<()> {
+        if self.format == OutputFormat::Jsonl {
+            return self.json_lines(records);
+        }
+        if self.format.is_document() {
+            return self.document(records);
+        }
+
+        if matches!(self.format, OutputFormat::Table | OutputFormat::Markdown) {
+            if records.is_empty() {
+                return Ok(());
+            }
+            self.line(&format!("\n{} ({} found):", title, records.len()))?;
+        }
+
+        let cells: Vec<Vec<Cell>> = records
+            .iter()
+            .map(|record| {
+                columns
+                    .iter()
+                    .map(|column| Cell {
+                        text: match record.get(column) {
+                            None | Some(serde_json::Value::Null) => String::new(),
+                            Some(serde_json::Value::String(text)) => text.clone(),
+                            Some(value) => value.to_string(),
+                        },
+                        color: None,
+                    })
+                    .collect()
+            })
+            .collect();
+        let columns: Vec<Column> = columns.iter().cloned().map(Column::named).collect();
+
+        self.write_table(&columns, &cells)
+    }
+
+    fn write_table(&mut self, columns: &[Column], cells: &[Vec<Cell>]) -> Result<()> {
+        match self.format {
+            OutputFormat::Table => {
+                let rendered = table::render_table(columns, cells, self.color);
+                self.out.write_all(rendered.as_bytes())?;
+            }
+            OutputFormat::Markdown => {
+                self.close_code_block()?;
+                let texts: Vec<Vec<String>> = cells
+                    .iter()
+                    .map(|row| row.iter().map(|cell| cell.text.clone()).collect())
+                    .collect();
+                let rendered = table::render_markdown_table(columns, &texts);
+                writeln!(self.out)?;
+                self.out.write_all(rendered.as_bytes())?;
+            }
+            OutputFormat::Csv => {
+                let mut writer = csv::Writer::from_writer(&mut self.out);
+                writer.write_record(columns.iter().map(|c| c.header.as_ref()))?;
+                for row in cells {
+                    writer.write_record(row.iter().map(|cell| cell.text.as_str()))?;
+                }
+                writer.flush()?;
+            }
+            OutputFormat::Json | OutputFormat::Jsonl | OutputFormat::Yaml => {}
+            OutputFormat::Mermaid
+            | OutputFormat::Plantuml
+            | OutputFormat::Cytoscape
+            | OutputFormat::Parquet => return Err(self.unsupported()),
+        }
+
+        Ok(())
+    }
+
+    /// Serialize a full result object; ignored for row-based formats
+    pub fn document<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        match self.format {
+            OutputFormat::Json => {
+                serde_json::to_writer_pretty(&mut self.out, value)?;
+                writeln!(self.out)?;
+            }
+            OutputFormat::Jsonl => match serde_json::to_value(value)? {
+                serde_json::Value::Array(items) => self.json_lines(&items)?,
+                value => self.json_lines(std::slice::from_ref(&value))?,
+            },
+            OutputFormat::Yaml => serde_yaml::to_writer(&mut self.out, value)?,
+            OutputFormat::Table | OutputFormat::Csv | OutputFormat::Markdown => {}
+            OutputFormat::Mermaid
+            | OutputFormat::Plantuml
+            | OutputFormat::Cytoscape
+            | OutputFormat::Parquet => return Err(self.unsupported()),
+        }
+
+        Ok(())
+    }
+
+    /// Write each item as one compact JSON line, without building the whole document
+    fn json_lines<T: Serialize>(&mut self, items: &[T]) -> Result<()> {
+        for item in items {
+            serde_json::to_writer(&mut self.out, item)?;
+            writeln!(self.out)?;
+        }
+        self.out.flush()?;
+        Ok(())
+    }
+
+    /// Write a call graph diagram; ignored for non-diagram formats
+    pub fn diagram(&mut self, diagram: &Diagram) -> Result<()> {
+        if self.format.is_diagram() && diagram.is_empty() {
+            eprintln!("No calls to draw");
+        }
+        match self.format {
+            OutputFormat::Mermaid => self.out.write_all(diagram.mermaid().as_bytes())?,
+            OutputFormat::Plantuml => self.out.write_all(diagram.plantuml().as_bytes())?,
+            OutputFormat::Cytoscape => {
+                serde_json::to_writer_pretty(&mut self.out, &diagram.cytoscape())?;
+                writeln!(self.out)?;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn unsupported(&self) -> anyhow::Error {
+        let commands = match self.format {
+            OutputFormat::Parquet => "database export",
+            _ => "query callgraph and query call-path",
+        };
+        anyhow::anyhow!(
+            "--format {} is only supported by {}",
+            self.format.name(),
+            commands
+        )
+    }
+
+    /// Write a free-form line; only emitted for the table and Markdown formats
+    pub fn line(&mut self, text: &str) -> Result<()> {
+        match self.format {
+            OutputFormat::Table => writeln!(self.out, "{}", text)?,
+            OutputFormat::Markdown => self.markdown_line(text)?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Unindented lines ending in `:` become headings, other unindented lines
+    /// paragraphs; indented listings are kept verbatim in a fenced code block
+    fn markdown_line(&mut self, text: &str) -> Result<()> {
+        let text = text.trim_start_matches('\n');
+        if text.starts_with(' ') {
+            if !self.in_code_block {
+                writeln!(self.out, "\n```text")?;
+                self.in_code_block = true;
+            }
+            return Ok(writeln!(self.out, "{}", text)?);
+        }
+
+        self.close_code_block()?;
+        if let Some(heading) = text.strip_suffix(':') {
+            writeln!(self.out, "\n### {}", heading)?;
+        } else if !text.is_empty() {
+            writeln!(self.out, "\n{}", text)?;
+        }
+        Ok(())
+    }
+
+    fn close_code_block(&mut self) -> Result<()> {
+        if self.in_code_block {
+            writeln!(self.out, "```")?;
+            self.in_code_block = false;
+        }
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Result<()> {
+        self.close_code_block()?;
+        self.out.flush()?;
+        match std::mem::replace(&mut self.out, Destination::Stdout(io::stdout())) {
+            Destination::File(file) => file.commit(),
+            Destination::Stdout(_) => Ok(()),
+        }
+    }
+}
+
+/// Where a renderer writes; a file only replaces its destination on `finish`
+enum Destination {
+    Stdout(io::Stdout),
+    File(AtomicFile),
+}
+
+impl Write for Destination {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Destination::Stdout(out) => out.write(buf),
+            Destination::File(file) => file.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Destination::Stdout(out) => out.flush(),
+            Destination::File(file) => file.flush(),
+        }
+    }
+}
@@ -0,0 +1,124 @@
+//! Call graph of one binary in the node-link JSON format read by
+//! `networkx.node_link_graph`
+
+use serde_json::{json, Map, Value};
+
+use crate::models::{Binary, CallGraphLink, Function};
+
+/// Directed node-link document: function UIDs as node ids with the stored function
+/// attributes, one link per caller/callee pair
+///
+/// Attributes a function does not have are left out rather than written as null.
+/// The edge provenance (`static`, `dynamic`, `heuristic`) is `edge_source`, since
+/// `source` and `target` name the link ends.
+pub fn node_link_graph(binary: &Binary, functions: &[Function], links: &[CallGraphLink]) -> Value {
+    let nodes: Vec<Value> = functions.iter().map(function_node).collect();
+    let links: Vec<Value> = links
+        .iter()
+        .map(|link| {
+            let mut attributes = Map::new();
+            attributes.insert("source".into(), json!(link.from_uid));
+            attributes.insert("target".into(), json!(link.to_uid));
+            attributes.insert("count".into(), json!(link.count));
+            attributes.insert("offsets".into(), json!(link.offsets));
+            insert_some(&mut attributes, "call_type", link.call_type.as_ref());
+            insert_some(&mut attributes, "edge_source", link.source.as_ref());
+            attributes.insert("confidence".into(), json!(link.confidence));
+            Value::Object(attributes)
+        })
+        .collect();
+
+    json!({
+        "directed": true,
+        "multigraph": false,
+        "graph": {
+            "name": binary.filename,
+            "hash": binary.hash,
+            "format": format!("{:?}", binary.format),
+            "arch": binary.arch.as_str(),
+        },
+        "nodes": nodes,
+        "links": links,
+    })
+}
+
+fn function_node(function: &Function) -> Value {
+    let mut attributes = Map::new();
+    attributes.insert("id".into(), json!(function.uid));
+    attributes.insert("name".into(), json!(function.name));
+    attributes.insert("type".into(), json!(format!("{:?}", function.r#type)));
+    attributes.insert("is_library".into(), json!(function.is_library));
+    insert_some(&mut attributes, "address", function.address.as_ref());
+    insert_some(&mut attributes, "size", function.size.as_ref());
+    insert_some(
+        &mut attributes,
+        "demangled_name",
+        function.demangled_name.as_ref(),
+    );
+    insert_some(
+        &mut attributes,
+        "analyst_name",
+        function.analyst_name.as_ref(),
+    );
+    insert_some(&mut attributes, "entry_kind", function.entry_kind.as_ref());
+    insert_some(
+        &mut attributes,
+        "signature_match",
+        function.signature_match.as_ref(),
+    );
+    insert_some(
+        &mut attributes,
+        "content_hash",
+        function.content_hash.as_ref(),
+    );
+    if !function.crypto.is_empty() {
+        attributes.insert("crypto".into(), json!(function.crypto));
+    }
+    Value::Object(attributes)
+}
+
+fn insert_some<T: serde::Serialize>(
+    attributes: &mut Map<String, Value>,
+    key: &str,
+    value: Option<T>,
+) {
+    if let Some(value) = value {
+        attributes.insert(key.into(), json!(value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_node_link_graph() {
+        let binary: Binary = serde_json::from_value(json!({
+            "hash": "abc", "filename": "a.exe", "file_path": "/samples/a.exe", "file_size": 1024,
+            "format": "PE", "arch": "x86_64"
+        }))
+        .unwrap();
+        let mut main = Function::create_internal("abc", 0x401000, "main", false);
+        main.analyst_name = Some("entry".to_string());
+        let import = Function::create_import("kernel32.dll", "Sleep");
+        let link = CallGraphLink {
+            from_uid: main.uid.clone(),
+            to_uid: import.uid.clone(),
+            count: 2,
+            offsets: vec!["0x401010".to_string(), "0x401020".to_string()],
+            call_type: Some("Direct".to_string()),
+            source: Some("static".to_string()),
+            confidence: 1.0,
+        };
+
+        let graph = node_link_graph(&binary, &[main, import], &[link]);
+        assert_eq!(graph["directed"], true);
+        assert_eq!(graph["graph"]["name"], "a.exe");
+        assert_eq!(graph["nodes"][0]["analyst_name"], "entry");
+        assert_eq!(graph["nodes"][1]["type"], "Import");
+        assert!(graph["nodes"][1].get("address").is_none());
+        assert_eq!(graph["links"][0]["target"], "imp:kernel32.dll:Sleep");
+        assert_eq!(graph["links"][0]["edge_source"], "static");
+        assert_eq!(graph["links"][0]["count"], 2);
+    }
+}
@@ -0,0 +1,301 @@
+//! Parquet tables with columns typed from their values, written by
+//! `database export --format parquet`
+
+use anyhow::Result;
+use neo4rs::BoltType;
+use parquet::basic::{LogicalType, Repetition, Type as PhysicalType};
+use parquet::data_type::{BoolType, ByteArray, ByteArrayType, DoubleType, Int64Type};
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::types::Type;
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+use std::sync::Arc;
+
+/// Rows per Parquet row group
+const ROW_GROUP: usize = 100_000;
+
+/// One cell of a table
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnValue {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Text(String),
+}
+
+/// Physical type of a column: integers mixed with floats become doubles, any other mix
+/// becomes text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColumnKind {
+    Bool,
+    Int,
+    Float,
+    Text,
+}
+
+/// Named columns and rows of values, written as nullable Parquet columns
+#[derive(Debug, Clone, Default)]
+pub struct ColumnarTable {
+    columns: Vec<String>,
+    rows: Vec<Vec<ColumnValue>>,
+}
+
+impl ColumnarTable {
+    /// Table of the `fixed` columns followed by every key of `records`, sorted; keys
+    /// a record lacks are null
+    pub fn from_records(fixed: &[&str], records: Vec<BTreeMap<String, ColumnValue>>) -> Self {
+        let keys: BTreeSet<&String> = records
+            .iter()
+            .flat_map(|record| record.keys())
+            .filter(|key| !fixed.contains(&key.as_str()))
+            .collect();
+        let columns: Vec<String> = fixed
+            .iter()
+            .map(|column| column.to_string())
+            .chain(keys.into_iter().cloned())
+            .collect();
+
+        let rows = records
+            .into_iter()
+            .map(|mut record| {
+                columns
+                    .iter()
+                    .map(|column| record.remove(column).unwrap_or(ColumnValue::Null))
+                    .collect()
+            })
+            .collect();
+        Self { columns, rows }
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    fn kinds(&self) -> Vec<ColumnKind> {
+        (0..self.columns.len())
+            .map(|index| {
+                let mut kind = None;
+                for row in &self.rows {
+                    let value_kind = match &row[index] {
+                        ColumnValue::Null => continue,
+                        ColumnValue::Bool(_) => ColumnKind::Bool,
+                        ColumnValue::Int(_) => ColumnKind::Int,
+                        ColumnValue::Float(_) => ColumnKind::Float,
+                        ColumnValue::Text(_) => ColumnKind::Text,
+                    };
+                    kind = Some(match (kind, value_kind) {
+                        (None, value_kind) => value_kind,
+                        (Some(kind), value_kind) if kind == value_kind => kind,
+                        (Some(ColumnKind::Int), ColumnKind::Float)
+                        | (Some(ColumnKind::Float), ColumnKind::Int) => ColumnKind::Float,
+                        _ => ColumnKind::Text,
+                    });
+                    if kind == Some(ColumnKind::Text) {
+                        break;
+                    }
+                }
+                kind.unwrap_or(ColumnKind::Text)
+            })
+            .collect()
+    }
+
+    /// Write the table as a Parquet file
+    pub fn write<W: Write + Send>(&self, writer: W) -> Result<()> {
+        let kinds = self.kinds();
+        let fields = self
+            .columns
+            .iter()
+            .zip(&kinds)
+            .map(|(name, kind)| {
+                let builder = match kind {
+                    ColumnKind::Bool => Type::primitive_type_builder(name, PhysicalType::BOOLEAN),
+                    ColumnKind::Int => Type::primitive_type_builder(name, PhysicalType::INT64),
+                    ColumnKind::Float => Type::primitive_type_builder(name, PhysicalType::DOUBLE),
+                    ColumnKind::Text => {
+                        Type::primitive_type_builder(name, PhysicalType::BYTE_ARRAY)
+                            .with_logical_type(Some(LogicalType::String))
+                    }
+                };
+                Ok(Arc::new(
+                    builder.with_repetition(Repetition::OPTIONAL).build()?,
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let schema = Arc::new(
+            Type::group_type_builder("schema")
+                .with_fields(fields)
+                .build()?,
+        );
+        let properties = Arc::new(WriterProperties::builder().build());
+        let mut writer = SerializedFileWriter::new(writer, schema, properties)?;
+
+        for chunk in self.rows.chunks(ROW_GROUP) {
+            let mut row_group = writer.next_row_group()?;
+            for (index, kind) in kinds.iter().enumerate() {
+                let Some(mut column) = row_group.next_column()? else {
+                    break;
+                };
+                let cells = chunk.iter().map(|row| &row[index]);
+                let levels: Vec<i16> = cells
+                    .clone()
+                    .map(|cell| i16::from(*cell != ColumnValue::Null))
+                    .collect();
+                match kind {
+                    ColumnKind::Bool => {
+                        let values: Vec<bool> = cells
+                            .filter_map(|cell| match cell {
+                                ColumnValue::Bool(value) => Some(*value),
+                                _ => None,
+                            })
+                            .collect();
+                        column
+                            .typed::<BoolType>()
+                            .write_batch(&values, Some(&levels), None)?;
+                    }
+                    ColumnKind::Int => {
+                        let values: Vec<i64> = cells
+                            .filter_map(|cell| match cell {
+                                ColumnValue::Int(value) => Some(*value),
+                                _ => None,
+                            })
+                            .collect();
+                        column
+                            .typed::<Int64Type>()
+                            .write_batch(&values, Some(&levels), None)?;
+                    }
+                    ColumnKind::Float => {
+                        let values: Vec<f64> = cells
+                            .filter_map(|cell| match cell {
+                                ColumnValue::Int(value) => Some(*value as f64),
+                                ColumnValue::Float(value) => Some(*value),
+                                _ => None,
+                            })
+                            .collect();
+                        column
+                            .typed::<DoubleType>()
+                            .write_batch(&values, Some(&levels), None)?;
+                    }
+                    ColumnKind::Text => {
+                        let values: Vec<ByteArray> = cells
+                            .filter_map(|cell| match cell {
+                                ColumnValue::Null => None,
+                                ColumnValue::Bool(value) => Some(value.to_string()),
+                                ColumnValue::Int(value) => Some(value.to_string()),
+                                ColumnValue::Float(value) => Some(value.to_string()),
+                                ColumnValue::Text(value) => Some(value.clone()),
+                            })
+                            .map(|text| ByteArray::from(text.into_bytes()))
+                            .collect();
+                        column.typed::<ByteArrayType>().write_batch(
+                            &values,
+                            Some(&levels),
+                            None,
+                        )?;
+                    }
+                }
+                column.close()?;
+            }
+            row_group.close()?;
+        }
+
+        writer.close()?;
+        Ok(())
+    }
+}
+
+/// Cell of a property value: lists and maps as JSON text, temporal values as ISO 8601
+pub fn bolt_value(value: &BoltType) -> ColumnValue {
+    match value {
+        BoltType::Null(_) => ColumnValue::Null,
+        BoltType::Boolean(value) => ColumnValue::Bool(value.value),
+        BoltType::Integer(value) => ColumnValue::Int(value.value),
+        BoltType::Float(value) => ColumnValue::Float(value.value),
+        BoltType::String(value) => ColumnValue::Text(value.value.clone()),
+        BoltType::List(_) | BoltType::Map(_) => ColumnValue::Text(bolt_json(value).to_string()),
+        other => ColumnValue::Text(bolt_text(other)),
+    }
+}
+
+fn bolt_json(value: &BoltType) -> serde_json::Value {
+    match value {
+        BoltType::Null(_) => serde_json::Value::Null,
+        BoltType::Boolean(value) => json!(value.value),
+        BoltType::Integer(value) => json!(value.value),
+        BoltType::Float(value) => json!(value.value),
+        BoltType::String(value) => json!(value.value),
+        BoltType::List(list) => list.value.iter().map(bolt_json).collect(),
+        BoltType::Map(map) => map
+            .value
+            .iter()
+            .map(|(key, value)| (key.value.clone(), bolt_json(value)))
+            .collect::<serde_json::Map<_, _>>()
+            .into(),
+        other => json!(bolt_text(other)),
+    }
+}
+
+fn bolt_text(value: &BoltType) -> String {
+    let converted = match value {
+        BoltType::DateTime(value) => {
+            chrono::DateTime::<chrono::FixedOffset>::try_from(value).map(|t| t.to_rfc3339())
+        }
+        BoltType::LocalDateTime(value) => {
+            chrono::NaiveDateTime::try_from(value).map(|t| t.to_string())
+        }
+        BoltType::Date(value) => chrono::NaiveDate::try_from(value).map(|t| t.to_string()),
+        _ => return format!("{:?}", value),
+    };
+    converted.unwrap_or_else(|_| format!("{:?}", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_columnar_table() {
+        let record = |pairs: &[(&str, ColumnValue)]| -> BTreeMap<String, ColumnValue> {
+            pairs
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.clone()))
+                .collect()
+        };
+        let table = ColumnarTable::from_records(
+            &["_id"],
+            vec![
+                record(&[
+                    ("_id", ColumnValue::Text("1".into())),
+                    ("size", ColumnValue::Int(16)),
+                    ("name", ColumnValue::Text("main".into())),
+                ]),
+                record(&[
+                    ("_id", ColumnValue::Text("2".into())),
+                    ("size", ColumnValue::Float(2.5)),
+                    ("is_library", ColumnValue::Bool(true)),
+                ]),
+            ],
+        );
+        assert_eq!(table.columns, vec!["_id", "is_library", "name", "size"]);
+        assert_eq!(table.rows[0][1], ColumnValue::Null);
+        assert_eq!(
+            table.kinds(),
+            vec![
+                ColumnKind::Text,
+                ColumnKind::Bool,
+                ColumnKind::Text,
+                ColumnKind::Float
+            ]
+        );
+
+        let mut file = Vec::new();
+        table.write(&mut file).unwrap();
+        assert_eq!(&file[..4], b"PAR1");
+    }
+}
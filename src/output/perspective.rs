@@ -0,0 +1,456 @@
+//! Ready-made visual exploration setups for the BinaryX schema: a Neo4j Bloom
+//! perspective and a Neo4j Browser guide
+//!
+//! Both carry the same node styles and search phrases, so analysts who do not use the
+//! CLI start from the labels, captions and pivots the CLI queries use.
+
+use serde_json::{json, Value};
+use std::fmt::Write;
+
+/// Bloom perspective format the generated file follows
+const BLOOM_VERSION: &str = "2.12.0";
+
+/// Label styled in the perspective
+struct NodeStyle {
+    label: &'static str,
+    color: &'static str,
+    /// Property shown as the node caption
+    caption: &'static str,
+    /// Further properties listed when a node is inspected
+    properties: &'static [&'static str],
+    size: f64,
+}
+
+const NODE_STYLES: &[NodeStyle] = &[
+    NodeStyle {
+        label: "Binary",
+        color: "#D9534F",
+        caption: "filename",
+        properties: &[
+            "hash",
+            "format",
+            "arch",
+            "file_size",
+            "compiler",
+            "packer",
+            "md5",
+            "sha1",
+            "imphash",
+            "risk_score",
+            "family",
+            "first_imported",
+        ],
+        size: 2.0,
+    },
+    NodeStyle {
+        label: "Function",
+        color: "#4C8EDA",
+        caption: "name",
+        properties: &[
+            "uid",
+            "address",
+            "type",
+            "size",
+            "demangled_name",
+            "analyst_name",
+            "is_library",
+            "in_degree",
+            "out_degree",
+        ],
+        size: 1.0,
+    },
+    NodeStyle {
+        label: "String",
+        color: "#8DCC93",
+        caption: "value",
+        properties: &["uid", "length", "truncated"],
+        size: 0.8,
+    },
+    NodeStyle {
+        label: "Library",
+        color: "#F79767",
+        caption: "name",
+        properties: &[],
+        size: 1.5,
+    },
+    NodeStyle {
+        label: "Resource",
+        color: "#C990C0",
+        caption: "sha256",
+        properties: &["uid", "type", "size"],
+        size: 1.0,
+    },
+    NodeStyle {
+        label: "Domain",
+        color: "#FFC454",
+        caption: "value",
+        properties: &[],
+        size: 1.0,
+    },
+    NodeStyle {
+        label: "IPAddress",
+        color: "#FFC454",
+        caption: "value",
+        properties: &[],
+        size: 1.0,
+    },
+    NodeStyle {
+        label: "URL",
+        color: "#FFC454",
+        caption: "value",
+        properties: &[],
+        size: 1.0,
+    },
+    NodeStyle {
+        label: "NamedObject",
+        color: "#57C7E3",
+        caption: "name",
+        properties: &["uid", "type"],
+        size: 1.0,
+    },
+];
+
+/// Relationship types with the color of their edges
+const RELATIONSHIP_STYLES: &[(&str, &str)] = &[
+    ("CONTAINS", "#A5ABB6"),
+    ("CALLS", "#4C8EDA"),
+    ("IMPORTS", "#F79767"),
+    ("IMPORTS_LIBRARY", "#F79767"),
+    ("BELONGS_TO", "#F79767"),
+    ("CONTAINS_STRING", "#8DCC93"),
+    ("REFERENCES", "#8DCC93"),
+    ("HAS_RESOURCE", "#C990C0"),
+    ("EMBEDS", "#D9534F"),
+    ("DROPS", "#D9534F"),
+    ("DOWNLOADS", "#D9534F"),
+    ("CALLED_AT_RUNTIME", "#F79767"),
+    ("CONTACTS", "#FFC454"),
+    ("REFERENCES_IOC", "#FFC454"),
+    ("RESOLVES_TO", "#FFC454"),
+    ("CREATES_OBJECT", "#57C7E3"),
+];
+
+/// Parameter of a search phrase, with the property Bloom suggests values from
+struct PhraseParam {
+    name: &'static str,
+    label: &'static str,
+    property: &'static str,
+}
+
+/// Saved search phrase; `phrase` names its parameters as `$name`
+struct SearchPhrase {
+    phrase: &'static str,
+    description: &'static str,
+    cypher: &'static str,
+    params: &'static [PhraseParam],
+}
+
+const BINARY_PARAM: PhraseParam = PhraseParam {
+    name: "binary",
+    label: "Binary",
+    property: "filename",
+};
+
+const SEARCH_PHRASES: &[SearchPhrase] = &[
+    SearchPhrase {
+        phrase: "Paths to API $api in $binary",
+        description: "Call chains inside a binary that end in an imported API",
+        cypher: "MATCH (b:Binary)
+WHERE b.filename = $binary OR b.hash = $binary
+MATCH (b)-[:CONTAINS]->(f:Function)
+MATCH p = (f)-[:CALLS*1..6]->(api:Function {type: 'Import'})
+WHERE toLower(api.name) = toLower($api)
+RETURN p LIMIT 100",
+        params: &[
+            PhraseParam {
+                name: "api",
+                label: "Function",
+                property: "name",
+            },
+            BINARY_PARAM,
+        ],
+    },
+    SearchPhrase {
+        phrase: "Binaries importing $api",
+        description: "Every sample whose import table names the API",
+        cypher: "MATCH (b:Binary)-[r:IMPORTS]->(api:Function)
+WHERE toLower(api.name) = toLower($api)
+RETURN b, r, api",
+        params: &[PhraseParam {
+            name: "api",
+            label: "Function",
+            property: "name",
+        }],
+    },
+    SearchPhrase {
+        phrase: "Binaries containing string $value",
+        description: "Samples sharing a string, matched as a substring",
+        cypher: "MATCH (b:Binary)-[r:CONTAINS_STRING]->(s:String)
+WHERE s.value CONTAINS $value
+RETURN b, r, s LIMIT 200",
+        params: &[PhraseParam {
+            name: "value",
+            label: "String",
+            property: "value",
+        }],
+    },
+    SearchPhrase {
+        phrase: "Call graph of $binary",
+        description: "Internal functions of a binary and the calls between them",
+        cypher: "MATCH (b:Binary)
+WHERE b.filename = $binary OR b.hash = $binary
+MATCH (b)-[:CONTAINS]->(f:Function)-[c:CALLS]->(g:Function)
+RETURN f, c, g LIMIT 1000",
+        params: &[BINARY_PARAM],
+    },
+    SearchPhrase {
+        phrase: "Samples sharing imports with $binary",
+        description: "Other binaries importing the same APIs, through the shared import nodes",
+        cypher: "MATCH (b:Binary)
+WHERE b.filename = $binary OR b.hash = $binary
+MATCH p = (b)-[:IMPORTS]->(:Function)<-[:IMPORTS]-(other:Binary)
+WHERE other <> b
+RETURN p LIMIT 300",
+        params: &[BINARY_PARAM],
+    },
+    SearchPhrase {
+        phrase: "Infrastructure of $binary",
+        description: "Domains, IPs, URLs and named objects linked to a binary",
+        cypher: "MATCH (b:Binary)
+WHERE b.filename = $binary OR b.hash = $binary
+MATCH p = (b)-[:CONTACTS|REFERENCES_IOC|CREATES_OBJECT]->()
+OPTIONAL MATCH q = (b)-[:CONTACTS]->(:Domain)-[:RESOLVES_TO]->(:IPAddress)
+RETURN p, q",
+        params: &[BINARY_PARAM],
+    },
+    SearchPhrase {
+        phrase: "Payloads of $binary",
+        description: "Samples embedded, dropped or downloaded by a binary, transitively",
+        cypher: "MATCH (b:Binary)
+WHERE b.filename = $binary OR b.hash = $binary
+MATCH p = (b)-[:EMBEDS|DROPS|DOWNLOADS*1..4]->(:Binary)
+RETURN p",
+        params: &[BINARY_PARAM],
+    },
+];
+
+/// Bloom perspective with categories per label, relationship colors and the search
+/// phrases, importable through Bloom's perspective gallery
+pub fn bloom_perspective() -> Value {
+    let categories: Vec<Value> = NODE_STYLES
+        .iter()
+        .enumerate()
+        .map(|(index, style)| {
+            let mut properties = vec![json!({
+                "name": style.caption,
+                "exclude": false,
+                "isCaption": true,
+                "dataType": "string",
+            })];
+            properties.extend(style.properties.iter().map(|property| {
+                json!({
+                    "name": property,
+                    "exclude": false,
+                    "isCaption": false,
+                    "dataType": "string",
+                })
+            }));
+            json!({
+                "id": index + 1,
+                "name": style.label,
+                "labels": [style.label],
+                "properties": properties,
+                "color": style.color,
+                "size": style.size,
+                "caption": [""],
+                "textSize": 1,
+                "textAlign": "top",
+                "styleRules": [],
+            })
+        })
+        .collect();
+
+    let relationship_types: Vec<Value> = RELATIONSHIP_STYLES
+        .iter()
+        .map(|(name, color)| {
+            json!({
+                "id": name,
+                "name": name,
+                "color": color,
+                "size": 1,
+                "properties": [],
+            })
+        })
+        .collect();
+
+    let templates: Vec<Value> = SEARCH_PHRASES
+        .iter()
+        .enumerate()
+        .map(|(index, phrase)| {
+            let params: Vec<Value> = phrase
+                .params
+                .iter()
+                .map(|param| {
+                    json!({
+                        "name": format!("${}", param.name),
+                        "dataType": "String",
+                        "suggestionLabel": param.label,
+                        "suggestionProp": param.property,
+                        "cypher": null,
+                    })
+                })
+                .collect();
+            json!({
+                "id": format!("binaryx-{}", index + 1),
+                "name": phrase.phrase,
+                "text": phrase.phrase,
+                "description": phrase.description,
+                "cypher": phrase.cypher,
+                "params": params,
+                "hasCypherErrors": false,
+            })
+        })
+        .collect();
+
+    json!({
+        "name": "BinaryX",
+        "id": "binaryx",
+        "version": BLOOM_VERSION,
+        "categories": categories,
+        "categoryIndex": NODE_STYLES.len(),
+        "relationshipTypes": relationship_types,
+        "templates": templates,
+        "hiddenRelationshipTypes": [],
+        "hiddenCategories": [],
+        "hideUncategorisedData": false,
+    })
+}
+
+/// Neo4j Browser guide (open with `:play <url>`): the schema, a `:style` command
+/// applying the node colors and one slide per search phrase with runnable Cypher
+pub fn browser_guide() -> anyhow::Result<String> {
+    let mut html = String::new();
+
+    writeln!(
+        html,
+        "<article class=\"guide\">\n<carousel class=\"deck container-fluid\">"
+    )?;
+
+    writeln!(
+        html,
+        "<slide class=\"row-fluid\">\n<div class=\"col-sm-12\">\n<h3>BinaryX graph</h3>"
+    )?;
+    writeln!(
+        html,
+        "<p>Binaries <code>CONTAINS</code> their functions, <code>IMPORTS</code> APIs \
+         (shared across samples), <code>CONTAINS_STRING</code> strings and \
+         <code>IMPORTS_LIBRARY</code> libraries. Functions <code>CALLS</code> each other.</p>"
+    )?;
+    writeln!(html, "<p>Apply the BinaryX colors and captions:</p>")?;
+    writeln!(
+        html,
+        "<pre class=\"code runnable\">:style\n{}</pre>",
+        escape(&grass_style())
+    )?;
+    writeln!(html, "</div>\n</slide>")?;
+
+    for phrase in SEARCH_PHRASES {
+        writeln!(
+            html,
+            "<slide class=\"row-fluid\">\n<div class=\"col-sm-12\">\n<h3>{}</h3>\n<p>{}</p>",
+            escape(phrase.phrase),
+            escape(phrase.description)
+        )?;
+        let params: Vec<String> = phrase
+            .params
+            .iter()
+            .map(|param| format!("{}: ''", param.name))
+            .collect();
+        writeln!(
+            html,
+            "<p>Set the parameters:</p>\n<pre class=\"code runnable\">:params {{{}}}</pre>",
+            params.join(", ")
+        )?;
+        writeln!(
+            html,
+            "<pre class=\"code runnable\">{}</pre>\n</div>\n</slide>",
+            escape(phrase.cypher)
+        )?;
+    }
+
+    writeln!(html, "</carousel>\n</article>")?;
+    Ok(html)
+}
+
+/// Neo4j Browser GraSS stylesheet with the node colors and captions
+fn grass_style() -> String {
+    let mut style = String::new();
+    for node in NODE_STYLES {
+        style.push_str(&format!(
+            "node.{} {{\n  color: {};\n  caption: \"{{{}}}\";\n}}\n",
+            node.label, node.color, node.caption
+        ));
+    }
+    style
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_phrase_params() {
+        // Every $name in a phrase is declared and used by its Cypher
+        for phrase in SEARCH_PHRASES {
+            for param in phrase.params {
+                let placeholder = format!("${}", param.name);
+                assert!(phrase.phrase.contains(&placeholder), "{}", phrase.phrase);
+                assert!(phrase.cypher.contains(&placeholder), "{}", phrase.phrase);
+            }
+        }
+
+        let perspective = bloom_perspective();
+        assert_eq!(
+            perspective["categories"].as_array().unwrap().len(),
+            NODE_STYLES.len()
+        );
+        assert_eq!(perspective["categories"][0]["labels"][0], "Binary");
+        assert_eq!(perspective["templates"][0]["params"][0]["name"], "$api");
+
+        let guide = browser_guide().unwrap();
+        assert!(guide.contains("node.Binary {"));
+        assert!(guide.contains("CALLS*1..6]-&gt;"));
+    }
+
+    #[test]
+    fn test_search_phrase_function_types() {
+        use crate::models::FunctionType;
+
+        // Function types are stored under their variant names, as import_functions_batch writes them
+        let stored: Vec<String> = [
+            FunctionType::Internal,
+            FunctionType::Import,
+            FunctionType::Export,
+            FunctionType::Thunk,
+        ]
+        .iter()
+        .map(|function_type| format!("{:?}", function_type))
+        .collect();
+
+        let mut filters = 0;
+        for phrase in SEARCH_PHRASES {
+            for rest in phrase.cypher.split("type: '").skip(1) {
+                let value = rest.split('\'').next().unwrap();
+                assert!(stored.iter().any(|t| t == value), "{}", phrase.phrase);
+                filters += 1;
+            }
+        }
+        assert!(filters > 0);
+    }
+}
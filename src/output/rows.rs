@@ -0,0 +1,1096 @@
+use serde::Serialize;
+
+use super::{Color, Column, TableRow};
+use crate::models::{
+    Binary, CorpusShare, CryptoHit, Distribution, Function, FunctionMetrics, FunctionType,
+    IndicatorHit, LibraryApiUse, NeighborHit, ObjectHit, PseudocodeHit, Resource, RiskContribution,
+    SearchHit, SearchKind, Section, SharedApiHit, StringSearchHit, UnresolvedCall, HIGH_ENTROPY,
+};
+use crate::neo4j::{CallEdge, DataXref, HotCallee, Xref};
+
+impl TableRow for Function {
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new("Name"),
+            Column::new("Type"),
+            Column::new("Address"),
+            Column::new("UID"),
+            Column::new("Comment").max_width(40),
+        ]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.display_name(),
+            match self.entry_kind {
+                Some(kind) => format!("{:?} ({})", self.r#type, kind.as_str()),
+                None => format!("{:?}", self.r#type),
+            },
+            self.address.clone().unwrap_or_else(|| "N/A".to_string()),
+            self.uid.clone(),
+            self.comment.clone().unwrap_or_default(),
+        ]
+    }
+
+    fn cell_color(&self, column: usize) -> Option<Color> {
+        if column != 1 {
+            return None;
+        }
+        function_type_color(&self.r#type)
+    }
+}
+
+/// Function listed by `query functions --verbose`, with its prototype
+#[derive(Debug, Clone, Serialize)]
+#[serde(transparent)]
+pub struct FunctionDetail(pub Function);
+
+impl TableRow for FunctionDetail {
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new("Prototype").max_width(80),
+            Column::new("Type"),
+            Column::new("Address"),
+            Column::new("Size"),
+            Column::new("Library"),
+            Column::new("UID"),
+            Column::new("Comment").max_width(40),
+        ]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        let function = &self.0;
+        let name = function.display_name();
+        let mut cells = function.cells();
+        vec![
+            function
+                .prototype
+                .as_ref()
+                .map_or_else(|| name.clone(), |prototype| prototype.declaration(&name)),
+            cells.remove(1),
+            function
+                .address
+                .clone()
+                .unwrap_or_else(|| "N/A".to_string()),
+            function.size.map(|s| s.to_string()).unwrap_or_default(),
+            match (&function.signature_match, function.is_library) {
+                (Some(signature), _) => signature.clone(),
+                (None, true) => "yes".to_string(),
+                (None, false) => String::new(),
+            },
+            function.uid.clone(),
+            function.comment.clone().unwrap_or_default(),
+        ]
+    }
+
+    fn cell_color(&self, column: usize) -> Option<Color> {
+        self.0.cell_color(column)
+    }
+}
+
+/// Highlight color used for a function type in tables
+pub fn function_type_color(function_type: &FunctionType) -> Option<Color> {
+    match function_type {
+        FunctionType::Internal => None,
+        FunctionType::Import => Some(Color::Cyan),
+        FunctionType::Export => Some(Color::Green),
+        FunctionType::Thunk => Some(Color::Yellow),
+    }
+}
+
+impl TableRow for FunctionMetrics {
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new("Name"),
+            Column::new("Address"),
+            Column::new("In"),
+            Column::new("Out"),
+            Column::new("Entry Depth"),
+            Column::new("APIs"),
+            Column::new("UID"),
+        ]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.name.clone(),
+            self.address.clone().unwrap_or_else(|| "N/A".to_string()),
+            self.in_degree.to_string(),
+            self.out_degree.to_string(),
+            self.entry_depth
+                .map(|d| d.to_string())
+                .unwrap_or_else(|| "-".to_string()),
+            self.reachable_apis.to_string(),
+            self.uid.clone(),
+        ]
+    }
+}
+
+impl TableRow for StringSearchHit {
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new("Score"),
+            Column::new("Samples"),
+            Column::new("Value").max_width(60),
+        ]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            format!("{:.4}", self.score),
+            self.sample_count.to_string(),
+            self.value.clone(),
+        ]
+    }
+
+    fn table_cells(&self) -> Vec<String> {
+        vec![
+            format!("{:.4}", self.score),
+            self.sample_count.to_string(),
+            self.value.replace('\n', "\\n").replace('\r', "\\r"),
+        ]
+    }
+}
+
+impl TableRow for PseudocodeHit {
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new("Score"),
+            Column::new("Function"),
+            Column::new("Address"),
+            Column::new("Binary"),
+            Column::new("Line"),
+            Column::new("Snippet").max_width(60),
+        ]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            format!("{:.4}", self.score),
+            self.name.clone(),
+            self.address.clone().unwrap_or_else(|| "N/A".to_string()),
+            self.binary.clone(),
+            self.line.map(|line| line.to_string()).unwrap_or_default(),
+            self.snippet.clone(),
+        ]
+    }
+}
+
+impl TableRow for Binary {
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new("Filename"),
+            Column::new("Format"),
+            Column::new("Arch"),
+            Column::new("Size"),
+            Column::new("Compiler"),
+            Column::new("Packer"),
+            Column::new("Entropy"),
+            Column::new("First Seen"),
+            Column::new("Hash"),
+        ]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.filename.clone(),
+            format!("{:?}", self.format),
+            self.arch.as_str().to_string(),
+            self.file_size.to_string(),
+            self.compiler.clone().unwrap_or_default(),
+            self.packer.clone().unwrap_or_default(),
+            self.max_entropy()
+                .map(|entropy| format!("{:.2}", entropy))
+                .unwrap_or_default(),
+            self.first_imported
+                .as_deref()
+                .map(|date| date.chars().take(10).collect())
+                .unwrap_or_default(),
+            self.hash.clone(),
+        ]
+    }
+
+    fn cell_color(&self, column: usize) -> Option<Color> {
+        (column == 6 && self.likely_packed).then_some(Color::Yellow)
+    }
+}
+
+impl TableRow for Section {
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new("Name"),
+            Column::new("Size"),
+            Column::new("Entropy"),
+        ]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.name.clone(),
+            self.size.map(|size| size.to_string()).unwrap_or_default(),
+            format!("{:.2}", self.entropy),
+        ]
+    }
+
+    fn cell_color(&self, column: usize) -> Option<Color> {
+        (column == 2 && self.entropy >= HIGH_ENTROPY).then_some(Color::Yellow)
+    }
+}
+
+impl TableRow for IndicatorHit {
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new("Kind"),
+            Column::new("Value").max_width(60),
+            Column::new("Relation"),
+            Column::new("Binary"),
+            Column::new("Hash"),
+        ]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.kind.clone(),
+            self.value.clone(),
+            self.relation.clone(),
+            self.binary.clone(),
+            self.hash.clone(),
+        ]
+    }
+
+    fn cell_color(&self, column: usize) -> Option<Color> {
+        (column == 2 && self.relation == "CONTACTS").then_some(Color::Yellow)
+    }
+}
+
+impl TableRow for SearchHit {
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new("Kind"),
+            Column::new("Match").max_width(60),
+            Column::new("Samples"),
+            Column::new("Detail"),
+            Column::new("Key"),
+        ]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.kind.as_str().to_string(),
+            self.name.clone(),
+            self.samples.to_string(),
+            self.detail.clone(),
+            self.key.clone(),
+        ]
+    }
+
+    fn table_cells(&self) -> Vec<String> {
+        let mut cells = self.cells();
+        cells[1] = self.name.replace('\n', "\\n").replace('\r', "\\r");
+        cells
+    }
+
+    fn cell_color(&self, column: usize) -> Option<Color> {
+        if column != 0 {
+            return None;
+        }
+        match self.kind {
+            SearchKind::Binary => Some(Color::Cyan),
+            SearchKind::Function => Some(Color::Green),
+            SearchKind::String => Some(Color::Yellow),
+            SearchKind::Library => Some(Color::Magenta),
+        }
+    }
+}
+
+impl TableRow for CryptoHit {
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new("Algorithms"),
+            Column::new("Function"),
+            Column::new("Address"),
+            Column::new("Binary"),
+            Column::new("UID"),
+        ]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.algorithms.join(", "),
+            self.name.clone(),
+            self.address.clone(),
+            self.binary.clone(),
+            self.uid.clone(),
+        ]
+    }
+}
+
+impl TableRow for ObjectHit {
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new("Kind"),
+            Column::new("Name").max_width(60),
+            Column::new("Source"),
+            Column::new("Samples"),
+            Column::new("Binary"),
+            Column::new("Hash"),
+        ]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.kind.clone(),
+            self.name.clone(),
+            self.source.clone(),
+            self.samples.to_string(),
+            self.binary.clone(),
+            self.hash.clone(),
+        ]
+    }
+
+    fn cell_color(&self, column: usize) -> Option<Color> {
+        (column == 2 && self.source == "sandbox").then_some(Color::Yellow)
+    }
+}
+
+impl TableRow for RiskContribution {
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new("Category"),
+            Column::new("API"),
+            Column::new("Weight"),
+            Column::new("Call Sites").max_width(60),
+        ]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.category.clone(),
+            self.api.clone(),
+            format!("{:.1}", self.weight),
+            if self.call_sites.is_empty() {
+                "-".to_string()
+            } else {
+                self.call_sites.join(", ")
+            },
+        ]
+    }
+
+    fn cell_color(&self, column: usize) -> Option<Color> {
+        (column == 2 && self.weight >= 4.0).then_some(Color::Magenta)
+    }
+}
+
+impl TableRow for SharedApiHit {
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new("Binary"),
+            Column::new("Hash"),
+            Column::new("Shared"),
+            Column::new("Coverage"),
+            Column::new("APIs").max_width(60),
+        ]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.binary.clone(),
+            self.hash.clone(),
+            self.shared.to_string(),
+            format!("{:.0}%", self.coverage * 100.0),
+            self.apis.join(", "),
+        ]
+    }
+}
+
+impl TableRow for NeighborHit {
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new("Binary"),
+            Column::new("Hash"),
+            Column::new("Similarity"),
+            Column::new("Shared"),
+            Column::new("Features"),
+        ]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.binary.clone(),
+            self.hash.clone(),
+            format!("{:.3}", self.similarity),
+            self.shared.to_string(),
+            self.features.to_string(),
+        ]
+    }
+}
+
+impl TableRow for Distribution {
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new("Metric"),
+            Column::new("Min"),
+            Column::new("P25"),
+            Column::new("Median"),
+            Column::new("P75"),
+            Column::new("P90"),
+            Column::new("Max"),
+            Column::new("Mean"),
+            Column::new("Histogram").max_width(60),
+        ]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.metric.clone(),
+            self.min.to_string(),
+            self.p25.to_string(),
+            self.median.to_string(),
+            self.p75.to_string(),
+            self.p90.to_string(),
+            self.max.to_string(),
+            format!("{:.1}", self.mean),
+            self.histogram
+                .iter()
+                .map(|bucket| format!("{}: {}", bucket.range, bucket.binaries))
+                .collect::<Vec<_>>()
+                .join(", "),
+        ]
+    }
+}
+
+impl TableRow for CorpusShare {
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new("Name"),
+            Column::new("Binaries"),
+            Column::new("Share"),
+        ]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.name.clone(),
+            self.binaries.to_string(),
+            format!("{:.0}%", self.share * 100.0),
+        ]
+    }
+}
+
+impl TableRow for LibraryApiUse {
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new("API"),
+            Column::new("Binaries"),
+            Column::new("Callers"),
+            Column::new("Example Callers").max_width(80),
+        ]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.api.clone(),
+            self.binaries.to_string(),
+            self.callers.to_string(),
+            self.examples.join(", "),
+        ]
+    }
+}
+
+impl TableRow for Resource {
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new("Type"),
+            Column::new("Name"),
+            Column::new("Language"),
+            Column::new("Size"),
+            Column::new("File Type").max_width(40),
+            Column::new("SHA-256"),
+        ]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.r#type.clone(),
+            self.name.clone().unwrap_or_default(),
+            self.language.clone().unwrap_or_default(),
+            self.size.to_string(),
+            self.file_type.clone().unwrap_or_default(),
+            self.sha256.clone(),
+        ]
+    }
+
+    fn cell_color(&self, column: usize) -> Option<Color> {
+        (column == 4 && self.is_embedded_pe()).then_some(Color::Yellow)
+    }
+}
+
+impl TableRow for Xref {
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new("From Function"),
+            Column::new("To Function"),
+            Column::new("Offset"),
+            Column::new("Calls"),
+            Column::new("Call Type"),
+            Column::new("Binary"),
+        ]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.from_function.clone(),
+            self.to_function.clone(),
+            self.offset.clone(),
+            self.count.to_string(),
+            self.call_type.clone().unwrap_or_default(),
+            self.binary.clone(),
+        ]
+    }
+}
+
+impl TableRow for HotCallee {
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new("Callee"),
+            Column::new("Type"),
+            Column::new("Calls"),
+            Column::new("Share"),
+            Column::new("Call Sites").max_width(40),
+            Column::new("Caller"),
+            Column::new("Binary"),
+        ]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.callee.clone(),
+            self.callee_type.clone(),
+            self.count.to_string(),
+            format!("{:.1}%", self.share),
+            self.offsets.join(", "),
+            self.caller.clone(),
+            self.binary.clone(),
+        ]
+    }
+}
+
+impl TableRow for UnresolvedCall {
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new("Address"),
+            Column::new("Role"),
+            Column::new("Calls"),
+            Column::new("Call Sites").max_width(40),
+            Column::new("Binary"),
+        ]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.address.clone(),
+            self.role.clone(),
+            self.count.to_string(),
+            self.call_sites.join(", "),
+            self.binary.clone(),
+        ]
+    }
+}
+
+impl TableRow for DataXref {
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new("Function"),
+            Column::new("Ref Type"),
+            Column::new("Offset"),
+            Column::new("Kind"),
+            Column::new("Target").max_width(60),
+            Column::new("Binary"),
+        ]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.function.clone(),
+            self.ref_type.clone(),
+            self.offset.clone(),
+            self.kind.clone(),
+            self.target.clone(),
+            self.binary.clone(),
+        ]
+    }
+}
+
+/// Fuzzy-hash match of a `query similar` search
+#[derive(Debug, Clone, Serialize)]
+pub struct SimilarBinary {
+    pub filename: String,
+    pub hash: String,
+    pub ssdeep_score: Option<u32>,
+    pub tlsh_distance: Option<u32>,
+}
+
+impl TableRow for SimilarBinary {
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new("Filename"),
+            Column::new("ssdeep"),
+            Column::new("TLSH"),
+            Column::new("Hash"),
+        ]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.filename.clone(),
+            self.ssdeep_score.map(|s| s.to_string()).unwrap_or_default(),
+            self.tlsh_distance
+                .map(|d| d.to_string())
+                .unwrap_or_default(),
+            self.hash.clone(),
+        ]
+    }
+}
+
+/// Binary ranked by embedding similarity in `query similar-by-embedding`
+#[derive(Debug, Clone, Serialize)]
+pub struct EmbeddingMatch {
+    pub binary: String,
+    pub hash: String,
+    pub similarity: f64,
+}
+
+impl TableRow for EmbeddingMatch {
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new("Binary"),
+            Column::new("Similarity"),
+            Column::new("Hash"),
+        ]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.binary.clone(),
+            format!("{:.3}", self.similarity),
+            self.hash.clone(),
+        ]
+    }
+}
+
+/// Function ranked by mnemonic histogram similarity to a reference function
+#[derive(Debug, Clone, Serialize)]
+pub struct OpcodeMatch {
+    pub name: String,
+    pub address: Option<String>,
+    pub binary: String,
+    pub similarity: f64,
+    pub uid: String,
+}
+
+impl TableRow for OpcodeMatch {
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new("Similarity"),
+            Column::new("Name"),
+            Column::new("Address"),
+            Column::new("Binary"),
+            Column::new("UID"),
+        ]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            format!("{:.4}", self.similarity),
+            self.name.clone(),
+            self.address.clone().unwrap_or_else(|| "N/A".to_string()),
+            self.binary.clone(),
+            self.uid.clone(),
+        ]
+    }
+}
+
+/// Function containing an address, from `query at-address`
+#[derive(Debug, Clone, Serialize)]
+pub struct AddressLocation {
+    pub address: String,
+    pub name: String,
+    /// Function start address
+    pub start: String,
+    /// Start of the function chunk holding the address, which `offset` is relative to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chunk: Option<String>,
+    pub offset: String,
+    pub size: Option<u64>,
+    /// False when the function has no size and was picked as the closest start below
+    pub bounded: bool,
+    pub uid: String,
+}
+
+impl TableRow for AddressLocation {
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new("Address"),
+            Column::new("Function"),
+            Column::new("Start"),
+            Column::new("Offset"),
+            Column::new("Size"),
+            Column::new("UID"),
+        ]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.address.clone(),
+            self.name.clone(),
+            self.start.clone(),
+            match &self.chunk {
+                Some(chunk) => format!("{} in chunk {}", self.offset, chunk),
+                None => self.offset.clone(),
+            },
+            self.size
+                .map(|size| format!("0x{:x}", size))
+                .unwrap_or_else(|| "unknown".to_string()),
+            self.uid.clone(),
+        ]
+    }
+
+    fn cell_color(&self, column: usize) -> Option<Color> {
+        (column == 4 && !self.bounded).then_some(Color::Yellow)
+    }
+}
+
+/// Flattened callee/caller entry of a call graph query
+#[derive(Debug, Clone, Serialize)]
+pub struct CallGraphEntry {
+    pub direction: &'static str,
+    pub uid: String,
+    pub name: String,
+    pub address: Option<String>,
+}
+
+impl TableRow for CallGraphEntry {
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new("Direction"),
+            Column::new("Name"),
+            Column::new("Address"),
+            Column::new("UID"),
+        ]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.direction.to_string(),
+            self.name.clone(),
+            self.address.clone().unwrap_or_else(|| "N/A".to_string()),
+            self.uid.clone(),
+        ]
+    }
+
+    fn cell_color(&self, column: usize) -> Option<Color> {
+        match (column, self.direction) {
+            (0, "callee") => Some(Color::Blue),
+            (0, "caller") => Some(Color::Magenta),
+            _ => None,
+        }
+    }
+}
+
+impl TableRow for CallEdge {
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new("Direction"),
+            Column::new("From"),
+            Column::new("To"),
+            Column::new("Offset"),
+            Column::new("Call Type"),
+            Column::new("Source"),
+            Column::new("Confidence"),
+            Column::new("Depth"),
+            Column::new("From UID"),
+            Column::new("To UID"),
+        ]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.direction.clone(),
+            self.from_name.clone(),
+            self.to_name.clone(),
+            self.offset.clone(),
+            self.call_type.clone(),
+            self.source.clone(),
+            format!("{:.2}", self.confidence),
+            self.depth.to_string(),
+            self.from_uid.clone(),
+            self.to_uid.clone(),
+        ]
+    }
+}
+
+/// One node of a call path or upward call chain, flattened for CSV output
+#[derive(Debug, Clone, Serialize)]
+pub struct CallPathEntry {
+    pub path_id: String,
+    pub direction: &'static str,
+    pub depth: usize,
+    pub name: String,
+    pub address: Option<String>,
+    pub call_site: Option<String>,
+}
+
+impl TableRow for CallPathEntry {
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new("Path"),
+            Column::new("Direction"),
+            Column::new("Depth"),
+            Column::new("Name"),
+            Column::new("Address"),
+            Column::new("Call Site"),
+        ]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.path_id.clone(),
+            self.direction.to_string(),
+            self.depth.to_string(),
+            self.name.clone(),
+            self.address.clone().unwrap_or_default(),
+            self.call_site.clone().unwrap_or_default(),
+        ]
+    }
+}
+
+/// Member function of a call cycle
+#[derive(Debug, Clone, Serialize)]
+pub struct CycleMemberEntry {
+    pub cycle_id: String,
+    pub size: usize,
+    pub name: String,
+    pub address: Option<String>,
+    pub uid: String,
+}
+
+impl TableRow for CycleMemberEntry {
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new("Cycle"),
+            Column::new("Size"),
+            Column::new("Name"),
+            Column::new("Address"),
+            Column::new("UID"),
+        ]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.cycle_id.clone(),
+            self.size.to_string(),
+            self.name.clone(),
+            self.address.clone().unwrap_or_else(|| "N/A".to_string()),
+            self.uid.clone(),
+        ]
+    }
+}
+
+/// Constraint or index reported by `database verify-schema`
+#[derive(Debug, Clone, Serialize)]
+pub struct SchemaEntry {
+    pub kind: String,
+    pub name: String,
+    /// `ok`, `missing`, `extra` or `unsupported`
+    pub status: String,
+}
+
+impl TableRow for SchemaEntry {
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new("Kind"),
+            Column::new("Name"),
+            Column::new("Status"),
+        ]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![self.kind.clone(), self.name.clone(), self.status.clone()]
+    }
+
+    fn cell_color(&self, column: usize) -> Option<Color> {
+        if column != 2 {
+            return None;
+        }
+        match self.status.as_str() {
+            "ok" => Some(Color::Green),
+            "missing" => Some(Color::Yellow),
+            _ => Some(Color::Cyan),
+        }
+    }
+}
+
+/// Outcome of one check of `database ping`
+#[derive(Debug, Clone, Serialize)]
+pub struct PingCheck {
+    pub check: String,
+    /// `ok` or `warn`
+    pub status: String,
+    pub detail: String,
+}
+
+impl PingCheck {
+    pub fn new(check: &str, status: &str, detail: impl Into<String>) -> Self {
+        Self {
+            check: check.to_string(),
+            status: status.to_string(),
+            detail: detail.into(),
+        }
+    }
+}
+
+impl TableRow for PingCheck {
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new("Check"),
+            Column::new("Status"),
+            Column::new("Detail").max_width(80),
+        ]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![self.check.clone(), self.status.clone(), self.detail.clone()]
+    }
+
+    fn cell_color(&self, column: usize) -> Option<Color> {
+        if column != 1 {
+            return None;
+        }
+        match self.status.as_str() {
+            "ok" => Some(Color::Green),
+            _ => Some(Color::Yellow),
+        }
+    }
+}
+
+/// Binary, function or relationship listed by `database diff`, or export or import
+/// listed by `analyze trade`
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapshotChange {
+    /// `added` or `removed`
+    pub change: String,
+    /// `binary`, `function`, `relationship`, `export` or `import`
+    pub kind: String,
+    pub item: String,
+}
+
+impl TableRow for SnapshotChange {
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new("Change"),
+            Column::new("Kind"),
+            Column::new("Item").max_width(100),
+        ]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![self.change.clone(), self.kind.clone(), self.item.clone()]
+    }
+
+    fn cell_color(&self, column: usize) -> Option<Color> {
+        if column != 0 {
+            return None;
+        }
+        match self.change.as_str() {
+            "added" => Some(Color::Green),
+            _ => Some(Color::Yellow),
+        }
+    }
+}
+
+/// Generic name/value pair used for statistics output
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricEntry {
+    pub metric: String,
+    pub value: String,
+}
+
+impl MetricEntry {
+    pub fn new(metric: impl Into<String>, value: impl ToString) -> Self {
+        Self {
+            metric: metric.into(),
+            value: value.to_string(),
+        }
+    }
+}
+
+impl TableRow for MetricEntry {
+    fn columns() -> Vec<Column> {
+        vec![Column::new("Metric"), Column::new("Value")]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![self.metric.clone(), self.value.clone()]
+    }
+}
+
+/// Query preset listed by `query preset --list`
+#[derive(Debug, Clone, Serialize)]
+pub struct PresetEntry {
+    pub name: String,
+    pub description: String,
+    /// Parameters as `key=default`
+    pub params: Vec<String>,
+}
+
+impl TableRow for PresetEntry {
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new("Name"),
+            Column::new("Description").max_width(60),
+            Column::new("Parameters"),
+        ]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.name.clone(),
+            self.description.clone(),
+            self.params.join(" "),
+        ]
+    }
+}
+
+/// Row of `query batch`, tagged with the input line that produced it
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchRow<R> {
+    pub target: String,
+    #[serde(flatten)]
+    pub row: R,
+}
+
+impl<R: TableRow> TableRow for BatchRow<R> {
+    fn columns() -> Vec<Column> {
+        let mut columns = vec![Column::new("Target")];
+        columns.extend(R::columns());
+        columns
+    }
+
+    fn cells(&self) -> Vec<String> {
+        let mut cells = vec![self.target.clone()];
+        cells.extend(self.row.cells());
+        cells
+    }
+
+    fn table_cells(&self) -> Vec<String> {
+        let mut cells = vec![self.target.clone()];
+        cells.extend(self.row.table_cells());
+        cells
+    }
+
+    fn cell_color(&self, column: usize) -> Option<Color> {
+        column
+            .checked_sub(1)
+            .and_then(|column| self.row.cell_color(column))
+    }
+}
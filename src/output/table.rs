@@ -0,0 +1,241 @@
+use std::borrow::Cow;
+
+/// ANSI terminal colors used to highlight table cells
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+}
+
+impl Color {
+    fn code(self) -> &'static str {
+        match self {
+            Color::Green => "32",
+            Color::Yellow => "33",
+            Color::Blue => "34",
+            Color::Magenta => "35",
+            Color::Cyan => "36",
+        }
+    }
+
+    pub fn paint(self, text: &str) -> String {
+        format!("\x1b[{}m{}\x1b[0m", self.code(), text)
+    }
+}
+
+/// A single table column: header text and optional maximum display width
+pub struct Column {
+    pub header: Cow<'static, str>,
+    pub max_width: Option<usize>,
+}
+
+impl Column {
+    pub const fn new(header: &'static str) -> Self {
+        Self {
+            header: Cow::Borrowed(header),
+            max_width: None,
+        }
+    }
+
+    /// Column whose header is only known at runtime
+    pub fn named(header: String) -> Self {
+        Self {
+            header: Cow::Owned(header),
+            max_width: None,
+        }
+    }
+
+    /// Truncate cells longer than `width` characters with a trailing "..."
+    pub const fn max_width(mut self, width: usize) -> Self {
+        self.max_width = Some(width);
+        self
+    }
+}
+
+/// A rendered cell value with an optional highlight color
+pub struct Cell {
+    pub text: String,
+    pub color: Option<Color>,
+}
+
+/// Lay out rows so every column is as wide as its widest cell
+pub fn render_table(columns: &[Column], rows: &[Vec<Cell>], color: bool) -> String {
+    let cells: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| {
+            columns
+                .iter()
+                .zip(row)
+                .map(|(column, cell)| fit(&cell.text, column.max_width))
+                .collect()
+        })
+        .collect();
+
+    let widths: Vec<usize> = columns
+        .iter()
+        .enumerate()
+        .map(|(i, column)| {
+            cells
+                .iter()
+                .filter_map(|row| row.get(i))
+                .map(|cell| cell.chars().count())
+                .chain(std::iter::once(column.header.chars().count()))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let mut out = String::new();
+
+    let header: Vec<String> = columns.iter().map(|c| c.header.to_string()).collect();
+    let header_line = join_padded(&header, &widths, |_, text| {
+        if color {
+            format!("\x1b[1m{}\x1b[0m", text)
+        } else {
+            text.to_string()
+        }
+    });
+    out.push_str(&header_line);
+    out.push('\n');
+
+    let total_width = widths.iter().sum::<usize>() + widths.len().saturating_sub(1) * 2;
+    out.push_str(&"-".repeat(total_width));
+    out.push('\n');
+
+    for (row, texts) in rows.iter().zip(&cells) {
+        let line = join_padded(texts, &widths, |i, text| {
+            match row.get(i).and_then(|cell| cell.color) {
+                Some(c) if color => c.paint(text),
+                _ => text.to_string(),
+            }
+        });
+        out.push_str(&line);
+        out.push('\n');
+    }
+
+    out
+}
+
+/// Pad each cell to its column width, styling the text but not the padding
+/// GitHub-flavored Markdown table; cells are never truncated
+pub fn render_markdown_table(columns: &[Column], rows: &[Vec<String>]) -> String {
+    let mut out = String::new();
+    let headers: Vec<String> = columns.iter().map(|c| c.header.to_string()).collect();
+    out.push_str(&markdown_row(&headers));
+    out.push_str(&markdown_row(&vec!["---".to_string(); columns.len()]));
+    for row in rows {
+        out.push_str(&markdown_row(row));
+    }
+    out
+}
+
+fn markdown_row(cells: &[String]) -> String {
+    let cells: Vec<String> = cells
+        .iter()
+        .map(|cell| {
+            cell.replace('\\', "\\\\")
+                .replace('|', "\\|")
+                .replace("\r\n", "<br>")
+                .replace('\n', "<br>")
+        })
+        .collect();
+    format!("| {} |\n", cells.join(" | "))
+}
+
+fn join_padded<F>(cells: &[String], widths: &[usize], style: F) -> String
+where
+    F: Fn(usize, &str) -> String,
+{
+    let last = cells.len().saturating_sub(1);
+    let mut line = String::new();
+
+    for (i, text) in cells.iter().enumerate() {
+        line.push_str(&style(i, text));
+        if i < last {
+            let padding = widths[i].saturating_sub(text.chars().count());
+            line.push_str(&" ".repeat(padding + 2));
+        }
+    }
+
+    line
+}
+
+fn fit(text: &str, max_width: Option<usize>) -> String {
+    match max_width {
+        Some(width) if text.chars().count() > width => {
+            let truncated: String = text.chars().take(width).collect();
+            format!("{}...", truncated)
+        }
+        _ => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plain(text: &str) -> Cell {
+        Cell {
+            text: text.to_string(),
+            color: None,
+        }
+    }
+
+    #[test]
+    fn test_columns_sized_to_widest_cell() {
+        let columns = [Column::new("Name"), Column::new("UID")];
+        let rows = vec![
+            vec![plain("a_very_long_function_name"), plain("abc:0x1")],
+            vec![plain("f"), plain("abc:0x401000")],
+        ];
+
+        let table = render_table(&columns, &rows, false);
+        let lines: Vec<&str> = table.lines().collect();
+
+        assert_eq!(lines[0], "Name                       UID");
+        assert_eq!(lines[2], "a_very_long_function_name  abc:0x1");
+        assert_eq!(lines[3], "f                          abc:0x401000");
+        assert_eq!(lines[1].len(), 25 + 2 + 12);
+    }
+
+    #[test]
+    fn test_max_width_truncates() {
+        let columns = [Column::new("Value").max_width(5)];
+        let rows = vec![vec![plain("abcdefghij")]];
+
+        let table = render_table(&columns, &rows, false);
+        assert_eq!(table.lines().nth(2), Some("abcde..."));
+    }
+
+    #[test]
+    fn test_color_does_not_affect_alignment() {
+        let columns = [Column::new("Type"), Column::new("Name")];
+        let rows = vec![vec![
+            Cell {
+                text: "Import".to_string(),
+                color: Some(Color::Cyan),
+            },
+            plain("CreateFileA"),
+        ]];
+
+        let table = render_table(&columns, &rows, true);
+        assert_eq!(
+            table.lines().nth(2),
+            Some("\x1b[36mImport\x1b[0m  CreateFileA")
+        );
+    }
+
+    #[test]
+    fn test_markdown_table_escapes_cells() {
+        let columns = [Column::new("Name").max_width(3), Column::new("Value")];
+        let rows = vec![vec!["a|b".to_string(), "line1\nline2".to_string()]];
+
+        assert_eq!(
+            render_markdown_table(&columns, &rows),
+            "| Name | Value |\n| --- | --- |\n| a\\|b | line1<br>line2 |\n"
+        );
+    }
+}
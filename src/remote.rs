@@ -0,0 +1,182 @@
+//! Read-only client of the `serve` REST API
+//!
+//! Requests go through a [`Transport`], so the same client runs natively or in a
+//! browser viewer that answers them with `fetch`. Results decode into the crate's
+//! models, and failed requests come back as the [`CliError`](crate::error::CliError)
+//! the server reported, so callers classify them as they do local errors.
+//!
+//! The module uses neither Bolt nor tokio: built with `--no-default-features --features
+//! remote`, the crate leaves out the importer and its database client and compiles
+//! for wasm32.
+
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use std::fmt::Write;
+use std::future::Future;
+
+use crate::error::{ErrorKind, ErrorReport};
+use crate::models::{Binary, CallGraphResponse, SimilarityResponse};
+
+/// Answer to a request: status code and body
+#[derive(Debug, Clone)]
+pub struct Response {
+    pub status: u16,
+    pub body: Vec<u8>,
+}
+
+/// HTTP GET against the server; futures need not be `Send`, as with `fetch`
+pub trait Transport {
+    /// `path` starts with `/` and includes the query string
+    fn get(&self, path: &str) -> impl Future<Output = Result<Response>>;
+}
+
+pub struct RemoteClient<T> {
+    transport: T,
+}
+
+impl<T: Transport> RemoteClient<T> {
+    pub fn new(transport: T) -> Self {
+        Self { transport }
+    }
+
+    /// Binaries by filename; the server default limit applies without `limit`
+    pub async fn binaries(&self, limit: Option<usize>) -> Result<Vec<Binary>> {
+        let mut path = "/api/binaries".to_string();
+        if let Some(limit) = limit {
+            write!(path, "?limit={}", limit)?;
+        }
+        self.get(&path).await
+    }
+
+    /// Binary by hash, else by filename
+    pub async fn binary(&self, binary: &str) -> Result<Binary> {
+        self.get(&format!("/api/binaries/{}", encode(binary))).await
+    }
+
+    pub async fn call_graph(
+        &self,
+        binary: &str,
+        max_edges: Option<usize>,
+    ) -> Result<CallGraphResponse> {
+        let mut path = format!("/api/binaries/{}/callgraph", encode(binary));
+        if let Some(max_edges) = max_edges {
+            write!(path, "?max_edges={}", max_edges)?;
+        }
+        self.get(&path).await
+    }
+
+    /// `k` nearest binaries in a feature space (`imports` by default)
+    pub async fn similar(
+        &self,
+        binary: &str,
+        k: Option<usize>,
+        space: Option<&str>,
+    ) -> Result<SimilarityResponse> {
+        let mut params = Vec::new();
+        if let Some(k) = k {
+            params.push(format!("k={}", k));
+        }
+        if let Some(space) = space {
+            params.push(format!("space={}", encode(space)));
+        }
+        let mut path = format!("/api/binaries/{}/similar", encode(binary));
+        if !params.is_empty() {
+            write!(path, "?{}", params.join("&"))?;
+        }
+        self.get(&path).await
+    }
+
+    async fn get<R: DeserializeOwned>(&self, path: &str) -> Result<R> {
+        let response = self.transport.get(path).await?;
+        if !(200..300).contains(&response.status) {
+            return Err(response_error(&response));
+        }
+        serde_json::from_slice(&response.body)
+            .map_err(|e| anyhow::anyhow!("Unexpected answer to {}: {}", path, e))
+    }
+}
+
+/// Error of a failed request, from its `ErrorReport` body when there is one
+fn response_error(response: &Response) -> anyhow::Error {
+    let Ok(report) = serde_json::from_slice::<ErrorReport>(&response.body) else {
+        return crate::error::connection(format!("Server answered {}", response.status));
+    };
+    let message = report
+        .message
+        .unwrap_or_else(|| format!("Server answered {}", response.status));
+    match report.kind {
+        Some(ErrorKind::NotFound) => crate::error::not_found(message),
+        Some(ErrorKind::Validation) => crate::error::validation(message, report.details),
+        Some(ErrorKind::Connection) => crate::error::connection(message),
+        Some(ErrorKind::PartialImport) => crate::error::partial_import(message, report.details),
+        _ => anyhow::anyhow!(message),
+    }
+}
+
+/// Percent-encode a path segment or query value
+fn encode(value: &str) -> String {
+    let mut encoded = String::new();
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            let _ = write!(encoded, "%{:02X}", byte);
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    /// Answers every request with one response and records the paths
+    struct FixedTransport {
+        response: Response,
+        paths: RefCell<Vec<String>>,
+    }
+
+    impl Transport for FixedTransport {
+        async fn get(&self, path: &str) -> Result<Response> {
+            self.paths.borrow_mut().push(path.to_string());
+            Ok(self.response.clone())
+        }
+    }
+
+    fn client(status: u16, body: &str) -> RemoteClient<FixedTransport> {
+        RemoteClient::new(FixedTransport {
+            response: Response {
+                status,
+                body: body.as_bytes().to_vec(),
+            },
+            paths: RefCell::new(Vec::new()),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_remote_client() {
+        let remote = client(
+            200,
+            r#"{"binary":"abc","truncated":false,
+                "nodes":[{"id":"f1","label":"main","group":"Function"}],"edges":[]}"#,
+        );
+        let graph = remote.call_graph("my app.exe", Some(10)).await.unwrap();
+        assert_eq!(graph.graph.nodes[0].label, "main");
+        assert_eq!(
+            remote.transport.paths.borrow()[0],
+            "/api/binaries/my%20app.exe/callgraph?max_edges=10"
+        );
+
+        let remote = client(
+            404,
+            r#"{"success":false,"exit_code":5,"kind":"not_found",
+                "message":"No binary found matching: 'x'","causes":[],"details":[]}"#,
+        );
+        let err = remote.binary("x").await.unwrap_err();
+        assert_eq!(crate::error::classify(&err), ErrorKind::NotFound);
+
+        let err = client(502, "Bad Gateway").binaries(None).await.unwrap_err();
+        assert_eq!(crate::error::classify(&err), ErrorKind::Connection);
+    }
+}
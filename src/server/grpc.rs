@@ -0,0 +1,380 @@
+//! gRPC API of `serve --grpc-listen`, generated from `proto/binaryx.proto`
+//!
+//! Calls are answered by the same queries as the REST API, and failed calls get the
+//! status matching the HTTP status the REST API would answer with.
+
+use std::collections::HashMap;
+use std::pin::Pin;
+
+use clap::ValueEnum;
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+use tokio_stream::Stream;
+use tonic::{Request, Response, Status};
+
+use super::jobs::{JobQueue, JobState, QueueFull};
+use super::{find_binary, limited, DEFAULT_MAX_EDGES, DEFAULT_NEIGHBORS, MAX_UPLOAD_BYTES};
+use crate::api::adapters::SourceFormat;
+use crate::api::DataImporter;
+use crate::error::ErrorKind;
+use crate::models::FeatureSpace;
+use crate::neo4j::CallPathAnalyzer;
+
+pub mod proto {
+    tonic::include_proto!("binaryx.v1");
+}
+
+use proto::binary_x_server::{BinaryX, BinaryXServer};
+
+/// Functions streamed by `ListFunctions` unless `limit` is given
+const DEFAULT_FUNCTION_LIMIT: usize = 1000;
+/// Calls followed by `FindCallPaths` unless `max_depth` is given, as in `query call-path`
+const DEFAULT_MAX_DEPTH: usize = 5;
+
+type ResponseStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send>>;
+
+/// Service answering from `importer`; the job calls need an import queue
+pub struct GrpcService {
+    importer: DataImporter,
+    jobs: Option<JobQueue>,
+}
+
+/// Answer gRPC calls on `listener` until the server fails
+pub async fn serve(
+    listener: TcpListener,
+    importer: DataImporter,
+    jobs: Option<JobQueue>,
+) -> anyhow::Result<()> {
+    let service = BinaryXServer::new(GrpcService { importer, jobs })
+        .max_decoding_message_size(MAX_UPLOAD_BYTES);
+    tonic::transport::Server::builder()
+        .add_service(service)
+        .serve_with_incoming(TcpListenerStream::new(listener))
+        .await?;
+    Ok(())
+}
+
+/// Status of a failed call, from the error kind as the REST API's HTTP status
+fn status(err: anyhow::Error) -> Status {
+    let message = format!("{:#}", err);
+    if err.is::<QueueFull>() {
+        return Status::resource_exhausted(message);
+    }
+    match crate::error::classify(&err) {
+        ErrorKind::NotFound => Status::not_found(message),
+        ErrorKind::Validation => Status::invalid_argument(message),
+        ErrorKind::Connection => Status::unavailable(message),
+        _ => {
+            eprintln!("[WARN] Request failed: {}", message);
+            Status::internal(message)
+        }
+    }
+}
+
+/// Proto3 has no unset integers: 0 stands for the default
+fn or_default(value: u32, default: usize) -> usize {
+    match value {
+        0 => default,
+        value => value as usize,
+    }
+}
+
+fn stream<T: Send + 'static>(items: Vec<T>) -> ResponseStream<T> {
+    Box::pin(tokio_stream::iter(items.into_iter().map(Ok)))
+}
+
+impl GrpcService {
+    fn jobs(&self) -> Result<&JobQueue, Status> {
+        self.jobs
+            .as_ref()
+            .ok_or_else(|| Status::failed_precondition("serve was started without --import-queue"))
+    }
+}
+
+#[tonic::async_trait]
+impl BinaryX for GrpcService {
+    type ListFunctionsStream = ResponseStream<proto::Function>;
+    type GetCallGraphStream = ResponseStream<proto::CallEdge>;
+    type FindCallPathsStream = ResponseStream<proto::CallPath>;
+
+    async fn list_functions(
+        &self,
+        request: Request<proto::FunctionsRequest>,
+    ) -> Result<Response<Self::ListFunctionsStream>, Status> {
+        let request = request.into_inner();
+        let sample = find_binary(&self.importer, &request.binary)
+            .await
+            .map_err(status)?;
+        let pattern = request.pattern.to_lowercase();
+        let functions = self
+            .importer
+            .session()
+            .query_binary_functions(&sample.hash)
+            .await
+            .map_err(status)?
+            .into_iter()
+            .filter(|function| function.name.to_lowercase().contains(&pattern))
+            .take(or_default(request.limit, DEFAULT_FUNCTION_LIMIT))
+            .map(|function| proto::Function {
+                uid: function.uid,
+                name: function.name,
+                address: function.address,
+                size: function.size,
+                r#type: format!("{:?}", function.r#type),
+                is_library: function.is_library,
+            })
+            .collect();
+        Ok(Response::new(stream(functions)))
+    }
+
+    async fn get_call_graph(
+        &self,
+        request: Request<proto::CallGraphRequest>,
+    ) -> Result<Response<Self::GetCallGraphStream>, Status> {
+        let request = request.into_inner();
+        let sample = find_binary(&self.importer, &request.binary)
+            .await
+            .map_err(status)?;
+        let (functions, links) = self
+            .importer
+            .session()
+            .query_binary_call_graph(&sample.hash)
+            .await
+            .map_err(status)?;
+        let names: HashMap<String, String> = functions
+            .into_iter()
+            .map(|function| (function.uid, function.name))
+            .collect();
+        let name = |uid: &str| names.get(uid).cloned().unwrap_or_default();
+
+        let (links, _) = limited(links, or_default(request.max_edges, DEFAULT_MAX_EDGES));
+        let edges = links
+            .into_iter()
+            .map(|link| proto::CallEdge {
+                from_name: name(&link.from_uid),
+                to_name: name(&link.to_uid),
+                offset: link.offsets.into_iter().next().unwrap_or_default(),
+                call_type: link.call_type.unwrap_or_default(),
+                source: link.source.unwrap_or_default(),
+                confidence: link.confidence,
+                from_uid: link.from_uid,
+                to_uid: link.to_uid,
+            })
+            .collect();
+        Ok(Response::new(stream(edges)))
+    }
+
+    async fn find_call_paths(
+        &self,
+        request: Request<proto::CallPathRequest>,
+    ) -> Result<Response<Self::FindCallPathsStream>, Status> {
+        let request = request.into_inner();
+        let binary = Some(request.binary.as_str()).filter(|binary| !binary.is_empty());
+        let max_depth = or_default(request.max_depth, DEFAULT_MAX_DEPTH);
+        let analyzer =
+            CallPathAnalyzer::new(self.importer.session().importer().connection().clone());
+        let paths = if request.to.is_empty() {
+            analyzer
+                .query_call_paths(&request.function, binary, max_depth)
+                .await
+        } else {
+            analyzer
+                .query_call_routes(&request.function, &request.to, binary, max_depth, true)
+                .await
+        }
+        .map_err(status)?;
+
+        let paths = paths
+            .into_iter()
+            .map(|path| proto::CallPath {
+                id: path.id,
+                nodes: path
+                    .nodes
+                    .into_iter()
+                    .map(|node| proto::CallPathNode {
+                        uid: node.uid,
+                        name: node.name,
+                        address: node.address,
+                        depth: node.depth as u32,
+                        call_site: node.call_site,
+                        call_type: node.call_type,
+                    })
+                    .collect(),
+            })
+            .collect();
+        Ok(Response::new(stream(paths)))
+    }
+
+    async fn find_similar(
+        &self,
+        request: Request<proto::SimilarRequest>,
+    ) -> Result<Response<proto::SimilarResponse>, Status> {
+        let request = request.into_inner();
+        let space = match request.space.as_str() {
+            "" => FeatureSpace::Imports,
+            space => FeatureSpace::from_str(space, true).map_err(|_| {
+                Status::invalid_argument(format!("Unknown feature space '{}'", space))
+            })?,
+        };
+        let sample = find_binary(&self.importer, &request.binary)
+            .await
+            .map_err(status)?;
+        let hits = self
+            .importer
+            .session()
+            .query_neighbors(
+                &sample.hash,
+                space,
+                or_default(request.k, DEFAULT_NEIGHBORS),
+            )
+            .await
+            .map_err(status)?;
+
+        Ok(Response::new(proto::SimilarResponse {
+            binary: sample.hash,
+            space: space.key().to_string(),
+            hits: hits
+                .into_iter()
+                .map(|hit| proto::NeighborHit {
+                    binary: hit.binary,
+                    hash: hit.hash,
+                    similarity: hit.similarity,
+                    shared: hit.shared,
+                    features: hit.features,
+                })
+                .collect(),
+        }))
+    }
+
+    async fn submit_import(
+        &self,
+        request: Request<proto::ImportRequest>,
+    ) -> Result<Response<proto::Job>, Status> {
+        let jobs = self.jobs()?;
+        let request = request.into_inner();
+        let source = match request.source.as_str() {
+            "" => SourceFormat::Auto,
+            source => SourceFormat::from_str(source, true).map_err(|_| {
+                Status::invalid_argument(format!("Unknown source format '{}'", source))
+            })?,
+        };
+        if request.data.is_empty() {
+            return Err(Status::invalid_argument("Empty upload"));
+        }
+        let name = Some(request.name).filter(|name| !name.is_empty());
+        let job = jobs
+            .submit(&request.data, name, source, request.executable)
+            .await
+            .map_err(status)?;
+        Ok(Response::new(job.into()))
+    }
+
+    async fn get_job(
+        &self,
+        request: Request<proto::JobRequest>,
+    ) -> Result<Response<proto::Job>, Status> {
+        let job = self.jobs()?.get(&request.into_inner().id).map_err(status)?;
+        Ok(Response::new(job.into()))
+    }
+}
+
+impl From<super::jobs::Job> for proto::Job {
+    fn from(job: super::jobs::Job) -> Self {
+        let state = match job.state {
+            JobState::Queued => "queued",
+            JobState::Running => "running",
+            JobState::Done => "done",
+            JobState::Failed => "failed",
+        };
+        Self {
+            id: job.id,
+            state: state.to_string(),
+            name: job.name,
+            source: job.source,
+            submitted_at: job.submitted_at,
+            started_at: job.started_at,
+            finished_at: job.finished_at,
+            binary_hash: job.binary_hash,
+            statistics: job.statistics.map(|stats| proto::ImportStatistics {
+                binaries: stats.binaries,
+                functions: stats.functions,
+                strings: stats.strings,
+                libraries: stats.libraries,
+                calls_relationships: stats.calls_relationships,
+                skipped_calls: stats.skipped_calls,
+            }),
+            errors: job.errors,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::proto::binary_x_client::BinaryXClient;
+    use super::*;
+    use crate::config::Config;
+    use tonic::Code;
+
+    #[tokio::test]
+    async fn test_grpc_round_trip() {
+        // Jobs are recorded without reaching the database, nothing listens on its port
+        let config = Config {
+            neo4j_uri: "bolt://127.0.0.1:1".to_string(),
+            ..Config::default()
+        };
+        let importer = DataImporter::new(&config).await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let jobs = JobQueue::open(dir.path().to_str().unwrap(), importer.clone(), 1, 10).unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(serve(listener, importer, Some(jobs)));
+
+        let mut client = BinaryXClient::connect(format!("http://{}", address))
+            .await
+            .unwrap();
+        let job = client
+            .submit_import(proto::ImportRequest {
+                data: b"{}".to_vec(),
+                name: "sample.json".to_string(),
+                source: "native".to_string(),
+                executable: false,
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(job.name.as_deref(), Some("sample.json"));
+        assert_eq!(job.source, "native");
+
+        let found = client
+            .get_job(proto::JobRequest { id: job.id.clone() })
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(found.id, job.id);
+        assert_eq!(found.submitted_at, job.submitted_at);
+
+        let missing = client
+            .get_job(proto::JobRequest {
+                id: uuid::Uuid::new_v4().to_string(),
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(missing.code(), Code::NotFound);
+
+        let empty = client
+            .submit_import(proto::ImportRequest::default())
+            .await
+            .unwrap_err();
+        assert_eq!(empty.code(), Code::InvalidArgument);
+
+        let space = client
+            .find_similar(proto::SimilarRequest {
+                binary: "sample".to_string(),
+                space: "colors".to_string(),
+                k: 0,
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(space.code(), Code::InvalidArgument);
+    }
+}
@@ -0,0 +1,335 @@
+//! Import queue of `serve --import-queue`
+//!
+//! Each upload becomes a job: its input `<id>.input` and its record `<id>.job.json` are
+//! kept in the queue directory, so jobs queued or interrupted while running are picked
+//! up again when the server restarts. A fixed number of workers imports them in
+//! submission order, and uploads are refused once too many jobs wait for a worker.
+
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::{mpsc, Mutex};
+
+use crate::api::adapters::{load_native, SourceFormat};
+use crate::api::executable::parse_executable;
+use crate::api::{DataImporter, ImportResult, ImportStatistics};
+use crate::output::AtomicFile;
+use crate::utils::time::now_iso;
+
+/// `source` of jobs holding a raw executable instead of extractor output
+const EXECUTABLE_SOURCE: &str = "executable";
+
+/// Shortest string kept when parsing uploaded executables, as `import binary`
+const MIN_STRING_LENGTH: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobState {
+    Queued,
+    Running,
+    /// Imported; per-item errors may still be listed
+    Done,
+    Failed,
+}
+
+/// Record of one upload, answered by `GET /api/jobs/<id>`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Job {
+    pub id: String,
+    pub state: JobState,
+    /// File name given with the `name` parameter
+    pub name: Option<String>,
+    /// Extractor output format (`--source` value) or `executable`
+    pub source: String,
+    pub submitted_at: String,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+    pub binary_hash: Option<String>,
+    pub statistics: Option<ImportStatistics>,
+    pub errors: Vec<String>,
+}
+
+impl Job {
+    fn record_path(dir: &Path, id: &str) -> PathBuf {
+        dir.join(format!("{}.job.json", id))
+    }
+
+    fn input_path(dir: &Path, id: &str) -> PathBuf {
+        dir.join(format!("{}.input", id))
+    }
+
+    fn load(dir: &Path, id: &str) -> Result<Self> {
+        let content = std::fs::read(Self::record_path(dir, id))?;
+        Ok(serde_json::from_slice(&content)?)
+    }
+
+    fn save(&self, dir: &Path) -> Result<()> {
+        let path = Self::record_path(dir, &self.id);
+        let mut file = AtomicFile::create(&path.to_string_lossy(), true)?;
+        serde_json::to_writer_pretty(&mut file, self)?;
+        file.commit()
+    }
+}
+
+/// Upload refused because the queue is full, answered with 429
+#[derive(Debug)]
+pub struct QueueFull(pub usize);
+
+impl fmt::Display for QueueFull {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Import queue is full ({} jobs waiting), retry later",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for QueueFull {}
+
+#[derive(Clone)]
+pub struct JobQueue {
+    dir: Arc<PathBuf>,
+    sender: mpsc::Sender<String>,
+    /// Jobs not taken by a worker yet; running jobs are not counted
+    queued: Arc<AtomicUsize>,
+    max_queued: usize,
+}
+
+impl JobQueue {
+    /// Queue in `dir` imported by `workers` concurrent imports, holding at most
+    /// `max_queued` jobs that wait for a worker, and re-queuing the jobs a previous
+    /// server left unfinished
+    pub fn open(
+        dir: &str,
+        importer: DataImporter,
+        workers: usize,
+        max_queued: usize,
+    ) -> Result<Self> {
+        let dir = PathBuf::from(dir);
+        std::fs::create_dir_all(&dir).map_err(|e| {
+            anyhow::anyhow!("Failed to create import queue '{}': {}", dir.display(), e)
+        })?;
+        let unfinished = unfinished_jobs(&dir)?;
+
+        // Submissions stop at `max_queued`, so the channel never holds more than that
+        // or the resumed jobs
+        let (sender, receiver) = mpsc::channel(max_queued.max(unfinished.len()).max(1));
+        let queue = Self {
+            dir: Arc::new(dir),
+            sender,
+            queued: Arc::new(AtomicUsize::new(0)),
+            max_queued,
+        };
+
+        if !unfinished.is_empty() {
+            eprintln!("Resuming {} queued imports", unfinished.len());
+        }
+        for mut job in unfinished {
+            job.state = JobState::Queued;
+            job.save(&queue.dir)?;
+            queue.queued.fetch_add(1, Ordering::SeqCst);
+            queue.sender.try_send(job.id)?;
+        }
+
+        let receiver = Arc::new(Mutex::new(receiver));
+        for _ in 0..workers.max(1) {
+            let receiver = receiver.clone();
+            let importer = importer.clone();
+            let queue = queue.clone();
+            tokio::spawn(async move {
+                loop {
+                    let Some(id) = receiver.lock().await.recv().await else {
+                        break;
+                    };
+                    queue.queued.fetch_sub(1, Ordering::SeqCst);
+                    run_job(&queue.dir, &importer, &id).await;
+                }
+            });
+        }
+
+        Ok(queue)
+    }
+
+    /// Store an upload as a queued job
+    pub async fn submit(
+        &self,
+        input: &[u8],
+        name: Option<String>,
+        source: SourceFormat,
+        executable: bool,
+    ) -> Result<Job> {
+        let max_queued = self.max_queued;
+        self.queued
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |queued| {
+                (queued < max_queued).then_some(queued + 1)
+            })
+            .map_err(|queued| anyhow::Error::new(QueueFull(queued)))?;
+
+        let job = Job {
+            id: uuid::Uuid::new_v4().to_string(),
+            state: JobState::Queued,
+            name,
+            source: if executable {
+                EXECUTABLE_SOURCE.to_string()
+            } else {
+                source_name(source)
+            },
+            submitted_at: now_iso(),
+            started_at: None,
+            finished_at: None,
+            binary_hash: None,
+            statistics: None,
+            errors: Vec::new(),
+        };
+        let stored = async {
+            tokio::fs::write(Job::input_path(&self.dir, &job.id), input).await?;
+            job.save(&self.dir)?;
+            self.sender.send(job.id.clone()).await?;
+            Ok::<_, anyhow::Error>(())
+        };
+        if let Err(e) = stored.await {
+            self.queued.fetch_sub(1, Ordering::SeqCst);
+            return Err(e);
+        }
+        Ok(job)
+    }
+
+    /// Jobs waiting for a worker
+    pub fn queued(&self) -> usize {
+        self.queued.load(Ordering::SeqCst)
+    }
+
+    pub fn get(&self, id: &str) -> Result<Job> {
+        let not_found = || crate::error::not_found(format!("No job '{}'", id));
+        // Ids are UUIDs, which also keeps the path inside the queue directory
+        if uuid::Uuid::parse_str(id).is_err() {
+            return Err(not_found());
+        }
+        Job::load(&self.dir, id).map_err(|_| not_found())
+    }
+}
+
+fn source_name(source: SourceFormat) -> String {
+    source
+        .to_possible_value()
+        .map_or_else(|| "auto".to_string(), |value| value.get_name().to_string())
+}
+
+/// Queued and running jobs in `dir`, oldest first
+fn unfinished_jobs(dir: &Path) -> Result<Vec<Job>> {
+    let mut jobs = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let name = entry?.file_name().to_string_lossy().into_owned();
+        let Some(id) = name.strip_suffix(".job.json") else {
+            continue;
+        };
+        match Job::load(dir, id) {
+            Ok(job) if matches!(job.state, JobState::Queued | JobState::Running) => jobs.push(job),
+            Ok(_) => {}
+            Err(e) => eprintln!("[WARN] Skipping unreadable job '{}': {}", name, e),
+        }
+    }
+    jobs.sort_by(|a, b| (&a.submitted_at, &a.id).cmp(&(&b.submitted_at, &b.id)));
+    Ok(jobs)
+}
+
+async fn run_job(dir: &Path, importer: &DataImporter, id: &str) {
+    let mut job = match Job::load(dir, id) {
+        Ok(job) => job,
+        Err(e) => {
+            eprintln!("[WARN] Job {} could not be read: {}", id, e);
+            return;
+        }
+    };
+    job.state = JobState::Running;
+    job.started_at = Some(now_iso());
+    if let Err(e) = job.save(dir) {
+        eprintln!("[WARN] Failed to update job {}: {}", id, e);
+    }
+
+    let input = Job::input_path(dir, id);
+    match import_job(importer, &input, &job.source).await {
+        Ok(result) => {
+            job.state = JobState::Done;
+            job.binary_hash = result.binary_hash;
+            job.statistics = Some(result.statistics);
+            job.errors = result.errors;
+        }
+        Err(e) => {
+            job.state = JobState::Failed;
+            job.errors.push(format!("{:#}", e));
+        }
+    }
+    job.finished_at = Some(now_iso());
+    if let Err(e) = job.save(dir) {
+        eprintln!("[WARN] Failed to update job {}: {}", id, e);
+    }
+    let _ = std::fs::remove_file(&input);
+}
+
+async fn import_job(importer: &DataImporter, input: &Path, source: &str) -> Result<ImportResult> {
+    let input = input.to_path_buf();
+    let source = source.to_string();
+    let data = tokio::task::spawn_blocking(move || {
+        if source == EXECUTABLE_SOURCE {
+            return parse_executable(&input, MIN_STRING_LENGTH);
+        }
+        let format = SourceFormat::from_str(&source, true)
+            .map_err(|_| anyhow::anyhow!("Unknown source format '{}'", source))?;
+        let (data, _) = load_native(&input, format)?;
+        let validation = DataImporter::validate_data(&data)?;
+        if !validation.valid {
+            return Err(crate::error::validation(
+                "Data validation failed",
+                validation.errors,
+            ));
+        }
+        Ok(data)
+    })
+    .await??;
+    importer.import_from_json(data).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unfinished_jobs() {
+        let dir = tempfile::tempdir().unwrap();
+        let job = |id: &str, state: JobState, submitted_at: &str| Job {
+            id: id.to_string(),
+            state,
+            name: None,
+            source: source_name(SourceFormat::Auto),
+            submitted_at: submitted_at.to_string(),
+            started_at: None,
+            finished_at: None,
+            binary_hash: None,
+            statistics: None,
+            errors: Vec::new(),
+        };
+        job("b", JobState::Queued, "2026-01-02T00:00:00Z")
+            .save(dir.path())
+            .unwrap();
+        job("a", JobState::Running, "2026-01-01T00:00:00Z")
+            .save(dir.path())
+            .unwrap();
+        job("c", JobState::Done, "2026-01-01T00:00:00Z")
+            .save(dir.path())
+            .unwrap();
+
+        let ids: Vec<String> = unfinished_jobs(dir.path())
+            .unwrap()
+            .into_iter()
+            .map(|job| job.id)
+            .collect();
+        assert_eq!(ids, vec!["a", "b"]);
+        assert_eq!(Job::load(dir.path(), "b").unwrap().source, "auto");
+    }
+}
@@ -0,0 +1,310 @@
+//! HTTP server of `serve`: a read-only REST API over the graph and, with `--ui`, an
+//! embedded page drawing call graphs and sample-similarity graphs fetched from it
+//!
+//! With `--import-queue`, samples uploaded to `POST /api/jobs` are imported in the
+//! background (see [`jobs`]) and their progress is answered by `GET /api/jobs/<id>`.
+//!
+//! Failed requests answer with the `--errors-json` document and a status derived
+//! from its error kind.
+//!
+//! With the `grpc` feature, `--grpc-listen` also serves the gRPC API of
+//! `proto/binaryx.proto` (see [`grpc`]).
+
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod jobs;
+
+use axum::body::Bytes;
+use axum::extract::{DefaultBodyLimit, Path, Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use clap::ValueEnum;
+use serde::Deserialize;
+
+use crate::api::adapters::SourceFormat;
+use crate::api::DataImporter;
+use crate::error::{ErrorKind, ErrorReport};
+use crate::models::{
+    Binary, CallGraphResponse, FeatureSpace, GraphNode, SimilarityLink, SimilarityResponse,
+};
+use crate::neo4j::{metrics, BinaryFilter};
+use crate::output::html;
+use jobs::{Job, JobQueue, QueueFull};
+
+/// Binaries listed by `GET /api/binaries` unless `limit` is given
+const DEFAULT_BINARY_LIMIT: usize = 200;
+/// Call graph edges returned unless `max_edges` is given, as in `report`
+const DEFAULT_MAX_EDGES: usize = 500;
+/// Neighbors returned unless `k` is given
+const DEFAULT_NEIGHBORS: usize = 10;
+/// Largest upload accepted by `POST /api/jobs`
+const MAX_UPLOAD_BYTES: usize = 512 * 1024 * 1024;
+/// Seconds a client is asked to wait after the import queue refused an upload
+const RETRY_AFTER_SECS: &str = "10";
+
+const UI_PAGE: &str = include_str!("ui.html");
+
+/// Routes of the REST API and `/metrics`, plus the UI page and its script with `ui` and
+/// the job routes with an import queue
+pub fn router(importer: DataImporter, ui: bool, jobs: Option<JobQueue>) -> Router {
+    let mut router = Router::new()
+        .route("/api/binaries", get(binaries))
+        .route("/api/binaries/{binary}", get(binary))
+        .route("/api/binaries/{binary}/callgraph", get(callgraph))
+        .route("/api/binaries/{binary}/similar", get(similar));
+    let queue = jobs.clone();
+    router = router.route(
+        "/metrics",
+        get(move || async move {
+            let depth = queue.as_ref().map(JobQueue::queued);
+            (
+                [(header::CONTENT_TYPE, metrics::CONTENT_TYPE)],
+                metrics::render(metrics::global(), depth),
+            )
+        }),
+    );
+    if ui {
+        router = router
+            .route("/", get(|| async { Html(UI_PAGE) }))
+            .route("/graph.js", get(graph_script));
+    }
+    let router = router.with_state(importer);
+    match jobs {
+        Some(jobs) => router.merge(
+            Router::new()
+                .route("/api/jobs", post(submit_job))
+                .route("/api/jobs/{id}", get(job))
+                .layer(DefaultBodyLimit::max(MAX_UPLOAD_BYTES))
+                .with_state(jobs),
+        ),
+        None => router,
+    }
+}
+
+/// Error answered as an `ErrorReport` document
+struct ApiError(anyhow::Error);
+
+impl<E: Into<anyhow::Error>> From<E> for ApiError {
+    fn from(err: E) -> Self {
+        Self(err.into())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let report = ErrorReport::failure(&self.0);
+        if self.0.is::<QueueFull>() {
+            return (
+                StatusCode::TOO_MANY_REQUESTS,
+                [(header::RETRY_AFTER, RETRY_AFTER_SECS)],
+                Json(report),
+            )
+                .into_response();
+        }
+        let status = match report.kind {
+            Some(ErrorKind::NotFound) => StatusCode::NOT_FOUND,
+            Some(ErrorKind::Validation) => StatusCode::BAD_REQUEST,
+            Some(ErrorKind::Connection) => StatusCode::BAD_GATEWAY,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        if status.is_server_error() {
+            eprintln!("[WARN] Request failed: {:#}", self.0);
+        }
+        (status, Json(report)).into_response()
+    }
+}
+
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+#[derive(Debug, Deserialize)]
+struct ListParams {
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CallGraphParams {
+    max_edges: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SimilarParams {
+    k: Option<usize>,
+    /// `FeatureSpace` value name, `imports` by default
+    space: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SubmitParams {
+    /// File name kept on the job
+    name: Option<String>,
+    /// `--source` value of `import json`, detected by default
+    source: Option<String>,
+    /// The upload is an executable to parse, as `import binary`
+    #[serde(default)]
+    executable: bool,
+}
+
+async fn graph_script() -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/javascript")],
+        html::GRAPH_SCRIPT,
+    )
+}
+
+async fn binaries(
+    State(importer): State<DataImporter>,
+    Query(params): Query<ListParams>,
+) -> ApiResult<Vec<Binary>> {
+    let binaries = importer
+        .session()
+        .query_binaries(
+            &BinaryFilter::default(),
+            params.limit.unwrap_or(DEFAULT_BINARY_LIMIT),
+        )
+        .await?;
+    Ok(Json(binaries))
+}
+
+async fn binary(
+    State(importer): State<DataImporter>,
+    Path(binary): Path<String>,
+) -> ApiResult<Binary> {
+    Ok(Json(find_binary(&importer, &binary).await?))
+}
+
+async fn callgraph(
+    State(importer): State<DataImporter>,
+    Path(binary): Path<String>,
+    Query(params): Query<CallGraphParams>,
+) -> ApiResult<CallGraphResponse> {
+    let sample = find_binary(&importer, &binary).await?;
+    let max_edges = params.max_edges.unwrap_or(DEFAULT_MAX_EDGES);
+    // One edge past the limit tells a truncated graph from one of exactly `max_edges`
+    let edges = importer
+        .session()
+        .query_binary_call_edges(&sample.hash, max_edges.saturating_add(1))
+        .await?;
+    let (edges, truncated) = limited(edges, max_edges);
+
+    Ok(Json(CallGraphResponse {
+        graph: html::graph_data(&edges),
+        binary: sample.hash,
+        truncated,
+    }))
+}
+
+/// The first `limit` items, and whether any were dropped
+fn limited<T>(mut items: Vec<T>, limit: usize) -> (Vec<T>, bool) {
+    let truncated = items.len() > limit;
+    items.truncate(limit);
+    (items, truncated)
+}
+
+async fn similar(
+    State(importer): State<DataImporter>,
+    Path(binary): Path<String>,
+    Query(params): Query<SimilarParams>,
+) -> ApiResult<SimilarityResponse> {
+    let space = match params.space.as_deref() {
+        Some(space) => FeatureSpace::from_str(space, true).map_err(|_| {
+            crate::error::validation(format!("Unknown feature space '{}'", space), Vec::new())
+        })?,
+        None => FeatureSpace::Imports,
+    };
+    let sample = find_binary(&importer, &binary).await?;
+    let hits = importer
+        .session()
+        .query_neighbors(&sample.hash, space, params.k.unwrap_or(DEFAULT_NEIGHBORS))
+        .await?;
+
+    let mut nodes = vec![GraphNode {
+        id: sample.hash.clone(),
+        label: sample.filename,
+        group: "Reference".to_string(),
+    }];
+    nodes.extend(hits.iter().map(|hit| GraphNode {
+        id: hit.hash.clone(),
+        label: hit.binary.clone(),
+        group: "Sample".to_string(),
+    }));
+    let edges = hits
+        .into_iter()
+        .map(|hit| SimilarityLink {
+            from: sample.hash.clone(),
+            to: hit.hash,
+            label: format!("{:.2}", hit.similarity),
+            similarity: hit.similarity,
+        })
+        .collect();
+    Ok(Json(SimilarityResponse {
+        binary: sample.hash,
+        space: space.key().to_string(),
+        directed: false,
+        nodes,
+        edges,
+    }))
+}
+
+async fn submit_job(
+    State(jobs): State<JobQueue>,
+    Query(params): Query<SubmitParams>,
+    body: Bytes,
+) -> Result<(StatusCode, Json<Job>), ApiError> {
+    let source = match params.source.as_deref() {
+        Some(source) => SourceFormat::from_str(source, true).map_err(|_| {
+            crate::error::validation(format!("Unknown source format '{}'", source), Vec::new())
+        })?,
+        None => SourceFormat::Auto,
+    };
+    if body.is_empty() {
+        return Err(crate::error::validation("Empty upload", Vec::new()).into());
+    }
+    let job = jobs
+        .submit(&body, params.name, source, params.executable)
+        .await?;
+    Ok((StatusCode::ACCEPTED, Json(job)))
+}
+
+async fn job(State(jobs): State<JobQueue>, Path(id): Path<String>) -> ApiResult<Job> {
+    Ok(Json(jobs.get(&id)?))
+}
+
+/// Binary by hash, else by filename
+async fn find_binary(importer: &DataImporter, binary: &str) -> anyhow::Result<Binary> {
+    let session = importer.session();
+    let sample = match session.query_binary_by_hash(binary).await? {
+        Some(found) => Some(found),
+        None => session.query_binary_info(binary).await?,
+    };
+    sample.ok_or_else(|| crate::error::not_found(format!("No binary found matching: '{}'", binary)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_status() {
+        let response = ApiError::from(crate::error::not_found("No binary")).into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = ApiError::from(crate::error::validation(
+            "Unknown feature space",
+            Vec::new(),
+        ))
+        .into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = ApiError::from(anyhow::Error::new(QueueFull(100))).into_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[test]
+    fn test_limited() {
+        assert_eq!(limited(vec![1, 2, 3], 3), (vec![1, 2, 3], false));
+        assert_eq!(limited(vec![1, 2, 3, 4], 3), (vec![1, 2, 3], true));
+        assert_eq!(limited(Vec::<u8>::new(), 0), (Vec::new(), false));
+    }
+}
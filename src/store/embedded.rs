@@ -0,0 +1,543 @@
+//! Graph kept in memory and saved as one JSON file, for `--backend embedded`
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use super::GraphStore;
+use crate::models::{Binary, CallGraphLink, Calls, Function, ImportGroup, Library, Resource};
+use crate::neo4j::{BinaryFilter, BinarySubgraph};
+use crate::output::AtomicFile;
+use crate::utils::time::now_iso;
+
+/// Version of the file layout, bumped on incompatible changes
+const FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct EmbeddedGraph {
+    version: u32,
+    /// Binaries by hash
+    binaries: BTreeMap<String, Binary>,
+    /// Functions by uid
+    functions: BTreeMap<String, Function>,
+    libraries: BTreeSet<String>,
+    /// Functions each binary CONTAINS, by binary hash
+    contains: BTreeMap<String, BTreeSet<String>>,
+    /// Import address of each function a binary IMPORTS, by binary hash and function uid
+    imports: BTreeMap<String, BTreeMap<String, String>>,
+    /// Libraries each binary imports from, by binary hash
+    imports_library: BTreeMap<String, BTreeSet<String>>,
+    /// Library of each import function, by function uid
+    belongs_to: BTreeMap<String, String>,
+    /// CALLS edges by caller and callee uid
+    calls: BTreeMap<String, BTreeMap<String, CallGraphLink>>,
+}
+
+impl Default for EmbeddedGraph {
+    fn default() -> Self {
+        Self {
+            version: FORMAT_VERSION,
+            binaries: BTreeMap::new(),
+            functions: BTreeMap::new(),
+            libraries: BTreeSet::new(),
+            contains: BTreeMap::new(),
+            imports: BTreeMap::new(),
+            imports_library: BTreeMap::new(),
+            belongs_to: BTreeMap::new(),
+            calls: BTreeMap::new(),
+        }
+    }
+}
+
+impl EmbeddedGraph {
+    /// Set the extracted properties of a function, keeping its analyst annotations
+    fn merge_function(&mut self, function: &Function) {
+        let mut function = function.clone();
+        if let Some(existing) = self.functions.get(&function.uid) {
+            function.analyst_name = existing.analyst_name.clone();
+            function.comment = existing.comment.clone();
+        }
+        self.functions.insert(function.uid.clone(), function);
+    }
+}
+
+/// Graph store backed by a local file; clones share the loaded graph
+///
+/// Changes stay in memory until [`EmbeddedStore::save`].
+#[derive(Clone)]
+pub struct EmbeddedStore {
+    path: PathBuf,
+    graph: Arc<Mutex<EmbeddedGraph>>,
+}
+
+impl EmbeddedStore {
+    /// Load the graph saved at `path`, or start an empty one if the file does not exist
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let graph = if path.exists() {
+            let content = std::fs::read(&path).map_err(|e| {
+                anyhow::anyhow!("Failed to read embedded graph '{}': {}", path.display(), e)
+            })?;
+            let graph: EmbeddedGraph = serde_json::from_slice(&content).map_err(|e| {
+                anyhow::anyhow!("Failed to parse embedded graph '{}': {}", path.display(), e)
+            })?;
+            if graph.version != FORMAT_VERSION {
+                return Err(anyhow::anyhow!(
+                    "Embedded graph '{}' has format version {}, expected {}",
+                    path.display(),
+                    graph.version,
+                    FORMAT_VERSION
+                ));
+            }
+            graph
+        } else {
+            EmbeddedGraph::default()
+        };
+
+        Ok(Self {
+            path,
+            graph: Arc::new(Mutex::new(graph)),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Write the graph to its file, replacing it only once fully written
+    pub fn save(&self) -> Result<()> {
+        if let Some(dir) = self.path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut file = AtomicFile::create(&self.path.to_string_lossy(), true)?;
+        serde_json::to_writer(&mut file, &*self.lock())?;
+        file.commit()
+    }
+
+    /// Functions and the CALLS edges leaving them of a binary, or `None` if it has not
+    /// been imported; strings are not stored
+    pub fn subgraph(&self, binary_hash: &str) -> Option<BinarySubgraph> {
+        let graph = self.lock();
+        graph.binaries.get(binary_hash)?;
+        let functions = graph.contains.get(binary_hash).cloned().unwrap_or_default();
+        let calls = functions
+            .iter()
+            .filter_map(|caller| graph.calls.get(caller).map(|callees| (caller, callees)))
+            .flat_map(|(caller, callees)| {
+                callees
+                    .keys()
+                    .map(move |callee| (caller.clone(), callee.clone()))
+            })
+            .collect();
+        Some(BinarySubgraph {
+            functions: functions.into_iter().collect(),
+            strings: Default::default(),
+            calls,
+        })
+    }
+
+    /// Delete the given CALLS edges and CONTAINS links of a binary, and the functions
+    /// no other binary contains along with their calls
+    pub fn prune(&self, binary_hash: &str, stale: &BinarySubgraph) {
+        let mut graph = self.lock();
+        for (caller, callee) in &stale.calls {
+            if let Some(callees) = graph.calls.get_mut(caller) {
+                callees.remove(callee);
+            }
+        }
+        if let Some(contained) = graph.contains.get_mut(binary_hash) {
+            contained.retain(|uid| !stale.functions.contains(uid));
+        }
+        let orphans: Vec<&String> = stale
+            .functions
+            .iter()
+            .filter(|uid| {
+                !graph
+                    .contains
+                    .values()
+                    .any(|contained| contained.contains(*uid))
+            })
+            .collect();
+        for uid in orphans {
+            graph.functions.remove(uid);
+            graph.calls.remove(uid);
+            for callees in graph.calls.values_mut() {
+                callees.remove(uid);
+            }
+        }
+        graph.calls.retain(|_, callees| !callees.is_empty());
+    }
+
+    fn lock(&self) -> MutexGuard<'_, EmbeddedGraph> {
+        self.graph.lock().expect("embedded graph lock")
+    }
+}
+
+fn matches_filter(binary: &Binary, filter: &BinaryFilter<'_>) -> bool {
+    let contains = |value: &Option<String>, pattern: Option<&str>| match pattern {
+        Some(pattern) => value
+            .as_deref()
+            .is_some_and(|value| value.to_lowercase().contains(&pattern.to_lowercase())),
+        None => true,
+    };
+    let imphash = binary.pe_info.as_ref().and_then(|pe| pe.imphash.as_deref());
+    let first_imported = binary.first_imported.as_deref();
+
+    contains(&binary.compiler, filter.compiler)
+        && contains(&binary.packer, filter.packer)
+        && filter
+            .imphash
+            .is_none_or(|wanted| imphash.is_some_and(|hash| hash.eq_ignore_ascii_case(wanted)))
+        && filter
+            .min_entropy
+            .is_none_or(|min| binary.max_entropy().is_some_and(|entropy| entropy >= min))
+        && filter.arch.is_none_or(|arch| binary.arch == arch)
+        && filter
+            .since
+            .is_none_or(|since| first_imported.is_some_and(|imported| imported >= since))
+        && filter.compiled_since.is_none_or(|since| {
+            binary
+                .compiled_at
+                .as_deref()
+                .is_some_and(|compiled| compiled >= since)
+        })
+}
+
+impl GraphStore for EmbeddedStore {
+    async fn import_binary(&self, binary: &Binary) -> Result<()> {
+        let mut graph = self.lock();
+        let now = now_iso();
+        let mut binary = binary.clone();
+        binary.first_imported = graph
+            .binaries
+            .get(&binary.hash)
+            .and_then(|existing| existing.first_imported.clone())
+            .or_else(|| Some(now.clone()));
+        binary.last_updated = Some(now);
+        graph.binaries.insert(binary.hash.clone(), binary);
+        Ok(())
+    }
+
+    async fn import_functions_batch(
+        &self,
+        binary_hash: &str,
+        functions: &[Function],
+    ) -> Result<()> {
+        let mut graph = self.lock();
+        if !graph.binaries.contains_key(binary_hash) {
+            return Ok(());
+        }
+        for function in functions {
+            graph.merge_function(function);
+        }
+        graph
+            .contains
+            .entry(binary_hash.to_string())
+            .or_default()
+            .extend(functions.iter().map(|function| function.uid.clone()));
+        Ok(())
+    }
+
+    async fn import_function(&self, function: &Function) -> Result<()> {
+        self.lock().merge_function(function);
+        Ok(())
+    }
+
+    async fn import_library(&self, library: &Library) -> Result<()> {
+        self.lock().libraries.insert(library.name.clone());
+        Ok(())
+    }
+
+    async fn create_imports_relationship(
+        &self,
+        binary_hash: &str,
+        library_name: &str,
+    ) -> Result<()> {
+        let mut graph = self.lock();
+        if graph.binaries.contains_key(binary_hash) && graph.libraries.contains(library_name) {
+            graph
+                .imports_library
+                .entry(binary_hash.to_string())
+                .or_default()
+                .insert(library_name.to_string());
+        }
+        Ok(())
+    }
+
+    async fn create_belongs_to_relationship(
+        &self,
+        function_uid: &str,
+        library_name: &str,
+    ) -> Result<()> {
+        let mut graph = self.lock();
+        if graph.functions.contains_key(function_uid) && graph.libraries.contains(library_name) {
+            graph
+                .belongs_to
+                .insert(function_uid.to_string(), library_name.to_string());
+        }
+        Ok(())
+    }
+
+    async fn create_imports_function_relationship_with_address(
+        &self,
+        binary_hash: &str,
+        function_uid: &str,
+        address: &str,
+    ) -> Result<()> {
+        let mut graph = self.lock();
+        if graph.binaries.contains_key(binary_hash) && graph.functions.contains_key(function_uid) {
+            graph
+                .imports
+                .entry(binary_hash.to_string())
+                .or_default()
+                .insert(function_uid.to_string(), address.to_string());
+        }
+        Ok(())
+    }
+
+    async fn create_calls_batch(&self, calls: &[(&str, &str, Calls)]) -> Result<()> {
+        let mut graph = self.lock();
+        for (from_uid, to_uid, call) in calls {
+            if !graph.functions.contains_key(*from_uid) || !graph.functions.contains_key(*to_uid) {
+                continue;
+            }
+            // One edge per caller/callee pair keeping each distinct call site and the
+            // most confident source, as in Neo4j
+            let link = graph
+                .calls
+                .entry(from_uid.to_string())
+                .or_default()
+                .entry(to_uid.to_string())
+                .or_insert_with(|| CallGraphLink {
+                    from_uid: from_uid.to_string(),
+                    to_uid: to_uid.to_string(),
+                    count: 0,
+                    offsets: Vec::new(),
+                    call_type: None,
+                    source: None,
+                    confidence: call.confidence,
+                });
+            if !link.offsets.contains(&call.offset) {
+                link.offsets.push(call.offset.clone());
+            }
+            link.count = link.offsets.len() as u64;
+            link.call_type = Some(format!("{:?}", call.call_type));
+            if link.source.is_none() || call.confidence >= link.confidence {
+                link.source = Some(call.source.as_str().to_string());
+                link.confidence = call.confidence;
+            }
+        }
+        Ok(())
+    }
+
+    async fn query_binaries(&self, filter: &BinaryFilter<'_>, limit: usize) -> Result<Vec<Binary>> {
+        let graph = self.lock();
+        let mut binaries: Vec<Binary> = graph
+            .binaries
+            .values()
+            .filter(|binary| matches_filter(binary, filter))
+            .cloned()
+            .collect();
+        binaries.sort_by(|a, b| a.filename.cmp(&b.filename));
+        binaries.truncate(limit);
+        Ok(binaries)
+    }
+
+    async fn query_binary_by_hash(&self, hash: &str) -> Result<Option<Binary>> {
+        let hash = hash.trim();
+        let lower = hash.to_lowercase();
+        let upper = hash.to_uppercase();
+        let graph = self.lock();
+        Ok(graph
+            .binaries
+            .values()
+            .find(|binary| {
+                binary.hash == hash
+                    || binary.hash == lower
+                    || binary.md5.as_deref() == Some(lower.as_str())
+                    || binary.sha1.as_deref() == Some(lower.as_str())
+                    || binary.ssdeep.as_deref() == Some(hash)
+                    || binary.tlsh.as_deref() == Some(upper.as_str())
+            })
+            .cloned())
+    }
+
+    async fn query_binary_info(&self, binary_name: &str) -> Result<Option<Binary>> {
+        let graph = self.lock();
+        Ok(graph
+            .binaries
+            .values()
+            .find(|binary| binary.hash == binary_name || binary.filename.contains(binary_name))
+            .cloned())
+    }
+
+    async fn query_binary_functions(&self, binary_hash: &str) -> Result<Vec<Function>> {
+        let graph = self.lock();
+        Ok(graph
+            .contains
+            .get(binary_hash)
+            .into_iter()
+            .flatten()
+            .filter_map(|uid| graph.functions.get(uid).cloned())
+            .collect())
+    }
+
+    async fn query_binary_imports(&self, binary_hash: &str) -> Result<Vec<ImportGroup>> {
+        let graph = self.lock();
+        let mut groups: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+        for uid in graph
+            .imports
+            .get(binary_hash)
+            .into_iter()
+            .flat_map(|imports| imports.keys())
+        {
+            let Some(function) = graph.functions.get(uid) else {
+                continue;
+            };
+            let library = graph.belongs_to.get(uid).map_or("unknown", String::as_str);
+            groups.entry(library).or_default().insert(&function.name);
+        }
+        Ok(groups
+            .into_iter()
+            .map(|(library, apis)| ImportGroup {
+                library: library.to_string(),
+                apis: apis.into_iter().map(str::to_string).collect(),
+            })
+            .collect())
+    }
+
+    async fn query_binary_call_graph(
+        &self,
+        binary_hash: &str,
+    ) -> Result<(Vec<Function>, Vec<CallGraphLink>)> {
+        let graph = self.lock();
+        let contained = graph.contains.get(binary_hash);
+        let members: BTreeSet<&String> = contained
+            .into_iter()
+            .flatten()
+            .chain(
+                graph
+                    .imports
+                    .get(binary_hash)
+                    .into_iter()
+                    .flat_map(|imports| imports.keys()),
+            )
+            .collect();
+
+        let functions = members
+            .iter()
+            .filter_map(|uid| graph.functions.get(*uid).cloned())
+            .collect();
+        let links = contained
+            .into_iter()
+            .flatten()
+            .filter_map(|uid| graph.calls.get(uid))
+            .flat_map(|callees| callees.values())
+            .filter(|link| members.contains(&link.to_uid))
+            .cloned()
+            .collect();
+
+        Ok((functions, links))
+    }
+
+    /// Resources are not kept by the embedded backend
+    async fn query_binary_resources(&self, _binary_hash: &str) -> Result<Vec<Resource>> {
+        Ok(Vec::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::CallType;
+
+    #[tokio::test]
+    async fn test_embedded_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("graph.json");
+        let store = EmbeddedStore::open(&path).unwrap();
+
+        let binary: Binary = serde_json::from_value(serde_json::json!({
+            "hash": "ab12",
+            "md5": "cd34",
+            "filename": "sample.exe",
+            "file_path": "/samples/sample.exe",
+            "file_size": 1024,
+            "format": "PE",
+            "arch": "x86_64",
+        }))
+        .unwrap();
+        store.import_binary(&binary).await.unwrap();
+
+        let main = Function::create_internal("ab12", 0x1000, "main", false);
+        let helper = Function::create_internal("ab12", 0x1100, "helper", false);
+        let sleep = Function::create_import("kernel32.dll", "Sleep");
+        store
+            .import_functions_batch("ab12", &[main.clone(), helper.clone()])
+            .await
+            .unwrap();
+        store.import_function(&sleep).await.unwrap();
+        store
+            .import_library(&Library::create("kernel32.dll"))
+            .await
+            .unwrap();
+        store
+            .create_belongs_to_relationship(&sleep.uid, "kernel32.dll")
+            .await
+            .unwrap();
+        store
+            .create_imports_function_relationship_with_address("ab12", &sleep.uid, "0x2000")
+            .await
+            .unwrap();
+        let call = |offset: &str| Calls::new(offset.to_string(), CallType::Direct);
+        store
+            .create_calls_batch(&[
+                (&main.uid, &helper.uid, call("0x1004")),
+                (&main.uid, &helper.uid, call("0x1010")),
+                (&main.uid, &helper.uid, call("0x1004")),
+                (&helper.uid, &sleep.uid, call("0x1104")),
+            ])
+            .await
+            .unwrap();
+        store.save().unwrap();
+
+        let store = EmbeddedStore::open(&path).unwrap();
+        let found = store.query_binary_by_hash("CD34").await.unwrap().unwrap();
+        assert_eq!(found.filename, "sample.exe");
+        assert!(found.first_imported.is_some());
+        assert_eq!(store.query_binary_functions("ab12").await.unwrap().len(), 2);
+
+        let imports = store.query_binary_imports("ab12").await.unwrap();
+        assert_eq!(imports.len(), 1);
+        assert_eq!(imports[0].library, "kernel32.dll");
+        assert_eq!(imports[0].apis, vec!["Sleep"]);
+
+        let (functions, links) = store.query_binary_call_graph("ab12").await.unwrap();
+        assert_eq!(functions.len(), 3);
+        assert_eq!(links.len(), 2);
+        let main_link = links.iter().find(|link| link.from_uid == main.uid).unwrap();
+        assert_eq!(main_link.count, 2);
+        assert_eq!(main_link.offsets, vec!["0x1004", "0x1010"]);
+
+        let subgraph = store.subgraph("ab12").unwrap();
+        assert_eq!(subgraph.functions.len(), 2);
+        assert_eq!(subgraph.calls.len(), 2);
+        assert!(store.subgraph("ef56").is_none());
+
+        // A re-import without `helper` drops it and both calls touching it
+        let stale = BinarySubgraph {
+            functions: [helper.uid.clone()].into_iter().collect(),
+            strings: Default::default(),
+            calls: [(helper.uid.clone(), sleep.uid.clone())]
+                .into_iter()
+                .collect(),
+        };
+        store.prune("ab12", &stale);
+        let (functions, links) = store.query_binary_call_graph("ab12").await.unwrap();
+        assert_eq!(functions.len(), 2);
+        assert!(links.is_empty());
+        assert!(store.subgraph("ab12").unwrap().calls.is_empty());
+    }
+}
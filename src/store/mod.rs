@@ -0,0 +1,200 @@
+//! Storage behind imports and the core binary queries
+//!
+//! Neo4j, through [`GraphImporter`], stores the full graph. The embedded backend keeps
+//! binaries, functions, imports, exports and calls in a local file so small
+//! investigations run without a database server.
+
+pub mod embedded;
+
+pub use embedded::EmbeddedStore;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+
+use crate::models::{Binary, CallGraphLink, Calls, Function, ImportGroup, Library, Resource};
+use crate::neo4j::{BinaryFilter, GraphImporter};
+
+/// Where the graph is kept, from `--backend` or `backend` in the config
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    /// Neo4j server at `neo4j_uri`
+    #[default]
+    Neo4j,
+    /// Local file at `embedded_path`; supports importing and the core binary queries
+    Embedded,
+}
+
+/// Writes of the core import (binary, functions, imports, exports and calls) and the
+/// queries answered from them
+pub trait GraphStore: Send + Sync {
+    fn import_binary(&self, binary: &Binary) -> impl Future<Output = Result<()>> + Send;
+
+    /// Merge `functions` and link them to their binary with CONTAINS
+    fn import_functions_batch(
+        &self,
+        binary_hash: &str,
+        functions: &[Function],
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// Merge a function without linking it to a binary (imports and exports)
+    fn import_function(&self, function: &Function) -> impl Future<Output = Result<()>> + Send;
+
+    fn import_library(&self, library: &Library) -> impl Future<Output = Result<()>> + Send;
+
+    /// Link a binary to a library it imports from (IMPORTS_LIBRARY)
+    fn create_imports_relationship(
+        &self,
+        binary_hash: &str,
+        library_name: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    fn create_belongs_to_relationship(
+        &self,
+        function_uid: &str,
+        library_name: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// Link a binary to an import function with its import address (IMPORTS)
+    fn create_imports_function_relationship_with_address(
+        &self,
+        binary_hash: &str,
+        function_uid: &str,
+        address: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// Record call sites on CALLS edges; each call is `(caller_uid, callee_uid, call)`
+    fn create_calls_batch(
+        &self,
+        calls: &[(&str, &str, Calls)],
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    fn query_binaries(
+        &self,
+        filter: &BinaryFilter<'_>,
+        limit: usize,
+    ) -> impl Future<Output = Result<Vec<Binary>>> + Send;
+
+    /// Binary by any of its stored hashes (SHA-256, MD5, SHA-1, ssdeep or TLSH)
+    fn query_binary_by_hash(
+        &self,
+        hash: &str,
+    ) -> impl Future<Output = Result<Option<Binary>>> + Send;
+
+    /// Binary with this hash or a filename containing it
+    fn query_binary_info(
+        &self,
+        binary_name: &str,
+    ) -> impl Future<Output = Result<Option<Binary>>> + Send;
+
+    fn query_binary_functions(
+        &self,
+        binary_hash: &str,
+    ) -> impl Future<Output = Result<Vec<Function>>> + Send;
+
+    /// Imported APIs of a binary grouped by library, both sorted by name
+    fn query_binary_imports(
+        &self,
+        binary_hash: &str,
+    ) -> impl Future<Output = Result<Vec<ImportGroup>>> + Send;
+
+    /// Contained and imported functions of a binary with the CALLS edges between them
+    fn query_binary_call_graph(
+        &self,
+        binary_hash: &str,
+    ) -> impl Future<Output = Result<(Vec<Function>, Vec<CallGraphLink>)>> + Send;
+
+    fn query_binary_resources(
+        &self,
+        binary_hash: &str,
+    ) -> impl Future<Output = Result<Vec<Resource>>> + Send;
+}
+
+impl GraphStore for GraphImporter {
+    async fn import_binary(&self, binary: &Binary) -> Result<()> {
+        GraphImporter::import_binary(self, binary).await
+    }
+
+    async fn import_functions_batch(
+        &self,
+        binary_hash: &str,
+        functions: &[Function],
+    ) -> Result<()> {
+        GraphImporter::import_functions_batch(self, binary_hash, functions).await
+    }
+
+    async fn import_function(&self, function: &Function) -> Result<()> {
+        GraphImporter::import_function(self, function).await
+    }
+
+    async fn import_library(&self, library: &Library) -> Result<()> {
+        GraphImporter::import_library(self, library).await
+    }
+
+    async fn create_imports_relationship(
+        &self,
+        binary_hash: &str,
+        library_name: &str,
+    ) -> Result<()> {
+        GraphImporter::create_imports_relationship(self, binary_hash, library_name).await
+    }
+
+    async fn create_belongs_to_relationship(
+        &self,
+        function_uid: &str,
+        library_name: &str,
+    ) -> Result<()> {
+        GraphImporter::create_belongs_to_relationship(self, function_uid, library_name).await
+    }
+
+    async fn create_imports_function_relationship_with_address(
+        &self,
+        binary_hash: &str,
+        function_uid: &str,
+        address: &str,
+    ) -> Result<()> {
+        GraphImporter::create_imports_function_relationship_with_address(
+            self,
+            binary_hash,
+            function_uid,
+            address,
+        )
+        .await
+    }
+
+    async fn create_calls_batch(&self, calls: &[(&str, &str, Calls)]) -> Result<()> {
+        GraphImporter::create_calls_batch(self, calls).await
+    }
+
+    async fn query_binaries(&self, filter: &BinaryFilter<'_>, limit: usize) -> Result<Vec<Binary>> {
+        GraphImporter::query_binaries(self, filter, limit).await
+    }
+
+    async fn query_binary_by_hash(&self, hash: &str) -> Result<Option<Binary>> {
+        GraphImporter::query_binary_by_hash(self, hash).await
+    }
+
+    async fn query_binary_info(&self, binary_name: &str) -> Result<Option<Binary>> {
+        GraphImporter::query_binary_info(self, binary_name).await
+    }
+
+    async fn query_binary_functions(&self, binary_hash: &str) -> Result<Vec<Function>> {
+        GraphImporter::query_binary_functions(self, binary_hash).await
+    }
+
+    async fn query_binary_imports(&self, binary_hash: &str) -> Result<Vec<ImportGroup>> {
+        GraphImporter::query_binary_imports(self, binary_hash).await
+    }
+
+    async fn query_binary_call_graph(
+        &self,
+        binary_hash: &str,
+    ) -> Result<(Vec<Function>, Vec<CallGraphLink>)> {
+        GraphImporter::query_binary_call_graph(self, binary_hash).await
+    }
+
+    async fn query_binary_resources(&self, binary_hash: &str) -> Result<Vec<Resource>> {
+        GraphImporter::query_binary_resources(self, binary_hash).await
+    }
+}
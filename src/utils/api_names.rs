@@ -0,0 +1,154 @@
+//! Canonical names of imported APIs
+//!
+//! `CreateFileA`, `CreateFileW` and `ws2_32!ord_23` style imports fragment API
+//! clustering. The canonical name drops the ANSI/wide suffix and resolves ordinals of
+//! common DLLs through the bundled export tables below.
+
+/// Exports by ordinal of DLLs commonly imported by ordinal
+const ORDINAL_EXPORTS: &[(&str, &[(u16, &str)])] = &[
+    ("ws2_32", WINSOCK_ORDINALS),
+    ("wsock32", WINSOCK_ORDINALS),
+    (
+        "oleaut32",
+        &[
+            (2, "SysAllocString"),
+            (3, "SysReAllocString"),
+            (4, "SysAllocStringLen"),
+            (5, "SysReAllocStringLen"),
+            (6, "SysFreeString"),
+            (7, "SysStringLen"),
+            (8, "VariantInit"),
+            (9, "VariantClear"),
+            (10, "VariantCopy"),
+            (11, "VariantCopyInd"),
+            (12, "VariantChangeType"),
+            (149, "SysStringByteLen"),
+            (150, "SysAllocStringByteLen"),
+        ],
+    ),
+];
+
+const WINSOCK_ORDINALS: &[(u16, &str)] = &[
+    (1, "accept"),
+    (2, "bind"),
+    (3, "closesocket"),
+    (4, "connect"),
+    (5, "getpeername"),
+    (6, "getsockname"),
+    (7, "getsockopt"),
+    (8, "htonl"),
+    (9, "htons"),
+    (10, "ioctlsocket"),
+    (11, "inet_addr"),
+    (12, "inet_ntoa"),
+    (13, "listen"),
+    (14, "ntohl"),
+    (15, "ntohs"),
+    (16, "recv"),
+    (17, "recvfrom"),
+    (18, "select"),
+    (19, "send"),
+    (20, "sendto"),
+    (21, "setsockopt"),
+    (22, "shutdown"),
+    (23, "socket"),
+    (51, "gethostbyaddr"),
+    (52, "gethostbyname"),
+    (53, "getprotobyname"),
+    (54, "getprotobynumber"),
+    (55, "getservbyname"),
+    (56, "getservbyport"),
+    (57, "gethostname"),
+    (111, "WSAGetLastError"),
+    (112, "WSASetLastError"),
+    (115, "WSAStartup"),
+    (116, "WSACleanup"),
+    (151, "__WSAFDIsSet"),
+];
+
+/// Canonical name of an API imported from `library`
+///
+/// Ordinal imports (`ord_23`, `Ordinal_23`, `#23`) of known DLLs are resolved to the
+/// export name, then a trailing `A`/`W` character-set suffix is removed.
+pub fn canonical_api_name(library: &str, name: &str) -> String {
+    let resolved = ordinal(name)
+        .and_then(|ordinal| ordinal_export(library, ordinal))
+        .unwrap_or(name);
+
+    strip_charset_suffix(resolved).to_string()
+}
+
+fn ordinal(name: &str) -> Option<u16> {
+    let lower = name.to_ascii_lowercase();
+    ["ordinal_", "ordinal", "ord_", "ord", "#"]
+        .iter()
+        .find_map(|prefix| lower.strip_prefix(prefix))
+        .and_then(|digits| digits.parse().ok())
+}
+
+fn ordinal_export(library: &str, ordinal: u16) -> Option<&'static str> {
+    let library = library.to_ascii_lowercase();
+    let module = library.strip_suffix(".dll").unwrap_or(&library);
+
+    ORDINAL_EXPORTS
+        .iter()
+        .find(|(dll, _)| *dll == module)?
+        .1
+        .iter()
+        .find(|(number, _)| *number == ordinal)
+        .map(|(_, export)| *export)
+}
+
+/// `CreateFileW` -> `CreateFile`; the suffix must follow a lowercase letter or digit
+fn strip_charset_suffix(name: &str) -> &str {
+    let bytes = name.as_bytes();
+    match bytes {
+        [.., before, b'A' | b'W']
+            if bytes.len() > 2 && (before.is_ascii_lowercase() || before.is_ascii_digit()) =>
+        {
+            &name[..name.len() - 1]
+        }
+        _ => name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_api_name() {
+        assert_eq!(
+            canonical_api_name("kernel32.dll", "CreateFileA"),
+            "CreateFile"
+        );
+        assert_eq!(
+            canonical_api_name("kernel32.dll", "CreateFileW"),
+            "CreateFile"
+        );
+        assert_eq!(
+            canonical_api_name("user32.dll", "CreateWindowExW"),
+            "CreateWindowEx"
+        );
+        assert_eq!(
+            canonical_api_name("kernel32.dll", "GetTempPath2W"),
+            "GetTempPath2"
+        );
+        assert_eq!(canonical_api_name("ws2_32.dll", "ord_23"), "socket");
+        assert_eq!(
+            canonical_api_name("WSOCK32.DLL", "Ordinal_115"),
+            "WSAStartup"
+        );
+        assert_eq!(canonical_api_name("oleaut32", "#6"), "SysFreeString");
+        assert_eq!(canonical_api_name("unknown.dll", "ord_7"), "ord_7");
+        assert_eq!(
+            canonical_api_name("kernel32.dll", "VirtualAlloc"),
+            "VirtualAlloc"
+        );
+        assert_eq!(
+            canonical_api_name("ntdll.dll", "NtQueryInformationProcess"),
+            "NtQueryInformationProcess"
+        );
+        assert_eq!(canonical_api_name("gdi32.dll", "AW"), "AW");
+    }
+}
@@ -0,0 +1,146 @@
+//! Well-known cryptographic constants
+//!
+//! Functions whose constant list (immediates and table words given by the extractor)
+//! holds enough of an algorithm's markers are tagged with that algorithm. MD5 and
+//! SHA-1 share their initial state, so only the values unique to each are markers.
+
+/// Algorithm tag, marker constants and how many distinct markers must be present
+const SIGNATURES: &[(&str, &[u64], usize)] = &[
+    (
+        "aes",
+        &[
+            // S-box, inverse S-box and T-table words
+            0x7b77_7c63,
+            0xc56f_6bf2,
+            0x2b67_0130,
+            0x76ab_d7fe,
+            0xd56a_0952,
+            0x38a5_3630,
+            0xc663_63a5,
+            0xa563_63c6,
+            0xf87c_7c84,
+            0x847c_7cf8,
+        ],
+        2,
+    ),
+    (
+        "sha1",
+        &[
+            0xc3d2_e1f0,
+            0x5a82_7999,
+            0x6ed9_eba1,
+            0x8f1b_bcdc,
+            0xca62_c1d6,
+        ],
+        2,
+    ),
+    (
+        "md5",
+        &[0xd76a_a478, 0xe8c7_b756, 0x2420_70db, 0xc1bd_ceee],
+        2,
+    ),
+    (
+        "sha256",
+        &[
+            0x6a09_e667,
+            0xbb67_ae85,
+            0x3c6e_f372,
+            0xa54f_f53a,
+            0x428a_2f98,
+            0x7137_4491,
+            0xb5c0_fbcf,
+            0xe9b5_dba5,
+        ],
+        2,
+    ),
+    (
+        "sha512",
+        &[
+            0x6a09_e667_f3bc_c908,
+            0xbb67_ae85_84ca_a73b,
+            0x428a_2f98_d728_ae22,
+            0x7137_4491_23ef_65cd,
+        ],
+        2,
+    ),
+    // Reflected and normal polynomials, first table entries
+    (
+        "crc32",
+        &[
+            0xedb8_8320,
+            0x04c1_1db7,
+            0x7707_3096,
+            0xee0e_612c,
+            0x9909_51ba,
+        ],
+        1,
+    ),
+    // Key schedule filling the state four bytes at a time
+    (
+        "rc4",
+        &[
+            0x0302_0100,
+            0x0706_0504,
+            0x0b0a_0908,
+            0x0f0e_0d0c,
+            0x0404_0404,
+        ],
+        2,
+    ),
+    (
+        "blowfish",
+        &[0x243f_6a88, 0x85a3_08d3, 0x1319_8a2e, 0x0370_7344],
+        2,
+    ),
+    // "expand 32-byte k", shared by Salsa20 and ChaCha
+    (
+        "chacha",
+        &[0x6170_7865, 0x3320_646e, 0x7962_2d32, 0x6b20_6574],
+        2,
+    ),
+    ("tea", &[0x9e37_79b9, 0x61c8_8647], 1),
+];
+
+/// Algorithms whose markers appear among `constants`, in table order
+pub fn detect_crypto(constants: &[u64]) -> Vec<String> {
+    SIGNATURES
+        .iter()
+        .filter(|(_, markers, min_matches)| {
+            markers
+                .iter()
+                .filter(|marker| constants.contains(marker))
+                .count()
+                >= *min_matches
+        })
+        .map(|(algorithm, _, _)| algorithm.to_string())
+        .collect()
+}
+
+/// Tags `detect_crypto` can produce
+pub fn known_algorithms() -> impl Iterator<Item = &'static str> {
+    SIGNATURES.iter().map(|(algorithm, _, _)| *algorithm)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_crypto() {
+        assert_eq!(
+            detect_crypto(&[0x6745_2301, 0xefcd_ab89, 0xc3d2_e1f0, 0x5a82_7999]),
+            vec!["sha1"]
+        );
+        // The shared MD5/SHA-1 initial state alone is not enough
+        assert!(detect_crypto(&[0x6745_2301, 0xefcd_ab89, 0x98ba_dcfe]).is_empty());
+        assert_eq!(
+            detect_crypto(&[0xedb8_8320, 0x0302_0100, 0x0404_0404, 1, 0x100]),
+            vec!["crc32", "rc4"]
+        );
+        assert_eq!(
+            detect_crypto(&[0x7b77_7c63, 0xc56f_6bf2, 0x7b77_7c63]),
+            vec!["aes"]
+        );
+        assert!(detect_crypto(&[0x7b77_7c63, 0x7b77_7c63]).is_empty());
+    }
+}
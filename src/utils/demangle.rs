@@ -0,0 +1,130 @@
+//! Demangling of C++ (Itanium and MSVC) and Rust symbol names
+
+/// Demangled form of `name`, or `None` if it is not a recognized mangled symbol
+///
+/// Handles Rust legacy (`_ZN...17h<hash>E`) and v0 (`_R...`) symbols, Itanium C++
+/// (`_Z...`, with the extra Mach-O underscore) and the qualified-name part of MSVC
+/// symbols (`?name@scope@@...`). MSVC argument types are not decoded.
+pub fn demangle(name: &str) -> Option<String> {
+    let demangled = if name.starts_with('?') {
+        demangle_msvc(name)
+    } else {
+        demangle_rust(name).or_else(|| demangle_itanium(name))
+    }?;
+
+    (demangled != name).then_some(demangled)
+}
+
+fn demangle_rust(name: &str) -> Option<String> {
+    // Itanium C++ names without a trailing Rust hash are left to cpp_demangle
+    if name.contains("_Z") && !has_rust_hash(name) {
+        return None;
+    }
+    let stripped = name
+        .strip_prefix('_')
+        .filter(|n| n.starts_with("_Z") || n.starts_with("_R"));
+    let symbol = rustc_demangle::try_demangle(stripped.unwrap_or(name)).ok()?;
+    Some(format!("{:#}", symbol))
+}
+
+fn has_rust_hash(name: &str) -> bool {
+    name.trim_end_matches('E')
+        .rsplit_once("17h")
+        .is_some_and(|(_, hash)| hash.len() == 16 && hash.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+fn demangle_itanium(name: &str) -> Option<String> {
+    let mangled = match name.strip_prefix('_') {
+        Some(rest) if rest.starts_with("_Z") => rest,
+        _ if name.starts_with("_Z") => name,
+        _ => return None,
+    };
+    cpp_demangle::Symbol::new(mangled).ok()?.demangle().ok()
+}
+
+/// Qualified name of an MSVC symbol, innermost scope last (e.g. `ns::Class::method`)
+fn demangle_msvc(name: &str) -> Option<String> {
+    let body = name.strip_prefix('?')?;
+
+    // Special names: only constructors and destructors are decoded
+    let (special, body) = match body.strip_prefix('?') {
+        Some(rest) => match rest.as_bytes().first()? {
+            b'0' => (Some(false), &rest[1..]),
+            b'1' => (Some(true), &rest[1..]),
+            _ => return None,
+        },
+        None => (None, body),
+    };
+
+    // Name fragments, innermost first, up to the `@` closing the qualified name
+    let mut fragments: Vec<&str> = Vec::new();
+    let mut rest = body;
+    while !rest.starts_with('@') {
+        let first = *rest.as_bytes().first()?;
+        if first.is_ascii_digit() {
+            // Back-reference to an earlier fragment
+            fragments.push(fragments.get((first - b'0') as usize)?);
+            rest = &rest[1..];
+        } else if first == b'?' {
+            // Templates and nested special names are not supported
+            return None;
+        } else {
+            let (fragment, after) = rest.split_once('@')?;
+            fragments.push(fragment);
+            rest = after;
+        }
+    }
+
+    match special {
+        // Constructors and destructors are named after their class, the innermost scope
+        Some(is_destructor) => {
+            let class = *fragments.first()?;
+            let name = if is_destructor {
+                format!("~{}", class)
+            } else {
+                class.to_string()
+            };
+            Some(join_scopes(&fragments, &name))
+        }
+        None => {
+            let (name, scopes) = fragments.split_first()?;
+            Some(join_scopes(scopes, name))
+        }
+    }
+}
+
+fn join_scopes(scopes: &[&str], name: &str) -> String {
+    let mut parts: Vec<&str> = scopes.iter().rev().copied().collect();
+    parts.push(name);
+    parts.join("::")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_demangle_schemes() {
+        assert_eq!(
+            demangle("_ZN7example4main17h3f0c0a1b2c3d4e5fE").as_deref(),
+            Some("example::main")
+        );
+        assert_eq!(demangle("_ZN3foo3barEv").as_deref(), Some("foo::bar()"));
+        assert_eq!(demangle("__ZN3foo3barEv").as_deref(), Some("foo::bar()"));
+        assert_eq!(
+            demangle("?CreateInstance@CFactory@ns@@QAEJPAUIUnknown@@ABU_GUID@@PAPAX@Z").as_deref(),
+            Some("ns::CFactory::CreateInstance")
+        );
+        assert_eq!(
+            demangle("??0CFactory@ns@@QAE@XZ").as_deref(),
+            Some("ns::CFactory::CFactory")
+        );
+        assert_eq!(
+            demangle("??1CFactory@@UAE@XZ").as_deref(),
+            Some("CFactory::~CFactory")
+        );
+        assert_eq!(demangle("?Release@?$CComPtr@UIUnknown@@@@QAEXXZ"), None);
+        assert_eq!(demangle("CreateFileW"), None);
+        assert_eq!(demangle("sub_401000"), None);
+    }
+}
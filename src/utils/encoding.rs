@@ -0,0 +1,102 @@
+/// Canonical name of a string encoding as reported by an extractor
+///
+/// Covers radare2 (`wide`, `utf16le`, `wide32`), IDA (`C_16`, `C_32`), Ghidra
+/// (`unicode`, `unicode32`), angr (`utf16`) and plain names such as `UTF-16LE`.
+/// Unknown names are kept lowercased.
+pub fn normalize_encoding(name: &str) -> String {
+    let key: String = name
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .collect::<String>()
+        .to_ascii_lowercase();
+    let canonical = match key.as_str() {
+        "ascii" | "c" | "char" | "string" | "cstring" | "terminatedcstring" => "ascii",
+        "utf8" => "utf8",
+        "utf16" | "utf16le" | "wide" | "wchar" | "widechar" | "unicode" | "unicode16" | "c16"
+        | "terminatedunicode" => "utf16le",
+        "utf16be" => "utf16be",
+        "utf32" | "utf32le" | "wide32" | "unicode32" | "c32" | "terminatedunicode32" => "utf32le",
+        "utf32be" => "utf32be",
+        _ => return name.trim().to_ascii_lowercase(),
+    };
+    canonical.to_string()
+}
+
+/// Decode wide text that reached the JSON in raw form, one character per byte with
+/// NULs between them (`"H\u0000i\u0000"`), so it hashes like its narrow twin
+///
+/// Text that is already decoded, or whose encoding is not a wide one, is returned
+/// unchanged. A terminator cut short by the extractor is tolerated.
+pub fn decode_wide(value: &str, encoding: &str) -> String {
+    let (width, little_endian) = match encoding {
+        "utf16le" => (2, true),
+        "utf16be" => (2, false),
+        "utf32le" => (4, true),
+        "utf32be" => (4, false),
+        _ => return value.to_string(),
+    };
+    if !value.contains('\0') || value.chars().any(|c| c as u32 > 0xFF) {
+        return value.to_string();
+    }
+
+    let mut bytes: Vec<u8> = value.chars().map(|c| c as u8).collect();
+    bytes.resize(bytes.len().div_ceil(width) * width, 0);
+
+    let decoded = if width == 2 {
+        let units: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|unit| {
+                let unit = [unit[0], unit[1]];
+                if little_endian {
+                    u16::from_le_bytes(unit)
+                } else {
+                    u16::from_be_bytes(unit)
+                }
+            })
+            .collect();
+        String::from_utf16_lossy(&units)
+    } else {
+        bytes
+            .chunks_exact(4)
+            .map(|unit| {
+                let unit = [unit[0], unit[1], unit[2], unit[3]];
+                let code = if little_endian {
+                    u32::from_le_bytes(unit)
+                } else {
+                    u32::from_be_bytes(unit)
+                };
+                char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER)
+            })
+            .collect()
+    };
+    decoded.trim_end_matches('\0').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_encoding() {
+        assert_eq!(normalize_encoding("UTF-16LE"), "utf16le");
+        assert_eq!(normalize_encoding("wide"), "utf16le");
+        assert_eq!(normalize_encoding("C_16"), "utf16le");
+        assert_eq!(normalize_encoding("unicode32"), "utf32le");
+        assert_eq!(normalize_encoding("C"), "ascii");
+        assert_eq!(normalize_encoding("Base64"), "base64");
+    }
+
+    #[test]
+    fn test_decode_wide() {
+        assert_eq!(decode_wide("c\0m\0d\0.\0e\0x\0e\0", "utf16le"), "cmd.exe");
+        assert_eq!(decode_wide("c\0m\0d", "utf16le"), "cmd");
+        assert_eq!(decode_wide("\0c\0m\0d", "utf16be"), "cmd");
+        assert_eq!(decode_wide("h\0\0\0i\0\0\0", "utf32le"), "hi");
+        assert_eq!(decode_wide("cmd.exe", "utf16le"), "cmd.exe");
+        assert_eq!(
+            decode_wide("\u{44}\0\u{4e2d}", "utf16le"),
+            "\u{44}\0\u{4e2d}"
+        );
+        assert_eq!(decode_wide("a\0b\0", "ascii"), "a\0b\0");
+    }
+}
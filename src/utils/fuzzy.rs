@@ -0,0 +1,212 @@
+const SPAMSUM_LENGTH: u32 = 64;
+const ROLLING_WINDOW: usize = 7;
+const MIN_BLOCKSIZE: u64 = 3;
+
+/// ssdeep match score between two `blocksize:hash1:hash2` digests (0 to 100)
+///
+/// Returns `None` when either digest cannot be parsed.
+pub fn ssdeep_compare(a: &str, b: &str) -> Option<u32> {
+    let (block_a, a1, a2) = parse_ssdeep(a)?;
+    let (block_b, b1, b2) = parse_ssdeep(b)?;
+
+    if block_a != block_b && block_a != block_b * 2 && block_b != block_a * 2 {
+        return Some(0);
+    }
+
+    let (a1, a2) = (eliminate_sequences(a1), eliminate_sequences(a2));
+    let (b1, b2) = (eliminate_sequences(b1), eliminate_sequences(b2));
+
+    if block_a == block_b && a1 == b1 {
+        return Some(100);
+    }
+
+    let score = if block_a == block_b {
+        score_strings(&a1, &b1, block_a).max(score_strings(&a2, &b2, block_a * 2))
+    } else if block_a == block_b * 2 {
+        score_strings(&a1, &b2, block_a)
+    } else {
+        score_strings(&a2, &b1, block_b)
+    };
+
+    Some(score)
+}
+
+fn parse_ssdeep(digest: &str) -> Option<(u64, &str, &str)> {
+    let mut parts = digest.trim().splitn(3, ':');
+    let block_size = parts.next()?.parse().ok()?;
+    let first = parts.next()?;
+    // A trailing `,"filename"` is part of the ssdeep tool output, not the digest
+    let second = parts.next()?.split(',').next()?;
+    Some((block_size, first, second))
+}
+
+/// Collapse runs of more than three identical characters, as ssdeep does before scoring
+fn eliminate_sequences(hash: &str) -> Vec<u8> {
+    let bytes = hash.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    for (i, &byte) in bytes.iter().enumerate() {
+        if i < 3 || byte != bytes[i - 1] || byte != bytes[i - 2] || byte != bytes[i - 3] {
+            out.push(byte);
+        }
+    }
+    out
+}
+
+fn score_strings(a: &[u8], b: &[u8], block_size: u64) -> u32 {
+    if a.len() < ROLLING_WINDOW
+        || b.len() < ROLLING_WINDOW
+        || !a
+            .windows(ROLLING_WINDOW)
+            .any(|window| b.windows(ROLLING_WINDOW).any(|other| other == window))
+    {
+        return 0;
+    }
+
+    let total_len = (a.len() + b.len()) as u32;
+    let distance = edit_distance(a, b) * SPAMSUM_LENGTH / total_len;
+    let distance = 100 * distance / SPAMSUM_LENGTH;
+    if distance >= 100 {
+        return 0;
+    }
+    let score = 100 - distance;
+
+    // Small block sizes cannot justify a high score on short digests
+    let cap_limit = (99 + ROLLING_WINDOW as u64) / ROLLING_WINDOW as u64 * MIN_BLOCKSIZE;
+    if block_size >= cap_limit {
+        return score;
+    }
+    let cap = (block_size / MIN_BLOCKSIZE) as u32 * a.len().min(b.len()) as u32;
+    score.min(cap)
+}
+
+/// Edit distance with unit insert/delete cost and a substitution cost of two
+fn edit_distance(a: &[u8], b: &[u8]) -> u32 {
+    let mut previous: Vec<u32> = (0..=b.len() as u32).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, &ca) in a.iter().enumerate() {
+        current[0] = i as u32 + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = previous[j] + if ca == cb { 0 } else { 2 };
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+/// TLSH distance between two digests (0 means identical, lower is more similar)
+///
+/// Accepts the standard 72-character `T1` form or the 70-character form without the
+/// version prefix. Returns `None` when either digest cannot be parsed.
+pub fn tlsh_distance(a: &str, b: &str) -> Option<u32> {
+    let a = parse_tlsh(a)?;
+    let b = parse_tlsh(b)?;
+
+    let mut diff = 0;
+
+    match mod_diff(a.lvalue, b.lvalue, 256) {
+        0 => {}
+        1 => diff += 1,
+        d => diff += d * 12,
+    }
+
+    for (x, y) in [(a.q1, b.q1), (a.q2, b.q2)] {
+        let d = mod_diff(x, y, 16);
+        diff += if d <= 1 { d } else { (d - 1) * 12 };
+    }
+
+    if a.checksum != b.checksum {
+        diff += 1;
+    }
+
+    for (x, y) in a.body.iter().zip(&b.body) {
+        for shift in [0, 2, 4, 6] {
+            let d = ((x >> shift) & 3).abs_diff((y >> shift) & 3) as u32;
+            diff += if d == 3 { 6 } else { d };
+        }
+    }
+
+    Some(diff)
+}
+
+struct TlshDigest {
+    checksum: u8,
+    lvalue: u32,
+    q1: u32,
+    q2: u32,
+    body: Vec<u8>,
+}
+
+fn parse_tlsh(digest: &str) -> Option<TlshDigest> {
+    let digest = digest.trim();
+    let hex = digest
+        .strip_prefix("T1")
+        .or_else(|| digest.strip_prefix("t1"))
+        .unwrap_or(digest);
+    if hex.len() != 70 || !hex.is_ascii() {
+        return None;
+    }
+
+    let bytes = (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+
+    // Header bytes are hex encoded with their nibbles swapped
+    let swap = |byte: u8| byte.rotate_left(4);
+    let q = swap(bytes[2]);
+
+    Some(TlshDigest {
+        checksum: swap(bytes[0]),
+        lvalue: swap(bytes[1]) as u32,
+        q1: (q & 0x0F) as u32,
+        q2: (q >> 4) as u32,
+        body: bytes[3..].to_vec(),
+    })
+}
+
+fn mod_diff(x: u32, y: u32, range: u32) -> u32 {
+    let (dl, dr) = if y > x {
+        (y - x, x + range - y)
+    } else {
+        (x - y, y + range - x)
+    };
+    dl.min(dr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SSDEEP: &str = "96:s4Ud1Lj96tHHlZDrwciQmA+4uy1I0G4HYuL8N3TzS8QsO/wqWXLcMSx:sF1LjEtHHlZDrJzrhuyZvHYm8tKp/RWO";
+    const TLSH: &str = "T1A5D0220E2A3C0C30A308000000C000F008800C800000000000082000C00000C0000000";
+
+    #[test]
+    fn test_ssdeep_compare() {
+        assert_eq!(ssdeep_compare(SSDEEP, SSDEEP), Some(100));
+
+        let modified = SSDEEP
+            .replace("s4Ud1Lj96tHH", "s4Ud1LjX6tHH")
+            .replace("sF1LjEtHHlZ", "sF1LjXtHHlZ");
+        let score = ssdeep_compare(SSDEEP, &modified).unwrap();
+        assert!(score > 50 && score < 100, "score {}", score);
+
+        // Block sizes 96 and 384 are not comparable
+        let distant = SSDEEP.replacen("96:", "384:", 1);
+        assert_eq!(ssdeep_compare(SSDEEP, &distant), Some(0));
+        assert_eq!(ssdeep_compare(SSDEEP, "not a digest"), None);
+    }
+
+    #[test]
+    fn test_tlsh_distance() {
+        assert_eq!(tlsh_distance(TLSH, TLSH), Some(0));
+        assert_eq!(tlsh_distance(TLSH, &TLSH[2..]), Some(0));
+
+        // Body byte 0x0C -> 0x0D changes one bit pair by one step
+        let changed = TLSH.replacen("2A3C0C", "2A3C0D", 1);
+        assert_eq!(tlsh_distance(TLSH, &changed), Some(1));
+        assert_eq!(tlsh_distance(TLSH, "T1ABC"), None);
+    }
+}
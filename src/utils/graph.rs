@@ -0,0 +1,207 @@
+use std::collections::HashSet;
+
+/// Strongly connected components of a directed graph (iterative Tarjan)
+///
+/// Nodes are `0..node_count`; components are returned in reverse topological order
+/// with their members in discovery order.
+pub fn strongly_connected_components(
+    node_count: usize,
+    edges: &[(usize, usize)],
+) -> Vec<Vec<usize>> {
+    let mut adjacency = vec![Vec::new(); node_count];
+    for &(from, to) in edges {
+        adjacency[from].push(to);
+    }
+
+    let mut index = vec![usize::MAX; node_count];
+    let mut lowlink = vec![0; node_count];
+    let mut on_stack = vec![false; node_count];
+    let mut stack = Vec::new();
+    let mut components = Vec::new();
+    let mut next_index = 0;
+
+    for root in 0..node_count {
+        if index[root] != usize::MAX {
+            continue;
+        }
+
+        // Each frame is (node, position of the next neighbor to visit)
+        let mut frames = vec![(root, 0)];
+        index[root] = next_index;
+        lowlink[root] = next_index;
+        next_index += 1;
+        stack.push(root);
+        on_stack[root] = true;
+
+        while let Some(&mut (node, ref mut next)) = frames.last_mut() {
+            if let Some(&neighbor) = adjacency[node].get(*next) {
+                *next += 1;
+                if index[neighbor] == usize::MAX {
+                    index[neighbor] = next_index;
+                    lowlink[neighbor] = next_index;
+                    next_index += 1;
+                    stack.push(neighbor);
+                    on_stack[neighbor] = true;
+                    frames.push((neighbor, 0));
+                } else if on_stack[neighbor] {
+                    lowlink[node] = lowlink[node].min(index[neighbor]);
+                }
+                continue;
+            }
+
+            frames.pop();
+            if let Some(&(parent, _)) = frames.last() {
+                lowlink[parent] = lowlink[parent].min(lowlink[node]);
+            }
+
+            if lowlink[node] == index[node] {
+                let mut component = Vec::new();
+                while let Some(member) = stack.pop() {
+                    on_stack[member] = false;
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                component.reverse();
+                components.push(component);
+            }
+        }
+    }
+
+    components
+}
+
+/// Call distance of every node from the nearest root (breadth-first), `None` if unreachable
+pub fn shortest_depths(
+    node_count: usize,
+    edges: &[(usize, usize)],
+    roots: &[usize],
+) -> Vec<Option<usize>> {
+    let mut adjacency = vec![Vec::new(); node_count];
+    for &(from, to) in edges {
+        adjacency[from].push(to);
+    }
+
+    let mut depths = vec![None; node_count];
+    let mut queue = std::collections::VecDeque::new();
+    for &root in roots {
+        if depths[root].is_none() {
+            depths[root] = Some(0);
+            queue.push_back(root);
+        }
+    }
+
+    while let Some(node) = queue.pop_front() {
+        let next_depth = depths[node].map(|d| d + 1);
+        for &neighbor in &adjacency[node] {
+            if depths[neighbor].is_none() {
+                depths[neighbor] = next_depth;
+                queue.push_back(neighbor);
+            }
+        }
+    }
+
+    depths
+}
+
+/// Number of distinct `targets` reachable from each node through at least one edge
+///
+/// Reachability is propagated over the component graph, so every member of a cycle
+/// shares the same result.
+pub fn reachable_counts(
+    node_count: usize,
+    edges: &[(usize, usize)],
+    targets: &[bool],
+) -> Vec<usize> {
+    let components = strongly_connected_components(node_count, edges);
+
+    let mut component_of = vec![0; node_count];
+    for (c, members) in components.iter().enumerate() {
+        for &member in members {
+            component_of[member] = c;
+        }
+    }
+
+    let mut successors = vec![Vec::new(); components.len()];
+    let mut cyclic = vec![false; components.len()];
+    for &(from, to) in edges {
+        let (cf, ct) = (component_of[from], component_of[to]);
+        if cf == ct {
+            cyclic[cf] = true;
+        } else {
+            successors[cf].push(to);
+        }
+    }
+
+    // Tarjan emits a component only after every component reachable from it
+    let mut reachable: Vec<HashSet<usize>> = Vec::with_capacity(components.len());
+    for (c, members) in components.iter().enumerate() {
+        let mut set = HashSet::new();
+        if cyclic[c] {
+            set.extend(members.iter().copied().filter(|&m| targets[m]));
+        }
+        for &to in &successors[c] {
+            if targets[to] {
+                set.insert(to);
+            }
+            set.extend(reachable[component_of[to]].iter().copied());
+        }
+        reachable.push(set);
+    }
+
+    (0..node_count)
+        .map(|node| reachable[component_of[node]].len())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(mut components: Vec<Vec<usize>>) -> Vec<Vec<usize>> {
+        for component in &mut components {
+            component.sort_unstable();
+        }
+        components.sort();
+        components
+    }
+
+    #[test]
+    fn test_mutual_recursion_groups() {
+        // 0 <-> 1, 2 -> 3 -> 4 -> 2, 1 -> 2, 5 isolated
+        let edges = [(0, 1), (1, 0), (1, 2), (2, 3), (3, 4), (4, 2)];
+        let components = sorted(strongly_connected_components(6, &edges));
+
+        assert_eq!(components, vec![vec![0, 1], vec![2, 3, 4], vec![5]]);
+    }
+
+    #[test]
+    fn test_acyclic_graph_has_singleton_components() {
+        let edges = [(0, 1), (1, 2), (0, 2)];
+        let components = strongly_connected_components(3, &edges);
+
+        assert_eq!(components.len(), 3);
+        assert!(components.iter().all(|c| c.len() == 1));
+    }
+
+    #[test]
+    fn test_shortest_depths_from_roots() {
+        let edges = [(0, 1), (1, 2), (0, 2), (3, 3)];
+        let depths = shortest_depths(4, &edges, &[0]);
+
+        assert_eq!(depths, vec![Some(0), Some(1), Some(1), None]);
+    }
+
+    #[test]
+    fn test_reachable_counts_through_cycles() {
+        // 0 -> 1 <-> 2 -> 3 (target), 2 -> 4 (target), 5 -> 3
+        let edges = [(0, 1), (1, 2), (2, 1), (2, 3), (2, 4), (5, 3)];
+        let targets = [false, false, false, true, true, false];
+
+        assert_eq!(
+            reachable_counts(6, &edges, &targets),
+            vec![2, 2, 2, 0, 0, 1]
+        );
+    }
+}
@@ -0,0 +1,215 @@
+use std::net::{IpAddr, Ipv4Addr};
+
+use crate::models::{IndicatorKind, NamedObject, NetworkIndicator, ObjectKind};
+
+/// Top-level domains accepted for bare domain names found in strings
+///
+/// Kept short on purpose: most file names and symbol names contain dots, and only
+/// a known TLD separates `evil.ru` from `config.ini`.
+const KNOWN_TLDS: &[&str] = &[
+    "biz", "cc", "cn", "co", "com", "de", "eu", "fr", "info", "io", "jp", "kr", "me", "net", "nl",
+    "online", "org", "pw", "ru", "site", "su", "top", "tk", "tv", "uk", "us", "ws", "xyz",
+];
+
+/// Network indicators contained in an extracted string
+///
+/// URLs also yield their host as a Domain or IPAddress indicator. Private, loopback
+/// and other non-routable IPv4 addresses are ignored.
+pub fn extract_indicators(text: &str) -> Vec<NetworkIndicator> {
+    let mut indicators = Vec::new();
+
+    for token in text.split(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | '<' | '>')) {
+        let token = token.trim_matches(|c: char| matches!(c, ',' | ';' | '(' | ')' | '[' | ']'));
+        if token.is_empty() {
+            continue;
+        }
+
+        if let Some(url) = parse_url(token) {
+            indicators.push(NetworkIndicator::new(IndicatorKind::Url, url.0));
+            indicators.extend(classify_host(&url.1));
+        } else {
+            indicators.extend(classify_host(token.trim_end_matches('.')));
+        }
+    }
+
+    indicators.dedup();
+    indicators
+}
+
+/// Domain or IPAddress indicator for a host name, with an optional `:port` suffix
+pub fn classify_host(host: &str) -> Option<NetworkIndicator> {
+    let host = match host.rsplit_once(':') {
+        Some((name, port)) if !port.is_empty() && port.chars().all(|c| c.is_ascii_digit()) => name,
+        _ => host,
+    };
+
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        return is_routable(ip)
+            .then(|| NetworkIndicator::new(IndicatorKind::IpAddress, ip.to_string()));
+    }
+
+    let domain = host.to_lowercase();
+    is_domain(&domain).then(|| NetworkIndicator::new(IndicatorKind::Domain, domain))
+}
+
+/// Split `scheme://host/...` into the URL (trailing punctuation removed) and its host
+fn parse_url(token: &str) -> Option<(String, String)> {
+    let (scheme, rest) = token.split_once("://")?;
+    let scheme = scheme.to_lowercase();
+    if !matches!(scheme.as_str(), "http" | "https" | "ftp") {
+        return None;
+    }
+
+    let rest = rest.trim_end_matches(['.', ',', ';']);
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host = authority.rsplit('@').next()?;
+    if host.is_empty() {
+        return None;
+    }
+
+    Some((format!("{}://{}", scheme, rest), host.to_string()))
+}
+
+fn is_domain(name: &str) -> bool {
+    let labels: Vec<&str> = name.split('.').collect();
+    if labels.len() < 2 || name.len() > 253 {
+        return false;
+    }
+
+    let valid_labels = labels.iter().all(|label| {
+        !label.is_empty()
+            && label.len() <= 63
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    });
+
+    valid_labels && labels.last().is_some_and(|tld| KNOWN_TLDS.contains(tld))
+}
+
+fn is_routable(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            !(v4.is_private()
+                || v4.is_loopback()
+                || v4.is_link_local()
+                || v4.is_broadcast()
+                || v4.is_unspecified()
+                || v4.is_multicast()
+                || v4.is_documentation()
+                || v4 == Ipv4Addr::new(255, 255, 255, 0))
+        }
+        IpAddr::V6(v6) => !(v6.is_loopback() || v6.is_unspecified() || v6.is_multicast()),
+    }
+}
+
+/// Prefixes of named pipe paths, compared lowercased
+const PIPE_PREFIXES: [&str; 4] = [
+    "\\\\.\\pipe\\",
+    "\\\\?\\pipe\\",
+    "\\??\\pipe\\",
+    "\\device\\namedpipe\\",
+];
+
+/// Named pipe or mutex named by an extracted string
+///
+/// Strings cannot tell a mutex from an event or semaphore, so names in the `Global\`
+/// and `Local\` namespaces are taken as mutexes, by far their most common use.
+pub fn classify_named_object(text: &str) -> Option<NamedObject> {
+    let text = text.trim();
+    if let Some(object) = pipe(text) {
+        return Some(object);
+    }
+
+    let name = text.strip_prefix("\\BaseNamedObjects\\").unwrap_or(text);
+    let (namespace, rest) = name.split_once('\\')?;
+    let valid = matches!(namespace, "Global" | "Local")
+        && !rest.is_empty()
+        && rest.len() <= 260
+        && !rest.contains(char::is_whitespace);
+    valid.then(|| NamedObject::new(ObjectKind::Mutex, name))
+}
+
+/// Named object of a known kind reported by a sandbox, with its name normalized
+pub fn named_object(kind: ObjectKind, name: &str) -> Option<NamedObject> {
+    let name = name.trim();
+    if name.is_empty() {
+        return None;
+    }
+    if kind == ObjectKind::Pipe {
+        return Some(pipe(name).unwrap_or_else(|| NamedObject::new(kind, name.to_lowercase())));
+    }
+    let name = name.strip_prefix("\\BaseNamedObjects\\").unwrap_or(name);
+    Some(NamedObject::new(kind, name))
+}
+
+/// Pipe named by a `\\.\pipe\name` style path; pipe names are case-insensitive
+fn pipe(path: &str) -> Option<NamedObject> {
+    let lower = path.to_lowercase();
+    let name = PIPE_PREFIXES
+        .iter()
+        .find_map(|prefix| lower.strip_prefix(prefix))?;
+    (!name.is_empty()).then(|| NamedObject::new(ObjectKind::Pipe, name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(text: &str) -> Vec<(IndicatorKind, String)> {
+        extract_indicators(text)
+            .into_iter()
+            .map(|i| (i.kind, i.value))
+            .collect()
+    }
+
+    #[test]
+    fn test_extract_url_with_host() {
+        assert_eq!(
+            values("GET https://Update.Example.com/gate.php?id=1 HTTP/1.1"),
+            vec![
+                (
+                    IndicatorKind::Url,
+                    "https://Update.Example.com/gate.php?id=1".to_string()
+                ),
+                (IndicatorKind::Domain, "update.example.com".to_string()),
+            ]
+        );
+        assert_eq!(
+            values("http://45.33.12.7:8080/"),
+            vec![
+                (IndicatorKind::Url, "http://45.33.12.7:8080/".to_string()),
+                (IndicatorKind::IpAddress, "45.33.12.7".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ignores_file_names_and_private_addresses() {
+        assert!(values("kernel32.dll config.ini 6.1.7600.16385").is_empty());
+        assert!(values("192.168.56.1 127.0.0.1 0.0.0.0").is_empty());
+        assert_eq!(
+            values("connect to evil-c2.ru:443"),
+            vec![(IndicatorKind::Domain, "evil-c2.ru".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_classify_named_objects() {
+        assert_eq!(
+            classify_named_object("\\\\.\\pipe\\MSSE-1337-server"),
+            Some(NamedObject::new(ObjectKind::Pipe, "msse-1337-server"))
+        );
+        assert_eq!(
+            classify_named_object("Global\\{A1B2}-lock"),
+            Some(NamedObject::new(ObjectKind::Mutex, "Global\\{A1B2}-lock"))
+        );
+        assert_eq!(
+            named_object(ObjectKind::Mutex, "\\BaseNamedObjects\\Local\\xyz"),
+            Some(NamedObject::new(ObjectKind::Mutex, "Local\\xyz"))
+        );
+        assert_eq!(classify_named_object("Global\\"), None);
+        assert_eq!(classify_named_object("Local\\some text"), None);
+        assert_eq!(classify_named_object("C:\\Windows\\notepad.exe"), None);
+    }
+}
@@ -1 +1,11 @@
+pub mod api_names;
+pub mod crypto;
+pub mod demangle;
+pub mod encoding;
+pub mod fuzzy;
+pub mod graph;
+pub mod ioc;
+pub mod presets;
+pub mod risk;
+pub mod time;
 pub mod uid;
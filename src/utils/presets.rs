@@ -0,0 +1,170 @@
+//! Named, parameterized Cypher queries run by `query preset`
+//!
+//! Presets are read from a TOML file (`queries.toml` by default) and added to the
+//! built-in ones below; a user preset with the same name replaces the built-in one.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// File looked up in the working directory when no preset file is given
+pub const DEFAULT_PRESETS_FILE: &str = "queries.toml";
+
+const BUILTIN_PRESETS: &str = r#"
+[presets.injection-paths]
+description = "Functions reaching process injection APIs within three calls"
+columns = ["binary", "function", "address", "apis"]
+cypher = '''
+MATCH (b:Binary)-[:CONTAINS]->(f:Function)-[:CALLS*1..3]->(api:Function)
+WHERE ($binary = '' OR b.filename CONTAINS $binary OR b.hash = $binary)
+  AND coalesce(api.canonical_name, api.name) IN
+      ['VirtualAllocEx', 'WriteProcessMemory', 'CreateRemoteThread', 'CreateRemoteThreadEx',
+       'NtCreateThreadEx', 'RtlCreateUserThread', 'QueueUserAPC', 'SetThreadContext',
+       'NtUnmapViewOfSection', 'ZwUnmapViewOfSection']
+RETURN b.filename AS binary,
+       coalesce(f.analyst_name, f.demangled_name, f.name) AS function,
+       f.address AS address,
+       collect(DISTINCT coalesce(api.canonical_name, api.name)) AS apis
+ORDER BY size(apis) DESC, binary, function
+LIMIT $limit
+'''
+
+[presets.injection-paths.params]
+binary = ""
+limit = 50
+
+[presets.c2-strings]
+description = "Strings that look like URLs, IP:port pairs, user agents or gate scripts"
+columns = ["binary", "string"]
+cypher = '''
+MATCH (b:Binary)-[:CONTAINS_STRING]->(s:String)
+WHERE ($binary = '' OR b.filename CONTAINS $binary OR b.hash = $binary)
+  AND (s.value =~ '(?i).*\\b(https?|ftp)://.*'
+       OR s.value =~ '.*\\b\\d{1,3}(\\.\\d{1,3}){3}:\\d{2,5}\\b.*'
+       OR s.value =~ '(?i).*(user-agent:|mozilla/\\d|/gate\\.php|\\.onion\\b).*')
+RETURN DISTINCT b.filename AS binary, s.value AS string
+ORDER BY binary, string
+LIMIT $limit
+'''
+
+[presets.c2-strings.params]
+binary = ""
+limit = 100
+"#;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryPreset {
+    #[serde(default)]
+    pub description: String,
+    pub cypher: String,
+    /// Parameters referenced by the query with their default values
+    #[serde(default)]
+    pub params: BTreeMap<String, Value>,
+    /// Result columns in display order; all returned keys, sorted, when empty
+    #[serde(default)]
+    pub columns: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QueryPresets {
+    #[serde(default)]
+    pub presets: BTreeMap<String, QueryPreset>,
+}
+
+impl QueryPresets {
+    /// Built-in presets plus those of `path`, or of `queries.toml` if it exists
+    pub fn load(path: Option<&str>) -> Result<Self> {
+        let mut presets = Self::parse(BUILTIN_PRESETS)?;
+
+        let path = match path {
+            Some(path) => Some(Path::new(path)),
+            None => Some(Path::new(DEFAULT_PRESETS_FILE)).filter(|p| p.exists()),
+        };
+        if let Some(path) = path {
+            let content = std::fs::read_to_string(path).map_err(|e| {
+                anyhow::anyhow!("Failed to read query presets '{}': {}", path.display(), e)
+            })?;
+            let user = Self::parse(&content).map_err(|e| {
+                anyhow::anyhow!("Failed to parse query presets '{}': {}", path.display(), e)
+            })?;
+            presets.presets.extend(user.presets);
+        }
+
+        Ok(presets)
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        Ok(toml::from_str(content)?)
+    }
+
+    pub fn get(&self, name: &str) -> Result<&QueryPreset> {
+        self.presets.get(name).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Unknown query preset '{}', available: {}",
+                name,
+                self.presets.keys().cloned().collect::<Vec<_>>().join(", ")
+            )
+        })
+    }
+}
+
+impl QueryPreset {
+    /// Query parameters from the defaults overridden by `KEY=VALUE` arguments
+    ///
+    /// A value is parsed as the type of the parameter's default, so `limit=10` stays
+    /// an integer; parameters the preset does not declare are rejected.
+    pub fn resolve_params(&self, overrides: &[String]) -> Result<Map<String, Value>> {
+        let mut params: Map<String, Value> = self.params.clone().into_iter().collect();
+
+        for assignment in overrides {
+            let (key, raw) = assignment.split_once('=').ok_or_else(|| {
+                anyhow::anyhow!("Invalid --param '{}', expected KEY=VALUE", assignment)
+            })?;
+            let Some(default) = self.params.get(key) else {
+                return Err(anyhow::anyhow!(
+                    "Unknown parameter '{}', the preset takes: {}",
+                    key,
+                    self.params.keys().cloned().collect::<Vec<_>>().join(", ")
+                ));
+            };
+
+            let value = match default {
+                Value::Number(n) if n.is_i64() => raw.parse::<i64>().map(Value::from).ok(),
+                Value::Number(_) => raw.parse::<f64>().ok().map(Value::from),
+                Value::Bool(_) => raw.parse::<bool>().map(Value::from).ok(),
+                _ => Some(Value::from(raw)),
+            }
+            .ok_or_else(|| anyhow::anyhow!("Invalid value '{}' for parameter '{}'", raw, key))?;
+            params.insert(key.to_string(), value);
+        }
+
+        Ok(params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_presets_and_params() {
+        let presets = QueryPresets::parse(BUILTIN_PRESETS).unwrap();
+        assert!(presets.get("c2-strings").is_ok());
+        assert!(presets.get("missing").is_err());
+
+        let preset = presets.get("injection-paths").unwrap();
+        assert!(preset.cypher.contains("$limit"));
+
+        let params = preset
+            .resolve_params(&["limit=10".to_string(), "binary=a=b.exe".to_string()])
+            .unwrap();
+        assert_eq!(params["limit"], Value::from(10));
+        assert_eq!(params["binary"], Value::from("a=b.exe"));
+
+        assert!(preset.resolve_params(&["limit=ten".to_string()]).is_err());
+        assert!(preset.resolve_params(&["depth=3".to_string()]).is_err());
+        assert!(preset.resolve_params(&["limit".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,165 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::models::RiskContribution;
+use crate::utils::api_names::canonical_api_name;
+
+/// Built-in weights as (category, canonical API name, weight)
+const DEFAULT_WEIGHTS: &[(&str, &str, f64)] = &[
+    ("injection", "OpenProcess", 1.0),
+    ("injection", "VirtualAllocEx", 3.0),
+    ("injection", "VirtualProtectEx", 3.0),
+    ("injection", "WriteProcessMemory", 4.0),
+    ("injection", "CreateRemoteThread", 5.0),
+    ("injection", "CreateRemoteThreadEx", 5.0),
+    ("injection", "NtCreateThreadEx", 5.0),
+    ("injection", "RtlCreateUserThread", 4.0),
+    ("injection", "QueueUserAPC", 4.0),
+    ("injection", "SetThreadContext", 4.0),
+    ("injection", "NtUnmapViewOfSection", 4.0),
+    ("injection", "ZwUnmapViewOfSection", 4.0),
+    ("injection", "SetWindowsHookEx", 3.0),
+    ("anti-debug", "IsDebuggerPresent", 2.0),
+    ("anti-debug", "CheckRemoteDebuggerPresent", 3.0),
+    ("anti-debug", "NtQueryInformationProcess", 2.0),
+    ("anti-debug", "NtSetInformationThread", 3.0),
+    ("anti-debug", "OutputDebugString", 1.0),
+    ("crypto", "CryptAcquireContext", 1.0),
+    ("crypto", "CryptGenKey", 2.0),
+    ("crypto", "CryptImportKey", 2.0),
+    ("crypto", "CryptDeriveKey", 2.0),
+    ("crypto", "CryptEncrypt", 2.0),
+    ("crypto", "CryptDecrypt", 2.0),
+    ("crypto", "BCryptEncrypt", 2.0),
+    ("crypto", "BCryptDecrypt", 2.0),
+    ("network", "WSAStartup", 1.0),
+    ("network", "socket", 1.0),
+    ("network", "connect", 1.0),
+    ("network", "InternetOpen", 1.0),
+    ("network", "InternetOpenUrl", 2.0),
+    ("network", "InternetReadFile", 1.0),
+    ("network", "HttpSendRequest", 2.0),
+    ("network", "WinHttpOpen", 1.0),
+    ("network", "WinHttpSendRequest", 2.0),
+    ("network", "URLDownloadToFile", 3.0),
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskWeight {
+    /// API name; A/W variants and ordinals match through their canonical name
+    pub api: String,
+    pub category: String,
+    pub weight: f64,
+}
+
+/// Weighting table of suspicious APIs used by `analyze risk`
+///
+/// Loaded from a JSON file of the form `{"apis": [{"api": "WriteProcessMemory",
+/// "category": "injection", "weight": 4.0}]}`, which replaces the built-in table.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskProfile {
+    pub apis: Vec<RiskWeight>,
+}
+
+impl Default for RiskProfile {
+    fn default() -> Self {
+        Self {
+            apis: DEFAULT_WEIGHTS
+                .iter()
+                .map(|(category, api, weight)| RiskWeight {
+                    api: api.to_string(),
+                    category: category.to_string(),
+                    weight: *weight,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl RiskProfile {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            anyhow::anyhow!("Failed to read risk profile '{}': {}", path.display(), e)
+        })?;
+        serde_json::from_str(&content).map_err(|e| {
+            anyhow::anyhow!("Failed to parse risk profile '{}': {}", path.display(), e)
+        })
+    }
+
+    /// Weight entry for an imported API, compared by case-insensitive canonical name
+    pub fn weight(&self, api: &str) -> Option<&RiskWeight> {
+        let api = canonical_api_name("", api);
+        self.apis
+            .iter()
+            .find(|entry| canonical_api_name("", &entry.api).eq_ignore_ascii_case(&api))
+    }
+
+    /// Contributions of the weighted APIs among `uses` (API name, call sites)
+    ///
+    /// Each API counts once, however many variants or call sites it has. Contributions
+    /// are ordered by weight, highest first.
+    pub fn contributions(&self, uses: &[(String, Vec<String>)]) -> Vec<RiskContribution> {
+        let mut by_api: BTreeMap<String, RiskContribution> = BTreeMap::new();
+
+        for (api, call_sites) in uses {
+            let Some(entry) = self.weight(api) else {
+                continue;
+            };
+            let contribution =
+                by_api
+                    .entry(entry.api.clone())
+                    .or_insert_with(|| RiskContribution {
+                        category: entry.category.clone(),
+                        api: entry.api.clone(),
+                        weight: entry.weight,
+                        call_sites: Vec::new(),
+                    });
+            contribution.call_sites.extend(call_sites.iter().cloned());
+        }
+
+        let mut contributions: Vec<RiskContribution> = by_api.into_values().collect();
+        for contribution in &mut contributions {
+            contribution.call_sites.sort();
+            contribution.call_sites.dedup();
+        }
+        contributions.sort_by(|a, b| b.weight.total_cmp(&a.weight));
+        contributions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_risk_contributions() {
+        let profile = RiskProfile::default();
+        let uses = vec![
+            (
+                "WriteProcessMemory".to_string(),
+                vec!["inject @ 0x401020".to_string()],
+            ),
+            ("OutputDebugStringA".to_string(), vec![]),
+            (
+                "OutputDebugStringW".to_string(),
+                vec!["log @ 0x401100".to_string()],
+            ),
+            ("socket".to_string(), vec![]),
+            (
+                "ExitProcess".to_string(),
+                vec!["main @ 0x401000".to_string()],
+            ),
+        ];
+
+        let contributions = profile.contributions(&uses);
+        let apis: Vec<&str> = contributions.iter().map(|c| c.api.as_str()).collect();
+        assert_eq!(apis, ["WriteProcessMemory", "OutputDebugString", "socket"]);
+        assert_eq!(contributions[1].call_sites, ["log @ 0x401100"]);
+
+        let score: f64 = contributions.iter().map(|c| c.weight).sum();
+        assert_eq!(score, 6.0);
+    }
+}
@@ -0,0 +1,102 @@
+/// Whether `text` starts with a `YYYY-MM-DD` date
+pub fn is_iso_date(text: &str) -> bool {
+    let bytes = text.as_bytes();
+    bytes.len() >= 10
+        && bytes[..10].iter().enumerate().all(|(i, b)| {
+            if i == 4 || i == 7 {
+                *b == b'-'
+            } else {
+                b.is_ascii_digit()
+            }
+        })
+}
+
+/// Build time reported by an extractor as an ISO 8601 UTC datetime
+///
+/// Accepts Unix epoch seconds in decimal or `0x` hex (the PE `TimeDateStamp`) and
+/// `YYYY-MM-DD[ HH:MM:SS]` text. Zero and anything else yield `None`.
+pub fn compile_time(timestamp: &str) -> Option<String> {
+    let text = timestamp.trim();
+    let epoch = match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16).ok().map(u64::from),
+        None => text.parse::<u64>().ok(),
+    };
+    if let Some(epoch) = epoch {
+        return (epoch > 0).then(|| epoch_to_iso(epoch));
+    }
+
+    if !is_iso_date(text) {
+        return None;
+    }
+    let date = &text[..10];
+    let time = text
+        .get(11..19)
+        .filter(|time| {
+            let bytes = time.as_bytes();
+            bytes[2] == b':' && bytes[5] == b':'
+        })
+        .unwrap_or("00:00:00");
+    Some(format!("{}T{}Z", date, time))
+}
+
+/// Current time as an ISO 8601 UTC datetime
+pub fn now_iso() -> String {
+    let epoch = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    epoch_to_iso(epoch)
+}
+
+fn epoch_to_iso(epoch: u64) -> String {
+    let days = (epoch / 86_400) as i64;
+    let seconds = epoch % 86_400;
+
+    // Civil date from days since 1970-01-01 (proleptic Gregorian calendar)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compile_time() {
+        assert_eq!(
+            compile_time("1700000000").as_deref(),
+            Some("2023-11-14T22:13:20Z")
+        );
+        assert_eq!(
+            compile_time("0x5E0BE100").as_deref(),
+            Some("2020-01-01T00:00:00Z")
+        );
+        assert_eq!(
+            compile_time("2024-02-29 13:05:09").as_deref(),
+            Some("2024-02-29T13:05:09Z")
+        );
+        assert_eq!(
+            compile_time("2024-02-29").as_deref(),
+            Some("2024-02-29T00:00:00Z")
+        );
+        assert_eq!(compile_time("0"), None);
+        assert_eq!(compile_time("Thu Jan  1"), None);
+    }
+}
@@ -1,40 +1,294 @@
-use sha2::{Sha256, Digest};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::num::IntErrorKind;
+
+use crate::models::ObjectKind;
+
+/// Identifier stored in the `uid` property of a node, see [`parse_uid`]
+///
+/// Displaying a `Uid` gives its canonical string, so constructors and parsing agree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Uid {
+    /// `{binary_hash}:{address}`, an internal or exported function
+    Function { binary_hash: String, address: u64 },
+    /// `imp:{library}:{name}`, an imported API shared by every binary
+    Import { library: String, name: String },
+    /// `str:{sha256}` of the string content
+    String { sha256: String },
+    /// `res:{sha256}` of the resource content
+    Resource { sha256: String },
+    /// `data:{binary_hash}:{address}`, a referenced address holding no string
+    Data {
+        binary_hash: String,
+        address: String,
+    },
+    /// `{binary_hash}:{role}:{address}`, a call endpoint that matched no function
+    ///
+    /// Data and unresolved call addresses are kept as recorded (normalized when they
+    /// parse), since extractors may report symbolic targets.
+    UnresolvedCall {
+        binary_hash: String,
+        role: String,
+        address: String,
+    },
+    /// `{kind}:{name}`, a mutex, pipe, event or semaphore
+    NamedObject { kind: ObjectKind, name: String },
+    /// `vtable:{binary_hash}:{address}`, a C++ virtual function table
+    VTable { binary_hash: String, address: u64 },
+    /// `chunk:{binary_hash}:{address}`, a block of a function outside its main body,
+    /// shared by every function it is a chunk of
+    Chunk { binary_hash: String, address: u64 },
+}
+
+impl fmt::Display for Uid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Uid::Function {
+                binary_hash,
+                address,
+            } => write!(f, "{}:{}", binary_hash, format_address(*address)),
+            Uid::Import { library, name } => write!(f, "imp:{}:{}", library, name),
+            Uid::String { sha256 } => write!(f, "str:{}", sha256),
+            Uid::Resource { sha256 } => write!(f, "res:{}", sha256),
+            Uid::Data {
+                binary_hash,
+                address,
+            } => write!(f, "data:{}:{}", binary_hash, address),
+            Uid::UnresolvedCall {
+                binary_hash,
+                role,
+                address,
+            } => write!(f, "{}:{}:{}", binary_hash, role, address),
+            Uid::NamedObject { kind, name } => write!(f, "{}:{}", kind.as_str(), name),
+            Uid::VTable {
+                binary_hash,
+                address,
+            } => write!(f, "vtable:{}:{}", binary_hash, format_address(*address)),
+            Uid::Chunk {
+                binary_hash,
+                address,
+            } => write!(f, "chunk:{}:{}", binary_hash, format_address(*address)),
+        }
+    }
+}
+
+impl Uid {
+    /// Binary the node belongs to, for per-binary UIDs
+    pub fn binary_hash(&self) -> Option<&str> {
+        match self {
+            Uid::Function { binary_hash, .. }
+            | Uid::Data { binary_hash, .. }
+            | Uid::UnresolvedCall { binary_hash, .. }
+            | Uid::VTable { binary_hash, .. }
+            | Uid::Chunk { binary_hash, .. } => Some(binary_hash),
+            _ => None,
+        }
+    }
+}
+
+pub fn function_uid(binary_hash: &str, address: u64) -> String {
+    Uid::Function {
+        binary_hash: binary_hash.to_string(),
+        address,
+    }
+    .to_string()
+}
+
+/// UID of an imported API; the library name is lowercased so every binary shares it
+pub fn import_uid(library: &str, name: &str) -> String {
+    Uid::Import {
+        library: library.to_lowercase(),
+        name: name.to_string(),
+    }
+    .to_string()
+}
 
 pub fn generate_string_uid(value: &str) -> String {
     let hash = Sha256::digest(value.as_bytes());
-    format!("str:{:x}", hash)
+    Uid::String {
+        sha256: format!("{:x}", hash),
+    }
+    .to_string()
+}
+
+pub fn resource_uid(sha256: &str) -> String {
+    Uid::Resource {
+        sha256: sha256.to_string(),
+    }
+    .to_string()
+}
+
+pub fn data_uid(binary_hash: &str, address: &str) -> String {
+    Uid::Data {
+        binary_hash: binary_hash.to_string(),
+        address: address.to_string(),
+    }
+    .to_string()
+}
+
+pub fn unresolved_call_uid(binary_hash: &str, role: &str, address: &str) -> String {
+    Uid::UnresolvedCall {
+        binary_hash: binary_hash.to_string(),
+        role: role.to_string(),
+        address: address.to_string(),
+    }
+    .to_string()
+}
+
+pub fn named_object_uid(kind: ObjectKind, name: &str) -> String {
+    Uid::NamedObject {
+        kind,
+        name: name.to_string(),
+    }
+    .to_string()
+}
+
+pub fn vtable_uid(binary_hash: &str, address: u64) -> String {
+    Uid::VTable {
+        binary_hash: binary_hash.to_string(),
+        address,
+    }
+    .to_string()
+}
+
+pub fn chunk_uid(binary_hash: &str, address: u64) -> String {
+    Uid::Chunk {
+        binary_hash: binary_hash.to_string(),
+        address,
+    }
+    .to_string()
+}
+
+/// Parse any node UID; function addresses may be given in any form [`parse_address`]
+/// accepts
+pub fn parse_uid(uid: &str) -> Option<Uid> {
+    let (prefix, rest) = uid.trim().split_once(':')?;
+    if rest.is_empty() {
+        return None;
+    }
+    match prefix {
+        "imp" => {
+            let (library, name) = rest.split_once(':')?;
+            Some(Uid::Import {
+                library: library.to_string(),
+                name: name.to_string(),
+            })
+        }
+        "str" => Some(Uid::String {
+            sha256: rest.to_string(),
+        }),
+        "res" => Some(Uid::Resource {
+            sha256: rest.to_string(),
+        }),
+        "data" => {
+            let (binary_hash, address) = rest.split_once(':')?;
+            Some(Uid::Data {
+                binary_hash: binary_hash.to_string(),
+                address: address.to_string(),
+            })
+        }
+        "vtable" => {
+            let (binary_hash, address) = rest.split_once(':')?;
+            Some(Uid::VTable {
+                binary_hash: binary_hash.to_string(),
+                address: parse_address(address)?,
+            })
+        }
+        "chunk" => {
+            let (binary_hash, address) = rest.split_once(':')?;
+            Some(Uid::Chunk {
+                binary_hash: binary_hash.to_string(),
+                address: parse_address(address)?,
+            })
+        }
+        _ => {
+            if let Ok(kind) = <ObjectKind as clap::ValueEnum>::from_str(prefix, false) {
+                return Some(Uid::NamedObject {
+                    kind,
+                    name: rest.to_string(),
+                });
+            }
+            match rest.split_once(':') {
+                Some((role, address)) => Some(Uid::UnresolvedCall {
+                    binary_hash: prefix.to_string(),
+                    role: role.to_string(),
+                    address: address.to_string(),
+                }),
+                None => Some(Uid::Function {
+                    binary_hash: prefix.to_string(),
+                    address: parse_address(rest)?,
+                }),
+            }
+        }
+    }
+}
+
+/// Why an address could not be read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressError {
+    Empty,
+    Malformed,
+    /// The value or a `base+offset` sum does not fit in 64 bits
+    Overflow,
+}
+
+impl fmt::Display for AddressError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            AddressError::Empty => "empty",
+            AddressError::Malformed => "not a number",
+            AddressError::Overflow => "exceeds 64 bits",
+        })
+    }
 }
 
 pub fn parse_address(address_str: &str) -> Option<u64> {
+    try_parse_address(address_str).ok()
+}
+
+/// Address written in hexadecimal or decimal, or a `base+offset` sum of them such as
+/// `0x140000000+0x1a2b`
+pub fn try_parse_address(address_str: &str) -> Result<u64, AddressError> {
     let trimmed = address_str.trim();
 
     if trimmed.is_empty() {
-        return None;
+        return Err(AddressError::Empty);
     }
 
+    trimmed.split('+').try_fold(0u64, |sum, term| {
+        sum.checked_add(parse_address_term(term.trim())?)
+            .ok_or(AddressError::Overflow)
+    })
+}
+
+fn parse_address_term(term: &str) -> Result<u64, AddressError> {
+    let hex = |digits: &str| {
+        u64::from_str_radix(digits, 16).map_err(|e| match e.kind() {
+            IntErrorKind::PosOverflow => AddressError::Overflow,
+            _ => AddressError::Malformed,
+        })
+    };
+
     // Try parsing with 0x/0X prefix
-    if let Some(hex_str) = trimmed
-        .strip_prefix("0x")
-        .or_else(|| trimmed.strip_prefix("0X"))
-    {
-        return u64::from_str_radix(hex_str, 16).ok();
+    if let Some(hex_str) = term.strip_prefix("0x").or_else(|| term.strip_prefix("0X")) {
+        return hex(hex_str);
     }
 
     // Try parsing pure hexadecimal (a-f characters)
-    if trimmed
+    if term
         .chars()
         .any(|c| c.is_ascii_hexdigit() && !c.is_ascii_digit())
     {
-        return u64::from_str_radix(trimmed, 16).ok();
+        return hex(term);
     }
 
     // Try parsing decimal
-    if let Ok(decimal) = trimmed.parse::<u64>() {
-        return Some(decimal);
+    if let Ok(decimal) = term.parse::<u64>() {
+        return Ok(decimal);
     }
 
     // Finally try as hexadecimal
-    u64::from_str_radix(trimmed, 16).ok()
+    hex(term)
 }
 
 pub fn format_address(address: u64) -> String {
@@ -45,6 +299,11 @@ pub fn normalize_address(address_str: &str) -> Option<String> {
     parse_address(address_str).map(format_address)
 }
 
+/// `address` relative to `image_base`; addresses below the base are already relative
+pub fn relative_address(address: u64, image_base: u64) -> u64 {
+    address.checked_sub(image_base).unwrap_or(address)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -75,6 +334,23 @@ mod tests {
         assert_eq!(parse_address("xyz"), None);
     }
 
+    #[test]
+    fn test_parse_address_expressions() {
+        assert_eq!(parse_address("0x400000+0x10"), Some(0x400010));
+        assert_eq!(parse_address("0x400000 + 16"), Some(0x400010));
+        assert_eq!(parse_address("0xffffffffffffffff"), Some(u64::MAX));
+        assert_eq!(
+            try_parse_address("0x1ffffffffffffffff"),
+            Err(AddressError::Overflow)
+        );
+        assert_eq!(
+            try_parse_address("0xffffffffffffffff+1"),
+            Err(AddressError::Overflow)
+        );
+        assert_eq!(try_parse_address("+0x10"), Err(AddressError::Malformed));
+        assert_eq!(try_parse_address("0x"), Err(AddressError::Malformed));
+    }
+
     #[test]
     fn test_normalize_address() {
         assert_eq!(normalize_address("0x1000"), Some("0x1000".to_string()));
@@ -82,6 +358,58 @@ mod tests {
         assert_eq!(normalize_address("0X00001000"), Some("0x1000".to_string()));
     }
 
+    #[test]
+    fn test_parse_uid_round_trip() {
+        let uids = [
+            function_uid("abc123", 0x401000),
+            import_uid("KERNEL32.dll", "CreateFileA"),
+            generate_string_uid("Hello"),
+            resource_uid("9f86d0"),
+            data_uid("abc123", "0x403000"),
+            unresolved_call_uid("abc123", "target", "0x402f00"),
+            named_object_uid(ObjectKind::Pipe, "msagent_12"),
+            vtable_uid("abc123", 0x40a2c8),
+            chunk_uid("abc123", 0x404f10),
+        ];
+        for uid in &uids {
+            assert_eq!(
+                parse_uid(uid).map(|parsed| parsed.to_string()).as_ref(),
+                Some(uid)
+            );
+        }
+
+        assert_eq!(uids[0], "abc123:0x401000");
+        assert_eq!(uids[1], "imp:kernel32.dll:CreateFileA");
+        assert_eq!(
+            parse_uid("abc123:0X00401000"),
+            Some(Uid::Function {
+                binary_hash: "abc123".to_string(),
+                address: 0x401000
+            })
+        );
+        assert_eq!(
+            parse_uid("imp:msvcrt:std::terminate").and_then(|uid| match uid {
+                Uid::Import { name, .. } => Some(name),
+                _ => None,
+            }),
+            Some("std::terminate".to_string())
+        );
+        assert_eq!(
+            parse_uid(&uids[5]).as_ref().and_then(Uid::binary_hash),
+            Some("abc123")
+        );
+        assert_eq!(
+            parse_uid(&uids[7]).as_ref().and_then(Uid::binary_hash),
+            Some("abc123")
+        );
+        assert_eq!(
+            parse_uid(&uids[8]).as_ref().and_then(Uid::binary_hash),
+            Some("abc123")
+        );
+        assert_eq!(parse_uid("abc123"), None);
+        assert_eq!(parse_uid("abc123:xyz"), None);
+    }
+
     #[test]
     fn test_generate_string_uid() {
         let uid1 = generate_string_uid("Hello");